@@ -210,6 +210,9 @@ typedef struct AREQ {
     /** The pipeline for this request */
   Pipeline pipeline;
 
+  /** The admission ticket of the query, held until it's freed (cursors included) */
+  AdmissionTicket *admissionTicket;
+
   /** Flags controlling query output */
   QEFlags reqflags;
 
@@ -411,6 +414,10 @@ void Grouper_AddReducer(Grouper *g, Reducer *r, RLookupKey *dst);
 
 void AREQ_Execute(AREQ *req, RedisModuleCtx *outctx);
 int prepareExecutionPlan(AREQ *req, QueryError *status);
+/**
+ * Take the admission ticket of the query, listed by FT._QUERIES. Fails if the query isn't admitted.
+ */
+int AREQ_Admit(AREQ *req, QueryError *status);
 void sendChunk(AREQ *req, RedisModule_Reply *reply, size_t limit);
 void AREQ_Free(AREQ *req);
 
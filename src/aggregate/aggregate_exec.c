@@ -29,6 +29,7 @@
 #include "hybrid/hybrid_request.h"
 #include "module.h"
 #include "result_processor.h"
+#include "zstr_rs.h"
 
 typedef enum {
   EXEC_NO_FLAGS = 0x00,
@@ -64,6 +65,8 @@ static const RSValue *getReplyKey(const RLookupKey *kk, const SearchResult *r) {
 static void reeval_key(RedisModule_Reply *reply, const RSValue *key) {
   RedisModuleCtx *outctx = reply->ctx;
   RedisModuleString *rskey = NULL;
+  // String keys borrow the bytes of the value, and are only copied once, when prefixed
+  RawZStr zskey = {0};
   if (!key) {
     RedisModule_Reply_Null(reply);
   }
@@ -80,15 +83,20 @@ static void reeval_key(RedisModule_Reply *reply, const RSValue *key) {
         // tell it's a double and not just a numeric string value
         rskey = RedisModule_CreateStringPrintf(outctx, "#%.17g", RSValue_Number_Get(key));
         break;
-      case RSValueType_String:
+      case RSValueType_String: {
         // Serialize string - by prepending "$" to it
-        rskey = RedisModule_CreateStringPrintf(outctx, "$%s", RSValue_String_Get(key, NULL));
+        uint32_t len;
+        const char *str = RSValue_String_Get(key, &len);
+        zskey = ZStr_Prefix('$', ZStr_Borrow(str, len));
         break;
+      }
       case RSValueType_RedisString:
-      case RSValueType_OwnRstring:
-        rskey = RedisModule_CreateStringPrintf(outctx, "$%s",
-          RedisModule_StringPtrLen(RSValue_RedisString_Get(key), NULL));
+      case RSValueType_OwnRstring: {
+        size_t len;
+        const char *str = RedisModule_StringPtrLen(RSValue_RedisString_Get(key), &len);
+        zskey = ZStr_Prefix('$', ZStr_Borrow(str, len));
         break;
+      }
       case RSValueType_Null:
       case RSValueType_Undef:
       case RSValueType_Array:
@@ -101,6 +109,9 @@ static void reeval_key(RedisModule_Reply *reply, const RSValue *key) {
     if (rskey) {
       RedisModule_Reply_String(reply, rskey);
       RedisModule_FreeString(outctx, rskey);
+    } else if (zskey.ptr) {
+      RedisModule_Reply_StringBuffer(reply, (const char *)zskey.ptr, zskey.len);
+      ZStr_Release(zskey);
     } else {
       RedisModule_Reply_Null(reply);
     }
@@ -358,7 +369,55 @@ long calc_results_len(AREQ *req, size_t limit) {
   return 1 + MIN(limit, MIN(reqLimit, reqResults)) * resultFactor;
 }
 
-static void finishSendChunk(AREQ *req, SearchResult **results, SearchResult *r, bool cursor_done) {
+// Record the query in the slow log of its index, if it was slow enough.
+// Documents are loaded and serialized while the iterators run, so that time
+// is accounted as executing the query.
+static void recordSlowQuery(AREQ *req, rs_wall_clock_ns_t duration, size_t nreturned) {
+  RedisSearchCtx *sctx = AREQ_SearchCtx(req);
+  // Cursor reads would be recorded without the query parsing
+  if (!sctx || !sctx->spec || (AREQ_RequestFlags(req) & QEXEC_F_IS_CURSOR)) {
+    return;
+  }
+
+  dict *paramsDict = req->searchopts.params;
+  size_t nparams = paramsDict ? dictSize(paramsDict) : 0;
+  SlowLogParam *params = nparams ? rm_malloc(nparams * sizeof(*params)) : NULL;
+  if (nparams) {
+    size_t i = 0;
+    dictIterator *iter = dictGetIterator(paramsDict);
+    dictEntry *entry = NULL;
+    while ((entry = dictNext(iter))) {
+      const char *name = dictGetKey(entry);
+      params[i].name = name;
+      params[i].name_len = strlen(name);
+      params[i].value = RedisModule_StringPtrLen(dictGetVal(entry), &params[i].value_len);
+      i++;
+    }
+    dictReleaseIterator(iter);
+  }
+
+  rs_wall_clock_ns_t parse = MIN(req->profileParseTime, duration);
+  SlowLogTimings timings = {.parse_ns = parse, .execute_ns = duration - parse};
+  const char *query = req->query ? req->query : "";
+  SlowLog_Record(sctx->spec->slowlog, query, strlen(query), params, nparams, timings,
+                 AREQ_QueryProcessingCtx(req)->totalResults, nreturned, time(NULL));
+  rm_free(params);
+}
+
+// Account for the query in the metrics of its index. A cursor query is counted
+// once, with the chunk which exhausts it.
+static void countIndexQuery(AREQ *req, rs_wall_clock_ns_t duration, bool cursor_done) {
+  RedisSearchCtx *sctx = AREQ_SearchCtx(req);
+  if (!sctx || !sctx->spec || ((AREQ_RequestFlags(req) & QEXEC_F_IS_CURSOR) && !cursor_done)) {
+    return;
+  }
+  size_t nameLen;
+  const char *name = HiddenString_GetUnsafe(sctx->spec->specName, &nameLen);
+  Metrics_QueryExecuted(name, nameLen, duration);
+}
+
+static void finishSendChunk(AREQ *req, SearchResult **results, SearchResult *r, bool cursor_done,
+                            size_t nreturned) {
   if (results) {
     destroyResults(results);
   } else {
@@ -373,6 +432,8 @@ static void finishSendChunk(AREQ *req, SearchResult **results, SearchResult *r,
   if (QueryError_IsOk(qctx->err) || hasTimeoutError(qctx->err)) {
     rs_wall_clock_ns_t duration = rs_wall_clock_elapsed_ns(&req->initClock);
     TotalGlobalStats_CountQuery(AREQ_RequestFlags(req), duration);
+    recordSlowQuery(req, duration, nreturned);
+    countIndexQuery(req, duration, cursor_done);
   }
 
   // Reset the total results length:
@@ -391,6 +452,7 @@ static void sendChunk_Resp2(AREQ *req, RedisModule_Reply *reply, size_t limit,
     ResultProcessor *rp = qctx->endProc;
     SearchResult **results = NULL;
     long nelem = 0, resultsLen = REDISMODULE_POSTPONED_ARRAY_LEN;
+    size_t nreturned = 0;
     bool cursor_done = false;
 
     startPipeline(req, rp, &results, &r, &rc);
@@ -437,6 +499,7 @@ static void sendChunk_Resp2(AREQ *req, RedisModule_Reply *reply, size_t limit,
 
     // If the policy is `ON_TIMEOUT FAIL`, we already aggregated the results
     if (results != NULL) {
+      nreturned = array_len(results);
       nelem += populateReplyWithResults(reply, results, req, &cv);
       results = NULL;
       goto done_2;
@@ -444,6 +507,7 @@ static void sendChunk_Resp2(AREQ *req, RedisModule_Reply *reply, size_t limit,
 
     if (rp->parent->resultLimit && rc == RS_RESULT_OK) {
       nelem += serializeResult(req, reply, &r, &cv);
+      nreturned++;
       SearchResult_Clear(&r);
     } else {
       goto done_2;
@@ -451,6 +515,7 @@ static void sendChunk_Resp2(AREQ *req, RedisModule_Reply *reply, size_t limit,
 
     while (--rp->parent->resultLimit && (rc = rp->Next(rp, &r)) == RS_RESULT_OK) {
       nelem += serializeResult(req, reply, &r, &cv);
+      nreturned++;
       SearchResult_Clear(&r);
     }
 
@@ -493,7 +558,7 @@ done_2:
     }
 
 done_2_err:
-    finishSendChunk(req, results, &r, cursor_done);
+    finishSendChunk(req, results, &r, cursor_done, nreturned);
 
     if (resultsLen != REDISMODULE_POSTPONED_ARRAY_LEN && rc == RS_RESULT_OK && resultsLen != nelem) {
       RS_LOG_ASSERT_FMT(false, "Failed to predict the number of replied results. Prediction=%ld, actual_number=%ld.", resultsLen, nelem);
@@ -511,6 +576,7 @@ static void sendChunk_Resp3(AREQ *req, RedisModule_Reply *reply, size_t limit,
     RedisSearchCtx *sctx = AREQ_SearchCtx(req);
     ResultProcessor *rp = qctx->endProc;
     SearchResult **results = NULL;
+    size_t nreturned = 0;
     bool cursor_done = false;
 
     startPipeline(req, rp, &results, &r, &rc);
@@ -558,11 +624,12 @@ static void sendChunk_Resp3(AREQ *req, RedisModule_Reply *reply, size_t limit,
     }
 
     if (results != NULL) {
-      populateReplyWithResults(reply, results, req, &cv);
+      nreturned = populateReplyWithResults(reply, results, req, &cv);
       results = NULL;
     } else {
       if (rp->parent->resultLimit && rc == RS_RESULT_OK) {
         serializeResult(req, reply, &r, &cv);
+        nreturned++;
       }
 
       SearchResult_Clear(&r);
@@ -572,6 +639,7 @@ static void sendChunk_Resp3(AREQ *req, RedisModule_Reply *reply, size_t limit,
 
       while (--rp->parent->resultLimit && (rc = rp->Next(rp, &r)) == RS_RESULT_OK) {
         serializeResult(req, reply, &r, &cv);
+        nreturned++;
         // Serialize it as a search result
         SearchResult_Clear(&r);
       }
@@ -636,7 +704,7 @@ done_3:
     }
 
 done_3_err:
-    finishSendChunk(req, results, &r, cursor_done);
+    finishSendChunk(req, results, &r, cursor_done, nreturned);
 }
 
 /**
@@ -799,11 +867,10 @@ int prepareExecutionPlan(AREQ *req, QueryError *status) {
 
   rs_wall_clock parseClock;
   bool is_profile = IsProfile(req);
-  if (is_profile) {
-    rs_wall_clock_init(&parseClock);
-    // Calculate the time elapsed for profileParseTime by using the initialized parseClock
-    req->profileParseTime = rs_wall_clock_diff_ns(&req->initClock, &parseClock);
-  }
+  rs_wall_clock_init(&parseClock);
+  // Calculate the time elapsed for profileParseTime by using the initialized parseClock.
+  // Also reported to the slow log, so measured without profiling too
+  req->profileParseTime = rs_wall_clock_diff_ns(&req->initClock, &parseClock);
 
   rc = AREQ_BuildPipeline(req, status);
 
@@ -919,6 +986,20 @@ int prepareRequest(AREQ **r_ptr, RedisModuleCtx *ctx, RedisModuleString **argv,
   return REDISMODULE_OK;
 }
 
+int AREQ_Admit(AREQ *r, QueryError *status) {
+  const IndexSpec *sp = AREQ_SearchCtx(r)->spec;
+  size_t nameLen;
+  const char *name = HiddenString_GetUnsafe(sp->specName, &nameLen);
+  const char *query = r->query ? r->query : "";
+  r->admissionTicket = Admission_Acquire(name, nameLen, query, strlen(query));
+  if (!r->admissionTicket) {
+    QueryError_SetError(status, QUERY_ERROR_CODE_GENERIC, "Too many concurrent queries, try again later");
+    return REDISMODULE_ERR;
+  }
+  r->pipeline.qctx.ticket = r->admissionTicket;
+  return REDISMODULE_OK;
+}
+
 static int buildPipelineAndExecute(AREQ *r, RedisModuleCtx *ctx, QueryError *status) {
   RedisSearchCtx *sctx = AREQ_SearchCtx(r);
   if (RunInThread()) {
@@ -987,6 +1068,10 @@ static int execCommandCommon(RedisModuleCtx *ctx, RedisModuleString **argv, int
     goto error;
   }
 
+  if (AREQ_Admit(r, &status) != REDISMODULE_OK) {
+    goto error;
+  }
+
   if (buildPipelineAndExecute(r, ctx, &status) != REDISMODULE_OK) {
     goto error;
   }
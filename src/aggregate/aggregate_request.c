@@ -1352,6 +1352,10 @@ void AREQ_Free(AREQ *req) {
   if (req->optimizer) {
     QOptimizer_Free(req->optimizer);
   }
+  // Free the slot once the pipeline, which checks whether the query was killed, is gone
+  if (req->admissionTicket) {
+    AdmissionTicket_Free(req->admissionTicket);
+  }
 
   QAST_Destroy(&req->ast);
 
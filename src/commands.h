@@ -57,6 +57,9 @@
 #define RS_DICT_DUMP "FT.DICTDUMP"
 #define RS_SYNDUMP_CMD "FT.SYNDUMP"
 #define RS_INDEX_LIST_CMD "FT._LIST"
+#define RS_SLOWLOG_CMD "FT._SLOWLOG"
+#define RS_QUERIES_CMD "FT._QUERIES"
+#define RS_METRICS_CMD "FT._METRICS"
 #define RS_SYNADD_CMD "FT.SYNADD" // Deprecated, always returns an error
 
 // read commands
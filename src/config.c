@@ -46,6 +46,7 @@ configPair_t __configPairs[] = {
   {"_PRINT_PROFILE_CLOCK",            "search-_print-profile-clock"},
   {"_PRIORITIZE_INTERSECT_UNION_CHILDREN", "search-_prioritize-intersect-union-children"},
   {"_BG_INDEX_MEM_PCT_THR",           "search-_bg-index-mem-pct-thr"},
+  {"_BG_WORK_PAUSE_MEM_PCT",          "search-_bg-work-pause-mem-pct"},
   {"BG_INDEX_SLEEP_GAP",              "search-bg-index-sleep-gap"},
   {"CONN_PER_SHARD",                  "search-conn-per-shard"},
   {"CURSOR_MAX_IDLE",                 "search-cursor-max-idle"},
@@ -90,6 +91,7 @@ configPair_t __configPairs[] = {
   {"BM25STD_TANH_FACTOR",             "search-bm25std-tanh-factor"},
   {"_BG_INDEX_OOM_PAUSE_TIME",         "search-_bg-index-oom-pause-time"},
   {"INDEXER_YIELD_EVERY_OPS",         "search-indexer-yield-every-ops"},
+  {"_SLOWLOG_THRESHOLD_MICROS",       "search-_slowlog-threshold-micros"},
   {"ON_OOM",                          "search-on-oom"},
 };
 
@@ -501,6 +503,24 @@ CONFIG_GETTER(getIndexingMemoryLimit) {
   return sdscatprintf(ss, "%u", config->indexingMemoryLimit);
 }
 
+// _BG_WORK_PAUSE_MEM_PCT
+CONFIG_SETTER(setBgWorkPauseMemoryLimit) {
+  uint8_t newLimit;
+  int acrc = AC_GetU8(ac, &newLimit, AC_F_GE0);
+  CHECK_RETURN_PARSE_ERROR(acrc);
+  if (newLimit > 100) {
+    QueryError_SetWithoutUserDataFmt(status, QUERY_ERROR_CODE_LIMIT, "Memory limit for background work cannot be greater then 100%%");
+    return REDISMODULE_ERR;
+  }
+  config->bgWorkPauseMemoryLimit = newLimit;
+  return REDISMODULE_OK;
+}
+
+CONFIG_GETTER(getBgWorkPauseMemoryLimit) {
+  sds ss = sdsempty();
+  return sdscatprintf(ss, "%u", config->bgWorkPauseMemoryLimit);
+}
+
 // BM25STD_TANH_FACTOR
 CONFIG_SETTER(setBM25StdTanhFactor) {
   unsigned int newFactor;
@@ -1066,6 +1086,19 @@ CONFIG_GETTER(getIndexerYieldEveryOps) {
   return sdscatprintf(ss, "%u", config->indexerYieldEveryOpsWhileLoading);
 }
 
+// _SLOWLOG_THRESHOLD_MICROS
+CONFIG_SETTER(setSlowlogThresholdMicros) {
+  unsigned int thresholdMicros;
+  int acrc = AC_GetUnsigned(ac, &thresholdMicros, AC_F_GE0);
+  config->slowlogThresholdMicros = thresholdMicros;
+  RETURN_STATUS(acrc);
+}
+
+CONFIG_GETTER(getSlowlogThresholdMicros) {
+  sds ss = sdsempty();
+  return sdscatprintf(ss, "%u", config->slowlogThresholdMicros);
+}
+
 // ON_OOM
 CONFIG_SETTER(setOnOom) {
   size_t len;
@@ -1414,6 +1447,11 @@ RSConfigOptions RSGlobalConfigOptions = {
          .helpText = "Set the percentage of memory usage threshold (out of maxmemory) at which background indexing will stop. The default is 100 percent.",
          .setValue = setIndexingMemoryLimit,
          .getValue = getIndexingMemoryLimit},
+        {.name = "_BG_WORK_PAUSE_MEM_PCT",
+         .helpText = "Set the percentage of memory usage (out of maxmemory) at which the background scan of the indexes and "
+                     "the application of the fork GC results pause, until usage drops back below it. The default is 0 (disabled).",
+         .setValue = setBgWorkPauseMemoryLimit,
+         .getValue = getBgWorkPauseMemoryLimit},
         {.name = "BM25STD_TANH_FACTOR",
           .helpText = "Set the BM25STD.TANH stretch factor. This is an integer value that divides the argument"
                       " of the tanh function that is used to normalize the score computed by the BM25STD scorer."
@@ -1430,6 +1468,11 @@ RSConfigOptions RSGlobalConfigOptions = {
          .helpText = "The number of operations to perform before yielding to Redis during indexing while loading",
          .setValue = setIndexerYieldEveryOps,
          .getValue = getIndexerYieldEveryOps},
+        {.name = "_SLOWLOG_THRESHOLD_MICROS",
+         .helpText = "Queries taking at least this many microseconds are recorded in the slow log of their index, "
+                     "read with FT._SLOWLOG. Applies to the indexes created afterwards",
+         .setValue = setSlowlogThresholdMicros,
+         .getValue = getSlowlogThresholdMicros},
         {.name = "ON_OOM",
          .helpText = "Action to perform when search OOM is exceeded (choose RETURN, FAIL or IGNORE)",
          .setValue = setOnOom,
@@ -1916,6 +1959,16 @@ int RegisterModuleConfig(RedisModuleCtx *ctx) {
     )
   )
 
+  RM_TRY(
+    RedisModule_RegisterNumericConfig(
+      ctx, "search-_bg-work-pause-mem-pct",
+      DEFAULT_BG_WORK_PAUSE_MEMORY_LIMIT,
+      REDISMODULE_CONFIG_DEFAULT | REDISMODULE_CONFIG_UNPREFIXED, 0,
+      100, get_uint8_numeric_config, set_uint8_numeric_config, NULL,
+      (void *)&(RSGlobalConfig.bgWorkPauseMemoryLimit)
+    )
+  )
+
   RM_TRY(
     RedisModule_RegisterNumericConfig(
       ctx, "search-bm25std-tanh-factor",
@@ -1945,6 +1998,15 @@ int RegisterModuleConfig(RedisModuleCtx *ctx) {
     )
   )
 
+  RM_TRY(
+    RedisModule_RegisterNumericConfig(
+      ctx, "search-_slowlog-threshold-micros", DEFAULT_SLOWLOG_THRESHOLD_MICROS,
+      REDISMODULE_CONFIG_UNPREFIXED, 0,
+      UINT32_MAX, get_uint_numeric_config, set_uint_numeric_config, NULL,
+      (void *)&(RSGlobalConfig.slowlogThresholdMicros)
+    )
+  )
+
   // String parameters
   RM_TRY(
     RedisModule_RegisterStringConfig(
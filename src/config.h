@@ -181,10 +181,14 @@ typedef struct {
   bool prioritizeIntersectUnionChildren;
     // The number of indexing operations per field to perform before yielding to Redis during indexing while loading (so redis can be responsive)
   unsigned int indexerYieldEveryOpsWhileLoading;
+  // Queries taking at least this long (in microseconds) are recorded in the slow log of their index
+  unsigned int slowlogThresholdMicros;
   // Limit the number of cursors that can be created for a single index
   long long indexCursorLimit;
   // The maximum ratio between current memory and max memory for which background indexing is allowed
   uint8_t indexingMemoryLimit;
+  // The percentage of max memory above which the background scan and the fork GC apply step pause (0 disables)
+  uint8_t bgWorkPauseMemoryLimit;
   // Enable to execute unstable features
   bool enableUnstableFeatures;
   // Control user data obfuscation in logs
@@ -309,11 +313,14 @@ char *getRedisConfigValue(RedisModuleCtx *ctx, const char* confName);
 #define MIN_MIN_STEM_LENGTH 2 // Minimum value for minStemLength
 #define MIN_OPERATION_WORKERS 4
 #define DEFAULT_INDEXING_MEMORY_LIMIT 100
+#define DEFAULT_BG_WORK_PAUSE_MEMORY_LIMIT 0
 #define DEFAULT_BM25STD_TANH_FACTOR 4
 #define BM25STD_TANH_FACTOR_MAX 10000
 #define BM25STD_TANH_FACTOR_MIN 1
 #define DEFAULT_BG_OOM_PAUSE_TIME_BEFOR_RETRY 5
 #define DEFAULT_INDEXER_YIELD_EVERY_OPS 1000
+#define DEFAULT_SLOWLOG_THRESHOLD_MICROS 10000
+#define SLOWLOG_MAX_LEN 128
 #define DEFAULT_SHARD_WINDOW_RATIO 1.0
 #define MIN_SHARD_WINDOW_RATIO 0.0  // Exclusive minimum (must be > 0.0)
 #define MAX_SHARD_WINDOW_RATIO 1.0
@@ -363,9 +370,11 @@ char *getRedisConfigValue(RedisModuleCtx *ctx, const char* confName);
     .enableUnstableFeatures = DEFAULT_UNSTABLE_FEATURES_ENABLE,                \
     .hideUserDataFromLog = false,                                              \
     .indexingMemoryLimit = DEFAULT_INDEXING_MEMORY_LIMIT,                      \
+    .bgWorkPauseMemoryLimit = DEFAULT_BG_WORK_PAUSE_MEMORY_LIMIT,              \
     .requestConfigParams.BM25STD_TanhFactor = DEFAULT_BM25STD_TANH_FACTOR,     \
     .bgIndexingOomPauseTimeBeforeRetry = DEFAULT_BG_OOM_PAUSE_TIME_BEFOR_RETRY,    \
     .indexerYieldEveryOpsWhileLoading = DEFAULT_INDEXER_YIELD_EVERY_OPS,       \
+    .slowlogThresholdMicros = DEFAULT_SLOWLOG_THRESHOLD_MICROS,                \
     .requestConfigParams.oomPolicy = OomPolicy_Ignore,                         \
   }
 
@@ -47,6 +47,9 @@ static InfoFieldSpec toplevelSpecs_g[] = {
     {.name = "offset_bits_per_record_avg", .type = InfoField_DoubleAverage},
     {.name = "indexing", .type = InfoField_WholeSum},
     {.name = "percent_indexed", .type = InfoField_DoubleAverage},
+    {.name = "bg_work_paused_on_memory", .type = InfoField_WholeSum},
+    {.name = "bg_work_memory_pauses", .type = InfoField_WholeSum},
+    {.name = "bg_work_memory_paused_ms", .type = InfoField_WholeSum},
     {.name = "hash_indexing_failures", .type = InfoField_WholeSum},
     {.name = "number_of_uses", .type = InfoField_Max},
     {.name = "cleaning", .type = InfoField_WholeSum}};
@@ -30,6 +30,10 @@
 #include "obfuscation/obfuscation_api.h"
 #include "info/info_command.h"
 #include "iterators/inverted_index_iterator.h"
+#include "gc_stats_rs.h"
+#include "mem_usage_rs.h"
+#include "scratch_rs.h"
+#include "info/field_spec_info.h"
 
 DebugCTX globalDebugCtx = {0};
 
@@ -757,11 +761,39 @@ DEBUG_COMMAND(DumpPhoneticHash) {
   return REDISMODULE_OK;
 }
 
+// Parse the arguments of GC_FORCEINVOKE following the index name.
+static bool GCForceInvokeParseArgs(RedisModuleString **argv, int argc, uint64_t *timeout, bool *dryRun) {
+  const char *args[2];
+  for (int i = 3; i < argc; i++) {
+    args[i - 3] = RedisModule_StringPtrLen(argv[i], NULL);
+  }
+  return GcForceInvoke_Parse(args, argc - 3, timeout, dryRun);
+}
+
 static int GCForceInvokeReply(RedisModuleCtx *ctx, RedisModuleString **argv, int argc) {
   if (!debugCommandsEnabled(ctx)) {
     return RedisModule_ReplyWithError(ctx, NODEBUG_ERR);
   }
-  return RedisModule_ReplyWithSimpleString(ctx, "DONE");
+  uint64_t timeout;
+  bool dryRun = false;
+  GCForceInvokeParseArgs(argv, argc, &timeout, &dryRun);
+  if (!dryRun) {
+    return RedisModule_ReplyWithSimpleString(ctx, "DONE");
+  }
+
+  // Reply what the run would have collected
+  StrongRef ref = IndexSpec_LoadUnsafe(RedisModule_StringPtrLen(argv[2], NULL));
+  IndexSpec *sp = StrongRef_Get(ref);
+  if (!sp) {
+    return RedisModule_ReplyWithError(ctx, "Unknown index name");
+  }
+  RedisModule_Reply _reply = RedisModule_NewReply(ctx), *reply = &_reply;
+  if (!GCContext_RenderDebugStats(sp->gc, reply, true)) {
+    // The run didn't happen, e.g. the fork failed
+    RedisModule_Reply_Error(reply, "No dry run recorded");
+  }
+  RedisModule_EndReply(reply);
+  return REDISMODULE_OK;
 }
 
 static int GCForceInvokeReplyTimeout(RedisModuleCtx *ctx, RedisModuleString **argv, int argc) {
@@ -771,18 +803,18 @@ static int GCForceInvokeReplyTimeout(RedisModuleCtx *ctx, RedisModuleString **ar
   return RedisModule_ReplyWithError(ctx, "INVOCATION FAILED");
 }
 
-// FT.DEBUG GC_FORCEINVOKE [TIMEOUT]
+// FT.DEBUG GC_FORCEINVOKE <index> [TIMEOUT] [DRYRUN]
 DEBUG_COMMAND(GCForceInvoke) {
   if (!debugCommandsEnabled(ctx)) {
     return RedisModule_ReplyWithError(ctx, NODEBUG_ERR);
   }
-  if (argc < 3 || argc > 4) {
+  if (argc < 3 || argc > 5) {
     return RedisModule_WrongArity(ctx);
   }
-  long long timeout = 30000;
-
-  if (argc == 4) {
-    RedisModule_StringToLongLong(argv[3], &timeout);
+  uint64_t timeout;
+  bool dryRun;
+  if (!GCForceInvokeParseArgs(argv, argc, &timeout, &dryRun)) {
+    return RedisModule_ReplyWithError(ctx, "Invalid arguments, expected [TIMEOUT] [DRYRUN]");
   }
   StrongRef ref = IndexSpec_LoadUnsafe(RedisModule_StringPtrLen(argv[2], NULL));
   IndexSpec *sp = StrongRef_Get(ref);
@@ -792,7 +824,256 @@ DEBUG_COMMAND(GCForceInvoke) {
 
   RedisModuleBlockedClient *bc = RedisModule_BlockClient(
       ctx, GCForceInvokeReply, GCForceInvokeReplyTimeout, NULL, timeout);
-  GCContext_ForceInvoke(sp->gc, bc);
+  GCContext_ForceInvoke(sp->gc, bc, dryRun);
+  return REDISMODULE_OK;
+}
+
+// FT.DEBUG GC_STATS <index>
+DEBUG_COMMAND(GCStats) {
+  if (!debugCommandsEnabled(ctx)) {
+    return RedisModule_ReplyWithError(ctx, NODEBUG_ERR);
+  }
+  if (argc != 3) {
+    return RedisModule_WrongArity(ctx);
+  }
+  StrongRef ref = IndexSpec_LoadUnsafe(RedisModule_StringPtrLen(argv[2], NULL));
+  IndexSpec *sp = StrongRef_Get(ref);
+  if (!sp) {
+    return RedisModule_ReplyWithError(ctx, "Unknown index name");
+  }
+  RedisModule_Reply _reply = RedisModule_NewReply(ctx), *reply = &_reply;
+  GCContext_RenderDebugStats(sp->gc, reply, false);
+  RedisModule_EndReply(reply);
+  return REDISMODULE_OK;
+}
+
+static void replyMemUsageMap(void *reply, const char *key) {
+  RedisModule_ReplyKV_Map(reply, key);
+}
+
+static void replyMemUsageMapEnd(void *reply) {
+  RedisModule_Reply_MapEnd(reply);
+}
+
+static void replyMemUsageLongLong(void *reply, const char *key, int64_t value) {
+  RedisModule_ReplyKV_LongLong(reply, key, value);
+}
+
+// The name of the encoding of the posting lists with the flags `flags`
+static const char *postingsEncoding(IndexFlags flags) {
+  const bool wide = flags & Index_WideSchema;
+  switch (flags & (Index_StoreFreqs | Index_StoreFieldFlags | Index_StoreTermOffsets)) {
+    case Index_StoreFreqs | Index_StoreFieldFlags | Index_StoreTermOffsets:
+      return wide ? "full_wide" : "full";
+    case Index_StoreFreqs | Index_StoreFieldFlags:
+      return wide ? "freqs_fields_wide" : "freqs_fields";
+    case Index_StoreFieldFlags | Index_StoreTermOffsets:
+      return wide ? "fields_offsets_wide" : "fields_offsets";
+    case Index_StoreFieldFlags:
+      return wide ? "fields_only_wide" : "fields_only";
+    case Index_StoreFreqs | Index_StoreTermOffsets:
+      return "freqs_offsets";
+    case Index_StoreFreqs:
+      return "freqs_only";
+    case Index_StoreTermOffsets:
+      return "offsets_only";
+    default:
+      return "doc_ids_only";
+  }
+}
+
+static void recordFieldMemUsage(MemUsage *usage, MemStructure structure, const FieldSpec *fs,
+                                size_t bytes) {
+  size_t len;
+  const char *name = HiddenString_GetUnsafe(fs->fieldName, &len);
+  MemUsage_Record(usage, structure, name, len, bytes);
+}
+
+// FT.DEBUG MEMUSAGE <index>
+// The bytes used by each structure of the index, broken down by field or by encoding
+DEBUG_COMMAND(MemUsageCommand) {
+  if (!debugCommandsEnabled(ctx)) {
+    return RedisModule_ReplyWithError(ctx, NODEBUG_ERR);
+  }
+  if (argc != 3) {
+    return RedisModule_WrongArity(ctx);
+  }
+  GET_SEARCH_CTX(argv[2])
+  IndexSpec *sp = sctx->spec;
+  MemUsage *usage = MemUsage_New();
+
+  MemUsage_Record(usage, MemStructure_TermTrie, NULL, 0, TrieType_MemUsage(sp->terms));
+
+  // The posting lists of the terms, by the encoding of each of them
+  rune *rstr = NULL;
+  t_len slen = 0;
+  float score = 0;
+  int dist = 0;
+  size_t termLen;
+  TrieIterator *it = Trie_Iterate(sp->terms, "", 0, 0, 1);
+  while (TrieIterator_Next(it, &rstr, &slen, NULL, &score, &dist)) {
+    char *term = runesToStr(rstr, slen, &termLen);
+    InvertedIndex *idx = Redis_OpenInvertedIndex(sctx, term, termLen, 0, NULL);
+    if (idx) {
+      const char *encoding = postingsEncoding(InvertedIndex_Flags(idx));
+      MemUsage_Record(usage, MemStructure_Postings, encoding, strlen(encoding),
+                      InvertedIndex_MemUsage(idx));
+    }
+    rm_free(term);
+  }
+  TrieIterator_Free(it);
+
+  MemUsage_Record(usage, MemStructure_Offsets, NULL, 0, sp->stats.offsetVecsSize);
+  MemUsage_Record(usage, MemStructure_DocTable, NULL, 0,
+                  sp->docs.memsize + TrieMap_MemUsage(sp->docs.dim.tm));
+  MemUsage_Record(usage, MemStructure_Sortables, NULL, 0, sp->docs.sortablesSize);
+  if (sp->suffix) {
+    // The TEXT fields share a suffix trie
+    MemUsage_Record(usage, MemStructure_SuffixTrie, "text", strlen("text"),
+                    TrieType_MemUsage(sp->suffix));
+  }
+
+  for (size_t i = 0; i < sp->numFields; i++) {
+    const FieldSpec *fs = sp->fields + i;
+    if (FIELD_IS(fs, INDEXFLD_T_NUMERIC) || FIELD_IS(fs, INDEXFLD_T_GEO)) {
+      RedisModuleString *keyName = IndexSpec_GetFormattedKey(sp, fs, fs->types);
+      NumericRangeTree *rt = openNumericKeysDict(sp, keyName, DONT_CREATE_INDEX);
+      if (rt) {
+        recordFieldMemUsage(usage, MemStructure_NumericTree, fs, NumericIndexType_MemUsage(rt));
+      }
+    }
+    if (FIELD_IS(fs, INDEXFLD_T_TAG)) {
+      RedisModuleString *keyName = TagIndex_FormatName(sp, fs->fieldName);
+      const TagIndex *idx = TagIndex_Open(sp, keyName, DONT_CREATE_INDEX);
+      RedisModule_FreeString(RSDummyContext, keyName);
+      if (idx) {
+        recordFieldMemUsage(usage, MemStructure_TagTrie, fs, TrieMap_MemUsage(idx->values));
+        if (idx->suffix) {
+          recordFieldMemUsage(usage, MemStructure_SuffixTrie, fs, TrieMap_MemUsage(idx->suffix));
+        }
+      }
+    }
+    if (FIELD_IS(fs, INDEXFLD_T_VECTOR)) {
+      recordFieldMemUsage(usage, MemStructure_VectorGraph, fs,
+                          IndexSpec_GetVectorIndexStats(sp, fs).memory);
+    }
+  }
+
+  RedisModule_Reply _reply = RedisModule_NewReply(ctx), *reply = &_reply;
+  RedisModule_Reply_Map(reply);
+  MemUsage_Reply(usage, reply, replyMemUsageMap, replyMemUsageMapEnd, replyMemUsageLongLong);
+  RedisModule_Reply_MapEnd(reply);
+  RedisModule_EndReply(reply);
+
+  MemUsage_Free(usage);
+  SearchCtx_Free(sctx);
+  return REDISMODULE_OK;
+}
+
+static void replyScratchThread(void *reply, const char *name, uintptr_t name_len, uintptr_t bytes) {
+  RedisModule_Reply_Map(reply);
+  RedisModule_ReplyKV_StringBuffer(reply, "thread", name, name_len);
+  RedisModule_ReplyKV_LongLong(reply, "bytes", bytes);
+  RedisModule_Reply_MapEnd(reply);
+}
+
+// FT.DEBUG SCRATCH_MEMORY
+// The memory held by the scratch buffers of each thread which used them
+DEBUG_COMMAND(ScratchMemory) {
+  if (!debugCommandsEnabled(ctx)) {
+    return RedisModule_ReplyWithError(ctx, NODEBUG_ERR);
+  }
+  if (argc != 2) {
+    return RedisModule_WrongArity(ctx);
+  }
+  RedisModule_Reply _reply = RedisModule_NewReply(ctx), *reply = &_reply;
+  RedisModule_Reply_Array(reply);
+  Scratch_ReplyThreads(reply, replyScratchThread);
+  RedisModule_Reply_ArrayEnd(reply);
+  RedisModule_EndReply(reply);
+  return REDISMODULE_OK;
+}
+
+static void replyWaitHistogram(RedisModule_Reply *reply, const char *key, const uint64_t *buckets) {
+  RedisModule_ReplyKV_Array(reply, key);
+  for (size_t i = 0; i < INDEX_LOCK_WAIT_BUCKETS; ++i) {
+    RedisModule_Reply_LongLong(reply, buckets[i]);
+  }
+  RedisModule_Reply_ArrayEnd(reply);
+}
+
+// FT.DEBUG LOCKSTATS <index> [RESET]
+// The contention statistics of the index lock. Bucket `i` of the wait histograms counts the waits
+// shorter than 2^i microseconds. RESET replies them, then resets them.
+DEBUG_COMMAND(LockStats) {
+  if (!debugCommandsEnabled(ctx)) {
+    return RedisModule_ReplyWithError(ctx, NODEBUG_ERR);
+  }
+  if (argc != 3 && argc != 4) {
+    return RedisModule_WrongArity(ctx);
+  }
+  bool reset = false;
+  if (argc == 4) {
+    if (strcasecmp(RedisModule_StringPtrLen(argv[3], NULL), "RESET")) {
+      return RedisModule_ReplyWithError(ctx, "Invalid argument, expected RESET");
+    }
+    reset = true;
+  }
+  StrongRef ref = IndexSpec_LoadUnsafe(RedisModule_StringPtrLen(argv[2], NULL));
+  IndexSpec *sp = StrongRef_Get(ref);
+  if (!sp) {
+    return RedisModule_ReplyWithError(ctx, "Unknown index name");
+  }
+  IndexLockStats stats = IndexLock_Stats(sp->rwlock);
+  if (reset) {
+    IndexLock_ResetStats(sp->rwlock);
+  }
+  RedisModule_Reply _reply = RedisModule_NewReply(ctx), *reply = &_reply;
+  RedisModule_Reply_Map(reply);
+    RedisModule_ReplyKV_LongLong(reply, "read_acquisitions", stats.read_acquisitions);
+    RedisModule_ReplyKV_LongLong(reply, "write_acquisitions", stats.write_acquisitions);
+    RedisModule_ReplyKV_LongLong(reply, "read_contended", stats.read_contended);
+    RedisModule_ReplyKV_LongLong(reply, "write_contended", stats.write_contended);
+    RedisModule_ReplyKV_LongLong(reply, "max_read_wait_us", stats.max_read_wait_us);
+    RedisModule_ReplyKV_LongLong(reply, "max_write_wait_us", stats.max_write_wait_us);
+    RedisModule_ReplyKV_LongLong(reply, "read_wait_p99_us", stats.read_wait_p99_us);
+    RedisModule_ReplyKV_LongLong(reply, "write_wait_p99_us", stats.write_wait_p99_us);
+    replyWaitHistogram(reply, "read_waits", stats.read_waits);
+    replyWaitHistogram(reply, "write_waits", stats.write_waits);
+  RedisModule_Reply_MapEnd(reply);
+  RedisModule_EndReply(reply);
+  return REDISMODULE_OK;
+}
+
+static void replyLiveRef(void *ctx, uint64_t id, const char *file, size_t file_len, uint32_t line) {
+  RedisModule_Reply *reply = ctx;
+  RedisModule_Reply_Stringf(reply, "%.*s:%u", (int)file_len, file, line);
+}
+
+// FT.DEBUG SPECREFS <index>
+// The references to the spec, and where its live strong references were created (debug builds only)
+DEBUG_COMMAND(SpecRefs) {
+  if (!debugCommandsEnabled(ctx)) {
+    return RedisModule_ReplyWithError(ctx, NODEBUG_ERR);
+  }
+  if (argc != 3) {
+    return RedisModule_WrongArity(ctx);
+  }
+  StrongRef ref = IndexSpec_LoadUnsafe(RedisModule_StringPtrLen(argv[2], NULL));
+  if (!StrongRef_Get(ref)) {
+    return RedisModule_ReplyWithError(ctx, "Unknown index name");
+  }
+  RefCounts counts = StrongRef_Counts(ref);
+  RedisModule_Reply _reply = RedisModule_NewReply(ctx), *reply = &_reply;
+  RedisModule_Reply_Map(reply);
+    RedisModule_ReplyKV_LongLong(reply, "strong", counts.strong);
+    RedisModule_ReplyKV_LongLong(reply, "weak", counts.weak);
+    RedisModule_ReplyKV_LongLong(reply, "invalid", counts.invalid);
+    RedisModule_ReplyKV_Array(reply, "live");
+      StrongRef_ForEachLive(ref, reply, replyLiveRef);
+    RedisModule_Reply_ArrayEnd(reply);
+  RedisModule_Reply_MapEnd(reply);
+  RedisModule_EndReply(reply);
   return REDISMODULE_OK;
 }
 
@@ -1317,6 +1598,41 @@ static void VecSim_Reply_Info_Iterator(RedisModuleCtx *ctx, VecSimDebugInfoItera
   }
 }
 
+// Repair the tiered vector indexes of the spec `arg` points a weak reference to
+static void vecsimRepairJob(void *arg) {
+  WeakRef *spRef = arg;
+  VecSim_CallTieredIndexesGC(*spRef);
+  WeakRef_Release(*spRef);
+  rm_free(spRef);
+}
+
+// FT.DEBUG VECSIM_REPAIR <index>
+// Repair the tombstones of the vector indexes, regardless of their ratio. The repair runs on the
+// workers thread pool if there is one, and replies the number of marked deleted vectors it repairs.
+DEBUG_COMMAND(VecsimRepair) {
+  if (!debugCommandsEnabled(ctx)) {
+    return RedisModule_ReplyWithError(ctx, NODEBUG_ERR);
+  }
+  if (argc != 3) {
+    return RedisModule_WrongArity(ctx);
+  }
+  StrongRef ref = IndexSpec_LoadUnsafe(RedisModule_StringPtrLen(argv[2], NULL));
+  IndexSpec *sp = StrongRef_Get(ref);
+  if (!sp) {
+    return RedisModule_ReplyWithError(ctx, "Unknown index name");
+  }
+  size_t markedDeleted = IndexSpec_GetVectorIndexesStats(sp).marked_deleted;
+
+  WeakRef *spRef = rm_malloc(sizeof(*spRef));
+  *spRef = StrongRef_Demote(ref);
+  if (RSGlobalConfig.numWorkerThreads) {
+    workersThreadPool_AddWork(vecsimRepairJob, spRef);
+  } else {
+    vecsimRepairJob(spRef);
+  }
+  return RedisModule_ReplyWithLongLong(ctx, markedDeleted);
+}
+
 /**
  * FT.DEBUG VECSIM_INFO <index> <field>
  */
@@ -2070,8 +2386,13 @@ DebugCommandType commands[] = {{"DUMP_INVIDX", DumpInvertedIndex}, // Print all
                                {"INVIDX_SUMMARY", InvertedIndexSummary}, // Print info about an inverted index and each of its blocks.
                                {"NUMIDX_SUMMARY", NumericIndexSummary}, // Quick summary of the numeric index
                                {"SPEC_INVIDXES_INFO", SpecInvertedIndexesInfo}, // Print general information about the inverted indexes in the spec
+                               {"SPECREFS", SpecRefs}, // Print the references to the spec, and where the live strong ones were created
+                               {"LOCKSTATS", LockStats}, // Print the contention statistics of the index lock
+                               {"MEMUSAGE", MemUsageCommand}, // Print the bytes used by each structure of the index
+                               {"SCRATCH_MEMORY", ScratchMemory}, // Print the memory held by the scratch buffers of each thread
                                {"GC_FORCEINVOKE", GCForceInvoke},
                                {"GC_FORCEBGINVOKE", GCForceBGInvoke},
+                               {"GC_STATS", GCStats}, // The statistics of the GC, including its last dry run
                                {"GC_CLEAN_NUMERIC", GCCleanNumeric},
                                {"GC_STOP_SCHEDULE", GCStopFutureRuns},
                                {"GC_CONTINUE_SCHEDULE", GCContinueFutureRuns},
@@ -2081,6 +2402,7 @@ DebugCommandType commands[] = {{"DUMP_INVIDX", DumpInvertedIndex}, // Print all
                                {"TTL_PAUSE", ttlPause},
                                {"TTL_EXPIRE", ttlExpire},
                                {"VECSIM_INFO", VecsimInfo},
+                               {"VECSIM_REPAIR", VecsimRepair}, // Repair the tombstones of the vector indexes
                                {"DELETE_LOCAL_CURSORS", DeleteCursors},
                                {"DUMP_HNSW", dumpHNSWData},
                                {"SET_MONITOR_EXPIRATION", setMonitorExpiration},
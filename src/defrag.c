@@ -0,0 +1,142 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+#include "defrag.h"
+#include "spec.h"
+#include "tag_index.h"
+#include "inverted_index.h"
+#include "triemap.h"
+
+extern dict *specDict_g;
+
+/* Where the defragmentation pass stopped. Specs and keys are tracked by their
+ * position in the dictionaries, since they may change between two cycles: an
+ * entry may then be visited twice or skipped until the next pass, which is
+ * harmless. */
+static struct {
+  size_t spec;      // position of the spec in specDict_g
+  size_t key;       // position of the entry in keysDict, its size stands for the doc table
+  size_t tagValue;  // position of the inverted index in the values of a tag index
+  uintptr_t cursor; // cursor inside the structure being relocated
+} defragState = {0};
+
+static void *defragAlloc(void *ctx, void *ptr) {
+  return RedisModule_DefragAlloc(ctx, ptr);
+}
+
+static int defragShouldStop(void *ctx) {
+  return RedisModule_DefragShouldStop(ctx);
+}
+
+static void *dictNth(dict *d, size_t n) {
+  dictIterator *iter = dictGetIterator(d);
+  dictEntry *entry = NULL;
+  while ((entry = dictNext(iter)) && n--) {
+  }
+  dictReleaseIterator(iter);
+  return entry ? dictGetVal(entry) : NULL;
+}
+
+static bool defragInvertedIndex(RedisModuleDefragCtx *ctx, InvertedIndex *ii) {
+  return InvertedIndex_Defrag(ii, &defragState.cursor, ctx, defragAlloc, defragShouldStop);
+}
+
+static bool defragTrieMap(RedisModuleDefragCtx *ctx, TrieMap *tm) {
+  return TrieMap_Defrag(tm, &defragState.cursor, ctx, defragAlloc, defragShouldStop);
+}
+
+/* The inverted indexes of the tag values first, then the values trie itself,
+ * which must not be relocated while it's being iterated. */
+static bool defragTagIndex(RedisModuleDefragCtx *ctx, TagIndex *idx) {
+  TrieMapIterator *it = TrieMap_Iterate(idx->values);
+  char *str;
+  tm_len_t slen;
+  void *ptr;
+  size_t n = 0;
+  bool done = true;
+  while (done && TrieMapIterator_Next(it, &str, &slen, &ptr)) {
+    if (n++ < defragState.tagValue) {
+      continue;
+    }
+    done = defragInvertedIndex(ctx, ptr);
+    if (done) {
+      defragState.tagValue++;
+    }
+  }
+  TrieMapIterator_Free(it);
+  if (!done) {
+    return false;
+  }
+  return defragTrieMap(ctx, idx->values);
+}
+
+static bool defragKey(RedisModuleDefragCtx *ctx, KeysDictValue *kdv) {
+  if (kdv->dtor == (void (*)(void *))InvertedIndex_Free) {
+    return defragInvertedIndex(ctx, kdv->p);
+  }
+  if (kdv->dtor == TagIndex_Free) {
+    return defragTagIndex(ctx, kdv->p);
+  }
+  // numeric trees and vector indexes manage their own memory
+  return true;
+}
+
+/* Resume the pass on `sp`. Returns false if it was interrupted. */
+static bool defragSpec(RedisModuleDefragCtx *ctx, IndexSpec *sp) {
+  if (sp->keysDict) {
+    for (size_t size = dictSize(sp->keysDict); defragState.key < size; defragState.key++) {
+      KeysDictValue *kdv = dictNth(sp->keysDict, defragState.key);
+      if (kdv && !defragKey(ctx, kdv)) {
+        return false;
+      }
+      defragState.tagValue = 0;
+    }
+  }
+  return defragTrieMap(ctx, sp->docs.dim.tm);
+}
+
+/* Returns 1 while there is work left for the next cycle, 0 once every index
+ * has been processed. */
+static int Defrag_Cycle(RedisModuleDefragCtx *ctx) {
+  for (size_t size = dictSize(specDict_g); defragState.spec < size; defragState.spec++) {
+    StrongRef spec_ref = {dictNth(specDict_g, defragState.spec)};
+    IndexSpec *sp = StrongRef_Get(spec_ref);
+    if (sp) {
+      // the background threads may be using the spec, come back to it later
+      if (!IndexLock_TryWriteLock(sp->rwlock)) {
+        return 1;
+      }
+      bool done = defragSpec(ctx, sp);
+      IndexLock_Unlock(sp->rwlock);
+      if (!done) {
+        return 1;
+      }
+    }
+    defragState.key = 0;
+    defragState.tagValue = 0;
+    defragState.cursor = 0;
+    if (RedisModule_DefragShouldStop(ctx)) {
+      defragState.spec++;
+      return defragState.spec < size;
+    }
+  }
+  defragState.spec = 0;
+  return 0;
+}
+
+static void Defrag_CycleLegacy(RedisModuleDefragCtx *ctx) {
+  Defrag_Cycle(ctx);
+}
+
+void Defrag_Register(RedisModuleCtx *ctx) {
+  if (RedisModule_RegisterDefragFunc2) {
+    RedisModule_RegisterDefragFunc2(ctx, Defrag_Cycle);
+  } else if (RedisModule_RegisterDefragFunc) {
+    RedisModule_RegisterDefragFunc(ctx, Defrag_CycleLegacy);
+  }
+}
@@ -0,0 +1,26 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+#ifndef RS_DEFRAG_H_
+#define RS_DEFRAG_H_
+
+#include "redismodule.h"
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Register the active defragmentation callback of the module, which relocates
+ * the inverted indexes, the tag values and the document tables of every index.
+ * Does nothing if the server doesn't support module defragmentation. */
+void Defrag_Register(RedisModuleCtx *ctx);
+
+#ifdef __cplusplus
+}
+#endif
+#endif
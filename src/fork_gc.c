@@ -57,7 +57,12 @@ typedef enum {
 // Assumes the spec is locked.
 static void FGC_updateStats(ForkGC *gc, RedisSearchCtx *sctx,
             size_t recordsRemoved, size_t bytesCollected, size_t bytesAdded, uint64_t blocksDenied) {
+  if (gc->dryRun) {
+    // Nothing was collected
+    return;
+  }
   sctx->spec->stats.numRecords -= recordsRemoved;
+  gc->runEntriesRemoved += recordsRemoved;
   sctx->spec->stats.invertedSize += bytesAdded;
   sctx->spec->stats.invertedSize -= bytesCollected;
   gc->stats.totalCollected += bytesCollected;
@@ -65,6 +70,25 @@ static void FGC_updateStats(ForkGC *gc, RedisSearchCtx *sctx,
   gc->stats.gcBlocksDenied += blocksDenied;
 }
 
+// Apply the delta to the index, taking ownership of it. In a dry run, only compute what applying
+// it would collect, leaving the index untouched. Returns whether the index was changed.
+static bool FGC_applyDelta(ForkGC *gc, InvertedIndex *idx, InvertedIndexGcDelta *delta,
+                           II_GCScanStats *info) {
+  if (gc->dryRun) {
+    InvertedIndex_PreviewGcDelta(idx, delta, info);
+    InvertedIndex_GcDelta_Free(delta);
+    return false;
+  }
+  InvertedIndex_ApplyGcDelta(idx, delta, info);
+  return true;
+}
+
+static void FGC_recordRun(ForkGC *gc, GcFieldType fieldType, uint32_t docsRemoved,
+                          const II_GCScanStats *info) {
+  GcRunStats_Record(gc->run, fieldType, docsRemoved, info->entries_removed, info->bytes_freed,
+                    info->bytes_allocated, info->blocks_ignored);
+}
+
 // Buff shouldn't be NULL.
 static void FGC_sendFixed(ForkGC *fgc, const void *buff, size_t len) {
   RS_LOG_ASSERT(len > 0, "buffer length cannot be 0");
@@ -541,18 +565,22 @@ static void resetCardinality(NumGcInfo *info, NumericRange *range, size_t blocks
   IndexResult_Free(res);
 }
 
-static void applyNumIdx(ForkGC *gc, RedisSearchCtx *sctx, NumGcInfo *ninfo) {
+// Returns whether the range was changed, see FGC_applyDelta.
+static bool applyNumIdx(ForkGC *gc, RedisSearchCtx *sctx, NumGcInfo *ninfo) {
   NumericRangeNode *currNode = ninfo->node;
   InvertedIndexGcDelta *delta = ninfo->delta;
   II_GCScanStats *info = &ninfo->info;
   size_t blocksSinceFork = InvertedIndex_NumBlocks(currNode->range->entries) - GcScanDelta_LastBlockIdx(delta) - 1; // record before applying changes
-  InvertedIndex_ApplyGcDelta(currNode->range->entries, delta, info);
+  if (!FGC_applyDelta(gc, currNode->range->entries, delta, info)) {
+    return false;
+  }
   currNode->range->invertedIndexSize += info->bytes_allocated;
   currNode->range->invertedIndexSize -= info->bytes_freed;
 
   FGC_updateStats(gc, sctx, info->entries_removed, info->bytes_freed, info->bytes_allocated, info->blocks_ignored);
 
   resetCardinality(ninfo, currNode->range, blocksSinceFork);
+  return true;
 }
 
 static FGCError FGC_parentHandleTerms(ForkGC *gc) {
@@ -597,10 +625,10 @@ static FGCError FGC_parentHandleTerms(ForkGC *gc) {
     goto cleanup;
   }
 
-  InvertedIndex_ApplyGcDelta(idx, delta, &info);
-  shouldFreeDeltas = false; // ownership passed to InvertedIndex_ApplyGcDelta
+  uint32_t docsRemoved = GcScanDelta_UniqueDocsRemoved(delta);
+  shouldFreeDeltas = false; // ownership passed to FGC_applyDelta
 
-  if (InvertedIndex_NumDocs(idx) == 0) {
+  if (FGC_applyDelta(gc, idx, delta, &info) && InvertedIndex_NumDocs(idx) == 0) {
 
     // inverted index was cleaned entirely lets free it
     RedisModuleString *termKey = fmtRedisTermKey(sctx, term, len);
@@ -628,6 +656,7 @@ static FGCError FGC_parentHandleTerms(ForkGC *gc) {
   }
 
   FGC_updateStats(gc, sctx, info.entries_removed, info.bytes_freed, info.bytes_allocated, info.blocks_ignored);
+  FGC_recordRun(gc, GcFieldType_Text, docsRemoved, &info);
 
 cleanup:
 
@@ -650,6 +679,7 @@ static FGCError FGC_parentHandleNumeric(ForkGC *gc) {
   RedisModuleString *keyName = NULL;
   uint64_t rtUniqueId;
   NumericRangeTree *rt = NULL;
+  GcFieldType fieldType = GcFieldType_Numeric;
   FGCError status = recvNumericTagHeader(gc, &fieldName, &fieldNameLen, &rtUniqueId);
   bool initialized = false;
   if (status == FGC_DONE) {
@@ -686,6 +716,9 @@ static FGCError FGC_parentHandleNumeric(ForkGC *gc) {
       fs = IndexSpec_GetFieldWithLength(sctx->spec, fieldName, fieldNameLen);
       keyName = IndexSpec_GetFormattedKey(sctx->spec, fs, fs->types);
       rt = openNumericKeysDict(sctx->spec, keyName, DONT_CREATE_INDEX);
+      if (FIELD_IS(fs, INDEXFLD_T_GEO)) {
+        fieldType = GcFieldType_Geo;
+      }
       initialized = true;
     }
 
@@ -695,19 +728,25 @@ static FGCError FGC_parentHandleNumeric(ForkGC *gc) {
     }
 
     if (!ninfo.node->range) {
-      gc->stats.gcNumericNodesMissed++;
+      if (!gc->dryRun) {
+        gc->stats.gcNumericNodesMissed++;
+      }
+      GcRunStats_NumericTreeMissed(gc->run);
       goto loop_cleanup;
     }
 
-    applyNumIdx(gc, sctx, &ninfo);
+    uint32_t docsRemoved = GcScanDelta_UniqueDocsRemoved(ninfo.delta);
     shouldFreeDeltas = false; // ownership passed to applyNumIdx
-    rt->numEntries -= ninfo.info.entries_removed;
-    rt->invertedIndexesSize -= ninfo.info.bytes_freed;
-    rt->invertedIndexesSize += ninfo.info.bytes_allocated;
+    if (applyNumIdx(gc, sctx, &ninfo)) {
+      rt->numEntries -= ninfo.info.entries_removed;
+      rt->invertedIndexesSize -= ninfo.info.bytes_freed;
+      rt->invertedIndexesSize += ninfo.info.bytes_allocated;
 
-    if (InvertedIndex_NumDocs(ninfo.node->range->entries) == 0) {
-      rt->emptyLeaves++;
+      if (InvertedIndex_NumDocs(ninfo.node->range->entries) == 0) {
+        rt->emptyLeaves++;
+      }
     }
+    FGC_recordRun(gc, fieldType, docsRemoved, &ninfo.info);
 
   loop_cleanup:
     if (shouldFreeDeltas) {
@@ -723,7 +762,7 @@ static FGCError FGC_parentHandleNumeric(ForkGC *gc) {
   rm_free(ninfo.registersWithoutLastBlock);
   rm_free(fieldName);
 
-  if (status == FGC_COLLECTED && rt && gc->cleanNumericEmptyNodes) {
+  if (status == FGC_COLLECTED && rt && gc->cleanNumericEmptyNodes && !gc->dryRun) {
     // We need to have a valid strong reference to the spec in order to dereference rt
     StrongRef spec_ref = IndexSpecRef_Promote(gc->index);
     IndexSpec *sp = StrongRef_Get(spec_ref);
@@ -808,11 +847,11 @@ static FGCError FGC_parentHandleTags(ForkGC *gc) {
       goto loop_cleanup;
     }
 
-    InvertedIndex_ApplyGcDelta(idx, delta, &info);
-    shouldFreeDeltas = false; // ownership passed to InvertedIndex_ApplyGcDelta
+    uint32_t docsRemoved = GcScanDelta_UniqueDocsRemoved(delta);
+    shouldFreeDeltas = false; // ownership passed to FGC_applyDelta
 
     // if tag value is empty, let's remove it.
-    if (InvertedIndex_NumDocs(idx) == 0) {
+    if (FGC_applyDelta(gc, idx, delta, &info) && InvertedIndex_NumDocs(idx) == 0) {
       // get memory before deleting the inverted index
       info.bytes_freed += InvertedIndex_MemUsage(idx);
       TrieMap_Delete(tagIdx->values, tagVal, tagValLen, (void (*)(void *))InvertedIndex_Free);
@@ -823,6 +862,7 @@ static FGCError FGC_parentHandleTags(ForkGC *gc) {
     }
 
     FGC_updateStats(gc, sctx, info.entries_removed, info.bytes_freed, info.bytes_allocated, info.blocks_ignored);
+    FGC_recordRun(gc, GcFieldType_Tag, docsRemoved, &info);
 
   loop_cleanup:
     RedisSearchCtx_UnlockSpec(sctx);
@@ -883,10 +923,9 @@ static FGCError FGC_parentHandleMissingDocs(ForkGC *gc) {
     goto cleanup;
   }
 
-  InvertedIndex_ApplyGcDelta(idx, delta, &info);
-  shouldFreeDeltas = false; // ownership passed to InvertedIndex_ApplyGcDelta
+  shouldFreeDeltas = false; // ownership passed to FGC_applyDelta
 
-  if (InvertedIndex_NumDocs(idx) == 0) {
+  if (FGC_applyDelta(gc, idx, delta, &info) && InvertedIndex_NumDocs(idx) == 0) {
     // inverted index was cleaned entirely lets free it
     info.bytes_freed += InvertedIndex_MemUsage(idx);
     dictDelete(sctx->spec->missingFieldDict, fieldName);
@@ -947,13 +986,13 @@ static FGCError FGC_parentHandleExistingDocs(ForkGC *gc) {
 
   InvertedIndex *idx = sp->existingDocs;
 
-  InvertedIndex_ApplyGcDelta(idx, delta, &info);
-  shouldFreeDeltas = false; // ownership passed to InvertedIndex_ApplyGcDelta
+  shouldFreeDeltas = false; // ownership passed to FGC_applyDelta
+  bool applied = FGC_applyDelta(gc, idx, delta, &info);
 
   // We don't count the records that we removed, because we also don't count
   // their addition (they are duplications so we have no such desire).
 
-  if (InvertedIndex_NumDocs(idx) == 0) {
+  if (applied && InvertedIndex_NumDocs(idx) == 0) {
     // inverted index was cleaned entirely, let's free it
     info.bytes_freed += InvertedIndex_MemUsage(idx);
     InvertedIndex_Free(idx);
@@ -1005,8 +1044,25 @@ static inline bool isOutOfMemory(RedisModuleCtx *ctx) {
   return used_memory_ratio > 1;
 }
 
-static int periodicCb(void *privdata) {
-  ForkGC *gc = privdata;
+// Collect the deleted documents, or in a dry run only record what would be collected in
+// `gc->rsStats`.
+// Publish the done run to the index events subscribers, unless the index was dropped meanwhile.
+static void FGC_publishCycle(ForkGC *gc, ssize_t collectedBefore) {
+  StrongRef spec_ref = IndexSpecRef_Promote(gc->index);
+  IndexSpec *spec = StrongRef_Get(spec_ref);
+  if (!spec) {
+    return;
+  }
+  ssize_t collected = gc->stats.totalCollected - collectedBefore;
+  IndexEventGcStats stats = {.bytes_collected = collected > 0 ? collected : 0,
+                             .entries_removed = gc->runEntriesRemoved};
+  size_t nameLen;
+  const char *name = HiddenString_GetUnsafe(spec->specName, &nameLen);
+  IndexEvents_GcCycleDone(name, nameLen, stats);
+  IndexSpecRef_Release(spec_ref);
+}
+
+static int runGC(ForkGC *gc, bool dryRun) {
   RedisModuleCtx *ctx = gc->ctx;
 
   // This check must be done first, because some values (like `deletedDocsFromLastRun`) that are used for
@@ -1025,7 +1081,8 @@ static int periodicCb(void *privdata) {
     return 0;
   }
 
-  if (gc->deletedDocsFromLastRun < RSGlobalConfig.gcConfigParams.forkGc.forkGcCleanThreshold) {
+  // A dry run reports what would be collected even below the threshold
+  if (!dryRun && gc->deletedDocsFromLastRun < RSGlobalConfig.gcConfigParams.forkGc.forkGcCleanThreshold) {
     IndexSpecRef_Release(early_check);
     return 1;
   }
@@ -1033,6 +1090,9 @@ static int periodicCb(void *privdata) {
   int gcrv = 1;
   pid_t cpid;
   TimeSample ts;
+  long long msApplying = 0;
+  ssize_t collectedBefore = gc->stats.totalCollected;
+  gc->runEntriesRemoved = 0;
 
   while (gc->pauseState == FGC_PAUSED_CHILD) {
     gc->execState = FGC_STATE_WAIT_FORK;
@@ -1089,8 +1149,9 @@ static int periodicCb(void *privdata) {
 
   // Now that we hold the GIL, we can cache this value knowing it won't change by the main thread
   // upon deleting a document (this is the actual number of documents to be cleaned by the fork).
-  size_t num_docs_to_clean = gc->deletedDocsFromLastRun;
-  gc->deletedDocsFromLastRun = 0;
+  // A dry run leaves them to the next run.
+  size_t num_docs_to_clean = dryRun ? 0 : gc->deletedDocsFromLastRun;
+  gc->deletedDocsFromLastRun -= num_docs_to_clean;
 
   gc->retryInterval.tv_sec = RSGlobalConfig.gcConfigParams.forkGc.forkGcRunIntervalSec;
 
@@ -1117,11 +1178,30 @@ static int periodicCb(void *privdata) {
       usleep(500);
     }
 
+    // Applying the child's results allocates on the main process, so hold it while
+    // the memory watcher reports that background work is paused
+    RedisModule_ThreadSafeContextLock(ctx);
+    bool memoryPaused = RedisMemory_PollWatcher(ctx);
+    RedisModule_ThreadSafeContextUnlock(ctx);
+    while (memoryPaused) {
+      gc->execState = FGC_STATE_WAIT_APPLY;
+      MemoryWatcher_WaitWhilePaused(100);
+      RedisModule_ThreadSafeContextLock(ctx);
+      memoryPaused = RedisMemory_PollWatcher(ctx);
+      RedisModule_ThreadSafeContextUnlock(ctx);
+    }
+
     gc->execState = FGC_STATE_APPLYING;
     gc->cleanNumericEmptyNodes = RSGlobalConfig.gcConfigParams.forkGc.forkGCCleanNumericEmptyNodes;
+    gc->dryRun = dryRun;
+    gc->run = GcRunStats_New(dryRun);
+    TimeSample applyTs;
+    TimeSampler_Start(&applyTs);
     if (FGC_parentHandleFromChild(gc) == FGC_SPEC_DELETED) {
       gcrv = 0;
     }
+    TimeSampler_End(&applyTs);
+    msApplying = TimeSampler_DurationMS(&applyTs);
     close(gc->pipe_read_fd);
     // give the child some time to exit gracefully
     for (int attempt = 0; attempt < GC_WAIT_ATTEMPTS; ++attempt) {
@@ -1136,7 +1216,7 @@ static int periodicCb(void *privdata) {
     RedisModule_KillForkChild(cpid);
     RedisModule_ThreadSafeContextUnlock(ctx);
 
-    if (gcrv) {
+    if (gcrv && !dryRun) {
       gcrv = VecSim_CallTieredIndexesGC(gc->index);
     }
   }
@@ -1146,13 +1226,29 @@ static int periodicCb(void *privdata) {
   TimeSampler_End(&ts);
   long long msRun = TimeSampler_DurationMS(&ts);
 
-  gc->stats.numCycles++;
-  gc->stats.totalMSRun += msRun;
-  gc->stats.lastRunTimeMs = msRun;
+  GcRunStats_SetTimes(gc->run, msRun - msApplying, msApplying);
+  GcStats_RecordRun(gc->rsStats, gc->run);
+  gc->run = NULL;
+  gc->dryRun = false;
+
+  if (!dryRun) {
+    gc->stats.numCycles++;
+    gc->stats.totalMSRun += msRun;
+    gc->stats.lastRunTimeMs = msRun;
+    FGC_publishCycle(gc, collectedBefore);
+  }
 
   return gcrv;
 }
 
+static int periodicCb(void *privdata) {
+  return runGC(privdata, false);
+}
+
+static int dryRunCb(void *privdata) {
+  return runGC(privdata, true);
+}
+
 #if defined(__has_feature)
 #if __has_feature(thread_sanitizer)
 #define NO_TSAN_CHECK __attribute__((no_sanitize("thread")))
@@ -1192,6 +1288,7 @@ void FGC_Apply(ForkGC *gc) NO_TSAN_CHECK {
 static void onTerminateCb(void *privdata) {
   ForkGC *gc = privdata;
   IndexsGlobalStats_UpdateLogicallyDeleted(-gc->deletedDocsFromLastRun);
+  GcStats_Free(gc->rsStats);
   WeakRef_Release(gc->index);
   RedisModule_FreeThreadSafeContext(gc->ctx);
   rm_free(gc);
@@ -1210,6 +1307,37 @@ static void statsCb(RedisModule_Reply *reply, void *gcCtx) {
   REPLY_KVNUM("gc_blocks_denied", (double)gc->stats.gcBlocksDenied);
 }
 
+static void replyMap(void *reply, const char *key) {
+  RedisModule_ReplyKV_Map(reply, key);
+}
+
+static void replyMapEnd(void *reply) {
+  RedisModule_Reply_MapEnd(reply);
+}
+
+static void replyLongLong(void *reply, const char *key, int64_t value) {
+  RedisModule_ReplyKV_LongLong(reply, key, value);
+}
+
+static void replyDouble(void *reply, const char *key, double value) {
+  RedisModule_ReplyKV_Double(reply, key, value);
+}
+
+static bool debugStatsCb(RedisModule_Reply *reply, void *gcCtx, bool lastDryRun) {
+  ForkGC *gc = gcCtx;
+  if (lastDryRun && !GcStats_HasLastDryRun(gc->rsStats)) {
+    return false;
+  }
+  RedisModule_Reply_Map(reply);
+  if (lastDryRun) {
+    GcStats_ReplyLastDryRun(gc->rsStats, reply, replyMap, replyMapEnd, replyLongLong, replyDouble);
+  } else {
+    GcStats_Reply(gc->rsStats, reply, replyMap, replyMapEnd, replyLongLong, replyDouble);
+  }
+  RedisModule_Reply_MapEnd(reply);
+  return true;
+}
+
 #ifdef FTINFO_FOR_INFO_MODULES
 static void statsForInfoCb(RedisModuleInfoCtx *ctx, void *gcCtx) {
   ForkGC *gc = gcCtx;
@@ -1241,6 +1369,7 @@ ForkGC *FGC_New(StrongRef spec_ref, GCCallbacks *callbacks) {
   *forkGc = (ForkGC){
       .index = StrongRef_Demote(spec_ref),
       .deletedDocsFromLastRun = 0,
+      .rsStats = GcStats_New(),
   };
   forkGc->retryInterval.tv_sec = RSGlobalConfig.gcConfigParams.forkGc.forkGcRunIntervalSec;
   forkGc->retryInterval.tv_nsec = 0;
@@ -1250,7 +1379,9 @@ ForkGC *FGC_New(StrongRef spec_ref, GCCallbacks *callbacks) {
 
   callbacks->onTerm = onTerminateCb;
   callbacks->periodicCallback = periodicCb;
+  callbacks->dryRunCallback = dryRunCb;
   callbacks->renderStats = statsCb;
+  callbacks->renderDebugStats = debugStatsCb;
   #ifdef FTINFO_FOR_INFO_MODULES
   callbacks->renderStatsForInfo = statsForInfoCb;
   #endif
@@ -13,6 +13,8 @@
 #include "redismodule.h"
 #include "gc.h"
 #include "VecSim/vec_sim.h"
+#include "gc_stats_rs.h"
+#include "index_events_rs.h"
 #include <poll.h>
 
 #ifdef __cplusplus
@@ -44,6 +46,14 @@ typedef struct ForkGC {
 
   // statistics for reporting
   ForkGCStats stats;
+  // statistics reported by FT.DEBUG GC_STATS, including the last dry run
+  GcStats *rsStats;
+  // the metrics of the run being applied, NULL between runs
+  GcRunStats *run;
+  // whether the current run only previews what it would collect
+  bool dryRun;
+  // the number of entries the current run removed, published once it's done
+  size_t runEntriesRemoved;
 
   int pipe_read_fd;
   int pipe_write_fd;
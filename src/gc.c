@@ -26,12 +26,14 @@ static redisearch_thpool_t *gcThreadpool_g = NULL;
 typedef struct GCDebugTask {
   GCContext* gc;
   RedisModuleBlockedClient* bClient;
+  bool dryRun;
 } GCDebugTask;
 
-static GCDebugTask *GCDebugTaskCreate(GCContext *gc, RedisModuleBlockedClient* bClient) {
+static GCDebugTask *GCDebugTaskCreate(GCContext *gc, RedisModuleBlockedClient* bClient, bool dryRun) {
   GCDebugTask *task = rm_new(GCDebugTask);
   task->gc = gc;
   task->bClient = bClient;
+  task->dryRun = dryRun;
   return task;
 }
 
@@ -93,7 +95,8 @@ static void debugTaskCallback(void* data) {
   GCContext* gc = task->gc;
   RedisModuleBlockedClient* bc = task->bClient;
 
-  int ret = gc->callbacks.periodicCallback(gc->gcCtx);
+  int ret = task->dryRun ? gc->callbacks.dryRunCallback(gc->gcCtx)
+                         : gc->callbacks.periodicCallback(gc->gcCtx);
 
   // if GC was invoke by debug command, we release the client
   // and terminate without rescheduling the task again.
@@ -129,6 +132,7 @@ void GCContext_Start(GCContext* gc) {
 void GCContext_StopMock(GCContext* gc) {
   // for fork gc debug
   RedisModule_FreeThreadSafeContext(((ForkGC *)gc->gcCtx)->ctx);
+  GcStats_Free(((ForkGC *)gc->gcCtx)->rsStats);
   WeakRef_Release(((ForkGC *)gc->gcCtx)->index);
   free(gc->gcCtx);
   free(gc);
@@ -138,6 +142,10 @@ void GCContext_RenderStats(GCContext* gc, RedisModule_Reply* reply) {
   gc->callbacks.renderStats(reply, gc->gcCtx);
 }
 
+bool GCContext_RenderDebugStats(GCContext* gc, RedisModule_Reply* reply, bool lastDryRun) {
+  return gc->callbacks.renderDebugStats(reply, gc->gcCtx, lastDryRun);
+}
+
 #ifdef FTINFO_FOR_INFO_MODULES
 void GCContext_RenderStatsForInfo(GCContext* gc, RedisModuleInfoCtx* ctx) {
   gc->callbacks.renderStatsForInfo(ctx, gc->gcCtx);
@@ -150,17 +158,17 @@ void GCContext_OnDelete(GCContext* gc) {
   }
 }
 
-void GCContext_CommonForceInvoke(GCContext* gc, RedisModuleBlockedClient* bc) {
-  GCDebugTask *task = GCDebugTaskCreate(gc, bc);
+void GCContext_CommonForceInvoke(GCContext* gc, RedisModuleBlockedClient* bc, bool dryRun) {
+  GCDebugTask *task = GCDebugTaskCreate(gc, bc, dryRun);
   redisearch_thpool_add_work(gcThreadpool_g, debugTaskCallback, task, THPOOL_PRIORITY_HIGH);
 }
 
-void GCContext_ForceInvoke(GCContext* gc, RedisModuleBlockedClient* bc) {
-  GCContext_CommonForceInvoke(gc, bc);
+void GCContext_ForceInvoke(GCContext* gc, RedisModuleBlockedClient* bc, bool dryRun) {
+  GCContext_CommonForceInvoke(gc, bc, dryRun);
 }
 
 void GCContext_ForceBGInvoke(GCContext* gc) {
-  GCContext_CommonForceInvoke(gc, NULL);
+  GCContext_CommonForceInvoke(gc, NULL, false);
 }
 
 static void GCContext_UnblockClient(void* data) {
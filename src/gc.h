@@ -25,7 +25,12 @@ extern "C" {
 
 typedef struct GCCallbacks {
   int  (*periodicCallback)(void* gcCtx);
+  // Run the GC without collecting, only recording what it would collect
+  int  (*dryRunCallback)(void* gcCtx);
   void (*renderStats)(RedisModule_Reply* reply, void* gc);
+  // Reply the statistics of FT.DEBUG GC_STATS, or only those of the last dry run, as a map.
+  // Returns false, replying nothing, if there was no dry run
+  bool (*renderDebugStats)(RedisModule_Reply* reply, void* gc, bool lastDryRun);
   void (*renderStatsForInfo)(RedisModuleInfoCtx* ctx, void* gc);
   void (*onDelete)(void* ctx);
   void (*onTerm)(void* ctx);
@@ -45,11 +50,12 @@ void GCContext_Start(GCContext* gc);
 void GCContext_StartNow(GCContext* gc);
 void GCContext_StopMock(GCContext* gc);
 void GCContext_RenderStats(GCContext* gc, RedisModule_Reply* ctx);
+bool GCContext_RenderDebugStats(GCContext* gc, RedisModule_Reply* reply, bool lastDryRun);
 #ifdef FTINFO_FOR_INFO_MODULES
 void GCContext_RenderStatsForInfo(GCContext* gc, RedisModuleInfoCtx* ctx);
 #endif
 void GCContext_OnDelete(GCContext* gc);
-void GCContext_ForceInvoke(GCContext* gc, RedisModuleBlockedClient* bc);
+void GCContext_ForceInvoke(GCContext* gc, RedisModuleBlockedClient* bc, bool dryRun);
 void GCContext_ForceBGInvoke(GCContext* gc);
 void GCContext_WaitForAllOperations(RedisModuleBlockedClient* bc);
 
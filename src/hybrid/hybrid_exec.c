@@ -506,6 +506,24 @@ static int HybridRequest_BuildPipelineAndExecute(StrongRef hybrid_ref, HybridPip
   }
 }
 
+// Take the admission ticket of the request, shared by its subqueries
+static int HybridRequest_Admit(HybridRequest *hreq, QueryError *status) {
+  size_t nameLen;
+  const char *name = HiddenString_GetUnsafe(hreq->sctx->spec->specName, &nameLen);
+  const char *query = hreq->requests[SEARCH_INDEX]->query;
+  if (!query) query = "";
+  hreq->admissionTicket = Admission_Acquire(name, nameLen, query, strlen(query));
+  if (!hreq->admissionTicket) {
+    QueryError_SetError(status, QUERY_ERROR_CODE_GENERIC, "Too many concurrent queries, try again later");
+    return REDISMODULE_ERR;
+  }
+  for (size_t i = 0; i < hreq->nrequests; i++) {
+    hreq->requests[i]->pipeline.qctx.ticket = hreq->admissionTicket;
+  }
+  hreq->tailPipeline->qctx.ticket = hreq->admissionTicket;
+  return REDISMODULE_OK;
+}
+
 static inline void DefaultCleanup(StrongRef hybrid_ref) {
   StrongRef_Release(hybrid_ref);
   CurrentThread_ClearIndexSpec();
@@ -574,6 +592,10 @@ int hybridCommandHandler(RedisModuleCtx *ctx, RedisModuleString **argv, int argc
   }
   SearchCtx_UpdateTime(hybridRequest->sctx, hybridRequest->reqConfig.queryTimeoutMS);
 
+  if (HybridRequest_Admit(hybridRequest, &status) != REDISMODULE_OK) {
+    return CleanupAndReplyStatus(ctx, hybrid_ref, cmd.hybridParams, &status);
+  }
+
   if (HybridRequest_BuildPipelineAndExecute(hybrid_ref, cmd.hybridParams, ctx, hybridRequest->sctx, &status, internal) != REDISMODULE_OK) {
     HybridRequest_GetError(hybridRequest, &status);
     HybridRequest_ClearErrors(hybridRequest);
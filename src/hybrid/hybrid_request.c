@@ -257,6 +257,11 @@ void HybridRequest_Free(HybridRequest *req) {
 
     // Clean up the tail pipeline error
     QueryError_ClearError(&req->tailPipelineError);
+
+    // Free the slot once the pipelines, which check whether the query was killed, are gone
+    if (req->admissionTicket) {
+      AdmissionTicket_Free(req->admissionTicket);
+    }
     if (req->args) {
       for (size_t ii = 0; ii < req->nargs; ++ii) {
         sdsfree(req->args[ii]);
@@ -34,6 +34,7 @@ typedef struct HybridRequest {
     RPStatus *subqueriesReturnCodes;  // Array to store return codes from each subquery
     RedisSearchCtx *sctx;
     QEFlags reqflags;
+    AdmissionTicket *admissionTicket;  // Shared by the subqueries, held until the request is freed
 } HybridRequest;
 
 // Blocked client context for HybridRequest background execution
@@ -140,9 +140,9 @@ static RSDocumentMetadata *makeDocumentId(RedisModuleCtx *ctx, RSAddDocumentCtx
       --spec->stats.numDocuments;
       DMD_Return(aCtx->oldMd);
       aCtx->oldMd = dmd;
-      if (spec->gc) {
-        GCContext_OnDelete(spec->gc);
-      }
+      size_t len;
+      const char *key = RedisModule_StringPtrLen(doc->docKey, &len);
+      IndexSpec_OnDocDeleted(spec, key, len);
       if (spec->flags & Index_HasVecSim) {
         for (int i = 0; i < spec->numFields; ++i) {
           if (spec->fields[i].types == INDEXFLD_T_VECTOR) {
@@ -396,6 +396,11 @@ static void Indexer_Process(RSAddDocumentCtx *aCtx) {
 
 int IndexDocument(RSAddDocumentCtx *aCtx) {
   Indexer_Process(aCtx);
+  if (!(aCtx->stateFlags & ACTX_F_ERRORED) && aCtx->doc->docId) {
+    size_t len;
+    const char *key = RedisModule_StringPtrLen(aCtx->doc->docKey, &len);
+    IndexSpec_OnDocIndexed(aCtx->spec, key, len);
+  }
   AddDocumentCtx_Finish(aCtx);
   return 0;
 }
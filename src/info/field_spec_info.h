@@ -85,5 +85,8 @@ AggregatedFieldSpecInfo AggregatedFieldSpecInfo_Deserialize(const MRReply *reply
 //Get the total memory usage of all the vector fields in the index (in bytes).
 size_t IndexSpec_VectorIndexesSize(IndexSpec *sp);
 
+//Get the stats of the vector field `fs` in the index `sp`.
+VectorIndexStats IndexSpec_GetVectorIndexStats(IndexSpec *sp, const FieldSpec *fs);
+
 //Get the combined stats of all vector fields in the index.
 VectorIndexStats IndexSpec_GetVectorIndexesStats(IndexSpec *sp);
@@ -10,7 +10,6 @@
 #include "util/dict.h"
 #include "spec.h"
 #include "field_spec_info.h"
-#include <string.h>  // Add this for strerror
 
 // Assuming the GIL is held by the caller
 TotalIndexesInfo IndexesInfo_TotalInfo() {
@@ -31,11 +30,7 @@ TotalIndexesInfo IndexesInfo_TotalInfo() {
       continue;
     }
     // Lock for read
-    int rc = pthread_rwlock_rdlock(&sp->rwlock);
-    if (rc != 0) {
-      RedisModule_Log(RSDummyContext, "warning", "Failed to acquire read lock on index %s: rc=%d (%s). Cannot continue getting Index info", HiddenString_GetUnsafe(sp->specName, NULL), rc, strerror(rc));
-      continue;
-    }
+    IndexLock_ReadLock(sp->rwlock);
 
     // Vector indexes stats
     VectorIndexStats vec_info = IndexSpec_GetVectorIndexesStats(sp);
@@ -74,7 +69,7 @@ TotalIndexesInfo IndexesInfo_TotalInfo() {
     }
     info.background_indexing_failures_OOM += sp->scan_failed_OOM;
 
-    pthread_rwlock_unlock(&sp->rwlock);
+    IndexLock_Unlock(sp->rwlock);
   }
   dictReleaseIterator(iter);
   if (info.min_mem == -1) info.min_mem = 0;             // No index found
@@ -21,6 +21,7 @@
 #include "field_spec_info.h"
 #include "info/info_redis/threads/current_thread.h"
 #include "obfuscation/obfuscation_api.h"
+#include "util/redis_mem_info.h"
 
 static void renderIndexOptions(RedisModule_Reply *reply, const IndexSpec *sp) {
 
@@ -290,6 +291,11 @@ void fillReplyWithIndexInfo(RedisSearchCtx* sctx, RedisModule_Reply *reply, bool
   double percent_indexed = IndexesScanner_IndexedPercent(sctx->redisCtx, scanner, sp);
   REPLY_KVNUM("percent_indexed", percent_indexed);
 
+  MemoryWatcherInfo memoryWatcher = MemoryWatcher_Info();
+  REPLY_KVINT("bg_work_paused_on_memory", memoryWatcher.paused);
+  REPLY_KVINT("bg_work_memory_pauses", memoryWatcher.pause_count);
+  REPLY_KVINT("bg_work_memory_paused_ms", memoryWatcher.paused_time_ms);
+
   REPLY_KVINT("number_of_uses", sp->counter);
 
   REPLY_KVINT("cleaning", CleanInProgressOrPending());
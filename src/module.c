@@ -48,6 +48,7 @@
 #include "resp3.h"
 #include "coord/rmr/rmr.h"
 #include "shard_window_ratio.h"
+#include "defrag.h"
 
 #include "hiredis/async.h"
 #include "coord/rmr/reply.h"
@@ -72,6 +73,7 @@
 #include "hybrid/hybrid_exec.h"
 #include "util/redis_mem_info.h"
 #include "notifications.h"
+#include "index_events_rs.h"
 
 #define VERIFY_ACL(ctx, idxR)                                                                     \
   do {                                                                                                      \
@@ -876,6 +878,10 @@ static int AlterIndexInternalCommand(RedisModuleCtx *ctx, RedisModuleString **ar
   RedisSearchCtx_UnlockSpec(&sctx);
   CurrentThread_ClearIndexSpec();
 
+  size_t nameLen;
+  const char *name = HiddenString_GetUnsafe(sp->specName, &nameLen);
+  IndexEvents_IndexAltered(name, nameLen);
+
   RedisModule_Replicate(ctx, RS_ALTER_IF_NX_CMD, "v", argv + 1, (size_t)argc - 1);
   return RedisModule_ReplyWithSimpleString(ctx, "OK");
 
@@ -1098,6 +1104,147 @@ int IndexList(RedisModuleCtx *ctx, RedisModuleString **argv, int argc) {
   return REDISMODULE_OK;
 }
 
+static void replySlowLogEntry(void *reply, const SlowLogEntryInfo *info) {
+  RedisModule_Reply_Map(reply);
+  RedisModule_ReplyKV_LongLong(reply, "id", info->id);
+  RedisModule_ReplyKV_LongLong(reply, "timestamp", info->timestamp);
+  RedisModule_ReplyKV_StringBuffer(reply, "query", info->query, info->query_len);
+  RedisModule_ReplyKV_Double(reply, "parse_ms", info->timings.parse_ns / 1e6);
+  RedisModule_ReplyKV_Double(reply, "execute_ms", info->timings.execute_ns / 1e6);
+  RedisModule_ReplyKV_Double(reply, "load_ms", info->timings.load_ns / 1e6);
+  RedisModule_ReplyKV_Double(reply, "serialize_ms", info->timings.serialize_ns / 1e6);
+  RedisModule_ReplyKV_LongLong(reply, "total_results", info->total_results);
+  RedisModule_ReplyKV_LongLong(reply, "returned_results", info->returned_results);
+  RedisModule_ReplyKV_Map(reply, "params");
+}
+
+static void replySlowLogParam(void *reply, const char *name, uintptr_t name_len,
+                              const char *value, uintptr_t value_len) {
+  RedisModule_Reply_StringBuffer(reply, name, name_len);
+  RedisModule_Reply_StringBuffer(reply, value, value_len);
+}
+
+static void replySlowLogEntryEnd(void *reply) {
+  RedisModule_Reply_MapEnd(reply); // >params
+  RedisModule_Reply_MapEnd(reply);
+}
+
+static void replyInFlightQuery(void *reply, uint64_t id, const char *index, uintptr_t index_len,
+                               const char *query, uintptr_t query_len, uint64_t elapsed_ms) {
+  RedisModule_Reply_Map(reply);
+  RedisModule_ReplyKV_LongLong(reply, "id", id);
+  RedisModule_ReplyKV_StringBuffer(reply, "index", index, index_len);
+  RedisModule_ReplyKV_StringBuffer(reply, "query", query, query_len);
+  RedisModule_ReplyKV_LongLong(reply, "elapsed_ms", elapsed_ms);
+  RedisModule_Reply_MapEnd(reply);
+}
+
+// FT._QUERIES LIST | KILL {id}
+int QueriesCommand(RedisModuleCtx *ctx, RedisModuleString **argv, int argc) {
+  const char *args[argc];
+  for (int i = 1; i < argc; i++) {
+    args[i - 1] = RedisModule_StringPtrLen(argv[i], NULL);
+  }
+  QueriesSubcommand subcommand;
+  uint64_t id;
+  if (!Admission_ParseCommand(args, argc - 1, &subcommand, &id)) {
+    return RedisModule_ReplyWithError(ctx, "Invalid arguments, expected LIST or KILL {id}");
+  }
+
+  switch (subcommand) {
+    case QueriesSubcommand_List: {
+      RedisModule_Reply _reply = RedisModule_NewReply(ctx), *reply = &_reply;
+      RedisModule_Reply_Array(reply);
+      Admission_ReplyInFlight(reply, replyInFlightQuery);
+      RedisModule_Reply_ArrayEnd(reply);
+      RedisModule_EndReply(reply);
+      return REDISMODULE_OK;
+    }
+    case QueriesSubcommand_Kill:
+      if (!Admission_Kill(id)) {
+        return RedisModule_ReplyWithError(ctx, "No such query");
+      }
+      return RedisModule_ReplyWithSimpleString(ctx, "OK");
+  }
+  return REDISMODULE_OK;
+}
+
+static void replyMetricsText(void *ctx, const char *text, uintptr_t len) {
+  RedisModule_ReplyWithStringBuffer(ctx, text, len);
+}
+
+// FT._METRICS
+// Replies the metrics of the module in the Prometheus text exposition format
+int MetricsCommand(RedisModuleCtx *ctx, RedisModuleString **argv, int argc) {
+  if (argc != 1) {
+    return RedisModule_WrongArity(ctx);
+  }
+
+  // The gauges are refreshed on read, the counters as the queries run
+  dictIterator *iter = dictGetIterator(specDict_g);
+  dictEntry *entry = NULL;
+  while ((entry = dictNext(iter))) {
+    IndexSpec *sp = StrongRef_Get(dictGetRef(entry));
+    if (!sp) {
+      continue;
+    }
+    size_t nameLen;
+    const char *name = HiddenString_GetUnsafe(sp->specName, &nameLen);
+    Metrics_SetIndexGauges(name, nameLen, sp->stats.numDocuments, sp->stats.numRecords);
+  }
+  dictReleaseIterator(iter);
+
+  Metrics_Reply(ctx, replyMetricsText);
+  return REDISMODULE_OK;
+}
+
+// FT._SLOWLOG {index} GET [count] | LEN | RESET
+int SlowLogCommand(RedisModuleCtx *ctx, RedisModuleString **argv, int argc) {
+  if (argc < 3) {
+    return RedisModule_WrongArity(ctx);
+  }
+
+  const char *args[argc - 2];
+  for (int i = 2; i < argc; i++) {
+    args[i - 2] = RedisModule_StringPtrLen(argv[i], NULL);
+  }
+  SlowLogSubcommand subcommand;
+  size_t count;
+  if (!SlowLog_ParseCommand(args, argc - 2, &subcommand, &count)) {
+    return RedisModule_ReplyWithError(ctx, "Invalid arguments, expected GET [count], LEN or RESET");
+  }
+
+  const char *idx = RedisModule_StringPtrLen(argv[1], NULL);
+  StrongRef ref = IndexSpec_LoadUnsafe(idx);
+  IndexSpec *sp = StrongRef_Get(ref);
+  if (!sp) {
+    return RedisModule_ReplyWithErrorFormat(ctx, "%s: no such index", idx);
+  }
+
+  // Verify ACL keys permission
+  if (!ACLUserMayAccessIndex(ctx, sp)) {
+    return RedisModule_ReplyWithError(ctx, NOPERM_ERR);
+  }
+
+  switch (subcommand) {
+    case SlowLogSubcommand_Get: {
+      RedisModule_Reply _reply = RedisModule_NewReply(ctx), *reply = &_reply;
+      RedisModule_Reply_Array(reply);
+      SlowLog_Reply(sp->slowlog, count, reply, replySlowLogEntry, replySlowLogParam,
+                    replySlowLogEntryEnd);
+      RedisModule_Reply_ArrayEnd(reply);
+      RedisModule_EndReply(reply);
+      return REDISMODULE_OK;
+    }
+    case SlowLogSubcommand_Len:
+      return RedisModule_ReplyWithLongLong(ctx, SlowLog_Len(sp->slowlog));
+    case SlowLogSubcommand_Reset:
+      SlowLog_Reset(sp->slowlog);
+      return RedisModule_ReplyWithSimpleString(ctx, "OK");
+  }
+  return REDISMODULE_OK;
+}
+
 // Restore an index schema from the given string.
 // Currently behaves as FT._CREATEIFNX (No error if index exists).
 // FT._RESTOREIFNX SCHEMA {encode version} {schema string}
@@ -1367,11 +1514,16 @@ int RediSearch_InitModuleInternal(RedisModuleCtx *ctx) {
 
   RM_TRY_F(IndexSpec_RegisterType, ctx);
 
+  // register the active defragmentation callback
+  Defrag_Register(ctx);
+
   RM_TRY_F(RegisterLegacyTypes, ctx);
 
   RedisModule_SubscribeToServerEvent(ctx, RedisModuleEvent_Loading, RDB_LoadingEvent);
   RedisModule_SubscribeToServerEvent(ctx, RedisModuleEvent_LoadingProgress, LoadingProgressCallback);
 
+  Indexes_StartEventsDispatch();
+
 // With coordinator we do not want to raise a move error for index commands so we do not specify
 // any key.
 #define INDEX_ONLY_CMD_ARGS 0, 0, 0
@@ -1487,6 +1639,15 @@ int RediSearch_InitModuleInternal(RedisModuleCtx *ctx) {
   RM_TRY(RMCreateSearchCommand(ctx, RS_SYNDUMP_CMD, SynDumpCommand, "readonly",
          INDEX_ONLY_CMD_ARGS, "", false))
 
+  RM_TRY(RMCreateSearchCommand(ctx, RS_SLOWLOG_CMD, SlowLogCommand, "readonly",
+         INDEX_ONLY_CMD_ARGS, "slow admin", false))
+
+  RM_TRY(RMCreateSearchCommand(ctx, RS_QUERIES_CMD, QueriesCommand, "readonly",
+         0, 0, 0, "slow admin dangerous", false))
+
+  RM_TRY(RMCreateSearchCommand(ctx, RS_METRICS_CMD, MetricsCommand, "readonly",
+         0, 0, 0, "slow admin", false))
+
   RM_TRY(RMCreateSearchCommand(ctx, RS_ALTER_CMD, AlterIndexCommand,
          "write deny-oom", INDEX_ONLY_CMD_ARGS, "", !IsEnterprise()))
 
@@ -1544,6 +1705,7 @@ void RediSearch_CleanupModule(void) {
 
   // First free all indexes
   Indexes_Free(specDict_g);
+  Indexes_StopEventsDispatch();
   dictRelease(specDict_g);
   specDict_g = NULL;
 
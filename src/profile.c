@@ -44,6 +44,11 @@ void printInvIdxIt(RedisModule_Reply *reply, QueryIterator *root, ProfileCounter
       printProfileType("NUMERIC");
       RedisModule_Reply_SimpleString(reply, "Term");
       RedisModule_Reply_SimpleStringf(reply, "%g - %g", it->profileCtx.numeric.rangeMin, it->profileCtx.numeric.rangeMax);
+      uint64_t skipped, decoded;
+      if (IndexReader_PruningStats(it->reader, &skipped, &decoded)) {
+        RedisModule_ReplyKV_LongLong(reply, "Blocks skipped", skipped);
+        RedisModule_ReplyKV_LongLong(reply, "Blocks decoded", decoded);
+      }
     } else {
       printProfileType("GEO");
       RedisModule_Reply_SimpleString(reply, "Term");
@@ -84,7 +84,7 @@ RedisModuleString *fmtRedisScoreIndexKey(const RedisSearchCtx *ctx, const char *
 
 void RedisSearchCtx_LockSpecRead(RedisSearchCtx *ctx) {
   RS_ASSERT(ctx->flags == RS_CTX_UNSET);
-  pthread_rwlock_rdlock(&ctx->spec->rwlock);
+  IndexLock_ReadLock(ctx->spec->rwlock);
   // pause rehashing while we're using the dict for reads only
   // Assert that the pause value before we pause is valid.
   RS_ASSERT_ALWAYS(dictPauseRehashing(ctx->spec->keysDict));
@@ -93,7 +93,7 @@ void RedisSearchCtx_LockSpecRead(RedisSearchCtx *ctx) {
 
 void RedisSearchCtx_LockSpecWrite(RedisSearchCtx *ctx) {
   RS_ASSERT(ctx->flags == RS_CTX_UNSET);
-  pthread_rwlock_wrlock(&ctx->spec->rwlock);
+  IndexLock_WriteLock(ctx->spec->rwlock);
   ctx->flags = RS_CTX_READWRITE;
 }
 
@@ -125,7 +125,7 @@ void RedisSearchCtx_UnlockSpec(RedisSearchCtx *sctx) {
     // Assert that it was actually previously paused
     RS_ASSERT_ALWAYS(dictResumeRehashing(sctx->spec->keysDict));
   }
-  pthread_rwlock_unlock(&sctx->spec->rwlock);
+  IndexLock_Unlock(sctx->spec->rwlock);
   sctx->flags = RS_CTX_UNSET;
 }
 
@@ -270,9 +270,7 @@ int RediSearch_DeleteDocument(RefManager* rm, const void* docKey, size_t len) {
     if (DocTable_Delete(&sp->docs, docKey, len)) {
       // Delete returns true/false, not RM_{OK,ERR}
       sp->stats.numDocuments--;
-      if (sp->gc) {
-        GCContext_OnDelete(sp->gc);
-      }
+      IndexSpec_OnDocDeleted(sp, docKey, len);
     } else {
       rc = REDISMODULE_ERR;
     }
@@ -0,0 +1,286 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The declarative description of an analysis chain, as stored in the index
+//! spec.
+
+use std::{
+    borrow::Cow,
+    fmt::{self, Display},
+    str::FromStr,
+    sync::Arc,
+};
+
+use crate::{
+    Analyzer, StopWords, TokenFilter,
+    filter::{PhoneticEncoder, PhoneticFilter, StemFilter, Stemmer, StopWordsFilter},
+    long_terms::{LongTermCounters, LongTermFilter, TermLimit},
+    ngram::NGramTokenizer,
+    tokenizer::{KeywordTokenizer, StandardTokenizer, Tokenizer, WhitespaceTokenizer},
+    word_delimiter::{LowercaseFilter, WordDelimiterFilter, WordDelimiterOptions},
+};
+
+/// The default smallest gram of the n-gram tokenizers (`MINGRAM`).
+pub const DEFAULT_MIN_GRAM: u8 = 2;
+/// The default largest gram of the n-gram tokenizer (`MAXGRAM`).
+pub const DEFAULT_MAX_GRAM: u8 = 3;
+/// The default largest gram of the edge n-gram tokenizer (`MAXGRAM`).
+pub const DEFAULT_MAX_EDGE_GRAM: u8 = 10;
+
+/// The tokenizer of a field (`TOKENIZER {name}`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TokenizerKind {
+    /// Split on punctuation and whitespace.
+    #[default]
+    Standard,
+    /// Split on whitespace only.
+    Whitespace,
+    /// Don't split at all (`NOTOKENIZE`).
+    Keyword { case_fold: bool },
+    /// All the substrings of `min..=max` characters of the words.
+    NGram { min: u8, max: u8 },
+    /// The prefixes of `min..=max` characters of the words.
+    EdgeNGram { min: u8, max: u8 },
+}
+
+impl TokenizerKind {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Whitespace => "whitespace",
+            Self::Keyword { .. } => "keyword",
+            Self::NGram { .. } => "ngram",
+            Self::EdgeNGram { .. } => "edge_ngram",
+        }
+    }
+
+    /// Whether the whole value is a single term. Query terms targeting such
+    /// fields must not be split either.
+    pub const fn is_keyword(self) -> bool {
+        matches!(self, Self::Keyword { .. })
+    }
+
+    /// The tokenizer, if it's an n-gram tokenizer, to look up substrings with.
+    pub fn ngram_tokenizer(self) -> Option<NGramTokenizer> {
+        match self {
+            Self::Standard | Self::Whitespace | Self::Keyword { .. } => None,
+            Self::NGram { min, max } => Some(NGramTokenizer::new(min.into(), max.into(), false)),
+            Self::EdgeNGram { min, max } => Some(NGramTokenizer::new(min.into(), max.into(), true)),
+        }
+    }
+
+    fn build(self) -> Box<dyn Tokenizer> {
+        if let Some(tokenizer) = self.ngram_tokenizer() {
+            return Box::new(tokenizer);
+        }
+        match self {
+            Self::Whitespace => Box::new(WhitespaceTokenizer),
+            Self::Keyword { case_fold } => Box::new(KeywordTokenizer { case_fold }),
+            _ => Box::new(StandardTokenizer),
+        }
+    }
+}
+
+impl FromStr for TokenizerKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s.to_ascii_lowercase().as_str() {
+            "standard" => Ok(Self::Standard),
+            "whitespace" => Ok(Self::Whitespace),
+            "keyword" => Ok(Self::Keyword { case_fold: true }),
+            "ngram" => Ok(Self::NGram {
+                min: DEFAULT_MIN_GRAM,
+                max: DEFAULT_MAX_GRAM,
+            }),
+            "edge_ngram" => Ok(Self::EdgeNGram {
+                min: DEFAULT_MIN_GRAM,
+                max: DEFAULT_MAX_EDGE_GRAM,
+            }),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The resources an analysis chain refers to by name, owned by the C side or
+/// by the index.
+pub trait AnalysisResources {
+    /// The stemmer for `language`, `None` meaning the index language.
+    /// Returns `None` if the language has no stemmer.
+    fn stemmer(&self, language: Option<&str>) -> Option<Arc<dyn Stemmer>>;
+    /// The stopwords of the index.
+    fn index_stopwords(&self) -> Arc<StopWords>;
+    /// The stopwords of `language`, used instead of those of the index for
+    /// fields and documents in that language. `None` if there's no such list.
+    fn language_stopwords(&self, _language: &str) -> Option<Arc<StopWords>> {
+        None
+    }
+    /// The filter expanding the synonyms of the given set.
+    fn synonyms(&self, set: &str) -> Option<Box<dyn TokenFilter>>;
+    /// The phonetic encoder for a matcher such as `dm:en`.
+    fn phonetic(&self, matcher: &str) -> Option<Arc<dyn PhoneticEncoder>>;
+    /// The counters of the over-long terms of the index. `None` if they
+    /// aren't reported, e.g. when analyzing a query.
+    fn long_term_counters(&self) -> Option<Arc<LongTermCounters>> {
+        None
+    }
+}
+
+/// An analysis chain referred to a missing resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    UnknownSynonymSet(String),
+    UnknownPhoneticMatcher(String),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownSynonymSet(set) => write!(f, "Unknown synonym set `{set}`"),
+            Self::UnknownPhoneticMatcher(m) => write!(f, "Unknown phonetic matcher `{m}`"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// The analysis chain of a TEXT field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalyzerConfig {
+    pub tokenizer: TokenizerKind,
+    /// Split technical terms into their parts.
+    pub word_delimiter: Option<WordDelimiterOptions>,
+    /// Whether terms are stemmed (`NOSTEM` disables it).
+    pub stem: bool,
+    /// The language to stem with, instead of the index language.
+    pub language: Option<String>,
+    /// The stopwords, instead of those of the index.
+    pub stopwords: Option<Vec<String>>,
+    /// The synonym set to expand terms with.
+    pub synonyms: Option<String>,
+    /// The phonetic matcher, e.g. `dm:en`.
+    pub phonetic: Option<String>,
+    /// The longest term, and what to do with the longer ones.
+    pub term_limit: TermLimit,
+}
+
+impl Default for AnalyzerConfig {
+    fn default() -> Self {
+        Self {
+            tokenizer: TokenizerKind::Standard,
+            word_delimiter: None,
+            stem: true,
+            language: None,
+            stopwords: None,
+            synonyms: None,
+            phonetic: None,
+            term_limit: TermLimit::default(),
+        }
+    }
+}
+
+impl AnalyzerConfig {
+    /// The chain for a document in `language`, `None` meaning the index
+    /// language. A language declared on the field takes precedence.
+    pub fn for_language(&self, language: Option<&str>) -> Cow<'_, Self> {
+        match language {
+            Some(language) if self.language.is_none() => Cow::Owned(Self {
+                language: Some(language.to_owned()),
+                ..self.clone()
+            }),
+            _ => Cow::Borrowed(self),
+        }
+    }
+
+    /// The chain for a query overriding the stopwords with `STOPWORDS`.
+    ///
+    /// The override takes precedence over the stopwords of the field and of
+    /// the index; an empty list, from `STOPWORDS 0`, disables stopwords
+    /// removal for the query.
+    pub fn with_query_stopwords(&self, stopwords: Option<&[String]>) -> Cow<'_, Self> {
+        match stopwords {
+            Some(words) if self.stopwords.as_deref() != Some(words) => Cow::Owned(Self {
+                stopwords: Some(words.to_vec()),
+                ..self.clone()
+            }),
+            _ => Cow::Borrowed(self),
+        }
+    }
+
+    /// The chain for verbatim query terms: they're kept as written, apart from
+    /// tokenization and case folding.
+    pub fn verbatim(&self) -> Self {
+        Self {
+            stem: false,
+            stopwords: Some(Vec::new()),
+            synonyms: None,
+            phonetic: None,
+            ..self.clone()
+        }
+    }
+
+    /// Build the chain: the tokenizer, then word splitting, the term length
+    /// limit, stopwords removal, synonym expansion, stemming and phonetic
+    /// encoding.
+    ///
+    /// Grams and keywords aren't words, so n-gram and keyword tokenizers are
+    /// only followed by the length limit.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ConfigError`] if the chain refers to a resource missing
+    /// from `resources`.
+    pub fn build(&self, resources: &dyn AnalysisResources) -> Result<Analyzer, ConfigError> {
+        let limit = Box::new(LongTermFilter::new(
+            self.term_limit,
+            resources.long_term_counters().unwrap_or_default(),
+        ));
+        if self.tokenizer.ngram_tokenizer().is_some() || self.tokenizer.is_keyword() {
+            return Ok(Analyzer::new(self.tokenizer.build(), vec![limit]));
+        }
+        let mut filters: Vec<Box<dyn TokenFilter>> = Vec::new();
+        if let Some(options) = self.word_delimiter {
+            filters.push(Box::new(WordDelimiterFilter(options)));
+        }
+        if self.tokenizer == TokenizerKind::Whitespace || self.word_delimiter.is_some() {
+            filters.push(Box::new(LowercaseFilter));
+        }
+        filters.push(limit);
+
+        let stopwords = match &self.stopwords {
+            Some(words) => Arc::new(StopWords::from_words(words)),
+            None => self
+                .language
+                .as_deref()
+                .and_then(|language| resources.language_stopwords(language))
+                .unwrap_or_else(|| resources.index_stopwords()),
+        };
+        if !stopwords.is_empty() {
+            filters.push(Box::new(StopWordsFilter(stopwords)));
+        }
+        if let Some(set) = &self.synonyms {
+            let filter = resources
+                .synonyms(set)
+                .ok_or_else(|| ConfigError::UnknownSynonymSet(set.clone()))?;
+            filters.push(filter);
+        }
+        if self.stem
+            && let Some(stemmer) = resources.stemmer(self.language.as_deref())
+        {
+            filters.push(Box::new(StemFilter(stemmer)));
+        }
+        if let Some(matcher) = &self.phonetic {
+            let encoder = resources
+                .phonetic(matcher)
+                .ok_or_else(|| ConfigError::UnknownPhoneticMatcher(matcher.clone()))?;
+            filters.push(Box::new(PhoneticFilter(encoder)));
+        }
+        Ok(Analyzer::new(self.tokenizer.build(), filters))
+    }
+}
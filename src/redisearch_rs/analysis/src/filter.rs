@@ -0,0 +1,92 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Token filters, transforming the output of a tokenizer.
+
+use std::sync::Arc;
+
+use crate::{StopWords, Token};
+
+/// Transforms a stream of tokens.
+pub trait TokenFilter: Send + Sync {
+    fn apply(&self, tokens: Vec<Token>) -> Vec<Token>;
+}
+
+/// Removes stopwords. Positions are kept, so that the gaps still count
+/// towards the slop of phrase queries.
+pub struct StopWordsFilter(pub Arc<StopWords>);
+
+impl TokenFilter for StopWordsFilter {
+    fn apply(&self, mut tokens: Vec<Token>) -> Vec<Token> {
+        tokens.retain(|t| t.variant || !self.0.contains(&t.term));
+        tokens
+    }
+}
+
+/// Reduces a term to its stem. Implemented by the Snowball stemmers on the C
+/// side.
+pub trait Stemmer: Send + Sync {
+    /// The stem of `term`, or `None` if it's the term itself.
+    fn stem(&self, term: &str) -> Option<String>;
+}
+
+/// The prefix of stems in the index, `STEM_PREFIX` in C, so that they don't
+/// match the identical unstemmed term when searching verbatim.
+pub const STEM_PREFIX: char = '+';
+
+/// Adds the stem of every term as a variant.
+pub struct StemFilter(pub Arc<dyn Stemmer>);
+
+impl TokenFilter for StemFilter {
+    fn apply(&self, tokens: Vec<Token>) -> Vec<Token> {
+        let mut out = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let stem = (!token.variant)
+                .then(|| self.0.stem(&token.term))
+                .flatten()
+                .filter(|stem| *stem != token.term);
+            let variant = stem.map(|stem| token.variant(format!("{STEM_PREFIX}{stem}")));
+            out.push(token);
+            out.extend(variant);
+        }
+        out
+    }
+}
+
+/// Encodes a term phonetically, e.g. with Double Metaphone.
+pub trait PhoneticEncoder: Send + Sync {
+    /// The phonetic codes of `term`.
+    fn encode(&self, term: &str) -> Vec<String>;
+}
+
+/// The prefix of phonetic codes in the index, `PHONETIC_PREFIX` in C.
+pub const PHONETIC_PREFIX: char = '<';
+
+/// Adds the phonetic codes of every term as variants.
+pub struct PhoneticFilter(pub Arc<dyn PhoneticEncoder>);
+
+impl TokenFilter for PhoneticFilter {
+    fn apply(&self, tokens: Vec<Token>) -> Vec<Token> {
+        let mut out = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let codes = if token.variant {
+                Vec::new()
+            } else {
+                self.0.encode(&token.term)
+            };
+            let variants: Vec<_> = codes
+                .into_iter()
+                .map(|code| token.variant(format!("{PHONETIC_PREFIX}{code}")))
+                .collect();
+            out.push(token);
+            out.extend(variants);
+        }
+        out
+    }
+}
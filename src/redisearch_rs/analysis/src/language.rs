@@ -0,0 +1,335 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Language detection, choosing the stemmer and stopwords of documents that
+//! don't declare their language with `LANGUAGE_FIELD`.
+//!
+//! Scripts used by a single supported language (Greek, Cyrillic, Arabic,
+//! Devanagari...) identify it directly. Latin-script languages are told apart
+//! by an n-gram classifier: the most frequent character trigrams of the text
+//! are ranked, and compared with the profile of every language by the
+//! "out-of-place" distance of Cavnar and Trenkle. The built-in profiles are
+//! trained from samples of common words embedded in the binary, so detection
+//! needs no external service.
+//!
+//! Detection is unreliable on short texts: the detector abstains when the text
+//! is too short or the best two languages are too close, in which case the
+//! index language is used.
+
+use std::{collections::HashMap, sync::LazyLock};
+
+/// The number of trigrams kept in a profile.
+const PROFILE_SIZE: usize = 300;
+/// The minimum number of distinct trigrams needed to classify a text.
+const MIN_TRIGRAMS: usize = 20;
+/// The minimum relative margin between the best and second best languages.
+const MIN_MARGIN: f64 = 0.02;
+
+/// The name of the attribute recording the detected language of a document,
+/// loadable with `LOAD`.
+pub const DETECTED_LANGUAGE_ATTR: &str = "__language";
+
+/// Whether a character belongs to a script.
+type IsScript = fn(char) -> bool;
+
+/// The languages identified by their script alone.
+const SCRIPTS: &[(IsScript, &str)] = &[
+    (|c| ('\u{0370}'..='\u{03FF}').contains(&c), "greek"),
+    (|c| ('\u{0400}'..='\u{04FF}').contains(&c), "russian"),
+    (|c| ('\u{0530}'..='\u{058F}').contains(&c), "armenian"),
+    (|c| ('\u{0590}'..='\u{05FF}').contains(&c), "yiddish"),
+    (|c| ('\u{0600}'..='\u{06FF}').contains(&c), "arabic"),
+    (|c| ('\u{0900}'..='\u{097F}').contains(&c), "hindi"),
+    (|c| ('\u{0B80}'..='\u{0BFF}').contains(&c), "tamil"),
+    (|c| ('\u{4E00}'..='\u{9FFF}').contains(&c), "chinese"),
+];
+
+/// Samples of common words of the Latin-script languages.
+const SAMPLES: &[(&str, &str)] = &[
+    (
+        "english",
+        "the of and to in is that it was for on are with as they be at one have this from \
+         or had by word but what some we can out other were all there when up use your how \
+         said an each she which do their time if will way about many then them write would \
+         like so these her long make thing see him two has look more day could go come did \
+         number sound no most people my over know water than call first who may down side \
+         been now find any new work part take get place made live where after back little \
+         only round man year came show every good give our under name very through just form \
+         sentence great think say help low line differ turn cause much mean before move right \
+         boy old too same tell does set three want air well also play small end put home read \
+         hand port large spell add even land here must big high such follow act why ask men \
+         change went light kind off need house picture try again animal point mother world",
+    ),
+    (
+        "french",
+        "le de un être et à il avoir ne je son que se qui ce dans en du elle au pour pas vous \
+         par sur faire plus dire me on mon lui nous comme mais pouvoir avec tout y aller voir \
+         en bien où sans tu ou leur homme si deux mari moi vouloir te femme venir quand grand \
+         celui notre devoir là jour prendre même votre rien petit encore aussi quelque dont \
+         tout mer trouver donner temps ça peu même falloir sous parler alors sa chose ton \
+         mettre vie savoir yeux passer autre après regarder toujours puis jamais cela aimer \
+         non heure croire cent monde donc enfant fois seul autre entre vers chez demander \
+         jeune jusque très moment rester répondre tout tête père fille mille premier car \
+         entendre ni bon trois coeur an quatre terre contre dieu monsieur voix penser quel",
+    ),
+    (
+        "german",
+        "der die und in den von zu das mit sich des auf für ist im dem nicht ein die eine \
+         als auch es an werden aus er hat dass sie nach wird bei einer der um am sind noch \
+         wie einem über einen das so zum war haben nur oder aber vor zur bis mehr durch man \
+         sein wurde sei in prozent hatte kann gegen vom können schon wenn habe seine mark \
+         ihre dann unter wir soll ich eines es jahr zwei jahren diese dieser wieder keine \
+         uhr seiner worden und will zwischen immer millionen ein was sagte gibt alle diesen \
+         seit muss wurden beim doch jetzt waren drei jahre mit neue neuen damit bereits da \
+         auch ihr seinen müssen ab ihrer nun lassen sehr dort hier ganz kommen gehen nichts \
+         wollen geben machen stehen wissen werden sehen denken finden bleiben liegen heißen",
+    ),
+    (
+        "spanish",
+        "de la que el en y a los se del las un por con no una su para es al lo como más o \
+         pero sus le ha me si sin sobre este ya entre cuando todo esta ser son dos también \
+         fue había era muy años hasta desde está mi porque qué sólo han yo hay vez puede \
+         todos así nos ni parte tiene él uno donde bien tiempo mismo ese ahora cada e vida \
+         otro después te otros aunque esa eso hace otra gobierno tan durante siempre día \
+         tanto ella tres sí dijo sido gran país según menos año antes estado contra sino \
+         forma caso nada hacer general estaba poco estos presidente mayor ante unos les algo \
+         hacia casa ellos ayer hecho primera mucho mientras además quien momento millones \
+         esto españa hombre están pues hoy lugar madrid nacional trabajo otras mejor nuevo",
+    ),
+    (
+        "italian",
+        "di e il la che in a per un è non con una sono le si da del al lo ma come anche \
+         più della nel io ha dei mi alla ci questo se gli ne delle o cosa suo tutto nella \
+         sua loro hanno perché quando molto fare essere questa ancora tra due stato può \
+         solo così dove sempre lui noi voi fatto tutti proprio anni cui prima dopo quello \
+         altro ogni poi già bene qui mia parte tempo dalla volta casa stata vita quindi \
+         niente nulla oggi allora grande modo degli pure mentre senza fino sulla cosa \
+         lavoro perché questi quella uomo giorno mondo paese donna momento altri anno \
+         detto sotto nuovo ecco stesso mai tanto certo sulla molti meno stati forse invece \
+         dire vedere andare venire sapere dovere volere potere dare stare sentire pensare",
+    ),
+    (
+        "portuguese",
+        "de a o que e do da em um para é com não uma os no se na por mais as dos como mas \
+         foi ao ele das tem à seu sua ou ser quando muito há nos já está eu também só pelo \
+         pela até isso ela entre era depois sem mesmo aos ter seus quem nas me esse eles \
+         estão você tinha foram essa num nem suas meu às minha têm numa pelos elas havia \
+         seja qual será nós tenho lhe deles essas esses pelas este fosse dele tu te vocês \
+         vos lhes meus minhas teu tua teus tuas nosso nossa nossos nossas dela delas esta \
+         estes estas aquele aquela aqueles aquelas isto aquilo estou estamos estive esteve \
+         estivemos estiveram estava estávamos estavam então coisa ano anos dia dias vez \
+         trabalho governo cidade país casa vida tempo homem mulher filho agora ainda sempre",
+    ),
+    (
+        "dutch",
+        "de en van ik te dat die in een hij het niet zijn is was op aan met als voor had er \
+         maar om hem dan zou of wat mijn men dit zo door over ze zich bij ook tot je mij uit \
+         der daar haar naar heb hoe heeft hebben deze u want nog zal me zij nu ge geen omdat \
+         iets worden toch al waren veel meer doen toen moet ben zonder kan hun dus alles \
+         onder ja eens hier wie werd altijd doch wordt wezen kunnen ons zelf tegen na reeds \
+         wil kon niets uw iemand geweest andere jaar jaren mensen twee drie nieuwe grote \
+         goed gaan komen zien maken zeggen weten staan laten geven vinden denken houden \
+         kijken werken nemen blijven lopen spelen vragen krijgen zitten liggen huis kind \
+         water stad land werk dag tijd weg hand leven vrouw man vader moeder school",
+    ),
+];
+
+/// The trigrams of `text`, with their counts. Words are lowercased and padded
+/// with spaces, so that trigrams capture word starts and ends.
+fn trigrams(text: &str) -> HashMap<[char; 3], u32> {
+    let mut counts = HashMap::new();
+    for word in text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+    {
+        let chars: Vec<char> = std::iter::once(' ')
+            .chain(word.chars().flat_map(char::to_lowercase))
+            .chain(std::iter::once(' '))
+            .collect();
+        for window in chars.windows(3) {
+            *counts.entry([window[0], window[1], window[2]]).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// The most frequent trigrams of `text`, by decreasing frequency.
+fn ranked(text: &str) -> Vec<[char; 3]> {
+    let mut trigrams: Vec<_> = trigrams(text).into_iter().collect();
+    // Break ties on the trigram itself, for determinism.
+    trigrams.sort_unstable_by(|(a, na), (b, nb)| nb.cmp(na).then(a.cmp(b)));
+    trigrams.truncate(PROFILE_SIZE);
+    trigrams.into_iter().map(|(t, _)| t).collect()
+}
+
+/// The trigram ranks of a language.
+#[derive(Debug, Clone)]
+struct Profile {
+    language: String,
+    ranks: HashMap<[char; 3], usize>,
+}
+
+impl Profile {
+    fn new(language: &str, sample: &str) -> Self {
+        let ranks = ranked(sample)
+            .into_iter()
+            .enumerate()
+            .map(|(rank, t)| (t, rank))
+            .collect();
+        Self {
+            language: language.to_owned(),
+            ranks,
+        }
+    }
+
+    /// The out-of-place distance of a text's ranked trigrams to this profile.
+    fn distance(&self, trigrams: &[[char; 3]]) -> usize {
+        trigrams
+            .iter()
+            .enumerate()
+            .map(|(rank, t)| {
+                self.ranks
+                    .get(t)
+                    .map_or(PROFILE_SIZE, |&r| r.abs_diff(rank))
+            })
+            .sum()
+    }
+}
+
+/// A detected language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    /// The name of the language, as accepted by `LANGUAGE`.
+    pub language: String,
+    /// How much closer the text is to this language than to the next best
+    /// one, between 0 and 1. Always 1 for languages detected by their script.
+    pub confidence: f64,
+}
+
+/// See the [module documentation](self).
+#[derive(Debug, Clone, Default)]
+pub struct LanguageDetector {
+    profiles: Vec<Profile>,
+}
+
+static BUILTIN: LazyLock<LanguageDetector> = LazyLock::new(|| {
+    let mut detector = LanguageDetector::default();
+    for (language, sample) in SAMPLES {
+        detector.add_profile(language, sample);
+    }
+    detector
+});
+
+impl LanguageDetector {
+    /// The detector with the built-in profiles.
+    pub fn builtin() -> &'static Self {
+        &BUILTIN
+    }
+
+    /// Add, or replace, the profile of `language`, trained from `sample`.
+    pub fn add_profile(&mut self, language: &str, sample: &str) {
+        self.profiles.retain(|p| p.language != language);
+        self.profiles.push(Profile::new(language, sample));
+    }
+
+    /// The languages of the profiles.
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.profiles.iter().map(|p| p.language.as_str())
+    }
+
+    /// The language of `text`, or `None` if it can't be told reliably.
+    pub fn detect(&self, text: &str) -> Option<Detection> {
+        if let Some(language) = script_language(text) {
+            return Some(Detection {
+                language: language.to_owned(),
+                confidence: 1.0,
+            });
+        }
+        let trigrams = ranked(text);
+        if trigrams.len() < MIN_TRIGRAMS {
+            return None;
+        }
+        let mut distances: Vec<_> = self
+            .profiles
+            .iter()
+            .map(|p| (p.distance(&trigrams), p))
+            .collect();
+        distances.sort_unstable_by_key(|(d, _)| *d);
+        let (best, profile) = distances.first()?;
+        let confidence = match distances.get(1) {
+            Some((second, _)) => (second - best) as f64 / *second as f64,
+            None => 1.0,
+        };
+        (confidence >= MIN_MARGIN).then(|| Detection {
+            language: profile.language.clone(),
+            confidence,
+        })
+    }
+}
+
+/// The language identified by the script of most letters of `text`, if any.
+fn script_language(text: &str) -> Option<&'static str> {
+    let mut counts = [0usize; SCRIPTS.len()];
+    let mut letters = 0;
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        letters += 1;
+        if let Some(i) = SCRIPTS.iter().position(|(is, _)| is(c)) {
+            counts[i] += 1;
+        }
+    }
+    let (i, &count) = counts.iter().enumerate().max_by_key(|(_, n)| **n)?;
+    (count > 0 && count * 2 > letters).then_some(SCRIPTS[i].1)
+}
+
+/// How the language of a document was chosen.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DocumentLanguage {
+    /// Given by the document's `LANGUAGE_FIELD`.
+    Declared(String),
+    /// Detected from the document's text.
+    Detected(Detection),
+    /// The index language, because detection is disabled or failed.
+    Default,
+}
+
+impl DocumentLanguage {
+    /// Choose the language of a document: the `declared` one if any, else the
+    /// one detected from its `text` by `detector`, if enabled.
+    pub fn resolve(
+        declared: Option<&str>,
+        detector: Option<&LanguageDetector>,
+        text: &str,
+    ) -> Self {
+        if let Some(language) = declared {
+            return Self::Declared(language.to_ascii_lowercase());
+        }
+        detector
+            .and_then(|d| d.detect(text))
+            .map_or(Self::Default, Self::Detected)
+    }
+
+    /// The language to analyze the document with, `None` meaning the index
+    /// language.
+    pub fn language(&self) -> Option<&str> {
+        match self {
+            Self::Declared(language) => Some(language),
+            Self::Detected(detection) => Some(&detection.language),
+            Self::Default => None,
+        }
+    }
+
+    /// The value of the [`DETECTED_LANGUAGE_ATTR`] attribute, set only for
+    /// detected languages.
+    pub fn detected_attr(&self) -> Option<&str> {
+        match self {
+            Self::Detected(detection) => Some(&detection.language),
+            _ => None,
+        }
+    }
+}
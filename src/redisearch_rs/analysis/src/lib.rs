@@ -0,0 +1,94 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Text analysis: turning the value of a TEXT field, or the terms of a query,
+//! into index terms.
+//!
+//! An [`Analyzer`] is a [`Tokenizer`] followed by a chain of [`TokenFilter`]s.
+//! Each TEXT field may declare its own chain with an [`AnalyzerConfig`]; the
+//! same chain must then be applied to the query terms targeting that field,
+//! so that they match what was indexed.
+
+pub mod config;
+pub mod filter;
+pub mod language;
+pub mod long_terms;
+pub mod ngram;
+pub mod stopwords;
+pub mod tokenizer;
+pub mod word_delimiter;
+
+use std::ops::Range;
+
+pub use config::{AnalysisResources, AnalyzerConfig, TokenizerKind};
+pub use filter::TokenFilter;
+pub use long_terms::{LongTermPolicy, TermLimit};
+pub use stopwords::StopWords;
+pub use tokenizer::Tokenizer;
+
+/// A term produced by analysis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// The term, as indexed.
+    pub term: String,
+    /// The position of the term in the field, starting at 1. Variants of a
+    /// term (stems, synonyms...) share its position.
+    pub position: u32,
+    /// The bytes of the source text the term comes from, for highlighting.
+    pub offset: Range<usize>,
+    /// Whether the term is a variant added by a filter, rather than a term of
+    /// the source text.
+    pub variant: bool,
+}
+
+impl Token {
+    pub fn new(term: impl Into<String>, position: u32, offset: Range<usize>) -> Self {
+        Self {
+            term: term.into(),
+            position,
+            offset,
+            variant: false,
+        }
+    }
+
+    /// A variant of `self` with the given term.
+    pub fn variant(&self, term: impl Into<String>) -> Self {
+        Self {
+            term: term.into(),
+            position: self.position,
+            offset: self.offset.clone(),
+            variant: true,
+        }
+    }
+}
+
+/// A tokenizer followed by a chain of filters.
+pub struct Analyzer {
+    tokenizer: Box<dyn Tokenizer>,
+    filters: Vec<Box<dyn TokenFilter>>,
+}
+
+impl Analyzer {
+    pub fn new(tokenizer: Box<dyn Tokenizer>, filters: Vec<Box<dyn TokenFilter>>) -> Self {
+        Self { tokenizer, filters }
+    }
+
+    /// The grams emitted so far, if the tokenizer is an n-gram tokenizer.
+    pub fn ngram_stats(&self) -> Option<ngram::NGramStats> {
+        self.tokenizer.ngram_stats()
+    }
+
+    /// Analyze `text`.
+    pub fn analyze(&self, text: &str) -> Vec<Token> {
+        let tokens = self.tokenizer.tokenize(text);
+        self.filters
+            .iter()
+            .fold(tokens, |tokens, filter| filter.apply(tokens))
+    }
+}
@@ -0,0 +1,192 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Handling of terms longer than a field accepts.
+//!
+//! Log lines, encoded blobs and minified code contain "terms" of up to
+//! megabytes, which bloat the terms trie and are never searched whole. The C
+//! tokenizer only bounds terms at `MAX_NORMALIZE_SIZE` on a dead code path,
+//! and otherwise indexes them as they are.
+//!
+//! A [`LongTermFilter`] bounds the terms of a field at [`TermLimit::max_len`]
+//! bytes, handling the longer ones by a [`LongTermPolicy`]:
+//!
+//! - `TRUNCATE` indexes their longest prefix fitting in the limit, cut on a
+//!   character boundary. Any term sharing that prefix then matches them;
+//! - `DROP` doesn't index them, leaving a gap in the positions as stopwords do;
+//! - `HASH` indexes a fixed-size hash of them, prefixed with
+//!   [`HASHED_PREFIX`], so that searching for the whole term still matches
+//!   exactly.
+//!
+//! Query terms go through the same chain as the indexed ones, so they are
+//! handled likewise.
+//!
+//! The over-long terms are counted in [`LongTermCounters`], which the index
+//! shares with its analysis chains through
+//! [`AnalysisResources::long_term_counters`](crate::AnalysisResources::long_term_counters)
+//! and reports in `FT.INFO`.
+
+use std::{
+    hash::Hasher,
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use fnv::Fnv64;
+
+use crate::{Token, TokenFilter};
+
+/// The default limit, `MAX_NORMALIZE_SIZE` in C.
+pub const DEFAULT_MAX_TERM_LEN: usize = 128;
+
+/// The prefix of the hashes of over-long terms in the index, so that they
+/// don't match a term of the same text.
+pub const HASHED_PREFIX: char = '#';
+
+/// What to do with the terms over the limit (`LONGTERMS {policy}`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LongTermPolicy {
+    #[default]
+    Truncate,
+    Drop,
+    Hash,
+}
+
+impl LongTermPolicy {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Truncate => "TRUNCATE",
+            Self::Drop => "DROP",
+            Self::Hash => "HASH",
+        }
+    }
+}
+
+impl FromStr for LongTermPolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        [Self::Truncate, Self::Drop, Self::Hash]
+            .into_iter()
+            .find(|policy| s.eq_ignore_ascii_case(policy.name()))
+            .ok_or(())
+    }
+}
+
+/// The longest term of a field, and what to do with the longer ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TermLimit {
+    /// The longest term indexed as is, in bytes (`MAXTERMLEN {n}`).
+    pub max_len: usize,
+    pub policy: LongTermPolicy,
+}
+
+impl Default for TermLimit {
+    fn default() -> Self {
+        Self {
+            max_len: DEFAULT_MAX_TERM_LEN,
+            policy: LongTermPolicy::default(),
+        }
+    }
+}
+
+/// How many over-long terms were found, by policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LongTermStats {
+    pub truncated: u64,
+    pub dropped: u64,
+    pub hashed: u64,
+}
+
+impl LongTermStats {
+    /// The over-long terms found, whatever was done with them.
+    pub const fn total(&self) -> u64 {
+        self.truncated + self.dropped + self.hashed
+    }
+}
+
+/// The counters behind [`LongTermStats`], shared by the chains of an index.
+#[derive(Debug, Default)]
+pub struct LongTermCounters {
+    truncated: AtomicU64,
+    dropped: AtomicU64,
+    hashed: AtomicU64,
+}
+
+impl LongTermCounters {
+    /// The over-long terms found so far.
+    pub fn stats(&self) -> LongTermStats {
+        LongTermStats {
+            truncated: self.truncated.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            hashed: self.hashed.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record(&self, policy: LongTermPolicy) {
+        let counter = match policy {
+            LongTermPolicy::Truncate => &self.truncated,
+            LongTermPolicy::Drop => &self.dropped,
+            LongTermPolicy::Hash => &self.hashed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// See the [module documentation](self).
+pub struct LongTermFilter {
+    limit: TermLimit,
+    counters: Arc<LongTermCounters>,
+}
+
+impl LongTermFilter {
+    pub const fn new(limit: TermLimit, counters: Arc<LongTermCounters>) -> Self {
+        Self { limit, counters }
+    }
+}
+
+impl TokenFilter for LongTermFilter {
+    fn apply(&self, mut tokens: Vec<Token>) -> Vec<Token> {
+        let max_len = self.limit.max_len;
+        tokens.retain_mut(|token| {
+            if token.term.len() <= max_len {
+                return true;
+            }
+            self.counters.record(self.limit.policy);
+            match self.limit.policy {
+                LongTermPolicy::Truncate => {
+                    let end = (0..=max_len)
+                        .rev()
+                        .find(|&i| token.term.is_char_boundary(i))
+                        .unwrap_or(0);
+                    token.term.truncate(end);
+                    !token.term.is_empty()
+                }
+                LongTermPolicy::Drop => false,
+                LongTermPolicy::Hash => {
+                    token.term = hashed_term(&token.term);
+                    true
+                }
+            }
+        });
+        tokens
+    }
+}
+
+/// The term indexed for the over-long `term` by [`LongTermPolicy::Hash`]: its
+/// 64-bit FNV-1a hash in hexadecimal, after [`HASHED_PREFIX`]. It's always 17
+/// bytes long, whatever the limit.
+pub fn hashed_term(term: &str) -> String {
+    let mut hasher = Fnv64::default();
+    hasher.write(term.as_bytes());
+    format!("{HASHED_PREFIX}{:016x}", hasher.finish())
+}
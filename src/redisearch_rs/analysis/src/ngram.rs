@@ -0,0 +1,179 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! N-gram tokenizers, enabling substring search without scanning the terms of
+//! the index.
+//!
+//! The n-gram tokenizer indexes every substring of every word whose length is
+//! within `min..=max` characters; the edge n-gram tokenizer only indexes the
+//! prefixes of that length. Words shorter than `min` are indexed whole.
+//!
+//! At query time, [`NGramTokenizer::query`] maps a searched substring to the
+//! grams to look up. Substrings longer than `max` are looked up through
+//! several grams, which don't guarantee a match: the candidates must be
+//! [verified](NGramTokenizer::verify) against the field value.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use crate::{
+    Token,
+    tokenizer::{StandardTokenizer, Tokenizer},
+};
+
+/// How many grams an n-gram field produces, reported by `FT.INFO` so users
+/// can see the cost of the field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NGramStats {
+    /// The words tokenized.
+    pub words: u64,
+    /// The grams emitted for them.
+    pub grams: u64,
+    /// The total size of the grams, in bytes.
+    pub gram_bytes: u64,
+}
+
+impl NGramStats {
+    /// The average number of grams per word.
+    pub fn grams_per_word(&self) -> f64 {
+        if self.words == 0 {
+            0.0
+        } else {
+            self.grams as f64 / self.words as f64
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    words: AtomicU64,
+    grams: AtomicU64,
+    gram_bytes: AtomicU64,
+}
+
+/// How to look up a substring in an n-gram field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GramQuery {
+    /// The substring is a gram: its postings are exactly the matches.
+    Exact(String),
+    /// The documents containing all the grams are candidates, to be verified.
+    Verify { grams: Vec<String> },
+    /// The substring is shorter than the smallest gram: the terms must be
+    /// scanned, as for a wildcard query.
+    Scan,
+}
+
+/// See the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct NGramTokenizer {
+    min: usize,
+    max: usize,
+    edge: bool,
+    counters: Arc<Counters>,
+}
+
+impl NGramTokenizer {
+    /// A tokenizer of grams of `min..=max` characters, only prefixes if `edge`.
+    pub fn new(min: usize, max: usize, edge: bool) -> Self {
+        assert!(0 < min && min <= max, "invalid gram range {min}..={max}");
+        Self {
+            min,
+            max,
+            edge,
+            counters: Arc::default(),
+        }
+    }
+
+    /// The grams indexed so far.
+    pub fn stats(&self) -> NGramStats {
+        NGramStats {
+            words: self.counters.words.load(Ordering::Relaxed),
+            grams: self.counters.grams.load(Ordering::Relaxed),
+            gram_bytes: self.counters.gram_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// How to look up the lowercased `substring`.
+    pub fn query(&self, substring: &str) -> GramQuery {
+        let chars: Vec<char> = substring.chars().collect();
+        let len = chars.len();
+        if len < self.min {
+            GramQuery::Scan
+        } else if len <= self.max {
+            GramQuery::Exact(substring.to_owned())
+        } else if self.edge {
+            GramQuery::Verify {
+                grams: vec![chars[..self.max].iter().collect()],
+            }
+        } else {
+            // Grams of the maximal length covering the substring.
+            let mut starts: Vec<_> = (0..=len - self.max).step_by(self.max).collect();
+            if starts.last() != Some(&(len - self.max)) {
+                starts.push(len - self.max);
+            }
+            let grams = starts
+                .into_iter()
+                .map(|start| chars[start..start + self.max].iter().collect())
+                .collect();
+            GramQuery::Verify { grams }
+        }
+    }
+
+    /// Whether `text` actually contains the lowercased `substring`: anywhere
+    /// in a word, or at the start of a word for edge n-grams.
+    pub fn verify(&self, substring: &str, text: &str) -> bool {
+        StandardTokenizer.tokenize(text).iter().any(|word| {
+            if self.edge {
+                word.term.starts_with(substring)
+            } else {
+                word.term.contains(substring)
+            }
+        })
+    }
+}
+
+impl Tokenizer for NGramTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        let words = StandardTokenizer.tokenize(text);
+        let mut out = Vec::new();
+        for word in &words {
+            let chars: Vec<char> = word.term.chars().collect();
+            let len = chars.len();
+            let before = out.len();
+            if len < self.min {
+                out.push(word.clone());
+            } else {
+                let starts = if self.edge { 0..1 } else { 0..len };
+                for start in starts {
+                    for n in self.min..=self.max.min(len - start) {
+                        let gram: String = chars[start..start + n].iter().collect();
+                        out.push(Token::new(gram, word.position, word.offset.clone()));
+                    }
+                }
+            }
+            let bytes: usize = out[before..].iter().map(|t| t.term.len()).sum();
+            self.counters
+                .grams
+                .fetch_add((out.len() - before) as u64, Ordering::Relaxed);
+            self.counters
+                .gram_bytes
+                .fetch_add(bytes as u64, Ordering::Relaxed);
+        }
+        self.counters
+            .words
+            .fetch_add(words.len() as u64, Ordering::Relaxed);
+        out
+    }
+
+    fn ngram_stats(&self) -> Option<NGramStats> {
+        Some(self.stats())
+    }
+}
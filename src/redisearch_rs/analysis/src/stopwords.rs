@@ -0,0 +1,67 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Stopword lists.
+
+use std::collections::HashSet;
+
+/// The stopwords used when an index doesn't declare its own, `DEFAULT_STOPWORDS`
+/// in `stopwords.h`.
+pub const DEFAULT_STOPWORDS: &[&str] = &[
+    "a", "is", "the", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in", "into",
+    "it", "no", "not", "of", "on", "or", "such", "that", "their", "then", "there", "these", "they",
+    "this", "to", "was", "will", "with",
+];
+
+/// A set of terms that aren't indexed. Terms are stored lowercased.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StopWords {
+    words: HashSet<String>,
+}
+
+impl StopWords {
+    /// An empty list, disabling stopword filtering.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// The [`DEFAULT_STOPWORDS`].
+    pub fn default_list() -> Self {
+        Self::from_words(DEFAULT_STOPWORDS.iter().copied())
+    }
+
+    pub fn from_words<S: AsRef<str>>(words: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            words: words
+                .into_iter()
+                .map(|w| w.as_ref().to_lowercase())
+                .collect(),
+        }
+    }
+
+    /// Whether `term`, which must already be lowercased, is a stopword.
+    pub fn contains(&self, term: &str) -> bool {
+        self.words.contains(term)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// The stopwords, sorted.
+    pub fn sorted(&self) -> Vec<&str> {
+        let mut words: Vec<_> = self.words.iter().map(String::as_str).collect();
+        words.sort_unstable();
+        words
+    }
+}
@@ -0,0 +1,162 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Tokenizers, splitting text into terms.
+
+use crate::{Token, ngram::NGramStats};
+
+/// Splits text into [`Token`]s.
+pub trait Tokenizer: Send + Sync {
+    fn tokenize(&self, text: &str) -> Vec<Token>;
+
+    /// The grams emitted so far, for n-gram tokenizers.
+    fn ngram_stats(&self) -> Option<NGramStats> {
+        None
+    }
+}
+
+/// Whether `c` separates tokens, mirroring `ToksepMap_g` in `toksep.h`.
+pub const fn is_separator(c: char) -> bool {
+    matches!(
+        c,
+        ' ' | '\t'
+            | '\n'
+            | '\r'
+            | ','
+            | '.'
+            | '/'
+            | '('
+            | ')'
+            | '{'
+            | '}'
+            | '['
+            | ']'
+            | ':'
+            | ';'
+            | '~'
+            | '!'
+            | '@'
+            | '#'
+            | '$'
+            | '%'
+            | '^'
+            | '&'
+            | '*'
+            | '-'
+            | '='
+            | '+'
+            | '|'
+            | '\''
+            | '`'
+            | '"'
+            | '<'
+            | '>'
+            | '?'
+    )
+}
+
+/// The default tokenizer: splits on [separators](is_separator), unless escaped
+/// with a backslash, and lowercases the terms.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StandardTokenizer;
+
+impl Tokenizer for StandardTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        // Terms are built in a scratch buffer, then copied into a string of
+        // the exact length.
+        scratch::with_bytes(|term| {
+            let mut tokens = Vec::new();
+            let mut start = 0;
+            let mut escaped = false;
+            let mut flush = |term: &mut Vec<u8>, start: usize, end: usize| {
+                if !term.is_empty() {
+                    let position = tokens.len() as u32 + 1;
+                    let lowered = std::str::from_utf8(term).expect("built from chars");
+                    tokens.push(Token::new(lowered, position, start..end));
+                    term.clear();
+                }
+            };
+            for (i, c) in text.char_indices() {
+                if escaped {
+                    escaped = false;
+                    push_lowercase(term, c);
+                } else if c == '\\' {
+                    if term.is_empty() {
+                        start = i;
+                    }
+                    escaped = true;
+                } else if is_separator(c) {
+                    flush(term, start, i);
+                } else {
+                    if term.is_empty() {
+                        start = i;
+                    }
+                    push_lowercase(term, c);
+                }
+            }
+            flush(term, start, text.len());
+            tokens
+        })
+    }
+}
+
+/// Append the lowercase of `c` to `term`.
+fn push_lowercase(term: &mut Vec<u8>, c: char) {
+    for c in c.to_lowercase() {
+        term.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+}
+
+/// Splits on whitespace only, preserving case and punctuation for the filters
+/// that need them, such as the [word-delimiter filter](crate::word_delimiter).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WhitespaceTokenizer;
+
+impl Tokenizer for WhitespaceTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut start = None;
+        for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+            match (start, c.is_whitespace()) {
+                (None, false) => start = Some(i),
+                (Some(s), true) => {
+                    let position = tokens.len() as u32 + 1;
+                    tokens.push(Token::new(&text[s..i], position, s..i));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        tokens
+    }
+}
+
+/// Indexes the whole value as a single term, for identifiers that must not be
+/// split but should still be ranked and highlighted like text.
+#[derive(Debug, Clone, Copy)]
+pub struct KeywordTokenizer {
+    /// Lowercase the value.
+    pub case_fold: bool,
+}
+
+impl Tokenizer for KeywordTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return Vec::new();
+        }
+        let start = text.len() - text.trim_start().len();
+        let term = if self.case_fold {
+            trimmed.to_lowercase()
+        } else {
+            trimmed.to_owned()
+        };
+        vec![Token::new(term, 1, start..start + trimmed.len())]
+    }
+}
@@ -0,0 +1,269 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The word-delimiter filter, splitting technical terms such as product names,
+//! SKUs and identifiers into their parts.
+//!
+//! A term is split:
+//!
+//! - on every character that's not alphanumeric (`wi-fi` → `wi`, `fi`;
+//!   `C++` → `C`);
+//! - on case changes (`PowerShot` → `Power`, `Shot`; `XMLHttp` → `XML`, `Http`);
+//! - between letters and digits (`SD500` → `SD`, `500`).
+//!
+//! The parts get consecutive positions, shifting the following terms. The
+//! original term and the concatenations of the parts can be added as variants,
+//! at the position of the first part, so that `wifi`, `wi-fi` and `wi fi` all
+//! match.
+//!
+//! Case changes can only be detected if the tokenizer preserves case, as the
+//! whitespace tokenizer does: the filter is then followed by lowercasing.
+
+use crate::{Token, TokenFilter};
+
+/// The options of the filter (`WORDDELIMITER {n} {option}...`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WordDelimiterOptions {
+    /// Split on case changes (disabled by `NOCASESPLIT`).
+    pub split_on_case_change: bool,
+    /// Split between letters and digits (disabled by `NONUMERICSPLIT`).
+    pub split_on_numerics: bool,
+    /// Keep the original term (`PRESERVEORIGINAL`).
+    pub preserve_original: bool,
+    /// Add the concatenation of consecutive letter parts (`CATENATEWORDS`).
+    pub catenate_words: bool,
+    /// Add the concatenation of consecutive digit parts (`CATENATENUMBERS`).
+    pub catenate_numbers: bool,
+    /// Add the concatenation of all the parts (`CATENATEALL`).
+    pub catenate_all: bool,
+}
+
+impl Default for WordDelimiterOptions {
+    fn default() -> Self {
+        Self {
+            split_on_case_change: true,
+            split_on_numerics: true,
+            preserve_original: false,
+            catenate_words: false,
+            catenate_numbers: false,
+            catenate_all: false,
+        }
+    }
+}
+
+impl WordDelimiterOptions {
+    const FLAGS: [&str; 6] = [
+        "NOCASESPLIT",
+        "NONUMERICSPLIT",
+        "PRESERVEORIGINAL",
+        "CATENATEWORDS",
+        "CATENATENUMBERS",
+        "CATENATEALL",
+    ];
+
+    /// Apply the option `name`. Returns `false` if it's unknown.
+    pub fn set(&mut self, name: &str) -> bool {
+        let Some(flag) = Self::FLAGS
+            .iter()
+            .position(|f| f.eq_ignore_ascii_case(name))
+        else {
+            return false;
+        };
+        match flag {
+            0 => self.split_on_case_change = false,
+            1 => self.split_on_numerics = false,
+            2 => self.preserve_original = true,
+            3 => self.catenate_words = true,
+            4 => self.catenate_numbers = true,
+            _ => self.catenate_all = true,
+        }
+        true
+    }
+
+    /// The options differing from the defaults, as they would be written on
+    /// `FT.CREATE`.
+    pub fn names(&self) -> Vec<&'static str> {
+        let set = [
+            !self.split_on_case_change,
+            !self.split_on_numerics,
+            self.preserve_original,
+            self.catenate_words,
+            self.catenate_numbers,
+            self.catenate_all,
+        ];
+        Self::FLAGS
+            .iter()
+            .zip(set)
+            .filter_map(|(name, set)| set.then_some(*name))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Lower,
+    Upper,
+    Digit,
+    Delimiter,
+}
+
+impl Class {
+    fn of(c: char) -> Self {
+        if c.is_numeric() {
+            Self::Digit
+        } else if c.is_uppercase() {
+            Self::Upper
+        } else if c.is_alphabetic() {
+            Self::Lower
+        } else {
+            Self::Delimiter
+        }
+    }
+}
+
+/// A part of a term: its byte range within the term, and whether it's made of
+/// digits.
+type Part = (std::ops::Range<usize>, bool);
+
+/// See the [module documentation](self).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WordDelimiterFilter(pub WordDelimiterOptions);
+
+impl WordDelimiterFilter {
+    fn split(&self, term: &str) -> Vec<Part> {
+        let chars: Vec<(usize, char)> = term.char_indices().collect();
+        let mut parts = Vec::new();
+        let mut start: Option<usize> = None;
+        for (i, &(at, c)) in chars.iter().enumerate() {
+            let class = Class::of(c);
+            if class == Class::Delimiter {
+                if let Some(s) = start.take() {
+                    parts.push(s..at);
+                }
+                continue;
+            }
+            if let Some(s) = start {
+                let prev = Class::of(chars[i - 1].1);
+                let next = chars.get(i + 1).map(|(_, c)| Class::of(*c));
+                let numeric_change =
+                    self.0.split_on_numerics && (prev == Class::Digit) != (class == Class::Digit);
+                let case_change = self.0.split_on_case_change
+                    && class == Class::Upper
+                    && (prev == Class::Lower
+                        || (prev == Class::Upper && next == Some(Class::Lower)));
+                if numeric_change || case_change {
+                    parts.push(s..at);
+                    start = Some(at);
+                }
+            } else {
+                start = Some(at);
+            }
+        }
+        if let Some(s) = start {
+            parts.push(s..term.len());
+        }
+        parts
+            .into_iter()
+            .map(|range| {
+                let digits = term[range.clone()].chars().all(char::is_numeric);
+                (range, digits)
+            })
+            .collect()
+    }
+
+    /// The concatenations of the parts requested by the options.
+    fn catenations(&self, term: &str, parts: &[Part]) -> Vec<String> {
+        let mut out = Vec::new();
+        let mut run = |digits: bool| {
+            for run in parts.chunk_by(|a, b| a.1 == b.1) {
+                if run[0].1 == digits && run.len() > 1 {
+                    out.push(run.iter().map(|(r, _)| &term[r.clone()]).collect());
+                }
+            }
+        };
+        if self.0.catenate_words {
+            run(false);
+        }
+        if self.0.catenate_numbers {
+            run(true);
+        }
+        if self.0.catenate_all && parts.len() > 1 {
+            let all: String = parts.iter().map(|(r, _)| &term[r.clone()]).collect();
+            if !out.contains(&all) {
+                out.push(all);
+            }
+        }
+        out
+    }
+}
+
+impl TokenFilter for WordDelimiterFilter {
+    fn apply(&self, tokens: Vec<Token>) -> Vec<Token> {
+        let mut out = Vec::with_capacity(tokens.len());
+        // How much the positions of the following tokens are shifted by the
+        // parts of the previous ones.
+        let mut shift = 0;
+        // The shift of the last source token, applying to its variants.
+        let mut source_shift = 0;
+        for mut token in tokens {
+            if token.variant {
+                token.position += source_shift;
+                out.push(token);
+                continue;
+            }
+            source_shift = shift;
+            let parts = self.split(&token.term);
+            let keep_offsets = token.term.len() == token.offset.len();
+            let base = token.position + shift;
+            let first = out.len();
+            for (i, (range, _)) in parts.iter().enumerate() {
+                let offset = if keep_offsets {
+                    token.offset.start + range.start..token.offset.start + range.end
+                } else {
+                    token.offset.clone()
+                };
+                out.push(Token::new(
+                    &token.term[range.clone()],
+                    base + i as u32,
+                    offset,
+                ));
+            }
+            let Some(head) = out.get(first).cloned() else {
+                // Only delimiters: nothing to index.
+                continue;
+            };
+            let original = (self.0.preserve_original
+                && (parts.len() > 1 || head.term != token.term))
+                .then(|| head.variant(token.term.clone()));
+            let catenations = self
+                .catenations(&token.term, &parts)
+                .into_iter()
+                .map(|term| head.variant(term));
+            let variants: Vec<_> = original.into_iter().chain(catenations).collect();
+            out.splice(first + 1..first + 1, variants);
+            shift += parts.len() as u32 - 1;
+        }
+        out
+    }
+}
+
+/// Lowercases the terms, after filters that need their case.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LowercaseFilter;
+
+impl TokenFilter for LowercaseFilter {
+    fn apply(&self, mut tokens: Vec<Token>) -> Vec<Token> {
+        for token in &mut tokens {
+            if token.term.chars().any(char::is_uppercase) {
+                token.term = token.term.to_lowercase();
+            }
+        }
+        tokens
+    }
+}
@@ -0,0 +1,293 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::sync::Arc;
+
+use analysis::{
+    AnalysisResources, AnalyzerConfig, StopWords, Token, TokenFilter, Tokenizer,
+    config::ConfigError,
+    filter::{PhoneticEncoder, Stemmer},
+    tokenizer::StandardTokenizer,
+};
+
+/// Strips a trailing "s" in English, and a trailing "en" in German.
+struct ToyStemmer(&'static str);
+
+impl Stemmer for ToyStemmer {
+    fn stem(&self, term: &str) -> Option<String> {
+        term.strip_suffix(self.0).map(str::to_owned)
+    }
+}
+
+/// Encodes a term as its first letter.
+struct FirstLetter;
+
+impl PhoneticEncoder for FirstLetter {
+    fn encode(&self, term: &str) -> Vec<String> {
+        term.chars().take(1).map(String::from).collect()
+    }
+}
+
+struct Resources;
+
+impl AnalysisResources for Resources {
+    fn stemmer(&self, language: Option<&str>) -> Option<Arc<dyn Stemmer>> {
+        match language {
+            None | Some("english") => Some(Arc::new(ToyStemmer("s"))),
+            Some("german") => Some(Arc::new(ToyStemmer("en"))),
+            Some(_) => None,
+        }
+    }
+
+    fn index_stopwords(&self) -> Arc<StopWords> {
+        Arc::new(StopWords::default_list())
+    }
+
+    fn synonyms(&self, _set: &str) -> Option<Box<dyn TokenFilter>> {
+        None
+    }
+
+    fn phonetic(&self, matcher: &str) -> Option<Arc<dyn PhoneticEncoder>> {
+        (matcher == "dm:en").then(|| Arc::new(FirstLetter) as _)
+    }
+}
+
+fn terms(tokens: &[Token]) -> Vec<(&str, u32)> {
+    tokens
+        .iter()
+        .map(|t| (t.term.as_str(), t.position))
+        .collect()
+}
+
+#[test]
+fn test_standard_tokenizer() {
+    let text = "Hello, World! foo\\-bar  x";
+    let tokens = StandardTokenizer.tokenize(text);
+    assert_eq!(
+        terms(&tokens),
+        [("hello", 1), ("world", 2), ("foo-bar", 3), ("x", 4)]
+    );
+    assert_eq!(&text[tokens[2].offset.clone()], "foo\\-bar");
+    assert!(StandardTokenizer.tokenize(" ,. ").is_empty());
+}
+
+#[test]
+fn test_default_chain() {
+    let analyzer = AnalyzerConfig::default().build(&Resources).unwrap();
+    let tokens = analyzer.analyze("The cats and dogs");
+    // Stopwords leave gaps in positions.
+    assert_eq!(
+        terms(&tokens),
+        [("cats", 2), ("+cat", 2), ("dogs", 4), ("+dog", 4)]
+    );
+    assert!(tokens[1].variant);
+}
+
+#[test]
+fn test_custom_chain() {
+    let config = AnalyzerConfig {
+        stem: true,
+        language: Some("german".to_owned()),
+        stopwords: Some(vec!["und".to_owned()]),
+        phonetic: Some("dm:en".to_owned()),
+        ..Default::default()
+    };
+    let analyzer = config.build(&Resources).unwrap();
+    assert_eq!(
+        terms(&analyzer.analyze("Katzen und the Hunde")),
+        [
+            ("katzen", 1),
+            ("<k", 1),
+            ("+katz", 1),
+            ("the", 3),
+            ("<t", 3),
+            ("hunde", 4),
+            ("<h", 4)
+        ]
+    );
+
+    let no_stem = AnalyzerConfig {
+        stem: false,
+        stopwords: Some(Vec::new()),
+        ..Default::default()
+    };
+    assert_eq!(
+        terms(&no_stem.build(&Resources).unwrap().analyze("the cats")),
+        [("the", 1), ("cats", 2)]
+    );
+}
+
+#[test]
+fn test_missing_resources() {
+    let config = AnalyzerConfig {
+        synonyms: Some("colors".to_owned()),
+        ..Default::default()
+    };
+    assert_eq!(
+        config.build(&Resources).err(),
+        Some(ConfigError::UnknownSynonymSet("colors".to_owned()))
+    );
+    let config = AnalyzerConfig {
+        phonetic: Some("dm:xx".to_owned()),
+        ..Default::default()
+    };
+    assert_eq!(
+        config.build(&Resources).err().unwrap().to_string(),
+        "Unknown phonetic matcher `dm:xx`"
+    );
+}
+
+#[test]
+fn test_ngram_chain_has_no_filters() {
+    let config = AnalyzerConfig {
+        tokenizer: "ngram".parse().unwrap(),
+        ..Default::default()
+    };
+    let analyzer = config.build(&Resources).unwrap();
+    assert_eq!(
+        terms(&analyzer.analyze("the cats")),
+        [
+            ("th", 1),
+            ("the", 1),
+            ("he", 1),
+            ("ca", 2),
+            ("cat", 2),
+            ("at", 2),
+            ("ats", 2),
+            ("ts", 2)
+        ]
+    );
+    assert_eq!(analyzer.ngram_stats().unwrap().grams, 8);
+    assert!(
+        AnalyzerConfig::default()
+            .build(&Resources)
+            .unwrap()
+            .ngram_stats()
+            .is_none()
+    );
+}
+
+#[test]
+fn test_word_delimiter_chain() {
+    let config = AnalyzerConfig {
+        tokenizer: "whitespace".parse().unwrap(),
+        word_delimiter: Some(Default::default()),
+        ..Default::default()
+    };
+    let analyzer = config.build(&Resources).unwrap();
+    assert_eq!(
+        terms(&analyzer.analyze("The PowerShots")),
+        [("power", 2), ("shots", 3), ("+shot", 3)]
+    );
+}
+
+#[test]
+fn test_keyword_chain() {
+    let analyzer = AnalyzerConfig {
+        tokenizer: "keyword".parse().unwrap(),
+        ..Default::default()
+    }
+    .build(&Resources)
+    .unwrap();
+    let text = "  The ACME-42/Cats ";
+    let tokens = analyzer.analyze(text);
+    // Neither split, nor stemmed, nor filtered as a stopword.
+    assert_eq!(terms(&tokens), [("the acme-42/cats", 1)]);
+    assert_eq!(&text[tokens[0].offset.clone()], "The ACME-42/Cats");
+    assert!(analyzer.analyze("   ").is_empty());
+
+    let case_sensitive = AnalyzerConfig {
+        tokenizer: analysis::TokenizerKind::Keyword { case_fold: false },
+        ..Default::default()
+    };
+    assert_eq!(
+        terms(&case_sensitive.build(&Resources).unwrap().analyze("AbC")),
+        [("AbC", 1)]
+    );
+}
+
+#[test]
+fn test_document_language_chain() {
+    struct GermanStopwords;
+
+    impl AnalysisResources for GermanStopwords {
+        fn stemmer(&self, language: Option<&str>) -> Option<Arc<dyn Stemmer>> {
+            Resources.stemmer(language)
+        }
+
+        fn index_stopwords(&self) -> Arc<StopWords> {
+            Resources.index_stopwords()
+        }
+
+        fn language_stopwords(&self, language: &str) -> Option<Arc<StopWords>> {
+            (language == "german").then(|| Arc::new(StopWords::from_words(["die"])))
+        }
+
+        fn synonyms(&self, set: &str) -> Option<Box<dyn TokenFilter>> {
+            Resources.synonyms(set)
+        }
+
+        fn phonetic(&self, matcher: &str) -> Option<Arc<dyn PhoneticEncoder>> {
+            Resources.phonetic(matcher)
+        }
+    }
+
+    let field = AnalyzerConfig::default();
+    let german = field.for_language(Some("german"));
+    assert_eq!(german.language.as_deref(), Some("german"));
+    let analyzer = german.build(&GermanStopwords).unwrap();
+    assert_eq!(
+        terms(&analyzer.analyze("die Katzen the")),
+        [("katzen", 2), ("+katz", 2), ("the", 3)]
+    );
+
+    // A language declared on the field wins.
+    let english = AnalyzerConfig {
+        language: Some("english".to_owned()),
+        ..Default::default()
+    };
+    assert_eq!(
+        english.for_language(Some("german")).language.as_deref(),
+        Some("english")
+    );
+    assert_eq!(*field.for_language(None), field);
+}
+
+#[test]
+fn test_query_stopwords() {
+    let field = AnalyzerConfig::default();
+    assert_eq!(*field.with_query_stopwords(None), field);
+
+    // `STOPWORDS 0` keeps every word, as in band names.
+    let none = field.with_query_stopwords(Some(&[]));
+    assert_eq!(
+        terms(&none.build(&Resources).unwrap().analyze("the the")),
+        [("the", 1), ("the", 2)]
+    );
+
+    let custom = ["cats".to_owned()];
+    let analyzer = field
+        .with_query_stopwords(Some(&custom))
+        .build(&Resources)
+        .unwrap();
+    assert_eq!(terms(&analyzer.analyze("the cats")), [("the", 1)]);
+}
+
+#[test]
+fn test_verbatim_chain() {
+    let config = AnalyzerConfig {
+        phonetic: Some("dm:en".to_owned()),
+        ..Default::default()
+    };
+    let analyzer = config.verbatim().build(&Resources).unwrap();
+    assert_eq!(
+        terms(&analyzer.analyze("The Cats")),
+        [("the", 1), ("cats", 2)]
+    );
+}
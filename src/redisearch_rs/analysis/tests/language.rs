@@ -0,0 +1,110 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use analysis::language::{DETECTED_LANGUAGE_ATTR, DocumentLanguage, LanguageDetector};
+
+fn detect(text: &str) -> Option<String> {
+    LanguageDetector::builtin().detect(text).map(|d| d.language)
+}
+
+#[test]
+fn test_latin_languages() {
+    let cases = [
+        (
+            "english",
+            "The quick brown fox jumps over the lazy dog while the children are playing \
+             outside in the garden with their friends.",
+        ),
+        (
+            "french",
+            "Le petit chat est assis sur la table de la cuisine pendant que les enfants \
+             jouent dans le jardin avec leurs amis.",
+        ),
+        (
+            "german",
+            "Der kleine Hund schläft auf dem Sofa, während die Kinder mit ihren Freunden \
+             draußen im Garten spielen.",
+        ),
+        (
+            "spanish",
+            "El perro pequeño duerme en el sofá mientras los niños juegan en el jardín \
+             con sus amigos de la escuela.",
+        ),
+        (
+            "italian",
+            "Il piccolo cane dorme sul divano mentre i bambini giocano nel giardino con \
+             i loro amici della scuola.",
+        ),
+        (
+            "portuguese",
+            "O pequeno cachorro dorme no sofá enquanto as crianças brincam no jardim com \
+             os seus amigos da escola.",
+        ),
+        (
+            "dutch",
+            "De kleine hond slaapt op de bank terwijl de kinderen met hun vrienden \
+             buiten in de tuin spelen.",
+        ),
+    ];
+    for (language, text) in cases {
+        assert_eq!(detect(text).as_deref(), Some(language), "{text}");
+    }
+}
+
+#[test]
+fn test_scripts() {
+    assert_eq!(detect("Привет, как дела?").as_deref(), Some("russian"));
+    assert_eq!(detect("Καλημέρα κόσμε").as_deref(), Some("greek"));
+    assert_eq!(detect("你好世界，欢迎 hello").as_deref(), Some("chinese"));
+}
+
+#[test]
+fn test_abstains_on_short_text() {
+    assert_eq!(detect("hello"), None);
+    assert_eq!(detect("SKU-1234 42"), None);
+}
+
+#[test]
+fn test_custom_profile() {
+    let mut detector = LanguageDetector::default();
+    detector.add_profile(
+        "alpha",
+        "aaa aab aba abb baa bab bba bbb abab baba aabb bbaa",
+    );
+    detector.add_profile(
+        "omega",
+        "xyz zyx yxz xzy zxy yzx xxyz zzyx yyxz xyzzy zyxxy",
+    );
+    assert_eq!(detector.languages().collect::<Vec<_>>(), ["alpha", "omega"]);
+    let detection = detector
+        .detect("abcde cdeab eabcd aceb cead ecda bdac dbea deabc bcdea")
+        .unwrap();
+    assert_eq!(detection.language, "alpha");
+    assert!(detection.confidence > 0.0 && detection.confidence <= 1.0);
+}
+
+#[test]
+fn test_document_language() {
+    let detector = LanguageDetector::builtin();
+    let text = "Der kleine Hund schläft auf dem Sofa, während die Kinder draußen spielen.";
+
+    let declared = DocumentLanguage::resolve(Some("French"), Some(detector), text);
+    assert_eq!(declared, DocumentLanguage::Declared("french".to_owned()));
+    assert_eq!(declared.language(), Some("french"));
+    assert_eq!(declared.detected_attr(), None);
+
+    let detected = DocumentLanguage::resolve(None, Some(detector), text);
+    assert_eq!(detected.language(), Some("german"));
+    assert_eq!(detected.detected_attr(), Some("german"));
+
+    let disabled = DocumentLanguage::resolve(None, None, text);
+    assert_eq!(disabled, DocumentLanguage::Default);
+    assert_eq!(disabled.language(), None);
+    assert_eq!(DETECTED_LANGUAGE_ATTR, "__language");
+}
@@ -0,0 +1,172 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::sync::Arc;
+
+use analysis::{
+    AnalysisResources, AnalyzerConfig, LongTermPolicy, StopWords, TermLimit, Token, TokenFilter,
+    filter::{PhoneticEncoder, Stemmer},
+    long_terms::{HASHED_PREFIX, LongTermCounters, LongTermStats, hashed_term},
+};
+
+/// No stemmer nor stopwords, and counters shared by all the chains built.
+#[derive(Default)]
+struct Resources {
+    counters: Arc<LongTermCounters>,
+}
+
+impl AnalysisResources for Resources {
+    fn stemmer(&self, _language: Option<&str>) -> Option<Arc<dyn Stemmer>> {
+        None
+    }
+
+    fn index_stopwords(&self) -> Arc<StopWords> {
+        Arc::new(StopWords::from_words(["the"]))
+    }
+
+    fn synonyms(&self, _set: &str) -> Option<Box<dyn TokenFilter>> {
+        None
+    }
+
+    fn phonetic(&self, _matcher: &str) -> Option<Arc<dyn PhoneticEncoder>> {
+        None
+    }
+
+    fn long_term_counters(&self) -> Option<Arc<LongTermCounters>> {
+        Some(Arc::clone(&self.counters))
+    }
+}
+
+fn config(max_len: usize, policy: LongTermPolicy) -> AnalyzerConfig {
+    AnalyzerConfig {
+        term_limit: TermLimit { max_len, policy },
+        ..Default::default()
+    }
+}
+
+fn terms(tokens: &[Token]) -> Vec<(&str, u32)> {
+    tokens
+        .iter()
+        .map(|t| (t.term.as_str(), t.position))
+        .collect()
+}
+
+#[test]
+fn test_default_limit() {
+    let resources = Resources::default();
+    let analyzer = AnalyzerConfig::default().build(&resources).unwrap();
+    let long = "x".repeat(1 << 20);
+    let tokens = analyzer.analyze(&format!("short {long}"));
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(tokens[1].term.len(), 128);
+    // The offset still covers the whole term, for highlighting.
+    assert_eq!(tokens[1].offset, 6..6 + long.len());
+    assert_eq!(resources.counters.stats().truncated, 1);
+}
+
+#[test]
+fn test_truncate() {
+    let resources = Resources::default();
+    let analyzer = config(5, LongTermPolicy::Truncate)
+        .build(&resources)
+        .unwrap();
+    assert_eq!(
+        terms(&analyzer.analyze("abcde abcdefgh")),
+        [("abcde", 1), ("abcde", 2)]
+    );
+    // Multi-byte characters aren't split.
+    assert_eq!(terms(&analyzer.analyze("ééé")), [("éé", 1)]);
+    assert_eq!(
+        resources.counters.stats(),
+        LongTermStats {
+            truncated: 2,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_drop() {
+    let resources = Resources::default();
+    let analyzer = config(4, LongTermPolicy::Drop).build(&resources).unwrap();
+    // Positions are kept, as for stopwords.
+    assert_eq!(terms(&analyzer.analyze("the longword cat")), [("cat", 3)]);
+    assert_eq!(resources.counters.stats().dropped, 1);
+    assert_eq!(resources.counters.stats().total(), 1);
+}
+
+#[test]
+fn test_hash() {
+    let resources = Resources::default();
+    let analyzer = config(4, LongTermPolicy::Hash).build(&resources).unwrap();
+    let tokens = analyzer.analyze("cat LongWord longword longwords");
+    let hashed = hashed_term("longword");
+    assert_eq!(hashed.len(), 17);
+    assert!(hashed.starts_with(HASHED_PREFIX));
+    // The same term always hashes the same, so that queries match it.
+    assert_eq!(
+        terms(&tokens),
+        [
+            ("cat", 1),
+            (hashed.as_str(), 2),
+            (hashed.as_str(), 3),
+            (hashed_term("longwords").as_str(), 4)
+        ]
+    );
+    assert_eq!(resources.counters.stats().hashed, 3);
+}
+
+#[test]
+fn test_keyword_chain_is_limited() {
+    let resources = Resources::default();
+    let analyzer = AnalyzerConfig {
+        tokenizer: "keyword".parse().unwrap(),
+        ..config(3, LongTermPolicy::Drop)
+    }
+    .build(&resources)
+    .unwrap();
+    assert!(analyzer.analyze("some id").is_empty());
+    assert_eq!(terms(&analyzer.analyze("id")), [("id", 1)]);
+}
+
+#[test]
+fn test_counters_are_optional() {
+    struct NoCounters;
+
+    impl AnalysisResources for NoCounters {
+        fn stemmer(&self, _language: Option<&str>) -> Option<Arc<dyn Stemmer>> {
+            None
+        }
+
+        fn index_stopwords(&self) -> Arc<StopWords> {
+            Arc::new(StopWords::from_words(["the"]))
+        }
+
+        fn synonyms(&self, _set: &str) -> Option<Box<dyn TokenFilter>> {
+            None
+        }
+
+        fn phonetic(&self, _matcher: &str) -> Option<Arc<dyn PhoneticEncoder>> {
+            None
+        }
+    }
+
+    let analyzer = config(2, LongTermPolicy::Truncate)
+        .build(&NoCounters)
+        .unwrap();
+    assert_eq!(terms(&analyzer.analyze("abc")), [("ab", 1)]);
+}
+
+#[test]
+fn test_parse_policy() {
+    assert_eq!("drop".parse(), Ok(LongTermPolicy::Drop));
+    assert_eq!("HASH".parse(), Ok(LongTermPolicy::Hash));
+    assert_eq!("split".parse::<LongTermPolicy>(), Err(()));
+    assert_eq!(LongTermPolicy::default().name(), "TRUNCATE");
+}
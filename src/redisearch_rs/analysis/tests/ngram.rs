@@ -0,0 +1,78 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use analysis::{
+    Tokenizer,
+    ngram::{GramQuery, NGramStats, NGramTokenizer},
+};
+
+fn grams(tokenizer: &NGramTokenizer, text: &str) -> Vec<String> {
+    tokenizer
+        .tokenize(text)
+        .into_iter()
+        .map(|t| t.term)
+        .collect()
+}
+
+#[test]
+fn test_ngram_tokenizer() {
+    let tokenizer = NGramTokenizer::new(2, 3, false);
+    assert_eq!(
+        grams(&tokenizer, "Abcd x"),
+        ["ab", "abc", "bc", "bcd", "cd", "x"]
+    );
+    let tokens = tokenizer.tokenize("foo bar");
+    assert!(tokens[..3].iter().all(|t| t.position == 1));
+    assert!(tokens[3..].iter().all(|t| t.position == 2));
+
+    assert_eq!(
+        tokenizer.stats(),
+        NGramStats {
+            words: 4,
+            grams: 12,
+            gram_bytes: 27,
+        }
+    );
+    assert_eq!(tokenizer.stats().grams_per_word(), 3.0);
+}
+
+#[test]
+fn test_edge_ngram_tokenizer() {
+    let tokenizer = NGramTokenizer::new(2, 4, true);
+    assert_eq!(
+        grams(&tokenizer, "SKU-12345"),
+        ["sk", "sku", "12", "123", "1234"]
+    );
+}
+
+#[test]
+fn test_query() {
+    let tokenizer = NGramTokenizer::new(2, 3, false);
+    assert_eq!(tokenizer.query("a"), GramQuery::Scan);
+    assert_eq!(tokenizer.query("abc"), GramQuery::Exact("abc".to_owned()));
+    assert_eq!(
+        tokenizer.query("abcdefg"),
+        GramQuery::Verify {
+            grams: vec!["abc".to_owned(), "def".to_owned(), "efg".to_owned()]
+        }
+    );
+    // Documents with all the grams may not contain the substring.
+    assert!(tokenizer.verify("abcdefg", "xabcdefgx"));
+    assert!(!tokenizer.verify("abcdefg", "abc def efg"));
+
+    let edge = NGramTokenizer::new(2, 3, true);
+    assert_eq!(
+        edge.query("abcd"),
+        GramQuery::Verify {
+            grams: vec!["abc".to_owned()]
+        }
+    );
+    assert!(edge.verify("abcd", "x ABCDE"));
+    assert!(!edge.verify("abcd", "xabcd"));
+}
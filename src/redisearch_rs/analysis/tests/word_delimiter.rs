@@ -0,0 +1,117 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use analysis::{
+    Token, TokenFilter, Tokenizer,
+    tokenizer::WhitespaceTokenizer,
+    word_delimiter::{LowercaseFilter, WordDelimiterFilter, WordDelimiterOptions},
+};
+
+fn analyze(options: WordDelimiterOptions, text: &str) -> Vec<(String, u32, bool)> {
+    let tokens = WhitespaceTokenizer.tokenize(text);
+    let tokens = WordDelimiterFilter(options).apply(tokens);
+    LowercaseFilter
+        .apply(tokens)
+        .into_iter()
+        .map(|t| (t.term, t.position, t.variant))
+        .collect()
+}
+
+fn terms(options: WordDelimiterOptions, text: &str) -> Vec<String> {
+    analyze(options, text).into_iter().map(|t| t.0).collect()
+}
+
+#[test]
+fn test_whitespace_tokenizer() {
+    let text = " Wi-Fi  C++\tx ";
+    let tokens = WhitespaceTokenizer.tokenize(text);
+    assert_eq!(
+        tokens,
+        [
+            Token::new("Wi-Fi", 1, 1..6),
+            Token::new("C++", 2, 8..11),
+            Token::new("x", 3, 12..13)
+        ]
+    );
+}
+
+#[test]
+fn test_split() {
+    let options = WordDelimiterOptions::default();
+    assert_eq!(terms(options, "wi-fi"), ["wi", "fi"]);
+    assert_eq!(terms(options, "C++"), ["c"]);
+    assert_eq!(terms(options, "PowerShot"), ["power", "shot"]);
+    assert_eq!(terms(options, "XMLHttpRequest"), ["xml", "http", "request"]);
+    assert_eq!(terms(options, "SD500-B"), ["sd", "500", "b"]);
+    assert!(terms(options, "--").is_empty());
+
+    let no_splits = WordDelimiterOptions {
+        split_on_case_change: false,
+        split_on_numerics: false,
+        ..Default::default()
+    };
+    assert_eq!(terms(no_splits, "PowerShot SD500"), ["powershot", "sd500"]);
+}
+
+#[test]
+fn test_positions_and_variants() {
+    let options = WordDelimiterOptions {
+        preserve_original: true,
+        catenate_all: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        analyze(options, "Wi-Fi PowerShot-SD500 ok"),
+        [
+            ("wi".to_owned(), 1, false),
+            ("wi-fi".to_owned(), 1, true),
+            ("wifi".to_owned(), 1, true),
+            ("fi".to_owned(), 2, false),
+            ("power".to_owned(), 3, false),
+            ("powershot-sd500".to_owned(), 3, true),
+            ("powershotsd500".to_owned(), 3, true),
+            ("shot".to_owned(), 4, false),
+            ("sd".to_owned(), 5, false),
+            ("500".to_owned(), 6, false),
+            ("ok".to_owned(), 7, false),
+        ]
+    );
+    // Nothing to preserve for terms that aren't split.
+    assert_eq!(analyze(options, "ok"), [("ok".to_owned(), 1, false)]);
+}
+
+#[test]
+fn test_catenate_runs() {
+    let options = WordDelimiterOptions {
+        catenate_words: true,
+        catenate_numbers: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        terms(options, "foo-bar-2024-10-x"),
+        ["foo", "foobar", "202410", "bar", "2024", "10", "x"]
+    );
+}
+
+#[test]
+fn test_offsets() {
+    let text = "see PowerShot";
+    let tokens = WordDelimiterFilter::default().apply(WhitespaceTokenizer.tokenize(text));
+    let parts: Vec<_> = tokens.iter().map(|t| &text[t.offset.clone()]).collect();
+    assert_eq!(parts, ["see", "Power", "Shot"]);
+}
+
+#[test]
+fn test_options() {
+    let mut options = WordDelimiterOptions::default();
+    assert!(options.set("preserveoriginal"));
+    assert!(options.set("NOCASESPLIT"));
+    assert!(!options.set("SPLIT"));
+    assert_eq!(options.names(), ["NOCASESPLIT", "PRESERVEORIGINAL"]);
+}
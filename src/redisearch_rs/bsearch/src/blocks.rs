@@ -0,0 +1,60 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Binary searches over a list of sorted blocks, such as the blocks of an
+//! inverted index or the leaves of the numeric tree.
+//!
+//! The candidate block is found from the first and last entries of each block,
+//! then the entry is searched within it.
+
+use std::cmp::Ordering;
+
+use crate::{bsearch_ge, bsearch_le};
+
+/// A block of entries, sorted within the block and across the list of blocks.
+pub trait SortedBlock {
+    type Entry;
+
+    /// The smallest entry of the block.
+    fn first(&self) -> &Self::Entry;
+
+    /// The greatest entry of the block.
+    fn last(&self) -> &Self::Entry;
+
+    /// All the entries of the block, in order.
+    fn entries(&self) -> &[Self::Entry];
+}
+
+/// The position `(block_idx, inner_idx)` of the first entry greater than or
+/// equal to `target`, or `None` if all the entries are smaller.
+///
+/// Only the last entry of the blocks before the candidate block is compared.
+pub fn bsearch_blocks<B: SortedBlock, K: ?Sized>(
+    blocks: &[B],
+    target: &K,
+    cmp: impl Fn(&B::Entry, &K) -> Ordering,
+) -> Option<(usize, usize)> {
+    let block = bsearch_ge(blocks, target, |b, t| cmp(b.last(), t))?;
+    let inner = bsearch_ge(blocks[block].entries(), target, &cmp)?;
+    Some((block, inner))
+}
+
+/// The position `(block_idx, inner_idx)` of the last entry less than or equal
+/// to `target`, or `None` if all the entries are greater.
+///
+/// Only the first entry of the blocks after the candidate block is compared.
+pub fn bsearch_blocks_le<B: SortedBlock, K: ?Sized>(
+    blocks: &[B],
+    target: &K,
+    cmp: impl Fn(&B::Entry, &K) -> Ordering,
+) -> Option<(usize, usize)> {
+    let block = bsearch_le(blocks, target, |b, t| cmp(b.first(), t))?;
+    let inner = bsearch_le(blocks[block].entries(), target, &cmp)?;
+    Some((block, inner))
+}
@@ -0,0 +1,426 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Binary searches over sorted slices, returning the position of the boundary
+//! element rather than a `Result` as [`slice::binary_search_by`] does.
+//!
+//! Range scans need the first element not below the lower bound and the last
+//! element not above the upper bound, including when the bounds fall between
+//! elements or on runs of duplicates. The functions here answer these
+//! questions directly:
+//!
+//! - [`bsearch_ge`]: the first element `>= target`;
+//! - [`bsearch_le`]: the last element `<= target`;
+//! - [`bsearch_eq`]: the first element `== target`;
+//! - [`bsearch_gt`]: the first element `> target`;
+//! - [`bsearch_lt`]: the last element `< target`.
+//!
+//! The last two are the bounds of exclusive ranges such as `(10 20`, which
+//! skip the runs of elements equal to the bounds.
+//!
+//! [`bsearch_ge_by_key`], [`bsearch_le_by_key`] and [`bsearch_eq_by_key`]
+//! compare a key extracted from each element instead, as
+//! [`slice::binary_search_by_key`] does, e.g. the document ID of a record.
+//!
+//! [`bsearch_range`] returns the positions of the elements between two bounds
+//! at once.
+//!
+//! [`partition_ge`] and [`partition_gt`] return the boundaries as indices
+//! rather than positions of elements, i.e. `arr.len()` rather than `None`
+//! when there's no element past the boundary, which is what range bounds
+//! need. [`insertion_point_for`] is where to insert into a sorted slice.
+//!
+//! [`bsearch_ge_batch`] answers [`bsearch_ge`] for many targets at once, such
+//! as the bounds of the ranges of a numeric union.
+//!
+//! [`bsearch_ge_fixed`] is a variant of [`bsearch_ge`] for fixed-size arrays,
+//! whose branchless loop [`partition_ge`] and [`partition_gt`] also use for
+//! slices of up to [`FIXED_MAX_LEN`] elements.
+//!
+//! The comparator compares an element of the slice with the target, so that
+//! the target doesn't need to be of the element type. The slice must be sorted
+//! according to it. In debug builds, the infallible searches check that the
+//! elements around the result and at both ends of the slice are consistent
+//! with it, and panic otherwise.
+//!
+//! Comparing some elements can fail, e.g. when they are decoded from blocks
+//! loaded from an RDB file, which may be corrupt. [`try_bsearch_ge`],
+//! [`try_bsearch_le`] and [`try_bsearch_eq`] take a comparator returning a
+//! `Result`, and return the first error it does.
+//!
+//! Entries split across a list of blocks are searched with [`bsearch_blocks`]
+//! and [`bsearch_blocks_le`].
+
+pub mod blocks;
+
+use std::{cmp::Ordering, ops::Range};
+
+pub use blocks::{SortedBlock, bsearch_blocks, bsearch_blocks_le};
+
+/// The index of the first element of `arr` greater than or equal to `target`,
+/// or `None` if all the elements are smaller.
+pub fn bsearch_ge<T, K: ?Sized>(
+    arr: &[T],
+    target: &K,
+    cmp: impl Fn(&T, &K) -> Ordering,
+) -> Option<usize> {
+    let i = partition_ge(arr, target, cmp);
+    (i < arr.len()).then_some(i)
+}
+
+/// The index of the first element of `arr` strictly greater than `target`,
+/// or `None` if none is.
+pub fn bsearch_gt<T, K: ?Sized>(
+    arr: &[T],
+    target: &K,
+    cmp: impl Fn(&T, &K) -> Ordering,
+) -> Option<usize> {
+    let i = partition_gt(arr, target, cmp);
+    (i < arr.len()).then_some(i)
+}
+
+/// The positions of the elements of `arr` between `lo` and `hi`, both
+/// included, or `None` if there are none, including when `lo` is above `hi`.
+///
+/// The upper bound is only searched for past the lower one, so the two
+/// searches together cost fewer comparisons than [`bsearch_ge`] and
+/// [`bsearch_le`].
+pub fn bsearch_range<T, K: ?Sized>(
+    arr: &[T],
+    lo: &K,
+    hi: &K,
+    cmp: impl Fn(&T, &K) -> Ordering,
+) -> Option<Range<usize>> {
+    let start = partition_ge(arr, lo, &cmp);
+    let end = start + partition_gt(&arr[start..], hi, &cmp);
+    (start < end).then_some(start..end)
+}
+
+/// The answers of [`bsearch_ge`] for each of `targets`, in the same order.
+///
+/// Each search starts from the answer to the previous target, galloping
+/// towards the new one, so that the whole batch is answered in about one pass
+/// over `arr` when the targets are sorted, e.g. the bounds of the disjoint
+/// ranges of a numeric union. Repeated targets are answered immediately. The
+/// targets don't need to be sorted, but each search costs up to twice a
+/// [`bsearch_ge`] when they jump around.
+///
+/// `INKEYS` keys don't go through it: they're looked up in the hash map of
+/// document keys, one at a time.
+pub fn bsearch_ge_batch<T, K>(
+    arr: &[T],
+    targets: &[K],
+    cmp: impl Fn(&T, &K) -> Ordering,
+) -> Vec<Option<usize>> {
+    let mut prev = 0;
+    targets
+        .iter()
+        .map(|target| {
+            let pred = |x: &T| cmp(x, target) == Ordering::Less;
+            prev = gallop_partition_point(arr, prev, pred);
+            debug_check_sorted(arr, prev, pred, "bsearch_ge_batch");
+            (prev < arr.len()).then_some(prev)
+        })
+        .collect()
+}
+
+/// Like [`slice::partition_point`], searching outwards from `start` with
+/// doubling steps, so that it's cheap when the result is close to `start`.
+fn gallop_partition_point<T>(arr: &[T], start: usize, pred: impl Fn(&T) -> bool) -> usize {
+    if start < arr.len() && pred(&arr[start]) {
+        // The boundary is after `start`.
+        let mut bound = start;
+        let mut step = 1;
+        while bound + step < arr.len() && pred(&arr[bound + step]) {
+            bound += step;
+            step *= 2;
+        }
+        let end = (bound + step).min(arr.len());
+        bound + 1 + arr[bound + 1..end].partition_point(pred)
+    } else {
+        // The boundary is at or before `start`.
+        let mut bound = start.min(arr.len());
+        let mut step = 1;
+        while step <= bound && !pred(&arr[bound - step]) {
+            bound -= step;
+            step *= 2;
+        }
+        let begin = if step <= bound { bound - step + 1 } else { 0 };
+        begin + arr[begin..bound].partition_point(pred)
+    }
+}
+
+/// The longest slice [`partition_ge`] and [`partition_gt`] search with
+/// [`bsearch_ge_fixed`]'s branchless loop, e.g. the skip entries in a block
+/// header.
+pub const FIXED_MAX_LEN: usize = 64;
+
+/// Like [`bsearch_ge`], for small arrays whose length is known at compile
+/// time.
+///
+/// The search is unrolled and doesn't branch on the comparisons, which is
+/// several times faster than [`slice::partition_point`] on arrays of a few
+/// dozen elements, since their comparisons are unpredictable.
+pub fn bsearch_ge_fixed<T, K: ?Sized, const N: usize>(
+    arr: &[T; N],
+    target: &K,
+    cmp: impl Fn(&T, &K) -> Ordering,
+) -> Option<usize> {
+    let pred = |x: &T| cmp(x, target) == Ordering::Less;
+    let i = branchless_partition_point(arr, pred);
+    debug_check_sorted(arr, i, pred, "bsearch_ge_fixed");
+    (i < N).then_some(i)
+}
+
+/// Like [`slice::partition_point`], but halving the range with a conditional
+/// move rather than a branch.
+#[inline(always)]
+fn branchless_partition_point<T>(arr: &[T], pred: impl Fn(&T) -> bool) -> usize {
+    if arr.is_empty() {
+        return 0;
+    }
+    let mut base = 0;
+    let mut size = arr.len();
+    while size > 1 {
+        let half = size / 2;
+        // `base + half < arr.len()` since `base + size <= arr.len()`.
+        base = if pred(&arr[base + half]) {
+            base + half
+        } else {
+            base
+        };
+        size -= half;
+    }
+    base + usize::from(pred(&arr[base]))
+}
+
+/// The index of the last element of `arr` less than or equal to `target`,
+/// or `None` if all the elements are greater.
+pub fn bsearch_le<T, K: ?Sized>(
+    arr: &[T],
+    target: &K,
+    cmp: impl Fn(&T, &K) -> Ordering,
+) -> Option<usize> {
+    partition_gt(arr, target, cmp).checked_sub(1)
+}
+
+/// The index of the last element of `arr` strictly less than `target`, or
+/// `None` if none is.
+pub fn bsearch_lt<T, K: ?Sized>(
+    arr: &[T],
+    target: &K,
+    cmp: impl Fn(&T, &K) -> Ordering,
+) -> Option<usize> {
+    partition_ge(arr, target, cmp).checked_sub(1)
+}
+
+/// The number of elements of `arr` smaller than `target`, which is the index
+/// of the first element greater than or equal to it, or `arr.len()`.
+pub fn partition_ge<T, K: ?Sized>(
+    arr: &[T],
+    target: &K,
+    cmp: impl Fn(&T, &K) -> Ordering,
+) -> usize {
+    let pred = |x: &T| cmp(x, target) == Ordering::Less;
+    let i = if arr.len() <= FIXED_MAX_LEN {
+        branchless_partition_point(arr, pred)
+    } else {
+        arr.partition_point(pred)
+    };
+    debug_check_sorted(arr, i, pred, "partition_ge");
+    i
+}
+
+/// The number of elements of `arr` smaller than or equal to `target`, which
+/// is the index of the first element greater than it, or `arr.len()`.
+pub fn partition_gt<T, K: ?Sized>(
+    arr: &[T],
+    target: &K,
+    cmp: impl Fn(&T, &K) -> Ordering,
+) -> usize {
+    let pred = |x: &T| cmp(x, target) != Ordering::Greater;
+    let i = if arr.len() <= FIXED_MAX_LEN {
+        branchless_partition_point(arr, pred)
+    } else {
+        arr.partition_point(pred)
+    };
+    debug_check_sorted(arr, i, pred, "partition_gt");
+    i
+}
+
+/// The index at which to insert `value` in `arr` to keep it sorted, after the
+/// elements equal to it, so that equal elements stay in insertion order.
+pub fn insertion_point_for<T>(arr: &[T], value: &T, cmp: impl Fn(&T, &T) -> Ordering) -> usize {
+    partition_gt(arr, value, cmp)
+}
+
+/// In debug builds, panic if `arr` isn't partitioned by `pred` at `i`, as it
+/// would be if it were sorted.
+///
+/// Only the elements on both sides of `i`, and the first and last elements,
+/// are checked, so that searches stay logarithmic. This catches most unsorted
+/// slices, which would otherwise silently give wrong results.
+#[track_caller]
+fn debug_check_sorted<T>(arr: &[T], i: usize, pred: impl Fn(&T) -> bool, search: &str) {
+    debug_check_partitioned(arr.len(), i, |j| pred(&arr[j]), search);
+}
+
+/// Like [`debug_check_sorted`], with `pred` taking the index of the element,
+/// so that it can borrow from the slice.
+#[track_caller]
+fn debug_check_partitioned(len: usize, i: usize, pred: impl Fn(usize) -> bool, search: &str) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let below = [0, i.wrapping_sub(1)].into_iter().filter(|&j| j < i);
+    let above = [i, len.wrapping_sub(1)]
+        .into_iter()
+        .filter(|&j| i <= j && j < len);
+    for j in below {
+        assert!(
+            pred(j),
+            "{search}: the slice of {len} elements isn't sorted: the element at {j} should be before the boundary at {i}"
+        );
+    }
+    for j in above {
+        assert!(
+            !pred(j),
+            "{search}: the slice of {len} elements isn't sorted: the element at {j} should be after the boundary at {i}"
+        );
+    }
+}
+
+/// The index of the first element of `arr` equal to `target`, if any.
+pub fn bsearch_eq<T, K: ?Sized>(
+    arr: &[T],
+    target: &K,
+    cmp: impl Fn(&T, &K) -> Ordering,
+) -> Option<usize> {
+    bsearch_ge(arr, target, &cmp).filter(|&i| cmp(&arr[i], target) == Ordering::Equal)
+}
+
+/// Like [`bsearch_ge`], comparing `key` with the key `f` extracts from each
+/// element, as [`slice::binary_search_by_key`] does. `arr` must be sorted by
+/// that key.
+pub fn bsearch_ge_by_key<'a, T, B: Ord>(
+    arr: &'a [T],
+    key: &B,
+    f: impl Fn(&'a T) -> B,
+) -> Option<usize> {
+    let i = partition_by_key(arr, f, |k| k < key, "bsearch_ge_by_key");
+    (i < arr.len()).then_some(i)
+}
+
+/// Like [`bsearch_le`], comparing `key` with the key `f` extracts from each
+/// element. `arr` must be sorted by that key.
+pub fn bsearch_le_by_key<'a, T, B: Ord>(
+    arr: &'a [T],
+    key: &B,
+    f: impl Fn(&'a T) -> B,
+) -> Option<usize> {
+    partition_by_key(arr, f, |k| k <= key, "bsearch_le_by_key").checked_sub(1)
+}
+
+/// Like [`bsearch_eq`], comparing `key` with the key `f` extracts from each
+/// element. `arr` must be sorted by that key.
+pub fn bsearch_eq_by_key<'a, T, B: Ord>(
+    arr: &'a [T],
+    key: &B,
+    f: impl Fn(&'a T) -> B,
+) -> Option<usize> {
+    let i = partition_by_key(arr, &f, |k| k < key, "bsearch_eq_by_key");
+    arr.get(i).filter(|x| f(x) == *key).map(|_| i)
+}
+
+/// The index of the first element of `arr` whose key, extracted by `f`,
+/// doesn't satisfy `pred`.
+///
+/// The elements are accessed by index rather than through a closure taking
+/// any reference, so that the keys may borrow from `arr`, e.g. a `&str` field.
+#[track_caller]
+fn partition_by_key<'a, T, B>(
+    arr: &'a [T],
+    f: impl Fn(&'a T) -> B,
+    pred: impl Fn(&B) -> bool,
+    search: &str,
+) -> usize {
+    let pred_at = |j: usize| pred(&f(&arr[j]));
+    let (mut begin, mut end) = (0, arr.len());
+    while begin < end {
+        let mid = begin + (end - begin) / 2;
+        if pred_at(mid) {
+            begin = mid + 1;
+        } else {
+            end = mid;
+        }
+    }
+    debug_check_partitioned(arr.len(), begin, pred_at, search);
+    begin
+}
+
+/// Like [`slice::partition_point`], but stops at the first error of `pred`.
+fn try_partition_point<T, E>(
+    arr: &[T],
+    mut pred: impl FnMut(&T) -> Result<bool, E>,
+) -> Result<usize, E> {
+    let (mut begin, mut end) = (0, arr.len());
+    while begin < end {
+        let mid = begin + (end - begin) / 2;
+        if pred(&arr[mid])? {
+            begin = mid + 1;
+        } else {
+            end = mid;
+        }
+    }
+    Ok(begin)
+}
+
+/// Like [`bsearch_ge`], with a fallible comparator.
+///
+/// # Errors
+///
+/// Returns the first error of `cmp`.
+pub fn try_bsearch_ge<T, K: ?Sized, E>(
+    arr: &[T],
+    target: &K,
+    cmp: impl Fn(&T, &K) -> Result<Ordering, E>,
+) -> Result<Option<usize>, E> {
+    let i = try_partition_point(arr, |x| Ok(cmp(x, target)? == Ordering::Less))?;
+    Ok((i < arr.len()).then_some(i))
+}
+
+/// Like [`bsearch_le`], with a fallible comparator.
+///
+/// # Errors
+///
+/// Returns the first error of `cmp`.
+pub fn try_bsearch_le<T, K: ?Sized, E>(
+    arr: &[T],
+    target: &K,
+    cmp: impl Fn(&T, &K) -> Result<Ordering, E>,
+) -> Result<Option<usize>, E> {
+    let i = try_partition_point(arr, |x| Ok(cmp(x, target)? != Ordering::Greater))?;
+    Ok(i.checked_sub(1))
+}
+
+/// Like [`bsearch_eq`], with a fallible comparator.
+///
+/// # Errors
+///
+/// Returns the first error of `cmp`.
+pub fn try_bsearch_eq<T, K: ?Sized, E>(
+    arr: &[T],
+    target: &K,
+    cmp: impl Fn(&T, &K) -> Result<Ordering, E>,
+) -> Result<Option<usize>, E> {
+    let Some(i) = try_bsearch_ge(arr, target, &cmp)? else {
+        return Ok(None);
+    };
+    Ok((cmp(&arr[i], target)? == Ordering::Equal).then_some(i))
+}
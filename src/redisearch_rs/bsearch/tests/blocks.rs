@@ -0,0 +1,86 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use bsearch::{SortedBlock, bsearch_blocks, bsearch_blocks_le, bsearch_ge};
+
+struct Block(Vec<u32>);
+
+impl SortedBlock for Block {
+    type Entry = u32;
+
+    fn first(&self) -> &u32 {
+        &self.0[0]
+    }
+
+    fn last(&self) -> &u32 {
+        self.0.last().unwrap()
+    }
+
+    fn entries(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+fn cmp(a: &u32, b: &u32) -> std::cmp::Ordering {
+    a.cmp(b)
+}
+
+fn blocks() -> Vec<Block> {
+    vec![
+        Block(vec![1, 3, 5]),
+        Block(vec![5, 5, 9]),
+        Block(vec![12]),
+        Block(vec![20, 30]),
+    ]
+}
+
+#[test]
+fn test_blocks_ge() {
+    let blocks = blocks();
+    assert_eq!(bsearch_blocks(&blocks, &0, cmp), Some((0, 0)));
+    assert_eq!(bsearch_blocks(&blocks, &4, cmp), Some((0, 2)));
+    // Duplicates spanning two blocks: the first one is found.
+    assert_eq!(bsearch_blocks(&blocks, &5, cmp), Some((0, 2)));
+    assert_eq!(bsearch_blocks(&blocks, &10, cmp), Some((2, 0)));
+    assert_eq!(bsearch_blocks(&blocks, &30, cmp), Some((3, 1)));
+    assert_eq!(bsearch_blocks(&blocks, &31, cmp), None);
+    assert_eq!(bsearch_blocks::<Block, _>(&[], &1, cmp), None);
+}
+
+#[test]
+fn test_blocks_le() {
+    let blocks = blocks();
+    assert_eq!(bsearch_blocks_le(&blocks, &0, cmp), None);
+    assert_eq!(bsearch_blocks_le(&blocks, &4, cmp), Some((0, 1)));
+    // Duplicates spanning two blocks: the last one is found.
+    assert_eq!(bsearch_blocks_le(&blocks, &5, cmp), Some((1, 1)));
+    assert_eq!(bsearch_blocks_le(&blocks, &19, cmp), Some((2, 0)));
+    assert_eq!(bsearch_blocks_le(&blocks, &100, cmp), Some((3, 1)));
+}
+
+#[test]
+fn test_blocks_match_flat_search() {
+    let blocks = blocks();
+    let flat: Vec<u32> = blocks.iter().flat_map(|b| b.0.clone()).collect();
+    let offsets: Vec<usize> = blocks
+        .iter()
+        .scan(0, |start, b| {
+            let offset = *start;
+            *start += b.0.len();
+            Some(offset)
+        })
+        .collect();
+    for target in 0..32 {
+        assert_eq!(
+            bsearch_blocks(&blocks, &target, cmp).map(|(b, i)| offsets[b] + i),
+            bsearch_ge(&flat, &target, cmp),
+            "target {target}"
+        );
+    }
+}
@@ -0,0 +1,362 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::cmp::Ordering;
+
+use bsearch::{
+    FIXED_MAX_LEN, bsearch_eq, bsearch_eq_by_key, bsearch_ge, bsearch_ge_batch, bsearch_ge_by_key,
+    bsearch_ge_fixed, bsearch_gt, bsearch_le, bsearch_le_by_key, bsearch_lt, bsearch_range,
+    insertion_point_for, partition_ge, partition_gt, try_bsearch_eq, try_bsearch_ge,
+    try_bsearch_le,
+};
+
+fn cmp(a: &u32, b: &u32) -> std::cmp::Ordering {
+    a.cmp(b)
+}
+
+#[test]
+fn test_ge() {
+    let arr = [1, 3, 3, 3, 7];
+    assert_eq!(bsearch_ge(&arr, &0, cmp), Some(0));
+    assert_eq!(bsearch_ge(&arr, &3, cmp), Some(1));
+    assert_eq!(bsearch_ge(&arr, &4, cmp), Some(4));
+    assert_eq!(bsearch_ge(&arr, &8, cmp), None);
+    assert_eq!(bsearch_ge(&[], &1, cmp), None);
+}
+
+#[test]
+fn test_le() {
+    let arr = [1, 3, 3, 3, 7];
+    assert_eq!(bsearch_le(&arr, &0, cmp), None);
+    assert_eq!(bsearch_le(&arr, &3, cmp), Some(3));
+    assert_eq!(bsearch_le(&arr, &6, cmp), Some(3));
+    assert_eq!(bsearch_le(&arr, &9, cmp), Some(4));
+    assert_eq!(bsearch_le(&[], &1, cmp), None);
+}
+
+#[test]
+fn test_eq() {
+    let arr = [1, 3, 3, 3, 7];
+    assert_eq!(bsearch_eq(&arr, &3, cmp), Some(1));
+    assert_eq!(bsearch_eq(&arr, &7, cmp), Some(4));
+    assert_eq!(bsearch_eq(&arr, &5, cmp), None);
+}
+
+#[test]
+fn test_gt() {
+    let arr = [1, 3, 3, 3, 7];
+    assert_eq!(bsearch_gt(&arr, &0, cmp), Some(0));
+    // Skips the whole run of duplicates.
+    assert_eq!(bsearch_gt(&arr, &3, cmp), Some(4));
+    assert_eq!(bsearch_gt(&arr, &4, cmp), Some(4));
+    assert_eq!(bsearch_gt(&arr, &7, cmp), None);
+    assert_eq!(bsearch_gt(&[], &1, cmp), None);
+}
+
+#[test]
+fn test_lt() {
+    let arr = [1, 3, 3, 3, 7];
+    assert_eq!(bsearch_lt(&arr, &1, cmp), None);
+    assert_eq!(bsearch_lt(&arr, &3, cmp), Some(0));
+    assert_eq!(bsearch_lt(&arr, &7, cmp), Some(3));
+    assert_eq!(bsearch_lt(&arr, &9, cmp), Some(4));
+    assert_eq!(bsearch_lt(&[], &1, cmp), None);
+}
+
+#[test]
+fn test_heterogeneous_target() {
+    let entries = [(1, "a"), (4, "b"), (9, "c")];
+    let by_id = |e: &(u64, &str), id: &u64| e.0.cmp(id);
+    assert_eq!(bsearch_ge(&entries, &5, by_id), Some(2));
+    assert_eq!(bsearch_le(&entries, &5, by_id), Some(1));
+}
+
+/// Entries decoded from bytes, where 0xFF marks a corrupt entry.
+fn decode(entry: &u8, target: &u8) -> Result<Ordering, String> {
+    if *entry == 0xFF {
+        return Err("corrupt entry".to_owned());
+    }
+    Ok(entry.cmp(target))
+}
+
+#[test]
+fn test_try_variants_match() {
+    let arr = [1u8, 3, 3, 3, 7];
+    for target in 0..9 {
+        let infallible = |a: &u8, b: &u8| a.cmp(b);
+        assert_eq!(
+            try_bsearch_ge(&arr, &target, decode),
+            Ok(bsearch_ge(&arr, &target, infallible))
+        );
+        assert_eq!(
+            try_bsearch_le(&arr, &target, decode),
+            Ok(bsearch_le(&arr, &target, infallible))
+        );
+        assert_eq!(
+            try_bsearch_eq(&arr, &target, decode),
+            Ok(bsearch_eq(&arr, &target, infallible))
+        );
+    }
+}
+
+#[test]
+fn test_try_variants_propagate_errors() {
+    let arr = [1u8, 3, 0xFF, 8, 9];
+    // The middle entry is compared first.
+    assert!(try_bsearch_ge(&arr, &5, decode).is_err());
+    assert!(try_bsearch_le(&arr, &5, decode).is_err());
+    assert!(try_bsearch_eq(&arr, &5, decode).is_err());
+
+    // A corrupt entry that the search doesn't reach goes unnoticed.
+    let arr = [1u8, 3, 5, 8, 0xFF];
+    assert_eq!(try_bsearch_ge(&arr, &2, decode), Ok(Some(1)));
+}
+
+#[test]
+fn test_ge_fixed() {
+    let arr = [1, 3, 3, 3, 7];
+    assert_eq!(bsearch_ge_fixed(&arr, &0, cmp), Some(0));
+    assert_eq!(bsearch_ge_fixed(&arr, &3, cmp), Some(1));
+    assert_eq!(bsearch_ge_fixed(&arr, &4, cmp), Some(4));
+    assert_eq!(bsearch_ge_fixed(&arr, &8, cmp), None);
+    assert_eq!(bsearch_ge_fixed(&[], &1, cmp), None);
+}
+
+#[test]
+fn test_ge_fixed_matches_partition_point() {
+    fn check<const N: usize>() {
+        // Every other value, with a run of duplicates in the middle.
+        let arr: [u32; N] = std::array::from_fn(|i| (2 * i as u32).min(N as u32));
+        for target in 0..=2 * N as u32 + 1 {
+            let expected = arr.partition_point(|&x| x < target);
+            let expected = (expected < N).then_some(expected);
+            assert_eq!(
+                bsearch_ge_fixed(&arr, &target, cmp),
+                expected,
+                "{N} {target}"
+            );
+            assert_eq!(bsearch_ge(&arr, &target, cmp), expected, "{N} {target}");
+            let expected = arr.partition_point(|&x| x <= target);
+            assert_eq!(partition_gt(&arr, &target, cmp), expected, "{N} {target}");
+        }
+    }
+    check::<1>();
+    check::<7>();
+    check::<8>();
+    check::<33>();
+    check::<FIXED_MAX_LEN>();
+    check::<{ FIXED_MAX_LEN + 1 }>();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "partition_ge: the slice of 5 elements isn't sorted")]
+fn test_unsorted_ge_panics() {
+    // 7 is found as the boundary, but the last element is smaller.
+    bsearch_ge(&[1, 3, 7, 8, 2], &5, cmp);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "partition_gt: the slice of 4 elements isn't sorted")]
+fn test_unsorted_le_panics() {
+    bsearch_le(&[9, 1, 2, 3], &5, cmp);
+}
+
+#[test]
+fn test_by_key() {
+    // `(doc_id, value)` records, sorted by document ID.
+    let records = [(1u64, 0.5), (3, 1.0), (3, 2.0), (7, 0.0)];
+    let doc_id = |r: &(u64, f64)| r.0;
+    assert_eq!(bsearch_ge_by_key(&records, &2, doc_id), Some(1));
+    assert_eq!(bsearch_ge_by_key(&records, &3, doc_id), Some(1));
+    assert_eq!(bsearch_ge_by_key(&records, &8, doc_id), None);
+    assert_eq!(bsearch_le_by_key(&records, &3, doc_id), Some(2));
+    assert_eq!(bsearch_le_by_key(&records, &0, doc_id), None);
+    assert_eq!(bsearch_eq_by_key(&records, &3, doc_id), Some(1));
+    assert_eq!(bsearch_eq_by_key(&records, &4, doc_id), None);
+    assert_eq!(bsearch_eq_by_key(&records, &9, doc_id), None);
+    assert_eq!(bsearch_eq_by_key(&[], &1, doc_id), None);
+}
+
+#[test]
+fn test_by_borrowed_key() {
+    // The keys may borrow from the elements.
+    let terms: Vec<(String, u32)> = [("apple", 3), ("banana", 1), ("cherry", 2)]
+        .into_iter()
+        .map(|(term, docs)| (term.to_owned(), docs))
+        .collect();
+    assert_eq!(
+        bsearch_eq_by_key(&terms, &"banana", |t| t.0.as_str()),
+        Some(1)
+    );
+    assert_eq!(
+        bsearch_ge_by_key(&terms, &"blueberry", |t| t.0.as_str()),
+        Some(2)
+    );
+    assert_eq!(
+        bsearch_le_by_key(&terms, &"aardvark", |t| t.0.as_str()),
+        None
+    );
+}
+
+#[test]
+fn test_by_key_matches_comparator_searches() {
+    for arr in sorted_arrays(6, 4) {
+        // Records sorted by their first member, the second one being noise.
+        let records: Vec<(u32, usize)> = arr.iter().enumerate().map(|(i, &x)| (x, i)).collect();
+        let key = |r: &(u32, usize)| r.0;
+        for target in 0..=4 {
+            assert_eq!(
+                bsearch_ge_by_key(&records, &target, key),
+                bsearch_ge(&arr, &target, cmp),
+                "{arr:?} {target}"
+            );
+            assert_eq!(
+                bsearch_le_by_key(&records, &target, key),
+                bsearch_le(&arr, &target, cmp),
+                "{arr:?} {target}"
+            );
+            assert_eq!(
+                bsearch_eq_by_key(&records, &target, key),
+                bsearch_eq(&arr, &target, cmp),
+                "{arr:?} {target}"
+            );
+        }
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "bsearch_ge_by_key: the slice of 3 elements isn't sorted")]
+fn test_unsorted_by_key_panics() {
+    bsearch_ge_by_key(&[(5, ()), (1, ()), (9, ())], &4, |r| r.0);
+}
+
+/// All the sorted arrays of up to `max_len` elements in `0..values`.
+fn sorted_arrays(max_len: usize, values: u32) -> Vec<Vec<u32>> {
+    let mut arrays = vec![Vec::new()];
+    let mut last_len = vec![Vec::new()];
+    for _ in 0..max_len {
+        last_len = last_len
+            .iter()
+            .flat_map(|arr: &Vec<u32>| {
+                let from = arr.last().copied().unwrap_or(0);
+                (from..values).map(move |v| {
+                    let mut longer = arr.clone();
+                    longer.push(v);
+                    longer
+                })
+            })
+            .collect();
+        arrays.extend(last_len.iter().cloned());
+    }
+    arrays
+}
+
+#[test]
+fn test_partitions_match_searches() {
+    for arr in sorted_arrays(6, 4) {
+        for target in 0..=4 {
+            let ge = partition_ge(&arr, &target, cmp);
+            let gt = partition_gt(&arr, &target, cmp);
+            assert_eq!(
+                Some(ge).filter(|&i| i < arr.len()),
+                bsearch_ge(&arr, &target, cmp)
+            );
+            assert_eq!(gt.checked_sub(1), bsearch_le(&arr, &target, cmp));
+            assert_eq!(
+                Some(gt).filter(|&i| i < arr.len()),
+                bsearch_gt(&arr, &target, cmp)
+            );
+            assert_eq!(ge.checked_sub(1), bsearch_lt(&arr, &target, cmp));
+            assert!(arr[..ge].iter().all(|&x| x < target), "{arr:?} {target}");
+            assert!(arr[ge..gt].iter().all(|&x| x == target), "{arr:?} {target}");
+            assert!(arr[gt..].iter().all(|&x| x > target), "{arr:?} {target}");
+
+            let at = insertion_point_for(&arr, &target, cmp);
+            assert_eq!(at, gt);
+            let mut inserted = arr.clone();
+            inserted.insert(at, target);
+            assert!(inserted.is_sorted(), "{arr:?} {target}");
+        }
+    }
+}
+
+#[test]
+fn test_insertion_point_keeps_insertion_order() {
+    let by_key = |a: &(u32, char), b: &(u32, char)| a.0.cmp(&b.0);
+    let mut arr = Vec::new();
+    for entry in [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd'), (2, 'e')] {
+        let at = insertion_point_for(&arr, &entry, by_key);
+        arr.insert(at, entry);
+    }
+    assert_eq!(arr, [(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c'), (2, 'e')]);
+}
+
+#[test]
+fn test_ge_batch() {
+    let arr = [1, 3, 3, 3, 7];
+    assert_eq!(
+        bsearch_ge_batch(&arr, &[0, 3, 3, 4, 8], cmp),
+        [Some(0), Some(1), Some(1), Some(4), None]
+    );
+    // Unsorted targets.
+    assert_eq!(
+        bsearch_ge_batch(&arr, &[8, 0, 7, 2], cmp),
+        [None, Some(0), Some(4), Some(1)]
+    );
+    assert_eq!(bsearch_ge_batch(&[], &[1, 2], cmp), [None, None]);
+    assert!(bsearch_ge_batch(&arr, &[], cmp).is_empty());
+}
+
+#[test]
+fn test_ge_batch_matches_single_searches() {
+    let arr: Vec<u32> = (0..200).map(|i| i / 3 * 2).collect();
+    // Sorted, reversed and jumping targets, with repeats.
+    let sorted: Vec<u32> = (0..140).collect();
+    let reversed: Vec<u32> = sorted.iter().rev().copied().collect();
+    let jumping: Vec<u32> = (0..140).map(|i| (i * 37) % 140).collect();
+    for targets in [sorted, reversed, jumping] {
+        let expected: Vec<_> = targets.iter().map(|t| bsearch_ge(&arr, t, cmp)).collect();
+        assert_eq!(bsearch_ge_batch(&arr, &targets, cmp), expected);
+    }
+}
+
+#[test]
+fn test_range() {
+    let arr = [1, 3, 3, 3, 7];
+    assert_eq!(bsearch_range(&arr, &3, &3, cmp), Some(1..4));
+    assert_eq!(bsearch_range(&arr, &0, &8, cmp), Some(0..5));
+    assert_eq!(bsearch_range(&arr, &2, &6, cmp), Some(1..4));
+    assert_eq!(bsearch_range(&arr, &4, &6, cmp), None);
+    assert_eq!(bsearch_range(&arr, &8, &9, cmp), None);
+    // Inverted bounds.
+    assert_eq!(bsearch_range(&arr, &7, &1, cmp), None);
+    assert_eq!(bsearch_range(&[], &1, &2, cmp), None);
+}
+
+#[test]
+fn test_range_matches_single_searches() {
+    for arr in sorted_arrays(6, 4) {
+        for lo in 0..5 {
+            for hi in 0..5 {
+                let expected = match (bsearch_ge(&arr, &lo, cmp), bsearch_le(&arr, &hi, cmp)) {
+                    (Some(start), Some(end)) if start <= end => Some(start..end + 1),
+                    _ => None,
+                };
+                assert_eq!(
+                    bsearch_range(&arr, &lo, &hi, cmp),
+                    expected,
+                    "{arr:?} [{lo}, {hi}]"
+                );
+            }
+        }
+    }
+}
@@ -96,6 +96,45 @@ pub fn run_cbinden(header_path: impl AsRef<Path>) -> Result<(), Box<dyn std::err
     Ok(())
 }
 
+/// The declarations of the C header at `header_path`, with comments and
+/// preprocessor directives removed and whitespace collapsed, e.g.
+/// `int rsb_eq(const void *arr, uintptr_t narr, uintptr_t elemsz, const void *s, rsbcompare cmp)`.
+///
+/// Used by the header tests of the FFI crates to check that the declarations
+/// the C code relies on don't drift from the Rust definitions.
+pub fn header_declarations(header_path: impl AsRef<Path>) -> std::io::Result<Vec<String>> {
+    let header = std::fs::read_to_string(header_path)?;
+    let mut code = String::with_capacity(header.len());
+    let mut rest = header.as_str();
+    while let Some(start) = rest.find("/*") {
+        code.push_str(&rest[..start]);
+        rest = rest[start..]
+            .find("*/")
+            .map_or("", |end| &rest[start + end + 2..]);
+    }
+    code.push_str(rest);
+
+    let code = code
+        .lines()
+        .map(|line| line.split("//").next().unwrap_or_default().trim())
+        .filter(|line| !line.starts_with('#') && !line.starts_with("extern \"C\""))
+        .collect::<Vec<_>>()
+        .join(" ");
+    Ok(code
+        .split(';')
+        .map(|decl| {
+            decl.split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .replace("( ", "(")
+                .replace(" )", ")")
+                .trim_start_matches("} ")
+                .to_owned()
+        })
+        .filter(|decl| !decl.is_empty() && decl != "}")
+        .collect())
+}
+
 /// Links static libraries
 ///
 /// This function configures the linker to include static libraries built by the main
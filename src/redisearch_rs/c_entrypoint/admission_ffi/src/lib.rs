@@ -0,0 +1,224 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! FFI layer to hand admission tickets to the C queries, and to list and kill
+//! them with `FT._QUERIES`.
+//!
+//! The queries of every index share a single controller. A ticket is taken
+//! when a query starts executing and freed with it, cursors included, so that
+//! `FT._QUERIES LIST` shows the cursors which weren't exhausted yet.
+
+use std::{
+    ffi::{CStr, c_char, c_void},
+    sync::LazyLock,
+};
+
+use pipeline::admission::{AdmissionConfig, AdmissionController, QueriesCommand, Ticket};
+
+/// The controller shared by every index. The caps are unlimited: tickets only
+/// track the running queries.
+static CONTROLLER: LazyLock<AdmissionController> = LazyLock::new(|| {
+    // Queries are admitted with `try_admit`, never queued, so the spawner is
+    // never called.
+    AdmissionController::new(AdmissionConfig::default(), |job| job())
+});
+
+/// The right of a query to run, taken with [`Admission_Acquire`].
+pub struct AdmissionTicket(Ticket);
+
+/// A subcommand of `FT._QUERIES`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueriesSubcommand {
+    List,
+    Kill,
+}
+
+/// Read the `len` bytes at `ptr` as a string, replacing invalid UTF-8.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads of `len` bytes, and may only be NULL if `len`
+/// is 0.
+unsafe fn str_from_raw(ptr: *const c_char, len: usize) -> String {
+    if len == 0 {
+        return String::new();
+    }
+    // SAFETY: The caller must ensure that `ptr` is valid for reads of `len` bytes
+    let bytes = unsafe { std::slice::from_raw_parts(ptr.cast::<u8>(), len) };
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Take a ticket for the query `query` on the index `index`. Returns NULL if
+/// the query isn't admitted, or a ticket which must be freed using
+/// [`AdmissionTicket_Free`] once the query is done.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `index` must be valid for reads of `index_len` bytes. It may be NULL if `index_len` is 0.
+/// - `query` must be valid for reads of `query_len` bytes. It may be NULL if `query_len` is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn Admission_Acquire(
+    index: *const c_char,
+    index_len: usize,
+    query: *const c_char,
+    query_len: usize,
+) -> *mut AdmissionTicket {
+    // SAFETY: The caller must ensure that `index` is valid for reads of `index_len` bytes
+    let index = unsafe { str_from_raw(index, index_len) };
+    // SAFETY: The caller must ensure that `query` is valid for reads of `query_len` bytes
+    let query = unsafe { str_from_raw(query, query_len) };
+    match CONTROLLER.try_admit(&index, &query) {
+        Ok(ticket) => Box::into_raw(Box::new(AdmissionTicket(ticket))),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Free the ticket taken with [`Admission_Acquire`], freeing its slot.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `ticket` must be a valid, non NULL, pointer returned by [`Admission_Acquire`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn AdmissionTicket_Free(ticket: *mut AdmissionTicket) {
+    debug_assert!(!ticket.is_null(), "ticket must not be null");
+
+    // SAFETY: The caller must ensure that `ticket` was returned by `Admission_Acquire`
+    let _ = unsafe { Box::from_raw(ticket) };
+}
+
+/// The id of the query holding `ticket`, as listed by `FT._QUERIES LIST`.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `ticket` must be a valid, non NULL, pointer returned by [`Admission_Acquire`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn AdmissionTicket_Id(ticket: *const AdmissionTicket) -> u64 {
+    debug_assert!(!ticket.is_null(), "ticket must not be null");
+
+    // SAFETY: The caller must ensure that `ticket` is a valid pointer to an `AdmissionTicket`
+    let ticket = unsafe { &*ticket };
+    ticket.0.id()
+}
+
+/// Whether the query holding `ticket` was killed with `FT._QUERIES KILL`, and
+/// must stop.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `ticket` must be a valid, non NULL, pointer returned by [`Admission_Acquire`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn AdmissionTicket_IsKilled(ticket: *const AdmissionTicket) -> bool {
+    debug_assert!(!ticket.is_null(), "ticket must not be null");
+
+    // SAFETY: The caller must ensure that `ticket` is a valid pointer to an `AdmissionTicket`
+    let ticket = unsafe { &*ticket };
+    ticket.0.is_killed()
+}
+
+/// Ask the query `id` to stop, for `FT._QUERIES KILL`. Returns whether it's
+/// running.
+#[unsafe(no_mangle)]
+pub extern "C" fn Admission_Kill(id: u64) -> bool {
+    CONTROLLER.kill(id)
+}
+
+/// Reply the queries holding a ticket to `FT._QUERIES LIST`, the oldest first:
+/// `query` is called with `reply` for each of them, with the time elapsed
+/// since it was admitted. The strings aren't NUL-terminated, and are only
+/// valid during the call.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `query` must be safe to call with `reply`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn Admission_ReplyInFlight(
+    reply: *mut c_void,
+    query: unsafe extern "C" fn(
+        reply: *mut c_void,
+        id: u64,
+        index: *const c_char,
+        index_len: usize,
+        query: *const c_char,
+        query_len: usize,
+        elapsed_ms: u64,
+    ),
+) {
+    for in_flight in CONTROLLER.in_flight() {
+        // SAFETY: The caller must ensure that `query` is safe to call with `reply`, and the
+        // strings outlive the call.
+        unsafe {
+            query(
+                reply,
+                in_flight.id,
+                in_flight.index.as_ptr().cast(),
+                in_flight.index.len(),
+                in_flight.query.as_ptr().cast(),
+                in_flight.query.len(),
+                in_flight.started.elapsed().as_millis() as u64,
+            )
+        };
+    }
+}
+
+/// Parse the `argc` arguments of `FT._QUERIES`. `id` is set to the id given
+/// to `KILL`. Returns false, leaving the output parameters untouched, if
+/// they're invalid.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `argv` must point to `argc` valid, NUL-terminated strings. It may be NULL if `argc` is 0.
+/// - `subcommand` and `id` must be valid, non NULL, pointers.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn Admission_ParseCommand(
+    argv: *const *const c_char,
+    argc: usize,
+    subcommand: *mut QueriesSubcommand,
+    id: *mut u64,
+) -> bool {
+    debug_assert!(!subcommand.is_null(), "subcommand must not be null");
+    debug_assert!(!id.is_null(), "id must not be null");
+
+    let argv = if argc == 0 {
+        &[][..]
+    } else {
+        // SAFETY: The caller must ensure that `argv` points to `argc` strings
+        unsafe { std::slice::from_raw_parts(argv, argc) }
+    };
+    let args: Option<Vec<&str>> = argv
+        .iter()
+        .map(|&arg| {
+            // SAFETY: The caller must ensure that the arguments are NUL-terminated strings
+            unsafe { CStr::from_ptr(arg) }.to_str().ok()
+        })
+        .collect();
+    let Some(command) = args.as_deref().and_then(QueriesCommand::parse) else {
+        return false;
+    };
+
+    match command {
+        QueriesCommand::List => {
+            // SAFETY: The caller must ensure that `subcommand` is a valid pointer
+            unsafe { *subcommand = QueriesSubcommand::List };
+        }
+        QueriesCommand::Kill(killed) => {
+            // SAFETY: The caller must ensure that `subcommand` is a valid pointer
+            unsafe { *subcommand = QueriesSubcommand::Kill };
+            // SAFETY: The caller must ensure that `id` is a valid pointer
+            unsafe { *id = killed };
+        }
+    }
+    true
+}
@@ -0,0 +1,141 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::ffi::{CString, c_char, c_void};
+
+use admission_ffi::{
+    Admission_Acquire, Admission_Kill, Admission_ParseCommand, Admission_ReplyInFlight,
+    AdmissionTicket, AdmissionTicket_Free, AdmissionTicket_Id, AdmissionTicket_IsKilled,
+    QueriesSubcommand,
+};
+
+fn string(ptr: *const c_char, len: usize) -> String {
+    // SAFETY: the controller passes `len` readable bytes.
+    let bytes = unsafe { std::slice::from_raw_parts(ptr.cast::<u8>(), len) };
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+unsafe extern "C" fn push_query(
+    reply: *mut c_void,
+    id: u64,
+    index: *const c_char,
+    index_len: usize,
+    query: *const c_char,
+    query_len: usize,
+    _elapsed_ms: u64,
+) {
+    // SAFETY: the tests pass a `Vec`.
+    let reply = unsafe { &mut *reply.cast::<Vec<(u64, String, String)>>() };
+    reply.push((id, string(index, index_len), string(query, query_len)));
+}
+
+/// The queries running on `index`: the tests run in parallel, sharing the
+/// controller.
+fn in_flight(index: &str) -> Vec<(u64, String)> {
+    let mut reply: Vec<(u64, String, String)> = Vec::new();
+    // SAFETY: `push_query` expects a `Vec`.
+    unsafe { Admission_ReplyInFlight((&raw mut reply).cast(), push_query) };
+    reply
+        .into_iter()
+        .filter(|(_, i, _)| i == index)
+        .map(|(id, _, query)| (id, query))
+        .collect()
+}
+
+fn acquire(index: &str, query: &str) -> *mut AdmissionTicket {
+    // SAFETY: the strings are valid for reads of their lengths.
+    let ticket = unsafe {
+        Admission_Acquire(
+            index.as_ptr().cast(),
+            index.len(),
+            query.as_ptr().cast(),
+            query.len(),
+        )
+    };
+    assert!(!ticket.is_null());
+    ticket
+}
+
+#[test]
+fn test_list_in_flight() {
+    let first = acquire("idx_list", "hello");
+    let second = acquire("idx_list", "@n:[1 2]");
+    // SAFETY: the ticket is valid.
+    let first_id = unsafe { AdmissionTicket_Id(first) };
+    // SAFETY: the ticket is valid.
+    let second_id = unsafe { AdmissionTicket_Id(second) };
+    assert_eq!(
+        in_flight("idx_list"),
+        [
+            (first_id, "hello".to_owned()),
+            (second_id, "@n:[1 2]".to_owned())
+        ]
+    );
+
+    // SAFETY: `first` was returned by `Admission_Acquire`.
+    unsafe { AdmissionTicket_Free(first) };
+    assert_eq!(in_flight("idx_list"), [(second_id, "@n:[1 2]".to_owned())]);
+    // SAFETY: `second` was returned by `Admission_Acquire`.
+    unsafe { AdmissionTicket_Free(second) };
+    assert!(in_flight("idx_list").is_empty());
+}
+
+#[test]
+fn test_kill() {
+    let ticket = acquire("idx_kill", "*");
+    // SAFETY: the ticket is valid.
+    let id = unsafe { AdmissionTicket_Id(ticket) };
+    // SAFETY: the ticket is valid.
+    assert!(!unsafe { AdmissionTicket_IsKilled(ticket) });
+
+    assert!(Admission_Kill(id));
+    // SAFETY: the ticket is valid.
+    assert!(unsafe { AdmissionTicket_IsKilled(ticket) });
+
+    // SAFETY: `ticket` was returned by `Admission_Acquire`.
+    unsafe { AdmissionTicket_Free(ticket) };
+    // The query is no longer running
+    assert!(!Admission_Kill(id));
+}
+
+#[test]
+fn test_acquire_empty_strings() {
+    // SAFETY: NULL pointers are allowed for empty strings.
+    let ticket = unsafe { Admission_Acquire(std::ptr::null(), 0, std::ptr::null(), 0) };
+    assert!(!ticket.is_null());
+    // SAFETY: the ticket is valid.
+    let id = unsafe { AdmissionTicket_Id(ticket) };
+    assert!(in_flight("").contains(&(id, String::new())));
+    // SAFETY: `ticket` was returned by `Admission_Acquire`.
+    unsafe { AdmissionTicket_Free(ticket) };
+}
+
+fn parse(args: &[&str]) -> Option<(QueriesSubcommand, u64)> {
+    let args: Vec<CString> = args.iter().map(|arg| CString::new(*arg).unwrap()).collect();
+    let argv: Vec<*const c_char> = args.iter().map(|arg| arg.as_ptr()).collect();
+    let mut subcommand = QueriesSubcommand::List;
+    let mut id = u64::MAX;
+    // SAFETY: `argv` points to `argv.len()` NUL-terminated strings, and the output pointers are
+    // valid.
+    let parsed =
+        unsafe { Admission_ParseCommand(argv.as_ptr(), argv.len(), &mut subcommand, &mut id) };
+    parsed.then_some((subcommand, id))
+}
+
+#[test]
+fn test_parse_command() {
+    assert_eq!(parse(&["LIST"]), Some((QueriesSubcommand::List, u64::MAX)));
+    assert_eq!(parse(&["kill", "7"]), Some((QueriesSubcommand::Kill, 7)));
+
+    assert_eq!(parse(&[]), None);
+    assert_eq!(parse(&["LIST", "1"]), None);
+    assert_eq!(parse(&["KILL"]), None);
+    assert_eq!(parse(&["KILL", "-1"]), None);
+    assert_eq!(parse(&["STOP", "1"]), None);
+}
@@ -0,0 +1,29 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The declarations of `admission_rs.h` the C code relies on. A failure means
+//! that the generated header changed: update the callers, then this test.
+
+use build_utils::header_declarations;
+
+#[test]
+fn test_header_declarations() {
+    let decls = header_declarations("../../headers/admission_rs.h").unwrap();
+    for expected in [
+        "struct AdmissionTicket *Admission_Acquire(const char *index, uintptr_t index_len, const char *query, uintptr_t query_len)",
+        "void AdmissionTicket_Free(struct AdmissionTicket *ticket)",
+        "uint64_t AdmissionTicket_Id(const struct AdmissionTicket *ticket)",
+        "bool AdmissionTicket_IsKilled(const struct AdmissionTicket *ticket)",
+        "bool Admission_Kill(uint64_t id)",
+        "void Admission_ReplyInFlight(void *reply, void (*query)(void *reply, uint64_t id, const char *index, uintptr_t index_len, const char *query, uintptr_t query_len, uint64_t elapsed_ms))",
+        "bool Admission_ParseCommand(const char *const *argv, uintptr_t argc, enum QueriesSubcommand *subcommand, uint64_t *id)",
+    ] {
+        assert!(decls.iter().any(|d| d == expected), "missing `{expected}`");
+    }
+}
@@ -0,0 +1,115 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! FFI layer to access, from C, the binary searches over arrays of opaque
+//! elements used by the trie to locate the children in a range.
+//!
+//! These replace the inline functions of `util/bsearch.h`, with the same
+//! names, signatures and results. Unlike them, they accept empty arrays.
+
+use std::ffi::{c_int, c_void};
+
+/// Compare the searched item `s` with the array element `elem`; return a
+/// negative value, zero or a positive value if `s` is less than, equal to or
+/// greater than `elem`.
+pub type RsbCompare = unsafe extern "C" fn(s: *const c_void, elem: *const c_void) -> c_int;
+
+/// The number of leading elements of the array for which `pred` holds. The
+/// array must be partitioned by `pred`.
+fn partition_point(
+    arr: *const c_void,
+    narr: usize,
+    elemsz: usize,
+    pred: impl Fn(*const c_void) -> bool,
+) -> usize {
+    let (mut begin, mut end) = (0, narr);
+    while begin < end {
+        let cur = begin + (end - begin) / 2;
+        if pred(arr.wrapping_byte_add(cur * elemsz)) {
+            begin = cur + 1;
+        } else {
+            end = cur;
+        }
+    }
+    begin
+}
+
+/// Find the index of the first element of the sorted array which is greater
+/// than the item `s`, or `narr` if there is none.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// 1. `arr` must point to `narr` contiguous elements of `elemsz` bytes each,
+///    sorted according to `cmp`.
+/// 2. `cmp` must be safe to call with `s` and a pointer to any element of
+///    `arr`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rsb_gt(
+    arr: *const c_void,
+    narr: usize,
+    elemsz: usize,
+    s: *const c_void,
+    cmp: RsbCompare,
+) -> c_int {
+    // Safety: Safe thanks to invariants 1. and 2.
+    let i = partition_point(arr, narr, elemsz, |elem| unsafe { cmp(s, elem) } >= 0);
+    i as c_int
+}
+
+/// Find the index of the last element of the sorted array which is less than
+/// the item `s`, or -1 if there is none.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// 1. `arr` must point to `narr` contiguous elements of `elemsz` bytes each,
+///    sorted according to `cmp`.
+/// 2. `cmp` must be safe to call with `s` and a pointer to any element of
+///    `arr`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rsb_lt(
+    arr: *const c_void,
+    narr: usize,
+    elemsz: usize,
+    s: *const c_void,
+    cmp: RsbCompare,
+) -> c_int {
+    // Safety: Safe thanks to invariants 1. and 2.
+    let i = partition_point(arr, narr, elemsz, |elem| unsafe { cmp(s, elem) } > 0);
+    i as c_int - 1
+}
+
+/// Find the index of an element of the sorted array equal to the item `s`,
+/// or -1 if there is none. The array must not have duplicate items.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// 1. `arr` must point to `narr` contiguous elements of `elemsz` bytes each,
+///    sorted according to `cmp`.
+/// 2. `cmp` must be safe to call with `s` and a pointer to any element of
+///    `arr`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rsb_eq(
+    arr: *const c_void,
+    narr: usize,
+    elemsz: usize,
+    s: *const c_void,
+    cmp: RsbCompare,
+) -> c_int {
+    // Safety: Safe thanks to invariants 1. and 2.
+    let i = partition_point(arr, narr, elemsz, |elem| unsafe { cmp(s, elem) } > 0);
+    if i == narr {
+        return -1;
+    }
+    // Safety: Safe thanks to invariants 1. and 2., since `i < narr`.
+    let rc = unsafe { cmp(s, arr.wrapping_byte_add(i * elemsz)) };
+    if rc == 0 { i as c_int } else { -1 }
+}
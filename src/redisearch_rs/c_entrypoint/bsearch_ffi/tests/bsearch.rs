@@ -0,0 +1,69 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::ffi::{c_int, c_void};
+
+use bsearch_ffi::{rsb_eq, rsb_gt, rsb_lt};
+
+unsafe extern "C" fn cmp_u32(s: *const c_void, elem: *const c_void) -> c_int {
+    // Safety: the tests below only search `u32` items in `u32` arrays.
+    let s = unsafe { *s.cast::<u32>() };
+    // Safety: as above.
+    let elem = unsafe { *elem.cast::<u32>() };
+    s.cmp(&elem) as c_int
+}
+
+fn search(
+    f: unsafe extern "C" fn(
+        *const c_void,
+        usize,
+        usize,
+        *const c_void,
+        bsearch_ffi::RsbCompare,
+    ) -> c_int,
+    arr: &[u32],
+    s: u32,
+) -> c_int {
+    // Safety: `arr` is a sorted array of `u32`, and `s` a `u32`.
+    unsafe {
+        f(
+            arr.as_ptr().cast(),
+            arr.len(),
+            size_of::<u32>(),
+            (&raw const s).cast(),
+            cmp_u32,
+        )
+    }
+}
+
+#[test]
+fn test_bounds() {
+    let arr = [10, 20, 30];
+    let cases = [
+        // (s, gt, lt, eq)
+        (5, 0, -1, -1),
+        (10, 1, -1, 0),
+        (15, 1, 0, -1),
+        (20, 2, 0, 1),
+        (30, 3, 1, 2),
+        (35, 3, 2, -1),
+    ];
+    for (s, gt, lt, eq) in cases {
+        assert_eq!(search(rsb_gt, &arr, s), gt, "rsb_gt({s})");
+        assert_eq!(search(rsb_lt, &arr, s), lt, "rsb_lt({s})");
+        assert_eq!(search(rsb_eq, &arr, s), eq, "rsb_eq({s})");
+    }
+}
+
+#[test]
+fn test_empty_array() {
+    assert_eq!(search(rsb_gt, &[], 1), 0);
+    assert_eq!(search(rsb_lt, &[], 1), -1);
+    assert_eq!(search(rsb_eq, &[], 1), -1);
+}
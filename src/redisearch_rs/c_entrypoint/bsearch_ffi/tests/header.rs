@@ -0,0 +1,26 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The declarations of `bsearch_rs.h` the C code relies on. A failure means
+//! that the generated header changed: update the callers, then this test.
+
+use build_utils::header_declarations;
+
+#[test]
+fn test_header_declarations() {
+    let decls = header_declarations("../../headers/bsearch_rs.h").unwrap();
+    for expected in [
+        "typedef int (*rsbcompare)(const void *s, const void *elem)",
+        "int rsb_gt(const void *arr, uintptr_t narr, uintptr_t elemsz, const void *s, rsbcompare cmp)",
+        "int rsb_lt(const void *arr, uintptr_t narr, uintptr_t elemsz, const void *s, rsbcompare cmp)",
+        "int rsb_eq(const void *arr, uintptr_t narr, uintptr_t elemsz, const void *s, rsbcompare cmp)",
+    ] {
+        assert!(decls.iter().any(|d| d == expected), "missing `{expected}`");
+    }
+}
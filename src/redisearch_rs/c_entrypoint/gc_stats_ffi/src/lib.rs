@@ -0,0 +1,360 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! FFI layer to record, from the C fork GC, the statistics of its runs, and to
+//! reply them to `FT.DEBUG GC_STATS` and `FT.DEBUG GC_FORCEINVOKE`.
+//!
+//! The replies are written through callbacks, which the C code implements with
+//! the `RedisModule_Reply` API.
+
+use std::{
+    ffi::{CStr, CString, c_char, c_void},
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use gc_stats::{FieldType, ForceInvoke, RunMode, RunStats, StatValue};
+
+/// The statistics of the GC of an index, accumulated over its runs. Runs are
+/// recorded by the GC thread while `FT.DEBUG GC_STATS` reads them from the
+/// main thread, hence the lock.
+pub struct GcStats(Mutex<gc_stats::GcStats>);
+
+/// The metrics of a GC run in progress.
+pub struct GcRunStats(RunStats);
+
+/// The type of the field an inverted index belongs to.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub enum GcFieldType {
+    Text,
+    Numeric,
+    Tag,
+    Geo,
+    Vector,
+}
+
+impl From<GcFieldType> for FieldType {
+    fn from(field_type: GcFieldType) -> Self {
+        match field_type {
+            GcFieldType::Text => Self::Text,
+            GcFieldType::Numeric => Self::Numeric,
+            GcFieldType::Tag => Self::Tag,
+            GcFieldType::Geo => Self::Geo,
+            GcFieldType::Vector => Self::Vector,
+        }
+    }
+}
+
+/// Create the statistics of the GC of an index. They must be freed using
+/// [`GcStats_Free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn GcStats_New() -> *mut GcStats {
+    Box::into_raw(Box::new(GcStats(Mutex::default())))
+}
+
+/// Free the statistics created using [`GcStats_New`].
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `stats` must be a valid, non NULL, pointer created using [`GcStats_New`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn GcStats_Free(stats: *mut GcStats) {
+    debug_assert!(!stats.is_null(), "stats must not be null");
+
+    // SAFETY: The caller must ensure that `stats` was created using `GcStats_New`
+    let _ = unsafe { Box::from_raw(stats) };
+}
+
+/// Start recording a GC run. A dry run only reports what it would collect. The
+/// run must be passed to [`GcStats_RecordRun`] once done.
+#[unsafe(no_mangle)]
+pub extern "C" fn GcRunStats_New(dry_run: bool) -> *mut GcRunStats {
+    let mode = if dry_run {
+        RunMode::DryRun
+    } else {
+        RunMode::Collect
+    };
+    Box::into_raw(Box::new(GcRunStats(RunStats::new(mode))))
+}
+
+/// Record what the run collected from an inverted index of a `field_type`
+/// field, where `docs` deleted documents were found. The other arguments are
+/// the fields of the `II_GCScanStats` the delta was applied, or previewed,
+/// with.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `run` must be a valid, non NULL, pointer created using [`GcRunStats_New`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn GcRunStats_Record(
+    run: *mut GcRunStats,
+    field_type: GcFieldType,
+    docs: u64,
+    entries_removed: usize,
+    bytes_freed: usize,
+    bytes_allocated: usize,
+    blocks_ignored: usize,
+) {
+    debug_assert!(!run.is_null(), "run must not be null");
+
+    // SAFETY: The caller must ensure that `run` is a valid pointer to a `GcRunStats`
+    let run = unsafe { &mut *run };
+    run.0.record(
+        field_type.into(),
+        docs,
+        &gc_stats::Collected {
+            entries_removed: entries_removed as u64,
+            bytes_freed: bytes_freed as u64,
+            bytes_allocated: bytes_allocated as u64,
+            blocks_ignored: blocks_ignored as u64,
+        },
+    );
+}
+
+/// Record that the run skipped a numeric tree which changed while it was
+/// scanned.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `run` must be a valid, non NULL, pointer created using [`GcRunStats_New`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn GcRunStats_NumericTreeMissed(run: *mut GcRunStats) {
+    debug_assert!(!run.is_null(), "run must not be null");
+
+    // SAFETY: The caller must ensure that `run` is a valid pointer to a `GcRunStats`
+    let run = unsafe { &mut *run };
+    run.0.numeric_trees_missed += 1;
+}
+
+/// Set the time the run spent in the forked child, and applying its results
+/// in the parent under the index write lock.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `run` must be a valid, non NULL, pointer created using [`GcRunStats_New`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn GcRunStats_SetTimes(run: *mut GcRunStats, forked_ms: u64, locked_ms: u64) {
+    debug_assert!(!run.is_null(), "run must not be null");
+
+    // SAFETY: The caller must ensure that `run` is a valid pointer to a `GcRunStats`
+    let run = unsafe { &mut *run };
+    run.0.forked = Duration::from_millis(forked_ms);
+    run.0.locked = Duration::from_millis(locked_ms);
+}
+
+/// Account for a finished run, taking ownership of `run`.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `stats` must be a valid, non NULL, pointer created using [`GcStats_New`].
+/// - `run` must be a valid, non NULL, pointer created using [`GcRunStats_New`]. It must not be
+///   used after this call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn GcStats_RecordRun(stats: *const GcStats, run: *mut GcRunStats) {
+    debug_assert!(!stats.is_null(), "stats must not be null");
+    debug_assert!(!run.is_null(), "run must not be null");
+
+    // SAFETY: The caller must ensure that `stats` is a valid pointer to a `GcStats`
+    let stats = unsafe { &*stats };
+    // SAFETY: The caller must ensure that `run` was created using `GcRunStats_New`
+    let run = unsafe { Box::from_raw(run) };
+    stats
+        .0
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .record_run(run.0);
+}
+
+/// Writes a statistics reply with the callbacks given by the C code.
+struct ReplyWriter {
+    reply: *mut c_void,
+    map: unsafe extern "C" fn(reply: *mut c_void, key: *const c_char),
+    map_end: unsafe extern "C" fn(reply: *mut c_void),
+    int_kv: unsafe extern "C" fn(reply: *mut c_void, key: *const c_char, value: i64),
+    double_kv: unsafe extern "C" fn(reply: *mut c_void, key: *const c_char, value: f64),
+}
+
+impl ReplyWriter {
+    /// Write `entries` in the map the reply is in, nested maps included.
+    fn write(&self, entries: &[(String, StatValue)]) {
+        for (key, value) in entries {
+            let key = CString::new(key.as_str()).expect("stat names have no NUL byte");
+            match value {
+                StatValue::Integer(value) => {
+                    // SAFETY: The callbacks are safe to call with `reply`, as required by the
+                    // functions creating the writer.
+                    unsafe { (self.int_kv)(self.reply, key.as_ptr(), *value) }
+                }
+                StatValue::Double(value) => {
+                    // SAFETY: As above.
+                    unsafe { (self.double_kv)(self.reply, key.as_ptr(), *value) }
+                }
+                StatValue::Map(entries) => {
+                    // SAFETY: As above.
+                    unsafe { (self.map)(self.reply, key.as_ptr()) };
+                    self.write(entries);
+                    // SAFETY: As above.
+                    unsafe { (self.map_end)(self.reply) };
+                }
+            }
+        }
+    }
+}
+
+/// Reply the statistics to `FT.DEBUG GC_STATS`, as the entries of the map
+/// `reply` is in.
+///
+/// `map` opens a nested map under a key, closed by `map_end`. `int_kv` and
+/// `double_kv` add an entry to the current map. They're all called with `reply`.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `stats` must be a valid, non NULL, pointer created using [`GcStats_New`].
+/// - The callbacks must be safe to call with `reply` and a NUL-terminated key.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn GcStats_Reply(
+    stats: *const GcStats,
+    reply: *mut c_void,
+    map: unsafe extern "C" fn(reply: *mut c_void, key: *const c_char),
+    map_end: unsafe extern "C" fn(reply: *mut c_void),
+    int_kv: unsafe extern "C" fn(reply: *mut c_void, key: *const c_char, value: i64),
+    double_kv: unsafe extern "C" fn(reply: *mut c_void, key: *const c_char, value: f64),
+) {
+    debug_assert!(!stats.is_null(), "stats must not be null");
+
+    // SAFETY: The caller must ensure that `stats` is a valid pointer to a `GcStats`
+    let stats = unsafe { &*stats };
+    let entries = stats
+        .0
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .reply();
+    ReplyWriter {
+        reply,
+        map,
+        map_end,
+        int_kv,
+        double_kv,
+    }
+    .write(&entries);
+}
+
+/// Whether a dry run was recorded, so that `FT.DEBUG GC_FORCEINVOKE ... DRYRUN`
+/// can reply an error before starting a reply with [`GcStats_ReplyLastDryRun`].
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `stats` must be a valid, non NULL, pointer created using [`GcStats_New`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn GcStats_HasLastDryRun(stats: *const GcStats) -> bool {
+    debug_assert!(!stats.is_null(), "stats must not be null");
+
+    // SAFETY: The caller must ensure that `stats` is a valid pointer to a `GcStats`
+    let stats = unsafe { &*stats };
+    stats
+        .0
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .last_dry_run()
+        .is_some()
+}
+
+/// Reply the metrics of the last dry run to `FT.DEBUG GC_FORCEINVOKE ...
+/// DRYRUN`, as the entries of the map `reply` is in. Returns false, writing
+/// nothing, if there was no dry run yet. See [`GcStats_Reply`] for the
+/// callbacks.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `stats` must be a valid, non NULL, pointer created using [`GcStats_New`].
+/// - The callbacks must be safe to call with `reply` and a NUL-terminated key.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn GcStats_ReplyLastDryRun(
+    stats: *const GcStats,
+    reply: *mut c_void,
+    map: unsafe extern "C" fn(reply: *mut c_void, key: *const c_char),
+    map_end: unsafe extern "C" fn(reply: *mut c_void),
+    int_kv: unsafe extern "C" fn(reply: *mut c_void, key: *const c_char, value: i64),
+    double_kv: unsafe extern "C" fn(reply: *mut c_void, key: *const c_char, value: f64),
+) -> bool {
+    debug_assert!(!stats.is_null(), "stats must not be null");
+
+    // SAFETY: The caller must ensure that `stats` is a valid pointer to a `GcStats`
+    let stats = unsafe { &*stats };
+    let Some(entries) = stats
+        .0
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .last_dry_run()
+        .map(RunStats::reply)
+    else {
+        return false;
+    };
+    ReplyWriter {
+        reply,
+        map,
+        map_end,
+        int_kv,
+        double_kv,
+    }
+    .write(&entries);
+    true
+}
+
+/// Parse the `argc` arguments of `FT.DEBUG GC_FORCEINVOKE` following the index
+/// name: an optional timeout and `DRYRUN`. Returns false, leaving the output
+/// parameters untouched, if they're invalid.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `argv` must point to `argc` valid, NUL-terminated strings. It may be NULL if `argc` is 0.
+/// - `timeout_ms` and `dry_run` must be valid, non NULL, pointers.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn GcForceInvoke_Parse(
+    argv: *const *const c_char,
+    argc: usize,
+    timeout_ms: *mut u64,
+    dry_run: *mut bool,
+) -> bool {
+    debug_assert!(!timeout_ms.is_null(), "timeout_ms must not be null");
+    debug_assert!(!dry_run.is_null(), "dry_run must not be null");
+
+    let argv = if argc == 0 {
+        &[][..]
+    } else {
+        // SAFETY: The caller must ensure that `argv` points to `argc` strings
+        unsafe { std::slice::from_raw_parts(argv, argc) }
+    };
+    let args: Option<Vec<&str>> = argv
+        .iter()
+        .map(|&arg| {
+            // SAFETY: The caller must ensure that the arguments are NUL-terminated strings
+            unsafe { CStr::from_ptr(arg) }.to_str().ok()
+        })
+        .collect();
+    let Some(Ok(invoke)) = args.map(ForceInvoke::parse) else {
+        return false;
+    };
+
+    // SAFETY: The caller must ensure that `timeout_ms` is a valid pointer
+    unsafe { *timeout_ms = invoke.timeout_ms };
+    // SAFETY: The caller must ensure that `dry_run` is a valid pointer
+    unsafe { *dry_run = invoke.mode == RunMode::DryRun };
+    true
+}
@@ -0,0 +1,182 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::ffi::{CStr, c_char, c_void};
+
+use gc_stats_ffi::{
+    GcFieldType, GcForceInvoke_Parse, GcRunStats_New, GcRunStats_NumericTreeMissed,
+    GcRunStats_Record, GcRunStats_SetTimes, GcStats_Free, GcStats_HasLastDryRun, GcStats_New,
+    GcStats_RecordRun, GcStats_Reply, GcStats_ReplyLastDryRun,
+};
+
+/// The reply as lines, nested maps indented.
+#[derive(Default)]
+struct Reply {
+    lines: Vec<String>,
+    depth: usize,
+}
+
+impl Reply {
+    fn push(&mut self, line: String) {
+        self.lines
+            .push(format!("{}{line}", "  ".repeat(self.depth)));
+    }
+}
+
+fn key(key: *const c_char) -> String {
+    // SAFETY: the keys are NUL-terminated strings.
+    unsafe { CStr::from_ptr(key) }.to_str().unwrap().to_owned()
+}
+
+unsafe extern "C" fn map(reply: *mut c_void, k: *const c_char) {
+    // SAFETY: the tests pass a `Reply`.
+    let reply = unsafe { &mut *reply.cast::<Reply>() };
+    reply.push(format!("{}:", key(k)));
+    reply.depth += 1;
+}
+
+unsafe extern "C" fn map_end(reply: *mut c_void) {
+    // SAFETY: the tests pass a `Reply`.
+    let reply = unsafe { &mut *reply.cast::<Reply>() };
+    reply.depth -= 1;
+}
+
+unsafe extern "C" fn int_kv(reply: *mut c_void, k: *const c_char, value: i64) {
+    // SAFETY: the tests pass a `Reply`.
+    let reply = unsafe { &mut *reply.cast::<Reply>() };
+    reply.push(format!("{}: {value}", key(k)));
+}
+
+unsafe extern "C" fn double_kv(reply: *mut c_void, k: *const c_char, value: f64) {
+    // SAFETY: the tests pass a `Reply`.
+    let reply = unsafe { &mut *reply.cast::<Reply>() };
+    reply.push(format!("{}: {value:.1}", key(k)));
+}
+
+#[test]
+fn test_run_and_reply() {
+    let stats = GcStats_New();
+    let run = GcRunStats_New(false);
+    // SAFETY: `run` was just created.
+    unsafe { GcRunStats_Record(run, GcFieldType::Tag, 3, 5, 200, 50, 1) };
+    // SAFETY: as above.
+    unsafe { GcRunStats_NumericTreeMissed(run) };
+    // SAFETY: as above.
+    unsafe { GcRunStats_SetTimes(run, 7, 3) };
+    // SAFETY: both were just created, and `run` isn't used after.
+    unsafe { GcStats_RecordRun(stats, run) };
+
+    let mut reply = Reply::default();
+    // SAFETY: `stats` is valid, and the callbacks are called with a `Reply`.
+    unsafe {
+        GcStats_Reply(
+            stats,
+            (&raw mut reply).cast(),
+            map,
+            map_end,
+            int_kv,
+            double_kv,
+        )
+    };
+    let lines = reply.lines;
+    assert_eq!(lines[0], "bytes_collected: 150");
+    assert_eq!(lines[1], "total_ms_run: 10");
+    assert_eq!(lines[2], "total_cycles: 1");
+    assert!(lines.contains(&"gc_numeric_trees_missed: 1.0".to_owned()));
+    assert!(lines.contains(&"fields:".to_owned()));
+    assert!(lines.contains(&"  tag:".to_owned()));
+    assert!(lines.contains(&"    docs_collected: 3".to_owned()));
+    assert!(lines.contains(&"last_run:".to_owned()));
+
+    let mut reply = Reply::default();
+    // SAFETY: as above.
+    let replied = unsafe {
+        GcStats_ReplyLastDryRun(
+            stats,
+            (&raw mut reply).cast(),
+            map,
+            map_end,
+            int_kv,
+            double_kv,
+        )
+    };
+    assert!(!replied);
+    assert!(reply.lines.is_empty());
+    // SAFETY: as above.
+    assert!(!unsafe { GcStats_HasLastDryRun(stats) });
+
+    // SAFETY: `stats` isn't used after.
+    unsafe { GcStats_Free(stats) };
+}
+
+#[test]
+fn test_dry_run() {
+    let stats = GcStats_New();
+    let run = GcRunStats_New(true);
+    // SAFETY: `run` was just created.
+    unsafe { GcRunStats_Record(run, GcFieldType::Text, 2, 4, 100, 0, 0) };
+    // SAFETY: both were just created, and `run` isn't used after.
+    unsafe { GcStats_RecordRun(stats, run) };
+
+    let mut reply = Reply::default();
+    // SAFETY: `stats` is valid, and the callbacks are called with a `Reply`.
+    let replied = unsafe {
+        GcStats_ReplyLastDryRun(
+            stats,
+            (&raw mut reply).cast(),
+            map,
+            map_end,
+            int_kv,
+            double_kv,
+        )
+    };
+    assert!(replied);
+    // SAFETY: as above.
+    assert!(unsafe { GcStats_HasLastDryRun(stats) });
+    assert_eq!(reply.lines[0], "dry_run: 1");
+    assert_eq!(reply.lines[1], "docs_collected: 2");
+
+    // The dry run doesn't count toward the totals.
+    let mut reply = Reply::default();
+    // SAFETY: as above.
+    unsafe {
+        GcStats_Reply(
+            stats,
+            (&raw mut reply).cast(),
+            map,
+            map_end,
+            int_kv,
+            double_kv,
+        )
+    };
+    assert_eq!(reply.lines[0], "bytes_collected: 0");
+    assert_eq!(reply.lines[2], "total_cycles: 0");
+
+    // SAFETY: `stats` isn't used after.
+    unsafe { GcStats_Free(stats) };
+}
+
+fn parse(args: &[&CStr]) -> Option<(u64, bool)> {
+    let argv: Vec<*const c_char> = args.iter().map(|arg| arg.as_ptr()).collect();
+    let (mut timeout_ms, mut dry_run) = (0, false);
+    // SAFETY: `argv` holds `args.len()` NUL-terminated strings.
+    let parsed =
+        unsafe { GcForceInvoke_Parse(argv.as_ptr(), argv.len(), &mut timeout_ms, &mut dry_run) };
+    parsed.then_some((timeout_ms, dry_run))
+}
+
+#[test]
+fn test_parse_force_invoke() {
+    assert_eq!(parse(&[]), Some((30_000, false)));
+    assert_eq!(parse(&[c"500", c"dryrun"]), Some((500, true)));
+    assert_eq!(parse(&[c"DRYRUN"]), Some((30_000, true)));
+    assert_eq!(parse(&[c"0"]), Some((0, false)));
+    assert_eq!(parse(&[c"99999999999999999999"]), None);
+    assert_eq!(parse(&[c"500", c"NOW"]), None);
+}
@@ -0,0 +1,27 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The declarations of `gc_stats_rs.h` the C code relies on. A failure means
+//! that the generated header changed: update the callers, then this test.
+
+use build_utils::header_declarations;
+
+#[test]
+fn test_header_declarations() {
+    let decls = header_declarations("../../headers/gc_stats_rs.h").unwrap();
+    for expected in [
+        "struct GcStats *GcStats_New(void)",
+        "struct GcRunStats *GcRunStats_New(bool dry_run)",
+        "void GcStats_RecordRun(const struct GcStats *stats, struct GcRunStats *run)",
+        "bool GcStats_HasLastDryRun(const struct GcStats *stats)",
+        "bool GcForceInvoke_Parse(const char *const *argv, uintptr_t argc, uint64_t *timeout_ms, bool *dry_run)",
+    ] {
+        assert!(decls.iter().any(|d| d == expected), "missing `{expected}`");
+    }
+}
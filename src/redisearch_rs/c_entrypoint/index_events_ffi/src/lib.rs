@@ -0,0 +1,369 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! FFI layer to publish index lifecycle events from the C code, and to
+//! subscribe to them from C modules.
+//!
+//! The events go through a process-wide [`EventBus`]. Publishing never blocks:
+//! the main thread delivers the queued events by calling
+//! [`IndexEvents_DispatchPending`] from an event-loop timer. The publishing
+//! functions return right away, without copying anything, when no subscriber
+//! is interested in their kind of event.
+
+#![allow(non_camel_case_types, non_snake_case)]
+
+use std::{
+    ffi::{c_char, c_void},
+    ptr, slice,
+    sync::{Arc, LazyLock},
+};
+
+use index_events::{
+    Envelope, EventBus, EventKind, EventMask, GcCycleStats, IndexEvent, ScanStats, SubscriptionId,
+};
+
+/// The maximum number of undelivered events. Past it, new events are dropped.
+const QUEUE_CAPACITY: usize = 1 << 16;
+
+static BUS: LazyLock<EventBus> = LazyLock::new(|| EventBus::new(QUEUE_CAPACITY));
+
+/// The mask to subscribe to all the event kinds.
+pub const INDEX_EVENTS_ALL: u8 = 0xFF;
+
+/// The type of an index lifecycle event.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexEventKind {
+    Created,
+    Dropped,
+    Altered,
+    DocIndexed,
+    DocDeleted,
+    GcCycleDone,
+    ScanFinished,
+}
+
+impl From<EventKind> for IndexEventKind {
+    fn from(kind: EventKind) -> Self {
+        match kind {
+            EventKind::Created => Self::Created,
+            EventKind::Dropped => Self::Dropped,
+            EventKind::Altered => Self::Altered,
+            EventKind::DocIndexed => Self::DocIndexed,
+            EventKind::DocDeleted => Self::DocDeleted,
+            EventKind::GcCycleDone => Self::GcCycleDone,
+            EventKind::ScanFinished => Self::ScanFinished,
+        }
+    }
+}
+
+/// The payload of a [`IndexEventKind::GcCycleDone`] event.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexEventGcStats {
+    pub bytes_collected: u64,
+    pub entries_removed: u64,
+}
+
+/// The payload of a [`IndexEventKind::ScanFinished`] event.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexEventScanStats {
+    pub docs_scanned: u64,
+    pub docs_indexed: u64,
+}
+
+/// An event, as seen by a C subscriber. The strings aren't NUL-terminated, and
+/// are only valid during the callback.
+#[repr(C)]
+#[derive(Debug)]
+pub struct IndexEventView {
+    pub seq: u64,
+    pub kind: IndexEventKind,
+    pub index: *const c_char,
+    pub index_len: usize,
+    /// The document key of the `DocIndexed` and `DocDeleted` events, NULL
+    /// otherwise.
+    pub key: *const c_char,
+    pub key_len: usize,
+    /// Zeroed unless `kind` is `GcCycleDone`.
+    pub gc: IndexEventGcStats,
+    /// Zeroed unless `kind` is `ScanFinished`.
+    pub scan: IndexEventScanStats,
+}
+
+impl IndexEventView {
+    fn new(envelope: &Envelope) -> Self {
+        let index = envelope.event.index();
+        let mut view = Self {
+            seq: envelope.seq,
+            kind: envelope.event.kind().into(),
+            index: index.as_ptr().cast(),
+            index_len: index.len(),
+            key: ptr::null(),
+            key_len: 0,
+            gc: IndexEventGcStats::default(),
+            scan: IndexEventScanStats::default(),
+        };
+        match &envelope.event {
+            IndexEvent::DocIndexed { key, .. } | IndexEvent::DocDeleted { key, .. } => {
+                view.key = key.as_ptr().cast();
+                view.key_len = key.len();
+            }
+            IndexEvent::GcCycleDone { stats, .. } => {
+                view.gc = IndexEventGcStats {
+                    bytes_collected: stats.bytes_collected,
+                    entries_removed: stats.entries_removed,
+                };
+            }
+            IndexEvent::ScanFinished { stats, .. } => {
+                view.scan = IndexEventScanStats {
+                    docs_scanned: stats.docs_scanned,
+                    docs_indexed: stats.docs_indexed,
+                };
+            }
+            IndexEvent::Created { .. }
+            | IndexEvent::Dropped { .. }
+            | IndexEvent::Altered { .. } => {}
+        }
+        view
+    }
+}
+
+/// A C subscriber, called with the `ctx` it subscribed with.
+pub type IndexEventCallback = unsafe extern "C" fn(ctx: *mut c_void, event: *const IndexEventView);
+
+struct CSubscriber {
+    callback: IndexEventCallback,
+    ctx: *mut c_void,
+}
+
+impl CSubscriber {
+    fn call(&self, envelope: &Envelope) {
+        let view = IndexEventView::new(envelope);
+        // SAFETY: Guaranteed by the caller of `IndexEvents_Subscribe`.
+        unsafe { (self.callback)(self.ctx, &view) };
+    }
+}
+
+// SAFETY: The caller of `IndexEvents_Subscribe` guarantees that `ctx` can be
+// used from the thread dispatching the events.
+unsafe impl Send for CSubscriber {}
+// SAFETY: Events are dispatched from one thread at a time.
+unsafe impl Sync for CSubscriber {}
+
+/// # Safety
+///
+/// `ptr` must point to `len` bytes, valid for reads, unless `len` is 0.
+const unsafe fn bytes<'a>(ptr: *const c_char, len: usize) -> &'a [u8] {
+    if len == 0 {
+        return &[];
+    }
+    // SAFETY: Guaranteed by the caller.
+    unsafe { slice::from_raw_parts(ptr.cast(), len) }
+}
+
+/// # Safety
+///
+/// `index` must point to `index_len` bytes, valid for reads, unless
+/// `index_len` is 0.
+unsafe fn index_name(index: *const c_char, index_len: usize) -> String {
+    // SAFETY: Guaranteed by the caller.
+    let name = unsafe { bytes(index, index_len) };
+    String::from_utf8_lossy(name).into_owned()
+}
+
+/// Publish that the index `index` was created.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `index` must point to `index_len` bytes, valid for reads, unless
+///   `index_len` is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexEvents_IndexCreated(index: *const c_char, index_len: usize) {
+    if !BUS.has_subscribers(EventKind::Created) {
+        return;
+    }
+    // SAFETY: Guaranteed by the caller.
+    let index = unsafe { index_name(index, index_len) };
+    BUS.publish(IndexEvent::Created { index });
+}
+
+/// Publish that the index `index` was dropped.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `index` must point to `index_len` bytes, valid for reads, unless
+///   `index_len` is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexEvents_IndexDropped(index: *const c_char, index_len: usize) {
+    if !BUS.has_subscribers(EventKind::Dropped) {
+        return;
+    }
+    // SAFETY: Guaranteed by the caller.
+    let index = unsafe { index_name(index, index_len) };
+    BUS.publish(IndexEvent::Dropped { index });
+}
+
+/// Publish that the schema of the index `index` was altered.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `index` must point to `index_len` bytes, valid for reads, unless
+///   `index_len` is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexEvents_IndexAltered(index: *const c_char, index_len: usize) {
+    if !BUS.has_subscribers(EventKind::Altered) {
+        return;
+    }
+    // SAFETY: Guaranteed by the caller.
+    let index = unsafe { index_name(index, index_len) };
+    BUS.publish(IndexEvent::Altered { index });
+}
+
+/// Publish that the document `key` was indexed by the index `index`.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `index` must point to `index_len` bytes, valid for reads, unless
+///   `index_len` is 0.
+/// - `key` must point to `key_len` bytes, valid for reads, unless `key_len`
+///   is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexEvents_DocIndexed(
+    index: *const c_char,
+    index_len: usize,
+    key: *const c_char,
+    key_len: usize,
+) {
+    if !BUS.has_subscribers(EventKind::DocIndexed) {
+        return;
+    }
+    // SAFETY: Guaranteed by the caller.
+    let index = unsafe { index_name(index, index_len) };
+    // SAFETY: Guaranteed by the caller.
+    let key = unsafe { bytes(key, key_len) }.to_vec();
+    BUS.publish(IndexEvent::DocIndexed { index, key });
+}
+
+/// Publish that the document `key` was deleted from the index `index`.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `index` must point to `index_len` bytes, valid for reads, unless
+///   `index_len` is 0.
+/// - `key` must point to `key_len` bytes, valid for reads, unless `key_len`
+///   is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexEvents_DocDeleted(
+    index: *const c_char,
+    index_len: usize,
+    key: *const c_char,
+    key_len: usize,
+) {
+    if !BUS.has_subscribers(EventKind::DocDeleted) {
+        return;
+    }
+    // SAFETY: Guaranteed by the caller.
+    let index = unsafe { index_name(index, index_len) };
+    // SAFETY: Guaranteed by the caller.
+    let key = unsafe { bytes(key, key_len) }.to_vec();
+    BUS.publish(IndexEvent::DocDeleted { index, key });
+}
+
+/// Publish that a GC cycle of the index `index` is done.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `index` must point to `index_len` bytes, valid for reads, unless
+///   `index_len` is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexEvents_GcCycleDone(
+    index: *const c_char,
+    index_len: usize,
+    stats: IndexEventGcStats,
+) {
+    if !BUS.has_subscribers(EventKind::GcCycleDone) {
+        return;
+    }
+    // SAFETY: Guaranteed by the caller.
+    let index = unsafe { index_name(index, index_len) };
+    let stats = GcCycleStats {
+        bytes_collected: stats.bytes_collected,
+        entries_removed: stats.entries_removed,
+    };
+    BUS.publish(IndexEvent::GcCycleDone { index, stats });
+}
+
+/// Publish that the background scan of the index `index` is done.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `index` must point to `index_len` bytes, valid for reads, unless
+///   `index_len` is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexEvents_ScanFinished(
+    index: *const c_char,
+    index_len: usize,
+    stats: IndexEventScanStats,
+) {
+    if !BUS.has_subscribers(EventKind::ScanFinished) {
+        return;
+    }
+    // SAFETY: Guaranteed by the caller.
+    let index = unsafe { index_name(index, index_len) };
+    let stats = ScanStats {
+        docs_scanned: stats.docs_scanned,
+        docs_indexed: stats.docs_indexed,
+    };
+    BUS.publish(IndexEvent::ScanFinished { index, stats });
+}
+
+/// Deliver the queued events to the subscribers, in order. Returns the number
+/// of events delivered, which is 0 when called from a subscriber.
+#[unsafe(no_mangle)]
+pub extern "C" fn IndexEvents_DispatchPending() -> usize {
+    BUS.dispatch_pending()
+}
+
+/// Call `callback` with `ctx` for every event whose kind is in `mask`, where
+/// kind `k` is the bit `1 << k`, and [`INDEX_EVENTS_ALL`] selects them all.
+/// Returns an id to pass to [`IndexEvents_Unsubscribe`].
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `callback` must be safe to call with `ctx` from the thread calling
+///   [`IndexEvents_DispatchPending`], until unsubscribed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexEvents_Subscribe(
+    mask: u8,
+    callback: IndexEventCallback,
+    ctx: *mut c_void,
+) -> u64 {
+    let subscriber = CSubscriber { callback, ctx };
+    let id = BUS.subscribe(
+        EventMask::from_bits(mask),
+        Arc::new(move |envelope: &Envelope| subscriber.call(envelope)),
+    );
+    id.as_u64()
+}
+
+/// Cancel the subscription `id`. Returns `false` if it doesn't exist.
+#[unsafe(no_mangle)]
+pub extern "C" fn IndexEvents_Unsubscribe(id: u64) -> bool {
+    BUS.unsubscribe(SubscriptionId::from_u64(id))
+}
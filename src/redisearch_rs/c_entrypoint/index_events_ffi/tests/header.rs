@@ -0,0 +1,30 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The declarations of `index_events_rs.h` the C code relies on. A failure
+//! means that the generated header changed: update the callers, then this test.
+
+use build_utils::header_declarations;
+
+#[test]
+fn test_header_declarations() {
+    let decls = header_declarations("../../headers/index_events_rs.h").unwrap();
+    for expected in [
+        "void IndexEvents_IndexCreated(const char *index, uintptr_t index_len)",
+        "void IndexEvents_IndexDropped(const char *index, uintptr_t index_len)",
+        "void IndexEvents_IndexAltered(const char *index, uintptr_t index_len)",
+        "void IndexEvents_DocIndexed(const char *index, uintptr_t index_len, const char *key, uintptr_t key_len)",
+        "void IndexEvents_DocDeleted(const char *index, uintptr_t index_len, const char *key, uintptr_t key_len)",
+        "void IndexEvents_GcCycleDone(const char *index, uintptr_t index_len, struct IndexEventGcStats stats)",
+        "void IndexEvents_ScanFinished(const char *index, uintptr_t index_len, struct IndexEventScanStats stats)",
+        "uintptr_t IndexEvents_DispatchPending(void)",
+    ] {
+        assert!(decls.iter().any(|d| d == expected), "missing `{expected}`");
+    }
+}
@@ -0,0 +1,202 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{
+    ffi::{c_char, c_void},
+    slice,
+    sync::Mutex,
+};
+
+use index_events_ffi::{
+    INDEX_EVENTS_ALL, IndexEventGcStats, IndexEventKind, IndexEventScanStats, IndexEventView,
+    IndexEvents_DispatchPending, IndexEvents_DocDeleted, IndexEvents_DocIndexed,
+    IndexEvents_GcCycleDone, IndexEvents_IndexCreated, IndexEvents_IndexDropped,
+    IndexEvents_ScanFinished, IndexEvents_Subscribe, IndexEvents_Unsubscribe,
+};
+
+/// The bus is process-wide: serialize the tests so they don't see each
+/// other's events.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, PartialEq)]
+struct Received {
+    kind: IndexEventKind,
+    index: String,
+    key: Option<Vec<u8>>,
+    gc: IndexEventGcStats,
+    scan: IndexEventScanStats,
+}
+
+unsafe extern "C" fn record(ctx: *mut c_void, event: *const IndexEventView) {
+    // SAFETY: The tests subscribe with a `Vec<Received>`.
+    let log = unsafe { &mut *ctx.cast::<Vec<Received>>() };
+    // SAFETY: The bus passes a valid event.
+    let event = unsafe { &*event };
+    // SAFETY: The index name is valid during the callback.
+    let index = unsafe { slice::from_raw_parts(event.index.cast::<u8>(), event.index_len) };
+    let key = (!event.key.is_null()).then(|| {
+        // SAFETY: The key is valid during the callback.
+        unsafe { slice::from_raw_parts(event.key.cast::<u8>(), event.key_len) }.to_vec()
+    });
+    log.push(Received {
+        kind: event.kind,
+        index: String::from_utf8(index.to_vec()).unwrap(),
+        key,
+        gc: event.gc,
+        scan: event.scan,
+    });
+}
+
+const fn str_arg(s: &str) -> (*const c_char, usize) {
+    (s.as_ptr().cast(), s.len())
+}
+
+#[test]
+fn test_publish_and_dispatch() {
+    let _serial = SERIAL.lock().unwrap();
+    let mut log: Vec<Received> = Vec::new();
+    // SAFETY: `log` outlives the subscription, and is only used by the
+    // dispatching thread.
+    let id = unsafe { IndexEvents_Subscribe(INDEX_EVENTS_ALL, record, (&raw mut log).cast()) };
+
+    let (idx, idx_len) = str_arg("idx");
+    let (key, key_len) = str_arg("doc:1");
+    // SAFETY: The strings are valid.
+    unsafe {
+        IndexEvents_IndexCreated(idx, idx_len);
+    }
+    // SAFETY: The strings are valid.
+    unsafe {
+        IndexEvents_DocIndexed(idx, idx_len, key, key_len);
+    }
+    // SAFETY: The strings are valid.
+    unsafe {
+        IndexEvents_GcCycleDone(
+            idx,
+            idx_len,
+            IndexEventGcStats {
+                bytes_collected: 100,
+                entries_removed: 3,
+            },
+        );
+    }
+    assert!(log.is_empty());
+    assert_eq!(IndexEvents_DispatchPending(), 3);
+    assert!(IndexEvents_Unsubscribe(id));
+    assert!(!IndexEvents_Unsubscribe(id));
+
+    let received = |kind, key: Option<&[u8]>, gc| Received {
+        kind,
+        index: "idx".to_owned(),
+        key: key.map(<[u8]>::to_vec),
+        gc,
+        scan: IndexEventScanStats::default(),
+    };
+    assert_eq!(
+        log,
+        [
+            received(IndexEventKind::Created, None, IndexEventGcStats::default()),
+            received(
+                IndexEventKind::DocIndexed,
+                Some(b"doc:1"),
+                IndexEventGcStats::default()
+            ),
+            received(
+                IndexEventKind::GcCycleDone,
+                None,
+                IndexEventGcStats {
+                    bytes_collected: 100,
+                    entries_removed: 3,
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_subscribe_with_mask() {
+    let _serial = SERIAL.lock().unwrap();
+    let mut log: Vec<Received> = Vec::new();
+    let mask = 1 << IndexEventKind::ScanFinished as u8 | 1 << IndexEventKind::DocDeleted as u8;
+    // SAFETY: `log` outlives the subscription, and is only used by the
+    // dispatching thread.
+    let id = unsafe { IndexEvents_Subscribe(mask, record, (&raw mut log).cast()) };
+
+    let (idx, idx_len) = str_arg("idx");
+    let (key, key_len) = str_arg("doc:1");
+    // SAFETY: The strings are valid.
+    unsafe {
+        IndexEvents_IndexDropped(idx, idx_len);
+    }
+    // SAFETY: The strings are valid.
+    unsafe {
+        IndexEvents_DocDeleted(idx, idx_len, key, key_len);
+    }
+    let scan = IndexEventScanStats {
+        docs_scanned: 10,
+        docs_indexed: 7,
+    };
+    // SAFETY: The string is valid.
+    unsafe {
+        IndexEvents_ScanFinished(idx, idx_len, scan);
+    }
+    // Nobody subscribes to `Dropped`: it isn't even queued.
+    assert_eq!(IndexEvents_DispatchPending(), 2);
+    assert!(IndexEvents_Unsubscribe(id));
+
+    let kinds: Vec<_> = log.iter().map(|r| r.kind).collect();
+    assert_eq!(
+        kinds,
+        [IndexEventKind::DocDeleted, IndexEventKind::ScanFinished]
+    );
+    assert_eq!(log[0].key.as_deref(), Some(&b"doc:1"[..]));
+    assert_eq!(log[1].scan, scan);
+    assert_eq!(log[1].gc, IndexEventGcStats::default());
+}
+
+#[test]
+fn test_publish_without_subscribers() {
+    let _serial = SERIAL.lock().unwrap();
+    let (idx, idx_len) = str_arg("idx");
+    let (key, key_len) = str_arg("doc:1");
+    // SAFETY: The strings are valid.
+    unsafe {
+        IndexEvents_DocIndexed(idx, idx_len, key, key_len);
+    }
+    // SAFETY: The strings are valid.
+    unsafe {
+        IndexEvents_DocDeleted(idx, idx_len, key, key_len);
+    }
+    assert_eq!(IndexEvents_DispatchPending(), 0);
+}
+
+unsafe extern "C" fn redispatch(ctx: *mut c_void, _event: *const IndexEventView) {
+    // SAFETY: The test subscribes with a `Vec<usize>`.
+    let nested = unsafe { &mut *ctx.cast::<Vec<usize>>() };
+    nested.push(IndexEvents_DispatchPending());
+}
+
+#[test]
+fn test_reentrant_dispatch() {
+    let _serial = SERIAL.lock().unwrap();
+    let mut nested: Vec<usize> = Vec::new();
+    // SAFETY: `nested` outlives the subscription, and is only used by the
+    // dispatching thread.
+    let id =
+        unsafe { IndexEvents_Subscribe(INDEX_EVENTS_ALL, redispatch, (&raw mut nested).cast()) };
+
+    let (idx, idx_len) = str_arg("idx");
+    // SAFETY: The string is valid.
+    unsafe {
+        IndexEvents_IndexCreated(idx, idx_len);
+    }
+    assert_eq!(IndexEvents_DispatchPending(), 1);
+    assert!(IndexEvents_Unsubscribe(id));
+    assert_eq!(nested, [0]);
+}
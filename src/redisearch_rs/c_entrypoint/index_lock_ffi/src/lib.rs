@@ -0,0 +1,187 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! FFI layer to lock the indexes from the C code with an [`IndexLock`], and
+//! to report its contention statistics to `FT.DEBUG LOCKSTATS`.
+
+use index_lock::{LockStats, WaitHistogram};
+
+/// The number of buckets of the wait histograms of [`IndexLockStats`].
+pub const INDEX_LOCK_WAIT_BUCKETS: usize = 24;
+
+const _: () = assert!(INDEX_LOCK_WAIT_BUCKETS == WaitHistogram::BUCKETS);
+
+/// The reader/writer lock of an index. It has no guards: the C code releases
+/// it using [`IndexLock_Unlock`].
+pub struct IndexLock(index_lock::IndexLock<()>);
+
+/// The contention statistics of an [`IndexLock`]. Bucket `i` of a wait
+/// histogram counts the waits shorter than `2^i` microseconds, and at least
+/// `2^(i-1)`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexLockStats {
+    pub read_acquisitions: u64,
+    pub write_acquisitions: u64,
+    /// Acquisitions that couldn't enter immediately.
+    pub read_contended: u64,
+    pub write_contended: u64,
+    pub max_read_wait_us: u64,
+    pub max_write_wait_us: u64,
+    /// An upper bound of the 99th percentile of the contended waits, 0 if
+    /// there were none.
+    pub read_wait_p99_us: u64,
+    pub write_wait_p99_us: u64,
+    pub read_waits: [u64; INDEX_LOCK_WAIT_BUCKETS],
+    pub write_waits: [u64; INDEX_LOCK_WAIT_BUCKETS],
+}
+
+impl From<LockStats> for IndexLockStats {
+    fn from(stats: LockStats) -> Self {
+        let micros =
+            |wait: std::time::Duration| u64::try_from(wait.as_micros()).unwrap_or(u64::MAX);
+        let p99 = |waits: &WaitHistogram| waits.quantile_upper_bound_micros(0.99).unwrap_or(0);
+        Self {
+            read_acquisitions: stats.read_acquisitions,
+            write_acquisitions: stats.write_acquisitions,
+            read_contended: stats.read_contended,
+            write_contended: stats.write_contended,
+            max_read_wait_us: micros(stats.max_read_wait),
+            max_write_wait_us: micros(stats.max_write_wait),
+            read_wait_p99_us: p99(&stats.read_waits),
+            write_wait_p99_us: p99(&stats.write_waits),
+            read_waits: stats.read_waits.buckets,
+            write_waits: stats.write_waits.buckets,
+        }
+    }
+}
+
+/// Create the lock of an index. It must be freed using [`IndexLock_Free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn IndexLock_New() -> *mut IndexLock {
+    Box::into_raw(Box::new(IndexLock(index_lock::IndexLock::new(()))))
+}
+
+/// Free a lock created using [`IndexLock_New`].
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`],
+///   which isn't held.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexLock_Free(lock: *mut IndexLock) {
+    debug_assert!(!lock.is_null(), "lock must not be null");
+
+    // SAFETY: The caller must ensure that `lock` was created using `IndexLock_New`
+    drop(unsafe { Box::from_raw(lock) });
+}
+
+/// Lock for reading, waiting as long as necessary.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexLock_ReadLock(lock: *const IndexLock) {
+    debug_assert!(!lock.is_null(), "lock must not be null");
+
+    // SAFETY: The caller must ensure that `lock` is a valid pointer to an `IndexLock`
+    unsafe { &*lock }.0.lock_read();
+}
+
+/// Lock for writing, waiting as long as necessary.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexLock_WriteLock(lock: *const IndexLock) {
+    debug_assert!(!lock.is_null(), "lock must not be null");
+
+    // SAFETY: The caller must ensure that `lock` is a valid pointer to an `IndexLock`
+    unsafe { &*lock }.0.lock_write();
+}
+
+/// Lock for reading if that's possible without waiting, returning whether it
+/// succeeded.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexLock_TryReadLock(lock: *const IndexLock) -> bool {
+    debug_assert!(!lock.is_null(), "lock must not be null");
+
+    // SAFETY: The caller must ensure that `lock` is a valid pointer to an `IndexLock`
+    unsafe { &*lock }.0.try_lock_read()
+}
+
+/// Lock for writing if that's possible without waiting, returning whether it
+/// succeeded.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexLock_TryWriteLock(lock: *const IndexLock) -> bool {
+    debug_assert!(!lock.is_null(), "lock must not be null");
+
+    // SAFETY: The caller must ensure that `lock` is a valid pointer to an `IndexLock`
+    unsafe { &*lock }.0.try_lock_write()
+}
+
+/// Release the lock, held for reading or writing.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`].
+/// - The calling thread must hold the lock.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexLock_Unlock(lock: *const IndexLock) {
+    debug_assert!(!lock.is_null(), "lock must not be null");
+
+    // SAFETY: The caller must ensure that `lock` is a valid pointer to an `IndexLock`
+    let lock = unsafe { &*lock };
+    // SAFETY: The caller must ensure that it holds the lock
+    unsafe { lock.0.unlock() };
+}
+
+/// The contention statistics of the lock.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexLock_Stats(lock: *const IndexLock) -> IndexLockStats {
+    debug_assert!(!lock.is_null(), "lock must not be null");
+
+    // SAFETY: The caller must ensure that `lock` is a valid pointer to an `IndexLock`
+    unsafe { &*lock }.0.stats().into()
+}
+
+/// Reset the contention statistics of the lock.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexLock_ResetStats(lock: *const IndexLock) {
+    debug_assert!(!lock.is_null(), "lock must not be null");
+
+    // SAFETY: The caller must ensure that `lock` is a valid pointer to an `IndexLock`
+    unsafe { &*lock }.0.reset_stats();
+}
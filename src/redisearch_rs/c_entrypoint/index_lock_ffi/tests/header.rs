@@ -0,0 +1,31 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The declarations of `index_lock_rs.h` the C code relies on. A failure means
+//! that the generated header changed: update the callers, then this test.
+
+use build_utils::header_declarations;
+
+#[test]
+fn test_header_declarations() {
+    let decls = header_declarations("../../headers/index_lock_rs.h").unwrap();
+    for expected in [
+        "struct IndexLock *IndexLock_New(void)",
+        "void IndexLock_Free(struct IndexLock *lock)",
+        "void IndexLock_ReadLock(const struct IndexLock *lock)",
+        "void IndexLock_WriteLock(const struct IndexLock *lock)",
+        "bool IndexLock_TryReadLock(const struct IndexLock *lock)",
+        "bool IndexLock_TryWriteLock(const struct IndexLock *lock)",
+        "void IndexLock_Unlock(const struct IndexLock *lock)",
+        "struct IndexLockStats IndexLock_Stats(const struct IndexLock *lock)",
+        "void IndexLock_ResetStats(const struct IndexLock *lock)",
+    ] {
+        assert!(decls.iter().any(|d| d == expected), "missing `{expected}`");
+    }
+}
@@ -0,0 +1,77 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{sync::mpsc, thread, time::Duration};
+
+use index_lock_ffi::{
+    IndexLock, IndexLock_Free, IndexLock_New, IndexLock_ReadLock, IndexLock_ResetStats,
+    IndexLock_Stats, IndexLock_TryReadLock, IndexLock_TryWriteLock, IndexLock_Unlock,
+    IndexLock_WriteLock,
+};
+
+/// Lets a lock be used from another thread.
+#[derive(Clone, Copy)]
+struct SendLock(*mut IndexLock);
+
+// SAFETY: `IndexLock` is `Sync`.
+unsafe impl Send for SendLock {}
+
+impl SendLock {
+    const fn get(self) -> *mut IndexLock {
+        self.0
+    }
+}
+
+#[test]
+fn test_lock_and_stats() {
+    let lock = IndexLock_New();
+    // SAFETY: `lock` is valid.
+    unsafe { IndexLock_ReadLock(lock) };
+    // SAFETY: `lock` is valid.
+    assert!(!unsafe { IndexLock_TryWriteLock(lock) });
+    // SAFETY: `lock` is valid, and held for reading.
+    unsafe { IndexLock_Unlock(lock) };
+
+    // SAFETY: `lock` is valid.
+    unsafe { IndexLock_WriteLock(lock) };
+    // SAFETY: `lock` is valid.
+    assert!(!unsafe { IndexLock_TryReadLock(lock) });
+    let (tx, rx) = mpsc::channel();
+    let shared = SendLock(lock);
+    let reader = thread::spawn(move || {
+        let lock = shared.get();
+        // SAFETY: `lock` is valid until joined.
+        unsafe { IndexLock_ReadLock(lock) };
+        tx.send(()).unwrap();
+        // SAFETY: `lock` is valid, and held for reading.
+        unsafe { IndexLock_Unlock(lock) };
+    });
+    thread::sleep(Duration::from_millis(20));
+    assert!(rx.try_recv().is_err());
+    // SAFETY: `lock` is valid, and held for writing.
+    unsafe { IndexLock_Unlock(lock) };
+    reader.join().unwrap();
+
+    // SAFETY: `lock` is valid.
+    let stats = unsafe { IndexLock_Stats(lock) };
+    assert_eq!(stats.read_acquisitions, 2);
+    assert_eq!(stats.write_acquisitions, 1);
+    assert_eq!(stats.read_contended, 1);
+    assert_eq!(stats.write_contended, 0);
+    assert_eq!(stats.read_waits.iter().sum::<u64>(), 1);
+    assert!(stats.read_wait_p99_us >= stats.max_read_wait_us);
+    assert_eq!(stats.write_wait_p99_us, 0);
+
+    // SAFETY: `lock` is valid.
+    unsafe { IndexLock_ResetStats(lock) };
+    // SAFETY: `lock` is valid.
+    assert_eq!(unsafe { IndexLock_Stats(lock) }.read_acquisitions, 0);
+    // SAFETY: `lock` is valid, and not held.
+    unsafe { IndexLock_Free(lock) };
+}
@@ -13,10 +13,11 @@
 mod fork_gc;
 
 use std::{
-    ffi::{c_char, c_void},
+    ffi::{c_char, c_int, c_void},
     fmt::Debug,
 };
 
+use defrag::{CallbackDefragger, DefragStatus};
 use ffi::{
     DocTable_Exists, IndexFlags, IndexFlags_Index_DocIdsOnly, IndexFlags_Index_StoreFieldFlags,
     IndexFlags_Index_StoreFreqs, IndexFlags_Index_StoreNumeric, IndexFlags_Index_StoreTermOffsets,
@@ -25,9 +26,8 @@ use ffi::{
 
 use fork_gc::{InvertedIndexGCCallback, InvertedIndexGCReader, InvertedIndexGCWriter};
 use inverted_index::{
-    EntriesTrackingIndex, FieldMaskTrackingIndex, FilterGeoReader, FilterMaskReader,
-    FilterNumericReader, GcApplyInfo, GcScanDelta, IndexBlock, IndexReader as _, NumericFilter,
-    RSIndexResult, ReadFilter,
+    FieldMaskTrackingIndex, FilterGeoReader, FilterMaskReader, GcApplyInfo, GcScanDelta,
+    IndexBlock, IndexReader as _, NumericFilter, RSIndexResult, ReadFilter,
     debug::{BlockSummary, Summary},
     doc_ids_only::DocIdsOnly,
     fields_offsets::{FieldsOffsets, FieldsOffsetsWide},
@@ -37,6 +37,7 @@ use inverted_index::{
     freqs_only::FreqsOnly,
     full::{Full, FullWide},
     numeric::Numeric,
+    numeric_index::{NumericIndex, NumericRangeReader},
     offsets_only::OffsetsOnly,
     raw_doc_ids_only::RawDocIdsOnly,
 };
@@ -74,8 +75,9 @@ pub enum InvertedIndex {
     FreqsOffsets(inverted_index::InvertedIndex<FreqsOffsets>),
     DocumentIdOnly(inverted_index::InvertedIndex<DocIdsOnly>),
     RawDocumentIdOnly(inverted_index::InvertedIndex<RawDocIdsOnly>),
-    // Needs to track the entries count because it has the `StoreNumeric` flag set
-    Numeric(EntriesTrackingIndex<Numeric>),
+    // Keeps the value range of each block so the range tree's queries skip blocks, and tracks
+    // the entries count because it has the `StoreNumeric` flag set
+    Numeric(NumericIndex),
 }
 
 impl Debug for InvertedIndex {
@@ -228,9 +230,9 @@ pub extern "C" fn NewInvertedIndex_Ex(
             inverted_index::InvertedIndex::new(flags, RawDocIdsOnly),
         ),
         (NUMERIC_MASK, _, false) => {
-            InvertedIndex::Numeric(EntriesTrackingIndex::new(flags, Numeric::new()))
+            InvertedIndex::Numeric(NumericIndex::new(flags, Numeric::new()))
         }
-        (NUMERIC_MASK, _, true) => InvertedIndex::Numeric(EntriesTrackingIndex::new(
+        (NUMERIC_MASK, _, true) => InvertedIndex::Numeric(NumericIndex::new(
             flags,
             Numeric::new().with_float_compression(),
         )),
@@ -554,6 +556,44 @@ pub unsafe extern "C" fn InvertedIndex_GcMarkerInc(ii: *mut InvertedIndex) {
     ii_dispatch!(ii, gc_marker_inc);
 }
 
+/// Relocate the blocks of the inverted index during active defragmentation,
+/// starting at block `*cursor`.
+///
+/// `alloc` and `should_stop` are `RedisModule_DefragAlloc` and
+/// `RedisModule_DefragShouldStop`, called with `ctx`. Returns `true` once the
+/// whole index has been processed, resetting `*cursor` to 0. Returns `false`
+/// if `should_stop` interrupted the pass, in which case the call must be
+/// repeated later with the same cursor.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `ii` must be a valid, non NULL, pointer to an `InvertedIndex` instance, not accessed by
+///   any other thread during the call.
+/// - `cursor` must be a valid, non NULL, pointer to a cursor, initially 0.
+/// - `alloc` must behave like `RedisModule_DefragAlloc`, and both callbacks must be safe to
+///   call with `ctx`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn InvertedIndex_Defrag(
+    ii: *mut InvertedIndex,
+    cursor: *mut usize,
+    ctx: *mut c_void,
+    alloc: unsafe extern "C" fn(ctx: *mut c_void, ptr: *mut c_void) -> *mut c_void,
+    should_stop: unsafe extern "C" fn(ctx: *mut c_void) -> c_int,
+) -> bool {
+    debug_assert!(!ii.is_null(), "ii must not be null");
+    debug_assert!(!cursor.is_null(), "cursor must not be null");
+
+    // SAFETY: The caller must ensure that `ii` is a valid pointer to an `InvertedIndex`
+    let ii = unsafe { &mut *ii };
+    // SAFETY: The caller must ensure that `cursor` is a valid pointer
+    let cursor = unsafe { &mut *cursor };
+    // SAFETY: The caller must ensure the callbacks behave as the Redis defrag API
+    let mut defragger = unsafe { CallbackDefragger::new(ctx, alloc, should_stop) };
+
+    ii_dispatch!(ii, defrag, cursor, &mut defragger) == DefragStatus::Done
+}
+
 /// Setting to pass to the GC scan function
 #[repr(C)]
 pub struct IndexRepairParams {
@@ -713,6 +753,42 @@ pub unsafe extern "C" fn InvertedIndex_ApplyGcDelta(
     unsafe { *apply_info = info };
 }
 
+/// Report what applying a GC delta to the inverted index would do, without modifying the index.
+/// This is the dry run of the GC. The output parameter `apply_info` will be set to what
+/// [`InvertedIndex_ApplyGcDelta`] would set it to.
+///
+/// Unlike [`InvertedIndex_ApplyGcDelta`], this doesn't take ownership of the `deltas` pointer,
+/// which must still be freed using [`InvertedIndex_GcDelta_Free`].
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `ii` must be a valid, non NULL, pointer to an `InvertedIndex` instance.
+/// - `deltas` must be a valid, non NULL, pointer to a `GcScanDelta` instance created using
+///   [`InvertedIndex_GcDelta_Read`].
+/// - `apply_info` must be a valid, non NULL, pointer to a `GcApplyInfo` instance.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn InvertedIndex_PreviewGcDelta(
+    ii: *const InvertedIndex,
+    deltas: *const GcScanDelta,
+    apply_info: *mut GcApplyInfo,
+) {
+    debug_assert!(!ii.is_null(), "ii must not be null");
+    debug_assert!(!deltas.is_null(), "deltas must not be null");
+    debug_assert!(!apply_info.is_null(), "apply_info must not be null");
+
+    // SAFETY: The caller must ensure that `ii` is a valid pointer to an `InvertedIndex`
+    let ii = unsafe { &*ii };
+
+    // SAFETY: The caller must ensure `deltas` is a valid pointer to a `GcScanDelta`
+    let deltas = unsafe { &*deltas };
+
+    let info = ii_dispatch!(ii, preview_gc, deltas);
+
+    // SAFETY: The caller must ensure `apply_info` is a valid pointer to a `GcApplyInfo`
+    unsafe { *apply_info = info };
+}
+
 /// Get the index of the last block in the GC delta.
 ///
 /// # Safety
@@ -729,6 +805,22 @@ pub unsafe extern "C" fn GcScanDelta_LastBlockIdx(gc_scan_delta: *const GcScanDe
     gc_scan_delta.last_block_idx()
 }
 
+/// Get the number of unique documents applying the GC delta removes from the index.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `gc_scan_delta` must be a valid, non NULL, pointer to a `GcScanDelta` instance.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn GcScanDelta_UniqueDocsRemoved(gc_scan_delta: *const GcScanDelta) -> u32 {
+    debug_assert!(!gc_scan_delta.is_null(), "gc_scan_delta must not be null");
+
+    // SAFETY: The caller must ensure `gc_scan_delta` is a valid pointer to a `GcScanDelta`
+    let gc_scan_delta = unsafe { &*gc_scan_delta };
+
+    gc_scan_delta.unique_docs_removed()
+}
+
 /// Get ID of the first document in the index block. This is used by some C tests.
 ///
 /// # Safety
@@ -844,12 +936,7 @@ pub enum IndexReader<'index_and_filter> {
         inverted_index::IndexReaderCore<'index_and_filter, RawDocIdsOnly, RawDocIdsOnly>,
     ),
     Numeric(inverted_index::IndexReaderCore<'index_and_filter, Numeric, Numeric>),
-    NumericFiltered(
-        FilterNumericReader<
-            'index_and_filter,
-            inverted_index::IndexReaderCore<'index_and_filter, Numeric, Numeric>,
-        >,
-    ),
+    NumericFiltered(NumericRangeReader<'index_and_filter>),
     NumericGeoFiltered(
         FilterGeoReader<
             'index_and_filter,
@@ -935,7 +1022,7 @@ pub unsafe extern "C" fn NewIndexReader(
         (InvertedIndex::RawDocumentIdOnly(ii), _) => IndexReader::RawDocumentIdOnly(ii.reader()),
         (InvertedIndex::Numeric(ii), ReadFilter::None) => IndexReader::Numeric(ii.reader()),
         (InvertedIndex::Numeric(ii), ReadFilter::Numeric(filter)) if filter.is_numeric_filter() => {
-            IndexReader::NumericFiltered(FilterNumericReader::new(filter, ii.reader()))
+            IndexReader::NumericFiltered(ii.range_reader(filter))
         }
         (InvertedIndex::Numeric(ii), ReadFilter::Numeric(filter)) => {
             IndexReader::NumericGeoFiltered(FilterGeoReader::new(filter, ii.reader()))
@@ -1043,10 +1130,10 @@ pub unsafe extern "C" fn IndexReader_IsIndex(
         (IndexReader::RawDocumentIdOnly(ir), InvertedIndex::RawDocumentIdOnly(ii)) => {
             ir.is_index(ii)
         }
-        (IndexReader::Numeric(ir), InvertedIndex::Numeric(ii)) => ir.is_index(ii.inner()),
-        (IndexReader::NumericFiltered(ir), InvertedIndex::Numeric(ii)) => ir.is_index(ii.inner()),
+        (IndexReader::Numeric(ir), InvertedIndex::Numeric(ii)) => ir.is_index(ii.inner().inner()),
+        (IndexReader::NumericFiltered(ir), InvertedIndex::Numeric(ii)) => ir.is_index(ii),
         (IndexReader::NumericGeoFiltered(ir), InvertedIndex::Numeric(ii)) => {
-            ir.is_index(ii.inner())
+            ir.is_index(ii.inner().inner())
         }
         _ => false,
     }
@@ -1203,6 +1290,40 @@ pub unsafe extern "C" fn IndexReader_NumericFilter(ir: *const IndexReader) -> *c
     }
 }
 
+/// Get the number of blocks the index reader skipped by their value range and the number it
+/// decoded so far, for the profile. Returns false, leaving the output parameters untouched, if the
+/// reader doesn't skip blocks, which is the case of all but the numeric range readers.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `ir` must be a valid, non NULL, pointer to an `IndexReader` instance.
+/// - `skipped` and `decoded` must be valid, non NULL, pointers to a `u64`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn IndexReader_PruningStats(
+    ir: *const IndexReader,
+    skipped: *mut u64,
+    decoded: *mut u64,
+) -> bool {
+    debug_assert!(!ir.is_null(), "ir must not be null");
+    debug_assert!(!skipped.is_null(), "skipped must not be null");
+    debug_assert!(!decoded.is_null(), "decoded must not be null");
+
+    // SAFETY: The caller must ensure that `ir` is a valid pointer to an `IndexReader`
+    let ir = unsafe { &*ir };
+
+    let IndexReader::NumericFiltered(ir) = ir else {
+        return false;
+    };
+    let stats = ir.stats();
+
+    // SAFETY: The caller must ensure that `skipped` is a valid pointer to a `u64`
+    unsafe { *skipped = stats.blocks_skipped };
+    // SAFETY: The caller must ensure that `decoded` is a valid pointer to a `u64`
+    unsafe { *decoded = stats.blocks_decoded };
+    true
+}
+
 /// Swap the inverted index of the reader with the given inverted index. This is only used by some
 /// C tests to trigger revalidation on the reader.
 ///
@@ -1263,12 +1384,15 @@ pub unsafe extern "C" fn IndexReader_SwapIndex(ir: *mut IndexReader, ii: *const
             let mut ii = ii;
             ir.swap_index(&mut ii)
         }
-        (IndexReader::Numeric(ir), InvertedIndex::Numeric(ii)) => ir.swap_index(&mut ii.inner()),
+        (IndexReader::Numeric(ir), InvertedIndex::Numeric(ii)) => {
+            ir.swap_index(&mut ii.inner().inner())
+        }
         (IndexReader::NumericFiltered(ir), InvertedIndex::Numeric(ii)) => {
-            ir.swap_index(&mut ii.inner())
+            let mut ii = ii;
+            ir.swap_index(&mut ii)
         }
         (IndexReader::NumericGeoFiltered(ir), InvertedIndex::Numeric(ii)) => {
-            ir.swap_index(&mut ii.inner())
+            ir.swap_index(&mut ii.inner().inner())
         }
         _ => {}
     }
@@ -0,0 +1,163 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! FFI layer to record, from the C code, the memory used by the structures of
+//! an index, and to reply it to `FT.DEBUG MEMUSAGE`.
+//!
+//! The reply is written through callbacks, which the C code implements with
+//! the `RedisModule_Reply` API.
+
+use std::ffi::{CString, c_char, c_void};
+
+use mem_usage::{MemValue, Structure};
+
+/// The memory used by the structures of an index, in bytes.
+pub struct MemUsage(mem_usage::MemUsage);
+
+/// A kind of structure of an index.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub enum MemStructure {
+    TermTrie,
+    Postings,
+    Offsets,
+    NumericTree,
+    TagTrie,
+    DocTable,
+    Sortables,
+    VectorGraph,
+    SuffixTrie,
+}
+
+impl From<MemStructure> for Structure {
+    fn from(structure: MemStructure) -> Self {
+        match structure {
+            MemStructure::TermTrie => Self::TermTrie,
+            MemStructure::Postings => Self::Postings,
+            MemStructure::Offsets => Self::Offsets,
+            MemStructure::NumericTree => Self::NumericTree,
+            MemStructure::TagTrie => Self::TagTrie,
+            MemStructure::DocTable => Self::DocTable,
+            MemStructure::Sortables => Self::Sortables,
+            MemStructure::VectorGraph => Self::VectorGraph,
+            MemStructure::SuffixTrie => Self::SuffixTrie,
+        }
+    }
+}
+
+/// Create an empty memory usage. It must be freed using [`MemUsage_Free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn MemUsage_New() -> *mut MemUsage {
+    Box::into_raw(Box::new(MemUsage(mem_usage::MemUsage::default())))
+}
+
+/// Free the memory usage created using [`MemUsage_New`].
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `usage` must be a valid, non NULL, pointer created using [`MemUsage_New`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn MemUsage_Free(usage: *mut MemUsage) {
+    debug_assert!(!usage.is_null(), "usage must not be null");
+
+    // SAFETY: The caller must ensure that `usage` was created using `MemUsage_New`
+    let _ = unsafe { Box::from_raw(usage) };
+}
+
+/// Add `bytes` used by `structure`. `instance` is the field or encoding of the
+/// structures with several instances, and is ignored for the others.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `usage` must be a valid, non NULL, pointer created using [`MemUsage_New`].
+/// - `instance` must be valid for reads of `instance_len` bytes. It may be NULL if `instance_len` is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn MemUsage_Record(
+    usage: *mut MemUsage,
+    structure: MemStructure,
+    instance: *const c_char,
+    instance_len: usize,
+    bytes: usize,
+) {
+    debug_assert!(!usage.is_null(), "usage must not be null");
+
+    let instance = if instance_len == 0 {
+        String::new()
+    } else {
+        // SAFETY: The caller must ensure that `instance` is valid for reads of `instance_len` bytes
+        let bytes = unsafe { std::slice::from_raw_parts(instance.cast::<u8>(), instance_len) };
+        String::from_utf8_lossy(bytes).into_owned()
+    };
+    // SAFETY: The caller must ensure that `usage` is a valid pointer created using `MemUsage_New`
+    let usage = unsafe { &mut *usage };
+    usage.0.record(structure.into(), &instance, bytes);
+}
+
+/// Write `entries` in the map the reply is in, nested maps included.
+///
+/// # Safety
+///
+/// The callbacks must be safe to call with `reply` and a NUL-terminated key.
+unsafe fn write(
+    entries: &[(String, MemValue)],
+    reply: *mut c_void,
+    map: unsafe extern "C" fn(reply: *mut c_void, key: *const c_char),
+    map_end: unsafe extern "C" fn(reply: *mut c_void),
+    int_kv: unsafe extern "C" fn(reply: *mut c_void, key: *const c_char, value: i64),
+) {
+    for (key, value) in entries {
+        // Field names can't hold a NUL byte in C, encodings never do
+        let key = CString::new(key.as_str()).expect("reply keys have no NUL byte");
+        match value {
+            MemValue::Integer(value) => {
+                let value = i64::try_from(*value).unwrap_or(i64::MAX);
+                // SAFETY: The caller must ensure that the callbacks are safe to call with `reply`
+                unsafe { int_kv(reply, key.as_ptr(), value) }
+            }
+            MemValue::Map(entries) => {
+                // SAFETY: As above.
+                unsafe { map(reply, key.as_ptr()) };
+                // SAFETY: As above.
+                unsafe { write(entries, reply, map, map_end, int_kv) };
+                // SAFETY: As above.
+                unsafe { map_end(reply) };
+            }
+        }
+    }
+}
+
+/// Reply the memory usage to `FT.DEBUG MEMUSAGE`, as the entries of the map
+/// `reply` is in: the total, then the bytes of every structure and, for the
+/// structures with several instances, their breakdown.
+///
+/// `map` opens a nested map under a key, closed by `map_end`. `int_kv` adds an
+/// entry to the current map. They're all called with `reply`.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `usage` must be a valid, non NULL, pointer created using [`MemUsage_New`].
+/// - The callbacks must be safe to call with `reply` and a NUL-terminated key.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn MemUsage_Reply(
+    usage: *const MemUsage,
+    reply: *mut c_void,
+    map: unsafe extern "C" fn(reply: *mut c_void, key: *const c_char),
+    map_end: unsafe extern "C" fn(reply: *mut c_void),
+    int_kv: unsafe extern "C" fn(reply: *mut c_void, key: *const c_char, value: i64),
+) {
+    debug_assert!(!usage.is_null(), "usage must not be null");
+
+    // SAFETY: The caller must ensure that `usage` is a valid pointer created using `MemUsage_New`
+    let entries = unsafe { &*usage }.0.reply();
+    // SAFETY: The caller must ensure that the callbacks are safe to call with `reply`
+    unsafe { write(&entries, reply, map, map_end, int_kv) };
+}
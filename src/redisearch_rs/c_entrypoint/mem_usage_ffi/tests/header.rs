@@ -0,0 +1,26 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The declarations of `mem_usage_rs.h` the C code relies on. A failure means
+//! that the generated header changed: update the callers, then this test.
+
+use build_utils::header_declarations;
+
+#[test]
+fn test_header_declarations() {
+    let decls = header_declarations("../../headers/mem_usage_rs.h").unwrap();
+    for expected in [
+        "struct MemUsage *MemUsage_New(void)",
+        "void MemUsage_Free(struct MemUsage *usage)",
+        "void MemUsage_Record(struct MemUsage *usage, enum MemStructure structure, const char *instance, uintptr_t instance_len, uintptr_t bytes)",
+        "void MemUsage_Reply(const struct MemUsage *usage, void *reply, void (*map)(void *reply, const char *key), void (*map_end)(void *reply), void (*int_kv)(void *reply, const char *key, int64_t value))",
+    ] {
+        assert!(decls.iter().any(|d| d == expected), "missing `{expected}`");
+    }
+}
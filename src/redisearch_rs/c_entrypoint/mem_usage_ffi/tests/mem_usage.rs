@@ -0,0 +1,118 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::ffi::{CStr, c_char, c_void};
+
+use mem_usage_ffi::{MemStructure, MemUsage_Free, MemUsage_New, MemUsage_Record, MemUsage_Reply};
+
+/// The reply as lines, nested maps indented.
+#[derive(Default)]
+struct Reply {
+    lines: Vec<String>,
+    depth: usize,
+}
+
+impl Reply {
+    fn push(&mut self, line: String) {
+        self.lines
+            .push(format!("{}{line}", "  ".repeat(self.depth)));
+    }
+}
+
+fn key(key: *const c_char) -> String {
+    // SAFETY: the keys are NUL-terminated strings.
+    unsafe { CStr::from_ptr(key) }.to_str().unwrap().to_owned()
+}
+
+unsafe extern "C" fn map(reply: *mut c_void, k: *const c_char) {
+    // SAFETY: the tests pass a `Reply`.
+    let reply = unsafe { &mut *reply.cast::<Reply>() };
+    reply.push(format!("{}:", key(k)));
+    reply.depth += 1;
+}
+
+unsafe extern "C" fn map_end(reply: *mut c_void) {
+    // SAFETY: the tests pass a `Reply`.
+    let reply = unsafe { &mut *reply.cast::<Reply>() };
+    reply.depth -= 1;
+}
+
+unsafe extern "C" fn int_kv(reply: *mut c_void, k: *const c_char, value: i64) {
+    // SAFETY: the tests pass a `Reply`.
+    let reply = unsafe { &mut *reply.cast::<Reply>() };
+    reply.push(format!("{}: {value}", key(k)));
+}
+
+#[test]
+fn test_record_and_reply() {
+    let usage = MemUsage_New();
+    for (structure, instance, bytes) in [
+        (MemStructure::TermTrie, "", 100),
+        (MemStructure::Postings, "full", 60),
+        (MemStructure::Postings, "freqs_only", 20),
+        (MemStructure::Postings, "full", 10),
+        (MemStructure::NumericTree, "price", 30),
+        // Structures with a single instance ignore the instance name
+        (MemStructure::DocTable, "title", 5),
+    ] {
+        // SAFETY: `usage` was created above and `instance` is valid for reads of its length.
+        unsafe {
+            MemUsage_Record(
+                usage,
+                structure,
+                instance.as_ptr().cast(),
+                instance.len(),
+                bytes,
+            )
+        };
+    }
+    // A NULL instance is an empty one
+    // SAFETY: `usage` was created above.
+    unsafe { MemUsage_Record(usage, MemStructure::Offsets, std::ptr::null(), 0, 7) };
+
+    let mut reply = Reply::default();
+    // SAFETY: `usage` was created above and the callbacks expect a `Reply`.
+    unsafe { MemUsage_Reply(usage, (&raw mut reply).cast(), map, map_end, int_kv) };
+    // SAFETY: `usage` was created above and isn't used afterwards.
+    unsafe { MemUsage_Free(usage) };
+
+    assert_eq!(
+        reply.lines,
+        [
+            "total_bytes: 232",
+            "term_trie:",
+            "  bytes: 100",
+            "postings:",
+            "  bytes: 90",
+            "  by_encoding:",
+            "    freqs_only: 20",
+            "    full: 70",
+            "offsets:",
+            "  bytes: 7",
+            "numeric_trees:",
+            "  bytes: 30",
+            "  by_field:",
+            "    price: 30",
+            "tag_tries:",
+            "  bytes: 0",
+            "  by_field:",
+            "doc_table:",
+            "  bytes: 5",
+            "sortables:",
+            "  bytes: 0",
+            "vector_graphs:",
+            "  bytes: 0",
+            "  by_field:",
+            "suffix_tries:",
+            "  bytes: 0",
+            "  by_field:",
+        ]
+    );
+    assert_eq!(reply.depth, 0);
+}
@@ -0,0 +1,78 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! FFI layer to the memory watcher of the module, which pauses the background
+//! scan of the indexes and the application of the fork GC results while the
+//! server is low on memory.
+//!
+//! The background threads poll the watcher with the memory readings they take
+//! under the GIL, with the pause threshold of `_BG_WORK_PAUSE_MEM_PCT`. Work
+//! resumes [`MEMORY_WATCHER_HYSTERESIS_PERCENT`] below it.
+
+use std::time::Duration;
+
+use memory_watcher::{MemoryWatcher, Thresholds};
+
+/// The gap between the pause and the resume thresholds, in percents of
+/// `maxmemory`.
+pub const MEMORY_WATCHER_HYSTERESIS_PERCENT: u8 = 10;
+
+static WATCHER: MemoryWatcher = MemoryWatcher::new(Thresholds::DISABLED);
+
+/// The state of the memory watcher, for `FT.INFO`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryWatcherInfo {
+    /// Whether background work is paused.
+    pub paused: bool,
+    /// How many times background work was paused since startup.
+    pub pause_count: u64,
+    /// The time spent paused since startup, including the current pause.
+    pub paused_time_ms: u64,
+}
+
+/// Feed the watcher with a reading of the `used` memory and `max_memory`, in
+/// bytes, a `max_memory` of 0 meaning unlimited. Work pauses above
+/// `pause_percent` of `max_memory`, 0 disabling the watcher.
+///
+/// Returns whether background work is paused.
+#[unsafe(no_mangle)]
+pub extern "C" fn MemoryWatcher_Poll(pause_percent: u8, used: u64, max_memory: u64) -> bool {
+    let pause_percent = pause_percent.min(100);
+    let thresholds = Thresholds::new(
+        pause_percent,
+        pause_percent.saturating_sub(MEMORY_WATCHER_HYSTERESIS_PERCENT),
+    )
+    .unwrap_or(Thresholds::DISABLED);
+    WATCHER.set_thresholds(thresholds);
+    WATCHER.observe(used, max_memory);
+    WATCHER.is_paused()
+}
+
+/// Block the calling background thread while work is paused, for at most
+/// `timeout_ms` milliseconds. It must not hold the GIL, so that the server
+/// can free memory meanwhile.
+///
+/// Returns whether work may proceed, `false` if the timeout elapsed while
+/// still paused.
+#[unsafe(no_mangle)]
+pub extern "C" fn MemoryWatcher_WaitWhilePaused(timeout_ms: u64) -> bool {
+    WATCHER.wait_while_paused(Duration::from_millis(timeout_ms))
+}
+
+/// The state of the watcher.
+#[unsafe(no_mangle)]
+pub extern "C" fn MemoryWatcher_Info() -> MemoryWatcherInfo {
+    let info = WATCHER.info();
+    MemoryWatcherInfo {
+        paused: info.paused,
+        pause_count: info.pause_count,
+        paused_time_ms: info.paused_time.as_millis() as u64,
+    }
+}
@@ -0,0 +1,27 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The declarations of `memory_watcher_rs.h` the C code relies on. A failure
+//! means that the generated header changed: update the callers, then this
+//! test.
+
+use build_utils::header_declarations;
+
+#[test]
+fn test_header_declarations() {
+    let decls = header_declarations("../../headers/memory_watcher_rs.h").unwrap();
+    for expected in [
+        "typedef struct MemoryWatcherInfo { bool paused",
+        "bool MemoryWatcher_Poll(uint8_t pause_percent, uint64_t used, uint64_t max_memory)",
+        "bool MemoryWatcher_WaitWhilePaused(uint64_t timeout_ms)",
+        "struct MemoryWatcherInfo MemoryWatcher_Info(void)",
+    ] {
+        assert!(decls.iter().any(|d| d == expected), "missing `{expected}`");
+    }
+}
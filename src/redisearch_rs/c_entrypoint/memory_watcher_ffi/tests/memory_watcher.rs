@@ -0,0 +1,43 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use memory_watcher_ffi::{MemoryWatcher_Info, MemoryWatcher_Poll, MemoryWatcher_WaitWhilePaused};
+
+const GB: u64 = 1 << 30;
+
+// The watcher is global: a single test drives it through its states.
+#[test]
+fn test_poll() {
+    // Disabled
+    assert!(!MemoryWatcher_Poll(0, 10 * GB, 10 * GB));
+    // Unlimited memory
+    assert!(!MemoryWatcher_Poll(80, 10 * GB, 0));
+    assert!(!MemoryWatcher_Poll(80, 8 * GB, 10 * GB));
+    assert!(!MemoryWatcher_Info().paused);
+
+    // Pause above 80%, resume below 70%
+    assert!(MemoryWatcher_Poll(80, 9 * GB, 10 * GB));
+    assert!(!MemoryWatcher_WaitWhilePaused(1));
+    assert!(MemoryWatcher_Poll(80, 7 * GB + GB / 2, 10 * GB));
+    let info = MemoryWatcher_Info();
+    assert!(info.paused);
+    assert_eq!(info.pause_count, 1);
+    assert!(!MemoryWatcher_Poll(80, 6 * GB, 10 * GB));
+    assert!(MemoryWatcher_WaitWhilePaused(1));
+    assert!(!MemoryWatcher_Info().paused);
+
+    // Disabling the watcher resumes paused work
+    assert!(MemoryWatcher_Poll(80, 9 * GB, 10 * GB));
+    assert!(!MemoryWatcher_Poll(0, 9 * GB, 10 * GB));
+    assert_eq!(MemoryWatcher_Info().pause_count, 2);
+
+    // A threshold above 100% is clamped
+    assert!(MemoryWatcher_Poll(200, 11 * GB, 10 * GB));
+    assert!(!MemoryWatcher_Poll(0, 0, 10 * GB));
+}
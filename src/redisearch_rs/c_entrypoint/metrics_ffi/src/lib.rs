@@ -0,0 +1,146 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! FFI layer to update the metrics of the module from the C code, and to
+//! reply them to `FT._METRICS`.
+//!
+//! The families are declared when the registry is first used. The query path
+//! updates the counters and histograms as queries finish, while the gauges of
+//! the indexes are refreshed by `FT._METRICS` before rendering.
+
+use std::{
+    ffi::{c_char, c_void},
+    sync::LazyLock,
+};
+
+use metrics::{INDEX_LABEL, MetricKind, Registry};
+
+/// The number of indexes with their own `index` label value.
+pub const METRICS_INDEX_LABEL_LIMIT: usize = 64;
+
+const QUERIES: &str = "redisearch_queries_total";
+const QUERY_DURATION: &str = "redisearch_query_duration_seconds";
+const INDEX_DOCS: &str = "redisearch_index_docs";
+const INDEX_RECORDS: &str = "redisearch_index_records";
+
+/// The upper bounds of the query duration buckets, in seconds.
+const QUERY_DURATION_BOUNDS: [f64; 8] = [0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0];
+
+static REGISTRY: LazyLock<Registry> = LazyLock::new(|| {
+    let registry = Registry::new(METRICS_INDEX_LABEL_LIMIT);
+    registry.describe(
+        QUERIES,
+        MetricKind::Counter,
+        "The number of queries executed on the index.",
+    );
+    registry.describe(
+        QUERY_DURATION,
+        MetricKind::Histogram(QUERY_DURATION_BOUNDS.to_vec()),
+        "The time spent executing the queries of the index.",
+    );
+    registry.describe(
+        INDEX_DOCS,
+        MetricKind::Gauge,
+        "The number of documents in the index.",
+    );
+    registry.describe(
+        INDEX_RECORDS,
+        MetricKind::Gauge,
+        "The number of records in the inverted indexes of the index.",
+    );
+    registry
+});
+
+/// Read the `len` bytes at `ptr` as an index name, replacing invalid UTF-8.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads of `len` bytes, and may only be NULL if `len`
+/// is 0.
+unsafe fn index_from_raw(ptr: *const c_char, len: usize) -> String {
+    if len == 0 {
+        return String::new();
+    }
+    // SAFETY: The caller must ensure that `ptr` is valid for reads of `len` bytes
+    let bytes = unsafe { std::slice::from_raw_parts(ptr.cast::<u8>(), len) };
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Account for a query on the index `index`, which took `duration_ns`
+/// nanoseconds.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `index` must be valid for reads of `index_len` bytes. It may be NULL if `index_len` is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn Metrics_QueryExecuted(
+    index: *const c_char,
+    index_len: usize,
+    duration_ns: u64,
+) {
+    // SAFETY: The caller must ensure that `index` is valid for reads of `index_len` bytes
+    let index = unsafe { index_from_raw(index, index_len) };
+    let labels = [(INDEX_LABEL, index.as_str())];
+    REGISTRY.inc_counter(QUERIES, &labels, 1.0);
+    REGISTRY.observe(QUERY_DURATION, &labels, duration_ns as f64 / 1e9);
+}
+
+/// Set the gauges of the index `index`: its number of documents and of
+/// inverted index records.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `index` must be valid for reads of `index_len` bytes. It may be NULL if `index_len` is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn Metrics_SetIndexGauges(
+    index: *const c_char,
+    index_len: usize,
+    num_docs: u64,
+    num_records: u64,
+) {
+    // SAFETY: The caller must ensure that `index` is valid for reads of `index_len` bytes
+    let index = unsafe { index_from_raw(index, index_len) };
+    let labels = [(INDEX_LABEL, index.as_str())];
+    REGISTRY.set_gauge(INDEX_DOCS, &labels, num_docs as f64);
+    REGISTRY.set_gauge(INDEX_RECORDS, &labels, num_records as f64);
+}
+
+/// Drop the series of the dropped index `index`.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `index` must be valid for reads of `index_len` bytes. It may be NULL if `index_len` is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn Metrics_RemoveIndex(index: *const c_char, index_len: usize) {
+    // SAFETY: The caller must ensure that `index` is valid for reads of `index_len` bytes
+    let index = unsafe { index_from_raw(index, index_len) };
+    REGISTRY.remove_index(&index);
+}
+
+/// Reply the metrics to `FT._METRICS`, in the Prometheus text exposition
+/// format: `text` is called once with `reply` and the rendered text, which
+/// isn't NUL-terminated and is only valid during the call.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `text` must be safe to call with `reply`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn Metrics_Reply(
+    reply: *mut c_void,
+    text: unsafe extern "C" fn(reply: *mut c_void, text: *const c_char, len: usize),
+) {
+    let rendered = REGISTRY.render();
+    // SAFETY: The caller must ensure that `text` is safe to call with `reply`, and `rendered`
+    // outlives the call.
+    unsafe { text(reply, rendered.as_ptr().cast(), rendered.len()) };
+}
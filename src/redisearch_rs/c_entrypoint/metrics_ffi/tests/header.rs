@@ -0,0 +1,26 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The declarations of `metrics_rs.h` the C code relies on. A failure means
+//! that the generated header changed: update the callers, then this test.
+
+use build_utils::header_declarations;
+
+#[test]
+fn test_header_declarations() {
+    let decls = header_declarations("../../headers/metrics_rs.h").unwrap();
+    for expected in [
+        "void Metrics_QueryExecuted(const char *index, uintptr_t index_len, uint64_t duration_ns)",
+        "void Metrics_SetIndexGauges(const char *index, uintptr_t index_len, uint64_t num_docs, uint64_t num_records)",
+        "void Metrics_RemoveIndex(const char *index, uintptr_t index_len)",
+        "void Metrics_Reply(void *reply, void (*text)(void *reply, const char *text, uintptr_t len))",
+    ] {
+        assert!(decls.iter().any(|d| d == expected), "missing `{expected}`");
+    }
+}
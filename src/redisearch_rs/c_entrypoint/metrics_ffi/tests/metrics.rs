@@ -0,0 +1,104 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::ffi::{c_char, c_void};
+
+use metrics_ffi::{
+    Metrics_QueryExecuted, Metrics_RemoveIndex, Metrics_Reply, Metrics_SetIndexGauges,
+};
+
+unsafe extern "C" fn set_text(reply: *mut c_void, text: *const c_char, len: usize) {
+    // SAFETY: the tests pass a `String`.
+    let reply = unsafe { &mut *reply.cast::<String>() };
+    // SAFETY: the registry passes `len` readable bytes.
+    let bytes = unsafe { std::slice::from_raw_parts(text.cast::<u8>(), len) };
+    *reply = String::from_utf8(bytes.to_vec()).unwrap();
+}
+
+fn render() -> String {
+    let mut text = String::new();
+    // SAFETY: `set_text` expects a `String`.
+    unsafe { Metrics_Reply((&raw mut text).cast(), set_text) };
+    text
+}
+
+/// The samples of `index`: the tests run in parallel, sharing the registry.
+fn samples(index: &str) -> Vec<String> {
+    let label = format!("index=\"{index}\"");
+    render()
+        .lines()
+        .filter(|line| line.contains(&label))
+        .map(str::to_owned)
+        .collect()
+}
+
+fn query(index: &str, duration_ns: u64) {
+    // SAFETY: `index` is valid for reads of its length.
+    unsafe { Metrics_QueryExecuted(index.as_ptr().cast(), index.len(), duration_ns) };
+}
+
+#[test]
+fn test_families() {
+    let text = render();
+    for family in [
+        "# TYPE redisearch_queries_total counter",
+        "# TYPE redisearch_query_duration_seconds histogram",
+        "# TYPE redisearch_index_docs gauge",
+        "# TYPE redisearch_index_records gauge",
+    ] {
+        assert!(
+            text.lines().any(|line| line == family),
+            "missing `{family}`"
+        );
+    }
+}
+
+#[test]
+fn test_queries() {
+    query("idx_queries", 2_000_000);
+    query("idx_queries", 20_000_000);
+
+    let samples = samples("idx_queries");
+    for expected in [
+        "redisearch_queries_total{index=\"idx_queries\"} 2",
+        "redisearch_query_duration_seconds_bucket{index=\"idx_queries\",le=\"0.001\"} 0",
+        "redisearch_query_duration_seconds_bucket{index=\"idx_queries\",le=\"0.005\"} 1",
+        "redisearch_query_duration_seconds_bucket{index=\"idx_queries\",le=\"0.05\"} 2",
+        "redisearch_query_duration_seconds_bucket{index=\"idx_queries\",le=\"+Inf\"} 2",
+        "redisearch_query_duration_seconds_sum{index=\"idx_queries\"} 0.022",
+        "redisearch_query_duration_seconds_count{index=\"idx_queries\"} 2",
+    ] {
+        assert!(
+            samples.iter().any(|s| s == expected),
+            "missing `{expected}`"
+        );
+    }
+}
+
+#[test]
+fn test_gauges_and_removal() {
+    let index = "idx_gauges";
+    // SAFETY: `index` is valid for reads of its length.
+    unsafe { Metrics_SetIndexGauges(index.as_ptr().cast(), index.len(), 3, 7) };
+    query(index, 1000);
+    assert_eq!(
+        samples(index)
+            .into_iter()
+            .filter(|s| s.starts_with("redisearch_index_"))
+            .collect::<Vec<_>>(),
+        [
+            "redisearch_index_docs{index=\"idx_gauges\"} 3",
+            "redisearch_index_records{index=\"idx_gauges\"} 7",
+        ]
+    );
+
+    // SAFETY: `index` is valid for reads of its length.
+    unsafe { Metrics_RemoveIndex(index.as_ptr().cast(), index.len()) };
+    assert!(samples(index).is_empty());
+}
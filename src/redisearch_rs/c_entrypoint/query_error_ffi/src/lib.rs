@@ -9,6 +9,7 @@
 
 mod opaque;
 
+use ffi_boundary::{PanicReport, ffi_boundary};
 use query_error::QueryError;
 use std::ffi::CStr;
 use std::os::raw::c_char;
@@ -65,9 +66,8 @@ pub const extern "C" fn QueryError_Strerror(maybe_code: u8) -> *const c_char {
 ///
 /// This does not mutate `query_error` if it already has an error set.
 ///
-/// # Panics
-///
-/// - `code` must be a valid variant of [`QueryErrorCode`].
+/// An invalid `code` is reported as a [`QueryErrorCode::Generic`] error
+/// rather than aborting, see [`set_panic_error`].
 ///
 /// # Safety
 ///
@@ -82,25 +82,31 @@ pub unsafe extern "C" fn QueryError_SetError(
     // Safety: see safety requirement above.
     let query_error =
         unsafe { QueryError::from_opaque_mut_ptr(query_error) }.expect("query_error is null");
-    let code = QueryErrorCode::from_repr(code).expect("invalid query error code");
 
-    let message = if message.is_null() {
-        None
-    } else {
-        // Safety: see safety requirement above.
-        Some(unsafe { CStr::from_ptr(message) }.to_owned())
-    };
+    ffi_boundary!(
+        "QueryError_SetError",
+        |report| set_panic_error(query_error, &report),
+        {
+            let code = QueryErrorCode::from_repr(code).expect("invalid query error code");
+
+            let message = if message.is_null() {
+                None
+            } else {
+                // Safety: see safety requirement above.
+                Some(unsafe { CStr::from_ptr(message) }.to_owned())
+            };
 
-    query_error.set_code_and_message(code, message);
+            query_error.set_code_and_message(code, message);
+        }
+    )
 }
 
 /// Sets the [`QueryErrorCode`] for a [`QueryError`].
 ///
 /// This does not mutate `query_error` if it already has an error set.
 ///
-/// # Panics
-///
-/// - `code` must be a valid variant of [`QueryErrorCode`].
+/// An invalid `code` is reported as a [`QueryErrorCode::Generic`] error
+/// rather than aborting, see [`set_panic_error`].
 ///
 /// # Safety
 ///
@@ -110,9 +116,15 @@ pub unsafe extern "C" fn QueryError_SetCode(query_error: *mut OpaqueQueryError,
     // Safety: see safety requirement above.
     let query_error =
         unsafe { QueryError::from_opaque_mut_ptr(query_error) }.expect("query_error is null");
-    let code = QueryErrorCode::from_repr(code).expect("invalid query error code");
 
-    query_error.set_code(code);
+    ffi_boundary!(
+        "QueryError_SetCode",
+        |report| set_panic_error(query_error, &report),
+        {
+            let code = QueryErrorCode::from_repr(code).expect("invalid query error code");
+            query_error.set_code(code);
+        }
+    )
 }
 
 /// Always sets the private message for a [`QueryError`].
@@ -264,9 +276,8 @@ pub unsafe extern "C" fn QueryError_ClearError(query_error: *mut OpaqueQueryErro
 /// if the private message is set. This differs from [`QueryError_SetCode`],
 /// as that function does not care if the private message is set.
 ///
-/// # Panics
-///
-/// - `code` must be a valid variant of [`QueryErrorCode`].
+/// An invalid `code` is reported as a [`QueryErrorCode::Generic`] error
+/// rather than aborting, see [`set_panic_error`].
 ///
 /// # Safety
 ///
@@ -276,13 +287,20 @@ pub unsafe extern "C" fn QueryError_MaybeSetCode(query_error: *mut OpaqueQueryEr
     // Safety: see safety requirement above.
     let query_error =
         unsafe { QueryError::from_opaque_mut_ptr(query_error) }.expect("query_error is null");
-    let code = QueryErrorCode::from_repr(code).expect("invalid query error code");
 
-    if query_error.private_message().is_none() || !query_error.is_ok() {
-        return;
-    }
+    ffi_boundary!(
+        "QueryError_MaybeSetCode",
+        |report| set_panic_error(query_error, &report),
+        {
+            let code = QueryErrorCode::from_repr(code).expect("invalid query error code");
+
+            if query_error.private_message().is_none() || !query_error.is_ok() {
+                return;
+            }
 
-    query_error.set_code(code);
+            query_error.set_code(code);
+        }
+    )
 }
 
 /// Returns whether the [`QueryError`] has the `reached_max_prefix_expansions`
@@ -349,3 +367,13 @@ pub unsafe extern "C" fn QueryError_SetQueryOOMWarning(query_error: *mut OpaqueQ
 
     query_error.warnings_mut().set_out_of_memory()
 }
+
+/// Records a panic caught at an FFI boundary as a [`QueryErrorCode::Generic`]
+/// error on `query_error`, for use as the `on_panic` handler of
+/// [`ffi_boundary::ffi_boundary!`].
+///
+/// Like [`QueryError::set_code_and_message`], this does nothing if
+/// `query_error` already has an error set.
+pub fn set_panic_error(query_error: &mut QueryError, report: &PanicReport) {
+    query_error.set_code_and_message(QueryErrorCode::Generic, Some(report.to_c_string()));
+}
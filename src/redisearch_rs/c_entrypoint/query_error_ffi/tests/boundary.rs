@@ -0,0 +1,73 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+
+//! Invalid input from C is caught at the boundary and reported on the query
+//! error, instead of aborting the server.
+
+use query_error::QueryError;
+use query_error_ffi::*;
+
+const INVALID_CODE: u8 = u8::MAX;
+
+/// Run `f` on a fresh query error, and return the error it leaves.
+fn with_query_error(f: impl FnOnce(*mut OpaqueQueryError)) -> QueryError {
+    ffi_boundary::set_logger(|_| {});
+    let mut query_error = QueryError_Default();
+    f(&mut query_error);
+    // Safety: `query_error` was created by `QueryError_Default`.
+    unsafe { QueryError::from_opaque(query_error) }
+}
+
+fn assert_internal_error(error: &QueryError, entry: &str) {
+    assert_eq!(error.code(), QueryErrorCode::Generic);
+    let message = error.private_message().unwrap().to_str().unwrap();
+    assert!(
+        message.starts_with(&format!("Internal error in {entry}: invalid query error code")),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_set_error_with_invalid_code() {
+    let error = with_query_error(|query_error| {
+        // Safety: `query_error` was created by `QueryError_Default`.
+        unsafe { QueryError_SetError(query_error, INVALID_CODE, c"oops".as_ptr()) };
+    });
+    assert_internal_error(&error, "QueryError_SetError");
+}
+
+#[test]
+fn test_set_code_with_invalid_code() {
+    let error = with_query_error(|query_error| {
+        // Safety: `query_error` was created by `QueryError_Default`.
+        unsafe { QueryError_SetCode(query_error, INVALID_CODE) };
+    });
+    assert_internal_error(&error, "QueryError_SetCode");
+}
+
+#[test]
+fn test_maybe_set_code_with_invalid_code() {
+    let error = with_query_error(|query_error| {
+        // Safety: `query_error` was created by `QueryError_Default`.
+        unsafe { QueryError_MaybeSetCode(query_error, INVALID_CODE) };
+    });
+    assert_internal_error(&error, "QueryError_MaybeSetCode");
+}
+
+#[test]
+fn test_existing_error_is_kept() {
+    let error = with_query_error(|query_error| {
+        // Safety: `query_error` was created by `QueryError_Default`.
+        unsafe { QueryError_SetCode(query_error, QueryErrorCode::Syntax as u8) };
+        // Safety: as above.
+        unsafe { QueryError_SetCode(query_error, INVALID_CODE) };
+    });
+    assert_eq!(error.code(), QueryErrorCode::Syntax);
+}
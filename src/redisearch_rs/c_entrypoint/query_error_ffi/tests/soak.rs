@@ -0,0 +1,61 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Drives the FFI functions the way C code does, through opaque values and
+//! pointers, so that Miri can check the conversions and catch leaks.
+
+use std::ffi::{CStr, CString};
+
+use opaque::testing::{roundtrip, soak_iterations};
+use query_error::QueryError;
+use query_error_ffi::*;
+
+#[test]
+fn test_layout_roundtrip() {
+    let mut error = QueryError::default();
+    error.set_code_and_message(QueryErrorCode::Syntax, Some(c"oops".to_owned()));
+    let back = roundtrip(error, |e| {
+        assert_eq!(e.code(), QueryErrorCode::Syntax);
+        assert_eq!(e.private_message(), Some(c"oops"));
+    });
+    assert_eq!(back.public_message(), Some(c"oops"));
+}
+
+#[test]
+fn test_soak() {
+    for i in 0..soak_iterations(10_000) {
+        let message = CString::new(format!("error #{i}")).unwrap();
+        let mut src = QueryError_Default();
+        let mut dest = QueryError_Default();
+
+        // Safety: `src` and `dest` were created by `QueryError_Default` and
+        // `message` is a valid C string.
+        unsafe {
+            QueryError_SetError(&mut src, QueryErrorCode::Syntax as u8, message.as_ptr());
+        }
+        // Safety: `src` and `dest` were created by `QueryError_Default`.
+        unsafe { QueryError_CloneFrom(&src, &mut dest) };
+        // Safety: `src` was created by `QueryError_Default`.
+        unsafe { QueryError_ClearError(&mut src) };
+
+        // Safety: `src` was created by `QueryError_Default`.
+        assert!(unsafe { QueryError_IsOk(&src) });
+        // Safety: `dest` was created by `QueryError_Default`.
+        let user_error = unsafe { QueryError_GetUserError(&dest) };
+        // Safety: the returned string is valid while `dest` isn't modified.
+        let user_error = unsafe { CStr::from_ptr(user_error) };
+        assert_eq!(user_error, message.as_c_str());
+
+        // Safety: both were created by `QueryError_Default`. Converting them
+        // back drops them, as C does with `QueryError_ClearError`.
+        drop(unsafe { QueryError::from_opaque(src) });
+        // Safety: see above.
+        drop(unsafe { QueryError::from_opaque(dest) });
+    }
+}
@@ -17,9 +17,21 @@
 #[global_allocator]
 static REDIS_MODULE_ALLOCATOR: redis_module::alloc::RedisAlloc = redis_module::alloc::RedisAlloc;
 
+pub use admission_ffi as admission;
+pub use bsearch_ffi as bsearch;
 pub use fnv_ffi as fnv;
+pub use gc_stats_ffi as gc_stats;
+pub use index_events_ffi as index_events;
+pub use index_lock_ffi as index_lock;
 pub use inverted_index_ffi as inverted_index;
+pub use mem_usage_ffi as mem_usage;
+pub use memory_watcher_ffi as memory_watcher;
+pub use metrics_ffi as metrics;
+pub use references_ffi as references;
 pub use result_processor_ffi as result_processor;
+pub use scratch_ffi as scratch;
+pub use slowlog_ffi as slowlog;
 pub use triemap_ffi as triemap;
 pub use types_ffi as types;
 pub use varint_ffi as varint;
+pub use zstr_ffi as zstr;
@@ -0,0 +1,425 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! FFI layer implementing `util/references.h`, the strong and weak references
+//! to index specs, with the `references` crate.
+//!
+//! The `rm` field of a C reference is the raw pointer of a Rust reference, see
+//! [`references::StrongRef::into_raw`]: it points at the allocation shared by
+//! the references to the object, so that cloning, promoting, demoting or
+//! releasing one only updates its reference counts (debug builds box strong
+//! references, to track them). The low bit of the pointer tells weak
+//! references from strong ones. The functions creating
+//! strong references take the C location they're called from, filled in by the
+//! macros of `util/references.h`, so that `FT.DEBUG SPECREFS` can list the
+//! leaked ones.
+
+#![allow(non_camel_case_types, non_snake_case)]
+
+use std::{
+    ffi::{CStr, c_char, c_void},
+    mem::ManuallyDrop,
+    ptr,
+};
+
+use references::Site;
+
+/// Frees the object of a reference, once its last strong reference is
+/// released.
+pub type RefManager_Free = Option<unsafe extern "C" fn(obj: *mut c_void)>;
+
+/// The object of a reference, along with the callback freeing it.
+pub struct Object {
+    obj: *mut c_void,
+    free: RefManager_Free,
+}
+
+impl Drop for Object {
+    fn drop(&mut self) {
+        if let Some(free) = self.free {
+            // SAFETY: `free` was given along with `obj` to `StrongRef_NewAt`,
+            // and this is the last reference to `obj`.
+            unsafe { free(self.obj) };
+        }
+    }
+}
+
+/// The object shared by references, which `rm` points to.
+pub struct RefManager {
+    _private: [u8; 0],
+}
+
+/// Set in the `rm` of weak references.
+const WEAK_TAG: usize = 1;
+
+fn is_weak(rm: *mut RefManager) -> bool {
+    rm.addr() & WEAK_TAG != 0
+}
+
+/// A strong reference, keeping the object alive. `rm` is NULL for an invalid
+/// reference, which doesn't need to be released.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct StrongRef {
+    pub rm: *mut RefManager,
+}
+
+/// A weak reference, which doesn't keep the object alive.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct WeakRef {
+    pub rm: *mut RefManager,
+}
+
+impl StrongRef {
+    const INVALID: Self = Self {
+        rm: ptr::null_mut(),
+    };
+
+    fn new(strong: Option<references::StrongRef<Object>>) -> Self {
+        strong.map_or(Self::INVALID, |strong| Self {
+            rm: strong.into_raw().cast_mut().cast(),
+        })
+    }
+
+    /// # Safety
+    ///
+    /// `self.rm` must be NULL or the `rm` of a strong reference, which
+    /// outlives the returned one.
+    unsafe fn get(self) -> Option<ManuallyDrop<references::StrongRef<Object>>> {
+        if self.rm.is_null() {
+            return None;
+        }
+        assert!(!is_weak(self.rm), "expected a strong reference");
+        // SAFETY: Ensured by the caller.
+        Some(unsafe { references::StrongRef::borrow_raw(self.rm.cast_const().cast()) })
+    }
+
+    /// # Safety
+    ///
+    /// `self.rm` must be the `rm` of a strong reference, which isn't used
+    /// after.
+    unsafe fn take(self) -> references::StrongRef<Object> {
+        debug_assert!(!self.rm.is_null(), "rm must not be null");
+        assert!(!is_weak(self.rm), "expected a strong reference");
+        // SAFETY: Ensured by the caller.
+        unsafe { references::StrongRef::from_raw(self.rm.cast_const().cast()) }
+    }
+}
+
+impl WeakRef {
+    fn new(weak: references::WeakRef<Object>) -> Self {
+        Self {
+            rm: weak
+                .into_raw()
+                .cast_mut()
+                .cast::<RefManager>()
+                .map_addr(|addr| addr | WEAK_TAG),
+        }
+    }
+
+    /// The untagged pointer to the shared allocation.
+    fn raw(self) -> *const () {
+        debug_assert!(!self.rm.is_null(), "rm must not be null");
+        assert!(is_weak(self.rm), "expected a weak reference");
+        self.rm
+            .map_addr(|addr| addr & !WEAK_TAG)
+            .cast_const()
+            .cast()
+    }
+
+    /// # Safety
+    ///
+    /// `self.rm` must be the `rm` of a weak reference, which outlives the
+    /// returned one.
+    unsafe fn get(self) -> ManuallyDrop<references::WeakRef<Object>> {
+        // SAFETY: Ensured by the caller.
+        unsafe { references::WeakRef::borrow_raw(self.raw()) }
+    }
+
+    /// # Safety
+    ///
+    /// `self.rm` must be the `rm` of a weak reference, which isn't used after.
+    unsafe fn take(self) -> references::WeakRef<Object> {
+        // SAFETY: Ensured by the caller.
+        unsafe { references::WeakRef::from_raw(self.raw()) }
+    }
+}
+
+/// # Safety
+///
+/// `file` must be a valid, non NULL, NUL-terminated string, which lives until
+/// the end of the program, such as `__FILE__`.
+unsafe fn site(file: *const c_char, line: u32) -> Site {
+    debug_assert!(!file.is_null(), "file must not be null");
+
+    // SAFETY: Ensured by the caller.
+    let file: &'static CStr = unsafe { CStr::from_ptr(file) };
+    Site {
+        file: file.to_str().unwrap_or("<non UTF-8 file>"),
+        line,
+    }
+}
+
+/// Create the first strong reference to `obj`, freed using `free_cb` once the
+/// last strong reference is released. Called through the `StrongRef_New`
+/// macro, with the location it's called from.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `free_cb` must be safe to call with `obj`, from any thread.
+/// - `file` must be a valid, non NULL, NUL-terminated string, which lives until the end of the
+///   program, such as `__FILE__`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn StrongRef_NewAt(
+    obj: *mut c_void,
+    free_cb: RefManager_Free,
+    file: *const c_char,
+    line: u32,
+) -> StrongRef {
+    let object = Object { obj, free: free_cb };
+    // SAFETY: The caller must ensure that `file` lives until the end of the program
+    let site = unsafe { site(file, line) };
+    StrongRef::new(Some(references::StrongRef::new_at(object, site)))
+}
+
+/// Clone a strong reference. The returned one is invalid if the object was
+/// invalidated. Called through the `StrongRef_Clone` macro, with the location
+/// it's called from.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `s_ref` must be a valid strong reference, or an invalid one.
+/// - `file` must be a valid, non NULL, NUL-terminated string, which lives until the end of the
+///   program, such as `__FILE__`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn StrongRef_CloneAt(
+    s_ref: StrongRef,
+    file: *const c_char,
+    line: u32,
+) -> StrongRef {
+    // SAFETY: The caller must ensure that `s_ref` is valid or invalid
+    let Some(strong) = (unsafe { s_ref.get() }) else {
+        return StrongRef::INVALID;
+    };
+    // SAFETY: The caller must ensure that `file` lives until the end of the program
+    let site = unsafe { site(file, line) };
+    StrongRef::new(strong.try_clone_at(site))
+}
+
+/// Get a strong reference from a weak one. The returned one is invalid if the
+/// object was freed or invalidated. The weak reference still needs to be
+/// released. Called through the `WeakRef_Promote` macro, with the location it's
+/// called from.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `w_ref` must be a valid weak reference.
+/// - `file` must be a valid, non NULL, NUL-terminated string, which lives until the end of the
+///   program, such as `__FILE__`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn WeakRef_PromoteAt(
+    w_ref: WeakRef,
+    file: *const c_char,
+    line: u32,
+) -> StrongRef {
+    // SAFETY: The caller must ensure that `w_ref` is valid
+    let weak = unsafe { w_ref.get() };
+    // SAFETY: The caller must ensure that `file` lives until the end of the program
+    let site = unsafe { site(file, line) };
+    StrongRef::new(weak.promote_at(site))
+}
+
+/// Get a weak reference from a strong one. The strong reference still needs to
+/// be released.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `s_ref` must be a valid, non invalid, strong reference.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn StrongRef_Demote(s_ref: StrongRef) -> WeakRef {
+    // SAFETY: The caller must ensure that `s_ref` is valid
+    let strong = unsafe { s_ref.get() }.expect("s_ref must not be invalid");
+    WeakRef::new(strong.demote())
+}
+
+/// Clone a weak reference.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `w_ref` must be a valid weak reference.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn WeakRef_Clone(w_ref: WeakRef) -> WeakRef {
+    // SAFETY: The caller must ensure that `w_ref` is valid
+    let weak = unsafe { w_ref.get() };
+    WeakRef::new(references::WeakRef::clone(&weak))
+}
+
+/// Release a weak reference.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `w_ref` must be a valid weak reference. It must not be used after this call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn WeakRef_Release(w_ref: WeakRef) {
+    // SAFETY: The caller must ensure that `w_ref` isn't used after
+    drop(unsafe { w_ref.take() });
+}
+
+/// Release a strong reference, freeing the object if it was the last one.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `s_ref` must be a valid, non invalid, strong reference. It must not be used after this call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn StrongRef_Release(s_ref: StrongRef) {
+    // SAFETY: The caller must ensure that `s_ref` isn't used after
+    drop(unsafe { s_ref.take() });
+}
+
+/// Get the object of a strong reference, or NULL if the reference is invalid.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `s_ref` must be a valid strong reference, or an invalid one.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn StrongRef_Get(s_ref: StrongRef) -> *mut c_void {
+    // SAFETY: The caller must ensure that `s_ref` is valid or invalid
+    unsafe { s_ref.get() }.map_or(ptr::null_mut(), |strong| strong.get().obj)
+}
+
+/// Invalidate the object: no new strong reference can be created from then on.
+/// The existing ones remain usable, and still need to be released.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `s_ref` must be a valid, non invalid, strong reference.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn StrongRef_Invalidate(s_ref: StrongRef) {
+    // SAFETY: The caller must ensure that `s_ref` is valid
+    unsafe { s_ref.get() }
+        .expect("s_ref must not be invalid")
+        .invalidate();
+}
+
+/// Whether both strong references are to the same object, or both invalid.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `s_ref` and `other` must be valid strong references, or invalid ones.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn StrongRef_Equals(s_ref: StrongRef, other: StrongRef) -> bool {
+    // SAFETY: The caller must ensure that `s_ref` is valid or invalid
+    let s_ref = unsafe { s_ref.get() };
+    // SAFETY: The caller must ensure that `other` is valid or invalid
+    let other = unsafe { other.get() };
+    match (s_ref, other) {
+        (Some(s_ref), Some(other)) => s_ref.ptr_eq(&other),
+        (s_ref, other) => s_ref.is_none() && other.is_none(),
+    }
+}
+
+/// Get the object of a reference, or NULL if it was freed or invalidated. For
+/// LLAPI and tests only, which hold the `rm` of references.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `rm` must be NULL or the `rm` of a valid reference.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn __RefManager_Get_Object(rm: *mut RefManager) -> *mut c_void {
+    if is_weak(rm) {
+        // SAFETY: The caller must ensure that `rm` is valid
+        let weak = unsafe { WeakRef { rm }.get() };
+        // The object outlives the promoted reference: it's not the last one.
+        weak.promote_at(Site::caller())
+            .map_or(ptr::null_mut(), |strong| strong.get().obj)
+    } else {
+        // SAFETY: The caller must ensure that `rm` is NULL or valid
+        unsafe { StrongRef_Get(StrongRef { rm }) }
+    }
+}
+
+/// The reference counts of an object, for `FT.DEBUG SPECREFS`.
+#[repr(C)]
+pub struct RefCounts {
+    pub strong: usize,
+    pub weak: usize,
+    pub invalid: bool,
+}
+
+/// Get the reference counts of the object of a strong reference.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `s_ref` must be a valid, non invalid, strong reference.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn StrongRef_Counts(s_ref: StrongRef) -> RefCounts {
+    // SAFETY: The caller must ensure that `s_ref` is valid
+    let info = unsafe { s_ref.get() }
+        .expect("s_ref must not be invalid")
+        .info();
+    RefCounts {
+        strong: info.strong,
+        weak: info.weak,
+        invalid: info.invalid,
+    }
+}
+
+/// Call `cb` with each live strong reference to the object of `s_ref`, oldest
+/// first: its id and the location it was created at, `file` being `file_len`
+/// bytes long and not NUL-terminated. Only debug builds track them: `cb` isn't
+/// called in release builds.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `s_ref` must be a valid, non invalid, strong reference.
+/// - `cb` must be safe to call with `ctx`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn StrongRef_ForEachLive(
+    s_ref: StrongRef,
+    ctx: *mut c_void,
+    cb: unsafe extern "C" fn(
+        ctx: *mut c_void,
+        id: u64,
+        file: *const c_char,
+        file_len: usize,
+        line: u32,
+    ),
+) {
+    // SAFETY: The caller must ensure that `s_ref` is valid
+    let info = unsafe { s_ref.get() }
+        .expect("s_ref must not be invalid")
+        .info();
+    for live in info.live {
+        let file = live.site.file;
+        // SAFETY: The caller must ensure that `cb` is safe to call with `ctx`
+        unsafe {
+            cb(
+                ctx,
+                live.id,
+                file.as_ptr().cast(),
+                file.len(),
+                live.site.line,
+            )
+        };
+    }
+}
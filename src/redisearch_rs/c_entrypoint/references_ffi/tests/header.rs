@@ -0,0 +1,29 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The declarations of `references_rs.h` the C code relies on. A failure means
+//! that the generated header changed: update the callers, then this test.
+
+use build_utils::header_declarations;
+
+#[test]
+fn test_header_declarations() {
+    let decls = header_declarations("../../headers/references_rs.h").unwrap();
+    for expected in [
+        "typedef void (*RefManager_Free)(void *obj)",
+        "typedef struct StrongRef { struct RefManager *rm",
+        "struct StrongRef StrongRef_NewAt(void *obj, RefManager_Free free_cb, const char *file, uint32_t line)",
+        "struct StrongRef WeakRef_PromoteAt(struct WeakRef w_ref, const char *file, uint32_t line)",
+        "void StrongRef_Release(struct StrongRef s_ref)",
+        "bool StrongRef_Equals(struct StrongRef s_ref, struct StrongRef other)",
+        "void *__RefManager_Get_Object(struct RefManager *rm)",
+    ] {
+        assert!(decls.iter().any(|d| d == expected), "missing `{expected}`");
+    }
+}
@@ -0,0 +1,178 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{
+    ffi::{c_char, c_void},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use references_ffi::{
+    __RefManager_Get_Object, StrongRef_CloneAt, StrongRef_Counts, StrongRef_Demote,
+    StrongRef_Equals, StrongRef_ForEachLive, StrongRef_Get, StrongRef_Invalidate, StrongRef_NewAt,
+    StrongRef_Release, WeakRef_Clone, WeakRef_PromoteAt, WeakRef_Release,
+};
+
+/// Counts how many times it's freed.
+unsafe extern "C" fn free_counter(obj: *mut c_void) {
+    // SAFETY: The tests pass an `AtomicUsize`.
+    let counter = unsafe { &*obj.cast::<AtomicUsize>() };
+    counter.fetch_add(1, Ordering::SeqCst);
+}
+
+const FILE: *const c_char = c"spec.c".as_ptr();
+
+#[test]
+fn test_freed_with_last_strong_ref() {
+    let freed = AtomicUsize::new(0);
+    let obj = (&raw const freed).cast_mut().cast();
+    // SAFETY: `free_counter` is called with `freed`, and `FILE` is static.
+    let strong = unsafe { StrongRef_NewAt(obj, Some(free_counter), FILE, 1) };
+    // SAFETY: `strong` is valid.
+    let weak = unsafe { StrongRef_Demote(strong) };
+    // SAFETY: `weak` is valid, and `FILE` is static.
+    let other = unsafe { WeakRef_PromoteAt(weak, FILE, 2) };
+    // SAFETY: both are valid.
+    assert!(unsafe { StrongRef_Equals(strong, other) });
+    // SAFETY: `other` is valid.
+    assert_eq!(unsafe { StrongRef_Get(other) }, obj);
+
+    // SAFETY: `strong` isn't used after.
+    unsafe { StrongRef_Release(strong) };
+    assert_eq!(freed.load(Ordering::SeqCst), 0);
+    // SAFETY: `other` isn't used after.
+    unsafe { StrongRef_Release(other) };
+    assert_eq!(freed.load(Ordering::SeqCst), 1);
+
+    // SAFETY: `weak` is valid, and `FILE` is static.
+    let promoted = unsafe { WeakRef_PromoteAt(weak, FILE, 3) };
+    assert!(promoted.rm.is_null());
+    // SAFETY: an invalid reference.
+    assert!(unsafe { StrongRef_Get(promoted) }.is_null());
+    // SAFETY: `weak` is valid.
+    assert!(unsafe { __RefManager_Get_Object(weak.rm) }.is_null());
+    // SAFETY: `weak` isn't used after.
+    unsafe { WeakRef_Release(weak) };
+}
+
+#[test]
+fn test_invalidate() {
+    let freed = AtomicUsize::new(0);
+    let obj = (&raw const freed).cast_mut().cast();
+    // SAFETY: `free_counter` is called with `freed`, and `FILE` is static.
+    let strong = unsafe { StrongRef_NewAt(obj, Some(free_counter), FILE, 1) };
+    // SAFETY: `strong` is valid.
+    let weak = unsafe { StrongRef_Demote(strong) };
+    // SAFETY: `weak` is valid.
+    let weak_clone = unsafe { WeakRef_Clone(weak) };
+    // SAFETY: `strong` is valid.
+    unsafe { StrongRef_Invalidate(strong) };
+
+    // SAFETY: `weak_clone` is valid, and `FILE` is static.
+    let promoted = unsafe { WeakRef_PromoteAt(weak_clone, FILE, 2) };
+    assert!(promoted.rm.is_null());
+    // SAFETY: `strong` is valid, and `FILE` is static.
+    let clone = unsafe { StrongRef_CloneAt(strong, FILE, 3) };
+    assert!(clone.rm.is_null());
+    // SAFETY: both are invalid.
+    assert!(unsafe { StrongRef_Equals(clone, clone) });
+    // SAFETY: `clone` is invalid and `strong` valid.
+    assert!(!unsafe { StrongRef_Equals(clone, strong) });
+
+    // The existing reference remains usable.
+    // SAFETY: `strong` is valid.
+    assert_eq!(unsafe { StrongRef_Get(strong) }, obj);
+    // SAFETY: `strong` is valid.
+    let counts = unsafe { StrongRef_Counts(strong) };
+    assert_eq!((counts.strong, counts.weak, counts.invalid), (1, 2, true));
+
+    // SAFETY: none of them is used after.
+    unsafe { WeakRef_Release(weak) };
+    // SAFETY: as above.
+    unsafe { WeakRef_Release(weak_clone) };
+    // SAFETY: as above.
+    unsafe { StrongRef_Release(strong) };
+    assert_eq!(freed.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_weak_refs_are_tagged() {
+    // SAFETY: No callback, and `FILE` is static.
+    let strong = unsafe { StrongRef_NewAt(std::ptr::null_mut(), None, FILE, 1) };
+    // SAFETY: `strong` is valid.
+    let weak = unsafe { StrongRef_Demote(strong) };
+    assert_eq!(strong.rm.addr() & 1, 0);
+    assert_eq!(weak.rm.addr() & 1, 1);
+    // Release builds don't box strong references: both point at the object.
+    #[cfg(not(debug_assertions))]
+    assert_eq!(strong.rm.addr(), weak.rm.addr() & !1);
+
+    // SAFETY: `weak` is valid, and `FILE` is static.
+    let promoted = unsafe { WeakRef_PromoteAt(weak, FILE, 2) };
+    // SAFETY: both are valid.
+    assert!(unsafe { StrongRef_Equals(strong, promoted) });
+    // SAFETY: `weak` is valid.
+    assert!(unsafe { __RefManager_Get_Object(weak.rm) }.is_null());
+    // SAFETY: none of them is used after.
+    unsafe { StrongRef_Release(promoted) };
+    // SAFETY: as above.
+    unsafe { WeakRef_Release(weak) };
+    // SAFETY: as above.
+    unsafe { StrongRef_Release(strong) };
+}
+
+#[cfg(debug_assertions)]
+#[test]
+fn test_for_each_live() {
+    unsafe extern "C" fn collect(
+        ctx: *mut c_void,
+        _id: u64,
+        file: *const c_char,
+        file_len: usize,
+        line: u32,
+    ) {
+        // SAFETY: The test passes a `Vec<String>`.
+        let sites = unsafe { &mut *ctx.cast::<Vec<String>>() };
+        // SAFETY: `file` is `file_len` bytes long.
+        let file = unsafe { std::slice::from_raw_parts(file.cast::<u8>(), file_len) };
+        sites.push(format!("{}:{line}", std::str::from_utf8(file).unwrap()));
+    }
+
+    // SAFETY: No callback, and `FILE` is static.
+    let strong = unsafe { StrongRef_NewAt(std::ptr::null_mut(), None, FILE, 10) };
+    // SAFETY: `strong` is valid, and `FILE` is static.
+    let leaked = unsafe { StrongRef_CloneAt(strong, FILE, 20) };
+    let mut sites = Vec::<String>::new();
+    // SAFETY: `strong` is valid, and `collect` is called with `sites`.
+    unsafe { StrongRef_ForEachLive(strong, (&raw mut sites).cast(), collect) };
+    assert_eq!(sites, ["spec.c:10", "spec.c:20"]);
+
+    // SAFETY: `leaked` isn't used after.
+    unsafe { StrongRef_Release(leaked) };
+    sites.clear();
+    // SAFETY: as above.
+    unsafe { StrongRef_ForEachLive(strong, (&raw mut sites).cast(), collect) };
+    assert_eq!(sites, ["spec.c:10"]);
+    // SAFETY: `strong` isn't used after.
+    unsafe { StrongRef_Release(strong) };
+}
+
+#[cfg(not(debug_assertions))]
+#[test]
+fn test_for_each_live_untracked() {
+    unsafe extern "C" fn unexpected(_: *mut c_void, _: u64, _: *const c_char, _: usize, _: u32) {
+        panic!("release builds don't track the live references");
+    }
+
+    // SAFETY: No callback, and `FILE` is static.
+    let strong = unsafe { StrongRef_NewAt(std::ptr::null_mut(), None, FILE, 10) };
+    // SAFETY: `strong` is valid, and `unexpected` ignores its context.
+    unsafe { StrongRef_ForEachLive(strong, std::ptr::null_mut(), unexpected) };
+    // SAFETY: `strong` isn't used after.
+    unsafe { StrongRef_Release(strong) };
+}
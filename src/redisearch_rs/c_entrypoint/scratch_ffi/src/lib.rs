@@ -0,0 +1,46 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! FFI layer to reply the memory held by the scratch buffers of the threads to
+//! `FT.DEBUG SCRATCH_MEMORY`.
+
+use std::ffi::{c_char, c_void};
+
+/// Reply the memory held by the scratch buffers of every thread which used
+/// them, in the order the threads first did: `thread` is called with `reply`
+/// for each of them, with its name and the bytes its buffers hold. The name
+/// isn't NUL-terminated, and is only valid during the call.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `thread` must be safe to call with `reply`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn Scratch_ReplyThreads(
+    reply: *mut c_void,
+    thread: unsafe extern "C" fn(
+        reply: *mut c_void,
+        name: *const c_char,
+        name_len: usize,
+        bytes: usize,
+    ),
+) {
+    for scratch in scratch::threads() {
+        // SAFETY: The caller must ensure that `thread` is safe to call with `reply`, and the name
+        // outlives the call.
+        unsafe {
+            thread(
+                reply,
+                scratch.thread.as_ptr().cast(),
+                scratch.thread.len(),
+                scratch.bytes,
+            )
+        };
+    }
+}
@@ -0,0 +1,20 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The declarations of `scratch_rs.h` the C code relies on. A failure means
+//! that the generated header changed: update the callers, then this test.
+
+use build_utils::header_declarations;
+
+#[test]
+fn test_header_declarations() {
+    let decls = header_declarations("../../headers/scratch_rs.h").unwrap();
+    let expected = "void Scratch_ReplyThreads(void *reply, void (*thread)(void *reply, const char *name, uintptr_t name_len, uintptr_t bytes))";
+    assert!(decls.iter().any(|d| d == expected), "missing `{expected}`");
+}
@@ -0,0 +1,46 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::ffi::{c_char, c_void};
+
+use scratch_ffi::Scratch_ReplyThreads;
+
+unsafe extern "C" fn push_thread(
+    reply: *mut c_void,
+    name: *const c_char,
+    name_len: usize,
+    bytes: usize,
+) {
+    // SAFETY: the test passes a `Vec`.
+    let reply = unsafe { &mut *reply.cast::<Vec<(String, usize)>>() };
+    // SAFETY: the name is valid for reads of `name_len` bytes.
+    let name = unsafe { std::slice::from_raw_parts(name.cast::<u8>(), name_len) };
+    reply.push((String::from_utf8(name.to_vec()).unwrap(), bytes));
+}
+
+#[test]
+fn test_reply_threads() {
+    std::thread::Builder::new()
+        .name("scratch-ffi-test".to_owned())
+        .spawn(|| {
+            scratch::with_bytes(|buf| buf.extend_from_slice(&[0; 100]));
+
+            let mut reply: Vec<(String, usize)> = Vec::new();
+            // SAFETY: `push_thread` expects a `Vec`.
+            unsafe { Scratch_ReplyThreads((&raw mut reply).cast(), push_thread) };
+            let (_, bytes) = reply
+                .iter()
+                .find(|(name, _)| name == "scratch-ffi-test")
+                .expect("the thread used its buffers");
+            assert!(*bytes >= 100);
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
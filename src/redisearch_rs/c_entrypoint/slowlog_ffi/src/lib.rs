@@ -0,0 +1,354 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! FFI layer to record the slow queries of an index from the C query
+//! execution, and to reply them to `FT._SLOWLOG`.
+//!
+//! As for the GC statistics, the entries are replied through callbacks, which
+//! the C code implements with the `RedisModule_Reply` API.
+
+use std::{
+    borrow::Cow,
+    ffi::{CStr, c_char, c_void},
+    time::Duration,
+};
+
+use pipeline::slowlog::{QueryTimings, SlowLogCommand, SlowLogConfig, SlowQuery};
+
+/// The slow log of an index. Queries are recorded by the threads running them
+/// while `FT._SLOWLOG` reads the log from the main thread, hence the log locks
+/// its entries.
+pub struct SlowLog(pipeline::slowlog::SlowLog);
+
+/// The time spent in each phase of a query, in nanoseconds.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SlowLogTimings {
+    pub parse_ns: u64,
+    pub execute_ns: u64,
+    pub load_ns: u64,
+    pub serialize_ns: u64,
+}
+
+impl From<SlowLogTimings> for QueryTimings {
+    fn from(timings: SlowLogTimings) -> Self {
+        Self {
+            parse: Duration::from_nanos(timings.parse_ns),
+            execute: Duration::from_nanos(timings.execute_ns),
+            load: Duration::from_nanos(timings.load_ns),
+            serialize: Duration::from_nanos(timings.serialize_ns),
+        }
+    }
+}
+
+impl From<QueryTimings> for SlowLogTimings {
+    fn from(timings: QueryTimings) -> Self {
+        Self {
+            parse_ns: timings.parse.as_nanos() as u64,
+            execute_ns: timings.execute.as_nanos() as u64,
+            load_ns: timings.load.as_nanos() as u64,
+            serialize_ns: timings.serialize.as_nanos() as u64,
+        }
+    }
+}
+
+/// A `PARAMS` argument of a query. The strings needn't be NUL-terminated.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SlowLogParam {
+    pub name: *const c_char,
+    pub name_len: usize,
+    pub value: *const c_char,
+    pub value_len: usize,
+}
+
+/// A recorded query, as passed to the reply callbacks. `query` isn't
+/// NUL-terminated, and is only valid during the callback.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SlowLogEntryInfo {
+    pub id: u64,
+    /// When the query was recorded, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub query: *const c_char,
+    pub query_len: usize,
+    pub timings: SlowLogTimings,
+    pub total_results: u64,
+    pub returned_results: u64,
+    pub num_params: usize,
+}
+
+/// A subcommand of `FT._SLOWLOG`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlowLogSubcommand {
+    Get,
+    Len,
+    Reset,
+}
+
+/// Create a slow log recording the queries taking at least `threshold_us`
+/// microseconds, keeping the `max_len` most recent ones. The values of the
+/// `PARAMS` of the queries are replaced by `?` if `redact_params` is set. The
+/// log must be freed using [`SlowLog_Free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn SlowLog_New(
+    threshold_us: u64,
+    max_len: usize,
+    redact_params: bool,
+) -> *mut SlowLog {
+    let config = SlowLogConfig {
+        threshold: Duration::from_micros(threshold_us),
+        max_len,
+        redact_params,
+    };
+    Box::into_raw(Box::new(SlowLog(pipeline::slowlog::SlowLog::new(config))))
+}
+
+/// Free the slow log created using [`SlowLog_New`].
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `log` must be a valid, non NULL, pointer created using [`SlowLog_New`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn SlowLog_Free(log: *mut SlowLog) {
+    debug_assert!(!log.is_null(), "log must not be null");
+
+    // SAFETY: The caller must ensure that `log` was created using `SlowLog_New`
+    let _ = unsafe { Box::from_raw(log) };
+}
+
+/// Read the `len` bytes at `ptr` as a string, replacing invalid UTF-8.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads of `len` bytes, and may only be NULL if `len`
+/// is 0.
+unsafe fn str_from_raw<'a>(ptr: *const c_char, len: usize) -> Cow<'a, str> {
+    if len == 0 {
+        return Cow::Borrowed("");
+    }
+    // SAFETY: The caller must ensure that `ptr` is valid for reads of `len` bytes
+    let bytes = unsafe { std::slice::from_raw_parts(ptr.cast::<u8>(), len) };
+    String::from_utf8_lossy(bytes)
+}
+
+/// Record the query `query`, run with the `num_params` arguments of its
+/// `PARAMS`, if it took at least the threshold of `log`. It matched
+/// `total_results` documents, of which `returned_results` were replied, and
+/// finished at `timestamp`, in seconds since the Unix epoch. Returns whether
+/// it was recorded.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `log` must be a valid, non NULL, pointer created using [`SlowLog_New`].
+/// - `query` must be valid for reads of `query_len` bytes. It may be NULL if `query_len` is 0.
+/// - `params` must point to `num_params` parameters, whose strings are valid for reads of their
+///   lengths. It may be NULL if `num_params` is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn SlowLog_Record(
+    log: *const SlowLog,
+    query: *const c_char,
+    query_len: usize,
+    params: *const SlowLogParam,
+    num_params: usize,
+    timings: SlowLogTimings,
+    total_results: u64,
+    returned_results: u64,
+    timestamp: u64,
+) -> bool {
+    debug_assert!(!log.is_null(), "log must not be null");
+
+    // SAFETY: The caller must ensure that `log` is a valid pointer to a `SlowLog`
+    let log = unsafe { &*log };
+    let timings = QueryTimings::from(timings);
+    // Fast queries are the common case: don't copy their strings
+    if timings.total() < log.0.config().threshold {
+        return false;
+    }
+
+    // SAFETY: The caller must ensure that `query` is valid for reads of `query_len` bytes
+    let query = unsafe { str_from_raw(query, query_len) };
+    let params = if num_params == 0 {
+        &[][..]
+    } else {
+        // SAFETY: The caller must ensure that `params` points to `num_params` parameters
+        unsafe { std::slice::from_raw_parts(params, num_params) }
+    };
+    let params: Vec<(Cow<'_, str>, Cow<'_, str>)> = params
+        .iter()
+        .map(|param| {
+            // SAFETY: The caller must ensure that the names are valid for reads of their lengths
+            let name = unsafe { str_from_raw(param.name, param.name_len) };
+            // SAFETY: The caller must ensure that the values are valid for reads of their lengths
+            let value = unsafe { str_from_raw(param.value, param.value_len) };
+            (name, value)
+        })
+        .collect();
+    let params: Vec<(&str, &str)> = params
+        .iter()
+        .map(|(name, value)| (name.as_ref(), value.as_ref()))
+        .collect();
+
+    log.0.record(
+        &SlowQuery {
+            query: &query,
+            params: &params,
+            timings,
+            total_results,
+            returned_results,
+        },
+        timestamp,
+    )
+}
+
+/// The number of entries of `log`, replied to `FT._SLOWLOG {index} LEN`.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `log` must be a valid, non NULL, pointer created using [`SlowLog_New`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn SlowLog_Len(log: *const SlowLog) -> usize {
+    debug_assert!(!log.is_null(), "log must not be null");
+
+    // SAFETY: The caller must ensure that `log` is a valid pointer to a `SlowLog`
+    let log = unsafe { &*log };
+    log.0.len()
+}
+
+/// Remove the entries of `log`, for `FT._SLOWLOG {index} RESET`. The ids of
+/// the next entries keep increasing.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `log` must be a valid, non NULL, pointer created using [`SlowLog_New`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn SlowLog_Reset(log: *const SlowLog) {
+    debug_assert!(!log.is_null(), "log must not be null");
+
+    // SAFETY: The caller must ensure that `log` is a valid pointer to a `SlowLog`
+    let log = unsafe { &*log };
+    log.0.reset();
+}
+
+/// Reply the `count` most recent entries of `log`, all of them if `count` is
+/// `SIZE_MAX`, to `FT._SLOWLOG {index} GET`, the most recent first.
+///
+/// For each entry, `entry` is called with its fields, then `param` with each
+/// of its `PARAMS` (the strings aren't NUL-terminated), then `entry_end`.
+/// They're all called with `reply`, while `log` is locked: they must not use
+/// `log`.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `log` must be a valid, non NULL, pointer created using [`SlowLog_New`].
+/// - The callbacks must be safe to call with `reply`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn SlowLog_Reply(
+    log: *const SlowLog,
+    count: usize,
+    reply: *mut c_void,
+    entry: unsafe extern "C" fn(reply: *mut c_void, info: *const SlowLogEntryInfo),
+    param: unsafe extern "C" fn(
+        reply: *mut c_void,
+        name: *const c_char,
+        name_len: usize,
+        value: *const c_char,
+        value_len: usize,
+    ),
+    entry_end: unsafe extern "C" fn(reply: *mut c_void),
+) {
+    debug_assert!(!log.is_null(), "log must not be null");
+
+    // SAFETY: The caller must ensure that `log` is a valid pointer to a `SlowLog`
+    let log = unsafe { &*log };
+    let count = (count != usize::MAX).then_some(count);
+    for recorded in log.0.get(count) {
+        let info = SlowLogEntryInfo {
+            id: recorded.id,
+            timestamp: recorded.timestamp,
+            query: recorded.query.as_ptr().cast(),
+            query_len: recorded.query.len(),
+            timings: recorded.timings.into(),
+            total_results: recorded.total_results,
+            returned_results: recorded.returned_results,
+            num_params: recorded.params.len(),
+        };
+        // SAFETY: The callbacks are safe to call with `reply`, as required by the caller, and
+        // `info` outlives the call.
+        unsafe { entry(reply, &info) };
+        for (name, value) in &recorded.params {
+            // SAFETY: As above, the strings outlive the call.
+            unsafe {
+                param(
+                    reply,
+                    name.as_ptr().cast(),
+                    name.len(),
+                    value.as_ptr().cast(),
+                    value.len(),
+                )
+            };
+        }
+        // SAFETY: As above.
+        unsafe { entry_end(reply) };
+    }
+}
+
+/// Parse the `argc` arguments of `FT._SLOWLOG` following the index name.
+/// `count` is set to the count of `GET`, or to `SIZE_MAX` without one. Returns
+/// false, leaving the output parameters untouched, if they're invalid.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `argv` must point to `argc` valid, NUL-terminated strings. It may be NULL if `argc` is 0.
+/// - `subcommand` and `count` must be valid, non NULL, pointers.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn SlowLog_ParseCommand(
+    argv: *const *const c_char,
+    argc: usize,
+    subcommand: *mut SlowLogSubcommand,
+    count: *mut usize,
+) -> bool {
+    debug_assert!(!subcommand.is_null(), "subcommand must not be null");
+    debug_assert!(!count.is_null(), "count must not be null");
+
+    let argv = if argc == 0 {
+        &[][..]
+    } else {
+        // SAFETY: The caller must ensure that `argv` points to `argc` strings
+        unsafe { std::slice::from_raw_parts(argv, argc) }
+    };
+    let args: Option<Vec<&str>> = argv
+        .iter()
+        .map(|&arg| {
+            // SAFETY: The caller must ensure that the arguments are NUL-terminated strings
+            unsafe { CStr::from_ptr(arg) }.to_str().ok()
+        })
+        .collect();
+    let Some(Ok(command)) = args.as_deref().map(SlowLogCommand::parse) else {
+        return false;
+    };
+
+    let (parsed, parsed_count) = match command {
+        SlowLogCommand::Get(n) => (SlowLogSubcommand::Get, n.unwrap_or(usize::MAX)),
+        SlowLogCommand::Len => (SlowLogSubcommand::Len, usize::MAX),
+        SlowLogCommand::Reset => (SlowLogSubcommand::Reset, usize::MAX),
+    };
+    // SAFETY: The caller must ensure that `subcommand` is a valid pointer
+    unsafe { *subcommand = parsed };
+    // SAFETY: The caller must ensure that `count` is a valid pointer
+    unsafe { *count = parsed_count };
+    true
+}
@@ -0,0 +1,29 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The declarations of `slowlog_rs.h` the C code relies on. A failure means
+//! that the generated header changed: update the callers, then this test.
+
+use build_utils::header_declarations;
+
+#[test]
+fn test_header_declarations() {
+    let decls = header_declarations("../../headers/slowlog_rs.h").unwrap();
+    for expected in [
+        "struct SlowLog *SlowLog_New(uint64_t threshold_us, uintptr_t max_len, bool redact_params)",
+        "void SlowLog_Free(struct SlowLog *log)",
+        "bool SlowLog_Record(const struct SlowLog *log, const char *query, uintptr_t query_len, const struct SlowLogParam *params, uintptr_t num_params, struct SlowLogTimings timings, uint64_t total_results, uint64_t returned_results, uint64_t timestamp)",
+        "uintptr_t SlowLog_Len(const struct SlowLog *log)",
+        "void SlowLog_Reset(const struct SlowLog *log)",
+        "void SlowLog_Reply(const struct SlowLog *log, uintptr_t count, void *reply, void (*entry)(void *reply, const struct SlowLogEntryInfo *info), void (*param)(void *reply, const char *name, uintptr_t name_len, const char *value, uintptr_t value_len), void (*entry_end)(void *reply))",
+        "bool SlowLog_ParseCommand(const char *const *argv, uintptr_t argc, enum SlowLogSubcommand *subcommand, uintptr_t *count)",
+    ] {
+        assert!(decls.iter().any(|d| d == expected), "missing `{expected}`");
+    }
+}
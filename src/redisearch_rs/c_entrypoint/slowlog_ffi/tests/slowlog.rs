@@ -0,0 +1,256 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::ffi::{CString, c_char, c_void};
+
+use slowlog_ffi::{
+    SlowLog_Free, SlowLog_Len, SlowLog_New, SlowLog_ParseCommand, SlowLog_Record, SlowLog_Reply,
+    SlowLog_Reset, SlowLogEntryInfo, SlowLogParam, SlowLogSubcommand, SlowLogTimings,
+};
+
+fn string(ptr: *const c_char, len: usize) -> String {
+    // SAFETY: the log passes `len` readable bytes.
+    let bytes = unsafe { std::slice::from_raw_parts(ptr.cast::<u8>(), len) };
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+/// The reply as lines, one per entry and per parameter.
+#[derive(Default)]
+struct Reply {
+    lines: Vec<String>,
+    open: bool,
+}
+
+unsafe extern "C" fn entry(reply: *mut c_void, info: *const SlowLogEntryInfo) {
+    // SAFETY: the tests pass a `Reply`.
+    let reply = unsafe { &mut *reply.cast::<Reply>() };
+    // SAFETY: the log passes a valid entry.
+    let info = unsafe { &*info };
+    assert!(!reply.open);
+    reply.open = true;
+    reply.lines.push(format!(
+        "{} @{} `{}` {}us {}/{} params={}",
+        info.id,
+        info.timestamp,
+        string(info.query, info.query_len),
+        (info.timings.parse_ns
+            + info.timings.execute_ns
+            + info.timings.load_ns
+            + info.timings.serialize_ns)
+            / 1000,
+        info.returned_results,
+        info.total_results,
+        info.num_params,
+    ));
+}
+
+unsafe extern "C" fn param(
+    reply: *mut c_void,
+    name: *const c_char,
+    name_len: usize,
+    value: *const c_char,
+    value_len: usize,
+) {
+    // SAFETY: the tests pass a `Reply`.
+    let reply = unsafe { &mut *reply.cast::<Reply>() };
+    assert!(reply.open);
+    reply.lines.push(format!(
+        "  {}={}",
+        string(name, name_len),
+        string(value, value_len)
+    ));
+}
+
+unsafe extern "C" fn entry_end(reply: *mut c_void) {
+    // SAFETY: the tests pass a `Reply`.
+    let reply = unsafe { &mut *reply.cast::<Reply>() };
+    assert!(reply.open);
+    reply.open = false;
+}
+
+fn reply(log: *const slowlog_ffi::SlowLog, count: usize) -> Vec<String> {
+    let mut reply = Reply::default();
+    // SAFETY: `log` is valid and the callbacks expect a `Reply`.
+    unsafe { SlowLog_Reply(log, count, (&raw mut reply).cast(), entry, param, entry_end) };
+    assert!(!reply.open);
+    reply.lines
+}
+
+const fn timings(execute_us: u64) -> SlowLogTimings {
+    SlowLogTimings {
+        parse_ns: 1000,
+        execute_ns: execute_us * 1000,
+        load_ns: 0,
+        serialize_ns: 0,
+    }
+}
+
+fn record(
+    log: *const slowlog_ffi::SlowLog,
+    query: &str,
+    params: &[(&str, &str)],
+    execute_us: u64,
+    timestamp: u64,
+) -> bool {
+    let params: Vec<SlowLogParam> = params
+        .iter()
+        .map(|(name, value)| SlowLogParam {
+            name: name.as_ptr().cast(),
+            name_len: name.len(),
+            value: value.as_ptr().cast(),
+            value_len: value.len(),
+        })
+        .collect();
+    // SAFETY: `log` is valid and the strings are valid for reads of their lengths.
+    unsafe {
+        SlowLog_Record(
+            log,
+            query.as_ptr().cast(),
+            query.len(),
+            params.as_ptr(),
+            params.len(),
+            timings(execute_us),
+            10,
+            3,
+            timestamp,
+        )
+    }
+}
+
+#[test]
+fn test_record_and_reply() {
+    let log = SlowLog_New(100, 2, false);
+
+    assert!(!record(log, "@t:fast", &[], 10, 1));
+    assert!(record(log, "@t:slow", &[], 200, 2));
+    assert!(record(
+        log,
+        "@v:[$lo $hi]",
+        &[("lo", "1"), ("hi", "5")],
+        300,
+        3
+    ));
+    // SAFETY: `log` is valid.
+    assert_eq!(unsafe { SlowLog_Len(log) }, 2);
+
+    assert_eq!(
+        reply(log, usize::MAX),
+        [
+            "1 @3 `@v:[$lo $hi]` 301us 3/10 params=2",
+            "  lo=1",
+            "  hi=5",
+            "0 @2 `@t:slow` 201us 3/10 params=0",
+        ]
+    );
+    assert_eq!(
+        reply(log, 1),
+        [
+            "1 @3 `@v:[$lo $hi]` 301us 3/10 params=2",
+            "  lo=1",
+            "  hi=5"
+        ]
+    );
+
+    // The oldest entry is dropped once the log is full
+    assert!(record(log, "@t:slower", &[], 400, 4));
+    assert_eq!(
+        reply(log, usize::MAX)
+            .into_iter()
+            .filter(|line| !line.starts_with(' '))
+            .collect::<Vec<_>>(),
+        [
+            "2 @4 `@t:slower` 401us 3/10 params=0",
+            "1 @3 `@v:[$lo $hi]` 301us 3/10 params=2",
+        ]
+    );
+
+    // SAFETY: `log` is valid.
+    unsafe { SlowLog_Reset(log) };
+    // SAFETY: `log` is valid.
+    assert_eq!(unsafe { SlowLog_Len(log) }, 0);
+    assert!(reply(log, usize::MAX).is_empty());
+
+    // The ids keep increasing after a reset
+    assert!(record(log, "@t:slow", &[], 200, 5));
+    assert_eq!(
+        reply(log, usize::MAX),
+        ["3 @5 `@t:slow` 201us 3/10 params=0"]
+    );
+
+    // SAFETY: `log` was created by `SlowLog_New`.
+    unsafe { SlowLog_Free(log) };
+}
+
+#[test]
+fn test_redacted_params() {
+    let log = SlowLog_New(0, 8, true);
+
+    assert!(record(log, "@t:$v", &[("v", "secret")], 0, 1));
+    assert_eq!(
+        reply(log, usize::MAX),
+        ["0 @1 `@t:$v` 1us 3/10 params=1", "  v=?"]
+    );
+
+    // SAFETY: `log` was created by `SlowLog_New`.
+    unsafe { SlowLog_Free(log) };
+}
+
+#[test]
+fn test_record_empty_query() {
+    let log = SlowLog_New(0, 8, false);
+
+    // SAFETY: `log` is valid, and NULL pointers are allowed for empty strings.
+    let recorded = unsafe {
+        SlowLog_Record(
+            log,
+            std::ptr::null(),
+            0,
+            std::ptr::null(),
+            0,
+            SlowLogTimings::default(),
+            0,
+            0,
+            1,
+        )
+    };
+    assert!(recorded);
+    assert_eq!(reply(log, usize::MAX), ["0 @1 `` 0us 0/0 params=0"]);
+
+    // SAFETY: `log` was created by `SlowLog_New`.
+    unsafe { SlowLog_Free(log) };
+}
+
+fn parse(args: &[&str]) -> Option<(SlowLogSubcommand, usize)> {
+    let args: Vec<CString> = args.iter().map(|arg| CString::new(*arg).unwrap()).collect();
+    let argv: Vec<*const c_char> = args.iter().map(|arg| arg.as_ptr()).collect();
+    let mut subcommand = SlowLogSubcommand::Reset;
+    let mut count = 0;
+    // SAFETY: `argv` points to `argv.len()` NUL-terminated strings, and the output pointers are
+    // valid.
+    let parsed =
+        unsafe { SlowLog_ParseCommand(argv.as_ptr(), argv.len(), &mut subcommand, &mut count) };
+    parsed.then_some((subcommand, count))
+}
+
+#[test]
+fn test_parse_command() {
+    assert_eq!(parse(&["GET"]), Some((SlowLogSubcommand::Get, usize::MAX)));
+    assert_eq!(parse(&["get", "5"]), Some((SlowLogSubcommand::Get, 5)));
+    assert_eq!(parse(&["LEN"]), Some((SlowLogSubcommand::Len, usize::MAX)));
+    assert_eq!(
+        parse(&["reset"]),
+        Some((SlowLogSubcommand::Reset, usize::MAX))
+    );
+
+    assert_eq!(parse(&[]), None);
+    assert_eq!(parse(&["GET", "many"]), None);
+    assert_eq!(parse(&["GET", "1", "2"]), None);
+    assert_eq!(parse(&["LEN", "1"]), None);
+    assert_eq!(parse(&["FLUSH"]), None);
+}
@@ -9,6 +9,7 @@
 
 #![allow(non_camel_case_types, non_snake_case)]
 
+use defrag::{CallbackDefragger, DefragStatus};
 use redis_module::raw::RedisModule_Free;
 use std::{
     ffi::{c_char, c_int, c_void},
@@ -355,3 +356,44 @@ pub unsafe extern "C" fn TrieMap_NNodes(t: *mut TrieMap) -> usize {
     let TrieMap(trie) = unsafe { &mut *t };
     trie.n_nodes()
 }
+
+/// Relocate the nodes of the triemap during active defragmentation, starting
+/// at node `*cursor`.
+///
+/// `alloc` and `should_stop` are `RedisModule_DefragAlloc` and
+/// `RedisModule_DefragShouldStop`, called with `ctx`. Returns `true` once the
+/// whole trie has been processed, resetting `*cursor` to 0. Returns `false`
+/// if `should_stop` interrupted the pass, in which case the call must be
+/// repeated later with the same cursor.
+///
+/// The values aren't relocated, since the trie doesn't own them.
+///
+/// # Safety
+///
+/// The following invariants must be upheld when calling this function:
+/// - `t` must point to a valid TrieMap obtained from [`NewTrieMap`] and cannot be NULL.
+/// - No iterator over `t` may be alive.
+/// - `cursor` must be a valid, non NULL, pointer to a cursor, initially 0.
+/// - `alloc` must behave like `RedisModule_DefragAlloc`, and both callbacks must be safe to
+///   call with `ctx`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn TrieMap_Defrag(
+    t: *mut TrieMap,
+    cursor: *mut usize,
+    ctx: *mut c_void,
+    alloc: unsafe extern "C" fn(ctx: *mut c_void, ptr: *mut c_void) -> *mut c_void,
+    should_stop: unsafe extern "C" fn(ctx: *mut c_void) -> c_int,
+) -> bool {
+    debug_assert!(!t.is_null(), "t cannot be NULL");
+    debug_assert!(!cursor.is_null(), "cursor cannot be NULL");
+
+    // SAFETY: The caller must ensure that `t` is a valid TrieMap obtained
+    // from `NewTrieMap`, which no iterator borrows.
+    let TrieMap(trie) = unsafe { &mut *t };
+    // SAFETY: The caller must ensure that `cursor` is a valid pointer.
+    let cursor = unsafe { &mut *cursor };
+    // SAFETY: The caller must ensure the callbacks behave as the Redis defrag API.
+    let mut defragger = unsafe { CallbackDefragger::new(ctx, alloc, should_stop) };
+
+    trie.defrag(cursor, &mut defragger) == DefragStatus::Done
+}
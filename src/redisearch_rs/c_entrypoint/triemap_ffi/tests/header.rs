@@ -0,0 +1,47 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The declarations of `triemap.h` the C code relies on. A failure means that
+//! the generated header changed: update the callers, then this test.
+
+use build_utils::header_declarations;
+
+#[test]
+fn test_header_declarations() {
+    let decls = header_declarations("../../headers/triemap.h").unwrap();
+    for expected in [
+        "typedef uint16_t tm_len_t",
+        "typedef void *(*TrieMapReplaceFunc)(void *oldval, void *newval)",
+        "typedef void (*freeCB)(void*)",
+        "typedef struct LowMemoryThinVecCVoid TrieMapResultBuf",
+        "typedef void (*TrieMapRangeCallback)(const char*, size_t, void*, void*)",
+        "extern void *TRIEMAP_NOTFOUND",
+        "struct TrieMap *NewTrieMap(void)",
+        "int TrieMap_Add(struct TrieMap *t, const char *str, tm_len_t len, void *value, TrieMapReplaceFunc cb)",
+        "void *TrieMap_Find(struct TrieMap *t, const char *str, tm_len_t len)",
+        "int TrieMap_Delete(struct TrieMap *t, const char *str, tm_len_t len, freeCB func)",
+        "void TrieMap_Free(struct TrieMap *t, freeCB func)",
+        "uintptr_t TrieMap_MemUsage(struct TrieMap *t)",
+        "uintptr_t TrieMap_NUniqueKeys(struct TrieMap *t)",
+        "uintptr_t TrieMap_NNodes(struct TrieMap *t)",
+        "TrieMapResultBuf TrieMap_FindPrefixes(struct TrieMap *t, const char *str, tm_len_t len)",
+        "void TrieMapResultBuf_Free(TrieMapResultBuf buf)",
+        "void **TrieMapResultBuf_Data(TrieMapResultBuf *buf)",
+        "void *TrieMapResultBuf_GetByIndex(TrieMapResultBuf *buf, uintptr_t index)",
+        "uintptr_t TrieMapResultBuf_Len(TrieMapResultBuf *buf)",
+        "struct TrieMapIterator *TrieMap_Iterate(struct TrieMap *t)",
+        "struct TrieMapIterator *TrieMap_IterateWithFilter(struct TrieMap *t, const char *prefix, tm_len_t prefix_len, enum tm_iter_mode iter_mode)",
+        "void TrieMapIterator_SetTimeout(struct TrieMapIterator *it, struct timespec timeout)",
+        "void TrieMapIterator_Free(struct TrieMapIterator *it)",
+        "int TrieMapIterator_Next(struct TrieMapIterator *it, char **ptr, tm_len_t *len, void **value)",
+        "void TrieMap_IterateRange(struct TrieMap *trie, const char *min, int minlen, bool includeMin, const char *max, int maxlen, bool includeMax, TrieMapRangeCallback callback, void *ctx)",
+    ] {
+        assert!(decls.iter().any(|d| d == expected), "missing `{expected}`");
+    }
+}
@@ -0,0 +1,36 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The declarations of `varint.h` the C code relies on. A failure means that
+//! the generated header changed: update the callers, then this test.
+
+use build_utils::header_declarations;
+
+#[test]
+fn test_header_declarations() {
+    let decls = header_declarations("../../headers/varint.h").unwrap();
+    for expected in [
+        "typedef t_fieldMask FieldMask",
+        "FieldMask ReadVarintFieldMask(BufferReader *b)",
+        "uintptr_t WriteVarintFieldMask(FieldMask value, BufferWriter *writer)",
+        "uint32_t ReadVarint(BufferReader *b)",
+        "uintptr_t WriteVarint(uint32_t value, BufferWriter *writer)",
+        "struct VarintVectorWriter *NewVarintVectorWriter(uintptr_t cap)",
+        "uintptr_t VVW_Write(struct VarintVectorWriter *writer, uint32_t value)",
+        "const uint8_t *VVW_GetByteData(const struct VarintVectorWriter *writer)",
+        "uintptr_t VVW_GetByteLength(const struct VarintVectorWriter *writer)",
+        "uintptr_t VVW_GetCount(const struct VarintVectorWriter *writer)",
+        "void VVW_Reset(struct VarintVectorWriter *writer)",
+        "void VVW_Free(struct VarintVectorWriter *writer)",
+        "uintptr_t VVW_Truncate(struct VarintVectorWriter *writer)",
+        "uint8_t *VVW_TakeByteData(struct VarintVectorWriter *writer, uintptr_t *len)",
+    ] {
+        assert!(decls.iter().any(|d| d == expected), "missing `{expected}`");
+    }
+}
@@ -0,0 +1,105 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! FFI layer to pass [`ZStr`]s between the C reply code and Rust.
+//!
+//! A [`RawZStr`] is owned by whoever holds it: passing one to a function of
+//! this module gives up its ownership, and every [`RawZStr`] returned must be
+//! released with [`ZStr_Release`], or passed on to a function taking
+//! ownership.
+
+use std::ffi::c_char;
+
+use zstr::ZStr;
+pub use zstr::{RawZStr, ReleaseFn};
+
+/// Borrow the `len` bytes at `ptr`, e.g. the contents of a
+/// `RedisModuleString` or an `sds` which outlive the returned string. Nothing
+/// is copied, and releasing it doesn't free the bytes.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `ptr` must be valid for reads of `len` bytes, and may only be NULL if
+///   `len` is 0. The bytes must not be modified or freed while the returned
+///   string is in use.
+#[unsafe(no_mangle)]
+pub const unsafe extern "C" fn ZStr_Borrow(ptr: *const c_char, len: usize) -> RawZStr {
+    debug_assert!(len == 0 || !ptr.is_null(), "ptr must not be null");
+    let empty: &[u8] = &[];
+    RawZStr {
+        // `ZStr` doesn't accept NULL, even for an empty string.
+        ptr: if len == 0 { empty.as_ptr() } else { ptr.cast() },
+        len,
+        handle: std::ptr::null_mut(),
+        release: None,
+    }
+}
+
+/// Copy the `len` bytes at `ptr` into a string owning them.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `ptr` must be valid for reads of `len` bytes, and may only be NULL if
+///   `len` is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ZStr_Copy(ptr: *const c_char, len: usize) -> RawZStr {
+    // SAFETY: upheld by the caller.
+    let borrowed = unsafe { ZStr_Borrow(ptr, len) };
+    // SAFETY: `ZStr_Borrow` returned a valid string, borrowing bytes which
+    // outlive this call.
+    unsafe { ZStr_IntoOwned(borrowed) }
+}
+
+/// Turn `s` into a string owning its bytes, which may outlive the bytes it
+/// borrowed. Only a borrowed string is copied.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `s` must be valid as described by [`RawZStr`], and the caller gives up
+///   its ownership.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ZStr_IntoOwned(s: RawZStr) -> RawZStr {
+    // SAFETY: upheld by the caller.
+    let s = unsafe { ZStr::from_raw(s) };
+    s.into_owned().into_raw()
+}
+
+/// A string owning `prefix` followed by the bytes of `s`, as sent for the
+/// sort keys and required fields of a shard reply (`$value`).
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `s` must be valid as described by [`RawZStr`], and the caller gives up
+///   its ownership.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ZStr_Prefix(prefix: c_char, s: RawZStr) -> RawZStr {
+    // SAFETY: upheld by the caller.
+    let s = unsafe { ZStr::from_raw(s) };
+    let mut bytes = Vec::with_capacity(1 + s.len());
+    bytes.push(prefix as u8);
+    bytes.extend_from_slice(&s);
+    ZStr::from(bytes).into_raw()
+}
+
+/// Release `s`, freeing its bytes if it owns them.
+///
+/// # Safety
+///
+/// The following invariant must be upheld when calling this function:
+/// - `s` must be valid as described by [`RawZStr`], and the caller gives up
+///   its ownership.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ZStr_Release(s: RawZStr) {
+    // SAFETY: upheld by the caller.
+    drop(unsafe { ZStr::from_raw(s) });
+}
@@ -0,0 +1,28 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The declarations of `zstr_rs.h` the C code relies on. A failure means
+//! that the generated header changed: update the callers, then this test.
+
+use build_utils::header_declarations;
+
+#[test]
+fn test_header_declarations() {
+    let decls = header_declarations("../../headers/zstr_rs.h").unwrap();
+    for expected in [
+        "typedef void (*ReleaseFn)(void *handle, const uint8_t *ptr, uintptr_t len)",
+        "struct RawZStr ZStr_Borrow(const char *ptr, uintptr_t len)",
+        "struct RawZStr ZStr_Copy(const char *ptr, uintptr_t len)",
+        "struct RawZStr ZStr_IntoOwned(struct RawZStr s)",
+        "struct RawZStr ZStr_Prefix(char prefix, struct RawZStr s)",
+        "void ZStr_Release(struct RawZStr s)",
+    ] {
+        assert!(decls.iter().any(|d| d == expected), "missing `{expected}`");
+    }
+}
@@ -0,0 +1,115 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{
+    ffi::c_void,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use zstr_ffi::{RawZStr, ZStr_Borrow, ZStr_Copy, ZStr_IntoOwned, ZStr_Prefix, ZStr_Release};
+
+/// The bytes of `s`.
+const fn bytes(s: &RawZStr) -> &[u8] {
+    // SAFETY: the strings of these tests are valid until released.
+    unsafe { std::slice::from_raw_parts(s.ptr, s.len) }
+}
+
+#[test]
+fn test_borrow_does_not_copy() {
+    let value = b"hello";
+    // SAFETY: `value` outlives the string.
+    let s = unsafe { ZStr_Borrow(value.as_ptr().cast(), value.len()) };
+    assert_eq!(s.ptr, value.as_ptr());
+    assert!(s.release.is_none());
+    assert_eq!(bytes(&s), b"hello");
+    // SAFETY: `s` is released only once.
+    unsafe { ZStr_Release(s) };
+}
+
+#[test]
+fn test_borrow_empty_null() {
+    // SAFETY: NULL is allowed for an empty string.
+    let s = unsafe { ZStr_Borrow(std::ptr::null(), 0) };
+    assert!(!s.ptr.is_null());
+    assert_eq!(bytes(&s), b"");
+    // SAFETY: `s` is released only once.
+    unsafe { ZStr_Release(s) };
+}
+
+#[test]
+fn test_copy_owns_its_bytes() {
+    let mut value = b"hello".to_vec();
+    // SAFETY: `value` is valid for reads of its length.
+    let s = unsafe { ZStr_Copy(value.as_ptr().cast(), value.len()) };
+    value.fill(b'x');
+    assert_ne!(s.ptr, value.as_ptr());
+    assert!(s.release.is_some());
+    assert_eq!(bytes(&s), b"hello");
+    // SAFETY: `s` is released only once.
+    unsafe { ZStr_Release(s) };
+}
+
+#[test]
+fn test_into_owned_keeps_owned_bytes() {
+    let value = b"hello";
+    // SAFETY: `value` is valid for reads of its length.
+    let copy = unsafe { ZStr_Copy(value.as_ptr().cast(), value.len()) };
+    let ptr = copy.ptr;
+    // SAFETY: ownership of `copy` is given up.
+    let owned = unsafe { ZStr_IntoOwned(copy) };
+    assert_eq!(owned.ptr, ptr);
+    // SAFETY: `owned` is released only once.
+    unsafe { ZStr_Release(owned) };
+}
+
+#[test]
+fn test_prefix() {
+    let value = b"a\0b";
+    // SAFETY: `value` outlives the string.
+    let s = unsafe { ZStr_Borrow(value.as_ptr().cast(), value.len()) };
+    // SAFETY: ownership of `s` is given up.
+    let prefixed = unsafe { ZStr_Prefix(b'$' as _, s) };
+    assert_eq!(bytes(&prefixed), b"$a\0b");
+    // SAFETY: `prefixed` is released only once.
+    unsafe { ZStr_Release(prefixed) };
+}
+
+static RELEASED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe extern "C" fn count_release(handle: *mut c_void, _ptr: *const u8, _len: usize) {
+    assert_eq!(handle as usize, 42);
+    RELEASED.fetch_add(1, Ordering::Relaxed);
+}
+
+#[test]
+fn test_foreign_string_released_once() {
+    let value: &'static [u8] = b"sds";
+    let foreign = || RawZStr {
+        ptr: value.as_ptr(),
+        len: value.len(),
+        handle: 42 as *mut c_void,
+        release: Some(count_release),
+    };
+
+    // SAFETY: `count_release` is the destructor of the string.
+    let prefixed = unsafe { ZStr_Prefix(b'$' as _, foreign()) };
+    assert_eq!(RELEASED.load(Ordering::Relaxed), 1);
+    assert_eq!(bytes(&prefixed), b"$sds");
+    // SAFETY: `prefixed` is released only once.
+    unsafe { ZStr_Release(prefixed) };
+    assert_eq!(RELEASED.load(Ordering::Relaxed), 1);
+
+    // SAFETY: `count_release` is the destructor of the string.
+    let owned = unsafe { ZStr_IntoOwned(foreign()) };
+    assert_eq!(owned.ptr, value.as_ptr());
+    assert_eq!(RELEASED.load(Ordering::Relaxed), 1);
+    // SAFETY: `owned` is released only once.
+    unsafe { ZStr_Release(owned) };
+    assert_eq!(RELEASED.load(Ordering::Relaxed), 2);
+}
@@ -0,0 +1,203 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Background compaction of long-lived indexes.
+//!
+//! GC removes deleted entries but leaves the structures shaped by their
+//! history: posting lists end up with many small sealed blocks, numeric range
+//! leaves with a high ratio of deleted entries, vector graphs with regions of
+//! tombstoned nodes. Indexes report such [`Candidate`]s, and the
+//! [`Scheduler`] picks which to compact on the worker pool: the candidates
+//! with the best benefit/cost ratio first, within a cost budget per cycle.
+//!
+//! [`plan_block_merges`] computes the merges of a posting list's blocks.
+
+mod plan;
+
+pub use plan::plan_block_merges;
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BinaryHeap},
+    time::{Duration, Instant},
+};
+
+/// The type of work a compaction task does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TaskKind {
+    /// Merge consecutive small sealed blocks of a posting list.
+    MergeBlocks,
+    /// Rebuild a numeric range leaf with a high ratio of deleted entries.
+    RebuildNumericLeaf,
+    /// Rewrite a region of a vector graph with many tombstoned nodes.
+    RewriteVectorRegion,
+}
+
+/// Something worth compacting, as reported by an index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub index: String,
+    pub kind: TaskKind,
+    /// Identifies the structure within the index (e.g. the term or the leaf).
+    pub target: String,
+    /// The estimated work, in bytes to rewrite.
+    pub cost: u64,
+    /// The estimated gain, in bytes reclaimed.
+    pub benefit: u64,
+}
+
+impl Candidate {
+    /// Compare the benefit/cost ratios without floating point:
+    /// `a.benefit / a.cost` vs `b.benefit / b.cost`.
+    fn cmp_ratio(&self, other: &Self) -> Ordering {
+        let lhs = u128::from(self.benefit) * u128::from(other.cost.max(1));
+        let rhs = u128::from(other.benefit) * u128::from(self.cost.max(1));
+        lhs.cmp(&rhs)
+    }
+}
+
+struct Queued(Candidate);
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Queued {}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Cheaper tasks first on equal ratios, so that more fit the budget.
+        self.0
+            .cmp_ratio(&other.0)
+            .then_with(|| other.0.cost.cmp(&self.0.cost))
+    }
+}
+
+/// The result of running a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TaskOutcome {
+    /// The bytes actually reclaimed.
+    pub reclaimed: u64,
+}
+
+/// Compaction statistics of an index, for `FT.INFO`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CompactionStats {
+    pub tasks_run: u64,
+    pub bytes_rewritten: u64,
+    pub bytes_reclaimed: u64,
+    pub time_spent: Duration,
+}
+
+/// The scheduling parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchedulerConfig {
+    /// The maximum cost run per cycle.
+    pub cycle_budget: u64,
+    /// Candidates reclaiming less than this are ignored.
+    pub min_benefit: u64,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            cycle_budget: 64 << 20,
+            min_benefit: 4 << 10,
+        }
+    }
+}
+
+/// See the [crate documentation](crate).
+#[derive(Default)]
+pub struct Scheduler {
+    config: SchedulerConfig,
+    queue: BinaryHeap<Queued>,
+    stats: BTreeMap<String, CompactionStats>,
+}
+
+impl Scheduler {
+    pub fn new(config: SchedulerConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Queue a candidate. Returns `false` if it's not worth compacting.
+    ///
+    /// A candidate replaces the queued one for the same structure, i.e. with
+    /// the same index, kind and target, as GC reports the same structure on
+    /// each run: the latest estimates win, even when they're no longer worth
+    /// compacting.
+    pub fn submit(&mut self, candidate: Candidate) -> bool {
+        self.queue.retain(|q| {
+            (&q.0.index, q.0.kind, &q.0.target)
+                != (&candidate.index, candidate.kind, &candidate.target)
+        });
+        if candidate.benefit < self.config.min_benefit {
+            return false;
+        }
+        self.queue.push(Queued(candidate));
+        true
+    }
+
+    /// The number of queued candidates.
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Run the most profitable candidates with `run`, until the cycle budget
+    /// is spent. A candidate that doesn't fit the remaining budget stays
+    /// queued for the next cycle, except when it's the first of the cycle, so
+    /// that large candidates aren't starved.
+    ///
+    /// Returns the candidates run, in order.
+    pub fn run_cycle(&mut self, mut run: impl FnMut(&Candidate) -> TaskOutcome) -> Vec<Candidate> {
+        let mut spent: u64 = 0;
+        let mut done = Vec::new();
+        let mut deferred = Vec::new();
+        while let Some(Queued(candidate)) = self.queue.pop() {
+            let fits = spent.saturating_add(candidate.cost) <= self.config.cycle_budget;
+            if !fits && !done.is_empty() {
+                deferred.push(Queued(candidate));
+                continue;
+            }
+            let start = Instant::now();
+            let outcome = run(&candidate);
+            let stats = self.stats.entry(candidate.index.clone()).or_default();
+            stats.tasks_run += 1;
+            stats.bytes_rewritten = stats.bytes_rewritten.saturating_add(candidate.cost);
+            stats.bytes_reclaimed = stats.bytes_reclaimed.saturating_add(outcome.reclaimed);
+            stats.time_spent += start.elapsed();
+            spent = spent.saturating_add(candidate.cost);
+            done.push(candidate);
+        }
+        self.queue.extend(deferred);
+        done
+    }
+
+    /// Forget the candidates and statistics of a dropped index.
+    pub fn drop_index(&mut self, index: &str) {
+        self.queue.retain(|q| q.0.index != index);
+        self.stats.remove(index);
+    }
+
+    /// The statistics of an index.
+    pub fn stats(&self, index: &str) -> CompactionStats {
+        self.stats.get(index).copied().unwrap_or_default()
+    }
+}
@@ -0,0 +1,40 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::ops::Range;
+
+/// Plan the merges of the sealed blocks of a posting list.
+///
+/// `entries` holds the number of entries of each block; the last block is
+/// still being written to and is never merged. Runs of consecutive blocks
+/// smaller than half of `target` are merged into blocks of at most `target`
+/// entries. Returns the ranges of blocks to merge, each into a single block,
+/// in order.
+pub fn plan_block_merges(entries: &[usize], target: usize) -> Vec<Range<usize>> {
+    let sealed = entries.len().saturating_sub(1);
+    let small = |n: usize| n < target / 2;
+    let mut merges = Vec::new();
+    let mut i = 0;
+    while i < sealed {
+        if !small(entries[i]) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let mut total = 0;
+        while i < sealed && small(entries[i]) && total + entries[i] <= target {
+            total += entries[i];
+            i += 1;
+        }
+        if i - start > 1 {
+            merges.push(start..i);
+        }
+    }
+    merges
+}
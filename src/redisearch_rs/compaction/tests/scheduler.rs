@@ -0,0 +1,136 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use compaction::{
+    Candidate, CompactionStats, Scheduler, SchedulerConfig, TaskKind, TaskOutcome,
+    plan_block_merges,
+};
+
+fn candidate(index: &str, target: &str, cost: u64, benefit: u64) -> Candidate {
+    Candidate {
+        index: index.to_owned(),
+        kind: TaskKind::MergeBlocks,
+        target: target.to_owned(),
+        cost,
+        benefit,
+    }
+}
+
+fn targets(candidates: &[Candidate]) -> Vec<&str> {
+    candidates.iter().map(|c| c.target.as_str()).collect()
+}
+
+#[test]
+fn test_plan_block_merges() {
+    assert_eq!(plan_block_merges(&[], 100), []);
+    assert_eq!(plan_block_merges(&[10], 100), []);
+    // The last block is never merged.
+    assert_eq!(plan_block_merges(&[10, 10], 100), []);
+    let merges = plan_block_merges(&[10, 20, 30, 5], 100);
+    assert_eq!(merges.len(), 1);
+    assert_eq!(merges[0], 0..3);
+    // Large blocks split runs, and merged blocks don't exceed the target.
+    assert_eq!(
+        plan_block_merges(&[40, 40, 40, 100, 10, 10, 60, 1], 100),
+        [0..2, 4..6]
+    );
+}
+
+#[test]
+fn test_best_ratio_first_within_budget() {
+    let mut scheduler = Scheduler::new(SchedulerConfig {
+        cycle_budget: 100,
+        min_benefit: 10,
+    });
+    assert!(scheduler.submit(candidate("idx", "low", 50, 10)));
+    assert!(scheduler.submit(candidate("idx", "high", 50, 100)));
+    assert!(scheduler.submit(candidate("idx", "mid", 80, 80)));
+    assert!(!scheduler.submit(candidate("idx", "tiny", 1, 5)));
+
+    let done = scheduler.run_cycle(|c| TaskOutcome {
+        reclaimed: c.benefit,
+    });
+    // `mid` doesn't fit after `high`, `low` does.
+    assert_eq!(targets(&done), ["high", "low"]);
+    assert_eq!(scheduler.pending(), 1);
+
+    let done = scheduler.run_cycle(|_| TaskOutcome::default());
+    assert_eq!(targets(&done), ["mid"]);
+
+    let stats = scheduler.stats("idx");
+    assert_eq!(stats.tasks_run, 3);
+    assert_eq!(stats.bytes_rewritten, 180);
+    assert_eq!(stats.bytes_reclaimed, 110);
+}
+
+#[test]
+fn test_oversized_candidate_runs_alone() {
+    let mut scheduler = Scheduler::new(SchedulerConfig {
+        cycle_budget: 10,
+        min_benefit: 0,
+    });
+    scheduler.submit(candidate("idx", "huge", 1000, 5000));
+    scheduler.submit(candidate("idx", "small", 5, 10));
+    let done = scheduler.run_cycle(|_| TaskOutcome::default());
+    assert_eq!(targets(&done), ["huge"]);
+    let done = scheduler.run_cycle(|_| TaskOutcome::default());
+    assert_eq!(targets(&done), ["small"]);
+}
+
+#[test]
+fn test_drop_index() {
+    let mut scheduler = Scheduler::default();
+    scheduler.submit(candidate("a", "t", 10, 1 << 20));
+    scheduler.submit(candidate("b", "t", 10, 1 << 20));
+    scheduler.run_cycle(|_| TaskOutcome::default());
+    scheduler.submit(candidate("a", "t", 10, 1 << 20));
+    scheduler.drop_index("a");
+    assert_eq!(scheduler.pending(), 0);
+    assert_eq!(scheduler.stats("a"), CompactionStats::default());
+    assert_eq!(scheduler.stats("b").tasks_run, 1);
+}
+
+#[test]
+fn test_huge_cost_doesnt_overflow_the_budget() {
+    let mut scheduler = Scheduler::new(SchedulerConfig {
+        cycle_budget: 10,
+        min_benefit: 0,
+    });
+    scheduler.submit(candidate("idx", "huge", u64::MAX, u64::MAX));
+    scheduler.submit(candidate("idx", "small", 5, 10));
+    // `huge` doesn't fit after `small`, without the sum overflowing.
+    let done = scheduler.run_cycle(|_| TaskOutcome::default());
+    assert_eq!(targets(&done), ["small"]);
+    assert_eq!(scheduler.pending(), 1);
+    let done = scheduler.run_cycle(|_| TaskOutcome::default());
+    assert_eq!(targets(&done), ["huge"]);
+    assert_eq!(scheduler.stats("idx").bytes_rewritten, u64::MAX);
+}
+
+#[test]
+fn test_resubmitting_replaces_the_candidate() {
+    let mut scheduler = Scheduler::new(SchedulerConfig {
+        cycle_budget: 100,
+        min_benefit: 10,
+    });
+    assert!(scheduler.submit(candidate("idx", "t", 50, 20)));
+    assert!(scheduler.submit(candidate("idx", "t", 40, 30)));
+    assert!(scheduler.submit(candidate("other", "t", 40, 30)));
+    assert_eq!(scheduler.pending(), 2);
+
+    let done = scheduler.run_cycle(|_| TaskOutcome::default());
+    assert_eq!(done.len(), 2);
+    let replaced = done.iter().find(|c| c.index == "idx").unwrap();
+    assert_eq!((replaced.cost, replaced.benefit), (40, 30));
+
+    // A report that's no longer worth compacting drops the queued candidate.
+    assert!(scheduler.submit(candidate("idx", "t", 50, 20)));
+    assert!(!scheduler.submit(candidate("idx", "t", 50, 5)));
+    assert_eq!(scheduler.pending(), 0);
+}
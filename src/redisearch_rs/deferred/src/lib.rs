@@ -0,0 +1,196 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Deferred construction of derived index structures after an RDB load.
+//!
+//! Structures that can be derived from the rest of the index, such as suffix
+//! tries or vector graphs, needn't be rebuilt before the server starts
+//! serving. Depending on the [`RebuildPolicy`], a [`Deferred`] structure is
+//! built during the load, on first use, or by the [`RebuildQueue`] in the
+//! background, whichever comes first.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    str::FromStr,
+    sync::{Arc, Mutex, OnceLock, Weak},
+    time::{Duration, Instant},
+};
+
+/// When to rebuild derived structures after a load (`INDEX_REBUILD_POLICY`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RebuildPolicy {
+    /// Rebuild during the load, as before.
+    Eager,
+    /// Rebuild on first use only.
+    Lazy,
+    /// Rebuild in the background, or on first use if that comes first.
+    #[default]
+    Background,
+}
+
+impl FromStr for RebuildPolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s.to_ascii_lowercase().as_str() {
+            "eager" => Ok(Self::Eager),
+            "lazy" => Ok(Self::Lazy),
+            "background" => Ok(Self::Background),
+            _ => Err(()),
+        }
+    }
+}
+
+type Builder<T> = Box<dyn FnOnce() -> T + Send>;
+
+/// A structure built at most once, on demand.
+pub struct Deferred<T> {
+    value: OnceLock<T>,
+    builder: Mutex<Option<Builder<T>>>,
+}
+
+impl<T> Deferred<T> {
+    /// A structure that's already built.
+    pub fn ready(value: T) -> Self {
+        Self {
+            value: OnceLock::from(value),
+            builder: Mutex::new(None),
+        }
+    }
+
+    /// A structure built by `builder` when first needed.
+    pub fn new(builder: impl FnOnce() -> T + Send + 'static) -> Self {
+        Self {
+            value: OnceLock::new(),
+            builder: Mutex::new(Some(Box::new(builder))),
+        }
+    }
+
+    /// The structure, building it if needed. Concurrent callers wait for a
+    /// single build.
+    pub fn get(&self) -> &T {
+        self.value.get_or_init(|| {
+            let builder = self
+                .builder
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .take()
+                .expect("a deferred structure has either a value or a builder");
+            builder()
+        })
+    }
+
+    /// The structure, if it's been built already.
+    pub fn get_if_built(&self) -> Option<&T> {
+        self.value.get()
+    }
+
+    pub fn is_built(&self) -> bool {
+        self.value.get().is_some()
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.get();
+        self.value.get_mut().expect("just built")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Deferred<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get_if_built() {
+            Some(value) => f.debug_tuple("Deferred").field(value).finish(),
+            None => f.write_str("Deferred(<not built>)"),
+        }
+    }
+}
+
+/// Something the [`RebuildQueue`] can build.
+pub trait Rebuild: Send + Sync {
+    /// Build the structure if it isn't yet.
+    fn rebuild(&self);
+}
+
+impl<T: Send + Sync> Rebuild for Deferred<T> {
+    fn rebuild(&self) {
+        self.get();
+    }
+}
+
+/// Statistics of the [`RebuildQueue`], for `FT.INFO`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RebuildStats {
+    pub pending: usize,
+    pub rebuilt: u64,
+    /// Structures dropped (e.g. with their index) before being rebuilt.
+    pub abandoned: u64,
+}
+
+/// Structures waiting to be rebuilt in the background, in load order.
+#[derive(Default)]
+pub struct RebuildQueue {
+    pending: Mutex<VecDeque<Weak<dyn Rebuild>>>,
+    stats: Mutex<RebuildStats>,
+}
+
+impl RebuildQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a structure loaded under `policy`. With
+    /// [`RebuildPolicy::Eager`] it's built immediately, with
+    /// [`RebuildPolicy::Background`] it's queued.
+    pub fn register<T: Rebuild + 'static>(&self, structure: &Arc<T>, policy: RebuildPolicy) {
+        match policy {
+            RebuildPolicy::Eager => structure.rebuild(),
+            RebuildPolicy::Lazy => {}
+            RebuildPolicy::Background => {
+                let weak: Weak<dyn Rebuild> = Arc::downgrade(structure) as Weak<dyn Rebuild>;
+                self.lock_pending().push_back(weak);
+            }
+        }
+    }
+
+    fn lock_pending(&self) -> std::sync::MutexGuard<'_, VecDeque<Weak<dyn Rebuild>>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_stats(&self) -> std::sync::MutexGuard<'_, RebuildStats> {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Rebuild queued structures until the queue is empty or `budget` is
+    /// exceeded, from a background thread. Returns the number rebuilt.
+    pub fn run(&self, budget: Duration) -> usize {
+        let start = Instant::now();
+        let mut rebuilt = 0;
+        while start.elapsed() < budget {
+            let Some(next) = self.lock_pending().pop_front() else {
+                break;
+            };
+            match next.upgrade() {
+                Some(structure) => {
+                    structure.rebuild();
+                    rebuilt += 1;
+                    self.lock_stats().rebuilt += 1;
+                }
+                None => self.lock_stats().abandoned += 1,
+            }
+        }
+        rebuilt
+    }
+
+    pub fn stats(&self) -> RebuildStats {
+        RebuildStats {
+            pending: self.lock_pending().len(),
+            ..*self.lock_stats()
+        }
+    }
+}
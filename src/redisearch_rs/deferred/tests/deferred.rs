@@ -0,0 +1,113 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+use deferred::{Deferred, RebuildPolicy, RebuildQueue, RebuildStats};
+
+/// A deferred "suffix trie" counting its builds.
+fn suffix_trie(builds: &Arc<AtomicUsize>, terms: &[&str]) -> Arc<Deferred<Vec<String>>> {
+    let builds = Arc::clone(builds);
+    let terms: Vec<String> = terms.iter().map(|t| t.to_string()).collect();
+    Arc::new(Deferred::new(move || {
+        builds.fetch_add(1, Ordering::SeqCst);
+        let mut suffixes: Vec<String> = terms
+            .iter()
+            .flat_map(|t| (0..t.len()).map(|i| t[i..].to_owned()))
+            .collect();
+        suffixes.sort();
+        suffixes
+    }))
+}
+
+#[test]
+fn test_policy_parse() {
+    assert_eq!("EAGER".parse(), Ok(RebuildPolicy::Eager));
+    assert_eq!("lazy".parse(), Ok(RebuildPolicy::Lazy));
+    assert_eq!("background".parse(), Ok(RebuildPolicy::Background));
+    assert!("never".parse::<RebuildPolicy>().is_err());
+}
+
+#[test]
+fn test_lazy_builds_on_first_use_once() {
+    let builds = Arc::new(AtomicUsize::new(0));
+    let trie = suffix_trie(&builds, &["ab"]);
+    let queue = RebuildQueue::new();
+    queue.register(&trie, RebuildPolicy::Lazy);
+    assert!(!trie.is_built());
+    assert_eq!(queue.stats().pending, 0);
+
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let trie = Arc::clone(&trie);
+            thread::spawn(move || trie.get().clone())
+        })
+        .collect();
+    for reader in readers {
+        assert_eq!(reader.join().unwrap(), ["ab", "b"]);
+    }
+    assert_eq!(builds.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_eager_and_background() {
+    let builds = Arc::new(AtomicUsize::new(0));
+    let queue = RebuildQueue::new();
+
+    let eager = suffix_trie(&builds, &["x"]);
+    queue.register(&eager, RebuildPolicy::Eager);
+    assert!(eager.is_built());
+
+    let used_first = suffix_trie(&builds, &["y"]);
+    let dropped = suffix_trie(&builds, &["z"]);
+    let background = suffix_trie(&builds, &["w"]);
+    for trie in [&used_first, &dropped, &background] {
+        queue.register(trie, RebuildPolicy::Background);
+    }
+    assert_eq!(queue.stats().pending, 3);
+
+    // A query needs it before the background rebuild gets to it.
+    assert_eq!(used_first.get(), &["y"]);
+    drop(dropped);
+
+    assert_eq!(queue.run(Duration::from_secs(60)), 2);
+    assert!(background.is_built());
+    assert_eq!(
+        queue.stats(),
+        RebuildStats {
+            pending: 0,
+            rebuilt: 2,
+            abandoned: 1
+        }
+    );
+    // `x`, `y` and `w`; the dropped one was never built.
+    assert_eq!(builds.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_budget() {
+    let builds = Arc::new(AtomicUsize::new(0));
+    let queue = RebuildQueue::new();
+    let trie = suffix_trie(&builds, &["a"]);
+    queue.register(&trie, RebuildPolicy::Background);
+    assert_eq!(queue.run(Duration::ZERO), 0);
+    assert_eq!(queue.stats().pending, 1);
+
+    let mut ready = Deferred::ready(vec![1]);
+    assert!(ready.is_built());
+    ready.get_mut().push(2);
+    assert_eq!(format!("{ready:?}"), "Deferred([1, 2])");
+}
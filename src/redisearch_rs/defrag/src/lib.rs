@@ -0,0 +1,188 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Active defragmentation of Rust-owned allocations.
+//!
+//! Redis' active defrag walks the module's data through the callback
+//! registered with `RedisModule_RegisterDefragFunc2`, asking the allocator to
+//! [relocate](Defragger::relocate) each allocation into a less fragmented
+//! region. Since the Rust global allocator is the Redis module allocator,
+//! Rust-owned buffers can be relocated the same way.
+//!
+//! Structures are defragmented incrementally: each call processes items
+//! starting at a cursor, until the [`Defragger`] asks to stop, and returns
+//! [`DefragStatus::Paused`] with the cursor to resume from on the next call.
+
+pub mod testing;
+
+use std::{
+    alloc::Layout,
+    ffi::{c_int, c_void},
+    ptr::NonNull,
+};
+
+/// The outcome of an incremental defragmentation pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefragStatus {
+    /// The whole structure has been processed; the cursor is reset.
+    Done,
+    /// The pass stopped early; call again with the same cursor to resume.
+    Paused,
+}
+
+/// Relocates allocations, typically by wrapping `RedisModule_DefragAlloc`.
+///
+/// # Safety
+///
+/// When [`Defragger::relocate`] returns a new pointer, the allocation must
+/// have been moved there with its contents, the old one freed, and the new
+/// one must be compatible with the same layout for the global allocator.
+pub unsafe trait Defragger {
+    /// Relocate the allocation at `ptr`, returning its new address, or `None`
+    /// if it was left in place.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been allocated by the global allocator with `layout`,
+    /// and must not be accessed through any other pointer afterwards if the
+    /// allocation moved.
+    unsafe fn relocate(&mut self, ptr: NonNull<u8>, layout: Layout) -> Option<NonNull<u8>>;
+
+    /// Whether the current pass should stop, e.g. because its time budget is
+    /// exhausted.
+    fn should_stop(&mut self) -> bool;
+}
+
+/// A type owning allocations that can be relocated all at once.
+pub trait Defrag {
+    /// Relocate the allocations owned by `self`. Returns whether any moved.
+    fn defrag(&mut self, defragger: &mut dyn Defragger) -> bool;
+}
+
+impl<T> Defrag for Vec<T> {
+    /// Relocate the buffer of the vector, but not what its elements own.
+    fn defrag(&mut self, defragger: &mut dyn Defragger) -> bool {
+        if self.capacity() == 0 || size_of::<T>() == 0 {
+            return false;
+        }
+        let Ok(layout) = Layout::array::<T>(self.capacity()) else {
+            return false;
+        };
+        let (len, capacity) = (self.len(), self.capacity());
+        let ptr = NonNull::from(self.as_mut_slice()).cast::<u8>();
+        // Safety: a vector with a non-zero capacity and non-zero-sized
+        // elements owns a buffer allocated by the global allocator with
+        // `layout`. If it moves, the old buffer is forgotten below.
+        let Some(new_ptr) = (unsafe { defragger.relocate(ptr, layout) }) else {
+            return false;
+        };
+        // Safety: the relocated buffer is allocated with `layout` and holds
+        // the `len` initialized elements.
+        let relocated = unsafe { Vec::from_raw_parts(new_ptr.cast().as_ptr(), len, capacity) };
+        // Safety: the old vector is overwritten without being dropped, since
+        // its buffer has been freed.
+        unsafe { std::ptr::write(self, relocated) };
+        true
+    }
+}
+
+impl<T: Defrag> Defrag for Box<T> {
+    /// Relocate the box, then what its value owns.
+    fn defrag(&mut self, defragger: &mut dyn Defragger) -> bool {
+        let mut moved = false;
+        if size_of::<T>() != 0 {
+            let ptr = NonNull::from(&mut **self).cast::<u8>();
+            // Safety: the value of a box of a non-zero-sized type is
+            // allocated by the global allocator with `Layout::new::<T>()`. If
+            // it moves, the old box is forgotten below.
+            let relocated = unsafe { defragger.relocate(ptr, Layout::new::<T>()) };
+            if let Some(new_ptr) = relocated {
+                // Safety: the relocated allocation holds the value.
+                let relocated = unsafe { Box::from_raw(new_ptr.cast().as_ptr()) };
+                // Safety: the old box is overwritten without being dropped,
+                // since its allocation has been freed.
+                unsafe { std::ptr::write(self, relocated) };
+                moved = true;
+            }
+        }
+        moved | (**self).defrag(defragger)
+    }
+}
+
+/// Defragment the items of `items` one by one, starting at `*cursor`.
+///
+/// The buffer of `items` itself is relocated when a pass starts from the
+/// beginning. Returns the number of items that moved along with the status.
+pub fn defrag_items<T: Defrag>(
+    items: &mut Vec<T>,
+    cursor: &mut usize,
+    defragger: &mut dyn Defragger,
+) -> (usize, DefragStatus) {
+    let mut moved = 0;
+    if *cursor == 0 && items.defrag(defragger) {
+        moved += 1;
+    }
+    while *cursor < items.len() {
+        if defragger.should_stop() {
+            return (moved, DefragStatus::Paused);
+        }
+        if items[*cursor].defrag(defragger) {
+            moved += 1;
+        }
+        *cursor += 1;
+    }
+    *cursor = 0;
+    (moved, DefragStatus::Done)
+}
+
+/// The signature of `RedisModule_DefragAlloc`, with the defrag context bound.
+pub type DefragAllocFn = unsafe extern "C" fn(ctx: *mut c_void, ptr: *mut c_void) -> *mut c_void;
+/// The signature of `RedisModule_DefragShouldStop`, with the defrag context
+/// bound.
+pub type DefragShouldStopFn = unsafe extern "C" fn(ctx: *mut c_void) -> c_int;
+
+/// A [`Defragger`] calling back into C, for use from FFI entry points.
+pub struct CallbackDefragger {
+    ctx: *mut c_void,
+    alloc: DefragAllocFn,
+    should_stop: DefragShouldStopFn,
+}
+
+impl CallbackDefragger {
+    /// # Safety
+    ///
+    /// `alloc` must behave like `RedisModule_DefragAlloc` (see [`Defragger`])
+    /// and both callbacks must be safe to call with `ctx`.
+    pub const unsafe fn new(
+        ctx: *mut c_void,
+        alloc: DefragAllocFn,
+        should_stop: DefragShouldStopFn,
+    ) -> Self {
+        Self {
+            ctx,
+            alloc,
+            should_stop,
+        }
+    }
+}
+
+// Safety: guaranteed by the requirements of `CallbackDefragger::new`.
+unsafe impl Defragger for CallbackDefragger {
+    unsafe fn relocate(&mut self, ptr: NonNull<u8>, _layout: Layout) -> Option<NonNull<u8>> {
+        // Safety: guaranteed by the requirements of `CallbackDefragger::new`
+        // and of this function.
+        let new_ptr = unsafe { (self.alloc)(self.ctx, ptr.as_ptr().cast()) };
+        NonNull::new(new_ptr.cast())
+    }
+
+    fn should_stop(&mut self) -> bool {
+        // Safety: guaranteed by the requirements of `CallbackDefragger::new`.
+        unsafe { (self.should_stop)(self.ctx) != 0 }
+    }
+}
@@ -0,0 +1,58 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+
+//! Helpers for tests of [`Defrag`](crate::Defrag) implementations.
+
+use std::{
+    alloc::{Layout, alloc, dealloc},
+    ptr::NonNull,
+};
+
+use crate::Defragger;
+
+/// Moves every allocation, and asks to stop every `stop_every` checks, so
+/// that tests exercise both relocation and resumption.
+#[derive(Debug)]
+pub struct MovingDefragger {
+    /// The number of allocations moved so far.
+    pub moves: usize,
+    stop_every: usize,
+    checks: usize,
+}
+
+impl MovingDefragger {
+    pub const fn new(stop_every: usize) -> Self {
+        Self {
+            moves: 0,
+            stop_every,
+            checks: 0,
+        }
+    }
+}
+
+// Safety: allocations are copied to a new allocation with the same layout,
+// and the old one is freed.
+unsafe impl Defragger for MovingDefragger {
+    unsafe fn relocate(&mut self, ptr: NonNull<u8>, layout: Layout) -> Option<NonNull<u8>> {
+        // Safety: layouts of relocated allocations are never zero-sized.
+        let new_ptr = NonNull::new(unsafe { alloc(layout) }).unwrap();
+        // Safety: both allocations are `layout.size()` long and distinct.
+        unsafe { std::ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), layout.size()) };
+        // Safety: `ptr` was allocated with `layout` and isn't used anymore.
+        unsafe { dealloc(ptr.as_ptr(), layout) };
+        self.moves += 1;
+        Some(new_ptr)
+    }
+
+    fn should_stop(&mut self) -> bool {
+        self.checks += 1;
+        self.checks.is_multiple_of(self.stop_every)
+    }
+}
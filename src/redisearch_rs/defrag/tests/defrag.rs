@@ -0,0 +1,107 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::ffi::{c_int, c_void};
+
+use defrag::{
+    CallbackDefragger, Defrag, DefragStatus, Defragger, defrag_items, testing::MovingDefragger,
+};
+
+/// A posting block: some metadata and an owned buffer.
+#[derive(Debug, Clone, PartialEq)]
+struct Block {
+    first_id: u64,
+    buffer: Vec<u8>,
+}
+
+impl Defrag for Block {
+    fn defrag(&mut self, defragger: &mut dyn Defragger) -> bool {
+        self.buffer.defrag(defragger)
+    }
+}
+
+#[test]
+fn test_vec_and_box() {
+    let mut defragger = MovingDefragger::new(usize::MAX);
+    let mut v: Vec<u32> = (0..100).collect();
+    let before = v.as_ptr();
+    assert!(v.defrag(&mut defragger));
+    assert_ne!(v.as_ptr(), before);
+    assert!(v.iter().copied().eq(0..100));
+
+    let mut empty: Vec<u32> = Vec::new();
+    assert!(!empty.defrag(&mut defragger));
+    let mut zst = vec![(); 10];
+    assert!(!zst.defrag(&mut defragger));
+
+    let mut boxed = Box::new(Block {
+        first_id: 7,
+        buffer: b"postings".to_vec(),
+    });
+    assert!(boxed.defrag(&mut defragger));
+    assert_eq!(boxed.first_id, 7);
+    assert_eq!(boxed.buffer, b"postings");
+    // The vector, the box, and the box's buffer.
+    assert_eq!(defragger.moves, 3);
+}
+
+#[test]
+fn test_incremental() {
+    let mut blocks: Vec<Block> = (0..10)
+        .map(|i| Block {
+            first_id: i * 100,
+            buffer: vec![i as u8; 16],
+        })
+        .collect();
+    let expected = blocks.clone();
+
+    let mut defragger = MovingDefragger::new(4);
+    let mut cursor = 0;
+    let mut passes = 0;
+    let mut moved = 0;
+    loop {
+        passes += 1;
+        let (n, status) = defrag_items(&mut blocks, &mut cursor, &mut defragger);
+        moved += n;
+        if status == DefragStatus::Done {
+            break;
+        }
+        assert_ne!(cursor, 0);
+    }
+    assert!(passes > 1);
+    assert_eq!(cursor, 0);
+    // The outer buffer, and each block's buffer.
+    assert_eq!(moved, 11);
+    assert_eq!(blocks, expected);
+}
+
+unsafe extern "C" fn c_alloc(ctx: *mut c_void, _ptr: *mut c_void) -> *mut c_void {
+    // Safety: `ctx` points to the call counter.
+    unsafe { *ctx.cast::<usize>() += 1 };
+    // Never moves anything.
+    std::ptr::null_mut()
+}
+
+const unsafe extern "C" fn c_should_stop(_ctx: *mut c_void) -> c_int {
+    0
+}
+
+#[test]
+fn test_callback_defragger() {
+    let mut calls = 0usize;
+    // Safety: the callbacks only access `ctx` as a counter.
+    let mut defragger =
+        unsafe { CallbackDefragger::new((&raw mut calls).cast(), c_alloc, c_should_stop) };
+    let mut blocks = vec![vec![1u8, 2, 3], vec![4u8]];
+    let mut cursor = 0;
+    let (moved, status) = defrag_items(&mut blocks, &mut cursor, &mut defragger);
+    assert_eq!((moved, status), (0, DefragStatus::Done));
+    assert_eq!(blocks, [vec![1, 2, 3], vec![4]]);
+    assert_eq!(calls, 3);
+}
@@ -0,0 +1,60 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Generate and check differential cases until a deadline.
+//!
+//! ```text
+//! soak [--minutes N] [--max-docs N]
+//! ```
+//!
+//! Exits with status 1, printing the failing case, on the first divergence.
+
+use std::{
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+
+/// The cases generated between two checks of the deadline.
+const BATCH: u32 = 256;
+
+fn main() -> ExitCode {
+    let mut minutes = 10;
+    let mut max_docs = 200;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().and_then(|v| v.parse().ok());
+        match (arg.as_str(), value) {
+            ("--minutes", Some(v)) => minutes = v,
+            ("--max-docs", Some(v)) => max_docs = v as usize,
+            _ => {
+                eprintln!("usage: soak [--minutes N] [--max-docs N]");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(minutes * 60);
+    let strategy = differential::strategy::case(max_docs);
+    let mut cases = 0u64;
+    while Instant::now() < deadline {
+        let mut runner = TestRunner::new(Config::with_cases(BATCH));
+        let result = runner.run(&strategy, |case| {
+            differential::check_case(&case).map_err(|e| TestCaseError::fail(e.to_string()))
+        });
+        if let Err(e) = result {
+            eprintln!("divergence after {cases} cases: {e}");
+            return ExitCode::FAILURE;
+        }
+        cases += u64::from(BATCH);
+    }
+    println!("{cases} cases, no divergence");
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,146 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The engines answering the queries of a case.
+
+use intersection::Intersection;
+use loser_tree::Union;
+use tag_index::TagIndex;
+
+use crate::model::{Case, DocId, Document, FieldKind, FieldValue, Query};
+
+/// An index answering queries.
+pub trait Engine {
+    /// The name of the engine, in reports.
+    fn name(&self) -> &'static str;
+
+    /// The documents matching `query`, by increasing id.
+    fn search(&self, query: &Query) -> Vec<DocId>;
+}
+
+/// Answers queries by scanning the documents.
+pub struct Oracle<'a> {
+    docs: &'a [Document],
+}
+
+impl<'a> Oracle<'a> {
+    pub const fn new(case: &'a Case) -> Self {
+        Self {
+            docs: case.docs.as_slice(),
+        }
+    }
+}
+
+impl Engine for Oracle<'_> {
+    fn name(&self) -> &'static str {
+        "oracle"
+    }
+
+    fn search(&self, query: &Query) -> Vec<DocId> {
+        self.docs
+            .iter()
+            .filter(|doc| Case::matches(doc, query))
+            .map(|doc| doc.id)
+            .collect()
+    }
+}
+
+enum FieldIndex {
+    /// The entries of a NUMERIC field, sorted by value then id.
+    Numeric(Vec<(f64, DocId)>),
+    Tag(TagIndex),
+}
+
+/// Answers queries with the Rust index structures.
+pub struct RustIndex {
+    /// Every indexed document, for negations.
+    all: Vec<DocId>,
+    fields: Vec<FieldIndex>,
+}
+
+impl RustIndex {
+    pub fn new(case: &Case) -> Self {
+        let mut fields: Vec<FieldIndex> = case
+            .schema
+            .iter()
+            .map(|kind| match kind {
+                FieldKind::Numeric => FieldIndex::Numeric(Vec::new()),
+                FieldKind::Tag => FieldIndex::Tag(TagIndex::default()),
+            })
+            .collect();
+        for doc in &case.docs {
+            for (index, value) in fields.iter_mut().zip(&doc.values) {
+                match (index, value) {
+                    (FieldIndex::Numeric(entries), Some(FieldValue::Numeric(n))) => {
+                        entries.push((*n, doc.id))
+                    }
+                    (FieldIndex::Tag(tags), Some(FieldValue::Tags(values))) => {
+                        tags.index(values, doc.id);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for field in &mut fields {
+            if let FieldIndex::Numeric(entries) = field {
+                entries.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+            }
+        }
+        Self {
+            all: case.docs.iter().map(|doc| doc.id).collect(),
+            fields,
+        }
+    }
+}
+
+impl Engine for RustIndex {
+    fn name(&self) -> &'static str {
+        "rust"
+    }
+
+    fn search(&self, query: &Query) -> Vec<DocId> {
+        match query {
+            Query::Numeric { field, min, max } => {
+                let FieldIndex::Numeric(entries) = &self.fields[*field] else {
+                    return Vec::new();
+                };
+                let by_value = |entry: &(f64, DocId), target: &f64| entry.0.total_cmp(target);
+                let Some(range) = bsearch::bsearch_range(entries, min, max, by_value) else {
+                    return Vec::new();
+                };
+                let mut ids: Vec<DocId> = entries[range].iter().map(|e| e.1).collect();
+                ids.sort_unstable();
+                ids
+            }
+            Query::Tag { field, value } => match &self.fields[*field] {
+                FieldIndex::Tag(tags) => tags.docs(value.as_bytes()).to_vec(),
+                FieldIndex::Numeric(_) => Vec::new(),
+            },
+            Query::And(children) => {
+                let results: Vec<Vec<DocId>> = children.iter().map(|q| self.search(q)).collect();
+                Intersection::new(results.iter().map(Vec::as_slice).collect()).collect()
+            }
+            Query::Or(children) => {
+                let results = children
+                    .iter()
+                    .map(|q| self.search(q).into_iter())
+                    .collect();
+                Union::new(results).collect()
+            }
+            Query::Not(child) => {
+                let excluded = self.search(child);
+                self.all
+                    .iter()
+                    .copied()
+                    .filter(|id| excluded.binary_search(id).is_err())
+                    .collect()
+            }
+        }
+    }
+}
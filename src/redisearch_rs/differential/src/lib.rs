@@ -0,0 +1,93 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Differential testing of index structures.
+//!
+//! Random [`Case`]s (a schema, documents and queries over them) are generated
+//! with proptest, indexed by every [`Engine`] and queried: all the engines
+//! must return the same documents as the first one, the [`Oracle`], which
+//! scans the documents instead of indexing them.
+//!
+//! [`RustIndex`] answers the queries with the Rust structures: tag indexes,
+//! sorted numeric entries, galloping intersections and loser-tree unions.
+//! The C structures join as another engine, built through the FFI in the same
+//! way as the benchers; that engine isn't part of this crate yet.
+//!
+//! Besides the tests, the `soak` binary generates cases for as long as asked,
+//! to run for hours in CI.
+
+pub mod engine;
+pub mod model;
+pub mod strategy;
+
+use std::fmt::{self, Display};
+
+pub use engine::{Engine, Oracle, RustIndex};
+pub use model::{Case, DocId, Query};
+
+/// Engines returned different documents for a query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub query: Query,
+    pub expected_engine: &'static str,
+    pub expected: Vec<DocId>,
+    pub engine: &'static str,
+    pub actual: Vec<DocId>,
+}
+
+impl Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} returned {:?} for {:?}, but {} returned {:?}",
+            self.engine, self.actual, self.query, self.expected_engine, self.expected
+        )
+    }
+}
+
+impl std::error::Error for Divergence {}
+
+/// Run the queries of `case` on every engine, comparing their results with
+/// those of the first one.
+///
+/// # Errors
+///
+/// Returns the first [`Divergence`].
+pub fn check(case: &Case, engines: &[&dyn Engine]) -> Result<(), Divergence> {
+    let Some((reference, others)) = engines.split_first() else {
+        return Ok(());
+    };
+    for query in &case.queries {
+        let expected = reference.search(query);
+        for engine in others {
+            let actual = engine.search(query);
+            if actual != expected {
+                return Err(Divergence {
+                    query: query.clone(),
+                    expected_engine: reference.name(),
+                    expected,
+                    engine: engine.name(),
+                    actual,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Index `case` with the [`Oracle`] and [`RustIndex`], and [`check`] them.
+///
+/// # Errors
+///
+/// Returns the first [`Divergence`].
+pub fn check_case(case: &Case) -> Result<(), Divergence> {
+    let oracle = Oracle::new(case);
+    let rust = RustIndex::new(case);
+    check(case, &[&oracle, &rust])
+}
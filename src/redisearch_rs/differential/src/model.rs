@@ -0,0 +1,81 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The schemas, documents and queries of the generated cases.
+
+/// A document ID. Documents are numbered from 1.
+pub type DocId = u64;
+
+/// The type of a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Numeric,
+    Tag,
+}
+
+/// The value of a field in a document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    Numeric(f64),
+    Tags(Vec<String>),
+}
+
+/// A document: the value of every field of the schema, if any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    pub id: DocId,
+    pub values: Vec<Option<FieldValue>>,
+}
+
+/// A query. Fields are referred to by their position in the schema.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    /// `@field:[min max]`, bounds included.
+    Numeric {
+        field: usize,
+        min: f64,
+        max: f64,
+    },
+    /// `@field:{value}`.
+    Tag {
+        field: usize,
+        value: String,
+    },
+    And(Vec<Query>),
+    Or(Vec<Query>),
+    Not(Box<Query>),
+}
+
+/// A generated test case.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Case {
+    pub schema: Vec<FieldKind>,
+    /// The documents, by increasing id.
+    pub docs: Vec<Document>,
+    pub queries: Vec<Query>,
+}
+
+impl Case {
+    /// Whether `doc` matches `query`, by definition.
+    pub fn matches(doc: &Document, query: &Query) -> bool {
+        match query {
+            Query::Numeric { field, min, max } => matches!(
+                doc.values[*field],
+                Some(FieldValue::Numeric(n)) if *min <= n && n <= *max
+            ),
+            Query::Tag { field, value } => matches!(
+                &doc.values[*field],
+                Some(FieldValue::Tags(tags)) if tags.contains(value)
+            ),
+            Query::And(children) => children.iter().all(|q| Self::matches(doc, q)),
+            Query::Or(children) => children.iter().any(|q| Self::matches(doc, q)),
+            Query::Not(child) => !Self::matches(doc, child),
+        }
+    }
+}
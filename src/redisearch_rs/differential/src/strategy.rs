@@ -0,0 +1,109 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The proptest strategies generating cases.
+//!
+//! Values are drawn from small domains, so that documents share values and
+//! queries match some of them: numbers are multiples of `0.5` in `-10..10`,
+//! and tags come from a short list, including one with a space and one in
+//! upper case.
+
+use proptest::prelude::*;
+
+use crate::model::{Case, Document, FieldKind, FieldValue, Query};
+
+const TAGS: [&str; 6] = ["red", "green", "blue", "light blue", "RED", "x"];
+
+fn number() -> impl Strategy<Value = f64> {
+    (-20i32..20).prop_map(|n| f64::from(n) / 2.0)
+}
+
+fn tag() -> impl Strategy<Value = String> {
+    prop::sample::select(TAGS.to_vec()).prop_map(str::to_owned)
+}
+
+/// A schema of one to five fields.
+pub fn schema() -> impl Strategy<Value = Vec<FieldKind>> {
+    prop::collection::vec(
+        prop::sample::select(vec![FieldKind::Numeric, FieldKind::Tag]),
+        1..6,
+    )
+}
+
+/// The values of a document. Any field may be missing.
+pub fn values(schema: &[FieldKind]) -> BoxedStrategy<Vec<Option<FieldValue>>> {
+    let mut fields: BoxedStrategy<Vec<Option<FieldValue>>> = Just(Vec::new()).boxed();
+    for kind in schema {
+        let value = match kind {
+            FieldKind::Numeric => number().prop_map(FieldValue::Numeric).boxed(),
+            FieldKind::Tag => prop::collection::vec(tag(), 1..4)
+                .prop_map(FieldValue::Tags)
+                .boxed(),
+        };
+        let value = prop_oneof![Just(None), value.prop_map(Some)];
+        fields = (fields, value)
+            .prop_map(|(mut fields, value)| {
+                fields.push(value);
+                fields
+            })
+            .boxed();
+    }
+    fields
+}
+
+/// A query over `schema`, nested up to `depth` levels.
+pub fn query(schema: &[FieldKind], depth: u32) -> BoxedStrategy<Query> {
+    let leaves: Vec<BoxedStrategy<Query>> = schema
+        .iter()
+        .enumerate()
+        .map(|(field, kind)| match kind {
+            FieldKind::Numeric => (number(), number())
+                .prop_map(move |(a, b)| Query::Numeric {
+                    field,
+                    min: a.min(b),
+                    max: a.max(b),
+                })
+                .boxed(),
+            FieldKind::Tag => tag()
+                .prop_map(move |value| Query::Tag { field, value })
+                .boxed(),
+        })
+        .collect();
+    let leaf = (0..leaves.len())
+        .prop_flat_map(move |i| leaves[i].clone())
+        .boxed();
+    if depth == 0 {
+        return leaf;
+    }
+    let child = query(schema, depth - 1);
+    prop_oneof![
+        leaf,
+        prop::collection::vec(child.clone(), 1..4).prop_map(Query::And),
+        prop::collection::vec(child.clone(), 1..4).prop_map(Query::Or),
+        child.prop_map(|q| Query::Not(Box::new(q))),
+    ]
+    .boxed()
+}
+
+/// A case of up to `max_docs` documents and 16 queries.
+pub fn case(max_docs: usize) -> impl Strategy<Value = Case> {
+    schema().prop_flat_map(move |schema| {
+        let docs = prop::collection::vec(values(&schema), 0..max_docs + 1);
+        let queries = prop::collection::vec(query(&schema, 3), 1..17);
+        (Just(schema), docs, queries).prop_map(|(schema, docs, queries)| Case {
+            schema,
+            docs: docs
+                .into_iter()
+                .zip(1..)
+                .map(|(values, id)| Document { id, values })
+                .collect(),
+            queries,
+        })
+    })
+}
@@ -0,0 +1,101 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use differential::{
+    Divergence, Engine, Oracle, RustIndex, check, check_case,
+    model::{Case, DocId, Document, FieldKind, FieldValue, Query},
+};
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+
+fn case(queries: Vec<Query>) -> Case {
+    let doc = |id, n: f64, tags: &[&str]| Document {
+        id,
+        values: vec![
+            Some(FieldValue::Numeric(n)),
+            (!tags.is_empty())
+                .then(|| FieldValue::Tags(tags.iter().map(|t| (*t).to_owned()).collect())),
+        ],
+    };
+    Case {
+        schema: vec![FieldKind::Numeric, FieldKind::Tag],
+        docs: vec![
+            doc(1, 1.0, &["red"]),
+            doc(2, 2.5, &["red", "blue"]),
+            doc(3, 2.5, &[]),
+            doc(4, -3.0, &["blue"]),
+        ],
+        queries,
+    }
+}
+
+fn tag(value: &str) -> Query {
+    Query::Tag {
+        field: 1,
+        value: value.to_owned(),
+    }
+}
+
+const fn range(min: f64, max: f64) -> Query {
+    Query::Numeric { field: 0, min, max }
+}
+
+#[test]
+fn test_engines_agree() {
+    let case = case(vec![]);
+    let (oracle, rust) = (Oracle::new(&case), RustIndex::new(&case));
+    for (query, expected) in [
+        (range(1.0, 2.5), vec![1, 2, 3]),
+        (range(3.0, 10.0), vec![]),
+        (tag("red"), vec![1, 2]),
+        (Query::And(vec![tag("red"), range(2.0, 3.0)]), vec![2]),
+        (Query::Or(vec![tag("blue"), range(1.0, 1.0)]), vec![1, 2, 4]),
+        (Query::Not(Box::new(tag("red"))), vec![3, 4]),
+    ] {
+        assert_eq!(oracle.search(&query), expected, "{query:?}");
+        assert_eq!(rust.search(&query), expected, "{query:?}");
+    }
+}
+
+#[test]
+fn test_divergence_is_reported() {
+    struct Broken;
+
+    impl Engine for Broken {
+        fn name(&self) -> &'static str {
+            "broken"
+        }
+
+        fn search(&self, _query: &Query) -> Vec<DocId> {
+            vec![1, 2]
+        }
+    }
+
+    let case = case(vec![tag("red"), tag("blue")]);
+    let err = check(&case, &[&Oracle::new(&case), &Broken]).unwrap_err();
+    assert_eq!(
+        err,
+        Divergence {
+            query: tag("blue"),
+            expected_engine: "oracle",
+            expected: vec![2, 4],
+            engine: "broken",
+            actual: vec![1, 2],
+        }
+    );
+}
+
+#[test]
+fn test_random_cases() {
+    let mut runner = TestRunner::new(Config::with_cases(200));
+    runner
+        .run(&differential::strategy::case(50), |case| {
+            check_case(&case).map_err(|e| TestCaseError::fail(e.to_string()))
+        })
+        .unwrap();
+}
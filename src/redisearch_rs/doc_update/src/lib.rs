@@ -0,0 +1,157 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Partial re-indexing of updated documents.
+//!
+//! An `HSET` touching a single field used to re-index the whole document:
+//! every posting was deleted and inserted again, and vectors were re-ingested.
+//! Instead, the new values of the indexed fields are compared with what was
+//! stored when the document was last indexed, and only the fields which
+//! changed are re-indexed.
+//!
+//! Sortable fields and vectors are compared with their stored value. Other
+//! fields are compared by the [fingerprint] of their value, recorded when
+//! the document is indexed.
+//!
+//! The postings of TEXT fields are shared: a term's entry for a document holds
+//! its frequency across all the TEXT fields, and a mask of the fields it
+//! appears in. As a result, when any TEXT field changes, all of them are
+//! re-indexed.
+
+use std::hash::Hasher;
+
+use fnv::Fnv64;
+
+/// The type of an indexed field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Text,
+    Tag,
+    Numeric,
+    Geo,
+    Vector,
+}
+
+/// The fingerprint of a field value, stored when the document is indexed.
+pub fn fingerprint(value: &[u8]) -> u64 {
+    let mut hasher = Fnv64::default();
+    hasher.write(value);
+    hasher.finish()
+}
+
+/// What was stored about a field when the document was last indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stored<'a> {
+    /// The value itself: a sortable value or the bytes of a vector.
+    Value(&'a [u8]),
+    /// The [fingerprint] of the value.
+    Fingerprint(u64),
+    /// The field had no value.
+    Missing,
+}
+
+impl Stored<'_> {
+    fn matches(self, new: Option<&[u8]>) -> bool {
+        match (self, new) {
+            (Self::Value(stored), Some(new)) => stored == new,
+            (Self::Fingerprint(stored), Some(new)) => stored == fingerprint(new),
+            (Self::Missing, None) => true,
+            _ => false,
+        }
+    }
+}
+
+/// How a field's postings are updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldAction {
+    /// The field had no value: only insert the new postings.
+    Insert,
+    /// Delete the old postings, and insert the new ones.
+    Replace,
+    /// The field lost its value: only delete the old postings.
+    Delete,
+}
+
+/// A field to re-index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldUpdate {
+    pub field: String,
+    pub kind: FieldKind,
+    pub action: FieldAction,
+}
+
+/// The fields of a document to re-index after an update.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdatePlan {
+    /// Nothing was stored about the document, e.g. because it was indexed by
+    /// an older version: re-index all of it.
+    Full,
+    /// Re-index the given fields only.
+    Partial(Vec<FieldUpdate>),
+}
+
+impl UpdatePlan {
+    /// Whether none of the indexed fields changed, e.g. when the update only
+    /// touched fields which aren't part of the schema.
+    pub const fn is_noop(&self) -> bool {
+        matches!(self, Self::Partial(fields) if fields.is_empty())
+    }
+}
+
+/// An indexed field, along with its old and new values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldState<'a> {
+    pub field: &'a str,
+    pub kind: FieldKind,
+    /// What was stored when the document was last indexed, or `None` if
+    /// nothing was.
+    pub stored: Option<Stored<'a>>,
+    /// The value after the update, or `None` if the field was removed.
+    pub new: Option<&'a [u8]>,
+}
+
+/// Compare the new values of the indexed fields of a document with the
+/// stored ones, and plan the re-indexing of those which changed.
+pub fn plan_update<'a>(fields: impl IntoIterator<Item = FieldState<'a>>) -> UpdatePlan {
+    let fields: Vec<FieldState> = fields.into_iter().collect();
+    let mut changed = Vec::with_capacity(fields.len());
+    for state in &fields {
+        let Some(stored) = state.stored else {
+            return UpdatePlan::Full;
+        };
+        changed.push(!stored.matches(state.new));
+    }
+    let text_changed = fields
+        .iter()
+        .zip(&changed)
+        .any(|(state, &changed)| changed && state.kind == FieldKind::Text);
+
+    let updates = fields
+        .iter()
+        .zip(changed)
+        .filter(|(state, changed)| *changed || (text_changed && state.kind == FieldKind::Text))
+        .filter_map(|(state, _)| {
+            let had_value = state.stored != Some(Stored::Missing);
+            let action = match (had_value, state.new.is_some()) {
+                (true, true) => FieldAction::Replace,
+                (false, true) => FieldAction::Insert,
+                (true, false) => FieldAction::Delete,
+                // A TEXT field without a value, re-indexed along with the
+                // others, still has nothing to index.
+                (false, false) => return None,
+            };
+            Some(FieldUpdate {
+                field: state.field.to_owned(),
+                kind: state.kind,
+                action,
+            })
+        })
+        .collect();
+    UpdatePlan::Partial(updates)
+}
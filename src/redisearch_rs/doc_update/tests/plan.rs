@@ -0,0 +1,136 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use doc_update::{
+    FieldAction, FieldKind, FieldState, FieldUpdate, Stored, UpdatePlan, fingerprint, plan_update,
+};
+
+fn state<'a>(
+    field: &'a str,
+    kind: FieldKind,
+    stored: Option<Stored<'a>>,
+    new: Option<&'a str>,
+) -> FieldState<'a> {
+    FieldState {
+        field,
+        kind,
+        stored,
+        new: new.map(str::as_bytes),
+    }
+}
+
+fn update(field: &str, kind: FieldKind, action: FieldAction) -> FieldUpdate {
+    FieldUpdate {
+        field: field.to_owned(),
+        kind,
+        action,
+    }
+}
+
+#[test]
+fn test_unrelated_update_is_noop() {
+    let plan = plan_update([
+        state(
+            "price",
+            FieldKind::Numeric,
+            Some(Stored::Value(b"10")),
+            Some("10"),
+        ),
+        state(
+            "title",
+            FieldKind::Text,
+            Some(Stored::Fingerprint(fingerprint(b"hello"))),
+            Some("hello"),
+        ),
+        state("color", FieldKind::Tag, Some(Stored::Missing), None),
+    ]);
+    assert!(plan.is_noop());
+}
+
+#[test]
+fn test_only_changed_fields_are_reindexed() {
+    let plan = plan_update([
+        state(
+            "price",
+            FieldKind::Numeric,
+            Some(Stored::Value(b"10")),
+            Some("12"),
+        ),
+        state("color", FieldKind::Tag, Some(Stored::Missing), Some("red")),
+        state(
+            "location",
+            FieldKind::Geo,
+            Some(Stored::Fingerprint(fingerprint(b"1,2"))),
+            None,
+        ),
+        // Re-ingesting an identical vector is skipped.
+        FieldState {
+            field: "embedding",
+            kind: FieldKind::Vector,
+            stored: Some(Stored::Value(&[0, 0, 128, 63])),
+            new: Some(&[0, 0, 128, 63]),
+        },
+    ]);
+    assert_eq!(
+        plan,
+        UpdatePlan::Partial(vec![
+            update("price", FieldKind::Numeric, FieldAction::Replace),
+            update("color", FieldKind::Tag, FieldAction::Insert),
+            update("location", FieldKind::Geo, FieldAction::Delete),
+        ])
+    );
+}
+
+#[test]
+fn test_text_fields_are_reindexed_together() {
+    let hello = fingerprint(b"hello");
+    let plan = plan_update([
+        state(
+            "title",
+            FieldKind::Text,
+            Some(Stored::Fingerprint(hello)),
+            Some("hello"),
+        ),
+        state(
+            "body",
+            FieldKind::Text,
+            Some(Stored::Fingerprint(hello)),
+            Some("world"),
+        ),
+        state("summary", FieldKind::Text, Some(Stored::Missing), None),
+        state(
+            "tags",
+            FieldKind::Tag,
+            Some(Stored::Fingerprint(hello)),
+            Some("hello"),
+        ),
+    ]);
+    assert_eq!(
+        plan,
+        UpdatePlan::Partial(vec![
+            update("title", FieldKind::Text, FieldAction::Replace),
+            update("body", FieldKind::Text, FieldAction::Replace),
+        ])
+    );
+}
+
+#[test]
+fn test_unknown_state_reindexes_everything() {
+    let plan = plan_update([
+        state(
+            "price",
+            FieldKind::Numeric,
+            Some(Stored::Value(b"1")),
+            Some("1"),
+        ),
+        state("title", FieldKind::Text, None, Some("hello")),
+    ]);
+    assert_eq!(plan, UpdatePlan::Full);
+    assert!(!plan.is_noop());
+}
@@ -0,0 +1,444 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Expressions evaluated when a document is indexed, to derive the value of
+//! a computed field from the document's fields, e.g. `lower(@title)` or
+//! `@price_cents / 100`.
+//!
+//! The language is the subset of `APPLY` expressions which only depends on
+//! the document itself:
+//!
+//! - number and string literals, and field references (`@field`);
+//! - `+`, `-`, `*`, `/` and unary `-` on numbers. Field values, which Redis
+//!   hashes hold as strings, are parsed as numbers when used as operands;
+//! - the functions `lower`, `upper`, `concat`, `strlen`, `floor` and `ceil`.
+//!
+//! A missing field is NULL, and follows the [NULL semantics](crate::null):
+//! the computed field of such a document has no value.
+
+use std::fmt::{self, Display};
+
+use crate::null::{propagate, propagate2};
+
+/// A value produced by an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    String(String),
+}
+
+impl Value {
+    /// The value as a number, parsing strings.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EvalError::NotANumber`] if the value isn't a finite number.
+    pub fn to_number(&self) -> Result<f64, EvalError> {
+        let number = match self {
+            Self::Number(number) => Some(*number),
+            Self::String(s) => s.trim().parse().ok(),
+        };
+        number
+            .filter(|n| !n.is_nan())
+            .ok_or_else(|| EvalError::NotANumber(self.to_string()))
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "{number}"),
+            Self::String(s) => f.write_str(s),
+        }
+    }
+}
+
+/// Errors returned when parsing an expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The expression ends early.
+    UnexpectedEnd,
+    /// Unexpected character at a byte offset.
+    Unexpected {
+        offset: usize,
+        found: char,
+    },
+    UnknownFunction(String),
+    /// A function is called with the wrong number of arguments.
+    Arity {
+        function: &'static str,
+        expected: &'static str,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => f.write_str("Unexpected end of expression"),
+            Self::Unexpected { offset, found } => {
+                write!(f, "Unexpected `{found}` at offset {offset}")
+            }
+            Self::UnknownFunction(name) => write!(f, "Unknown function `{name}`"),
+            Self::Arity { function, expected } => {
+                write!(f, "`{function}` expects {expected} argument(s)")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Errors returned when evaluating an expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    /// An operand of an arithmetic operation isn't a number.
+    NotANumber(String),
+}
+
+impl Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotANumber(value) => write!(f, "`{value}` is not a number"),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Lower,
+    Upper,
+    Concat,
+    Strlen,
+    Floor,
+    Ceil,
+}
+
+impl Function {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "lower" => Self::Lower,
+            "upper" => Self::Upper,
+            "concat" => Self::Concat,
+            "strlen" => Self::Strlen,
+            "floor" => Self::Floor,
+            "ceil" => Self::Ceil,
+            _ => return None,
+        })
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Lower => "lower",
+            Self::Upper => "upper",
+            Self::Concat => "concat",
+            Self::Strlen => "strlen",
+            Self::Floor => "floor",
+            Self::Ceil => "ceil",
+        }
+    }
+
+    const fn check_arity(self, args: usize) -> Result<(), ParseError> {
+        let (ok, expected) = match self {
+            Self::Concat => (args >= 1, "at least 1"),
+            _ => (args == 1, "1"),
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(ParseError::Arity {
+                function: self.name(),
+                expected,
+            })
+        }
+    }
+}
+
+/// A parsed expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    Field(String),
+    Neg(Box<Expr>),
+    Binary {
+        op: BinaryOp,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    Call {
+        function: Function,
+        args: Vec<Expr>,
+    },
+}
+
+impl Expr {
+    /// Parse an expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseError`] if `source` isn't a valid expression.
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser { source, offset: 0 };
+        let expr = parser.sum()?;
+        parser.skip_whitespace();
+        match parser.peek() {
+            None => Ok(expr),
+            Some(found) => Err(parser.unexpected(found)),
+        }
+    }
+
+    /// The fields the expression reads.
+    pub fn fields(&self) -> Vec<&str> {
+        let mut fields = Vec::new();
+        self.collect_fields(&mut fields);
+        fields
+    }
+
+    fn collect_fields<'a>(&'a self, fields: &mut Vec<&'a str>) {
+        match self {
+            Self::Literal(_) => {}
+            Self::Field(name) => {
+                if !fields.contains(&name.as_str()) {
+                    fields.push(name);
+                }
+            }
+            Self::Neg(operand) => operand.collect_fields(fields),
+            Self::Binary { lhs, rhs, .. } => {
+                lhs.collect_fields(fields);
+                rhs.collect_fields(fields);
+            }
+            Self::Call { args, .. } => args.iter().for_each(|arg| arg.collect_fields(fields)),
+        }
+    }
+
+    /// Evaluate the expression, `field(name)` returning the value of a field
+    /// of the document. Returns `None` if the result is NULL.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EvalError`] if an operand has the wrong type.
+    pub fn eval<'a>(
+        &self,
+        field: &impl Fn(&str) -> Option<&'a str>,
+    ) -> Result<Option<Value>, EvalError> {
+        Ok(match self {
+            Self::Literal(value) => Some(value.clone()),
+            Self::Field(name) => field(name).map(|value| Value::String(value.to_owned())),
+            Self::Neg(operand) => {
+                let operand = operand.eval(field)?.map(|v| v.to_number()).transpose()?;
+                propagate(operand, |n| Value::Number(-n))
+            }
+            Self::Binary { op, lhs, rhs } => {
+                let lhs = lhs.eval(field)?.map(|v| v.to_number()).transpose()?;
+                let rhs = rhs.eval(field)?.map(|v| v.to_number()).transpose()?;
+                propagate2(lhs, rhs, |a, b| {
+                    Value::Number(match op {
+                        BinaryOp::Add => a + b,
+                        BinaryOp::Sub => a - b,
+                        BinaryOp::Mul => a * b,
+                        BinaryOp::Div => a / b,
+                    })
+                })
+            }
+            Self::Call { function, args } => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(field))
+                    .collect::<Result<Option<Vec<Value>>, _>>()?;
+                let Some(args) = args else {
+                    return Ok(None);
+                };
+                Some(call(*function, args)?)
+            }
+        })
+    }
+}
+
+fn call(function: Function, args: Vec<Value>) -> Result<Value, EvalError> {
+    let number = |args: &[Value]| args[0].to_number();
+    Ok(match function {
+        Function::Lower => Value::String(args[0].to_string().to_lowercase()),
+        Function::Upper => Value::String(args[0].to_string().to_uppercase()),
+        Function::Concat => Value::String(args.iter().map(Value::to_string).collect()),
+        Function::Strlen => Value::Number(args[0].to_string().chars().count() as f64),
+        Function::Floor => Value::Number(number(&args)?.floor()),
+        Function::Ceil => Value::Number(number(&args)?.ceil()),
+    })
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    offset: usize,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.source[self.offset..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start().len();
+    }
+
+    const fn unexpected(&self, found: char) -> ParseError {
+        ParseError::Unexpected {
+            offset: self.offset,
+            found,
+        }
+    }
+
+    /// Consume `c` if it's the next non-whitespace character.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.offset += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self
+                .peek()
+                .map_or(ParseError::UnexpectedEnd, |found| self.unexpected(found)))
+        }
+    }
+
+    /// Consume the longest prefix whose characters satisfy `pred`.
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> &str {
+        let start = self.offset;
+        let len = self.rest().find(|c| !pred(c)).unwrap_or(self.rest().len());
+        self.offset += len;
+        &self.source[start..self.offset]
+    }
+
+    fn sum(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.product()?;
+        loop {
+            let op = if self.eat('+') {
+                BinaryOp::Add
+            } else if self.eat('-') {
+                BinaryOp::Sub
+            } else {
+                return Ok(lhs);
+            };
+            let rhs = self.product()?;
+            lhs = binary(op, lhs, rhs);
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr, ParseError> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                BinaryOp::Mul
+            } else if self.eat('/') {
+                BinaryOp::Div
+            } else {
+                return Ok(lhs);
+            };
+            let rhs = self.unary()?;
+            lhs = binary(op, lhs, rhs);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        if self.eat('-') {
+            Ok(Expr::Neg(Box::new(self.unary()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        self.skip_whitespace();
+        let found = self.peek().ok_or(ParseError::UnexpectedEnd)?;
+        match found {
+            '(' => {
+                self.offset += 1;
+                let expr = self.sum()?;
+                self.expect(')')?;
+                Ok(expr)
+            }
+            '@' => {
+                self.offset += 1;
+                let name = self.take_while(is_identifier);
+                if name.is_empty() {
+                    return Err(self
+                        .peek()
+                        .map_or(ParseError::UnexpectedEnd, |c| self.unexpected(c)));
+                }
+                Ok(Expr::Field(name.to_owned()))
+            }
+            '\'' | '"' => {
+                self.offset += 1;
+                let value = self.take_while(|c| c != found).to_owned();
+                self.expect(found)?;
+                Ok(Expr::Literal(Value::String(value)))
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let start = self.offset;
+                let literal = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                let number = literal.parse().map_err(|_| ParseError::Unexpected {
+                    offset: start,
+                    found,
+                })?;
+                Ok(Expr::Literal(Value::Number(number)))
+            }
+            c if is_identifier(c) => {
+                let name = self.take_while(is_identifier).to_owned();
+                let function = Function::parse(&name).ok_or(ParseError::UnknownFunction(name))?;
+                self.expect('(')?;
+                let mut args = Vec::new();
+                if !self.eat(')') {
+                    loop {
+                        args.push(self.sum()?);
+                        if self.eat(')') {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+                function.check_arity(args.len())?;
+                Ok(Expr::Call { function, args })
+            }
+            c => Err(self.unexpected(c)),
+        }
+    }
+}
+
+fn binary(op: BinaryOp, lhs: Expr, rhs: Expr) -> Expr {
+    Expr::Binary {
+        op,
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+    }
+}
+
+const fn is_identifier(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
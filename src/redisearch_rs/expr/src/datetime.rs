@@ -0,0 +1,744 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Date and time functions.
+//!
+//! Timestamps are expressed as seconds since the Unix epoch, matching how
+//! numeric (and therefore `DATETIME`) fields store them in the index.
+//!
+//! Every function that depends on the wall clock (e.g. "which day is it?")
+//! takes a [`UtcOffset`]. Only fixed offsets are supported: the module does not
+//! ship a timezone database, so daylight saving transitions are not applied.
+//!
+//! Calendar conversions use the proleptic Gregorian calendar and are exact for
+//! every representable year, unlike the `fast_timegm` helper used by the C
+//! implementation which is only correct until 2100.
+
+use std::fmt::{self, Display, Write};
+use std::str::FromStr;
+
+const SECONDS_PER_MINUTE: i64 = 60;
+const SECONDS_PER_HOUR: i64 = 60 * SECONDS_PER_MINUTE;
+const SECONDS_PER_DAY: i64 = 24 * SECONDS_PER_HOUR;
+
+/// Years beyond this bound are rejected so that day and second computations
+/// can never overflow.
+const MAX_YEAR: i64 = 1_000_000_000;
+
+/// The format used by `timefmt` when no format is given, same as the C `ISOFMT`.
+pub const ISO_FORMAT: &str = "%FT%TZ";
+
+const WEEKDAY_NAMES: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// Errors returned when parsing dates, formats or offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateTimeError {
+    /// The input does not match the expected format.
+    InvalidFormat,
+    /// A component is outside its valid range (e.g. month 13), or the result
+    /// cannot be represented as a timestamp.
+    OutOfRange,
+    /// The timezone offset could not be parsed.
+    InvalidOffset,
+    /// The time unit is not one of the supported units.
+    UnknownUnit,
+}
+
+impl Display for DateTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Self::InvalidFormat => "invalid date/time format",
+            Self::OutOfRange => "date/time component out of range",
+            Self::InvalidOffset => "invalid timezone offset",
+            Self::UnknownUnit => "unknown time unit",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for DateTimeError {}
+
+/// A fixed offset from UTC, in seconds east of Greenwich.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct UtcOffset(i32);
+
+impl UtcOffset {
+    /// The UTC timezone.
+    pub const UTC: Self = Self(0);
+
+    /// The largest supported offset magnitude, matching ISO-8601 (`±23:59`).
+    const MAX_SECONDS: i32 = 24 * 3600 - 60;
+
+    /// Create an offset from a number of seconds east of UTC.
+    ///
+    /// Returns `None` if the offset is not strictly within a day.
+    pub const fn from_seconds(seconds: i32) -> Option<Self> {
+        if seconds.abs() > Self::MAX_SECONDS {
+            None
+        } else {
+            Some(Self(seconds))
+        }
+    }
+
+    /// The offset in seconds east of UTC.
+    pub const fn seconds(self) -> i32 {
+        self.0
+    }
+}
+
+impl FromStr for UtcOffset {
+    type Err = DateTimeError;
+
+    /// Parses `Z`, `UTC`, `GMT`, `±HH`, `±HHMM` and `±HH:MM`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("z")
+            || s.eq_ignore_ascii_case("utc")
+            || s.eq_ignore_ascii_case("gmt")
+        {
+            return Ok(Self::UTC);
+        }
+        let mut cursor = Cursor::new(s);
+        let offset = cursor.offset().ok_or(DateTimeError::InvalidOffset)?;
+        if !cursor.is_empty() {
+            return Err(DateTimeError::InvalidOffset);
+        }
+        Ok(offset)
+    }
+}
+
+impl Display for UtcOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { '-' } else { '+' };
+        let abs = self.0.unsigned_abs();
+        write!(f, "{sign}{:02}{:02}", abs / 3600, (abs / 60) % 60)
+    }
+}
+
+/// Units accepted by [`truncate_to`] and [`add_interval`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    /// ISO weeks, starting on Monday.
+    Week,
+    Month,
+    Year,
+}
+
+impl FromStr for TimeUnit {
+    type Err = DateTimeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unit = match s.to_ascii_lowercase().as_str() {
+            "second" | "seconds" | "s" => Self::Second,
+            "minute" | "minutes" | "m" => Self::Minute,
+            "hour" | "hours" | "h" => Self::Hour,
+            "day" | "days" | "d" => Self::Day,
+            "week" | "weeks" | "w" => Self::Week,
+            "month" | "months" => Self::Month,
+            "year" | "years" | "y" => Self::Year,
+            _ => return Err(DateTimeError::UnknownUnit),
+        };
+        Ok(unit)
+    }
+}
+
+/// A broken-down wall-clock time, the Rust counterpart of `struct tm`.
+///
+/// Unlike `struct tm`, months and days are 1-based and the year is not
+/// offset by 1900.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateTime {
+    pub year: i64,
+    /// `1..=12`
+    pub month: u8,
+    /// `1..=31`
+    pub day: u8,
+    /// `0..=23`
+    pub hour: u8,
+    /// `0..=59`
+    pub minute: u8,
+    /// `0..=59`
+    pub second: u8,
+}
+
+impl DateTime {
+    /// Midnight of the given date.
+    pub const fn from_date(year: i64, month: u8, day: u8) -> Self {
+        Self {
+            year,
+            month,
+            day,
+            hour: 0,
+            minute: 0,
+            second: 0,
+        }
+    }
+
+    /// Convert a timestamp to the wall-clock time observed at `offset`.
+    pub const fn from_timestamp(ts: i64, offset: UtcOffset) -> Self {
+        let local = ts + offset.0 as i64;
+        let days = local.div_euclid(SECONDS_PER_DAY);
+        let secs = local.rem_euclid(SECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        Self {
+            year,
+            month,
+            day,
+            hour: (secs / SECONDS_PER_HOUR) as u8,
+            minute: ((secs % SECONDS_PER_HOUR) / SECONDS_PER_MINUTE) as u8,
+            second: (secs % SECONDS_PER_MINUTE) as u8,
+        }
+    }
+
+    /// Convert this wall-clock time, observed at `offset`, back to a timestamp.
+    ///
+    /// Returns [`DateTimeError::OutOfRange`] if any component is invalid.
+    pub fn to_timestamp(&self, offset: UtcOffset) -> Result<i64, DateTimeError> {
+        self.validate()?;
+        let days = days_from_civil(self.year, self.month, self.day);
+        let secs = self.hour as i64 * SECONDS_PER_HOUR
+            + self.minute as i64 * SECONDS_PER_MINUTE
+            + self.second as i64;
+        days.checked_mul(SECONDS_PER_DAY)
+            .and_then(|d| d.checked_add(secs))
+            .and_then(|t| t.checked_sub(offset.0 as i64))
+            .ok_or(DateTimeError::OutOfRange)
+    }
+
+    /// Day of the week, `0` being Sunday (same as `tm_wday`).
+    pub const fn day_of_week(&self) -> u8 {
+        weekday_from_days(days_from_civil(self.year, self.month, self.day))
+    }
+
+    /// Zero-based day of the year (same as `tm_yday`).
+    pub const fn day_of_year(&self) -> u16 {
+        (days_from_civil(self.year, self.month, self.day) - days_from_civil(self.year, 1, 1)) as u16
+    }
+
+    fn validate(&self) -> Result<(), DateTimeError> {
+        let valid = (-MAX_YEAR..=MAX_YEAR).contains(&self.year)
+            && (1..=12).contains(&self.month)
+            && self.day >= 1
+            && self.day <= days_in_month(self.year, self.month)
+            && self.hour < 24
+            && self.minute < 60
+            && self.second < 60;
+        if valid {
+            Ok(())
+        } else {
+            Err(DateTimeError::OutOfRange)
+        }
+    }
+}
+
+/// Whether `year` is a leap year in the proleptic Gregorian calendar.
+pub const fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// The number of days in the given month, `month` being 1-based.
+pub const fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Number of days since 1970-01-01 for the given civil date.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+const fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = month as i64;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Civil date for the given number of days since 1970-01-01.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+const fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u8;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Day of the week for the given number of days since the epoch, `0` being Sunday.
+const fn weekday_from_days(days: i64) -> u8 {
+    // 1970-01-01 was a Thursday.
+    (days + 4).rem_euclid(7) as u8
+}
+
+/// Convert a numeric value to a whole-second timestamp, rejecting NaN and
+/// values that do not fit an `i64`.
+fn to_seconds(ts: f64) -> Option<i64> {
+    // `i64::MAX as f64` rounds up, hence the strict comparison.
+    if ts.is_finite() && ts >= i64::MIN as f64 && ts < i64::MAX as f64 {
+        Some(ts.floor() as i64)
+    } else {
+        None
+    }
+}
+
+/// Round `ts` down to the beginning of the `unit` it falls in, as observed at `offset`.
+///
+/// This generalizes the `hour`, `minute`, `day` and `month` functions,
+/// which are all expressed in terms of it.
+pub fn truncate_to(ts: f64, unit: TimeUnit, offset: UtcOffset) -> Option<f64> {
+    let ts = to_seconds(ts)?;
+    let local = ts.checked_add(offset.0 as i64)?;
+    let truncated = match unit {
+        TimeUnit::Second => local,
+        TimeUnit::Minute => local - local.rem_euclid(SECONDS_PER_MINUTE),
+        TimeUnit::Hour => local - local.rem_euclid(SECONDS_PER_HOUR),
+        TimeUnit::Day => local - local.rem_euclid(SECONDS_PER_DAY),
+        TimeUnit::Week => {
+            let days = local.div_euclid(SECONDS_PER_DAY);
+            // Monday is 1 in `tm_wday` numbering.
+            let since_monday = (weekday_from_days(days) as i64 + 6) % 7;
+            (days - since_monday) * SECONDS_PER_DAY
+        }
+        TimeUnit::Month | TimeUnit::Year => {
+            let dt = DateTime::from_timestamp(local, UtcOffset::UTC);
+            let month = if unit == TimeUnit::Month { dt.month } else { 1 };
+            days_from_civil(dt.year, month, 1) * SECONDS_PER_DAY
+        }
+    };
+    Some((truncated - offset.0 as i64) as f64)
+}
+
+/// Add `amount` units to `ts`.
+///
+/// Months and years are calendar-aware: adding a month to January 31st yields
+/// the last day of February. Fixed-size units are added as plain seconds.
+pub fn add_interval(ts: f64, amount: i64, unit: TimeUnit, offset: UtcOffset) -> Option<f64> {
+    let secs = to_seconds(ts)?;
+    let fixed = |unit_secs: i64| {
+        amount
+            .checked_mul(unit_secs)
+            .and_then(|delta| secs.checked_add(delta))
+    };
+    let shifted = match unit {
+        TimeUnit::Second => fixed(1)?,
+        TimeUnit::Minute => fixed(SECONDS_PER_MINUTE)?,
+        TimeUnit::Hour => fixed(SECONDS_PER_HOUR)?,
+        TimeUnit::Day => fixed(SECONDS_PER_DAY)?,
+        TimeUnit::Week => fixed(7 * SECONDS_PER_DAY)?,
+        TimeUnit::Month | TimeUnit::Year => {
+            let months = if unit == TimeUnit::Year {
+                amount.checked_mul(12)?
+            } else {
+                amount
+            };
+            let mut dt = DateTime::from_timestamp(secs, offset);
+            let total = (dt.year.checked_mul(12)? + (dt.month as i64 - 1)).checked_add(months)?;
+            dt.year = total.div_euclid(12);
+            dt.month = (total.rem_euclid(12) + 1) as u8;
+            dt.day = dt.day.min(days_in_month(dt.year, dt.month));
+            dt.to_timestamp(offset).ok()?
+        }
+    };
+    // Keep the sub-second part of the original value.
+    Some(shifted as f64 + (ts - ts.floor()))
+}
+
+/// `hour(ts)`: `ts` rounded down to the hour.
+pub fn hour(ts: f64, offset: UtcOffset) -> Option<f64> {
+    truncate_to(ts, TimeUnit::Hour, offset)
+}
+
+/// `minute(ts)`: `ts` rounded down to the minute.
+pub fn minute(ts: f64, offset: UtcOffset) -> Option<f64> {
+    truncate_to(ts, TimeUnit::Minute, offset)
+}
+
+/// `day(ts)`: `ts` rounded down to midnight.
+pub fn day(ts: f64, offset: UtcOffset) -> Option<f64> {
+    truncate_to(ts, TimeUnit::Day, offset)
+}
+
+/// `month(ts)`: `ts` rounded down to the first day of the month.
+pub fn month(ts: f64, offset: UtcOffset) -> Option<f64> {
+    truncate_to(ts, TimeUnit::Month, offset)
+}
+
+/// `year(ts)`: the year `ts` falls in.
+pub fn year(ts: f64, offset: UtcOffset) -> Option<f64> {
+    Some(DateTime::from_timestamp(to_seconds(ts)?, offset).year as f64)
+}
+
+/// `dayofmonth(ts)`: the day of the month, `1..=31`.
+pub fn dayofmonth(ts: f64, offset: UtcOffset) -> Option<f64> {
+    Some(DateTime::from_timestamp(to_seconds(ts)?, offset).day as f64)
+}
+
+/// `dayofweek(ts)`: the day of the week, `0` being Sunday.
+pub fn dayofweek(ts: f64, offset: UtcOffset) -> Option<f64> {
+    Some(DateTime::from_timestamp(to_seconds(ts)?, offset).day_of_week() as f64)
+}
+
+/// `dayofyear(ts)`: the zero-based day of the year.
+pub fn dayofyear(ts: f64, offset: UtcOffset) -> Option<f64> {
+    Some(DateTime::from_timestamp(to_seconds(ts)?, offset).day_of_year() as f64)
+}
+
+/// `monthofyear(ts)`: the zero-based month of the year.
+pub fn monthofyear(ts: f64, offset: UtcOffset) -> Option<f64> {
+    Some((DateTime::from_timestamp(to_seconds(ts)?, offset).month - 1) as f64)
+}
+
+/// `timefmt(ts, [fmt])`: format `ts` using a `strftime`-like format string.
+///
+/// Supported conversions: `%Y %y %m %d %e %H %I %M %S %p %j %a %A %b %h %B %u %w
+/// %s %z %F %T %D %R %n %t %%`. Returns `None` for unsupported conversions, in
+/// which case the C implementation also yields NULL.
+pub fn timefmt(ts: f64, fmt: Option<&str>, offset: UtcOffset) -> Option<String> {
+    let secs = to_seconds(ts)?;
+    let dt = DateTime::from_timestamp(secs, offset);
+    let mut out = String::new();
+    let mut chars = fmt.unwrap_or(ISO_FORMAT).chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        // `write!` into a `String` never fails.
+        let _ = match chars.next()? {
+            'Y' => write!(out, "{}", dt.year),
+            'y' => write!(out, "{:02}", dt.year.rem_euclid(100)),
+            'm' => write!(out, "{:02}", dt.month),
+            'd' => write!(out, "{:02}", dt.day),
+            'e' => write!(out, "{:2}", dt.day),
+            'H' => write!(out, "{:02}", dt.hour),
+            'I' => write!(out, "{:02}", (dt.hour + 11) % 12 + 1),
+            'M' => write!(out, "{:02}", dt.minute),
+            'S' => write!(out, "{:02}", dt.second),
+            'p' => out.write_str(if dt.hour < 12 { "AM" } else { "PM" }),
+            'j' => write!(out, "{:03}", dt.day_of_year() + 1),
+            'a' => out.write_str(&WEEKDAY_NAMES[dt.day_of_week() as usize][..3]),
+            'A' => out.write_str(WEEKDAY_NAMES[dt.day_of_week() as usize]),
+            'b' | 'h' => out.write_str(&MONTH_NAMES[dt.month as usize - 1][..3]),
+            'B' => out.write_str(MONTH_NAMES[dt.month as usize - 1]),
+            'u' => write!(out, "{}", (dt.day_of_week() + 6) % 7 + 1),
+            'w' => write!(out, "{}", dt.day_of_week()),
+            's' => write!(out, "{secs}"),
+            'z' => write!(out, "{offset}"),
+            'F' => write!(out, "{}-{:02}-{:02}", dt.year, dt.month, dt.day),
+            'T' => write!(out, "{:02}:{:02}:{:02}", dt.hour, dt.minute, dt.second),
+            'D' => write!(
+                out,
+                "{:02}/{:02}/{:02}",
+                dt.month,
+                dt.day,
+                dt.year.rem_euclid(100)
+            ),
+            'R' => write!(out, "{:02}:{:02}", dt.hour, dt.minute),
+            'n' => out.write_char('\n'),
+            't' => out.write_char('\t'),
+            '%' => out.write_char('%'),
+            _ => return None,
+        };
+    }
+    Some(out)
+}
+
+/// `parsetime(value, fmt)`: parse `value` according to a `strptime`-like format.
+///
+/// Supported conversions: `%Y %y %m %d %e %H %M %S %j %b %h %B %s %z %F %T %D %R %%`.
+/// Whitespace in the format matches any amount of whitespace in the input.
+/// Components missing from the format default to 1970-01-01T00:00:00.
+/// A `%z` conversion shifts the result from the parsed offset to UTC.
+pub fn parsetime(value: &str, fmt: &str) -> Option<f64> {
+    let mut dt = DateTime::from_date(1970, 1, 1);
+    let mut offset = UtcOffset::UTC;
+    let mut day_of_year = None;
+    let mut epoch = None;
+
+    let mut input = Cursor::new(value);
+    let mut fmt_chars = fmt.chars();
+    while let Some(c) = fmt_chars.next() {
+        if c.is_whitespace() {
+            input.skip_whitespace();
+            continue;
+        }
+        if c != '%' {
+            input.literal(c)?;
+            continue;
+        }
+        match fmt_chars.next()? {
+            'Y' => dt.year = input.signed(4)?,
+            'y' => {
+                // POSIX: 69-99 map to the 1900s, 00-68 to the 2000s.
+                let y = input.number(2)?;
+                dt.year = if y < 69 { 2000 + y } else { 1900 + y };
+            }
+            'm' => dt.month = input.number(2)? as u8,
+            'd' | 'e' => {
+                input.skip_whitespace();
+                dt.day = input.number(2)? as u8;
+            }
+            'H' => dt.hour = input.number(2)? as u8,
+            'M' => dt.minute = input.number(2)? as u8,
+            'S' => dt.second = input.number(2)? as u8,
+            'j' => day_of_year = Some(input.number(3)?),
+            'b' | 'h' | 'B' => dt.month = input.month_name()?,
+            's' => epoch = Some(input.signed(1)?),
+            'z' => offset = input.offset()?,
+            'F' => {
+                dt.year = input.signed(4)?;
+                input.literal('-')?;
+                dt.month = input.number(2)? as u8;
+                input.literal('-')?;
+                dt.day = input.number(2)? as u8;
+            }
+            'T' => {
+                dt.hour = input.number(2)? as u8;
+                input.literal(':')?;
+                dt.minute = input.number(2)? as u8;
+                input.literal(':')?;
+                dt.second = input.number(2)? as u8;
+            }
+            'D' => {
+                dt.month = input.number(2)? as u8;
+                input.literal('/')?;
+                dt.day = input.number(2)? as u8;
+                input.literal('/')?;
+                let y = input.number(2)?;
+                dt.year = if y < 69 { 2000 + y } else { 1900 + y };
+            }
+            'R' => {
+                dt.hour = input.number(2)? as u8;
+                input.literal(':')?;
+                dt.minute = input.number(2)? as u8;
+            }
+            '%' => input.literal('%')?,
+            _ => return None,
+        }
+    }
+
+    if let Some(epoch) = epoch {
+        return Some(epoch as f64);
+    }
+    if let Some(yday) = day_of_year {
+        let days_in_year = if is_leap_year(dt.year) { 366 } else { 365 };
+        if !(1..=days_in_year).contains(&yday) {
+            return None;
+        }
+        let days = days_from_civil(dt.year, 1, 1) + yday - 1;
+        let (_, month, day) = civil_from_days(days);
+        dt.month = month;
+        dt.day = day;
+    }
+    dt.to_timestamp(offset).ok().map(|ts| ts as f64)
+}
+
+/// Parse an ISO-8601 date or date-time into a timestamp.
+///
+/// Accepted forms are `YYYY-MM-DD`, optionally followed by `T` (or a space)
+/// and `HH:MM`, `HH:MM:SS` or `HH:MM:SS.fff`, optionally followed by `Z`,
+/// `±HH`, `±HHMM` or `±HH:MM`. Values without an offset are interpreted as UTC.
+pub fn parse_iso8601(s: &str) -> Result<f64, DateTimeError> {
+    let mut input = Cursor::new(s.trim());
+    let invalid = DateTimeError::InvalidFormat;
+
+    let mut dt = DateTime::from_date(0, 1, 1);
+    dt.year = input.signed(4).ok_or(invalid)?;
+    input.literal('-').ok_or(invalid)?;
+    dt.month = input.exact(2).ok_or(invalid)? as u8;
+    input.literal('-').ok_or(invalid)?;
+    dt.day = input.exact(2).ok_or(invalid)? as u8;
+
+    let mut fraction = 0.0;
+    if input.literal('T').or_else(|| input.literal(' ')).is_some() {
+        dt.hour = input.exact(2).ok_or(invalid)? as u8;
+        input.literal(':').ok_or(invalid)?;
+        dt.minute = input.exact(2).ok_or(invalid)? as u8;
+        if input.literal(':').is_some() {
+            dt.second = input.exact(2).ok_or(invalid)? as u8;
+            if input.literal('.').is_some() || input.literal(',').is_some() {
+                fraction = input.fraction().ok_or(invalid)?;
+            }
+        }
+    }
+
+    let offset = if input.is_empty() || input.literal('z').is_some() {
+        UtcOffset::UTC
+    } else {
+        input.offset().ok_or(DateTimeError::InvalidOffset)?
+    };
+    if !input.is_empty() {
+        return Err(invalid);
+    }
+
+    Ok(dt.to_timestamp(offset)? as f64 + fraction)
+}
+
+/// Parse a bound of a numeric range on a field declared with the `DATETIME` attribute.
+///
+/// In addition to the plain numbers accepted for any numeric field (including
+/// `inf`, `+inf` and `-inf`), ISO-8601 literals as accepted by [`parse_iso8601`]
+/// are converted to their timestamp.
+pub fn parse_datetime_bound(s: &str) -> Result<f64, DateTimeError> {
+    match s.parse::<f64>() {
+        Ok(n) if !n.is_nan() => Ok(n),
+        _ => parse_iso8601(s),
+    }
+}
+
+/// A minimal scanner over the input of [`parsetime`] and [`parse_iso8601`].
+struct Cursor<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Cursor<'a> {
+    const fn new(s: &'a str) -> Self {
+        Self { rest: s }
+    }
+
+    const fn is_empty(&self) -> bool {
+        self.rest.is_empty()
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn literal(&mut self, c: char) -> Option<()> {
+        self.rest = self.rest.strip_prefix(c)?;
+        Some(())
+    }
+
+    /// Up to `max_digits` decimal digits, at least one.
+    fn number(&mut self, max_digits: usize) -> Option<i64> {
+        let len = self
+            .rest
+            .bytes()
+            .take(max_digits)
+            .take_while(u8::is_ascii_digit)
+            .count();
+        if len == 0 {
+            return None;
+        }
+        let (digits, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        digits.parse().ok()
+    }
+
+    /// Exactly `digits` decimal digits.
+    fn exact(&mut self, digits: usize) -> Option<i64> {
+        let before = self.rest.len();
+        let n = self.number(digits)?;
+        (before - self.rest.len() == digits).then_some(n)
+    }
+
+    /// An optionally signed number of at least `min_digits` digits.
+    fn signed(&mut self, min_digits: usize) -> Option<i64> {
+        let negative = self.literal('-').is_some();
+        if !negative {
+            let _ = self.literal('+');
+        }
+        let before = self.rest.len();
+        let n = self.number(19)?;
+        if before - self.rest.len() < min_digits {
+            return None;
+        }
+        Some(if negative { -n } else { n })
+    }
+
+    /// The digits following a decimal separator, as a fraction of one.
+    fn fraction(&mut self) -> Option<f64> {
+        let len = self.rest.bytes().take_while(u8::is_ascii_digit).count();
+        if len == 0 {
+            return None;
+        }
+        let (digits, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        format!("0.{digits}").parse().ok()
+    }
+
+    /// `Z`, `±HH`, `±HHMM` or `±HH:MM`.
+    fn offset(&mut self) -> Option<UtcOffset> {
+        if self.literal('Z').is_some() {
+            return Some(UtcOffset::UTC);
+        }
+        let negative = match self.rest.as_bytes().first()? {
+            b'+' => false,
+            b'-' => true,
+            _ => return None,
+        };
+        self.rest = &self.rest[1..];
+        let hours = self.exact(2)?;
+        let _ = self.literal(':');
+        let minutes = if self.rest.starts_with(|c: char| c.is_ascii_digit()) {
+            self.exact(2)?
+        } else {
+            0
+        };
+        if minutes >= 60 {
+            return None;
+        }
+        let seconds = (hours * 3600 + minutes * 60) as i32;
+        UtcOffset::from_seconds(if negative { -seconds } else { seconds })
+    }
+
+    /// A full or abbreviated English month name, case-insensitively.
+    fn month_name(&mut self) -> Option<u8> {
+        for (i, name) in MONTH_NAMES.iter().enumerate() {
+            for candidate in [*name, &name[..3]] {
+                let matches = self
+                    .rest
+                    .get(..candidate.len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(candidate));
+                if matches {
+                    self.rest = &self.rest[candidate.len()..];
+                    return Some(i as u8 + 1);
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,19 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Building blocks for the expression language used by `APPLY`, `FILTER` and
+//! the reducers of `FT.AGGREGATE`.
+//!
+//! Each module groups a family of functions that can be registered with the
+//! expression evaluator. Functions operate on plain Rust types; converting
+//! from and to `RSValue`s is left to the caller.
+
+pub mod computed;
+pub mod datetime;
+pub mod null;
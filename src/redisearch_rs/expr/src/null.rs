@@ -0,0 +1,152 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! NULL semantics, shared by `APPLY`, `FILTER` and `SORTBY`.
+//!
+//! A value is NULL when the document has no value for a field, or when it's
+//! computed from a NULL. NULLs are represented as `None`:
+//!
+//! - functions and operators with a NULL argument return NULL, see
+//!   [`propagate`] and [`propagate2`];
+//! - comparisons with a NULL are neither true nor false: a `FILTER` whose
+//!   condition is NULL drops the row, see [`compare`] and [`passes_filter`];
+//! - `&&`, `||` and `!` follow three-valued logic, so `NULL || true` is true;
+//! - `exists(@field)` is the only way to test for NULL, see [`exists`];
+//! - NULLs sort last, both in ascending and descending order, see [`sort_cmp`].
+//!
+//! In [`NullMode::Strict`], a missing field is an error rather than a NULL,
+//! for users who'd rather fail than get partial results.
+
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// How missing fields are handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NullMode {
+    /// A missing field is NULL.
+    #[default]
+    Lenient,
+    /// A missing field is an error.
+    Strict,
+}
+
+impl FromStr for NullMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("lenient") {
+            Ok(Self::Lenient)
+        } else if s.eq_ignore_ascii_case("strict") {
+            Ok(Self::Strict)
+        } else {
+            Err(())
+        }
+    }
+}
+
+/// A field is missing in [`NullMode::Strict`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingField(pub String);
+
+impl Display for MissingField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Missing value for field `{}`", self.0)
+    }
+}
+
+impl std::error::Error for MissingField {}
+
+/// The value of the field `name` of a document, `None` if it has none.
+///
+/// # Errors
+///
+/// Returns [`MissingField`] if the value is missing in [`NullMode::Strict`].
+pub fn field_value<T>(
+    name: &str,
+    value: Option<T>,
+    mode: NullMode,
+) -> Result<Option<T>, MissingField> {
+    match (value, mode) {
+        (None, NullMode::Strict) => Err(MissingField(name.to_owned())),
+        (value, _) => Ok(value),
+    }
+}
+
+/// Apply `f` to a possibly NULL argument.
+pub fn propagate<T, R>(a: Option<T>, f: impl FnOnce(T) -> R) -> Option<R> {
+    a.map(f)
+}
+
+/// Apply `f` to two possibly NULL arguments: the result is NULL if either is.
+pub fn propagate2<A, B, R>(a: Option<A>, b: Option<B>, f: impl FnOnce(A, B) -> R) -> Option<R> {
+    Some(f(a?, b?))
+}
+
+/// Compare two values, NULL if either is.
+pub fn compare<T: PartialOrd>(a: Option<&T>, b: Option<&T>) -> Option<Ordering> {
+    a?.partial_cmp(b?)
+}
+
+/// Whether a row passes a `FILTER` whose condition evaluated to `condition`.
+pub const fn passes_filter(condition: Option<bool>) -> bool {
+    matches!(condition, Some(true))
+}
+
+/// Three-valued `&&`: false if either side is false, NULL if either is NULL.
+pub const fn and(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+    match (a, b) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+    }
+}
+
+/// Three-valued `||`: true if either side is true, NULL if either is NULL.
+pub const fn or(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+    match (a, b) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None,
+    }
+}
+
+/// Three-valued `!`.
+pub const fn not(a: Option<bool>) -> Option<bool> {
+    match a {
+        Some(a) => Some(!a),
+        None => None,
+    }
+}
+
+/// `exists(value)`.
+pub const fn exists<T>(value: Option<&T>) -> bool {
+    value.is_some()
+}
+
+/// The order of two rows sorted by a value, `cmp` ordering non-NULL values.
+///
+/// NULLs come last whatever the direction, so that `LIMIT` returns rows with
+/// a value first.
+pub fn sort_cmp<T>(
+    a: Option<&T>,
+    b: Option<&T>,
+    ascending: bool,
+    cmp: impl FnOnce(&T, &T) -> Ordering,
+) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if ascending => cmp(a, b),
+        (Some(a), Some(b)) => cmp(b, a),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
@@ -0,0 +1,92 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use expr::computed::{EvalError, Expr, ParseError, Value};
+
+fn eval(source: &str, doc: &[(&str, &str)]) -> Result<Option<Value>, EvalError> {
+    let lookup = |name: &str| doc.iter().find(|(f, _)| *f == name).map(|(_, v)| *v);
+    Expr::parse(source).unwrap().eval(&lookup)
+}
+
+fn string(s: &str) -> Option<Value> {
+    Some(Value::String(s.to_owned()))
+}
+
+#[test]
+fn test_string_functions() {
+    let doc = [("title", "Hello World"), ("brand", "ACME")];
+    assert_eq!(eval("lower(@title)", &doc), Ok(string("hello world")));
+    assert_eq!(eval("upper( @title )", &doc), Ok(string("HELLO WORLD")));
+    assert_eq!(
+        eval("concat(@brand, ':', lower(@title))", &doc),
+        Ok(string("ACME:hello world"))
+    );
+    assert_eq!(eval("strlen(@brand)", &doc), Ok(Some(Value::Number(4.0))));
+}
+
+#[test]
+fn test_arithmetic() {
+    let doc = [("cents", "1250"), ("celsius", "-40")];
+    assert_eq!(eval("@cents / 100", &doc), Ok(Some(Value::Number(12.5))));
+    assert_eq!(
+        eval("@celsius * 9 / 5 + 32", &doc),
+        Ok(Some(Value::Number(-40.0)))
+    );
+    assert_eq!(eval("-(1 - 3) * 2", &doc), Ok(Some(Value::Number(4.0))));
+    assert_eq!(
+        eval("floor(@cents / 1000)", &doc),
+        Ok(Some(Value::Number(1.0)))
+    );
+    assert_eq!(eval("concat('n', 1 + 1)", &doc), Ok(string("n2")));
+    assert_eq!(
+        eval("@cents * 2", &[("cents", "free")]),
+        Err(EvalError::NotANumber("free".to_owned()))
+    );
+}
+
+#[test]
+fn test_missing_fields_are_null() {
+    assert_eq!(eval("lower(@title)", &[]), Ok(None));
+    assert_eq!(eval("concat('a', @title)", &[]), Ok(None));
+    assert_eq!(eval("-@price + 1", &[]), Ok(None));
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(
+        Expr::parse("lower(@a").unwrap_err(),
+        ParseError::UnexpectedEnd
+    );
+    assert_eq!(
+        Expr::parse("@a +* 2"),
+        Err(ParseError::Unexpected {
+            offset: 4,
+            found: '*'
+        })
+    );
+    assert_eq!(
+        Expr::parse("reverse(@a)").unwrap_err().to_string(),
+        "Unknown function `reverse`"
+    );
+    assert_eq!(
+        Expr::parse("lower(@a, @b)").unwrap_err().to_string(),
+        "`lower` expects 1 argument(s)"
+    );
+    assert_eq!(
+        Expr::parse("@a 2").unwrap_err(),
+        ParseError::Unexpected {
+            offset: 3,
+            found: '2'
+        }
+    );
+    assert_eq!(
+        Expr::parse("concat(@a, lower(@b), @a)").unwrap().fields(),
+        ["a", "b"]
+    );
+}
@@ -0,0 +1,203 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use expr::datetime::*;
+
+// 2024-02-29T13:45:30Z, a Thursday.
+const LEAP_DAY: f64 = 1_709_214_330.0;
+
+fn offset(s: &str) -> UtcOffset {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_roundtrip_civil() {
+    for ts in [
+        0,
+        1,
+        -1,
+        951_782_400,
+        4_107_542_400,
+        -62_135_596_800,
+        253_402_300_799,
+    ] {
+        let dt = DateTime::from_timestamp(ts, UtcOffset::UTC);
+        assert_eq!(dt.to_timestamp(UtcOffset::UTC), Ok(ts), "{dt:?}");
+    }
+    assert_eq!(
+        DateTime::from_timestamp(-1, UtcOffset::UTC),
+        DateTime {
+            year: 1969,
+            month: 12,
+            day: 31,
+            hour: 23,
+            minute: 59,
+            second: 59
+        }
+    );
+}
+
+#[test]
+fn test_invalid_civil() {
+    let dt = DateTime::from_date(2023, 2, 29);
+    assert_eq!(
+        dt.to_timestamp(UtcOffset::UTC),
+        Err(DateTimeError::OutOfRange)
+    );
+}
+
+#[test]
+fn test_offsets() {
+    assert_eq!(offset("Z"), UtcOffset::UTC);
+    assert_eq!(offset("utc"), UtcOffset::UTC);
+    assert_eq!(offset("+02:00").seconds(), 7200);
+    assert_eq!(offset("-0530").seconds(), -19800);
+    assert_eq!(offset("+09").seconds(), 32400);
+    assert_eq!(offset("-05:30").to_string(), "-0530");
+    assert!("+24:00".parse::<UtcOffset>().is_err());
+    assert!("+02:60".parse::<UtcOffset>().is_err());
+    assert!("Europe/Paris".parse::<UtcOffset>().is_err());
+}
+
+#[test]
+fn test_components() {
+    let utc = UtcOffset::UTC;
+    assert_eq!(year(LEAP_DAY, utc), Some(2024.0));
+    assert_eq!(monthofyear(LEAP_DAY, utc), Some(1.0));
+    assert_eq!(dayofmonth(LEAP_DAY, utc), Some(29.0));
+    assert_eq!(dayofweek(LEAP_DAY, utc), Some(4.0));
+    assert_eq!(dayofyear(LEAP_DAY, utc), Some(59.0));
+    assert_eq!(hour(LEAP_DAY, utc), Some(1_709_211_600.0));
+    assert_eq!(minute(LEAP_DAY, utc), Some(1_709_214_300.0));
+    assert_eq!(day(LEAP_DAY, utc), Some(1_709_164_800.0));
+    assert_eq!(month(LEAP_DAY, utc), Some(1_706_745_600.0));
+    assert_eq!(year(f64::NAN, utc), None);
+    assert_eq!(year(f64::INFINITY, utc), None);
+}
+
+#[test]
+fn test_components_with_offset() {
+    // 13:45 UTC is already the next day in Kiribati (+14:00).
+    let kiribati = offset("+14:00");
+    assert_eq!(dayofmonth(LEAP_DAY, kiribati), Some(1.0));
+    assert_eq!(monthofyear(LEAP_DAY, kiribati), Some(2.0));
+    assert_eq!(dayofweek(LEAP_DAY, kiribati), Some(5.0));
+    // Local midnight, expressed in UTC.
+    assert_eq!(day(LEAP_DAY, kiribati), Some(1_709_200_800.0));
+    // Half-hour offsets shift hour boundaries.
+    assert_eq!(hour(LEAP_DAY, offset("+05:30")), Some(1_709_213_400.0));
+}
+
+#[test]
+fn test_truncate_to() {
+    let utc = UtcOffset::UTC;
+    let trunc = |unit: &str| truncate_to(LEAP_DAY, unit.parse().unwrap(), utc);
+    assert_eq!(trunc("second"), Some(LEAP_DAY));
+    assert_eq!(trunc("hour"), hour(LEAP_DAY, utc));
+    // Monday 2024-02-26.
+    assert_eq!(trunc("week"), Some(1_708_905_600.0));
+    assert_eq!(trunc("year"), Some(1_704_067_200.0));
+    assert_eq!(
+        "fortnight".parse::<TimeUnit>(),
+        Err(DateTimeError::UnknownUnit)
+    );
+    // Truncation before the epoch rounds towards the past.
+    assert_eq!(truncate_to(-1.0, TimeUnit::Day, utc), Some(-86400.0));
+}
+
+#[test]
+fn test_add_interval() {
+    let utc = UtcOffset::UTC;
+    let jan31 = parse_iso8601("2023-01-31T10:00:00Z").unwrap();
+    assert_eq!(
+        add_interval(jan31, 1, TimeUnit::Month, utc),
+        Some(parse_iso8601("2023-02-28T10:00:00Z").unwrap())
+    );
+    assert_eq!(
+        add_interval(LEAP_DAY, 1, TimeUnit::Year, utc),
+        Some(parse_iso8601("2025-02-28T13:45:30Z").unwrap())
+    );
+    assert_eq!(
+        add_interval(jan31, -13, TimeUnit::Month, utc),
+        Some(parse_iso8601("2021-12-31T10:00:00Z").unwrap())
+    );
+    assert_eq!(add_interval(1.5, 2, TimeUnit::Hour, utc), Some(7201.5));
+    assert_eq!(add_interval(0.0, i64::MAX, TimeUnit::Day, utc), None);
+}
+
+#[test]
+fn test_timefmt() {
+    let utc = UtcOffset::UTC;
+    assert_eq!(
+        timefmt(LEAP_DAY, None, utc).as_deref(),
+        Some("2024-02-29T13:45:30Z")
+    );
+    assert_eq!(
+        timefmt(LEAP_DAY, Some("%a %A %b %B %j %u %w %I%p %y %D %R %%"), utc).as_deref(),
+        Some("Thu Thursday Feb February 060 4 4 01PM 24 02/29/24 13:45 %")
+    );
+    assert_eq!(
+        timefmt(LEAP_DAY, Some("%F %T %z"), offset("-08:00")).as_deref(),
+        Some("2024-02-29 05:45:30 -0800")
+    );
+    assert_eq!(timefmt(LEAP_DAY, Some("%Q"), utc), None);
+    assert_eq!(timefmt(LEAP_DAY, Some("%"), utc), None);
+}
+
+#[test]
+fn test_parsetime() {
+    assert_eq!(parsetime("2024-02-29T13:45:30", "%FT%T"), Some(LEAP_DAY));
+    assert_eq!(
+        parsetime("29 feb 2024  13:45:30", "%d %b %Y %H:%M:%S"),
+        Some(LEAP_DAY)
+    );
+    assert_eq!(
+        parsetime("2024-02-29 15:45:30 +0200", "%F %T %z"),
+        Some(LEAP_DAY)
+    );
+    assert_eq!(parsetime("2024 060", "%Y %j"), Some(1_709_164_800.0));
+    assert_eq!(parsetime("1709214330", "%s"), Some(LEAP_DAY));
+    assert_eq!(parsetime("99", "%y"), Some(915_148_800.0));
+    assert_eq!(parsetime("2023-02-29", "%F"), None);
+    assert_eq!(parsetime("2024/02/29", "%F"), None);
+    assert_eq!(parsetime("2023 366", "%Y %j"), None);
+}
+
+#[test]
+fn test_parse_iso8601() {
+    assert_eq!(parse_iso8601("2024-02-29T13:45:30Z"), Ok(LEAP_DAY));
+    assert_eq!(parse_iso8601("2024-02-29 13:45:30"), Ok(LEAP_DAY));
+    assert_eq!(parse_iso8601("2024-02-29T14:45:30+01:00"), Ok(LEAP_DAY));
+    assert_eq!(
+        parse_iso8601("2024-02-29T13:45:30.25Z"),
+        Ok(LEAP_DAY + 0.25)
+    );
+    assert_eq!(parse_iso8601("2024-02-29T13:45Z"), Ok(LEAP_DAY - 30.0));
+    assert_eq!(parse_iso8601("2024-02-29"), Ok(1_709_164_800.0));
+    assert_eq!(parse_iso8601("1969-12-31T23:59:59Z"), Ok(-1.0));
+    assert_eq!(
+        parse_iso8601("2024-2-29"),
+        Err(DateTimeError::InvalidFormat)
+    );
+    assert_eq!(parse_iso8601("2024-02-30"), Err(DateTimeError::OutOfRange));
+    assert_eq!(
+        parse_iso8601("2024-02-29T13:45:30 garbage"),
+        Err(DateTimeError::InvalidOffset)
+    );
+}
+
+#[test]
+fn test_parse_datetime_bound() {
+    assert_eq!(parse_datetime_bound("1709214330"), Ok(LEAP_DAY));
+    assert_eq!(parse_datetime_bound("-inf"), Ok(f64::NEG_INFINITY));
+    assert_eq!(parse_datetime_bound("+inf"), Ok(f64::INFINITY));
+    assert_eq!(parse_datetime_bound("2024-02-29T13:45:30Z"), Ok(LEAP_DAY));
+    assert!(parse_datetime_bound("nan").is_err());
+    assert!(parse_datetime_bound("yesterday").is_err());
+}
@@ -0,0 +1,74 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::cmp::Ordering;
+
+use expr::null::*;
+
+#[test]
+fn test_missing_fields() {
+    assert_eq!(
+        field_value("price", Some(1.0), NullMode::Strict),
+        Ok(Some(1.0))
+    );
+    assert_eq!(
+        field_value::<f64>("price", None, NullMode::Lenient),
+        Ok(None)
+    );
+    assert_eq!(
+        field_value::<f64>("price", None, NullMode::Strict),
+        Err(MissingField("price".to_owned()))
+    );
+    assert_eq!("STRICT".parse(), Ok(NullMode::Strict));
+    assert_eq!("other".parse::<NullMode>(), Err(()));
+}
+
+#[test]
+fn test_propagation() {
+    assert_eq!(propagate(Some(2.0), |x: f64| x * 2.0), Some(4.0));
+    assert_eq!(propagate(None, |x: f64| x * 2.0), None);
+    assert_eq!(
+        propagate2(Some(1.0), Some(2.0), |a: f64, b| a + b),
+        Some(3.0)
+    );
+    assert_eq!(propagate2(Some(1.0), None, |a: f64, b: f64| a + b), None);
+
+    // `@price > 10` on a document without price.
+    let condition = compare(None, Some(&10.0)).map(Ordering::is_gt);
+    assert!(!passes_filter(condition));
+    // Neither does its negation pass.
+    assert!(!passes_filter(not(condition)));
+    assert!(passes_filter(
+        compare(Some(&11.0), Some(&10.0)).map(Ordering::is_gt)
+    ));
+}
+
+#[test]
+fn test_three_valued_logic() {
+    let values = [Some(true), Some(false), None];
+    for a in values {
+        assert_eq!(and(a, Some(false)), Some(false));
+        assert_eq!(or(a, Some(true)), Some(true));
+        assert_eq!(and(a, Some(true)), a);
+        assert_eq!(or(a, Some(false)), a);
+    }
+    assert_eq!(and(None, None), None);
+    assert_eq!(not(None), None);
+    assert!(exists(Some(&0)));
+    assert!(!exists::<i32>(None));
+}
+
+#[test]
+fn test_nulls_sort_last() {
+    let mut values = vec![Some(2), None, Some(1), None, Some(3)];
+    values.sort_by(|a, b| sort_cmp(a.as_ref(), b.as_ref(), true, Ord::cmp));
+    assert_eq!(values, [Some(1), Some(2), Some(3), None, None]);
+    values.sort_by(|a, b| sort_cmp(a.as_ref(), b.as_ref(), false, Ord::cmp));
+    assert_eq!(values, [Some(3), Some(2), Some(1), None, None]);
+}
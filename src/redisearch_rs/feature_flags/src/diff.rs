@@ -0,0 +1,389 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The comparison of the C and Rust replies of a shadowed query.
+//!
+//! Both replies are canonicalized before being compared: fields are sorted by
+//! name, and results whose scores tie are sorted by id, since either path may
+//! return them in any order. Numbers are equal within
+//! [`DiffConfig::float_tolerance`]. The remaining [`Difference`]s are
+//! classified, and the diffs of the last queries are kept in a [`DiffLog`],
+//! which [dumps](DiffLog::dump) them a line per diff.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{self, Display},
+    sync::{Mutex, atomic::Ordering},
+};
+
+use crate::{Flag, FlagMode};
+
+/// A value of a result field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+}
+
+/// A result of a query reply.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub id: String,
+    pub score: Option<f64>,
+    pub fields: Vec<(String, Value)>,
+}
+
+/// The reply of a query, as compared by shadow mode.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchReply {
+    pub total: u64,
+    pub rows: Vec<Row>,
+}
+
+/// How replies are compared.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffConfig {
+    /// The relative tolerance of numbers, and the absolute tolerance of
+    /// numbers smaller than 1.
+    pub float_tolerance: f64,
+    /// Whether the order of results with tied scores is ignored.
+    pub ignore_tie_order: bool,
+}
+
+impl Default for DiffConfig {
+    fn default() -> Self {
+        Self {
+            float_tolerance: 1e-9,
+            ignore_tie_order: true,
+        }
+    }
+}
+
+impl DiffConfig {
+    fn approx_eq(&self, a: f64, b: f64) -> bool {
+        a == b || (a - b).abs() <= self.float_tolerance * a.abs().max(b.abs()).max(1.0)
+    }
+
+    fn values_eq(&self, a: &Value, b: &Value) -> bool {
+        match (a, b) {
+            (Value::Number(a), Value::Number(b)) => self.approx_eq(*a, *b),
+            (Value::Array(a), Value::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| self.values_eq(a, b))
+            }
+            _ => a == b,
+        }
+    }
+
+    fn scores_eq(&self, a: Option<f64>, b: Option<f64>) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) => self.approx_eq(a, b),
+            _ => a == b,
+        }
+    }
+}
+
+/// A difference between the C and Rust replies.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    /// The Rust path panicked.
+    Panic(String),
+    TotalResults {
+        c: u64,
+        rust: u64,
+    },
+    /// A result only returned by C.
+    Missing(String),
+    /// A result only returned by Rust.
+    Extra(String),
+    /// The results returned by both are not in the same order: `c` and `rust`
+    /// are the first results that differ.
+    Order {
+        position: usize,
+        c: String,
+        rust: String,
+    },
+    Score {
+        id: String,
+        c: Option<f64>,
+        rust: Option<f64>,
+    },
+    /// A field whose value differs, or which only one path returned.
+    Field {
+        id: String,
+        field: String,
+        c: Option<Value>,
+        rust: Option<Value>,
+    },
+}
+
+impl Difference {
+    /// The class of the difference, to aggregate mismatches.
+    pub const fn class(&self) -> &'static str {
+        match self {
+            Self::Panic(_) => "panic",
+            Self::TotalResults { .. } => "total",
+            Self::Missing(_) => "missing",
+            Self::Extra(_) => "extra",
+            Self::Order { .. } => "order",
+            Self::Score { .. } => "score",
+            Self::Field { .. } => "field",
+        }
+    }
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Panic(message) => write!(f, "panic: {message}"),
+            Self::TotalResults { c, rust } => write!(f, "total C={c} Rust={rust}"),
+            Self::Missing(id) => write!(f, "missing {id}"),
+            Self::Extra(id) => write!(f, "extra {id}"),
+            Self::Order { position, c, rust } => {
+                write!(f, "order at {position} C={c} Rust={rust}")
+            }
+            Self::Score { id, c, rust } => write!(f, "score {id} C={c:?} Rust={rust:?}"),
+            Self::Field { id, field, c, rust } => {
+                write!(f, "field {id}.{field} C={c:?} Rust={rust:?}")
+            }
+        }
+    }
+}
+
+impl SearchReply {
+    /// Sort the fields of every row by name, and the rows with tied scores by
+    /// id if `config` ignores their order.
+    pub fn canonicalize(&mut self, config: &DiffConfig) {
+        for row in &mut self.rows {
+            row.fields.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+        if !config.ignore_tie_order {
+            return;
+        }
+        let mut start = 0;
+        while start < self.rows.len() {
+            let score = self.rows[start].score;
+            let mut end = start + 1;
+            while end < self.rows.len()
+                && score.is_some()
+                && config.scores_eq(score, self.rows[end].score)
+            {
+                end += 1;
+            }
+            self.rows[start..end].sort_by(|a, b| a.id.cmp(&b.id));
+            start = end;
+        }
+    }
+
+    /// The differences of `rust` from `self`, the C reply.
+    pub fn diff(&self, rust: &Self, config: &DiffConfig) -> Vec<Difference> {
+        let mut c = self.clone();
+        let mut rust = rust.clone();
+        c.canonicalize(config);
+        rust.canonicalize(config);
+
+        let mut differences = Vec::new();
+        if c.total != rust.total {
+            differences.push(Difference::TotalResults {
+                c: c.total,
+                rust: rust.total,
+            });
+        }
+        let c_rows: HashMap<&str, &Row> = c.rows.iter().map(|r| (r.id.as_str(), r)).collect();
+        let rust_rows: HashMap<&str, &Row> = rust.rows.iter().map(|r| (r.id.as_str(), r)).collect();
+        for row in &c.rows {
+            if !rust_rows.contains_key(row.id.as_str()) {
+                differences.push(Difference::Missing(row.id.clone()));
+            }
+        }
+        for row in &rust.rows {
+            if !c_rows.contains_key(row.id.as_str()) {
+                differences.push(Difference::Extra(row.id.clone()));
+            }
+        }
+
+        // The order of the results returned by both.
+        let common = |rows: &[Row], other: &HashMap<&str, &Row>| -> Vec<String> {
+            rows.iter()
+                .filter(|r| other.contains_key(r.id.as_str()))
+                .map(|r| r.id.clone())
+                .collect()
+        };
+        let c_order = common(&c.rows, &rust_rows);
+        let rust_order = common(&rust.rows, &c_rows);
+        if let Some(position) = c_order.iter().zip(&rust_order).position(|(a, b)| a != b) {
+            differences.push(Difference::Order {
+                position,
+                c: c_order[position].clone(),
+                rust: rust_order[position].clone(),
+            });
+        }
+
+        for id in &c_order {
+            let (c, rust) = (c_rows[id.as_str()], rust_rows[id.as_str()]);
+            if !config.scores_eq(c.score, rust.score) {
+                differences.push(Difference::Score {
+                    id: id.clone(),
+                    c: c.score,
+                    rust: rust.score,
+                });
+            }
+            diff_fields(id, &c.fields, &rust.fields, config, &mut differences);
+        }
+        differences
+    }
+}
+
+/// Compare fields sorted by name.
+fn diff_fields(
+    id: &str,
+    c: &[(String, Value)],
+    rust: &[(String, Value)],
+    config: &DiffConfig,
+    differences: &mut Vec<Difference>,
+) {
+    let (mut c, mut rust) = (c.iter().peekable(), rust.iter().peekable());
+    loop {
+        let (field, c_value, rust_value) = match (c.peek(), rust.peek()) {
+            (None, None) => return,
+            (Some(a), Some(b)) if a.0 == b.0 => {
+                let (a, b) = (c.next().unwrap(), rust.next().unwrap());
+                if config.values_eq(&a.1, &b.1) {
+                    continue;
+                }
+                (&a.0, Some(&a.1), Some(&b.1))
+            }
+            (Some(a), Some(b)) if a.0 < b.0 => {
+                let a = c.next().unwrap();
+                (&a.0, Some(&a.1), None)
+            }
+            (Some(_), None) => {
+                let a = c.next().unwrap();
+                (&a.0, Some(&a.1), None)
+            }
+            (_, Some(_)) => {
+                let b = rust.next().unwrap();
+                (&b.0, None, Some(&b.1))
+            }
+        };
+        differences.push(Difference::Field {
+            id: id.to_owned(),
+            field: field.clone(),
+            c: c_value.cloned(),
+            rust: rust_value.cloned(),
+        });
+    }
+}
+
+/// The diff of a shadowed query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffRecord {
+    /// Increases with every recorded diff.
+    pub id: u64,
+    pub flag: &'static str,
+    pub context: String,
+    pub differences: Vec<Difference>,
+}
+
+impl Display for DiffRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {} {}:", self.id, self.flag, self.context)?;
+        for (i, difference) in self.differences.iter().enumerate() {
+            let separator = if i == 0 { " " } else { "; " };
+            write!(f, "{separator}{difference}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The diffs of the last shadowed queries that mismatched.
+#[derive(Debug)]
+pub struct DiffLog {
+    capacity: usize,
+    records: Mutex<(VecDeque<DiffRecord>, u64)>,
+}
+
+impl DiffLog {
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: Mutex::new((VecDeque::new(), 0)),
+        }
+    }
+
+    /// Record a diff, evicting the oldest one if the log is full.
+    pub fn record(&self, flag: &'static str, context: &str, differences: Vec<Difference>) {
+        let mut records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        let (records, next_id) = &mut *records;
+        if self.capacity == 0 {
+            return;
+        }
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(DiffRecord {
+            id: *next_id,
+            flag,
+            context: context.to_owned(),
+            differences,
+        });
+        *next_id += 1;
+    }
+
+    /// The recorded diffs, oldest first.
+    pub fn records(&self) -> Vec<DiffRecord> {
+        let records = self.records.lock().unwrap_or_else(|e| e.into_inner());
+        records.0.iter().cloned().collect()
+    }
+
+    /// A line per diff, oldest first.
+    pub fn dump(&self) -> Vec<String> {
+        self.records().iter().map(ToString::to_string).collect()
+    }
+
+    pub fn reset(&self) {
+        self.records
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .0
+            .clear();
+    }
+}
+
+impl Flag {
+    /// Run the query implementation selected by the flag. In
+    /// [`FlagMode::Shadow`], run both, compare their replies with `config`
+    /// and record the differences in `diffs`.
+    pub fn run_reply(
+        &self,
+        context: &str,
+        c: impl FnOnce() -> SearchReply,
+        rust: impl FnOnce() -> SearchReply,
+        config: &DiffConfig,
+        diffs: &DiffLog,
+    ) -> SearchReply {
+        match self.mode() {
+            FlagMode::Off => c(),
+            FlagMode::On => rust(),
+            FlagMode::Shadow => {
+                let (expected, actual) = self.run_both(c, rust);
+                let differences = match actual {
+                    Ok(actual) => expected.diff(&actual, config),
+                    Err(message) => vec![Difference::Panic(message)],
+                };
+                if !differences.is_empty() {
+                    self.mismatches.fetch_add(1, Ordering::Relaxed);
+                    diffs.record(self.name, context, differences);
+                }
+                expected
+            }
+        }
+    }
+}
@@ -0,0 +1,246 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Runtime flags selecting between the C and Rust implementations of the
+//! subsystems being ported, set with `FT.CONFIG SET <flag> on|off|shadow`.
+//!
+//! In [`FlagMode::Shadow`], [`Flag::run`] runs both implementations and
+//! compares their results. The C result is the one used, so that a bug in the
+//! Rust path can't reach clients, and every mismatch is reported with the
+//! context of the query to the caller's logger. A panic of the Rust path is
+//! reported as a mismatch too, as long as the module unwinds on panic.
+//!
+//! Query replies are compared by [`Flag::run_reply`] instead, which tolerates
+//! the differences expected between the two paths, see [`diff`].
+
+pub mod diff;
+
+use std::{
+    fmt::{self, Debug, Display},
+    panic::{self, AssertUnwindSafe},
+    str::FromStr,
+    sync::atomic::{AtomicU8, AtomicU64, Ordering},
+};
+
+/// Which implementation of a subsystem runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlagMode {
+    /// The C implementation.
+    Off,
+    /// The Rust implementation.
+    On,
+    /// Both, comparing their results and using the C one.
+    Shadow,
+}
+
+impl FlagMode {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::On => "on",
+            Self::Shadow => "shadow",
+        }
+    }
+
+    const fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::On,
+            2 => Self::Shadow,
+            _ => Self::Off,
+        }
+    }
+}
+
+impl FromStr for FlagMode {
+    type Err = FlagError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Off, Self::On, Self::Shadow]
+            .into_iter()
+            .find(|mode| mode.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| FlagError::BadValue(s.to_owned()))
+    }
+}
+
+/// Errors of `FT.CONFIG SET` on a flag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlagError {
+    UnknownFlag(String),
+    BadValue(String),
+}
+
+impl Display for FlagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFlag(name) => write!(f, "Unknown feature flag {name}"),
+            Self::BadValue(value) => {
+                write!(
+                    f,
+                    "Invalid feature flag value `{value}`, expected on, off or shadow"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for FlagError {}
+
+/// A mismatch between the C and Rust results of a shadowed run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch<'a> {
+    pub flag: &'static str,
+    /// What was run, such as the query and the index.
+    pub context: &'a str,
+    /// The C result, formatted with `Debug`.
+    pub c: String,
+    /// The Rust result, formatted with `Debug`, or the panic message.
+    pub rust: String,
+}
+
+impl Display for Mismatch<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} shadow mismatch on {}: C returned {}, Rust returned {}",
+            self.flag, self.context, self.c, self.rust
+        )
+    }
+}
+
+/// The counters of the shadowed runs of a flag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShadowStats {
+    pub runs: u64,
+    pub mismatches: u64,
+}
+
+/// A feature flag, see the [crate](crate) docs.
+#[derive(Debug)]
+pub struct Flag {
+    name: &'static str,
+    mode: AtomicU8,
+    shadow_runs: AtomicU64,
+    mismatches: AtomicU64,
+}
+
+impl Flag {
+    pub const fn new(name: &'static str, mode: FlagMode) -> Self {
+        Self {
+            name,
+            mode: AtomicU8::new(mode as u8),
+            shadow_runs: AtomicU64::new(0),
+            mismatches: AtomicU64::new(0),
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn mode(&self) -> FlagMode {
+        FlagMode::from_u8(self.mode.load(Ordering::Relaxed))
+    }
+
+    pub fn set_mode(&self, mode: FlagMode) {
+        self.mode.store(mode as u8, Ordering::Relaxed);
+    }
+
+    pub fn shadow_stats(&self) -> ShadowStats {
+        ShadowStats {
+            runs: self.shadow_runs.load(Ordering::Relaxed),
+            mismatches: self.mismatches.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Run the implementation selected by the flag. In
+    /// [`FlagMode::Shadow`], run both and pass mismatches to `log`.
+    pub fn run<T: PartialEq + Debug>(
+        &self,
+        context: &str,
+        c: impl FnOnce() -> T,
+        rust: impl FnOnce() -> T,
+        log: impl FnOnce(&Mismatch<'_>),
+    ) -> T {
+        match self.mode() {
+            FlagMode::Off => c(),
+            FlagMode::On => rust(),
+            FlagMode::Shadow => {
+                let (expected, actual) = self.run_both(c, rust);
+                let rust = match actual {
+                    Ok(actual) if actual == expected => return expected,
+                    Ok(actual) => format!("{actual:?}"),
+                    Err(message) => format!("a panic: {message}"),
+                };
+                self.mismatches.fetch_add(1, Ordering::Relaxed);
+                log(&Mismatch {
+                    flag: self.name,
+                    context,
+                    c: format!("{expected:?}"),
+                    rust,
+                });
+                expected
+            }
+        }
+    }
+
+    /// Run both implementations for a shadowed run, returning the C result
+    /// and the Rust result or its panic message.
+    fn run_both<T>(
+        &self,
+        c: impl FnOnce() -> T,
+        rust: impl FnOnce() -> T,
+    ) -> (T, Result<T, String>) {
+        let expected = c();
+        let actual = panic::catch_unwind(AssertUnwindSafe(rust)).map_err(|payload| {
+            payload
+                .downcast_ref::<&str>()
+                .map(|s| (*s).to_owned())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown payload".to_owned())
+        });
+        self.shadow_runs.fetch_add(1, Ordering::Relaxed);
+        (expected, actual)
+    }
+}
+
+/// Whether queries run on the Rust query path.
+pub static RUST_QUERY_PATH: Flag = Flag::new("RUST_QUERY_PATH", FlagMode::Off);
+
+/// Whether documents are indexed by the Rust indexer.
+pub static RUST_INDEXER: Flag = Flag::new("RUST_INDEXER", FlagMode::Off);
+
+/// The known flags, for `FT.CONFIG`.
+pub static FLAGS: [&Flag; 2] = [&RUST_QUERY_PATH, &RUST_INDEXER];
+
+/// The flag with the given configuration name, ignoring case.
+pub fn find(name: &str) -> Option<&'static Flag> {
+    FLAGS
+        .iter()
+        .copied()
+        .find(|flag| flag.name.eq_ignore_ascii_case(name))
+}
+
+/// `FT.CONFIG SET <name> <value>`.
+///
+/// # Errors
+///
+/// Returns [`FlagError`] if the flag or the value is unknown.
+pub fn set(name: &str, value: &str) -> Result<(), FlagError> {
+    let flag = find(name).ok_or_else(|| FlagError::UnknownFlag(name.to_owned()))?;
+    flag.set_mode(value.parse()?);
+    Ok(())
+}
+
+/// `FT.CONFIG GET` of the flags.
+pub fn list() -> Vec<(&'static str, &'static str)> {
+    FLAGS
+        .iter()
+        .map(|flag| (flag.name, flag.mode().name()))
+        .collect()
+}
@@ -0,0 +1,138 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use feature_flags::{
+    Flag, FlagMode,
+    diff::{DiffConfig, DiffLog, Difference, Row, SearchReply, Value},
+};
+
+fn row(id: &str, score: f64, fields: &[(&str, Value)]) -> Row {
+    Row {
+        id: id.to_owned(),
+        score: Some(score),
+        fields: fields
+            .iter()
+            .map(|(name, value)| ((*name).to_owned(), value.clone()))
+            .collect(),
+    }
+}
+
+const fn reply(rows: Vec<Row>) -> SearchReply {
+    SearchReply {
+        total: rows.len() as u64,
+        rows,
+    }
+}
+
+#[test]
+fn test_equivalent_replies() {
+    let config = DiffConfig::default();
+    let c = reply(vec![
+        row("a", 2.0, &[("x", Value::Number(1.0)), ("y", Value::Null)]),
+        row("b", 1.0, &[]),
+        row("c", 1.0, &[]),
+    ]);
+    // Tied results in another order, fields in another order, and a score
+    // within tolerance.
+    let rust = reply(vec![
+        row(
+            "a",
+            2.0 + 1e-12,
+            &[("y", Value::Null), ("x", Value::Number(1.0))],
+        ),
+        row("c", 1.0, &[]),
+        row("b", 1.0, &[]),
+    ]);
+    assert_eq!(c.diff(&rust, &config), []);
+
+    let strict = DiffConfig {
+        ignore_tie_order: false,
+        ..config
+    };
+    assert_eq!(
+        c.diff(&rust, &strict),
+        [Difference::Order {
+            position: 1,
+            c: "b".to_owned(),
+            rust: "c".to_owned()
+        }]
+    );
+}
+
+#[test]
+fn test_classified_differences() {
+    let c = reply(vec![
+        row("a", 3.0, &[("x", Value::String("1".to_owned()))]),
+        row("b", 2.0, &[]),
+        row("c", 1.0, &[]),
+    ]);
+    let rust = reply(vec![
+        row("c", 3.0, &[]),
+        row("a", 1.5, &[("y", Value::Null)]),
+    ]);
+    let differences = c.diff(&rust, &DiffConfig::default());
+    let classes: Vec<_> = differences.iter().map(Difference::class).collect();
+    assert_eq!(
+        classes,
+        [
+            "total", "missing", "order", "score", "field", "field", "score"
+        ]
+    );
+    assert_eq!(differences[1], Difference::Missing("b".to_owned()));
+    assert_eq!(
+        differences[4].to_string(),
+        "field a.x C=Some(String(\"1\")) Rust=None"
+    );
+}
+
+#[test]
+fn test_diff_log() {
+    let log = DiffLog::new(2);
+    for i in 0..3 {
+        log.record(
+            "RUST_QUERY_PATH",
+            &format!("query {i}"),
+            vec![Difference::Extra(format!("doc{i}"))],
+        );
+    }
+    assert_eq!(
+        log.dump(),
+        [
+            "#1 RUST_QUERY_PATH query 1: extra doc1",
+            "#2 RUST_QUERY_PATH query 2: extra doc2"
+        ]
+    );
+    log.reset();
+    assert!(log.dump().is_empty());
+}
+
+#[test]
+fn test_run_reply() {
+    let flag = Flag::new("TEST", FlagMode::Shadow);
+    let log = DiffLog::new(10);
+    let config = DiffConfig::default();
+    let c = || reply(vec![row("a", 1.0, &[])]);
+
+    let result = flag.run_reply("q1", c, c, &config, &log);
+    assert_eq!(result, c());
+    let result = flag.run_reply("q2", c, || reply(Vec::new()), &config, &log);
+    assert_eq!(result, c());
+    let result = flag.run_reply("q3", c, || panic!("boom"), &config, &log);
+    assert_eq!(result, c());
+
+    assert_eq!(
+        log.dump(),
+        [
+            "#0 TEST q2: total C=1 Rust=0; missing a",
+            "#1 TEST q3: panic: boom"
+        ]
+    );
+    assert_eq!(flag.shadow_stats().runs, 3);
+    assert_eq!(flag.shadow_stats().mismatches, 2);
+}
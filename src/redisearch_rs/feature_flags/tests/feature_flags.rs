@@ -0,0 +1,88 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use feature_flags::{Flag, FlagError, FlagMode, RUST_INDEXER, ShadowStats};
+
+fn run(flag: &Flag, c: u32, rust: u32) -> (u32, Vec<String>) {
+    let mut logged = Vec::new();
+    let result = flag.run(
+        "FT.SEARCH idx hello",
+        || c,
+        || rust,
+        |m| logged.push(m.to_string()),
+    );
+    (result, logged)
+}
+
+#[test]
+fn test_modes() {
+    let flag = Flag::new("TEST", FlagMode::Off);
+    assert_eq!(run(&flag, 1, 2), (1, vec![]));
+    flag.set_mode(FlagMode::On);
+    assert_eq!(run(&flag, 1, 2), (2, vec![]));
+    assert_eq!(flag.shadow_stats(), ShadowStats::default());
+}
+
+#[test]
+fn test_shadow_mode() {
+    let flag = Flag::new("TEST", FlagMode::Shadow);
+    assert_eq!(run(&flag, 1, 1), (1, vec![]));
+    assert_eq!(
+        run(&flag, 1, 2),
+        (
+            1,
+            vec![
+                "TEST shadow mismatch on FT.SEARCH idx hello: C returned 1, Rust returned 2"
+                    .to_owned()
+            ]
+        )
+    );
+    assert_eq!(
+        flag.shadow_stats(),
+        ShadowStats {
+            runs: 2,
+            mismatches: 1
+        }
+    );
+}
+
+#[test]
+fn test_shadow_panic() {
+    let flag = Flag::new("TEST", FlagMode::Shadow);
+    let mut logged = None;
+    let result = flag.run(
+        "ctx",
+        || vec![1],
+        || panic!("not ported"),
+        |m| logged = Some(m.rust.clone()),
+    );
+    assert_eq!(result, vec![1]);
+    assert_eq!(logged.as_deref(), Some("a panic: not ported"));
+}
+
+#[test]
+fn test_config() {
+    assert_eq!("Shadow".parse(), Ok(FlagMode::Shadow));
+    feature_flags::set("rust_indexer", "shadow").unwrap();
+    assert_eq!(RUST_INDEXER.mode(), FlagMode::Shadow);
+    assert_eq!(
+        feature_flags::list(),
+        [("RUST_QUERY_PATH", "off"), ("RUST_INDEXER", "shadow")]
+    );
+    assert_eq!(
+        feature_flags::set("RUST_GC", "on"),
+        Err(FlagError::UnknownFlag("RUST_GC".to_owned()))
+    );
+    assert_eq!(
+        feature_flags::set("RUST_INDEXER", "maybe")
+            .unwrap_err()
+            .to_string(),
+        "Invalid feature flag value `maybe`, expected on, off or shadow"
+    );
+}
@@ -8,13 +8,15 @@
 */
 
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use build_utils::{git_root, rerun_if_c_changes};
 
-fn main() {
-    let root = git_root().expect("Could not find git root for static library linking");
+/// The files generated by `bindgen`, also found in `bindings/` when checked in.
+const GENERATED: [&str; 2] = ["bindings.rs", "mirrors.rs"];
 
+fn main() {
     // Construct the correct folder path based on OS and architecture
     let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap();
 
@@ -30,6 +32,45 @@ fn main() {
         println!("cargo:rustc-link-arg=-Wl,--unresolved-symbols=ignore-in-object-files");
     }
 
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let checked_in = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("bindings");
+
+    if cfg!(feature = "checked-in-bindings") {
+        copy_checked_in(&checked_in, &out_dir);
+    } else {
+        generate(&out_dir);
+        // Refresh the checked-in bindings with `UPDATE_BINDINGS=1`.
+        println!("cargo:rerun-if-env-changed=UPDATE_BINDINGS");
+        if env::var_os("UPDATE_BINDINGS").is_some() {
+            fs::create_dir_all(&checked_in).expect("Couldn't create the checked-in bindings dir");
+            for file in GENERATED {
+                fs::copy(out_dir.join(file), checked_in.join(file))
+                    .expect("Couldn't check in the bindings");
+            }
+        }
+    }
+}
+
+/// Use the bindings checked in `bindings/` instead of running `bindgen`, e.g.
+/// where `libclang` or the C headers aren't available.
+fn copy_checked_in(checked_in: &Path, out_dir: &Path) {
+    for file in GENERATED {
+        let path = checked_in.join(file);
+        println!("cargo:rerun-if-changed={}", path.display());
+        fs::copy(&path, out_dir.join(file)).unwrap_or_else(|e| {
+            panic!(
+                "Couldn't read the checked-in bindings {}: {e}. \
+                 Generate them with `UPDATE_BINDINGS=1` and the `checked-in-bindings` feature off.",
+                path.display()
+            )
+        });
+    }
+}
+
+/// Generate the bindings, and the layouts of the mirrored types, with `bindgen`.
+fn generate(out_dir: &Path) {
+    let root = git_root().expect("Could not find git root for static library linking");
+
     let includes = {
         let redis_modules = root.join("deps").join("RedisModulesSDK");
         let src = root.join("src");
@@ -70,27 +111,78 @@ fn main() {
         root.join("src").join("util").join("arr").join("arr.h"),
     ];
 
-    let mut bindings = bindgen::Builder::default();
-
-    for header in headers {
-        bindings = bindings
-            .header(header.display().to_string())
-            .allowlist_file(header.display().to_string());
+    let mut parser = bindgen::Builder::default();
+    for header in &headers {
+        parser = parser.header(header.display().to_string());
 
         println!("cargo:rerun-if-changed={}", header.display());
     }
     for include in includes {
-        bindings = bindings.clang_arg(format!("-I{}", include.display()));
+        parser = parser.clang_arg(format!("-I{}", include.display()));
         // Re-run the build script if any of the C files in the included
         // directory changes
         let _ = rerun_if_c_changes(&include);
     }
 
-    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let mut bindings = parser.clone();
+    for header in &headers {
+        bindings = bindings.allowlist_file(header.display().to_string());
+    }
+
+    // The C structs mirrored by hand in `src/lib.rs` are left out of the
+    // bindings, and generated on their own instead, so that the layouts of
+    // the mirrors can be checked against them.
+    let mut mirrors = parser.allowlist_recursively(false);
+    for mirrored in mirrored_types() {
+        bindings = bindings.blocklist_type(&mirrored);
+        mirrors = mirrors.allowlist_type(&mirrored);
+    }
+
     bindings
         .allowlist_file(".*/types_rs.h")
         .generate()
         .expect("Unable to generate bindings")
         .write_to_file(out_dir.join("bindings.rs"))
         .expect("Couldn't write bindings!");
+
+    mirrors
+        .generate()
+        .expect("Unable to generate the layouts of the mirrored types")
+        .write_to_file(out_dir.join("mirrors.rs"))
+        .expect("Couldn't write the layouts of the mirrored types!");
+}
+
+/// The C structs with a hand-written mirror in `src/lib.rs`: every
+/// `#[repr(C)]` struct declared there.
+///
+/// They're found by scanning the source rather than listed by hand, so that a
+/// new mirror can't be forgotten: it would otherwise clash with the generated
+/// definition, or escape the layout checks.
+fn mirrored_types() -> Vec<String> {
+    let lib = Path::new("src").join("lib.rs");
+    println!("cargo:rerun-if-changed={}", lib.display());
+    let source = fs::read_to_string(&lib).expect("Couldn't read src/lib.rs");
+
+    let mut mirrored = Vec::new();
+    let mut lines = source.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        if line != "#[repr(C)]" {
+            continue;
+        }
+        // Skip the other attributes, up to the item itself.
+        let item = lines.by_ref().find(|line| !line.starts_with("#["));
+        let name = item
+            .and_then(|item| item.strip_prefix("pub struct "))
+            .and_then(|rest| {
+                rest.split(|c: char| !c.is_alphanumeric() && c != '_')
+                    .next()
+            });
+        match name {
+            Some(name) => mirrored.push(name.to_owned()),
+            None => {
+                panic!("`#[repr(C)]` in src/lib.rs must be on a `pub struct` mirroring a C struct")
+            }
+        }
+    }
+    mirrored
 }
@@ -32,6 +32,40 @@ use std::{cell::UnsafeCell, pin::Pin, ptr};
 
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 
+/// The C structs mirrored by hand in this crate, as generated by `bindgen`.
+/// They are only used to check the layouts of the mirrors.
+mod c_layout {
+    use super::*;
+
+    include!(concat!(env!("OUT_DIR"), "/mirrors.rs"));
+}
+
+/// Check at compile time that the hand-written mirror `$mirror` of the C
+/// struct `$c` lists all of its `$fields`, at the same offsets, and has the
+/// same size and alignment.
+macro_rules! assert_same_layout {
+    ($mirror:ident, [$($field:ident),* $(,)?]) => {
+        const _: () = {
+            use std::mem::{align_of, offset_of, size_of};
+
+            // Fails to compile if the C struct has fields missing from the
+            // list.
+            fn exhaustive(c: &c_layout::$mirror) {
+                let c_layout::$mirror { $($field: _),* } = c;
+            }
+
+            assert!(size_of::<$mirror>() == size_of::<c_layout::$mirror>());
+            assert!(align_of::<$mirror>() == align_of::<c_layout::$mirror>());
+            $(
+                assert!(
+                    offset_of!($mirror, $field) == offset_of!(c_layout::$mirror, $field),
+                    concat!("mismatched offset of ", stringify!($mirror), "::", stringify!($field)),
+                );
+            )*
+        };
+    };
+}
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct QueryProcessingCtx {
@@ -43,10 +77,28 @@ pub struct QueryProcessingCtx {
     pub totalResults: u32,
     pub resultLimit: u32,
     pub err: *mut QueryError,
+    pub bgScanOOM: bool,
     pub isProfile: bool,
     pub timeoutPolicy: RSTimeoutPolicy,
 }
 
+assert_same_layout!(
+    QueryProcessingCtx,
+    [
+        rootProc,
+        endProc,
+        initTime,
+        GILTime,
+        minScore,
+        totalResults,
+        resultLimit,
+        err,
+        bgScanOOM,
+        isProfile,
+        timeoutPolicy,
+    ]
+);
+
 impl QueryProcessingCtx {
     pub fn new() -> Pin<Box<Self>> {
         let ctx = Self {
@@ -61,6 +113,7 @@ impl QueryProcessingCtx {
             totalResults: 0,
             resultLimit: 0,
             err: ptr::null_mut(),
+            bgScanOOM: false,
             isProfile: false,
             timeoutPolicy: 0,
         };
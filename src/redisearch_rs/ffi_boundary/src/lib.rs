@@ -0,0 +1,173 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Panic containment for `extern "C"` entry points.
+//!
+//! A panic unwinding out of an `extern "C"` function aborts the process, which
+//! for us means taking down the whole Redis server. Entry points wrap their
+//! body in [`ffi_boundary!`]: a panic is caught, [logged](set_logger) together
+//! with the backtrace captured at the panic site, and turned into an error
+//! value the C caller already knows how to handle (typically a
+//! `QueryErrorCode::Generic` query error and a NULL or sentinel return value).
+//!
+//! ```
+//! use ffi_boundary::ffi_boundary;
+//!
+//! #[unsafe(no_mangle)]
+//! pub extern "C" fn Example_Divide(a: u32, b: u32) -> i64 {
+//!     ffi_boundary!("Example_Divide", |_report| -1, {
+//!         i64::from(a / b)
+//!     })
+//! }
+//!
+//! # ffi_boundary::set_logger(|_| {});
+//! assert_eq!(Example_Divide(6, 3), 2);
+//! assert_eq!(Example_Divide(6, 0), -1);
+//! ```
+
+use std::{
+    backtrace::Backtrace,
+    cell::{Cell, RefCell},
+    ffi::CString,
+    fmt::{self, Display},
+    panic::{self, AssertUnwindSafe, PanicHookInfo},
+    sync::{Once, RwLock},
+};
+
+/// Run `$body` and, should it panic, evaluate to `$on_panic(report)` instead,
+/// where `report` is the [`PanicReport`] describing the panic.
+///
+/// `$entry` names the entry point in the log.
+#[macro_export]
+macro_rules! ffi_boundary {
+    ($entry:expr, $on_panic:expr, $body:block) => {
+        $crate::catch($entry, || $body).unwrap_or_else($on_panic)
+    };
+}
+
+/// A panic caught at an FFI boundary.
+#[derive(Debug, Clone)]
+pub struct PanicReport {
+    /// The entry point the panic was caught in.
+    pub entry: &'static str,
+    /// The panic message, if the payload was a string.
+    pub message: String,
+    /// Where the panic happened, as `file:line:column`.
+    pub location: Option<String>,
+    /// The backtrace captured when the panic happened.
+    pub backtrace: String,
+}
+
+impl PanicReport {
+    /// The report as an error message for a C string field, e.g. a query
+    /// error detail.
+    pub fn to_c_string(&self) -> CString {
+        // The message can't contain NUL bytes once they've been replaced.
+        CString::new(self.to_string().replace('\0', "\\0")).unwrap_or_default()
+    }
+}
+
+impl Display for PanicReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Internal error in {}: {}", self.entry, self.message)
+    }
+}
+
+/// The function panic reports are logged with.
+pub type Logger = fn(&PanicReport);
+
+static LOGGER: RwLock<Logger> = RwLock::new(log_to_stderr);
+
+/// Replace the function panic reports are logged with. The module sets it at
+/// load time to log through `RedisModule_Log`.
+pub fn set_logger(logger: Logger) {
+    *LOGGER.write().unwrap_or_else(|e| e.into_inner()) = logger;
+}
+
+fn log_to_stderr(report: &PanicReport) {
+    eprintln!(
+        "{report} at {}\n{}",
+        report.location.as_deref().unwrap_or("<unknown>"),
+        report.backtrace
+    );
+}
+
+/// What the panic hook records for the innermost boundary of the thread.
+struct Captured {
+    message: String,
+    location: Option<String>,
+    backtrace: String,
+}
+
+thread_local! {
+    /// The number of nested boundaries on this thread.
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    static CAPTURED: RefCell<Option<Captured>> = const { RefCell::new(None) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+/// Chain a panic hook that captures the backtrace of panics happening within
+/// a boundary, leaving other panics to the previous hook.
+fn install_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if DEPTH.get() == 0 {
+                previous(info);
+                return;
+            }
+            let captured = Captured {
+                message: payload_message(info),
+                location: info.location().map(ToString::to_string),
+                backtrace: Backtrace::force_capture().to_string(),
+            };
+            CAPTURED.set(Some(captured));
+        }));
+    });
+}
+
+fn payload_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = info.payload();
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_owned()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "<non-string panic payload>".to_owned()
+    }
+}
+
+/// Run `f`, catching any panic. Prefer the [`ffi_boundary!`] macro.
+///
+/// The caught panic is logged before being returned.
+pub fn catch<R>(entry: &'static str, f: impl FnOnce() -> R) -> Result<R, PanicReport> {
+    install_hook();
+    DEPTH.set(DEPTH.get() + 1);
+    // The state `f` may have left half-modified is not observed again by
+    // Rust code: the C caller gets an error and is expected to drop it.
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    DEPTH.set(DEPTH.get() - 1);
+
+    result.map_err(|_| {
+        let captured = CAPTURED.take().unwrap_or_else(|| Captured {
+            message: "<unknown panic>".to_owned(),
+            location: None,
+            backtrace: String::new(),
+        });
+        let report = PanicReport {
+            entry,
+            message: captured.message,
+            location: captured.location,
+            backtrace: captured.backtrace,
+        };
+        (LOGGER.read().unwrap_or_else(|e| e.into_inner()))(&report);
+        report
+    })
+}
@@ -0,0 +1,122 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::sync::Mutex;
+
+use ffi_boundary::{PanicReport, catch, ffi_boundary, set_logger};
+
+/// The entry points logged so far, across all the tests of this file.
+static LOGGED: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+fn record(report: &PanicReport) {
+    LOGGED.lock().unwrap().push(report.entry);
+}
+
+fn setup() {
+    set_logger(record);
+}
+
+// Stand-ins for the entry points of each subsystem, panicking the way their
+// internals would on a broken invariant.
+
+extern "C" fn varint_entry(len: usize) -> usize {
+    ffi_boundary!("VVW_Write", |_| 0, {
+        let buf = [0u8; 4];
+        buf[..len].len()
+    })
+}
+
+extern "C" fn trie_entry(depth: u32) -> *const u8 {
+    ffi_boundary!("TrieMap_Add", |_| std::ptr::null(), {
+        assert!(depth < 8, "trie too deep: {depth}");
+        c"ok".as_ptr().cast()
+    })
+}
+
+extern "C" fn inverted_index_entry(flags: u32) -> i32 {
+    ffi_boundary!("NewInvertedIndex_Ex", |_| -1, {
+        match flags {
+            0 => unreachable!("no encoding for flags {flags:#x}"),
+            _ => 1,
+        }
+    })
+}
+
+extern "C" fn query_error_entry(code: u8) -> u8 {
+    ffi_boundary!("QueryError_SetCode", |_| u8::MAX, {
+        let codes: Vec<u8> = (0..4).collect();
+        codes[usize::from(code)]
+    })
+}
+
+#[test]
+fn test_no_panic_passes_through() {
+    setup();
+    assert_eq!(varint_entry(3), 3);
+    assert!(!trie_entry(1).is_null());
+    assert_eq!(inverted_index_entry(2), 1);
+    assert_eq!(query_error_entry(3), 3);
+}
+
+#[test]
+fn test_panics_are_contained() {
+    setup();
+    assert_eq!(varint_entry(5), 0);
+    assert!(trie_entry(9).is_null());
+    assert_eq!(inverted_index_entry(0), -1);
+    assert_eq!(query_error_entry(4), u8::MAX);
+
+    let logged = LOGGED.lock().unwrap();
+    for entry in [
+        "VVW_Write",
+        "TrieMap_Add",
+        "NewInvertedIndex_Ex",
+        "QueryError_SetCode",
+    ] {
+        assert!(logged.contains(&entry), "{entry} not logged");
+    }
+}
+
+#[test]
+fn test_report_contents() {
+    setup();
+    let report = catch("FT.TEST", || -> () { panic!("broken invariant {}", 42) }).unwrap_err();
+    assert_eq!(report.entry, "FT.TEST");
+    assert_eq!(report.message, "broken invariant 42");
+    assert!(report.location.as_deref().unwrap().contains("boundary.rs"));
+    assert!(!report.backtrace.is_empty());
+    assert_eq!(
+        report.to_string(),
+        "Internal error in FT.TEST: broken invariant 42"
+    );
+
+    let report = catch("FT.TEST", || std::panic::panic_any(7)).unwrap_err();
+    assert_eq!(report.message, "<non-string panic payload>");
+
+    let report = catch("FT.TEST", || panic!("nul\0byte")).unwrap_err();
+    assert_eq!(
+        report.to_c_string().to_str().unwrap(),
+        "Internal error in FT.TEST: nul\\0byte"
+    );
+}
+
+#[test]
+fn test_nested_boundaries() {
+    setup();
+    let outer = catch("outer", || {
+        let inner = catch("inner", || panic!("inner panic"));
+        assert_eq!(inner.unwrap_err().entry, "inner");
+        panic!("outer panic")
+    });
+    let report = outer.unwrap_err();
+    assert_eq!(report.entry, "outer");
+    assert_eq!(report.message, "outer panic");
+    // The boundary keeps working once a panic has been caught.
+    assert_eq!(catch("after", || 1).unwrap(), 1);
+}
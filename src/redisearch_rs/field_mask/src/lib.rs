@@ -0,0 +1,350 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Masks of the TEXT fields a term occurs in.
+//!
+//! Postings of TEXT fields record which fields of the document the term
+//! occurs in as a bit mask, `t_fieldMask`, which is 128 bits wide on 64-bit
+//! platforms. An index therefore can't have more TEXT fields than that. The
+//! [`FieldMask`] trait abstracts over the representation, so that iterators
+//! and scorers can be written once for:
+//!
+//! - `u128` and `u64`, the fixed masks used today;
+//! - [`WideMask`], a variable-length mask for schemas with more TEXT fields.
+//!
+//! [`Mask`] picks between the two at runtime, from the number of TEXT fields
+//! of the schema, so that indexes within the limit keep the compact
+//! representation.
+
+use std::fmt::Debug;
+
+/// A set of field indices.
+pub trait FieldMask: Clone + Eq + Debug {
+    /// The number of fields the mask can hold, or `None` if unbounded.
+    const CAPACITY: Option<usize>;
+
+    /// The mask of no field.
+    fn empty() -> Self;
+
+    /// The mask of every field, `RS_FIELDMASK_ALL`.
+    fn all() -> Self;
+
+    /// Add the field `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is beyond the capacity of the mask.
+    fn insert(&mut self, index: usize);
+
+    /// Whether the field `index` is in the mask.
+    fn contains(&self, index: usize) -> bool;
+
+    /// Whether the masks have a field in common, which is how records are
+    /// filtered by the fields of a query.
+    fn intersects(&self, other: &Self) -> bool;
+
+    /// Add the fields of `other`, e.g. when merging the records of a union.
+    fn union_with(&mut self, other: &Self);
+
+    /// Keep only the fields also in `other`.
+    fn intersect_with(&mut self, other: &Self);
+
+    fn is_empty(&self) -> bool;
+
+    /// The mask of the single field `index`.
+    fn single(index: usize) -> Self {
+        let mut mask = Self::empty();
+        mask.insert(index);
+        mask
+    }
+}
+
+macro_rules! impl_fixed_mask {
+    ($($int:ty),*) => {$(
+        impl FieldMask for $int {
+            const CAPACITY: Option<usize> = Some(<$int>::BITS as usize);
+
+            fn empty() -> Self {
+                0
+            }
+
+            fn all() -> Self {
+                <$int>::MAX
+            }
+
+            fn insert(&mut self, index: usize) {
+                assert!(index < <$int>::BITS as usize, "field index {index} out of range");
+                *self |= 1 << index;
+            }
+
+            fn contains(&self, index: usize) -> bool {
+                index < <$int>::BITS as usize && self & (1 << index) != 0
+            }
+
+            fn intersects(&self, other: &Self) -> bool {
+                self & other != 0
+            }
+
+            fn union_with(&mut self, other: &Self) {
+                *self |= other;
+            }
+
+            fn intersect_with(&mut self, other: &Self) {
+                *self &= other;
+            }
+
+            fn is_empty(&self) -> bool {
+                *self == 0
+            }
+        }
+    )*};
+}
+
+impl_fixed_mask!(u64, u128);
+
+/// A mask of any number of fields.
+///
+/// The fields beyond the stored words all have the same state, `rest`, so
+/// that [`FieldMask::all`] doesn't need to know the number of fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WideMask {
+    words: Vec<u64>,
+    rest: bool,
+}
+
+impl WideMask {
+    const fn rest_word(&self) -> u64 {
+        if self.rest { u64::MAX } else { 0 }
+    }
+
+    /// The word holding the fields `64 * i..64 * (i + 1)`.
+    fn word(&self, i: usize) -> u64 {
+        self.words.get(i).copied().unwrap_or(self.rest_word())
+    }
+
+    /// Combine the words of both masks with `op`.
+    fn combine(&mut self, other: &Self, op: impl Fn(u64, u64) -> u64, rest: bool) {
+        let len = self.words.len().max(other.words.len());
+        let fill = self.rest_word();
+        self.words.resize(len, fill);
+        for (i, word) in self.words.iter_mut().enumerate() {
+            *word = op(*word, other.word(i));
+        }
+        self.rest = rest;
+        self.trim();
+    }
+
+    /// Drop the trailing words equal to `rest`, so that equal masks have the
+    /// same representation.
+    fn trim(&mut self) {
+        let fill = self.rest_word();
+        while self.words.last() == Some(&fill) {
+            self.words.pop();
+        }
+    }
+
+    /// The indices of the fields in the mask, in increasing order. For a mask
+    /// with every field beyond some index, only those below `limit` are
+    /// returned.
+    pub fn iter(&self, limit: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..limit).filter(|&i| self.contains(i))
+    }
+}
+
+impl FieldMask for WideMask {
+    const CAPACITY: Option<usize> = None;
+
+    fn empty() -> Self {
+        Self {
+            words: Vec::new(),
+            rest: false,
+        }
+    }
+
+    fn all() -> Self {
+        Self {
+            words: Vec::new(),
+            rest: true,
+        }
+    }
+
+    fn insert(&mut self, index: usize) {
+        let (word, bit) = (index / 64, index % 64);
+        if self.words.len() <= word {
+            let fill = self.rest_word();
+            self.words.resize(word + 1, fill);
+        }
+        self.words[word] |= 1 << bit;
+        self.trim();
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        self.word(index / 64) & (1 << (index % 64)) != 0
+    }
+
+    fn intersects(&self, other: &Self) -> bool {
+        let len = self.words.len().max(other.words.len());
+        (self.rest && other.rest) || (0..len).any(|i| self.word(i) & other.word(i) != 0)
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        let rest = self.rest || other.rest;
+        self.combine(other, |a, b| a | b, rest);
+    }
+
+    fn intersect_with(&mut self, other: &Self) {
+        let rest = self.rest && other.rest;
+        self.combine(other, |a, b| a & b, rest);
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.rest && self.words.is_empty()
+    }
+}
+
+/// The mask of an index: a `u128` while the schema fits, a [`WideMask`]
+/// beyond. Masks of the same fields are equal whatever their representation.
+#[derive(Debug, Clone)]
+pub enum Mask {
+    Fixed(u128),
+    Wide(WideMask),
+    /// Every field, `RS_FIELDMASK_ALL`, whatever the width of the schema.
+    ///
+    /// Unlike `Fixed(u128::MAX)`, which is the first 128 fields only, this
+    /// also holds the fields of a wide schema.
+    All,
+}
+
+impl Mask {
+    /// Whether an index with `text_fields` TEXT fields needs wide masks.
+    pub const fn needs_wide(text_fields: usize) -> bool {
+        text_fields > u128::BITS as usize
+    }
+
+    /// The empty mask of the representation used by an index with
+    /// `text_fields` TEXT fields.
+    pub fn empty_for(text_fields: usize) -> Self {
+        if Self::needs_wide(text_fields) {
+            Self::Wide(WideMask::empty())
+        } else {
+            Self::Fixed(0)
+        }
+    }
+
+    /// Apply `op` to both masks, as wide masks if either is.
+    fn combine(&mut self, other: &Self, op: impl Fn(&mut WideMask, &WideMask)) {
+        match (&mut *self, other) {
+            (Self::Wide(a), Self::Wide(b)) => op(a, b),
+            (Self::Wide(a), _) => op(a, &other.to_wide()),
+            (Self::Fixed(_) | Self::All, _) => {
+                let mut wide = self.to_wide();
+                op(&mut wide, &other.to_wide());
+                *self = Self::Wide(wide);
+            }
+        }
+    }
+
+    /// The mask as a [`WideMask`].
+    pub fn to_wide(&self) -> WideMask {
+        match self {
+            Self::Fixed(mask) => widen(*mask),
+            Self::Wide(mask) => mask.clone(),
+            Self::All => WideMask::all(),
+        }
+    }
+}
+
+/// The [`WideMask`] of the same fields as `mask`.
+fn widen(mask: u128) -> WideMask {
+    let mut wide = WideMask {
+        words: vec![mask as u64, (mask >> 64) as u64],
+        rest: false,
+    };
+    wide.trim();
+    wide
+}
+
+impl PartialEq for Mask {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Fixed(a), Self::Fixed(b)) => a == b,
+            _ => self.to_wide() == other.to_wide(),
+        }
+    }
+}
+
+impl Eq for Mask {}
+
+impl FieldMask for Mask {
+    const CAPACITY: Option<usize> = None;
+
+    fn empty() -> Self {
+        Self::Fixed(0)
+    }
+
+    fn all() -> Self {
+        Self::All
+    }
+
+    /// Switches to a [`WideMask`] if `index` doesn't fit in a `u128`.
+    fn insert(&mut self, index: usize) {
+        match self {
+            Self::All => {}
+            Self::Fixed(mask) if index < u128::BITS as usize => mask.insert(index),
+            Self::Fixed(mask) => {
+                let mut wide = widen(*mask);
+                wide.insert(index);
+                *self = Self::Wide(wide);
+            }
+            Self::Wide(mask) => mask.insert(index),
+        }
+    }
+
+    fn contains(&self, index: usize) -> bool {
+        match self {
+            Self::Fixed(mask) => mask.contains(index),
+            Self::Wide(mask) => mask.contains(index),
+            Self::All => true,
+        }
+    }
+
+    fn intersects(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Fixed(a), Self::Fixed(b)) => a.intersects(b),
+            (Self::All, mask) | (mask, Self::All) => !mask.is_empty(),
+            _ => self.to_wide().intersects(&other.to_wide()),
+        }
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        match (&mut *self, other) {
+            (Self::Fixed(a), Self::Fixed(b)) => a.union_with(b),
+            (Self::All, _) => {}
+            (_, Self::All) => *self = Self::All,
+            _ => self.combine(other, WideMask::union_with),
+        }
+    }
+
+    fn intersect_with(&mut self, other: &Self) {
+        match (&mut *self, other) {
+            (Self::Fixed(a), Self::Fixed(b)) => a.intersect_with(b),
+            (_, Self::All) => {}
+            (Self::All, _) => *self = other.clone(),
+            _ => self.combine(other, WideMask::intersect_with),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::Fixed(mask) => *mask == 0,
+            Self::Wide(mask) => mask.is_empty(),
+            Self::All => false,
+        }
+    }
+}
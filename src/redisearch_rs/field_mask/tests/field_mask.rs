@@ -0,0 +1,106 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use field_mask::{FieldMask, Mask, WideMask};
+
+/// The fields of `mask` below `limit`, through the generic interface.
+fn fields<M: FieldMask>(mask: &M, limit: usize) -> Vec<usize> {
+    (0..limit).filter(|&i| mask.contains(i)).collect()
+}
+
+/// Exercise a mask type through the generic interface, as iterators and
+/// scorers do.
+fn check_generic<M: FieldMask>(high: usize) {
+    let mut title = M::single(0);
+    title.insert(high);
+    let body = M::single(1);
+    assert!(!title.intersects(&body));
+    assert!(title.intersects(&M::all()));
+    assert!(!M::empty().intersects(&M::all()));
+
+    let mut both = title.clone();
+    both.union_with(&body);
+    assert_eq!(fields(&both, high + 1), [0, 1, high]);
+
+    both.intersect_with(&body);
+    assert_eq!(both, body);
+    both.intersect_with(&M::empty());
+    assert!(both.is_empty());
+    assert!(M::empty().is_empty());
+    assert!(!M::all().is_empty());
+}
+
+#[test]
+fn test_fixed() {
+    check_generic::<u64>(63);
+    check_generic::<u128>(127);
+    assert_eq!(<u128 as FieldMask>::CAPACITY, Some(128));
+    assert!(!FieldMask::contains(&u64::MAX, 64));
+}
+
+#[test]
+#[should_panic(expected = "field index 128 out of range")]
+fn test_fixed_overflow() {
+    FieldMask::insert(&mut 0u128, 128);
+}
+
+#[test]
+fn test_wide() {
+    check_generic::<WideMask>(1000);
+    assert_eq!(WideMask::CAPACITY, None);
+
+    // Every field, however many there are.
+    assert!(WideMask::all().contains(5000));
+    let mut all_but = WideMask::all();
+    all_but.intersect_with(&WideMask::single(3));
+    assert_eq!(all_but, WideMask::single(3));
+
+    let mut mask = WideMask::single(200);
+    mask.union_with(&WideMask::single(7));
+    assert_eq!(mask.iter(300).collect::<Vec<_>>(), [7, 200]);
+}
+
+#[test]
+fn test_mask() {
+    check_generic::<Mask>(500);
+
+    assert!(!Mask::needs_wide(128));
+    assert!(Mask::needs_wide(129));
+    assert_eq!(Mask::empty_for(10), Mask::Fixed(0));
+    assert_eq!(Mask::empty_for(200), Mask::Wide(WideMask::empty()));
+
+    // The representation switches when a field doesn't fit.
+    let mut mask = Mask::single(3);
+    assert!(matches!(mask, Mask::Fixed(8)));
+    mask.insert(130);
+    assert!(matches!(mask, Mask::Wide(_)));
+    assert_eq!(fields(&mask, 200), [3, 130]);
+
+    // Equality doesn't depend on the representation.
+    assert_eq!(Mask::Wide(WideMask::single(3)), Mask::Fixed(8));
+    assert_eq!(Mask::Wide(WideMask::all()), Mask::all());
+
+    // `RS_FIELDMASK_ALL` covers the fields beyond 128 too.
+    let mut wide = Mask::single(300);
+    wide.intersect_with(&Mask::all());
+    assert_eq!(fields(&wide, 400), [300]);
+    assert!(Mask::all().contains(300));
+
+    // Whereas a mask of the first 128 fields is just that.
+    let mut first = Mask::Fixed(u128::MAX);
+    assert!(first.contains(127));
+    assert!(!first.contains(128));
+    assert_ne!(first, Mask::all());
+    first.union_with(&Mask::single(200));
+    assert_eq!(fields(&first, 300).len(), 129);
+    assert!(!first.contains(250));
+    let mut all = Mask::all();
+    all.intersect_with(&Mask::Fixed(u128::MAX));
+    assert_eq!(all, Mask::Fixed(u128::MAX));
+}
@@ -0,0 +1,376 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Statistics of the garbage collector, for `FT.DEBUG GC_STATS`, `FT.INFO`
+//! and `INFO MODULES`.
+//!
+//! Besides the totals kept by the C fork GC (`bytes_collected`,
+//! `total_cycles`, ...), every run records what it collected per field type
+//! and how long it spent in the forked child vs. holding the index lock in the
+//! parent, which is what tuning the GC frequency of large deployments needs.
+//!
+//! A run in [`RunMode::DryRun`] scans the index as usual, but only reports
+//! what would have been collected: it's kept as the last dry run, and doesn't
+//! count toward the totals.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    time::Duration,
+};
+
+/// The type of the field an inverted index belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FieldType {
+    Text,
+    Numeric,
+    Tag,
+    Geo,
+    Vector,
+}
+
+impl FieldType {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Numeric => "numeric",
+            Self::Tag => "tag",
+            Self::Geo => "geo",
+            Self::Vector => "vector",
+        }
+    }
+}
+
+/// What applying the GC to a single inverted index did, or would do in a dry
+/// run. Mirrors `GcApplyInfo` of the `inverted_index` crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Collected {
+    /// The number of entries removed, including duplicates.
+    pub entries_removed: u64,
+    pub bytes_freed: u64,
+    pub bytes_allocated: u64,
+    /// The blocks left alone because the index changed since it was scanned.
+    pub blocks_ignored: u64,
+}
+
+/// The totals of a run for one field type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FieldStats {
+    /// The number of deleted documents removed from the indexes.
+    pub docs_collected: u64,
+    pub entries_removed: u64,
+    pub bytes_freed: u64,
+    pub bytes_allocated: u64,
+    pub blocks_ignored: u64,
+}
+
+impl FieldStats {
+    /// The bytes actually given back: repaired blocks are reallocated.
+    pub const fn bytes_reclaimed(&self) -> u64 {
+        self.bytes_freed.saturating_sub(self.bytes_allocated)
+    }
+
+    const fn add(&mut self, other: &Self) {
+        self.docs_collected += other.docs_collected;
+        self.entries_removed += other.entries_removed;
+        self.bytes_freed += other.bytes_freed;
+        self.bytes_allocated += other.bytes_allocated;
+        self.blocks_ignored += other.blocks_ignored;
+    }
+}
+
+/// Whether a run collects garbage or only reports what it would collect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RunMode {
+    #[default]
+    Collect,
+    DryRun,
+}
+
+/// The metrics of a single GC run.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RunStats {
+    pub mode: RunMode,
+    per_field: BTreeMap<FieldType, FieldStats>,
+    /// The time spent scanning the indexes in the forked child.
+    pub forked: Duration,
+    /// The time spent applying the results in the parent, under the index
+    /// write lock.
+    pub locked: Duration,
+    /// The numeric trees which changed while they were scanned, and were
+    /// skipped.
+    pub numeric_trees_missed: u64,
+}
+
+impl RunStats {
+    pub fn new(mode: RunMode) -> Self {
+        Self {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    /// Record what was collected from an inverted index of a `field_type`
+    /// field, where `docs` deleted documents were found.
+    pub fn record(&mut self, field_type: FieldType, docs: u64, collected: &Collected) {
+        self.per_field
+            .entry(field_type)
+            .or_default()
+            .add(&FieldStats {
+                docs_collected: docs,
+                entries_removed: collected.entries_removed,
+                bytes_freed: collected.bytes_freed,
+                bytes_allocated: collected.bytes_allocated,
+                blocks_ignored: collected.blocks_ignored,
+            });
+    }
+
+    /// The totals of a field type, zero if nothing of that type was collected.
+    pub fn field(&self, field_type: FieldType) -> FieldStats {
+        self.per_field.get(&field_type).copied().unwrap_or_default()
+    }
+
+    /// The totals of every field type something was collected from.
+    pub fn fields(&self) -> impl Iterator<Item = (FieldType, &FieldStats)> {
+        self.per_field.iter().map(|(&t, s)| (t, s))
+    }
+
+    /// The totals of the run over all field types.
+    pub fn total(&self) -> FieldStats {
+        let mut total = FieldStats::default();
+        for stats in self.per_field.values() {
+            total.add(stats);
+        }
+        total
+    }
+
+    /// The wall time of the run.
+    pub fn duration(&self) -> Duration {
+        self.forked + self.locked
+    }
+
+    /// The detailed reply of the run, in `GC_STATS` and `GC_FORCEINVOKE`.
+    pub fn reply(&self) -> Vec<(String, StatValue)> {
+        let total = self.total();
+        let mut reply = vec![
+            stat("dry_run", self.mode == RunMode::DryRun),
+            stat("docs_collected", total.docs_collected),
+            stat("entries_removed", total.entries_removed),
+            stat("bytes_reclaimed", total.bytes_reclaimed()),
+            stat("blocks_ignored", total.blocks_ignored),
+            stat("numeric_trees_missed", self.numeric_trees_missed),
+            stat("forked_time_ms", millis(self.forked)),
+            stat("locked_time_ms", millis(self.locked)),
+        ];
+        let fields = self
+            .fields()
+            .map(|(field_type, stats)| {
+                let stats = vec![
+                    stat("docs_collected", stats.docs_collected),
+                    stat("entries_removed", stats.entries_removed),
+                    stat("bytes_reclaimed", stats.bytes_reclaimed()),
+                ];
+                (field_type.name().to_owned(), StatValue::Map(stats))
+            })
+            .collect();
+        reply.push(("fields".to_owned(), StatValue::Map(fields)));
+        reply
+    }
+}
+
+/// A value in a statistics reply.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatValue {
+    Integer(i64),
+    Double(f64),
+    Map(Vec<(String, StatValue)>),
+}
+
+impl From<u64> for StatValue {
+    fn from(value: u64) -> Self {
+        Self::Integer(value.try_into().unwrap_or(i64::MAX))
+    }
+}
+
+impl From<i64> for StatValue {
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<bool> for StatValue {
+    fn from(value: bool) -> Self {
+        Self::Integer(value.into())
+    }
+}
+
+impl From<f64> for StatValue {
+    fn from(value: f64) -> Self {
+        Self::Double(value)
+    }
+}
+
+fn stat(name: &str, value: impl Into<StatValue>) -> (String, StatValue) {
+    (name.to_owned(), value.into())
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// The statistics of the GC of an index, accumulated over its runs.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GcStats {
+    /// The bytes reclaimed. Can go negative, as in C, when repairing blocks
+    /// allocates more than it frees.
+    pub bytes_collected: i64,
+    pub total_run: Duration,
+    pub cycles: u64,
+    pub numeric_trees_missed: u64,
+    /// The blocks left alone because the index changed since it was scanned.
+    pub blocks_denied: u64,
+    per_field: BTreeMap<FieldType, FieldStats>,
+    last_run: Option<RunStats>,
+    last_dry_run: Option<RunStats>,
+}
+
+impl GcStats {
+    /// Account for a finished run. Dry runs are only kept as
+    /// [`Self::last_dry_run`].
+    pub fn record_run(&mut self, run: RunStats) {
+        if run.mode == RunMode::DryRun {
+            self.last_dry_run = Some(run);
+            return;
+        }
+        for (field_type, stats) in run.fields() {
+            self.per_field.entry(field_type).or_default().add(stats);
+            self.bytes_collected += stats.bytes_freed as i64 - stats.bytes_allocated as i64;
+            self.blocks_denied += stats.blocks_ignored;
+        }
+        self.numeric_trees_missed += run.numeric_trees_missed;
+        self.total_run += run.duration();
+        self.cycles += 1;
+        self.last_run = Some(run);
+    }
+
+    pub const fn last_run(&self) -> Option<&RunStats> {
+        self.last_run.as_ref()
+    }
+
+    pub const fn last_dry_run(&self) -> Option<&RunStats> {
+        self.last_dry_run.as_ref()
+    }
+
+    /// The totals of a field type over all runs.
+    pub fn field(&self, field_type: FieldType) -> FieldStats {
+        self.per_field.get(&field_type).copied().unwrap_or_default()
+    }
+
+    /// The reply of `GC_STATS`: the fields reported by the C fork GC, in the
+    /// same order, followed by the totals per field type and the last runs.
+    pub fn reply(&self) -> Vec<(String, StatValue)> {
+        let total_ms = self.total_run.as_millis() as u64;
+        let last_ms = self.last_run.as_ref().map_or(0.0, |run| {
+            // The C GC reports whole milliseconds.
+            run.duration().as_millis() as f64
+        });
+        let mut reply = vec![
+            stat("bytes_collected", self.bytes_collected),
+            stat("total_ms_run", total_ms),
+            stat("total_cycles", self.cycles),
+            stat(
+                "average_cycle_time_ms",
+                total_ms as f64 / self.cycles as f64,
+            ),
+            stat("last_run_time_ms", last_ms),
+            stat("gc_numeric_trees_missed", self.numeric_trees_missed as f64),
+            stat("gc_blocks_denied", self.blocks_denied as f64),
+        ];
+        let fields = self
+            .per_field
+            .iter()
+            .map(|(field_type, stats)| {
+                let stats = vec![
+                    stat("docs_collected", stats.docs_collected),
+                    stat("bytes_reclaimed", stats.bytes_reclaimed()),
+                ];
+                (field_type.name().to_owned(), StatValue::Map(stats))
+            })
+            .collect();
+        reply.push(("fields".to_owned(), StatValue::Map(fields)));
+        for (name, run) in [
+            ("last_run", &self.last_run),
+            ("last_dry_run", &self.last_dry_run),
+        ] {
+            if let Some(run) = run {
+                reply.push((name.to_owned(), StatValue::Map(run.reply())));
+            }
+        }
+        reply
+    }
+}
+
+/// Errors returned when parsing the arguments of `GC_FORCEINVOKE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvokeError {
+    BadTimeout(String),
+    UnknownArgument(String),
+}
+
+impl Display for InvokeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadTimeout(value) => write!(f, "Invalid timeout `{value}`"),
+            Self::UnknownArgument(arg) => write!(f, "Unknown argument `{arg}`"),
+        }
+    }
+}
+
+impl std::error::Error for InvokeError {}
+
+/// The arguments of `FT.DEBUG GC_FORCEINVOKE <index> [timeout] [DRYRUN]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForceInvoke {
+    /// How long to block the client waiting for the run, in milliseconds. 0
+    /// blocks it until the run ends.
+    pub timeout_ms: u64,
+    pub mode: RunMode,
+}
+
+impl ForceInvoke {
+    /// The timeout when none is given, as in C.
+    pub const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+
+    /// Parse the arguments following the index name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the timeout isn't a `u64`, or if there are other
+    /// arguments.
+    pub fn parse<'a>(args: impl IntoIterator<Item = &'a str>) -> Result<Self, InvokeError> {
+        let mut invoke = Self {
+            timeout_ms: Self::DEFAULT_TIMEOUT_MS,
+            mode: RunMode::Collect,
+        };
+        let mut timeout_given = false;
+        for arg in args {
+            if arg.eq_ignore_ascii_case("DRYRUN") {
+                invoke.mode = RunMode::DryRun;
+            } else if !timeout_given && arg.bytes().all(|b| b.is_ascii_digit()) {
+                invoke.timeout_ms = arg
+                    .parse()
+                    .map_err(|_| InvokeError::BadTimeout(arg.to_owned()))?;
+                timeout_given = true;
+            } else {
+                return Err(InvokeError::UnknownArgument(arg.to_owned()));
+            }
+        }
+        Ok(invoke)
+    }
+}
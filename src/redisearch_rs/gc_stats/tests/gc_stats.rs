@@ -0,0 +1,154 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::time::Duration;
+
+use gc_stats::{
+    Collected, FieldType, ForceInvoke, GcStats, InvokeError, RunMode, RunStats, StatValue,
+};
+
+const fn collected(entries_removed: u64, bytes_freed: u64, bytes_allocated: u64) -> Collected {
+    Collected {
+        entries_removed,
+        bytes_freed,
+        bytes_allocated,
+        blocks_ignored: 0,
+    }
+}
+
+fn run(mode: RunMode) -> RunStats {
+    let mut run = RunStats::new(mode);
+    run.record(FieldType::Text, 2, &collected(5, 184, 168));
+    run.record(FieldType::Text, 1, &collected(1, 56, 0));
+    run.record(
+        FieldType::Numeric,
+        3,
+        &Collected {
+            blocks_ignored: 1,
+            ..collected(3, 100, 0)
+        },
+    );
+    run.forked = Duration::from_millis(30);
+    run.locked = Duration::from_millis(5);
+    run
+}
+
+fn get<'a>(reply: &'a [(String, StatValue)], name: &str) -> &'a StatValue {
+    &reply.iter().find(|(n, _)| n == name).unwrap().1
+}
+
+#[test]
+fn test_run_stats() {
+    let run = run(RunMode::Collect);
+    let text = run.field(FieldType::Text);
+    assert_eq!(text.docs_collected, 3);
+    assert_eq!(text.entries_removed, 6);
+    assert_eq!(text.bytes_reclaimed(), 72);
+    assert_eq!(run.field(FieldType::Tag).docs_collected, 0);
+    assert_eq!(run.total().docs_collected, 6);
+    assert_eq!(run.duration(), Duration::from_millis(35));
+
+    let reply = run.reply();
+    assert_eq!(*get(&reply, "dry_run"), StatValue::Integer(0));
+    assert_eq!(*get(&reply, "bytes_reclaimed"), StatValue::Integer(172));
+    assert_eq!(*get(&reply, "blocks_ignored"), StatValue::Integer(1));
+    assert_eq!(*get(&reply, "locked_time_ms"), StatValue::Double(5.0));
+    let StatValue::Map(fields) = get(&reply, "fields") else {
+        panic!("fields are a map");
+    };
+    assert_eq!(
+        fields.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>(),
+        ["text", "numeric"]
+    );
+}
+
+#[test]
+fn test_totals() {
+    let mut stats = GcStats::default();
+    stats.record_run(run(RunMode::Collect));
+    stats.record_run(run(RunMode::Collect));
+
+    assert_eq!(stats.cycles, 2);
+    assert_eq!(stats.bytes_collected, 344);
+    assert_eq!(stats.blocks_denied, 2);
+    assert_eq!(stats.total_run, Duration::from_millis(70));
+    assert_eq!(stats.field(FieldType::Numeric).docs_collected, 6);
+
+    let reply = stats.reply();
+    let names: Vec<_> = reply.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "bytes_collected",
+            "total_ms_run",
+            "total_cycles",
+            "average_cycle_time_ms",
+            "last_run_time_ms",
+            "gc_numeric_trees_missed",
+            "gc_blocks_denied",
+            "fields",
+            "last_run"
+        ]
+    );
+    assert_eq!(
+        *get(&reply, "average_cycle_time_ms"),
+        StatValue::Double(35.0)
+    );
+}
+
+#[test]
+fn test_dry_run() {
+    let mut stats = GcStats::default();
+    stats.record_run(run(RunMode::DryRun));
+
+    // Nothing was collected.
+    assert_eq!(stats.cycles, 0);
+    assert_eq!(stats.bytes_collected, 0);
+    assert!(stats.last_run().is_none());
+
+    let dry_run = stats.last_dry_run().unwrap();
+    assert_eq!(dry_run.total().bytes_reclaimed(), 172);
+    let reply = stats.reply();
+    assert_eq!(
+        *get(&reply, "last_dry_run"),
+        StatValue::Map(dry_run.reply())
+    );
+    assert_eq!(*get(&dry_run.reply(), "dry_run"), StatValue::Integer(1));
+}
+
+#[test]
+fn test_force_invoke_args() {
+    assert_eq!(
+        ForceInvoke::parse([]),
+        Ok(ForceInvoke {
+            timeout_ms: ForceInvoke::DEFAULT_TIMEOUT_MS,
+            mode: RunMode::Collect
+        })
+    );
+    assert_eq!(
+        ForceInvoke::parse(["100", "dryrun"]),
+        Ok(ForceInvoke {
+            timeout_ms: 100,
+            mode: RunMode::DryRun
+        })
+    );
+    assert_eq!(
+        ForceInvoke::parse(["DRYRUN"]).unwrap().timeout_ms,
+        ForceInvoke::DEFAULT_TIMEOUT_MS
+    );
+    assert_eq!(ForceInvoke::parse(["0"]).unwrap().timeout_ms, 0);
+    assert_eq!(
+        ForceInvoke::parse(["99999999999999999999"]),
+        Err(InvokeError::BadTimeout("99999999999999999999".to_owned()))
+    );
+    assert_eq!(
+        ForceInvoke::parse(["1", "2"]).unwrap_err().to_string(),
+        "Unknown argument `2`"
+    );
+}
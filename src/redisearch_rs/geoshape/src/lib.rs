@@ -0,0 +1,189 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! GEOSHAPE query predicates, and the metadata of the results they match.
+//!
+//! The R-tree of GEOSHAPE fields lives in `src/geometry`, which returns the
+//! candidates whose bounding box matches. [`GeoShapeQuery::evaluate`] checks
+//! the predicate on the shape of a candidate and, if asked to, computes the
+//! distance between its centroid and the centroid of the query shape, so that
+//! mapping clients get it without a second round trip.
+//!
+//! The metadata is loadable as the pseudo-fields [`DISTANCE_FIELD`] and
+//! [`PREDICATE_FIELD`], in `RETURN` and in `APPLY` expressions, e.g.
+//! `APPLY "@__geo_distance / 1000" AS km`.
+
+pub mod shape;
+
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+pub use shape::{Point, Shape, WktError};
+
+/// The pseudo-field holding the distance between the centroids of the result
+/// and of the query shape: in meters for spherical coordinates, in the units
+/// of the coordinates for flat ones.
+pub const DISTANCE_FIELD: &str = "__geo_distance";
+
+/// The pseudo-field holding the predicate which matched the result.
+pub const PREDICATE_FIELD: &str = "__geo_predicate";
+
+/// The radius of the Earth used by Redis geo commands.
+const EARTH_RADIUS_METERS: f64 = 6372797.560856;
+
+/// The coordinate system of a GEOSHAPE field (`COORD_SYSTEM`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoordSystem {
+    /// Longitude and latitude, in degrees.
+    #[default]
+    Spherical,
+    /// Cartesian coordinates.
+    Flat,
+}
+
+impl CoordSystem {
+    /// The distance between `a` and `b`: great-circle in meters for
+    /// spherical coordinates, Euclidean for flat ones.
+    pub fn distance(self, a: Point, b: Point) -> f64 {
+        match self {
+            Self::Flat => (a.x - b.x).hypot(a.y - b.y),
+            Self::Spherical => {
+                let (lat1, lat2) = (a.y.to_radians(), b.y.to_radians());
+                let d_lat = (b.y - a.y).to_radians();
+                let d_lon = (b.x - a.x).to_radians();
+                let h = (d_lat / 2.0).sin().powi(2)
+                    + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+                2.0 * EARTH_RADIUS_METERS * h.sqrt().asin()
+            }
+        }
+    }
+}
+
+/// The relation between the shape of a document and the query shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Predicate {
+    /// The document shape is within the query shape.
+    Within,
+    /// The document shape contains the query shape.
+    Contains,
+    /// The shapes have at least one point in common.
+    Intersects,
+    /// The shapes have no point in common.
+    Disjoint,
+}
+
+impl Predicate {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Within => "WITHIN",
+            Self::Contains => "CONTAINS",
+            Self::Intersects => "INTERSECTS",
+            Self::Disjoint => "DISJOINT",
+        }
+    }
+
+    /// Whether `doc` relates to `query` by this predicate.
+    pub fn holds(self, doc: &Shape, query: &Shape) -> bool {
+        match self {
+            Self::Within => doc.within(query),
+            Self::Contains => query.within(doc),
+            Self::Intersects => doc.intersects(query),
+            Self::Disjoint => !doc.intersects(query),
+        }
+    }
+}
+
+impl Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The error returned when parsing an unknown [`Predicate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownPredicate(pub String);
+
+impl Display for UnknownPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown geoshape predicate `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownPredicate {}
+
+impl FromStr for Predicate {
+    type Err = UnknownPredicate;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Self::Within,
+            Self::Contains,
+            Self::Intersects,
+            Self::Disjoint,
+        ]
+        .into_iter()
+        .find(|p| p.as_str().eq_ignore_ascii_case(s))
+        .ok_or_else(|| UnknownPredicate(s.to_owned()))
+    }
+}
+
+/// A GEOSHAPE query, e.g. `@geom:[WITHIN $poly]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoShapeQuery {
+    pub predicate: Predicate,
+    pub shape: Shape,
+    pub coords: CoordSystem,
+    /// Whether to compute the [metadata](GeoMatch) of the results.
+    pub with_metadata: bool,
+}
+
+/// The metadata of a result of a [`GeoShapeQuery`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoMatch {
+    pub predicate: Predicate,
+    /// The distance between the centroids, if the query asked for metadata.
+    pub distance: Option<f64>,
+}
+
+/// The value of a pseudo-field of a [`GeoMatch`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PseudoValue {
+    Number(f64),
+    String(&'static str),
+}
+
+impl GeoShapeQuery {
+    /// The metadata of `doc` if it matches the query, `None` otherwise.
+    pub fn evaluate(&self, doc: &Shape) -> Option<GeoMatch> {
+        if !self.predicate.holds(doc, &self.shape) {
+            return None;
+        }
+        let distance = self
+            .with_metadata
+            .then(|| self.coords.distance(doc.centroid(), self.shape.centroid()));
+        Some(GeoMatch {
+            predicate: self.predicate,
+            distance,
+        })
+    }
+}
+
+impl GeoMatch {
+    /// The value of the pseudo-field `name`, if it's one of the metadata
+    /// pseudo-fields and was computed.
+    pub fn pseudo_field(&self, name: &str) -> Option<PseudoValue> {
+        match name {
+            DISTANCE_FIELD => self.distance.map(PseudoValue::Number),
+            PREDICATE_FIELD => Some(PseudoValue::String(self.predicate.as_str())),
+            _ => None,
+        }
+    }
+}
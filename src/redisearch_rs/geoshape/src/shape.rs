@@ -0,0 +1,190 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Shapes of GEOSHAPE fields, parsed from WKT, and the relations between them.
+
+use std::fmt::{self, Display};
+
+/// A point, as `x` (longitude) and `y` (latitude) in spherical coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+}
+
+/// A shape stored in a GEOSHAPE field or given in a query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Point(Point),
+    /// The outer ring of a polygon, closed: the last point is the first one.
+    Polygon(Vec<Point>),
+}
+
+/// Errors returned when parsing WKT.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WktError {
+    /// The geometry type isn't `POINT` or `POLYGON`.
+    UnsupportedType(String),
+    /// The text isn't well-formed WKT.
+    Syntax(String),
+    /// The polygon has fewer than 3 distinct points, or isn't closed.
+    BadPolygon,
+}
+
+impl Display for WktError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedType(kind) => write!(f, "Unsupported geometry type {kind}"),
+            Self::Syntax(wkt) => write!(f, "Invalid WKT: {wkt}"),
+            Self::BadPolygon => f.write_str("A polygon must be a closed ring of at least 3 points"),
+        }
+    }
+}
+
+impl std::error::Error for WktError {}
+
+impl Shape {
+    /// Parse a `POINT (x y)` or `POLYGON ((x y, ...))`. Only the outer ring of
+    /// a polygon is kept: holes aren't supported.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`WktError`] if `wkt` isn't a well-formed point or polygon.
+    pub fn parse_wkt(wkt: &str) -> Result<Self, WktError> {
+        let syntax = || WktError::Syntax(wkt.to_owned());
+        let wkt = wkt.trim();
+        let open = wkt.find('(').ok_or_else(syntax)?;
+        let kind = wkt[..open].trim();
+        let body = wkt[open..].strip_suffix(')').ok_or_else(syntax)?;
+        let body = &body[1..];
+        if kind.eq_ignore_ascii_case("POINT") {
+            return Ok(Self::Point(parse_point(body).ok_or_else(syntax)?));
+        }
+        if !kind.eq_ignore_ascii_case("POLYGON") {
+            return Err(WktError::UnsupportedType(kind.to_owned()));
+        }
+        let body = body.trim().strip_prefix('(').ok_or_else(syntax)?;
+        let outer = body.split(')').next().ok_or_else(syntax)?;
+        let ring = outer
+            .split(',')
+            .map(parse_point)
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(syntax)?;
+        if ring.len() < 4 || ring.first() != ring.last() {
+            return Err(WktError::BadPolygon);
+        }
+        Ok(Self::Polygon(ring))
+    }
+
+    /// The center of mass of the shape.
+    pub fn centroid(&self) -> Point {
+        let ring = match self {
+            Self::Point(p) => return *p,
+            Self::Polygon(ring) => ring,
+        };
+        let (mut area, mut x, mut y) = (0.0, 0.0, 0.0);
+        for edge in ring.windows(2) {
+            let cross = edge[0].x * edge[1].y - edge[1].x * edge[0].y;
+            area += cross;
+            x += (edge[0].x + edge[1].x) * cross;
+            y += (edge[0].y + edge[1].y) * cross;
+        }
+        if area == 0.0 {
+            // A degenerate polygon: the mean of its points.
+            let n = (ring.len() - 1) as f64;
+            let sum = ring[1..]
+                .iter()
+                .fold((0.0, 0.0), |s, p| (s.0 + p.x, s.1 + p.y));
+            return Point::new(sum.0 / n, sum.1 / n);
+        }
+        Point::new(x / (3.0 * area), y / (3.0 * area))
+    }
+
+    /// Whether every point of `self` is in `other`, boundaries included.
+    pub fn within(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Point(a), Self::Point(b)) => a == b,
+            (Self::Point(p), Self::Polygon(ring)) => in_ring(*p, ring),
+            // A polygon has an area, a point doesn't.
+            (Self::Polygon(_), Self::Point(_)) => false,
+            (Self::Polygon(inner), Self::Polygon(outer)) => {
+                inner.iter().all(|&p| in_ring(p, outer)) && !edges_cross(inner, outer)
+            }
+        }
+    }
+
+    /// Whether `self` and `other` have at least one point in common.
+    pub fn intersects(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Point(a), Self::Point(b)) => a == b,
+            (Self::Point(p), Self::Polygon(ring)) | (Self::Polygon(ring), Self::Point(p)) => {
+                in_ring(*p, ring)
+            }
+            (Self::Polygon(a), Self::Polygon(b)) => {
+                in_ring(a[0], b) || in_ring(b[0], a) || edges_cross(a, b)
+            }
+        }
+    }
+}
+
+fn parse_point(text: &str) -> Option<Point> {
+    let mut coords = text.split_whitespace().map(str::parse::<f64>);
+    match (coords.next(), coords.next(), coords.next()) {
+        (Some(Ok(x)), Some(Ok(y)), None) => Some(Point::new(x, y)),
+        _ => None,
+    }
+}
+
+/// Whether `p` is inside the closed `ring` or on its boundary, by ray casting.
+fn in_ring(p: Point, ring: &[Point]) -> bool {
+    let mut inside = false;
+    for edge in ring.windows(2) {
+        let (a, b) = (edge[0], edge[1]);
+        if on_segment(p, a, b) {
+            return true;
+        }
+        if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// The sign of the turn from `a -> b` to `a -> c`.
+fn orientation(a: Point, b: Point, c: Point) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+fn on_segment(p: Point, a: Point, b: Point) -> bool {
+    orientation(a, b, p) == 0.0
+        && p.x >= a.x.min(b.x)
+        && p.x <= a.x.max(b.x)
+        && p.y >= a.y.min(b.y)
+        && p.y <= a.y.max(b.y)
+}
+
+/// Whether an edge of `a` properly crosses an edge of `b`, i.e. at a single
+/// point inside both.
+fn edges_cross(a: &[Point], b: &[Point]) -> bool {
+    a.windows(2).any(|e| {
+        b.windows(2).any(|f| {
+            let d1 = orientation(f[0], f[1], e[0]);
+            let d2 = orientation(f[0], f[1], e[1]);
+            let d3 = orientation(e[0], e[1], f[0]);
+            let d4 = orientation(e[0], e[1], f[1]);
+            d1 * d2 < 0.0 && d3 * d4 < 0.0
+        })
+    })
+}
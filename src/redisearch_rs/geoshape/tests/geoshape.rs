@@ -0,0 +1,121 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use geoshape::{
+    CoordSystem, DISTANCE_FIELD, GeoShapeQuery, PREDICATE_FIELD, Point, Predicate, PseudoValue,
+    Shape, WktError,
+};
+
+fn shape(wkt: &str) -> Shape {
+    Shape::parse_wkt(wkt).unwrap()
+}
+
+fn square() -> Shape {
+    shape("POLYGON((0 0, 4 0, 4 4, 0 4, 0 0))")
+}
+
+#[test]
+fn test_parse_wkt() {
+    assert_eq!(
+        shape(" point (1.5 -2) "),
+        Shape::Point(Point::new(1.5, -2.0))
+    );
+    assert!(matches!(square(), Shape::Polygon(ring) if ring.len() == 5));
+    assert_eq!(
+        Shape::parse_wkt("LINESTRING(0 0, 1 1)"),
+        Err(WktError::UnsupportedType("LINESTRING".to_owned()))
+    );
+    assert_eq!(
+        Shape::parse_wkt("POLYGON((0 0, 1 0, 1 1))"),
+        Err(WktError::BadPolygon)
+    );
+    assert!(matches!(
+        Shape::parse_wkt("POINT(1)"),
+        Err(WktError::Syntax(_))
+    ));
+}
+
+#[test]
+fn test_centroid() {
+    assert_eq!(square().centroid(), Point::new(2.0, 2.0));
+    // Clockwise rings have the same centroid.
+    assert_eq!(
+        shape("POLYGON((0 0, 0 2, 2 2, 2 0, 0 0))").centroid(),
+        Point::new(1.0, 1.0)
+    );
+    assert_eq!(shape("POINT(3 4)").centroid(), Point::new(3.0, 4.0));
+}
+
+#[test]
+fn test_predicates() {
+    let inner = shape("POLYGON((1 1, 2 1, 2 2, 1 2, 1 1))");
+    let overlapping = shape("POLYGON((3 3, 6 3, 6 6, 3 6, 3 3))");
+    let far = shape("POLYGON((10 10, 11 10, 11 11, 10 10))");
+
+    assert!(Predicate::Within.holds(&inner, &square()));
+    assert!(!Predicate::Within.holds(&overlapping, &square()));
+    assert!(Predicate::Contains.holds(&square(), &inner));
+    assert!(Predicate::Intersects.holds(&overlapping, &square()));
+    assert!(Predicate::Disjoint.holds(&far, &square()));
+    assert!(!Predicate::Disjoint.holds(&inner, &square()));
+
+    // Points on the boundary are within the polygon.
+    assert!(Predicate::Within.holds(&shape("POINT(4 2)"), &square()));
+    assert!(!Predicate::Within.holds(&shape("POINT(5 2)"), &square()));
+}
+
+#[test]
+fn test_parse_predicate() {
+    assert_eq!("within".parse(), Ok(Predicate::Within));
+    assert_eq!("DISJOINT".parse(), Ok(Predicate::Disjoint));
+    assert_eq!(
+        "NEAR".parse::<Predicate>().unwrap_err().to_string(),
+        "Unknown geoshape predicate `NEAR`"
+    );
+}
+
+#[test]
+fn test_metadata() {
+    let mut query = GeoShapeQuery {
+        predicate: Predicate::Within,
+        shape: square(),
+        coords: CoordSystem::Flat,
+        with_metadata: false,
+    };
+    let doc = shape("POINT(2 5)");
+    assert_eq!(query.evaluate(&doc), None);
+
+    let doc = shape("POINT(2 3)");
+    let found = query.evaluate(&doc).unwrap();
+    assert_eq!(found.distance, None);
+    assert_eq!(found.pseudo_field(DISTANCE_FIELD), None);
+
+    query.with_metadata = true;
+    let found = query.evaluate(&doc).unwrap();
+    assert_eq!(
+        found.pseudo_field(DISTANCE_FIELD),
+        Some(PseudoValue::Number(1.0))
+    );
+    assert_eq!(
+        found.pseudo_field(PREDICATE_FIELD),
+        Some(PseudoValue::String("WITHIN"))
+    );
+    assert_eq!(found.pseudo_field("__score"), None);
+}
+
+#[test]
+fn test_spherical_distance() {
+    // One degree of latitude.
+    let d = CoordSystem::Spherical.distance(Point::new(0.0, 0.0), Point::new(0.0, 1.0));
+    assert!((d - 111_226.3).abs() < 1.0, "{d}");
+    assert_eq!(
+        CoordSystem::Spherical.distance(Point::new(2.35, 48.85), Point::new(2.35, 48.85)),
+        0.0
+    );
+}
@@ -0,0 +1,70 @@
+#pragma once
+
+/* Warning, this file is autogenerated by cbindgen from `src/redisearch_rs/c_entrypoint/admission_ffi/build.rs. Don't modify it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * A subcommand of `FT._QUERIES`.
+ */
+typedef enum QueriesSubcommand {
+  QueriesSubcommand_List,
+  QueriesSubcommand_Kill,
+} QueriesSubcommand;
+
+/**
+ * The right of a query to run, taken with [`Admission_Acquire`].
+ */
+typedef struct AdmissionTicket AdmissionTicket;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ */
+struct AdmissionTicket *Admission_Acquire(const char *index,
+                                         uintptr_t index_len,
+                                         const char *query,
+                                         uintptr_t query_len);
+
+/**
+ */
+void AdmissionTicket_Free(struct AdmissionTicket *ticket);
+
+/**
+ */
+uint64_t AdmissionTicket_Id(const struct AdmissionTicket *ticket);
+
+/**
+ */
+bool AdmissionTicket_IsKilled(const struct AdmissionTicket *ticket);
+
+/**
+ */
+bool Admission_Kill(uint64_t id);
+
+/**
+ */
+void Admission_ReplyInFlight(void *reply,
+                             void (*query)(void *reply,
+                                           uint64_t id,
+                                           const char *index,
+                                           uintptr_t index_len,
+                                           const char *query,
+                                           uintptr_t query_len,
+                                           uint64_t elapsed_ms));
+
+/**
+ */
+bool Admission_ParseCommand(const char *const *argv,
+                            uintptr_t argc,
+                            enum QueriesSubcommand *subcommand,
+                            uint64_t *id);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
@@ -0,0 +1,65 @@
+#pragma once
+
+/* Warning, this file is autogenerated by cbindgen from `src/redisearch_rs/c_entrypoint/bsearch_ffi/build.rs. Don't modify it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Compare the searched item `s` with the array element `elem`; return a
+ * negative value, zero or a positive value if `s` is less than, equal to or
+ * greater than `elem`.
+ */
+typedef int (*rsbcompare)(const void *s, const void *elem);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Find the index of the first element of the sorted array which is greater
+ * than the item `s`, or `narr` if there is none.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * 1. `arr` must point to `narr` contiguous elements of `elemsz` bytes each,
+ *    sorted according to `cmp`.
+ * 2. `cmp` must be safe to call with `s` and a pointer to any element of
+ *    `arr`.
+ */
+int rsb_gt(const void *arr, uintptr_t narr, uintptr_t elemsz, const void *s, rsbcompare cmp);
+
+/**
+ * Find the index of the last element of the sorted array which is less than
+ * the item `s`, or -1 if there is none.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * 1. `arr` must point to `narr` contiguous elements of `elemsz` bytes each,
+ *    sorted according to `cmp`.
+ * 2. `cmp` must be safe to call with `s` and a pointer to any element of
+ *    `arr`.
+ */
+int rsb_lt(const void *arr, uintptr_t narr, uintptr_t elemsz, const void *s, rsbcompare cmp);
+
+/**
+ * Find the index of an element of the sorted array equal to the item `s`,
+ * or -1 if there is none. The array must not have duplicate items.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * 1. `arr` must point to `narr` contiguous elements of `elemsz` bytes each,
+ *    sorted according to `cmp`.
+ * 2. `cmp` must be safe to call with `s` and a pointer to any element of
+ *    `arr`.
+ */
+int rsb_eq(const void *arr, uintptr_t narr, uintptr_t elemsz, const void *s, rsbcompare cmp);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
@@ -0,0 +1,180 @@
+#pragma once
+
+/* Warning, this file is autogenerated by cbindgen from `src/redisearch_rs/c_entrypoint/gc_stats_ffi/build.rs. Don't modify it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The type of the field an inverted index belongs to.
+ */
+typedef enum GcFieldType {
+  GcFieldType_Text,
+  GcFieldType_Numeric,
+  GcFieldType_Tag,
+  GcFieldType_Geo,
+  GcFieldType_Vector,
+} GcFieldType;
+
+/**
+ * The metrics of a GC run in progress.
+ */
+typedef struct GcRunStats GcRunStats;
+
+/**
+ * The statistics of the GC of an index, accumulated over its runs. Runs are
+ * recorded by the GC thread while `FT.DEBUG GC_STATS` reads them from the
+ * main thread, hence the lock.
+ */
+typedef struct GcStats GcStats;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Create the statistics of the GC of an index. They must be freed using
+ * [`GcStats_Free`].
+ */
+struct GcStats *GcStats_New(void);
+
+/**
+ * Free the statistics created using [`GcStats_New`].
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `stats` must be a valid, non NULL, pointer created using [`GcStats_New`].
+ */
+void GcStats_Free(struct GcStats *stats);
+
+/**
+ * Start recording a GC run. A dry run only reports what it would collect. The
+ * run must be passed to [`GcStats_RecordRun`] once done.
+ */
+struct GcRunStats *GcRunStats_New(bool dry_run);
+
+/**
+ * Record what the run collected from an inverted index of a `field_type`
+ * field, where `docs` deleted documents were found. The other arguments are
+ * the fields of the `II_GCScanStats` the delta was applied, or previewed,
+ * with.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `run` must be a valid, non NULL, pointer created using [`GcRunStats_New`].
+ */
+void GcRunStats_Record(struct GcRunStats *run,
+                       enum GcFieldType field_type,
+                       uint64_t docs,
+                       uintptr_t entries_removed,
+                       uintptr_t bytes_freed,
+                       uintptr_t bytes_allocated,
+                       uintptr_t blocks_ignored);
+
+/**
+ * Record that the run skipped a numeric tree which changed while it was
+ * scanned.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `run` must be a valid, non NULL, pointer created using [`GcRunStats_New`].
+ */
+void GcRunStats_NumericTreeMissed(struct GcRunStats *run);
+
+/**
+ * Set the time the run spent in the forked child, and applying its results
+ * in the parent under the index write lock.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `run` must be a valid, non NULL, pointer created using [`GcRunStats_New`].
+ */
+void GcRunStats_SetTimes(struct GcRunStats *run, uint64_t forked_ms, uint64_t locked_ms);
+
+/**
+ * Account for a finished run, taking ownership of `run`.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `stats` must be a valid, non NULL, pointer created using [`GcStats_New`].
+ * - `run` must be a valid, non NULL, pointer created using [`GcRunStats_New`]. It must not be
+ *   used after this call.
+ */
+void GcStats_RecordRun(const struct GcStats *stats, struct GcRunStats *run);
+
+/**
+ * Reply the statistics to `FT.DEBUG GC_STATS`, as the entries of the map
+ * `reply` is in.
+ *
+ * `map` opens a nested map under a key, closed by `map_end`. `int_kv` and
+ * `double_kv` add an entry to the current map. They're all called with `reply`.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `stats` must be a valid, non NULL, pointer created using [`GcStats_New`].
+ * - The callbacks must be safe to call with `reply` and a NUL-terminated key.
+ */
+void GcStats_Reply(const struct GcStats *stats,
+                   void *reply,
+                   void (*map)(void *reply, const char *key),
+                   void (*map_end)(void *reply),
+                   void (*int_kv)(void *reply, const char *key, int64_t value),
+                   void (*double_kv)(void *reply, const char *key, double value));
+
+/**
+ * Whether a dry run was recorded, so that `FT.DEBUG GC_FORCEINVOKE ... DRYRUN`
+ * can reply an error before starting a reply with [`GcStats_ReplyLastDryRun`].
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `stats` must be a valid, non NULL, pointer created using [`GcStats_New`].
+ */
+bool GcStats_HasLastDryRun(const struct GcStats *stats);
+
+/**
+ * Reply the metrics of the last dry run to `FT.DEBUG GC_FORCEINVOKE ...
+ * DRYRUN`, as the entries of the map `reply` is in. Returns false, writing
+ * nothing, if there was no dry run yet. See [`GcStats_Reply`] for the
+ * callbacks.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `stats` must be a valid, non NULL, pointer created using [`GcStats_New`].
+ * - The callbacks must be safe to call with `reply` and a NUL-terminated key.
+ */
+bool GcStats_ReplyLastDryRun(const struct GcStats *stats,
+                             void *reply,
+                             void (*map)(void *reply, const char *key),
+                             void (*map_end)(void *reply),
+                             void (*int_kv)(void *reply, const char *key, int64_t value),
+                             void (*double_kv)(void *reply, const char *key, double value));
+
+/**
+ * Parse the `argc` arguments of `FT.DEBUG GC_FORCEINVOKE` following the index
+ * name: an optional timeout and `DRYRUN`. Returns false, leaving the output
+ * parameters untouched, if they're invalid.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `argv` must point to `argc` valid, NUL-terminated strings. It may be NULL if `argc` is 0.
+ * - `timeout_ms` and `dry_run` must be valid, non NULL, pointers.
+ */
+bool GcForceInvoke_Parse(const char *const *argv,
+                         uintptr_t argc,
+                         uint64_t *timeout_ms,
+                         bool *dry_run);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
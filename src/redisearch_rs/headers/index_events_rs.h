@@ -0,0 +1,195 @@
+#pragma once
+
+/* Warning, this file is autogenerated by cbindgen from `src/redisearch_rs/c_entrypoint/index_events_ffi/build.rs. Don't modify it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The mask to subscribe to all the event kinds.
+ */
+#define INDEX_EVENTS_ALL 0xFF
+
+/**
+ * The type of an index lifecycle event.
+ */
+typedef enum IndexEventKind {
+  IndexEventKind_Created,
+  IndexEventKind_Dropped,
+  IndexEventKind_Altered,
+  IndexEventKind_DocIndexed,
+  IndexEventKind_DocDeleted,
+  IndexEventKind_GcCycleDone,
+  IndexEventKind_ScanFinished,
+} IndexEventKind;
+
+/**
+ * The payload of a [`IndexEventKind::GcCycleDone`] event.
+ */
+typedef struct IndexEventGcStats {
+  uint64_t bytes_collected;
+  uint64_t entries_removed;
+} IndexEventGcStats;
+
+/**
+ * The payload of a [`IndexEventKind::ScanFinished`] event.
+ */
+typedef struct IndexEventScanStats {
+  uint64_t docs_scanned;
+  uint64_t docs_indexed;
+} IndexEventScanStats;
+
+/**
+ * An event, as seen by a C subscriber. The strings aren't NUL-terminated, and
+ * are only valid during the callback.
+ */
+typedef struct IndexEventView {
+  uint64_t seq;
+  enum IndexEventKind kind;
+  const char *index;
+  uintptr_t index_len;
+  /**
+   * The document key of the `DocIndexed` and `DocDeleted` events, NULL
+   * otherwise.
+   */
+  const char *key;
+  uintptr_t key_len;
+  /**
+   * Zeroed unless `kind` is `GcCycleDone`.
+   */
+  struct IndexEventGcStats gc;
+  /**
+   * Zeroed unless `kind` is `ScanFinished`.
+   */
+  struct IndexEventScanStats scan;
+} IndexEventView;
+
+/**
+ * A C subscriber, called with the `ctx` it subscribed with.
+ */
+typedef void (*IndexEventCallback)(void *ctx, const struct IndexEventView *event);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Publish that the index `index` was created.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `index` must point to `index_len` bytes, valid for reads, unless
+ *   `index_len` is 0.
+ */
+void IndexEvents_IndexCreated(const char *index, uintptr_t index_len);
+
+/**
+ * Publish that the index `index` was dropped.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `index` must point to `index_len` bytes, valid for reads, unless
+ *   `index_len` is 0.
+ */
+void IndexEvents_IndexDropped(const char *index, uintptr_t index_len);
+
+/**
+ * Publish that the schema of the index `index` was altered.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `index` must point to `index_len` bytes, valid for reads, unless
+ *   `index_len` is 0.
+ */
+void IndexEvents_IndexAltered(const char *index, uintptr_t index_len);
+
+/**
+ * Publish that the document `key` was indexed by the index `index`.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `index` must point to `index_len` bytes, valid for reads, unless
+ *   `index_len` is 0.
+ * - `key` must point to `key_len` bytes, valid for reads, unless `key_len`
+ *   is 0.
+ */
+void IndexEvents_DocIndexed(const char *index,
+                            uintptr_t index_len,
+                            const char *key,
+                            uintptr_t key_len);
+
+/**
+ * Publish that the document `key` was deleted from the index `index`.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `index` must point to `index_len` bytes, valid for reads, unless
+ *   `index_len` is 0.
+ * - `key` must point to `key_len` bytes, valid for reads, unless `key_len`
+ *   is 0.
+ */
+void IndexEvents_DocDeleted(const char *index,
+                            uintptr_t index_len,
+                            const char *key,
+                            uintptr_t key_len);
+
+/**
+ * Publish that a GC cycle of the index `index` is done.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `index` must point to `index_len` bytes, valid for reads, unless
+ *   `index_len` is 0.
+ */
+void IndexEvents_GcCycleDone(const char *index,
+                             uintptr_t index_len,
+                             struct IndexEventGcStats stats);
+
+/**
+ * Publish that the background scan of the index `index` is done.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `index` must point to `index_len` bytes, valid for reads, unless
+ *   `index_len` is 0.
+ */
+void IndexEvents_ScanFinished(const char *index,
+                              uintptr_t index_len,
+                              struct IndexEventScanStats stats);
+
+/**
+ * Deliver the queued events to the subscribers, in order. Returns the number
+ * of events delivered, which is 0 when called from a subscriber.
+ */
+uintptr_t IndexEvents_DispatchPending(void);
+
+/**
+ * Call `callback` with `ctx` for every event whose kind is in `mask`, where
+ * kind `k` is the bit `1 << k`, and [`INDEX_EVENTS_ALL`] selects them all.
+ * Returns an id to pass to [`IndexEvents_Unsubscribe`].
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `callback` must be safe to call with `ctx` from the thread calling
+ *   [`IndexEvents_DispatchPending`], until unsubscribed.
+ */
+uint64_t IndexEvents_Subscribe(uint8_t mask, IndexEventCallback callback, void *ctx);
+
+/**
+ * Cancel the subscription `id`. Returns `false` if it doesn't exist.
+ */
+bool IndexEvents_Unsubscribe(uint64_t id);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
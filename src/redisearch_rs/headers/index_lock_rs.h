@@ -0,0 +1,141 @@
+#pragma once
+
+/* Warning, this file is autogenerated by cbindgen from `src/redisearch_rs/c_entrypoint/index_lock_ffi/build.rs. Don't modify it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The number of buckets of the wait histograms of [`IndexLockStats`].
+ */
+#define INDEX_LOCK_WAIT_BUCKETS 24
+
+/**
+ * The reader/writer lock of an index. It has no guards: the C code releases
+ * it using [`IndexLock_Unlock`].
+ */
+typedef struct IndexLock IndexLock;
+
+/**
+ * The contention statistics of an [`IndexLock`]. Bucket `i` of a wait
+ * histogram counts the waits shorter than `2^i` microseconds, and at least
+ * `2^(i-1)`.
+ */
+typedef struct IndexLockStats {
+  uint64_t read_acquisitions;
+  uint64_t write_acquisitions;
+  /**
+   * Acquisitions that couldn't enter immediately.
+   */
+  uint64_t read_contended;
+  uint64_t write_contended;
+  uint64_t max_read_wait_us;
+  uint64_t max_write_wait_us;
+  /**
+   * An upper bound of the 99th percentile of the contended waits, 0 if
+   * there were none.
+   */
+  uint64_t read_wait_p99_us;
+  uint64_t write_wait_p99_us;
+  uint64_t read_waits[INDEX_LOCK_WAIT_BUCKETS];
+  uint64_t write_waits[INDEX_LOCK_WAIT_BUCKETS];
+} IndexLockStats;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Create the lock of an index. It must be freed using [`IndexLock_Free`].
+ */
+struct IndexLock *IndexLock_New(void);
+
+/**
+ * Free a lock created using [`IndexLock_New`].
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`],
+ *   which isn't held.
+ */
+void IndexLock_Free(struct IndexLock *lock);
+
+/**
+ * Lock for reading, waiting as long as necessary.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`].
+ */
+void IndexLock_ReadLock(const struct IndexLock *lock);
+
+/**
+ * Lock for writing, waiting as long as necessary.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`].
+ */
+void IndexLock_WriteLock(const struct IndexLock *lock);
+
+/**
+ * Lock for reading if that's possible without waiting, returning whether it
+ * succeeded.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`].
+ */
+bool IndexLock_TryReadLock(const struct IndexLock *lock);
+
+/**
+ * Lock for writing if that's possible without waiting, returning whether it
+ * succeeded.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`].
+ */
+bool IndexLock_TryWriteLock(const struct IndexLock *lock);
+
+/**
+ * Release the lock, held for reading or writing.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`].
+ * - The calling thread must hold the lock.
+ */
+void IndexLock_Unlock(const struct IndexLock *lock);
+
+/**
+ * The contention statistics of the lock.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`].
+ */
+struct IndexLockStats IndexLock_Stats(const struct IndexLock *lock);
+
+/**
+ * Reset the contention statistics of the lock.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `lock` must be a valid, non NULL, pointer created using [`IndexLock_New`].
+ */
+void IndexLock_ResetStats(const struct IndexLock *lock);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
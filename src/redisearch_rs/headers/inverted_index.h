@@ -322,6 +322,31 @@ uint32_t InvertedIndex_GcMarker(const struct InvertedIndex *ii);
  */
 void InvertedIndex_GcMarkerInc(struct InvertedIndex *ii);
 
+/**
+ * Relocate the blocks of the inverted index during active defragmentation,
+ * starting at block `*cursor`.
+ *
+ * `alloc` and `should_stop` are `RedisModule_DefragAlloc` and
+ * `RedisModule_DefragShouldStop`, called with `ctx`. Returns `true` once the
+ * whole index has been processed, resetting `*cursor` to 0. Returns `false`
+ * if `should_stop` interrupted the pass, in which case the call must be
+ * repeated later with the same cursor.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `ii` must be a valid, non NULL, pointer to an `InvertedIndex` instance, not accessed by
+ *   any other thread during the call.
+ * - `cursor` must be a valid, non NULL, pointer to a cursor, initially 0.
+ * - `alloc` must behave like `RedisModule_DefragAlloc`, and both callbacks must be safe to
+ *   call with `ctx`.
+ */
+bool InvertedIndex_Defrag(struct InvertedIndex *ii,
+                          uintptr_t *cursor,
+                          void *ctx,
+                          void *(*alloc)(void *ctx, void *ptr),
+                          int (*should_stop)(void *ctx));
+
 /**
  * Scan the inverted index for garbage and write the GC delta to the provided writer. The function
  * returns true if the scan was successful and false otherwise.
@@ -384,6 +409,26 @@ void InvertedIndex_ApplyGcDelta(struct InvertedIndex *ii,
                                 struct InvertedIndexGcDelta *deltas,
                                 struct II_GCScanStats *apply_info);
 
+/**
+ * Report what applying a GC delta to the inverted index would do, without modifying the index.
+ * This is the dry run of the GC. The output parameter `apply_info` will be set to what
+ * [`InvertedIndex_ApplyGcDelta`] would set it to.
+ *
+ * Unlike [`InvertedIndex_ApplyGcDelta`], this doesn't take ownership of the `deltas` pointer,
+ * which must still be freed using [`InvertedIndex_GcDelta_Free`].
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `ii` must be a valid, non NULL, pointer to an `InvertedIndex` instance.
+ * - `deltas` must be a valid, non NULL, pointer to a `GcScanDelta` instance created using
+ *   [`InvertedIndex_GcDelta_Read`].
+ * - `apply_info` must be a valid, non NULL, pointer to a `GcApplyInfo` instance.
+ */
+void InvertedIndex_PreviewGcDelta(const struct InvertedIndex *ii,
+                                  const struct InvertedIndexGcDelta *deltas,
+                                  struct II_GCScanStats *apply_info);
+
 /**
  * Get the index of the last block in the GC delta.
  *
@@ -394,6 +439,16 @@ void InvertedIndex_ApplyGcDelta(struct InvertedIndex *ii,
  */
 uintptr_t GcScanDelta_LastBlockIdx(const struct InvertedIndexGcDelta *gc_scan_delta);
 
+/**
+ * Get the number of unique documents applying the GC delta removes from the index.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `gc_scan_delta` must be a valid, non NULL, pointer to a `GcScanDelta` instance.
+ */
+uint32_t GcScanDelta_UniqueDocsRemoved(const struct InvertedIndexGcDelta *gc_scan_delta);
+
 /**
  * Get ID of the first document in the index block. This is used by some C tests.
  *
@@ -573,6 +628,19 @@ IndexFlags IndexReader_Flags(const struct IndexReader *ir);
  */
 const NumericFilter *IndexReader_NumericFilter(const struct IndexReader *ir);
 
+/**
+ * Get the number of blocks the index reader skipped by their value range and the number it
+ * decoded so far, for the profile. Returns false, leaving the output parameters untouched, if the
+ * reader doesn't skip blocks, which is the case of all but the numeric range readers.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `ir` must be a valid, non NULL, pointer to an `IndexReader` instance.
+ * - `skipped` and `decoded` must be valid, non NULL, pointers to a `u64`.
+ */
+bool IndexReader_PruningStats(const struct IndexReader *ir, uint64_t *skipped, uint64_t *decoded);
+
 /**
  * Swap the inverted index of the reader with the given inverted index. This is only used by some
  * C tests to trigger revalidation on the reader.
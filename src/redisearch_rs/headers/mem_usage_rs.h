@@ -0,0 +1,87 @@
+#pragma once
+
+/* Warning, this file is autogenerated by cbindgen from `src/redisearch_rs/c_entrypoint/mem_usage_ffi/build.rs. Don't modify it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * A kind of structure of an index.
+ */
+typedef enum MemStructure {
+  MemStructure_TermTrie,
+  MemStructure_Postings,
+  MemStructure_Offsets,
+  MemStructure_NumericTree,
+  MemStructure_TagTrie,
+  MemStructure_DocTable,
+  MemStructure_Sortables,
+  MemStructure_VectorGraph,
+  MemStructure_SuffixTrie,
+} MemStructure;
+
+/**
+ * The memory used by the structures of an index, in bytes.
+ */
+typedef struct MemUsage MemUsage;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Create an empty memory usage. It must be freed using [`MemUsage_Free`].
+ */
+struct MemUsage *MemUsage_New(void);
+
+/**
+ * Free the memory usage created using [`MemUsage_New`].
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `usage` must be a valid, non NULL, pointer created using [`MemUsage_New`].
+ */
+void MemUsage_Free(struct MemUsage *usage);
+
+/**
+ * Add `bytes` used by `structure`. `instance` is the field or encoding of the
+ * structures with several instances, and is ignored for the others.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `usage` must be a valid, non NULL, pointer created using [`MemUsage_New`].
+ * - `instance` must be valid for reads of `instance_len` bytes. It may be NULL if `instance_len` is 0.
+ */
+void MemUsage_Record(struct MemUsage *usage,
+                     enum MemStructure structure,
+                     const char *instance,
+                     uintptr_t instance_len,
+                     uintptr_t bytes);
+
+/**
+ * Reply the memory usage to `FT.DEBUG MEMUSAGE`, as the entries of the map
+ * `reply` is in: the total, then the bytes of every structure and, for the
+ * structures with several instances, their breakdown.
+ *
+ * `map` opens a nested map under a key, closed by `map_end`. `int_kv` adds an
+ * entry to the current map. They're all called with `reply`.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `usage` must be a valid, non NULL, pointer created using [`MemUsage_New`].
+ * - The callbacks must be safe to call with `reply` and a NUL-terminated key.
+ */
+void MemUsage_Reply(const struct MemUsage *usage,
+                    void *reply,
+                    void (*map)(void *reply, const char *key),
+                    void (*map_end)(void *reply),
+                    void (*int_kv)(void *reply, const char *key, int64_t value));
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
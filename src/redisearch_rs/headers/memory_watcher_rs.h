@@ -0,0 +1,64 @@
+#pragma once
+
+/* Warning, this file is autogenerated by cbindgen from `src/redisearch_rs/c_entrypoint/memory_watcher_ffi/build.rs. Don't modify it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The gap between the pause and the resume thresholds, in percents of
+ * `maxmemory`.
+ */
+#define MEMORY_WATCHER_HYSTERESIS_PERCENT 10
+
+/**
+ * The state of the memory watcher, for `FT.INFO`.
+ */
+typedef struct MemoryWatcherInfo {
+  /**
+   * Whether background work is paused.
+   */
+  bool paused;
+  /**
+   * How many times background work was paused since startup.
+   */
+  uint64_t pause_count;
+  /**
+   * The time spent paused since startup, including the current pause.
+   */
+  uint64_t paused_time_ms;
+} MemoryWatcherInfo;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Feed the watcher with a reading of the `used` memory and `max_memory`, in
+ * bytes, a `max_memory` of 0 meaning unlimited. Work pauses above
+ * `pause_percent` of `max_memory`, 0 disabling the watcher.
+ *
+ * Returns whether background work is paused.
+ */
+bool MemoryWatcher_Poll(uint8_t pause_percent, uint64_t used, uint64_t max_memory);
+
+/**
+ * Block the calling background thread while work is paused, for at most
+ * `timeout_ms` milliseconds. It must not hold the GIL, so that the server
+ * can free memory meanwhile.
+ *
+ * Returns whether work may proceed, `false` if the timeout elapsed while
+ * still paused.
+ */
+bool MemoryWatcher_WaitWhilePaused(uint64_t timeout_ms);
+
+/**
+ * The state of the watcher.
+ */
+struct MemoryWatcherInfo MemoryWatcher_Info(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
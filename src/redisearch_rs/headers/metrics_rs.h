@@ -0,0 +1,68 @@
+#pragma once
+
+/* Warning, this file is autogenerated by cbindgen from `src/redisearch_rs/c_entrypoint/metrics_ffi/build.rs. Don't modify it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The number of indexes with their own `index` label value.
+ */
+#define METRICS_INDEX_LABEL_LIMIT 64
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Account for a query on the index `index`, which took `duration_ns`
+ * nanoseconds.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `index` must be valid for reads of `index_len` bytes. It may be NULL if `index_len` is 0.
+ */
+void Metrics_QueryExecuted(const char *index, uintptr_t index_len, uint64_t duration_ns);
+
+/**
+ * Set the gauges of the index `index`: its number of documents and of
+ * inverted index records.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `index` must be valid for reads of `index_len` bytes. It may be NULL if `index_len` is 0.
+ */
+void Metrics_SetIndexGauges(const char *index,
+                            uintptr_t index_len,
+                            uint64_t num_docs,
+                            uint64_t num_records);
+
+/**
+ * Drop the series of the dropped index `index`.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `index` must be valid for reads of `index_len` bytes. It may be NULL if `index_len` is 0.
+ */
+void Metrics_RemoveIndex(const char *index, uintptr_t index_len);
+
+/**
+ * Reply the metrics to `FT._METRICS`, in the Prometheus text exposition
+ * format: `text` is called once with `reply` and the rendered text, which
+ * isn't NUL-terminated and is only valid during the call.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `text` must be safe to call with `reply`.
+ */
+void Metrics_Reply(void *reply, void (*text)(void *reply, const char *text, uintptr_t len));
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
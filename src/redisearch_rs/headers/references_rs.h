@@ -0,0 +1,210 @@
+#pragma once
+
+/* Warning, this file is autogenerated by cbindgen from `src/redisearch_rs/c_entrypoint/references_ffi/build.rs. Don't modify it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The object shared by references, which `rm` points to.
+ */
+typedef struct RefManager RefManager;
+
+/**
+ * A strong reference, keeping the object alive. `rm` is NULL for an invalid
+ * reference, which doesn't need to be released.
+ */
+typedef struct StrongRef {
+  struct RefManager *rm;
+} StrongRef;
+
+/**
+ * Frees the object of a reference, once its last strong reference is
+ * released.
+ */
+typedef void (*RefManager_Free)(void *obj);
+
+/**
+ * A weak reference, which doesn't keep the object alive.
+ */
+typedef struct WeakRef {
+  struct RefManager *rm;
+} WeakRef;
+
+/**
+ * The reference counts of an object, for `FT.DEBUG SPECREFS`.
+ */
+typedef struct RefCounts {
+  uintptr_t strong;
+  uintptr_t weak;
+  bool invalid;
+} RefCounts;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Create the first strong reference to `obj`, freed using `free_cb` once the
+ * last strong reference is released. Called through the `StrongRef_New`
+ * macro, with the location it's called from.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `free_cb` must be safe to call with `obj`, from any thread.
+ * - `file` must be a valid, non NULL, NUL-terminated string, which lives until the end of the
+ *   program, such as `__FILE__`.
+ */
+struct StrongRef StrongRef_NewAt(void *obj,
+                                 RefManager_Free free_cb,
+                                 const char *file,
+                                 uint32_t line);
+
+/**
+ * Clone a strong reference. The returned one is invalid if the object was
+ * invalidated. Called through the `StrongRef_Clone` macro, with the location
+ * it's called from.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `s_ref` must be a valid strong reference, or an invalid one.
+ * - `file` must be a valid, non NULL, NUL-terminated string, which lives until the end of the
+ *   program, such as `__FILE__`.
+ */
+struct StrongRef StrongRef_CloneAt(struct StrongRef s_ref, const char *file, uint32_t line);
+
+/**
+ * Get a strong reference from a weak one. The returned one is invalid if the
+ * object was freed or invalidated. The weak reference still needs to be
+ * released. Called through the `WeakRef_Promote` macro, with the location it's
+ * called from.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `w_ref` must be a valid weak reference.
+ * - `file` must be a valid, non NULL, NUL-terminated string, which lives until the end of the
+ *   program, such as `__FILE__`.
+ */
+struct StrongRef WeakRef_PromoteAt(struct WeakRef w_ref, const char *file, uint32_t line);
+
+/**
+ * Get a weak reference from a strong one. The strong reference still needs to
+ * be released.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `s_ref` must be a valid, non invalid, strong reference.
+ */
+struct WeakRef StrongRef_Demote(struct StrongRef s_ref);
+
+/**
+ * Clone a weak reference.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `w_ref` must be a valid weak reference.
+ */
+struct WeakRef WeakRef_Clone(struct WeakRef w_ref);
+
+/**
+ * Release a weak reference.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `w_ref` must be a valid weak reference. It must not be used after this call.
+ */
+void WeakRef_Release(struct WeakRef w_ref);
+
+/**
+ * Release a strong reference, freeing the object if it was the last one.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `s_ref` must be a valid, non invalid, strong reference. It must not be used after this call.
+ */
+void StrongRef_Release(struct StrongRef s_ref);
+
+/**
+ * Get the object of a strong reference, or NULL if the reference is invalid.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `s_ref` must be a valid strong reference, or an invalid one.
+ */
+void *StrongRef_Get(struct StrongRef s_ref);
+
+/**
+ * Invalidate the object: no new strong reference can be created from then on.
+ * The existing ones remain usable, and still need to be released.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `s_ref` must be a valid, non invalid, strong reference.
+ */
+void StrongRef_Invalidate(struct StrongRef s_ref);
+
+/**
+ * Whether both strong references are to the same object, or both invalid.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `s_ref` and `other` must be valid strong references, or invalid ones.
+ */
+bool StrongRef_Equals(struct StrongRef s_ref, struct StrongRef other);
+
+/**
+ * Get the object of a reference, or NULL if it was freed or invalidated. For
+ * LLAPI and tests only, which hold the `rm` of references.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `rm` must be NULL or the `rm` of a valid reference.
+ */
+void *__RefManager_Get_Object(struct RefManager *rm);
+
+/**
+ * Get the reference counts of the object of a strong reference.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `s_ref` must be a valid, non invalid, strong reference.
+ */
+struct RefCounts StrongRef_Counts(struct StrongRef s_ref);
+
+/**
+ * Call `cb` with each live strong reference to the object of `s_ref`, oldest
+ * first: its id and the location it was created at, `file` being `file_len`
+ * bytes long and not NUL-terminated. Only debug builds track them: `cb` isn't
+ * called in release builds.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `s_ref` must be a valid, non invalid, strong reference.
+ * - `cb` must be safe to call with `ctx`.
+ */
+void StrongRef_ForEachLive(struct StrongRef s_ref,
+                           void *ctx,
+                           void (*cb)(void *ctx,
+                                      uint64_t id,
+                                      const char *file,
+                                      uintptr_t file_len,
+                                      uint32_t line));
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
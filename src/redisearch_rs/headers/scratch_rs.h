@@ -0,0 +1,33 @@
+#pragma once
+
+/* Warning, this file is autogenerated by cbindgen from `src/redisearch_rs/c_entrypoint/scratch_ffi/build.rs. Don't modify it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Reply the memory held by the scratch buffers of every thread which used
+ * them, in the order the threads first did: `thread` is called with `reply`
+ * for each of them, with its name and the bytes its buffers hold. The name
+ * isn't NUL-terminated, and is only valid during the call.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `thread` must be safe to call with `reply`.
+ */
+void Scratch_ReplyThreads(void *reply,
+                          void (*thread)(void *reply,
+                                         const char *name,
+                                         uintptr_t name_len,
+                                         uintptr_t bytes));
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
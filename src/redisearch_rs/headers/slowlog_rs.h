@@ -0,0 +1,104 @@
+#pragma once
+
+/* Warning, this file is autogenerated by cbindgen from `src/redisearch_rs/c_entrypoint/slowlog_ffi/build.rs. Don't modify it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * A subcommand of `FT._SLOWLOG`.
+ */
+typedef enum SlowLogSubcommand {
+  SlowLogSubcommand_Get,
+  SlowLogSubcommand_Len,
+  SlowLogSubcommand_Reset,
+} SlowLogSubcommand;
+
+/**
+ * The slow log of an index. Queries are recorded by the threads running them
+ * while `FT._SLOWLOG` reads the log from the main thread, hence the log locks
+ * its entries.
+ */
+typedef struct SlowLog SlowLog;
+
+/**
+ * A `PARAMS` argument of a query. The strings needn't be NUL-terminated.
+ */
+typedef struct SlowLogParam {
+  const char *name;
+  uintptr_t name_len;
+  const char *value;
+  uintptr_t value_len;
+} SlowLogParam;
+
+/**
+ * The time spent in each phase of a query, in nanoseconds.
+ */
+typedef struct SlowLogTimings {
+  uint64_t parse_ns;
+  uint64_t execute_ns;
+  uint64_t load_ns;
+  uint64_t serialize_ns;
+} SlowLogTimings;
+
+/**
+ * A recorded query, as passed to the reply callbacks. `query` isn't
+ * NUL-terminated, and is only valid during the callback.
+ */
+typedef struct SlowLogEntryInfo {
+  uint64_t id;
+  /**
+   * When the query was recorded, in seconds since the Unix epoch.
+   */
+  uint64_t timestamp;
+  const char *query;
+  uintptr_t query_len;
+  struct SlowLogTimings timings;
+  uint64_t total_results;
+  uint64_t returned_results;
+  uintptr_t num_params;
+} SlowLogEntryInfo;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+struct SlowLog *SlowLog_New(uint64_t threshold_us, uintptr_t max_len, bool redact_params);
+
+void SlowLog_Free(struct SlowLog *log);
+
+bool SlowLog_Record(const struct SlowLog *log,
+                    const char *query,
+                    uintptr_t query_len,
+                    const struct SlowLogParam *params,
+                    uintptr_t num_params,
+                    struct SlowLogTimings timings,
+                    uint64_t total_results,
+                    uint64_t returned_results,
+                    uint64_t timestamp);
+
+uintptr_t SlowLog_Len(const struct SlowLog *log);
+
+void SlowLog_Reset(const struct SlowLog *log);
+
+void SlowLog_Reply(const struct SlowLog *log,
+                   uintptr_t count,
+                   void *reply,
+                   void (*entry)(void *reply, const struct SlowLogEntryInfo *info),
+                   void (*param)(void *reply,
+                                 const char *name,
+                                 uintptr_t name_len,
+                                 const char *value,
+                                 uintptr_t value_len),
+                   void (*entry_end)(void *reply));
+
+bool SlowLog_ParseCommand(const char *const *argv,
+                          uintptr_t argc,
+                          enum SlowLogSubcommand *subcommand,
+                          uintptr_t *count);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
@@ -185,6 +185,33 @@ uintptr_t TrieMap_NUniqueKeys(struct TrieMap *t);
  */
 uintptr_t TrieMap_NNodes(struct TrieMap *t);
 
+/**
+ * Relocate the nodes of the triemap during active defragmentation, starting
+ * at node `*cursor`.
+ *
+ * `alloc` and `should_stop` are `RedisModule_DefragAlloc` and
+ * `RedisModule_DefragShouldStop`, called with `ctx`. Returns `true` once the
+ * whole trie has been processed, resetting `*cursor` to 0. Returns `false`
+ * if `should_stop` interrupted the pass, in which case the call must be
+ * repeated later with the same cursor.
+ *
+ * The values aren't relocated, since the trie doesn't own them.
+ *
+ * # Safety
+ *
+ * The following invariants must be upheld when calling this function:
+ * - `t` must point to a valid TrieMap obtained from [`NewTrieMap`] and cannot be NULL.
+ * - No iterator over `t` may be alive.
+ * - `cursor` must be a valid, non NULL, pointer to a cursor, initially 0.
+ * - `alloc` must behave like `RedisModule_DefragAlloc`, and both callbacks must be safe to
+ *   call with `ctx`.
+ */
+bool TrieMap_Defrag(struct TrieMap *t,
+                    uintptr_t *cursor,
+                    void *ctx,
+                    void *(*alloc)(void *ctx, void *ptr),
+                    int (*should_stop)(void *ctx));
+
 /**
  * Find nodes that have a given prefix. Results are placed in an array.
  * The `results` buffer is initialized by this function using the Redis allocator
@@ -0,0 +1,101 @@
+#pragma once
+
+/* Warning, this file is autogenerated by cbindgen from `src/redisearch_rs/c_entrypoint/zstr_ffi/build.rs. Don't modify it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Frees a string handed over with [`RawZStr`].
+ *
+ * It receives the `handle` and the bytes of the string: the destructor of an
+ * `sds` only needs the handle, the one of a Rust buffer only the bytes.
+ */
+typedef void (*ReleaseFn)(void *handle, const uint8_t *ptr, uintptr_t len);
+
+/**
+ * A [`ZStr`] handed over to C.
+ *
+ * Whoever holds it must eventually call `release(handle, ptr, len)` if
+ * `release` isn't NULL, or turn it back into a [`ZStr`] with
+ * [`ZStr::from_raw`].
+ */
+typedef struct RawZStr {
+  const uint8_t *ptr;
+  uintptr_t len;
+  void *handle;
+  /**
+   * NULL if the bytes don't need freeing, e.g. for a static string.
+   */
+  ReleaseFn release;
+} RawZStr;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Borrow the `len` bytes at `ptr`, e.g. the contents of a
+ * `RedisModuleString` or an `sds` which outlive the returned string. Nothing
+ * is copied, and releasing it doesn't free the bytes.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `ptr` must be valid for reads of `len` bytes, and may only be NULL if
+ *   `len` is 0. The bytes must not be modified or freed while the returned
+ *   string is in use.
+ */
+struct RawZStr ZStr_Borrow(const char *ptr, uintptr_t len);
+
+/**
+ * Copy the `len` bytes at `ptr` into a string owning them.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `ptr` must be valid for reads of `len` bytes, and may only be NULL if
+ *   `len` is 0.
+ */
+struct RawZStr ZStr_Copy(const char *ptr, uintptr_t len);
+
+/**
+ * Turn `s` into a string owning its bytes, which may outlive the bytes it
+ * borrowed. Only a borrowed string is copied.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `s` must be valid as described by [`RawZStr`], and the caller gives up
+ *   its ownership.
+ */
+struct RawZStr ZStr_IntoOwned(struct RawZStr s);
+
+/**
+ * A string owning `prefix` followed by the bytes of `s`, as sent for the
+ * sort keys and required fields of a shard reply (`$value`).
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `s` must be valid as described by [`RawZStr`], and the caller gives up
+ *   its ownership.
+ */
+struct RawZStr ZStr_Prefix(char prefix, struct RawZStr s);
+
+/**
+ * Release `s`, freeing its bytes if it owns them.
+ *
+ * # Safety
+ *
+ * The following invariant must be upheld when calling this function:
+ * - `s` must be valid as described by [`RawZStr`], and the caller gives up
+ *   its ownership.
+ */
+void ZStr_Release(struct RawZStr s);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
@@ -0,0 +1,262 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Highlighting of the matched terms in the fields returned by a query, the
+//! Rust port of `highlight_processor.c` and `fragmenter.c`.
+//!
+//! The matched terms are located by their positions, from the offset vectors
+//! of the query results, rather than by re-matching the query terms against
+//! the text: that way, stems, synonyms and phonetic matches are highlighted
+//! exactly as they were matched. The field is re-tokenized with the analyzer
+//! of the field to map positions to bytes of the text.
+//!
+//! # Multi-value fields
+//!
+//! TEXT fields indexed from JSON arrays hold several values. The indexer
+//! leaves a gap of `MULTI_TEXT_SLOP` positions between values, so that phrases
+//! don't match across them; the highlighter lays positions out the same way,
+//! then highlights each value on its own and replies them as an array. TAG
+//! lists are highlighted by value: a tag matching the query is wrapped as a
+//! whole.
+//!
+//! # Phrases
+//!
+//! The occurrences of a quoted phrase are wrapped in a single pair of tags,
+//! from its first term to its last, rather than term by term, so that UIs
+//! render `"<b>new york</b>"` rather than `"<b>new</b> <b>york</b>"`. They
+//! are given by the positions of their first and last terms, as found by the
+//! phrase iterator while intersecting the offset vectors of the terms; see
+//! [`exact_phrase_occurrences`].
+
+pub mod summarize;
+
+use std::ops::{Range, RangeInclusive};
+
+use analysis::{Token, Tokenizer};
+
+/// The default gap between the positions of the values of a multi-value
+/// field, the default of `MULTI_TEXT_SLOP`.
+pub const DEFAULT_MULTI_TEXT_SLOP: u32 = 100;
+
+/// The strings wrapped around matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightTags {
+    pub open: String,
+    pub close: String,
+}
+
+impl Default for HighlightTags {
+    fn default() -> Self {
+        Self {
+            open: "<b>".to_owned(),
+            close: "</b>".to_owned(),
+        }
+    }
+}
+
+/// The value of a field, as loaded from the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldValue<'a> {
+    Single(&'a str),
+    /// The values of a JSON array, in order.
+    Multi(&'a [&'a str]),
+}
+
+/// A highlighted field, keeping the boundaries of multi-value fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Highlighted {
+    Single(String),
+    Multi(Vec<String>),
+}
+
+/// What a query result matched in a field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Matches<'a> {
+    /// The positions of the matched terms.
+    pub positions: &'a [u32],
+    /// The positions of the first and last terms of every phrase occurrence.
+    pub phrases: &'a [RangeInclusive<u32>],
+}
+
+impl<'a> Matches<'a> {
+    /// Matched terms, outside of phrases.
+    pub const fn terms(positions: &'a [u32]) -> Self {
+        Self {
+            positions,
+            phrases: &[],
+        }
+    }
+
+    /// Whether the token at `position` is part of a match.
+    pub fn contains(&self, position: u32) -> bool {
+        self.positions.contains(&position) || self.phrases.iter().any(|p| p.contains(&position))
+    }
+
+    /// The bytes to highlight among `tokens`: a range per matched term, and a
+    /// single range per phrase occurrence, spanning the text between its
+    /// terms.
+    pub fn ranges(&self, tokens: &[Token]) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = self
+            .phrases
+            .iter()
+            .filter_map(|phrase| {
+                let first = tokens.iter().find(|t| t.position == *phrase.start())?;
+                let last = tokens.iter().find(|t| t.position == *phrase.end())?;
+                Some(first.offset.start..last.offset.end)
+            })
+            .collect();
+        ranges.extend(
+            tokens
+                .iter()
+                .filter(|t| self.positions.contains(&t.position))
+                .map(|t| t.offset.clone()),
+        );
+        ranges
+    }
+}
+
+/// The occurrences of an exact phrase, given the sorted positions of each of
+/// its terms: the intersection the phrase iterator computes from the offset
+/// vectors of the terms.
+pub fn exact_phrase_occurrences(terms: &[&[u32]]) -> Vec<RangeInclusive<u32>> {
+    let Some((first, rest)) = terms.split_first() else {
+        return Vec::new();
+    };
+    first
+        .iter()
+        .filter(|&&start| {
+            rest.iter()
+                .zip(1..)
+                .all(|(positions, i)| positions.binary_search(&(start + i)).is_ok())
+        })
+        .map(|&start| start..=start + rest.len() as u32)
+        .collect()
+}
+
+/// Wrap the byte `ranges` of `text` in `tags`. Overlapping or adjacent
+/// ranges are wrapped in a single pair of tags.
+pub fn mark(text: &str, ranges: &[Range<usize>], tags: &HighlightTags) -> String {
+    let mut ranges = ranges.to_vec();
+    ranges.sort_unstable_by_key(|r| r.start);
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut iter = ranges.into_iter().peekable();
+    while let Some(mut range) = iter.next() {
+        while let Some(next) = iter.next_if(|next| next.start <= range.end) {
+            range.end = range.end.max(next.end);
+        }
+        // Ranges overlapping a previous one were merged into it.
+        let start = range.start.max(copied);
+        if start >= range.end {
+            continue;
+        }
+        out.push_str(&text[copied..start]);
+        out.push_str(&tags.open);
+        out.push_str(&text[start..range.end]);
+        out.push_str(&tags.close);
+        copied = range.end;
+    }
+    out.push_str(&text[copied..]);
+    out
+}
+
+/// Highlights the fields of a query result.
+pub struct Highlighter<'a> {
+    tokenizer: &'a dyn Tokenizer,
+    tags: &'a HighlightTags,
+    multi_text_slop: u32,
+}
+
+impl<'a> Highlighter<'a> {
+    pub const fn new(tokenizer: &'a dyn Tokenizer, tags: &'a HighlightTags) -> Self {
+        Self {
+            tokenizer,
+            tags,
+            multi_text_slop: DEFAULT_MULTI_TEXT_SLOP,
+        }
+    }
+
+    /// Use the `MULTI_TEXT_SLOP` the field was indexed with.
+    pub const fn with_multi_text_slop(mut self, slop: u32) -> Self {
+        self.multi_text_slop = slop;
+        self
+    }
+
+    /// The tokens of every value of a field, with the positions the indexer
+    /// gave them.
+    pub fn tokens(&self, values: &[&str]) -> Vec<Vec<Token>> {
+        // Mirrors `multiTextOffsetDelta` in `document.c`: the first position
+        // of a value is `MULTI_TEXT_SLOP` after the last one of the previous
+        // value.
+        let gap = if values.len() > 1 {
+            self.multi_text_slop.saturating_sub(1)
+        } else {
+            0
+        };
+        let mut base = 0;
+        values
+            .iter()
+            .map(|value| {
+                let mut tokens = self.tokenizer.tokenize(value);
+                for token in &mut tokens {
+                    token.position += base;
+                }
+                base = tokens.last().map_or(base, |t| t.position) + gap;
+                tokens
+            })
+            .collect()
+    }
+
+    /// Highlight the `matches` in a TEXT field.
+    pub fn highlight(&self, value: FieldValue, matches: &Matches) -> Highlighted {
+        let single;
+        let values = match value {
+            FieldValue::Single(value) => {
+                single = [value];
+                &single[..]
+            }
+            FieldValue::Multi(values) => values,
+        };
+        let mut highlighted: Vec<String> = values
+            .iter()
+            .zip(self.tokens(values))
+            .map(|(value, tokens)| mark(value, &matches.ranges(&tokens), self.tags))
+            .collect();
+        match value {
+            FieldValue::Single(_) => Highlighted::Single(highlighted.remove(0)),
+            FieldValue::Multi(_) => Highlighted::Multi(highlighted),
+        }
+    }
+
+    /// Highlight the values of a TAG field equal to one of the `matched` tags.
+    pub fn highlight_tags(
+        &self,
+        values: &[&str],
+        matched: &[&str],
+        case_sensitive: bool,
+    ) -> Vec<String> {
+        values
+            .iter()
+            .map(|value| {
+                let is_match = matched.iter().any(|tag| {
+                    if case_sensitive {
+                        tag == value
+                    } else {
+                        tag.to_lowercase() == value.to_lowercase()
+                    }
+                });
+                if is_match {
+                    format!("{}{value}{}", self.tags.open, self.tags.close)
+                } else {
+                    (*value).to_owned()
+                }
+            })
+            .collect()
+    }
+}
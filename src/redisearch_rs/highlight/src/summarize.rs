@@ -0,0 +1,214 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Summaries of the matched parts of a field, as returned with `SUMMARIZE`.
+//!
+//! The matches are grouped into fragments of about `LEN` tokens: matches less
+//! than `LEN` tokens apart go in the same fragment, which is then padded with
+//! context on both sides. Fragments are cut between tokens, never within a
+//! word, and preferably at a sentence or clause break: when the context holds
+//! one of the `BOUNDARIES` characters, the fragment starts after it or ends
+//! with it. The `FRAGS` fragments with the most matches are returned in
+//! document order, overlapping ones being merged, each followed by the
+//! `SEPARATOR`.
+
+use std::ops::Range;
+
+use analysis::Token;
+
+use crate::{Highlighter, Matches, mark};
+
+/// The default of `LEN`, `SUMMARIZE_FRAGSIZE_DEFAULT` in C.
+pub const DEFAULT_FRAGMENT_LEN: usize = 20;
+/// The default of `FRAGS`, `SUMMARIZE_FRAGCOUNT_DEFAULT` in C.
+pub const DEFAULT_FRAGMENTS: usize = 3;
+/// The default of `SEPARATOR`.
+pub const DEFAULT_SEPARATOR: &str = "... ";
+/// The default of `BOUNDARIES`.
+pub const DEFAULT_BOUNDARIES: &str = ".!?;";
+
+const BOUNDARIES_OPT: &str = "BOUNDARIES";
+
+/// The options of `SUMMARIZE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SummarizeOptions {
+    /// The maximum number of fragments (`FRAGS`).
+    pub fragments: usize,
+    /// The number of tokens of a fragment (`LEN`).
+    pub len: usize,
+    /// Appended to every fragment (`SEPARATOR`).
+    pub separator: String,
+    /// The characters fragments preferably start after or end with
+    /// (`BOUNDARIES`). Empty to cut at any token.
+    pub boundaries: String,
+}
+
+impl Default for SummarizeOptions {
+    fn default() -> Self {
+        Self {
+            fragments: DEFAULT_FRAGMENTS,
+            len: DEFAULT_FRAGMENT_LEN,
+            separator: DEFAULT_SEPARATOR.to_owned(),
+            boundaries: DEFAULT_BOUNDARIES.to_owned(),
+        }
+    }
+}
+
+impl SummarizeOptions {
+    /// Try to handle the `SUMMARIZE` option `name`, taking its argument from
+    /// `args`. Returns `false` if `name` isn't handled here, or if its
+    /// argument is missing.
+    ///
+    /// `FRAGS`, `LEN` and `SEPARATOR` are still parsed by `summarize_spec.c`.
+    pub fn try_parse_option<'a>(
+        &mut self,
+        name: &str,
+        args: &mut impl Iterator<Item = &'a str>,
+    ) -> bool {
+        if !name.eq_ignore_ascii_case(BOUNDARIES_OPT) {
+            return false;
+        }
+        let Some(boundaries) = args.next() else {
+            return false;
+        };
+        boundaries.clone_into(&mut self.boundaries);
+        true
+    }
+
+    fn is_boundary(&self, c: char) -> bool {
+        self.boundaries.contains(c)
+    }
+}
+
+/// A fragment being built: a range of tokens around a group of matches.
+struct Fragment {
+    /// Indexes of the first and last matched tokens.
+    first: usize,
+    last: usize,
+    matches: usize,
+}
+
+impl Highlighter<'_> {
+    /// Summarize `text`, highlighting the `matches`.
+    ///
+    /// Without matches, the summary is the beginning of the text.
+    pub fn summarize(&self, text: &str, matches: &Matches, options: &SummarizeOptions) -> String {
+        let tokens = self.tokenizer.tokenize(text);
+        if tokens.is_empty() {
+            return String::new();
+        }
+        let len = options.len.max(1);
+        let mut fragments: Vec<Fragment> = Vec::new();
+        scratch::with_offsets(|matched| {
+            matched.extend(
+                (0..tokens.len() as u32).filter(|&i| matches.contains(tokens[i as usize].position)),
+            );
+            for &i in matched.iter() {
+                let i = i as usize;
+                match fragments.last_mut() {
+                    Some(fragment) if i - fragment.first < len => {
+                        fragment.last = i;
+                        fragment.matches += 1;
+                    }
+                    _ => fragments.push(Fragment {
+                        first: i,
+                        last: i,
+                        matches: 1,
+                    }),
+                }
+            }
+        });
+        if fragments.is_empty() {
+            fragments.push(Fragment {
+                first: 0,
+                last: 0,
+                matches: 0,
+            });
+        }
+
+        // The best fragments, in document order.
+        fragments.sort_by(|a, b| b.matches.cmp(&a.matches).then(a.first.cmp(&b.first)));
+        fragments.truncate(options.fragments.max(1));
+        fragments.sort_by_key(|f| f.first);
+
+        let mut spans: Vec<Range<usize>> = Vec::with_capacity(fragments.len());
+        for fragment in &fragments {
+            let span = span(text, &tokens, fragment, len, options);
+            match spans.last_mut() {
+                Some(previous) if span.start <= previous.end => {
+                    previous.end = previous.end.max(span.end);
+                }
+                _ => spans.push(span),
+            }
+        }
+
+        let highlighted = matches.ranges(&tokens);
+        let mut summary = String::new();
+        for span in spans {
+            let ranges: Vec<Range<usize>> = highlighted
+                .iter()
+                .filter(|offset| offset.start >= span.start && offset.end <= span.end)
+                .map(|offset| offset.start - span.start..offset.end - span.start)
+                .collect();
+            summary.push_str(&mark(&text[span], &ranges, self.tags));
+            summary.push_str(&options.separator);
+        }
+        summary
+    }
+}
+
+/// The bytes of `text` covered by `fragment`, padded with context.
+///
+/// The context is added a token at a time, alternately on the right and on
+/// the left, until the fragment is `len` tokens long. A side stops growing at
+/// a boundary, which is kept at the end of the fragment and left out at its
+/// start, so that context the fragment can't use on one side goes to the
+/// other.
+fn span(
+    text: &str,
+    tokens: &[Token],
+    fragment: &Fragment,
+    len: usize,
+    options: &SummarizeOptions,
+) -> Range<usize> {
+    // The first boundary between tokens `i` and `i + 1`, if any.
+    let boundary_after = |i: usize| {
+        let from = tokens[i].offset.end;
+        let to = tokens.get(i + 1).map_or(text.len(), |t| t.offset.start);
+        text[from..to]
+            .char_indices()
+            .find(|&(_, c)| options.is_boundary(c))
+            .map(|(at, c)| from + at + c.len_utf8())
+    };
+
+    let (mut left, mut right) = (fragment.first, fragment.last);
+    let mut budget = len.saturating_sub(right - left + 1);
+    let mut left_open = true;
+    let mut right_open = true;
+    while budget > 0 && (left_open || right_open) {
+        if right_open {
+            if right + 1 < tokens.len() && boundary_after(right).is_none() {
+                right += 1;
+                budget -= 1;
+            } else {
+                right_open = false;
+            }
+        }
+        if left_open && budget > 0 {
+            if left > 0 && boundary_after(left - 1).is_none() {
+                left -= 1;
+                budget -= 1;
+            } else {
+                left_open = false;
+            }
+        }
+    }
+    let end = boundary_after(right).unwrap_or(tokens[right].offset.end);
+    tokens[left].offset.start..end
+}
@@ -0,0 +1,118 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use analysis::tokenizer::StandardTokenizer;
+use highlight::{
+    FieldValue, HighlightTags, Highlighted, Highlighter, Matches, exact_phrase_occurrences, mark,
+};
+
+#[test]
+fn test_mark() {
+    let tags = HighlightTags::default();
+    assert_eq!(
+        mark("hello world", &[6..11, 0..5], &tags),
+        "<b>hello</b> <b>world</b>"
+    );
+    // Overlapping and adjacent ranges share a pair of tags.
+    assert_eq!(mark("abcdef", &[0..2, 1..3, 3..4], &tags), "<b>abcd</b>ef");
+    assert_eq!(mark("abc", &[], &tags), "abc");
+}
+
+#[test]
+fn test_single_value() {
+    let tags = HighlightTags::default();
+    let highlighter = Highlighter::new(&StandardTokenizer, &tags);
+    assert_eq!(
+        highlighter.highlight(
+            FieldValue::Single("The quick, brown fox"),
+            &Matches::terms(&[2, 4])
+        ),
+        Highlighted::Single("The <b>quick</b>, brown <b>fox</b>".to_owned())
+    );
+}
+
+#[test]
+fn test_multi_value_positions() {
+    let tags = HighlightTags::default();
+    let highlighter = Highlighter::new(&StandardTokenizer, &tags).with_multi_text_slop(10);
+    let values = ["red apple", "green pear", "", "apple pie"];
+    let positions: Vec<Vec<u32>> = highlighter
+        .tokens(&values)
+        .iter()
+        .map(|tokens| tokens.iter().map(|t| t.position).collect())
+        .collect();
+    // Each value starts `MULTI_TEXT_SLOP` after the end of the previous one,
+    // empty values included.
+    assert_eq!(positions, [vec![1, 2], vec![12, 13], vec![], vec![32, 33]]);
+
+    // Positions 2 and 32 are "apple" in the first and last values.
+    assert_eq!(
+        highlighter.highlight(FieldValue::Multi(&values), &Matches::terms(&[2, 32])),
+        Highlighted::Multi(vec![
+            "red <b>apple</b>".to_owned(),
+            "green pear".to_owned(),
+            String::new(),
+            "<b>apple</b> pie".to_owned(),
+        ])
+    );
+
+    // A single value isn't offset, even in an array.
+    assert_eq!(highlighter.tokens(&["a b"])[0].last().unwrap().position, 2);
+}
+
+#[test]
+fn test_tag_lists() {
+    let tags = HighlightTags {
+        open: "[".to_owned(),
+        close: "]".to_owned(),
+    };
+    let highlighter = Highlighter::new(&StandardTokenizer, &tags);
+    let values = ["Red", "blue", "green"];
+    assert_eq!(
+        highlighter.highlight_tags(&values, &["red", "green"], false),
+        ["[Red]", "blue", "[green]"]
+    );
+    assert_eq!(
+        highlighter.highlight_tags(&values, &["red"], true),
+        ["Red", "blue", "green"]
+    );
+}
+
+#[test]
+fn test_phrases() {
+    let tags = HighlightTags::default();
+    let highlighter = Highlighter::new(&StandardTokenizer, &tags);
+    let text = "From New  York to new york, via York";
+    // "new york" and "york"
+    let new = [2, 5];
+    let york = [3, 6, 8];
+    let phrases = exact_phrase_occurrences(&[&new, &york]);
+    assert_eq!(phrases, [2..=3, 5..=6]);
+
+    let matches = Matches {
+        positions: &[8],
+        phrases: &phrases,
+    };
+    assert_eq!(
+        highlighter.highlight(FieldValue::Single(text), &matches),
+        Highlighted::Single("From <b>New  York</b> to <b>new york</b>, via <b>York</b>".to_owned())
+    );
+    // A phrase term also matched on its own doesn't split the phrase.
+    let overlapping = Matches {
+        positions: &[3],
+        phrases: &phrases[..1],
+    };
+    assert_eq!(
+        highlighter.highlight(FieldValue::Single(text), &overlapping),
+        Highlighted::Single("From <b>New  York</b> to new york, via York".to_owned())
+    );
+
+    assert!(exact_phrase_occurrences(&[&new, &[4, 9]]).is_empty());
+    assert!(exact_phrase_occurrences(&[]).is_empty());
+}
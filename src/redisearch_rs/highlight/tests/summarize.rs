@@ -0,0 +1,141 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use analysis::tokenizer::StandardTokenizer;
+use highlight::{
+    HighlightTags, Highlighter, Matches,
+    summarize::{DEFAULT_BOUNDARIES, SummarizeOptions},
+};
+
+const TEXT: &str = "Redis is an in-memory store. It keeps data in memory for speed! \
+    Search adds secondary indexes to it; queries run on the indexes. \
+    Vectors are supported too.";
+
+fn options(len: usize, fragments: usize) -> SummarizeOptions {
+    SummarizeOptions {
+        len,
+        fragments,
+        ..Default::default()
+    }
+}
+
+/// The positions of the tokens of `TEXT` equal to one of `terms`.
+fn positions(terms: &[&str]) -> Vec<u32> {
+    use analysis::Tokenizer;
+    StandardTokenizer
+        .tokenize(TEXT)
+        .into_iter()
+        .filter(|t| terms.contains(&t.term.as_str()))
+        .map(|t| t.position)
+        .collect()
+}
+
+#[test]
+fn test_fragments_snap_to_boundaries() {
+    let tags = HighlightTags::default();
+    let highlighter = Highlighter::new(&StandardTokenizer, &tags);
+    // The context would start in the previous sentence, and end in the next
+    // clause.
+    assert_eq!(
+        highlighter.summarize(
+            TEXT,
+            &Matches::terms(&positions(&["speed"])),
+            &options(8, 3)
+        ),
+        "It keeps data in memory for <b>speed</b>!... "
+    );
+
+    // Without boundaries, fragments are cut at the token count, between words.
+    let no_boundaries = SummarizeOptions {
+        boundaries: String::new(),
+        ..options(4, 3)
+    };
+    assert_eq!(
+        highlighter.summarize(
+            TEXT,
+            &Matches::terms(&positions(&["speed"])),
+            &no_boundaries
+        ),
+        "for <b>speed</b>! Search adds... "
+    );
+}
+
+#[test]
+fn test_best_fragments_in_document_order() {
+    let tags = HighlightTags::default();
+    let highlighter = Highlighter::new(&StandardTokenizer, &tags);
+    // The fragment with two matches is kept, along with the earliest one with
+    // a single match, and they're returned in document order.
+    let matched = positions(&["redis", "indexes", "vectors"]);
+    assert_eq!(
+        highlighter.summarize(TEXT, &Matches::terms(&matched), &options(6, 2)),
+        "<b>Redis</b> is an in-memory store.... \
+         on the <b>indexes</b>. <b>Vectors</b> are supported... "
+    );
+    // Overlapping fragments are merged.
+    assert_eq!(
+        highlighter.summarize(
+            TEXT,
+            &Matches::terms(&positions(&["secondary", "to"])),
+            &options(12, 2)
+        ),
+        "Search adds <b>secondary</b> indexes <b>to</b> it;... "
+    );
+}
+
+#[test]
+fn test_without_matches() {
+    let tags = HighlightTags::default();
+    let highlighter = Highlighter::new(&StandardTokenizer, &tags);
+    assert_eq!(
+        highlighter.summarize(TEXT, &Matches::default(), &options(20, 3)),
+        "Redis is an in-memory store.... "
+    );
+    assert_eq!(
+        highlighter.summarize("", &Matches::default(), &options(20, 3)),
+        ""
+    );
+}
+
+#[test]
+fn test_parse_boundaries() {
+    let mut options = SummarizeOptions::default();
+    assert_eq!(options.boundaries, DEFAULT_BOUNDARIES);
+    let mut args = ["|,"].into_iter();
+    assert!(options.try_parse_option("boundaries", &mut args));
+    assert_eq!(options.boundaries, "|,");
+    assert!(!options.try_parse_option("LEN", &mut ["3"].into_iter()));
+    assert!(!options.try_parse_option("BOUNDARIES", &mut std::iter::empty()));
+}
+
+#[test]
+fn test_fragment_length_is_bounded() {
+    let tags = HighlightTags::default();
+    let highlighter = Highlighter::new(&StandardTokenizer, &tags);
+    // Matches a couple of tokens apart don't chain into one fragment longer
+    // than `len`.
+    let text = "cat dog cat dog cat dog cat dog cat";
+    let matched: Vec<u32> = {
+        use analysis::Tokenizer;
+        StandardTokenizer
+            .tokenize(text)
+            .into_iter()
+            .filter(|t| t.term == "cat")
+            .map(|t| t.position)
+            .collect()
+    };
+    let no_boundaries = SummarizeOptions {
+        boundaries: String::new(),
+        ..options(3, 1)
+    };
+    assert_eq!(
+        highlighter.summarize(text, &Matches::terms(&matched), &no_boundaries),
+        "<b>cat</b> dog <b>cat</b>... "
+    );
+}
@@ -0,0 +1,325 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Index lifecycle events.
+//!
+//! Producers (index creation, the indexer, GC, the background scanner)
+//! [publish](EventBus::publish) [`IndexEvent`]s to an [`EventBus`]. Publishing
+//! never blocks on subscribers: events are appended to a bounded queue, which is
+//! drained by whoever owns the bus calling [`EventBus::dispatch_pending`]
+//! (typically the main thread, from an event-loop timer).
+//!
+//! # Ordering
+//!
+//! Every event gets a sequence number when it's published. Subscribers receive
+//! events in sequence order, and for a given event, subscribers are invoked in
+//! the order they subscribed. When the queue is full, new events are dropped
+//! and counted; subscribers can detect the gap from the sequence numbers.
+//!
+//! Producers on hot paths, such as the indexer, should check
+//! [`EventBus::has_subscribers`] before building an event, so that nothing is
+//! allocated while nobody listens.
+//!
+//! # Re-entrancy
+//!
+//! Subscribers may publish events, (un)subscribe, and call
+//! [`EventBus::dispatch_pending`] from their callback. A re-entrant dispatch
+//! returns right away without delivering anything: the events published in
+//! the meantime stay queued, and are delivered by the next top-level call.
+//!
+//! Clients can subscribe to events through [RESP3 push messages](push).
+
+pub mod push;
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Mutex, MutexGuard, RwLock,
+        atomic::{AtomicU8, Ordering},
+    },
+    thread::{self, ThreadId},
+};
+
+/// Statistics of a completed GC cycle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcCycleStats {
+    pub bytes_collected: u64,
+    pub entries_removed: u64,
+}
+
+/// Statistics of a completed background scan.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanStats {
+    pub docs_scanned: u64,
+    pub docs_indexed: u64,
+}
+
+/// An index lifecycle event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexEvent {
+    Created { index: String },
+    Dropped { index: String },
+    Altered { index: String },
+    DocIndexed { index: String, key: Vec<u8> },
+    DocDeleted { index: String, key: Vec<u8> },
+    GcCycleDone { index: String, stats: GcCycleStats },
+    ScanFinished { index: String, stats: ScanStats },
+}
+
+impl IndexEvent {
+    pub const fn kind(&self) -> EventKind {
+        match self {
+            Self::Created { .. } => EventKind::Created,
+            Self::Dropped { .. } => EventKind::Dropped,
+            Self::Altered { .. } => EventKind::Altered,
+            Self::DocIndexed { .. } => EventKind::DocIndexed,
+            Self::DocDeleted { .. } => EventKind::DocDeleted,
+            Self::GcCycleDone { .. } => EventKind::GcCycleDone,
+            Self::ScanFinished { .. } => EventKind::ScanFinished,
+        }
+    }
+
+    /// The name of the index the event relates to.
+    pub fn index(&self) -> &str {
+        match self {
+            Self::Created { index }
+            | Self::Dropped { index }
+            | Self::Altered { index }
+            | Self::DocIndexed { index, .. }
+            | Self::DocDeleted { index, .. }
+            | Self::GcCycleDone { index, .. }
+            | Self::ScanFinished { index, .. } => index,
+        }
+    }
+}
+
+/// The type of an [`IndexEvent`], without its payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum EventKind {
+    Created,
+    Dropped,
+    Altered,
+    DocIndexed,
+    DocDeleted,
+    GcCycleDone,
+    ScanFinished,
+}
+
+/// A set of [`EventKind`]s a subscriber is interested in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EventMask(u8);
+
+impl EventMask {
+    pub const ALL: Self = Self(u8::MAX);
+    pub const NONE: Self = Self(0);
+
+    /// The mask whose bit `1 << kind` is set for each kind it contains.
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    pub const fn with(self, kind: EventKind) -> Self {
+        Self(self.0 | 1 << kind as u8)
+    }
+
+    pub const fn contains(self, kind: EventKind) -> bool {
+        self.0 & (1 << kind as u8) != 0
+    }
+
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl From<EventKind> for EventMask {
+    fn from(kind: EventKind) -> Self {
+        Self::NONE.with(kind)
+    }
+}
+
+/// An event along with its sequence number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    pub seq: u64,
+    pub event: IndexEvent,
+}
+
+/// Identifies a subscription, to cancel it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+impl SubscriptionId {
+    /// The raw value of the id, to hand it over to C.
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+
+    /// Rebuild an id from its [raw value](Self::as_u64).
+    pub const fn from_u64(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+/// A subscriber callback.
+pub type Subscriber = Arc<dyn Fn(&Envelope) + Send + Sync>;
+
+struct Subscription {
+    id: SubscriptionId,
+    mask: EventMask,
+    callback: Subscriber,
+}
+
+#[derive(Default)]
+struct Queue {
+    events: VecDeque<Envelope>,
+    next_seq: u64,
+    dropped: u64,
+}
+
+/// See the [crate documentation](crate).
+pub struct EventBus {
+    capacity: usize,
+    queue: Mutex<Queue>,
+    subscribers: RwLock<Vec<Subscription>>,
+    /// The union of the masks of the subscribers, updated along with them.
+    subscribed: AtomicU8,
+    next_subscription: Mutex<u64>,
+    /// Serializes dispatching so that events are never delivered out of order.
+    dispatching: Mutex<()>,
+    /// The thread currently dispatching, to detect re-entrant dispatches,
+    /// which would otherwise deadlock on `dispatching`.
+    dispatcher: Mutex<Option<ThreadId>>,
+}
+
+impl EventBus {
+    /// Create a bus holding at most `capacity` undelivered events.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            queue: Mutex::default(),
+            subscribers: RwLock::default(),
+            subscribed: AtomicU8::new(EventMask::NONE.0),
+            next_subscription: Mutex::new(0),
+            dispatching: Mutex::new(()),
+            dispatcher: Mutex::new(None),
+        }
+    }
+
+    /// Register `callback` for the events whose kind is in `mask`.
+    pub fn subscribe(&self, mask: EventMask, callback: Subscriber) -> SubscriptionId {
+        let id = {
+            let mut next = lock(&self.next_subscription);
+            *next += 1;
+            SubscriptionId(*next)
+        };
+        let mut subscribers = self.subscribers.write().unwrap_or_else(|e| e.into_inner());
+        subscribers.push(Subscription { id, mask, callback });
+        self.update_subscribed(&subscribers);
+        id
+    }
+
+    /// Cancel a subscription. Returns `false` if it doesn't exist.
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut subscribers = self.subscribers.write().unwrap_or_else(|e| e.into_inner());
+        let before = subscribers.len();
+        subscribers.retain(|s| s.id != id);
+        self.update_subscribed(&subscribers);
+        before != subscribers.len()
+    }
+
+    /// Whether a subscriber is interested in the events of `kind`. Producers
+    /// may skip building events nobody subscribes to.
+    ///
+    /// This is a single atomic load: a subscription made concurrently may miss
+    /// the events published meanwhile.
+    pub fn has_subscribers(&self, kind: EventKind) -> bool {
+        EventMask(self.subscribed.load(Ordering::Relaxed)).contains(kind)
+    }
+
+    fn update_subscribed(&self, subscribers: &[Subscription]) {
+        let mask = subscribers
+            .iter()
+            .fold(EventMask::NONE, |mask, s| mask.union(s.mask));
+        self.subscribed.store(mask.0, Ordering::Relaxed);
+    }
+
+    /// Queue `event` for delivery, without waiting for subscribers.
+    ///
+    /// Returns the event's sequence number, or `None` if the queue was full
+    /// and the event was dropped.
+    pub fn publish(&self, event: IndexEvent) -> Option<u64> {
+        let mut queue = lock(&self.queue);
+        let seq = queue.next_seq;
+        queue.next_seq += 1;
+        if queue.events.len() >= self.capacity {
+            queue.dropped += 1;
+            return None;
+        }
+        queue.events.push_back(Envelope { seq, event });
+        Some(seq)
+    }
+
+    /// Deliver all the queued events to the subscribers.
+    ///
+    /// Returns the number of events delivered. Subscribers may publish new
+    /// events from their callback; those are delivered by the next call.
+    /// Called from a subscriber, this returns 0 without delivering anything
+    /// (see [Re-entrancy](crate#re-entrancy)).
+    pub fn dispatch_pending(&self) -> usize {
+        let current = thread::current().id();
+        if *lock(&self.dispatcher) == Some(current) {
+            return 0;
+        }
+        let _dispatching = lock(&self.dispatching);
+        *lock(&self.dispatcher) = Some(current);
+        // Reset the dispatcher even if a subscriber panics.
+        let _dispatcher = DispatcherGuard(&self.dispatcher);
+        let events = std::mem::take(&mut lock(&self.queue).events);
+        // Snapshot the subscribers so that callbacks may (un)subscribe.
+        let subscribers: Vec<(EventMask, Subscriber)> = self
+            .subscribers
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|s| (s.mask, Arc::clone(&s.callback)))
+            .collect();
+        for envelope in &events {
+            let kind = envelope.event.kind();
+            for (mask, callback) in &subscribers {
+                if mask.contains(kind) {
+                    callback(envelope);
+                }
+            }
+        }
+        events.len()
+    }
+
+    /// The number of events waiting to be dispatched.
+    pub fn pending(&self) -> usize {
+        lock(&self.queue).events.len()
+    }
+
+    /// The number of events dropped because the queue was full.
+    pub fn dropped(&self) -> u64 {
+        lock(&self.queue).dropped
+    }
+}
+
+struct DispatcherGuard<'a>(&'a Mutex<Option<ThreadId>>);
+
+impl Drop for DispatcherGuard<'_> {
+    fn drop(&mut self) {
+        *lock(self.0) = None;
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -0,0 +1,253 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Delivery of index events to clients as RESP3 push messages.
+//!
+//! A client opts in with `FT._SUBSCRIBE <index|*> [EVENTS <count> <kind>...]`
+//! and receives, for every matching event, a push message of the form
+//! `["ft.event", <kind>, <index>, <seq>, {<payload>}]`. Push messages don't
+//! exist in RESP2, so RESP2 clients are refused.
+//!
+//! Without `EVENTS`, a client receives the index lifecycle events only: the
+//! per-document events are far too frequent to be pushed by default.
+//! Subscriptions are bound to the client connection and must be cancelled
+//! with [`PushSubscriptions::client_disconnected`] when it goes away.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use crate::{Envelope, EventBus, EventKind, EventMask, IndexEvent, SubscriptionId, lock};
+
+/// The first element of every push message, identifying its type.
+pub const PUSH_TYPE: &str = "ft.event";
+
+/// The events pushed when a subscription doesn't list any.
+pub const LIFECYCLE_EVENTS: EventMask = EventMask::NONE
+    .with(EventKind::Created)
+    .with(EventKind::Dropped)
+    .with(EventKind::Altered)
+    .with(EventKind::GcCycleDone)
+    .with(EventKind::ScanFinished);
+
+impl EventKind {
+    /// The name of the kind in commands and push messages.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Dropped => "dropped",
+            Self::Altered => "altered",
+            Self::DocIndexed => "doc_indexed",
+            Self::DocDeleted => "doc_deleted",
+            Self::GcCycleDone => "gc_cycle_done",
+            Self::ScanFinished => "scan_finished",
+        }
+    }
+}
+
+impl FromStr for EventKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Self::Created,
+            Self::Dropped,
+            Self::Altered,
+            Self::DocIndexed,
+            Self::DocDeleted,
+            Self::GcCycleDone,
+            Self::ScanFinished,
+        ]
+        .into_iter()
+        .find(|kind| kind.name().eq_ignore_ascii_case(s))
+        .ok_or(())
+    }
+}
+
+/// An element of a push message, independent of the reply API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushValue {
+    Bulk(Vec<u8>),
+    Integer(u64),
+    Map(Vec<(&'static str, PushValue)>),
+}
+
+impl From<&str> for PushValue {
+    fn from(s: &str) -> Self {
+        Self::Bulk(s.as_bytes().to_vec())
+    }
+}
+
+/// The push message announcing `envelope`.
+pub fn push_message(envelope: &Envelope) -> Vec<PushValue> {
+    let event = &envelope.event;
+    let payload = match event {
+        IndexEvent::Created { .. } | IndexEvent::Dropped { .. } | IndexEvent::Altered { .. } => {
+            Vec::new()
+        }
+        IndexEvent::DocIndexed { key, .. } | IndexEvent::DocDeleted { key, .. } => {
+            vec![("key", PushValue::Bulk(key.clone()))]
+        }
+        IndexEvent::GcCycleDone { stats, .. } => vec![
+            ("bytes_collected", PushValue::Integer(stats.bytes_collected)),
+            ("entries_removed", PushValue::Integer(stats.entries_removed)),
+        ],
+        IndexEvent::ScanFinished { stats, .. } => vec![
+            ("docs_scanned", PushValue::Integer(stats.docs_scanned)),
+            ("docs_indexed", PushValue::Integer(stats.docs_indexed)),
+        ],
+    };
+    vec![
+        PUSH_TYPE.into(),
+        event.kind().name().into(),
+        event.index().into(),
+        PushValue::Integer(envelope.seq),
+        PushValue::Map(payload),
+    ]
+}
+
+/// Errors returned by `FT._SUBSCRIBE` and `FT._UNSUBSCRIBE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubscribeError {
+    /// The client uses RESP2, which has no push messages.
+    Resp2,
+    /// The index argument is missing.
+    MissingIndex,
+    /// `EVENTS` isn't followed by a valid count and that many kinds.
+    BadEvents,
+    UnknownEvent(String),
+    UnknownArgument(String),
+}
+
+impl Display for SubscribeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Resp2 => f.write_str("Event subscriptions require RESP3"),
+            Self::MissingIndex => f.write_str("Missing index name"),
+            Self::BadEvents => f.write_str("Bad arguments for EVENTS"),
+            Self::UnknownEvent(kind) => write!(f, "Unknown event `{kind}`"),
+            Self::UnknownArgument(arg) => write!(f, "Unknown argument `{arg}`"),
+        }
+    }
+}
+
+impl std::error::Error for SubscribeError {}
+
+/// The arguments of `FT._SUBSCRIBE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscribeCommand {
+    /// The index whose events are pushed, or `None` for every index (`*`).
+    pub index: Option<String>,
+    pub mask: EventMask,
+}
+
+impl SubscribeCommand {
+    /// Parse the arguments following the command name.
+    pub fn parse<'a>(args: impl IntoIterator<Item = &'a str>) -> Result<Self, SubscribeError> {
+        let mut args = args.into_iter();
+        let index = match args.next().ok_or(SubscribeError::MissingIndex)? {
+            "*" => None,
+            index => Some(index.to_owned()),
+        };
+        let mut mask = LIFECYCLE_EVENTS;
+        while let Some(arg) = args.next() {
+            if !arg.eq_ignore_ascii_case("EVENTS") {
+                return Err(SubscribeError::UnknownArgument(arg.to_owned()));
+            }
+            let count: usize = args
+                .next()
+                .and_then(|n| n.parse().ok())
+                .filter(|&n| n > 0)
+                .ok_or(SubscribeError::BadEvents)?;
+            mask = EventMask::NONE;
+            for _ in 0..count {
+                let kind = args.next().ok_or(SubscribeError::BadEvents)?;
+                mask = mask.with(
+                    kind.parse()
+                        .map_err(|()| SubscribeError::UnknownEvent(kind.to_owned()))?,
+                );
+            }
+        }
+        Ok(Self { index, mask })
+    }
+}
+
+/// Identifies a client connection.
+pub type ClientId = u64;
+
+/// Sends push messages to clients.
+pub trait PushSink: Send + Sync {
+    /// Send `message` to `client`. Called from [`EventBus::dispatch_pending`],
+    /// on the thread owning the bus.
+    fn push(&self, client: ClientId, message: &[PushValue]);
+}
+
+/// The push subscriptions of every client, registered on an [`EventBus`].
+pub struct PushSubscriptions {
+    bus: Arc<EventBus>,
+    sink: Arc<dyn PushSink>,
+    clients: Mutex<HashMap<ClientId, Vec<SubscriptionId>>>,
+}
+
+impl PushSubscriptions {
+    pub fn new(bus: Arc<EventBus>, sink: Arc<dyn PushSink>) -> Self {
+        Self {
+            bus,
+            sink,
+            clients: Mutex::default(),
+        }
+    }
+
+    /// Execute `FT._SUBSCRIBE` for `client`, which speaks RESP `protocol`.
+    ///
+    /// A client may hold several subscriptions; an event matching more than
+    /// one of them is pushed once per subscription.
+    pub fn subscribe(
+        &self,
+        client: ClientId,
+        protocol: u8,
+        command: SubscribeCommand,
+    ) -> Result<SubscriptionId, SubscribeError> {
+        if protocol < 3 {
+            return Err(SubscribeError::Resp2);
+        }
+        let sink = Arc::clone(&self.sink);
+        let SubscribeCommand { index, mask } = command;
+        let id = self.bus.subscribe(
+            mask,
+            Arc::new(move |envelope: &Envelope| {
+                if index.as_deref().is_none_or(|i| i == envelope.event.index()) {
+                    sink.push(client, &push_message(envelope));
+                }
+            }),
+        );
+        lock(&self.clients).entry(client).or_default().push(id);
+        Ok(id)
+    }
+
+    /// Execute `FT._UNSUBSCRIBE` for `client`, cancelling all its
+    /// subscriptions. Returns the number cancelled.
+    pub fn unsubscribe(&self, client: ClientId) -> usize {
+        let ids = lock(&self.clients).remove(&client).unwrap_or_default();
+        ids.iter().filter(|&&id| self.bus.unsubscribe(id)).count()
+    }
+
+    /// Drop the subscriptions of a client whose connection was closed.
+    pub fn client_disconnected(&self, client: ClientId) {
+        self.unsubscribe(client);
+    }
+
+    /// The number of clients with at least one subscription.
+    pub fn clients(&self) -> usize {
+        lock(&self.clients).len()
+    }
+}
@@ -0,0 +1,204 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::sync::{Arc, Mutex};
+
+use index_events::{
+    Envelope, EventBus, EventKind, EventMask, GcCycleStats, IndexEvent, Subscriber,
+};
+
+fn created(index: &str) -> IndexEvent {
+    IndexEvent::Created {
+        index: index.to_owned(),
+    }
+}
+
+type Log = Arc<Mutex<Vec<(&'static str, u64)>>>;
+
+/// A shared log, and a way to create subscribers appending `(name, seq)` to it.
+fn recorder() -> (Log, impl Fn(&'static str) -> Subscriber) {
+    let log = Log::default();
+    let make = {
+        let log = Arc::clone(&log);
+        move |name: &'static str| -> Subscriber {
+            let log = Arc::clone(&log);
+            Arc::new(move |e: &Envelope| log.lock().unwrap().push((name, e.seq)))
+        }
+    };
+    (log, make)
+}
+
+#[test]
+fn test_publish_does_not_deliver_until_dispatch() {
+    let bus = EventBus::new(16);
+    let (log, subscriber) = recorder();
+    bus.subscribe(EventMask::ALL, subscriber("a"));
+
+    assert_eq!(bus.publish(created("idx")), Some(0));
+    assert!(log.lock().unwrap().is_empty());
+    assert_eq!(bus.pending(), 1);
+
+    assert_eq!(bus.dispatch_pending(), 1);
+    assert_eq!(*log.lock().unwrap(), [("a", 0)]);
+    assert_eq!(bus.pending(), 0);
+}
+
+#[test]
+fn test_ordering() {
+    let bus = EventBus::new(16);
+    let (log, subscriber) = recorder();
+    bus.subscribe(EventMask::ALL, subscriber("first"));
+    bus.subscribe(EventMask::ALL, subscriber("second"));
+    bus.publish(created("a"));
+    bus.publish(created("b"));
+    bus.dispatch_pending();
+    assert_eq!(
+        *log.lock().unwrap(),
+        [("first", 0), ("second", 0), ("first", 1), ("second", 1)]
+    );
+}
+
+#[test]
+fn test_mask_filters_events() {
+    let bus = EventBus::new(16);
+    let (log, subscriber) = recorder();
+    let mask = EventMask::from(EventKind::GcCycleDone).with(EventKind::Dropped);
+    bus.subscribe(mask, subscriber("gc"));
+
+    bus.publish(created("idx"));
+    bus.publish(IndexEvent::GcCycleDone {
+        index: "idx".to_owned(),
+        stats: GcCycleStats {
+            bytes_collected: 1024,
+            entries_removed: 3,
+        },
+    });
+    bus.publish(IndexEvent::Dropped {
+        index: "idx".to_owned(),
+    });
+    assert_eq!(bus.dispatch_pending(), 3);
+    assert_eq!(*log.lock().unwrap(), [("gc", 1), ("gc", 2)]);
+}
+
+#[test]
+fn test_has_subscribers() {
+    let bus = EventBus::new(16);
+    let (_log, subscriber) = recorder();
+    assert!(!bus.has_subscribers(EventKind::DocIndexed));
+
+    let gc = bus.subscribe(EventKind::GcCycleDone.into(), subscriber("gc"));
+    let docs = bus.subscribe(
+        EventMask::from(EventKind::DocIndexed).with(EventKind::GcCycleDone),
+        subscriber("docs"),
+    );
+    assert!(bus.has_subscribers(EventKind::DocIndexed));
+    assert!(bus.has_subscribers(EventKind::GcCycleDone));
+    assert!(!bus.has_subscribers(EventKind::DocDeleted));
+
+    assert!(bus.unsubscribe(docs));
+    assert!(!bus.has_subscribers(EventKind::DocIndexed));
+    assert!(bus.has_subscribers(EventKind::GcCycleDone));
+    assert!(bus.unsubscribe(gc));
+    assert!(!bus.has_subscribers(EventKind::GcCycleDone));
+}
+
+#[test]
+fn test_full_queue_drops_events() {
+    let bus = EventBus::new(2);
+    assert_eq!(bus.publish(created("a")), Some(0));
+    assert_eq!(bus.publish(created("b")), Some(1));
+    assert_eq!(bus.publish(created("c")), None);
+    assert_eq!(bus.dropped(), 1);
+    bus.dispatch_pending();
+    // The gap in sequence numbers reveals the dropped event.
+    assert_eq!(bus.publish(created("d")), Some(3));
+}
+
+#[test]
+fn test_unsubscribe_and_reentrant_publish() {
+    let bus = Arc::new(EventBus::new(16));
+    let (log, subscriber) = recorder();
+    let id = bus.subscribe(EventMask::ALL, subscriber("a"));
+
+    let republish = {
+        let bus = Arc::downgrade(&bus);
+        Arc::new(move |e: &Envelope| {
+            if let IndexEvent::Created { index } = &e.event {
+                bus.upgrade().unwrap().publish(IndexEvent::Altered {
+                    index: index.clone(),
+                });
+            }
+        })
+    };
+    bus.subscribe(EventKind::Created.into(), republish);
+
+    bus.publish(created("idx"));
+    assert_eq!(bus.dispatch_pending(), 1);
+    assert_eq!(bus.pending(), 1);
+    assert!(bus.unsubscribe(id));
+    assert!(!bus.unsubscribe(id));
+    assert_eq!(bus.dispatch_pending(), 1);
+    assert_eq!(*log.lock().unwrap(), [("a", 0)]);
+}
+
+#[test]
+fn test_reentrant_dispatch_is_deferred() {
+    let bus = Arc::new(EventBus::new(16));
+    let (log, subscriber) = recorder();
+    bus.subscribe(EventMask::ALL, subscriber("a"));
+    let nested = Arc::new(Mutex::new(Vec::new()));
+    let redispatch = {
+        let bus = Arc::downgrade(&bus);
+        let nested = Arc::clone(&nested);
+        Arc::new(move |e: &Envelope| {
+            if let IndexEvent::Created { index } = &e.event {
+                let bus = bus.upgrade().unwrap();
+                bus.publish(IndexEvent::Dropped {
+                    index: index.clone(),
+                });
+                nested.lock().unwrap().push(bus.dispatch_pending());
+            }
+        })
+    };
+    bus.subscribe(EventKind::Created.into(), redispatch);
+
+    bus.publish(created("idx"));
+    assert_eq!(bus.dispatch_pending(), 1);
+    assert_eq!(*nested.lock().unwrap(), [0]);
+    assert_eq!(*log.lock().unwrap(), [("a", 0)]);
+    assert_eq!(bus.dispatch_pending(), 1);
+    assert_eq!(*log.lock().unwrap(), [("a", 0), ("a", 1)]);
+}
+
+#[test]
+fn test_dispatch_after_panicking_subscriber() {
+    let bus = Arc::new(EventBus::new(16));
+    let (log, subscriber) = recorder();
+    let panicking: Subscriber = Arc::new(|e: &Envelope| assert_ne!(e.seq, 0));
+    let id = bus.subscribe(EventMask::ALL, panicking);
+    bus.subscribe(EventMask::ALL, subscriber("a"));
+
+    bus.publish(created("idx"));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| bus.dispatch_pending()));
+    assert!(result.is_err());
+    bus.unsubscribe(id);
+    bus.publish(created("idx"));
+    assert_eq!(bus.dispatch_pending(), 1);
+    assert_eq!(*log.lock().unwrap(), [("a", 1)]);
+}
+
+#[test]
+fn test_event_accessors() {
+    let event = IndexEvent::DocIndexed {
+        index: "idx".to_owned(),
+        key: b"doc:1".to_vec(),
+    };
+    assert_eq!(event.kind(), EventKind::DocIndexed);
+    assert_eq!(event.index(), "idx");
+}
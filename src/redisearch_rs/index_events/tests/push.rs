@@ -0,0 +1,166 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::sync::{Arc, Mutex};
+
+use index_events::{
+    EventBus, EventKind, GcCycleStats, IndexEvent, ScanStats,
+    push::{
+        ClientId, LIFECYCLE_EVENTS, PushSink, PushSubscriptions, PushValue, SubscribeCommand,
+        SubscribeError,
+    },
+};
+
+#[derive(Default)]
+struct Recorder(Mutex<Vec<(ClientId, Vec<PushValue>)>>);
+
+impl PushSink for Recorder {
+    fn push(&self, client: ClientId, message: &[PushValue]) {
+        self.0.lock().unwrap().push((client, message.to_vec()));
+    }
+}
+
+impl Recorder {
+    fn take(&self) -> Vec<(ClientId, Vec<PushValue>)> {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+}
+
+fn setup() -> (Arc<EventBus>, Arc<Recorder>, PushSubscriptions) {
+    let bus = Arc::new(EventBus::new(16));
+    let sink = Arc::new(Recorder::default());
+    let subscriptions = PushSubscriptions::new(Arc::clone(&bus), Arc::clone(&sink) as _);
+    (bus, sink, subscriptions)
+}
+
+fn subscribe(args: &str) -> SubscribeCommand {
+    SubscribeCommand::parse(args.split_whitespace()).unwrap()
+}
+
+#[test]
+fn test_parse() {
+    let all = subscribe("*");
+    assert_eq!(all.index, None);
+    assert_eq!(all.mask, LIFECYCLE_EVENTS);
+    assert!(!all.mask.contains(EventKind::DocIndexed));
+
+    let gc = subscribe("idx EVENTS 2 GC_CYCLE_DONE doc_deleted");
+    assert_eq!(gc.index.as_deref(), Some("idx"));
+    assert!(gc.mask.contains(EventKind::GcCycleDone));
+    assert!(gc.mask.contains(EventKind::DocDeleted));
+    assert!(!gc.mask.contains(EventKind::Created));
+
+    let parse = |args: &str| SubscribeCommand::parse(args.split_whitespace()).unwrap_err();
+    assert_eq!(parse(""), SubscribeError::MissingIndex);
+    assert_eq!(parse("idx EVENTS 0"), SubscribeError::BadEvents);
+    assert_eq!(parse("idx EVENTS 2 created"), SubscribeError::BadEvents);
+    assert_eq!(
+        parse("idx EVENTS 1 renamed").to_string(),
+        "Unknown event `renamed`"
+    );
+    assert_eq!(
+        parse("idx LIMIT"),
+        SubscribeError::UnknownArgument("LIMIT".to_owned())
+    );
+}
+
+#[test]
+fn test_push_messages() {
+    let (bus, sink, subscriptions) = setup();
+    subscriptions.subscribe(7, 3, subscribe("*")).unwrap();
+
+    bus.publish(IndexEvent::ScanFinished {
+        index: "idx".to_owned(),
+        stats: ScanStats {
+            docs_scanned: 10,
+            docs_indexed: 8,
+        },
+    });
+    // Per-document events are not pushed by default.
+    bus.publish(IndexEvent::DocIndexed {
+        index: "idx".to_owned(),
+        key: b"doc:1".to_vec(),
+    });
+    bus.publish(IndexEvent::Altered {
+        index: "other".to_owned(),
+    });
+    assert!(sink.take().is_empty());
+    bus.dispatch_pending();
+
+    assert_eq!(
+        sink.take(),
+        [
+            (
+                7,
+                vec![
+                    "ft.event".into(),
+                    "scan_finished".into(),
+                    "idx".into(),
+                    PushValue::Integer(0),
+                    PushValue::Map(vec![
+                        ("docs_scanned", PushValue::Integer(10)),
+                        ("docs_indexed", PushValue::Integer(8)),
+                    ]),
+                ]
+            ),
+            (
+                7,
+                vec![
+                    "ft.event".into(),
+                    "altered".into(),
+                    "other".into(),
+                    PushValue::Integer(2),
+                    PushValue::Map(Vec::new()),
+                ]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_index_filter_and_unsubscribe() {
+    let (bus, sink, subscriptions) = setup();
+    subscriptions
+        .subscribe(1, 3, subscribe("idx EVENTS 1 gc_cycle_done"))
+        .unwrap();
+    subscriptions.subscribe(2, 3, subscribe("other")).unwrap();
+    assert_eq!(subscriptions.clients(), 2);
+
+    let gc = |index: &str| IndexEvent::GcCycleDone {
+        index: index.to_owned(),
+        stats: GcCycleStats::default(),
+    };
+    bus.publish(gc("idx"));
+    bus.publish(gc("other"));
+    bus.dispatch_pending();
+    let clients: Vec<_> = sink
+        .take()
+        .into_iter()
+        .map(|(c, m)| (c, m[2].clone()))
+        .collect();
+    assert_eq!(clients, [(1, "idx".into()), (2, "other".into())]);
+
+    assert_eq!(subscriptions.unsubscribe(1), 1);
+    assert_eq!(subscriptions.unsubscribe(1), 0);
+    subscriptions.client_disconnected(2);
+    assert_eq!(subscriptions.clients(), 0);
+    bus.publish(gc("idx"));
+    bus.dispatch_pending();
+    assert!(sink.take().is_empty());
+}
+
+#[test]
+fn test_resp2_is_refused() {
+    let (_, _, subscriptions) = setup();
+    assert_eq!(
+        subscriptions.subscribe(1, 2, subscribe("*")),
+        Err(SubscribeError::Resp2)
+    );
+    assert_eq!(subscriptions.clients(), 0);
+}
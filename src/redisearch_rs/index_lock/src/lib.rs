@@ -0,0 +1,330 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The per-index reader/writer lock.
+//!
+//! Queries take the lock for reading, indexing and GC for writing. The lock is
+//! phase-fair: a waiting writer stops new readers from entering, and the
+//! readers that queued up while a writer held the lock all enter before the
+//! next writer. Neither side can starve the other under mixed loads.
+//!
+//! Before blocking, an acquirer spins for a while. The spin budget adapts to
+//! how often spinning pays off for this lock.
+//!
+//! Each lock keeps [contention statistics](LockStats), including histograms of
+//! the time spent waiting, reported by `FT.DEBUG LOCKSTATS`.
+
+mod stats;
+
+pub use stats::{LockStats, WaitHistogram};
+
+use std::{
+    cell::UnsafeCell,
+    ops::{Deref, DerefMut},
+    sync::{
+        Condvar, Mutex, MutexGuard,
+        atomic::{AtomicU32, Ordering},
+    },
+    time::Instant,
+};
+
+use stats::StatsRecorder;
+
+/// The maximum number of spin iterations before blocking.
+const MAX_SPIN: u32 = 1 << 10;
+/// The initial spin budget.
+const INITIAL_SPIN: u32 = 1 << 6;
+
+#[derive(Debug, Default)]
+struct State {
+    readers: u32,
+    writer: bool,
+    waiting_readers: u32,
+    waiting_writers: u32,
+    /// Readers that were waiting when the last writer released the lock, and
+    /// have priority over the next writer.
+    phase_readers: u32,
+}
+
+impl State {
+    const fn can_read(&self) -> bool {
+        !self.writer && (self.waiting_writers == 0 || self.phase_readers > 0)
+    }
+
+    const fn can_write(&self) -> bool {
+        !self.writer && self.readers == 0 && self.phase_readers == 0
+    }
+}
+
+/// See the [crate documentation](crate).
+pub struct IndexLock<T> {
+    state: Mutex<State>,
+    readers_cv: Condvar,
+    writers_cv: Condvar,
+    spin_budget: AtomicU32,
+    stats: StatsRecorder,
+    value: UnsafeCell<T>,
+}
+
+// Safety: the lock hands out `&T` to several threads at once, and `&mut T` to
+// one thread at a time, like `std::sync::RwLock`.
+unsafe impl<T: Send + Sync> Sync for IndexLock<T> {}
+// Safety: owning the lock is owning a `T`.
+unsafe impl<T: Send> Send for IndexLock<T> {}
+
+impl<T> IndexLock<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            state: Mutex::default(),
+            readers_cv: Condvar::new(),
+            writers_cv: Condvar::new(),
+            spin_budget: AtomicU32::new(INITIAL_SPIN),
+            stats: StatsRecorder::default(),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Try to enter without waiting, returning whether it succeeded.
+    fn try_enter(&self, write: bool) -> bool {
+        let mut state = self.lock_state();
+        if write && state.can_write() {
+            state.writer = true;
+            true
+        } else if !write && state.can_read() {
+            state.readers += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Spin up to the current budget, adapting it to the outcome.
+    fn spin(&self, write: bool) -> bool {
+        let budget = self.spin_budget.load(Ordering::Relaxed);
+        for _ in 0..budget {
+            std::hint::spin_loop();
+            if self.try_enter(write) {
+                let grown = (budget * 2).clamp(1, MAX_SPIN);
+                self.spin_budget.store(grown, Ordering::Relaxed);
+                return true;
+            }
+        }
+        self.spin_budget.store(budget / 2, Ordering::Relaxed);
+        false
+    }
+
+    fn acquire(&self, write: bool) {
+        if self.try_enter(write) {
+            self.stats.record(write, None);
+            return;
+        }
+        let start = Instant::now();
+        if !self.spin(write) {
+            self.block(write);
+        }
+        self.stats.record(write, Some(start.elapsed()));
+    }
+
+    fn block(&self, write: bool) {
+        let mut state = self.lock_state();
+        if write {
+            state.waiting_writers += 1;
+            while !state.can_write() {
+                state = self
+                    .writers_cv
+                    .wait(state)
+                    .unwrap_or_else(|e| e.into_inner());
+            }
+            state.waiting_writers -= 1;
+            state.writer = true;
+        } else {
+            state.waiting_readers += 1;
+            while !state.can_read() {
+                state = self
+                    .readers_cv
+                    .wait(state)
+                    .unwrap_or_else(|e| e.into_inner());
+            }
+            state.waiting_readers -= 1;
+            state.phase_readers = state.phase_readers.saturating_sub(1);
+            state.readers += 1;
+        }
+    }
+
+    fn release_read(&self) {
+        let mut state = self.lock_state();
+        state.readers -= 1;
+        if state.readers == 0 && state.waiting_writers > 0 {
+            self.writers_cv.notify_one();
+        }
+    }
+
+    fn release_write(&self) {
+        let mut state = self.lock_state();
+        state.writer = false;
+        if state.waiting_readers > 0 {
+            // Let every queued reader in before the next writer.
+            state.phase_readers = state.waiting_readers;
+            self.readers_cv.notify_all();
+        } else if state.waiting_writers > 0 {
+            self.writers_cv.notify_one();
+        }
+    }
+
+    /// Lock for reading, waiting as long as necessary.
+    pub fn read(&self) -> ReadGuard<'_, T> {
+        self.acquire(false);
+        ReadGuard { lock: self }
+    }
+
+    /// Lock for writing, waiting as long as necessary.
+    pub fn write(&self) -> WriteGuard<'_, T> {
+        self.acquire(true);
+        WriteGuard { lock: self }
+    }
+
+    /// Lock for reading if that's possible without waiting.
+    pub fn try_read(&self) -> Option<ReadGuard<'_, T>> {
+        self.try_enter(false).then(|| {
+            self.stats.record(false, None);
+            ReadGuard { lock: self }
+        })
+    }
+
+    /// Lock for writing if that's possible without waiting.
+    pub fn try_write(&self) -> Option<WriteGuard<'_, T>> {
+        self.try_enter(true).then(|| {
+            self.stats.record(true, None);
+            WriteGuard { lock: self }
+        })
+    }
+
+    /// Lock for reading without a guard, for callers which can't hold one,
+    /// such as the C code. Must be released using [`Self::unlock`].
+    pub fn lock_read(&self) {
+        self.acquire(false);
+    }
+
+    /// Lock for writing without a guard. See [`Self::lock_read`].
+    pub fn lock_write(&self) {
+        self.acquire(true);
+    }
+
+    /// Lock for reading without a guard if that's possible without waiting,
+    /// returning whether it succeeded. See [`Self::lock_read`].
+    pub fn try_lock_read(&self) -> bool {
+        let entered = self.try_enter(false);
+        if entered {
+            self.stats.record(false, None);
+        }
+        entered
+    }
+
+    /// Lock for writing without a guard if that's possible without waiting,
+    /// returning whether it succeeded. See [`Self::lock_read`].
+    pub fn try_lock_write(&self) -> bool {
+        let entered = self.try_enter(true);
+        if entered {
+            self.stats.record(true, None);
+        }
+        entered
+    }
+
+    /// Release a lock taken using [`Self::lock_read`], [`Self::lock_write`],
+    /// [`Self::try_lock_read`] or [`Self::try_lock_write`].
+    ///
+    /// # Safety
+    ///
+    /// The calling thread must hold the lock, taken using one of those
+    /// methods.
+    pub unsafe fn unlock(&self) {
+        // A writer excludes the readers: if there's one, it's the caller.
+        let writer = self.lock_state().writer;
+        if writer {
+            self.release_write();
+        } else {
+            self.release_read();
+        }
+    }
+
+    /// The contention statistics of this lock.
+    pub fn stats(&self) -> LockStats {
+        self.stats.snapshot()
+    }
+
+    /// Reset the contention statistics.
+    pub fn reset_stats(&self) {
+        self.stats.reset();
+    }
+
+    pub const fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: Default> Default for IndexLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+/// Shared access to the value of an [`IndexLock`].
+pub struct ReadGuard<'a, T> {
+    lock: &'a IndexLock<T>,
+}
+
+impl<T> Deref for ReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // Safety: the guard holds a read lock, so there's no writer.
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> Drop for ReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.release_read();
+    }
+}
+
+/// Exclusive access to the value of an [`IndexLock`].
+pub struct WriteGuard<'a, T> {
+    lock: &'a IndexLock<T>,
+}
+
+impl<T> Deref for WriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // Safety: the guard holds the write lock, so there's no other access.
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> DerefMut for WriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // Safety: the guard holds the write lock, so there's no other access.
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T> Drop for WriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.release_write();
+    }
+}
@@ -0,0 +1,140 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// Histogram of wait times with power-of-two buckets: bucket `i` counts the
+/// waits shorter than `2^i` microseconds (and at least `2^(i-1)`), the last
+/// one all the longer waits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WaitHistogram {
+    pub buckets: [u64; WaitHistogram::BUCKETS],
+}
+
+impl WaitHistogram {
+    pub const BUCKETS: usize = 24;
+
+    /// The bucket a wait falls into.
+    pub fn bucket(wait: Duration) -> usize {
+        let micros = u64::try_from(wait.as_micros()).unwrap_or(u64::MAX);
+        let bucket = (u64::BITS - micros.leading_zeros()) as usize;
+        bucket.min(Self::BUCKETS - 1)
+    }
+
+    /// The total number of waits.
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// An upper bound of the `q` quantile (`0.0..=1.0`) of the waits, in
+    /// microseconds, or `None` if there were no waits.
+    pub fn quantile_upper_bound_micros(&self, q: f64) -> Option<u64> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let target = ((count as f64 * q).ceil() as u64).clamp(1, count);
+        let mut seen = 0;
+        for (i, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= target {
+                return Some(1 << i);
+            }
+        }
+        None
+    }
+}
+
+/// The contention statistics of a lock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LockStats {
+    pub read_acquisitions: u64,
+    pub write_acquisitions: u64,
+    /// Acquisitions that couldn't enter immediately.
+    pub read_contended: u64,
+    pub write_contended: u64,
+    pub max_read_wait: Duration,
+    pub max_write_wait: Duration,
+    pub read_waits: WaitHistogram,
+    pub write_waits: WaitHistogram,
+}
+
+#[derive(Default)]
+struct SideRecorder {
+    acquisitions: AtomicU64,
+    contended: AtomicU64,
+    max_wait_nanos: AtomicU64,
+    buckets: [AtomicU64; WaitHistogram::BUCKETS],
+}
+
+impl SideRecorder {
+    fn record(&self, wait: Option<Duration>) {
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+        let Some(wait) = wait else {
+            return;
+        };
+        self.contended.fetch_add(1, Ordering::Relaxed);
+        let nanos = u64::try_from(wait.as_nanos()).unwrap_or(u64::MAX);
+        self.max_wait_nanos.fetch_max(nanos, Ordering::Relaxed);
+        self.buckets[WaitHistogram::bucket(wait)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn reset(&self) {
+        self.acquisitions.store(0, Ordering::Relaxed);
+        self.contended.store(0, Ordering::Relaxed);
+        self.max_wait_nanos.store(0, Ordering::Relaxed);
+        for bucket in &self.buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+    }
+
+    fn histogram(&self) -> WaitHistogram {
+        WaitHistogram {
+            buckets: std::array::from_fn(|i| self.buckets[i].load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct StatsRecorder {
+    read: SideRecorder,
+    write: SideRecorder,
+}
+
+impl StatsRecorder {
+    /// Record an acquisition, with its wait if it was contended.
+    pub(crate) fn record(&self, write: bool, wait: Option<Duration>) {
+        if write {
+            self.write.record(wait)
+        } else {
+            self.read.record(wait)
+        }
+    }
+
+    pub(crate) fn reset(&self) {
+        self.read.reset();
+        self.write.reset();
+    }
+
+    pub(crate) fn snapshot(&self) -> LockStats {
+        LockStats {
+            read_acquisitions: self.read.acquisitions.load(Ordering::Relaxed),
+            write_acquisitions: self.write.acquisitions.load(Ordering::Relaxed),
+            read_contended: self.read.contended.load(Ordering::Relaxed),
+            write_contended: self.write.contended.load(Ordering::Relaxed),
+            max_read_wait: Duration::from_nanos(self.read.max_wait_nanos.load(Ordering::Relaxed)),
+            max_write_wait: Duration::from_nanos(self.write.max_wait_nanos.load(Ordering::Relaxed)),
+            read_waits: self.read.histogram(),
+            write_waits: self.write.histogram(),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{
+    sync::{Arc, mpsc},
+    thread,
+    time::Duration,
+};
+
+use index_lock::{IndexLock, WaitHistogram};
+
+#[test]
+fn test_readers_share_writers_exclude() {
+    let lock = IndexLock::new(1);
+    {
+        let a = lock.read();
+        let b = lock.read();
+        assert_eq!(*a + *b, 2);
+        assert!(lock.try_write().is_none());
+    }
+    {
+        let mut w = lock.write();
+        *w += 1;
+        assert!(lock.try_read().is_none());
+        assert!(lock.try_write().is_none());
+    }
+    assert_eq!(*lock.read(), 2);
+
+    let stats = lock.stats();
+    assert_eq!(stats.read_acquisitions, 3);
+    assert_eq!(stats.write_acquisitions, 1);
+    assert_eq!(stats.read_contended, 0);
+}
+
+#[test]
+fn test_waiting_writer_blocks_new_readers() {
+    let lock = Arc::new(IndexLock::new(0));
+    let reader = lock.read();
+
+    let (tx, rx) = mpsc::channel();
+    let writer = {
+        let lock = Arc::clone(&lock);
+        thread::spawn(move || {
+            *lock.write() += 1;
+            tx.send(()).unwrap();
+        })
+    };
+    // Wait for the writer to queue up behind the reader.
+    while lock.try_read().is_some() {
+        thread::yield_now();
+    }
+    assert!(rx.try_recv().is_err());
+    drop(reader);
+    rx.recv().unwrap();
+    writer.join().unwrap();
+
+    assert_eq!(*lock.read(), 1);
+    let stats = lock.stats();
+    assert_eq!(stats.write_contended, 1);
+    assert_eq!(stats.write_waits.count(), 1);
+}
+
+#[test]
+fn test_mixed_load_is_consistent() {
+    const WRITERS: usize = 4;
+    const READERS: usize = 4;
+    const ITERATIONS: usize = 500;
+
+    // Writers keep both values equal; readers must never see them differ.
+    let lock = Arc::new(IndexLock::new((0u64, 0u64)));
+    let mut handles = Vec::new();
+    for _ in 0..WRITERS {
+        let lock = Arc::clone(&lock);
+        handles.push(thread::spawn(move || {
+            for _ in 0..ITERATIONS {
+                let mut guard = lock.write();
+                guard.0 += 1;
+                thread::yield_now();
+                guard.1 += 1;
+            }
+        }));
+    }
+    for _ in 0..READERS {
+        let lock = Arc::clone(&lock);
+        handles.push(thread::spawn(move || {
+            for _ in 0..ITERATIONS {
+                let guard = lock.read();
+                assert_eq!(guard.0, guard.1);
+            }
+        }));
+    }
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let total = (WRITERS * ITERATIONS) as u64;
+    assert_eq!(*lock.read(), (total, total));
+    let stats = lock.stats();
+    assert_eq!(stats.write_acquisitions, total);
+    assert_eq!(stats.read_waits.count(), stats.read_contended);
+
+    lock.reset_stats();
+    assert_eq!(lock.stats().write_acquisitions, 0);
+}
+
+#[test]
+fn test_lock_without_guard() {
+    let lock = Arc::new(IndexLock::new(()));
+    lock.lock_read();
+    lock.lock_read();
+    assert!(!lock.try_lock_write());
+    // Safety: this thread holds two read locks.
+    unsafe { lock.unlock() };
+    // Safety: as above, one.
+    unsafe { lock.unlock() };
+
+    assert!(lock.try_lock_write());
+    assert!(!lock.try_lock_read());
+    let (tx, rx) = mpsc::channel();
+    let reader = {
+        let lock = Arc::clone(&lock);
+        thread::spawn(move || {
+            lock.lock_read();
+            tx.send(()).unwrap();
+            // Safety: this thread holds a read lock.
+            unsafe { lock.unlock() };
+        })
+    };
+    assert!(rx.recv_timeout(Duration::from_millis(50)).is_err());
+    // Safety: this thread holds the write lock.
+    unsafe { lock.unlock() };
+    reader.join().unwrap();
+    rx.recv().unwrap();
+
+    let stats = lock.stats();
+    assert_eq!(stats.read_acquisitions, 3);
+    assert_eq!(stats.write_acquisitions, 1);
+    assert_eq!(stats.read_contended, 1);
+}
+
+#[test]
+fn test_histogram() {
+    assert_eq!(WaitHistogram::bucket(Duration::ZERO), 0);
+    assert_eq!(WaitHistogram::bucket(Duration::from_micros(1)), 1);
+    assert_eq!(WaitHistogram::bucket(Duration::from_micros(3)), 2);
+    assert_eq!(WaitHistogram::bucket(Duration::from_micros(4)), 3);
+    assert_eq!(
+        WaitHistogram::bucket(Duration::from_secs(3600)),
+        WaitHistogram::BUCKETS - 1
+    );
+
+    let mut histogram = WaitHistogram::default();
+    assert_eq!(histogram.quantile_upper_bound_micros(0.5), None);
+    histogram.buckets[1] = 9;
+    histogram.buckets[10] = 1;
+    assert_eq!(histogram.quantile_upper_bound_micros(0.5), Some(2));
+    assert_eq!(histogram.quantile_upper_bound_micros(0.99), Some(1024));
+}
@@ -0,0 +1,192 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Computed fields, whose value is derived from other fields when a document
+//! is indexed.
+//!
+//! `FT.CREATE idx SCHEMA title TEXT title_key TAG COMPUTED "lower(@title)"`
+//! indexes `lower(@title)` in the TAG field `title_key`, so users don't have
+//! to write the transformed value to their documents just to index it. See
+//! [`expr::computed`] for the expression language.
+//!
+//! Computed fields are virtual: they don't exist in the document, so their
+//! value is stored in the sorting vector, which makes them sortable and
+//! loadable like any other sortable field.
+
+use std::fmt::{self, Display};
+
+use expr::computed::{EvalError, Expr, Value};
+
+use crate::SpecError;
+
+const COMPUTED_OPT: &str = "COMPUTED";
+
+/// How the value of a computed field is indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputedKind {
+    Text,
+    Tag,
+    Numeric,
+}
+
+/// A computed field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputedField {
+    name: String,
+    kind: ComputedKind,
+    source: String,
+    expr: Expr,
+}
+
+impl ComputedField {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub const fn kind(&self) -> ComputedKind {
+        self.kind
+    }
+
+    /// The expression as written on `FT.CREATE`.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+/// An error evaluating a computed field, which fails the indexing of the
+/// document like an invalid NUMERIC value does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComputeError {
+    pub field: String,
+    pub error: EvalError,
+}
+
+impl Display for ComputeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Could not compute field `{}`: {}",
+            self.field, self.error
+        )
+    }
+}
+
+impl std::error::Error for ComputeError {}
+
+/// The computed fields of an index, in schema order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComputedFields {
+    fields: Vec<ComputedField>,
+}
+
+impl ComputedFields {
+    /// Try to handle the field option `name` of the field `field`, taking its
+    /// argument from `args`.
+    ///
+    /// Returns `Ok(false)` if `name` is not `COMPUTED`, leaving it to the
+    /// caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpecError::BadValue`] if the expression is missing or
+    /// invalid, or reads a computed field, and [`SpecError::DuplicateOption`]
+    /// if the field is already computed.
+    pub fn try_set_field_option<'a>(
+        &mut self,
+        field: &str,
+        kind: ComputedKind,
+        name: &str,
+        args: &mut impl Iterator<Item = &'a str>,
+    ) -> Result<bool, SpecError> {
+        if !name.eq_ignore_ascii_case(COMPUTED_OPT) {
+            return Ok(false);
+        }
+        let bad_value = |value: &str| SpecError::BadValue {
+            option: COMPUTED_OPT,
+            value: value.to_owned(),
+        };
+        let source = args.next().ok_or_else(|| bad_value(""))?;
+        let expr = Expr::parse(source).map_err(|_| bad_value(source))?;
+        // Computed fields are evaluated independently, from the document only.
+        if expr
+            .fields()
+            .iter()
+            .any(|&read| read == field || self.get(read).is_some())
+        {
+            return Err(bad_value(source));
+        }
+        if self.get(field).is_some() {
+            return Err(SpecError::DuplicateOption(COMPUTED_OPT));
+        }
+        self.fields.push(ComputedField {
+            name: field.to_owned(),
+            kind,
+            source: source.to_owned(),
+            expr,
+        });
+        Ok(true)
+    }
+
+    pub fn get(&self, field: &str) -> Option<&ComputedField> {
+        self.fields.iter().find(|f| f.name == field)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ComputedField> {
+        self.fields.iter()
+    }
+
+    /// Whether `field` is read by a computed field, and so can't be removed.
+    pub fn is_read(&self, field: &str) -> bool {
+        self.fields.iter().any(|f| f.expr.fields().contains(&field))
+    }
+
+    /// Forget a removed field.
+    pub fn remove(&mut self, field: &str) {
+        self.fields.retain(|f| f.name != field);
+    }
+
+    /// Compute the values of a document's computed fields, `doc(name)`
+    /// returning the value of one of its fields.
+    ///
+    /// Fields whose expression is NULL have no value. NUMERIC fields get a
+    /// number, and other fields a string.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ComputeError`] for the first expression which fails, or
+    /// whose value isn't a number for a NUMERIC field.
+    pub fn evaluate<'a>(
+        &self,
+        doc: impl Fn(&str) -> Option<&'a str>,
+    ) -> Result<Vec<(&str, Value)>, ComputeError> {
+        let mut values = Vec::with_capacity(self.fields.len());
+        for field in &self.fields {
+            let error = |error| ComputeError {
+                field: field.name.clone(),
+                error,
+            };
+            let Some(value) = field.expr.eval(&doc).map_err(error)? else {
+                continue;
+            };
+            let value = match field.kind {
+                ComputedKind::Numeric => Value::Number(value.to_number().map_err(error)?),
+                ComputedKind::Text | ComputedKind::Tag => Value::String(value.to_string()),
+            };
+            values.push((field.name.as_str(), value));
+        }
+        Ok(values)
+    }
+
+    /// The options of `field` as they would be written on `FT.CREATE`.
+    pub fn to_args(&self, field: &str) -> Vec<String> {
+        self.get(field)
+            .map(|f| vec![COMPUTED_OPT.to_owned(), f.source.clone()])
+            .unwrap_or_default()
+    }
+}
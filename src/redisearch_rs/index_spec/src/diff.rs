@@ -0,0 +1,310 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Differences between two index definitions.
+//!
+//! Users keeping their schemas as code diff the current definition of an
+//! index against the proposed one in CI, before running `FT.ALTER`. Each
+//! [`SpecChange`] reports whether it can be applied to the live index or
+//! requires rebuilding it, and whether it changes the results of existing
+//! queries. A proposed `FT.CREATE` is parsed into an [`IndexSpec`] first.
+
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+};
+
+use crate::{FieldType, IndexSpec, SchemaField};
+
+/// What applying a change takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Impact {
+    /// The change applies to the live index, e.g. with `FT.ALTER`.
+    Hot,
+    /// The documents must be indexed again.
+    Reindex,
+}
+
+/// A difference between two index definitions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecChange {
+    FieldAdded(String),
+    FieldRemoved(String),
+    FieldTypeChanged {
+        field: String,
+        from: FieldType,
+        to: FieldType,
+    },
+    /// A field option of [`SchemaField`] was added (`enabled`) or removed.
+    FieldOptionChanged {
+        field: String,
+        option: &'static str,
+        enabled: bool,
+    },
+    /// The analysis chain of a TEXT field changed.
+    AnalyzerChanged(String),
+    /// `INTEGER` was added to or removed from a NUMERIC field.
+    NumericStorageChanged(String),
+    /// `DATETIME` was added to or removed from a NUMERIC field.
+    BoundSyntaxChanged(String),
+    /// The `ORDER` of a TAG or TEXT field changed.
+    SortOrderChanged(String),
+    ComputedChanged(String),
+    PartitioningChanged,
+    QueryDefaultsChanged,
+    TermPruningChanged,
+    DetectLanguageChanged,
+    SynonymModeChanged,
+}
+
+impl SpecChange {
+    /// What applying the change takes.
+    pub const fn impact(&self) -> Impact {
+        match self {
+            // `FT.ALTER SCHEMA ADD` scans the existing documents in the
+            // background.
+            Self::FieldAdded(_) | Self::QueryDefaultsChanged => Impact::Hot,
+            // Pruning is sticky, new thresholds only apply from then on.
+            Self::TermPruningChanged => Impact::Hot,
+            // Orders are applied to the sortable values when sorting.
+            Self::SortOrderChanged(_) => Impact::Hot,
+            // Range bounds are parsed with the syntax of the field when
+            // queries run, the stored values don't change.
+            Self::BoundSyntaxChanged(_) => Impact::Hot,
+            Self::FieldRemoved(_)
+            | Self::FieldTypeChanged { .. }
+            | Self::FieldOptionChanged { .. }
+            | Self::AnalyzerChanged(_)
+            | Self::NumericStorageChanged(_)
+            | Self::ComputedChanged(_)
+            | Self::PartitioningChanged
+            | Self::DetectLanguageChanged
+            | Self::SynonymModeChanged => Impact::Reindex,
+        }
+    }
+
+    /// Whether existing queries may return different results, or fail,
+    /// once the change is applied.
+    pub const fn changes_query_semantics(&self) -> bool {
+        match self {
+            // Queries can't reference a field which didn't exist.
+            Self::FieldAdded(_) | Self::PartitioningChanged => false,
+            // `SORTABLE` changes the order of ties, the others what can be
+            // matched.
+            Self::FieldOptionChanged { .. } => true,
+            Self::FieldRemoved(_)
+            | Self::FieldTypeChanged { .. }
+            | Self::AnalyzerChanged(_)
+            | Self::NumericStorageChanged(_)
+            | Self::BoundSyntaxChanged(_)
+            | Self::SortOrderChanged(_)
+            | Self::ComputedChanged(_)
+            | Self::QueryDefaultsChanged
+            | Self::TermPruningChanged
+            | Self::DetectLanguageChanged
+            | Self::SynonymModeChanged => true,
+        }
+    }
+}
+
+impl Display for SpecChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FieldAdded(field) => write!(f, "field `{field}` added"),
+            Self::FieldRemoved(field) => write!(f, "field `{field}` removed"),
+            Self::FieldTypeChanged { field, from, to } => {
+                write!(f, "field `{field}` changed from {from} to {to}")
+            }
+            Self::FieldOptionChanged {
+                field,
+                option,
+                enabled,
+            } => {
+                let action = if *enabled { "added to" } else { "removed from" };
+                write!(f, "{option} {action} field `{field}`")
+            }
+            Self::AnalyzerChanged(field) => write!(f, "analysis of field `{field}` changed"),
+            Self::NumericStorageChanged(field) => {
+                write!(f, "storage of field `{field}` changed")
+            }
+            Self::BoundSyntaxChanged(field) => {
+                write!(f, "DATETIME changed on field `{field}`")
+            }
+            Self::SortOrderChanged(field) => write!(f, "sort order of field `{field}` changed"),
+            Self::ComputedChanged(field) => {
+                write!(f, "expression of computed field `{field}` changed")
+            }
+            Self::PartitioningChanged => f.write_str("PARTITIONBY changed"),
+            Self::QueryDefaultsChanged => f.write_str("query defaults changed"),
+            Self::TermPruningChanged => f.write_str("term pruning changed"),
+            Self::DetectLanguageChanged => f.write_str("DETECTLANGUAGE changed"),
+            Self::SynonymModeChanged => f.write_str("SYNONYMMODE changed"),
+        }
+    }
+}
+
+/// The differences between two index definitions, see [`diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpecDiff {
+    changes: Vec<SpecChange>,
+}
+
+impl SpecDiff {
+    /// The changes, fields first in the order of the new schema.
+    pub fn changes(&self) -> &[SpecChange] {
+        &self.changes
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// What applying all the changes takes.
+    pub fn impact(&self) -> Impact {
+        self.changes
+            .iter()
+            .map(SpecChange::impact)
+            .max()
+            .unwrap_or(Impact::Hot)
+    }
+
+    /// The changes which may change the results of existing queries.
+    pub fn semantic_changes(&self) -> impl Iterator<Item = &SpecChange> {
+        self.changes.iter().filter(|c| c.changes_query_semantics())
+    }
+}
+
+/// One line per change, with its impact.
+impl Display for SpecDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            let impact = match change.impact() {
+                Impact::Hot => "hot",
+                Impact::Reindex => "reindex",
+            };
+            let semantics = if change.changes_query_semantics() {
+                ", changes query results"
+            } else {
+                ""
+            };
+            writeln!(f, "{change} ({impact}{semantics})")?;
+        }
+        Ok(())
+    }
+}
+
+/// The changes turning `old` into `new`.
+pub fn diff(old: &IndexSpec, new: &IndexSpec) -> SpecDiff {
+    let mut changes = Vec::new();
+
+    for field in old.fields() {
+        if new.field(&field.name).is_none() {
+            changes.push(SpecChange::FieldRemoved(field.name.clone()));
+        }
+    }
+    for field in new.fields() {
+        match old.field(&field.name) {
+            None => changes.push(SpecChange::FieldAdded(field.name.clone())),
+            Some(previous) => diff_field(old, previous, new, field, &mut changes),
+        }
+    }
+
+    let computed: BTreeSet<&str> = old
+        .computed_fields()
+        .iter()
+        .chain(new.computed_fields().iter())
+        .map(|c| c.name())
+        .collect();
+    for name in computed {
+        let (before, after) = (
+            old.computed_fields().get(name),
+            new.computed_fields().get(name),
+        );
+        // Computed fields added or removed along with their field are
+        // reported as such.
+        if before.is_some() && after.is_some() && before != after {
+            changes.push(SpecChange::ComputedChanged(name.to_owned()));
+        }
+    }
+
+    if old.partitioning() != new.partitioning() {
+        changes.push(SpecChange::PartitioningChanged);
+    }
+    if old.query_defaults() != new.query_defaults() {
+        changes.push(SpecChange::QueryDefaultsChanged);
+    }
+    if old.term_pruning() != new.term_pruning() {
+        changes.push(SpecChange::TermPruningChanged);
+    }
+    if old.detect_language() != new.detect_language() {
+        changes.push(SpecChange::DetectLanguageChanged);
+    }
+    if old.synonym_mode() != new.synonym_mode() {
+        changes.push(SpecChange::SynonymModeChanged);
+    }
+    SpecDiff { changes }
+}
+
+fn diff_field(
+    old_spec: &IndexSpec,
+    old: &SchemaField,
+    new_spec: &IndexSpec,
+    new: &SchemaField,
+    changes: &mut Vec<SpecChange>,
+) {
+    let name = &new.name;
+    if old.field_type != new.field_type {
+        changes.push(SpecChange::FieldTypeChanged {
+            field: name.clone(),
+            from: old.field_type,
+            to: new.field_type,
+        });
+        // The options of different types aren't comparable.
+        return;
+    }
+    let options = [
+        ("SORTABLE", old.sortable, new.sortable),
+        ("NOINDEX", old.no_index, new.no_index),
+        ("INDEXMISSING", old.index_missing, new.index_missing),
+        ("INDEXEMPTY", old.index_empty, new.index_empty),
+    ];
+    for (option, before, after) in options {
+        if before != after {
+            changes.push(SpecChange::FieldOptionChanged {
+                field: name.clone(),
+                option,
+                enabled: after,
+            });
+        }
+    }
+    match new.field_type {
+        FieldType::Text
+            if old_spec.field_analyzers().for_field(name)
+                != new_spec.field_analyzers().for_field(name) =>
+        {
+            changes.push(SpecChange::AnalyzerChanged(name.clone()));
+        }
+        FieldType::Numeric
+            if old_spec.numeric_fields().storage(name)
+                != new_spec.numeric_fields().storage(name) =>
+        {
+            changes.push(SpecChange::NumericStorageChanged(name.clone()));
+        }
+        _ => {}
+    }
+    if new.field_type == FieldType::Numeric
+        && old_spec.numeric_fields().bound_syntax(name)
+            != new_spec.numeric_fields().bound_syntax(name)
+    {
+        changes.push(SpecChange::BoundSyntaxChanged(name.clone()));
+    }
+    if old_spec.sort_orders().get(name) != new_spec.sort_orders().get(name) {
+        changes.push(SpecChange::SortOrderChanged(name.clone()));
+    }
+}
@@ -0,0 +1,278 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Per-field analysis chains.
+//!
+//! A TEXT field may declare its own analysis chain with the following field
+//! options on `FT.CREATE`:
+//!
+//! - `TOKENIZER {name}`: the tokenizer, `standard` by default, or `ngram` and
+//!   `edge_ngram` for substring search;
+//! - `MINGRAM {n}` and `MAXGRAM {n}`: the gram lengths of the n-gram tokenizers;
+//! - `NOTOKENIZE`: index the whole value as a single term, lowercased unless
+//!   `CASESENSITIVE` is given too. Unlike a TAG, the field is scored and
+//!   highlighted, and supports prefix and fuzzy matching;
+//! - `WORDDELIMITER {n} {option}...`: split technical terms into their parts,
+//!   see [`analysis::word_delimiter`] for the options;
+//! - `NOSTEM`: don't stem the terms;
+//! - `LANGUAGE {language}`: stem with `language` rather than the index language;
+//! - `STOPWORDS {n} {word}...`: the stopwords, instead of those of the index;
+//! - `SYNONYMS {set}`: expand the terms with the given synonym set;
+//! - `PHONETIC {matcher}`: add phonetic codes, e.g. `dm:en`;
+//! - `MAXTERMLEN {n}`: the longest term, in bytes, 128 by default;
+//! - `LONGTERMS {policy}`: what to do with the longer terms, `TRUNCATE` them
+//!   (the default), `DROP` them or index a `HASH` of them, see
+//!   [`analysis::long_terms`].
+//!
+//! Queries targeting a field explicitly are analyzed with its chain, so that
+//! their terms match what was indexed. Queries targeting several fields use
+//! their chain if they all share it, and the default chain otherwise.
+
+use std::collections::BTreeMap;
+
+use analysis::{
+    AnalyzerConfig, LongTermPolicy, TermLimit, TokenizerKind, word_delimiter::WordDelimiterOptions,
+};
+use query::FieldSelector;
+
+use crate::SpecError;
+
+const TOKENIZER_OPT: &str = "TOKENIZER";
+const MIN_GRAM_OPT: &str = "MINGRAM";
+const MAX_GRAM_OPT: &str = "MAXGRAM";
+const NOTOKENIZE_OPT: &str = "NOTOKENIZE";
+const CASE_SENSITIVE_OPT: &str = "CASESENSITIVE";
+const WORD_DELIMITER_OPT: &str = "WORDDELIMITER";
+const NOSTEM_OPT: &str = "NOSTEM";
+const LANGUAGE_OPT: &str = "LANGUAGE";
+const STOPWORDS_OPT: &str = "STOPWORDS";
+const SYNONYMS_OPT: &str = "SYNONYMS";
+const PHONETIC_OPT: &str = "PHONETIC";
+const MAX_TERM_LEN_OPT: &str = "MAXTERMLEN";
+const LONG_TERMS_OPT: &str = "LONGTERMS";
+
+/// See the [module documentation](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldAnalyzers {
+    default: AnalyzerConfig,
+    fields: BTreeMap<String, AnalyzerConfig>,
+}
+
+impl FieldAnalyzers {
+    /// Try to handle the field option `name` of a TEXT field, whose arguments
+    /// are consumed from `args`.
+    ///
+    /// Returns `Ok(false)` if `name` is not an analysis option, leaving it to
+    /// the caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpecError::BadValue`] if an argument is missing or invalid.
+    pub fn try_set_field_option<'a>(
+        config: &mut AnalyzerConfig,
+        name: &str,
+        args: &mut impl Iterator<Item = &'a str>,
+    ) -> Result<bool, SpecError> {
+        let mut next = |option| {
+            args.next().ok_or(SpecError::BadValue {
+                option,
+                value: String::new(),
+            })
+        };
+        let bad_value = |option, value: &str| SpecError::BadValue {
+            option,
+            value: value.to_owned(),
+        };
+
+        if name.eq_ignore_ascii_case(TOKENIZER_OPT) {
+            let value = next(TOKENIZER_OPT)?;
+            config.tokenizer = value
+                .parse()
+                .map_err(|()| bad_value(TOKENIZER_OPT, value))?;
+        } else if name.eq_ignore_ascii_case(MIN_GRAM_OPT) || name.eq_ignore_ascii_case(MAX_GRAM_OPT)
+        {
+            let option = if name.eq_ignore_ascii_case(MIN_GRAM_OPT) {
+                MIN_GRAM_OPT
+            } else {
+                MAX_GRAM_OPT
+            };
+            let value = next(option)?;
+            let n: u8 = value
+                .parse()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| bad_value(option, value))?;
+            let (TokenizerKind::NGram { min, max } | TokenizerKind::EdgeNGram { min, max }) =
+                &mut config.tokenizer
+            else {
+                // The option only makes sense after `TOKENIZER ngram`.
+                return Err(bad_value(option, value));
+            };
+            // Keep `min <= max` whatever the order of the options.
+            let (new_min, new_max) = if option == MIN_GRAM_OPT {
+                (n, (*max).max(n))
+            } else {
+                ((*min).min(n), n)
+            };
+            (*min, *max) = (new_min, new_max);
+        } else if name.eq_ignore_ascii_case(NOTOKENIZE_OPT) {
+            if !config.tokenizer.is_keyword() {
+                config.tokenizer = TokenizerKind::Keyword { case_fold: true };
+            }
+        } else if name.eq_ignore_ascii_case(CASE_SENSITIVE_OPT) {
+            let TokenizerKind::Keyword { case_fold } = &mut config.tokenizer else {
+                // The option only makes sense after `NOTOKENIZE`.
+                return Err(bad_value(CASE_SENSITIVE_OPT, name));
+            };
+            *case_fold = false;
+        } else if name.eq_ignore_ascii_case(WORD_DELIMITER_OPT) {
+            let count = next(WORD_DELIMITER_OPT)?;
+            let count: usize = count
+                .parse()
+                .map_err(|_| bad_value(WORD_DELIMITER_OPT, count))?;
+            let mut options = WordDelimiterOptions::default();
+            for _ in 0..count {
+                let option = next(WORD_DELIMITER_OPT)?;
+                if !options.set(option) {
+                    return Err(bad_value(WORD_DELIMITER_OPT, option));
+                }
+            }
+            config.word_delimiter = Some(options);
+        } else if name.eq_ignore_ascii_case(NOSTEM_OPT) {
+            config.stem = false;
+        } else if name.eq_ignore_ascii_case(LANGUAGE_OPT) {
+            config.language = Some(next(LANGUAGE_OPT)?.to_ascii_lowercase());
+        } else if name.eq_ignore_ascii_case(STOPWORDS_OPT) {
+            let count = next(STOPWORDS_OPT)?;
+            let count: usize = count.parse().map_err(|_| bad_value(STOPWORDS_OPT, count))?;
+            let words = (0..count)
+                .map(|_| next(STOPWORDS_OPT).map(str::to_owned))
+                .collect::<Result<_, _>>()?;
+            config.stopwords = Some(words);
+        } else if name.eq_ignore_ascii_case(SYNONYMS_OPT) {
+            config.synonyms = Some(next(SYNONYMS_OPT)?.to_owned());
+        } else if name.eq_ignore_ascii_case(PHONETIC_OPT) {
+            config.phonetic = Some(next(PHONETIC_OPT)?.to_owned());
+        } else if name.eq_ignore_ascii_case(MAX_TERM_LEN_OPT) {
+            let value = next(MAX_TERM_LEN_OPT)?;
+            config.term_limit.max_len = value
+                .parse()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| bad_value(MAX_TERM_LEN_OPT, value))?;
+        } else if name.eq_ignore_ascii_case(LONG_TERMS_OPT) {
+            let value = next(LONG_TERMS_OPT)?;
+            config.term_limit.policy = value
+                .parse()
+                .map_err(|()| bad_value(LONG_TERMS_OPT, value))?;
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Set the chain of `field`. Fields without one use the default chain.
+    pub fn set(&mut self, field: impl Into<String>, config: AnalyzerConfig) {
+        let field = field.into();
+        if config == self.default {
+            self.fields.remove(&field);
+        } else {
+            self.fields.insert(field, config);
+        }
+    }
+
+    /// Forget the chain of a removed field.
+    pub fn remove(&mut self, field: &str) {
+        self.fields.remove(field);
+    }
+
+    /// The chain of `field`.
+    pub fn for_field(&self, field: &str) -> &AnalyzerConfig {
+        self.fields.get(field).unwrap_or(&self.default)
+    }
+
+    /// The chain to analyze the terms of a query node targeting `fields` with.
+    pub fn for_query(&self, fields: &FieldSelector) -> &AnalyzerConfig {
+        match fields {
+            FieldSelector::Named(names) => {
+                let mut configs = names.iter().map(|name| self.for_field(name));
+                match configs.next() {
+                    Some(first) if configs.all(|c| c == first) => first,
+                    _ => &self.default,
+                }
+            }
+            FieldSelector::All => &self.default,
+        }
+    }
+
+    /// The options of `field` as they would be written on `FT.CREATE`.
+    pub fn to_args(&self, field: &str) -> Vec<String> {
+        let Some(config) = self.fields.get(field) else {
+            return Vec::new();
+        };
+        let mut args = Vec::new();
+        match config.tokenizer {
+            TokenizerKind::Standard => {}
+            TokenizerKind::Keyword { case_fold } => {
+                args.push(NOTOKENIZE_OPT.to_owned());
+                if !case_fold {
+                    args.push(CASE_SENSITIVE_OPT.to_owned());
+                }
+            }
+            tokenizer => {
+                args.extend([TOKENIZER_OPT.to_owned(), tokenizer.name().to_owned()]);
+            }
+        }
+        if let TokenizerKind::NGram { min, max } | TokenizerKind::EdgeNGram { min, max } =
+            config.tokenizer
+        {
+            args.extend([
+                MIN_GRAM_OPT.to_owned(),
+                min.to_string(),
+                MAX_GRAM_OPT.to_owned(),
+                max.to_string(),
+            ]);
+        }
+        if let Some(options) = config.word_delimiter {
+            let names = options.names();
+            args.extend([WORD_DELIMITER_OPT.to_owned(), names.len().to_string()]);
+            args.extend(names.into_iter().map(str::to_owned));
+        }
+        if !config.stem {
+            args.push(NOSTEM_OPT.to_owned());
+        }
+        if let Some(language) = &config.language {
+            args.extend([LANGUAGE_OPT.to_owned(), language.clone()]);
+        }
+        if let Some(words) = &config.stopwords {
+            args.extend([STOPWORDS_OPT.to_owned(), words.len().to_string()]);
+            args.extend(words.iter().cloned());
+        }
+        if let Some(set) = &config.synonyms {
+            args.extend([SYNONYMS_OPT.to_owned(), set.clone()]);
+        }
+        if let Some(matcher) = &config.phonetic {
+            args.extend([PHONETIC_OPT.to_owned(), matcher.clone()]);
+        }
+        let default_limit = TermLimit::default();
+        if config.term_limit.max_len != default_limit.max_len {
+            args.extend([
+                MAX_TERM_LEN_OPT.to_owned(),
+                config.term_limit.max_len.to_string(),
+            ]);
+        }
+        if config.term_limit.policy != LongTermPolicy::default() {
+            args.extend([
+                LONG_TERMS_OPT.to_owned(),
+                config.term_limit.policy.name().to_owned(),
+            ]);
+        }
+        args
+    }
+}
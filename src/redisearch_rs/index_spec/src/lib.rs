@@ -0,0 +1,229 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The definition of an index, as created by `FT.CREATE` and modified by `FT.ALTER`.
+//!
+//! This crate only holds the parts of `IndexSpec` that have been ported to Rust
+//! so far; the remaining state still lives in `spec.h`.
+
+pub mod computed_fields;
+pub mod diff;
+pub mod field_analyzers;
+pub mod numeric_storage;
+pub mod partitioning;
+pub mod query_defaults;
+pub mod schema;
+pub mod sort_orders;
+pub mod term_pruning;
+
+use std::fmt::{self, Display};
+
+use synonyms::SynonymMode;
+
+pub use computed_fields::ComputedFields;
+pub use diff::SpecDiff;
+pub use field_analyzers::FieldAnalyzers;
+pub use numeric_storage::NumericFields;
+pub use partitioning::Partitioning;
+pub use query_defaults::QueryDefaults;
+pub use schema::{FieldType, SchemaField};
+pub use sort_orders::SortOrders;
+pub use term_pruning::TermPruning;
+
+/// Errors returned when building or altering an [`IndexSpec`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecError {
+    /// The value given to an option is invalid.
+    BadValue { option: &'static str, value: String },
+    /// The option was given more than once.
+    DuplicateOption(&'static str),
+    /// The schema already has a field with this name.
+    DuplicateField(String),
+}
+
+impl Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadValue { option, value } => write!(f, "Invalid value for {option}: {value}"),
+            Self::DuplicateOption(option) => write!(f, "Option {option} was specified twice"),
+            Self::DuplicateField(field) => write!(f, "Duplicate field in schema - {field}"),
+        }
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+/// The Rust-owned part of an index definition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexSpec {
+    name: String,
+    fields: Vec<SchemaField>,
+    query_defaults: QueryDefaults,
+    field_analyzers: FieldAnalyzers,
+    numeric_fields: NumericFields,
+    computed_fields: ComputedFields,
+    sort_orders: SortOrders,
+    partitioning: Option<Partitioning>,
+    term_pruning: TermPruning,
+    detect_language: bool,
+    synonym_mode: SynonymMode,
+}
+
+impl IndexSpec {
+    /// Create a spec for the index `name` with default settings.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            fields: Vec::new(),
+            query_defaults: QueryDefaults::default(),
+            field_analyzers: FieldAnalyzers::default(),
+            numeric_fields: NumericFields::default(),
+            computed_fields: ComputedFields::default(),
+            sort_orders: SortOrders::default(),
+            partitioning: None,
+            term_pruning: TermPruning::default(),
+            detect_language: false,
+            synonym_mode: SynonymMode::Index,
+        }
+    }
+
+    /// The name of the index.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The fields of the schema, in declaration order.
+    pub fn fields(&self) -> &[SchemaField] {
+        &self.fields
+    }
+
+    /// The field named `name`.
+    pub fn field(&self, name: &str) -> Option<&SchemaField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    /// Add a field to the schema, while parsing `FT.CREATE` or `FT.ALTER
+    /// SCHEMA ADD`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpecError::DuplicateField`] if a field with the same name
+    /// already exists.
+    pub fn add_field(&mut self, field: SchemaField) -> Result<(), SpecError> {
+        if self.field(&field.name).is_some() {
+            return Err(SpecError::DuplicateField(field.name));
+        }
+        self.fields.push(field);
+        Ok(())
+    }
+
+    /// Defaults and restrictions applied to every query against this index.
+    pub const fn query_defaults(&self) -> &QueryDefaults {
+        &self.query_defaults
+    }
+
+    /// Mutable access to the query defaults, used while parsing `FT.CREATE`.
+    pub const fn query_defaults_mut(&mut self) -> &mut QueryDefaults {
+        &mut self.query_defaults
+    }
+
+    /// The analysis chains of the TEXT fields.
+    pub const fn field_analyzers(&self) -> &FieldAnalyzers {
+        &self.field_analyzers
+    }
+
+    /// Mutable access to the analysis chains, used while parsing `FT.CREATE`
+    /// and `FT.ALTER`.
+    pub const fn field_analyzers_mut(&mut self) -> &mut FieldAnalyzers {
+        &mut self.field_analyzers
+    }
+
+    /// The storage of the NUMERIC fields.
+    pub const fn numeric_fields(&self) -> &NumericFields {
+        &self.numeric_fields
+    }
+
+    /// Mutable access to the storage of the NUMERIC fields, used while parsing
+    /// `FT.CREATE` and `FT.ALTER`.
+    pub const fn numeric_fields_mut(&mut self) -> &mut NumericFields {
+        &mut self.numeric_fields
+    }
+
+    /// The fields computed from other fields when documents are indexed.
+    pub const fn computed_fields(&self) -> &ComputedFields {
+        &self.computed_fields
+    }
+
+    /// Mutable access to the computed fields, used while parsing `FT.CREATE`
+    /// and `FT.ALTER`.
+    pub const fn computed_fields_mut(&mut self) -> &mut ComputedFields {
+        &mut self.computed_fields
+    }
+
+    /// The declared sort orders of the TAG and TEXT fields.
+    pub const fn sort_orders(&self) -> &SortOrders {
+        &self.sort_orders
+    }
+
+    /// Mutable access to the sort orders, used while parsing `FT.CREATE` and
+    /// `FT.ALTER`.
+    pub const fn sort_orders_mut(&mut self) -> &mut SortOrders {
+        &mut self.sort_orders
+    }
+
+    /// How documents are routed to partitions (`PARTITIONBY`), if the index
+    /// is partitioned.
+    pub const fn partitioning(&self) -> Option<&Partitioning> {
+        self.partitioning.as_ref()
+    }
+
+    /// Set the partitioning scheme, while parsing `FT.CREATE`. It can't be
+    /// changed afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpecError::DuplicateOption`] if the scheme was already set.
+    pub fn set_partitioning(&mut self, partitioning: Partitioning) -> Result<(), SpecError> {
+        if self.partitioning.is_some() {
+            return Err(SpecError::DuplicateOption("PARTITIONBY"));
+        }
+        self.partitioning = Some(partitioning);
+        Ok(())
+    }
+
+    /// The frequent-term pruning options.
+    pub const fn term_pruning(&self) -> &TermPruning {
+        &self.term_pruning
+    }
+
+    /// Mutable access to the pruning options, used while parsing `FT.CREATE`.
+    pub const fn term_pruning_mut(&mut self) -> &mut TermPruning {
+        &mut self.term_pruning
+    }
+
+    /// Whether the language of documents without `LANGUAGE_FIELD` is detected
+    /// from their text (`DETECTLANGUAGE`), rather than assumed to be the
+    /// index language.
+    pub const fn detect_language(&self) -> bool {
+        self.detect_language
+    }
+
+    pub const fn set_detect_language(&mut self, detect: bool) {
+        self.detect_language = detect;
+    }
+
+    /// When synonyms are applied (`SYNONYMMODE`).
+    pub const fn synonym_mode(&self) -> SynonymMode {
+        self.synonym_mode
+    }
+
+    pub const fn set_synonym_mode(&mut self, mode: SynonymMode) {
+        self.synonym_mode = mode;
+    }
+}
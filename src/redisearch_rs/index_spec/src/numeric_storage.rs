@@ -0,0 +1,191 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Integer storage of NUMERIC fields.
+//!
+//! Numeric values are stored as `f64`, which can't represent every integer
+//! above 2^53: snowflake-style IDs lose precision and distinct IDs collide.
+//! The `INTEGER` field option, e.g. `id NUMERIC INTEGER`, stores the values of
+//! a field as exact `i64`s instead. Range queries on such fields are parsed
+//! with [`IntegerRange`](query::numeric::IntegerRange), and values are sorted
+//! and replied as integers.
+//!
+//! The `DATETIME` field option, e.g. `created NUMERIC DATETIME`, doesn't change
+//! the storage: the values are timestamps, in seconds since the Unix epoch.
+//! It lets the bounds of range queries on the field be written as ISO-8601
+//! literals too, see [`BoundSyntax`].
+
+use std::{
+    cmp::Ordering,
+    collections::BTreeSet,
+    fmt::{self, Display},
+};
+
+use query::numeric::{BoundSyntax, NumericSchema};
+
+use crate::SpecError;
+
+const INTEGER_OPT: &str = "INTEGER";
+const DATETIME_OPT: &str = "DATETIME";
+
+/// Integers from documents written as floats, e.g. `12.0`, are only accepted
+/// up to this magnitude, beyond which the float may not be the integer that
+/// was meant.
+const MAX_EXACT_FLOAT: f64 = 9_007_199_254_740_992.0;
+
+/// How the values of a NUMERIC field are stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumericStorage {
+    #[default]
+    Double,
+    Integer,
+}
+
+impl NumericStorage {
+    /// Parse the value of a document field, as received from the client.
+    ///
+    /// Returns `None` if `value` isn't a number, or, for integer storage, if
+    /// it isn't an integer in the `i64` range.
+    pub fn parse_value(self, value: &str) -> Option<NumericValue> {
+        let value = value.trim();
+        match self {
+            Self::Double => {
+                let number: f64 = value.parse().ok()?;
+                (!number.is_nan()).then_some(NumericValue::Double(number))
+            }
+            Self::Integer => {
+                if let Ok(integer) = value.parse() {
+                    return Some(NumericValue::Integer(integer));
+                }
+                let number: f64 = value.parse().ok()?;
+                (number.fract() == 0.0 && number.abs() <= MAX_EXACT_FLOAT)
+                    .then_some(NumericValue::Integer(number as i64))
+            }
+        }
+    }
+}
+
+/// A value of a NUMERIC field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumericValue {
+    Double(f64),
+    Integer(i64),
+}
+
+impl NumericValue {
+    /// The value as an `f64`, for scoring and for `APPLY` expressions.
+    pub const fn as_f64(self) -> f64 {
+        match self {
+            Self::Double(value) => value,
+            Self::Integer(value) => value as f64,
+        }
+    }
+
+    /// The order of the values when sorting by the field.
+    ///
+    /// Integers are compared exactly. Values of different storages only meet
+    /// while a field is being reindexed, and are compared as `f64`s.
+    pub fn sort_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Integer(a), Self::Integer(b)) => a.cmp(b),
+            _ => self.as_f64().total_cmp(&other.as_f64()),
+        }
+    }
+}
+
+/// The value as replied to the client.
+///
+/// Integers are written in full rather than with the `%.17g` format of
+/// doubles, so RESP2 clients receive the exact value as a bulk string. RESP3
+/// clients receive integers as RESP integers.
+impl Display for NumericValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Double(value) => write!(f, "{value}"),
+            Self::Integer(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// The storage and bound syntax of the NUMERIC fields of an index.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NumericFields {
+    integer: BTreeSet<String>,
+    datetime: BTreeSet<String>,
+}
+
+impl NumericFields {
+    /// Try to handle the field option `name` of the NUMERIC field `field`.
+    ///
+    /// Returns `Ok(false)` if `name` is neither `INTEGER` nor `DATETIME`,
+    /// leaving it to the caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpecError::DuplicateOption`] if the option was already given.
+    pub fn try_set_field_option(&mut self, field: &str, name: &str) -> Result<bool, SpecError> {
+        let (option, fields) = if name.eq_ignore_ascii_case(INTEGER_OPT) {
+            (INTEGER_OPT, &mut self.integer)
+        } else if name.eq_ignore_ascii_case(DATETIME_OPT) {
+            (DATETIME_OPT, &mut self.datetime)
+        } else {
+            return Ok(false);
+        };
+        if !fields.insert(field.to_owned()) {
+            return Err(SpecError::DuplicateOption(option));
+        }
+        Ok(true)
+    }
+
+    /// The storage of `field`.
+    pub fn storage(&self, field: &str) -> NumericStorage {
+        if self.integer.contains(field) {
+            NumericStorage::Integer
+        } else {
+            NumericStorage::Double
+        }
+    }
+
+    /// How the bounds of range queries on `field` are written.
+    pub fn bound_syntax(&self, field: &str) -> BoundSyntax {
+        if self.datetime.contains(field) {
+            BoundSyntax::DateTime
+        } else {
+            BoundSyntax::Number
+        }
+    }
+
+    /// Forget the options of a removed field.
+    pub fn remove(&mut self, field: &str) {
+        self.integer.remove(field);
+        self.datetime.remove(field);
+    }
+
+    /// The options of `field` as they would be written on `FT.CREATE`.
+    pub fn to_args(&self, field: &str) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.storage(field) == NumericStorage::Integer {
+            args.push(INTEGER_OPT.to_owned());
+        }
+        if self.bound_syntax(field) == BoundSyntax::DateTime {
+            args.push(DATETIME_OPT.to_owned());
+        }
+        args
+    }
+}
+
+impl NumericSchema for NumericFields {
+    fn bound_syntax(&self, field: &str) -> BoundSyntax {
+        Self::bound_syntax(self, field)
+    }
+
+    fn integer_storage(&self, field: &str) -> bool {
+        self.storage(field) == NumericStorage::Integer
+    }
+}
@@ -0,0 +1,388 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Native partitioning of an index by time.
+//!
+//! With `PARTITIONBY {field} {HOUR|DAY|MONTH}`, documents are routed to a
+//! sub-index per period of the timestamp held by `field`, in seconds since the
+//! Unix epoch. Queries constraining `field` only run on the partitions the
+//! constraint overlaps, and old partitions are dropped as a whole instead of
+//! deleting their documents one by one. This replaces the pattern of creating
+//! an index per month and querying them all from the client.
+//!
+//! Sub-indexes are named `{index}@{period}`, e.g. `logs@2024-01-31` for daily
+//! partitions.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+use query::{
+    node::{QueryNode, QueryNodeKind},
+    numeric::{IntegerRange, NumericRange},
+};
+
+use crate::SpecError;
+
+const PARTITION_BY_OPT: &str = "PARTITIONBY";
+
+const SECONDS_PER_HOUR: i64 = 3600;
+const SECONDS_PER_DAY: i64 = 24 * SECONDS_PER_HOUR;
+
+/// The length of the partitions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    Hour,
+    Day,
+    Month,
+}
+
+impl Granularity {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_uppercase().as_str() {
+            "HOUR" => Some(Self::Hour),
+            "DAY" => Some(Self::Day),
+            "MONTH" => Some(Self::Month),
+            _ => None,
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Hour => "HOUR",
+            Self::Day => "DAY",
+            Self::Month => "MONTH",
+        }
+    }
+}
+
+/// Identifies a partition: the number of periods since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PartitionKey(pub i64);
+
+/// Errors returned when routing a document to its partition.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PartitionError {
+    /// The document doesn't have the partitioning field.
+    MissingField(String),
+    /// The value of the partitioning field isn't a finite number.
+    BadValue { field: String, value: String },
+}
+
+impl Display for PartitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingField(field) => write!(f, "Missing partitioning field `{field}`"),
+            Self::BadValue { field, value } => {
+                write!(
+                    f,
+                    "Invalid timestamp for partitioning field `{field}`: {value}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PartitionError {}
+
+/// The partitioning scheme of an index, set by `PARTITIONBY`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Partitioning {
+    field: String,
+    granularity: Granularity,
+}
+
+impl Partitioning {
+    /// Try to handle the `FT.CREATE` option `name`, taking its arguments from
+    /// `args`.
+    ///
+    /// Returns `Ok(None)` if `name` is not the partitioning option, leaving it
+    /// to the caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpecError::BadValue`] if the field or the granularity is
+    /// missing, or if the granularity is unknown.
+    pub fn try_parse_option<'a>(
+        name: &str,
+        args: &mut impl Iterator<Item = &'a str>,
+    ) -> Result<Option<Self>, SpecError> {
+        if !name.eq_ignore_ascii_case(PARTITION_BY_OPT) {
+            return Ok(None);
+        }
+        let bad_value = |value: Option<&str>| SpecError::BadValue {
+            option: PARTITION_BY_OPT,
+            value: value.unwrap_or_default().to_owned(),
+        };
+        let field = args.next().ok_or_else(|| bad_value(None))?;
+        let granularity = args.next();
+        let granularity = granularity
+            .and_then(Granularity::parse)
+            .ok_or_else(|| bad_value(granularity))?;
+        Ok(Some(Self::new(field, granularity)))
+    }
+
+    pub fn new(field: impl Into<String>, granularity: Granularity) -> Self {
+        Self {
+            field: field.into(),
+            granularity,
+        }
+    }
+
+    /// The field holding the timestamp documents are partitioned by.
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    pub const fn granularity(&self) -> Granularity {
+        self.granularity
+    }
+
+    /// The option as it would be written on `FT.CREATE`.
+    pub fn to_args(&self) -> Vec<String> {
+        vec![
+            PARTITION_BY_OPT.to_owned(),
+            self.field.clone(),
+            self.granularity.name().to_owned(),
+        ]
+    }
+
+    /// The partition of a document whose partitioning field holds `value`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PartitionError`] if the field is missing or isn't a number.
+    pub fn route(&self, value: Option<&str>) -> Result<PartitionKey, PartitionError> {
+        let value = value.ok_or_else(|| PartitionError::MissingField(self.field.clone()))?;
+        let timestamp: f64 = value
+            .trim()
+            .parse()
+            .ok()
+            .filter(|t: &f64| t.is_finite())
+            .ok_or_else(|| PartitionError::BadValue {
+                field: self.field.clone(),
+                value: value.to_owned(),
+            })?;
+        Ok(self.key_of(timestamp.floor() as i64))
+    }
+
+    /// The partition holding the timestamp `seconds`.
+    pub const fn key_of(&self, seconds: i64) -> PartitionKey {
+        PartitionKey(match self.granularity {
+            Granularity::Hour => seconds.div_euclid(SECONDS_PER_HOUR),
+            Granularity::Day => seconds.div_euclid(SECONDS_PER_DAY),
+            Granularity::Month => {
+                let (year, month, _) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+                (year - 1970) * 12 + (month as i64 - 1)
+            }
+        })
+    }
+
+    /// The timestamps `[start, end)` covered by the partition `key`.
+    pub const fn bounds(&self, key: PartitionKey) -> (i64, i64) {
+        match self.granularity {
+            Granularity::Hour => (key.0 * SECONDS_PER_HOUR, (key.0 + 1) * SECONDS_PER_HOUR),
+            Granularity::Day => (key.0 * SECONDS_PER_DAY, (key.0 + 1) * SECONDS_PER_DAY),
+            Granularity::Month => (month_start(key.0), month_start(key.0 + 1)),
+        }
+    }
+
+    /// The name of the sub-index of the partition `key` of `index`.
+    pub fn sub_index_name(&self, index: &str, key: PartitionKey) -> String {
+        let (start, _) = self.bounds(key);
+        let (year, month, day) = civil_from_days(start.div_euclid(SECONDS_PER_DAY));
+        match self.granularity {
+            Granularity::Hour => {
+                let hour = start.rem_euclid(SECONDS_PER_DAY) / SECONDS_PER_HOUR;
+                format!("{index}@{year:04}-{month:02}-{day:02}T{hour:02}")
+            }
+            Granularity::Day => format!("{index}@{year:04}-{month:02}-{day:02}"),
+            Granularity::Month => format!("{index}@{year:04}-{month:02}"),
+        }
+    }
+
+    /// Whether the partition `key` may hold values in `range`.
+    pub fn overlaps(&self, key: PartitionKey, range: &NumericRange) -> bool {
+        let (start, end) = self.bounds(key);
+        let (start, end) = (start as f64, end as f64);
+        (range.max > start || (range.max == start && range.max_inclusive)) && range.min < end
+    }
+
+    /// The ranges of the partitioning field a query is restricted to, or
+    /// `None` if the query may match documents of any partition.
+    ///
+    /// Only constraints which every result must satisfy are considered: a
+    /// range on the field at the root, in an intersection, or in every branch
+    /// of a union.
+    pub fn query_ranges(&self, root: &QueryNode) -> Option<Vec<NumericRange>> {
+        match &root.kind {
+            QueryNodeKind::Numeric {
+                field,
+                min,
+                max,
+                inclusive_min,
+                inclusive_max,
+            } if *field == self.field => Some(vec![NumericRange {
+                min: *min,
+                max: *max,
+                min_inclusive: *inclusive_min,
+                max_inclusive: *inclusive_max,
+            }]),
+            QueryNodeKind::IntegerNumeric { field, min, max } if *field == self.field => {
+                Some(vec![
+                    IntegerRange {
+                        min: *min,
+                        max: *max,
+                    }
+                    .to_numeric(),
+                ])
+            }
+            QueryNodeKind::NumericUnion { field, ranges } if *field == self.field => {
+                Some(ranges.clone())
+            }
+            QueryNodeKind::Phrase { .. } => root
+                .children
+                .iter()
+                .find_map(|child| self.query_ranges(child)),
+            QueryNodeKind::Union if !root.children.is_empty() => root
+                .children
+                .iter()
+                .map(|child| self.query_ranges(child))
+                .try_fold(Vec::new(), |mut all, ranges| {
+                    all.extend(ranges?);
+                    Some(all)
+                }),
+            _ => None,
+        }
+    }
+}
+
+/// The partitions of an index which currently exist, and their number of
+/// documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Partitions {
+    scheme: Partitioning,
+    live: BTreeMap<PartitionKey, u64>,
+}
+
+impl Partitions {
+    pub const fn new(scheme: Partitioning) -> Self {
+        Self {
+            scheme,
+            live: BTreeMap::new(),
+        }
+    }
+
+    pub const fn scheme(&self) -> &Partitioning {
+        &self.scheme
+    }
+
+    /// Route a new document to its partition, creating the partition if
+    /// needed. Returns the partition and whether it was created.
+    ///
+    /// # Errors
+    ///
+    /// See [`Partitioning::route`].
+    pub fn add_document(
+        &mut self,
+        value: Option<&str>,
+    ) -> Result<(PartitionKey, bool), PartitionError> {
+        let key = self.scheme.route(value)?;
+        let docs = self.live.entry(key).or_default();
+        *docs += 1;
+        Ok((key, *docs == 1))
+    }
+
+    /// Account for a document deleted from the partition `key`.
+    ///
+    /// Empty partitions are kept until dropped, as more documents may be
+    /// routed to them.
+    pub fn remove_document(&mut self, key: PartitionKey) {
+        if let Some(docs) = self.live.get_mut(&key) {
+            *docs = docs.saturating_sub(1);
+        }
+    }
+
+    /// The existing partitions, oldest first.
+    pub fn keys(&self) -> impl Iterator<Item = PartitionKey> + '_ {
+        self.live.keys().copied()
+    }
+
+    /// The number of documents in the partition `key`.
+    pub fn num_docs(&self, key: PartitionKey) -> Option<u64> {
+        self.live.get(&key).copied()
+    }
+
+    /// The partitions a query has to run on, oldest first.
+    pub fn prune(&self, root: &QueryNode) -> Vec<PartitionKey> {
+        let Some(ranges) = self.scheme.query_ranges(root) else {
+            return self.keys().collect();
+        };
+        self.keys()
+            .filter(|&key| ranges.iter().any(|range| self.scheme.overlaps(key, range)))
+            .collect()
+    }
+
+    /// Drop the partitions entirely older than the timestamp `seconds`, e.g.
+    /// to enforce a retention period.
+    ///
+    /// The partitions are removed at once, so no query sees some of them
+    /// dropped and others not. Returns the dropped partitions, whose
+    /// sub-indexes should then be freed in the background.
+    pub fn drop_before(&mut self, seconds: i64) -> Vec<PartitionKey> {
+        let first_kept = self.scheme.key_of(seconds);
+        let kept = self.live.split_off(&first_kept);
+        let dropped = std::mem::replace(&mut self.live, kept);
+        dropped.into_keys().collect()
+    }
+
+    /// Drop a single partition. Returns `false` if it doesn't exist.
+    pub fn drop_partition(&mut self, key: PartitionKey) -> bool {
+        self.live.remove(&key).is_some()
+    }
+}
+
+/// The timestamp of the first second of the `months`-th month since the epoch.
+const fn month_start(months: i64) -> i64 {
+    let year = 1970 + months.div_euclid(12);
+    let month = months.rem_euclid(12) as u32 + 1;
+    days_from_civil(year, month, 1) * SECONDS_PER_DAY
+}
+
+/// The number of days since the epoch of a date of the proleptic Gregorian
+/// calendar, after Howard Hinnant's `days_from_civil`.
+const fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The inverse of [`days_from_civil`].
+const fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
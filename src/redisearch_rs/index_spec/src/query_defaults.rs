@@ -0,0 +1,179 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Defaults and restrictions applied to every query against an index.
+//!
+//! They are declared on `FT.CREATE` with the following options:
+//!
+//! - `DEFAULTDIALECT {n}`: the dialect used when the query doesn't specify one,
+//!   taking precedence over the global `DEFAULT_DIALECT` configuration;
+//! - `DEFAULTSCORER {name}`: the scorer used when the query doesn't specify one;
+//! - `MAXLIMIT {n}`: an upper bound on the number of results a query may request,
+//!   larger `LIMIT`s being silently capped;
+//! - `QUERYFILTER {query}`: a query intersected with every query against the index.
+//!
+//! The query filter is applied after the query rewrite hooks have run, so that
+//! no hook can remove it: it's meant to enforce e.g. tenant isolation server-side.
+
+use query::QueryNode;
+
+use crate::SpecError;
+
+/// The oldest supported query dialect, `MIN_DIALECT_VERSION` in C.
+pub const MIN_DIALECT: u32 = 1;
+/// The newest supported query dialect, `MAX_DIALECT_VERSION` in C.
+pub const MAX_DIALECT: u32 = 4;
+
+const DEFAULT_DIALECT_OPT: &str = "DEFAULTDIALECT";
+const DEFAULT_SCORER_OPT: &str = "DEFAULTSCORER";
+const MAX_LIMIT_OPT: &str = "MAXLIMIT";
+const QUERY_FILTER_OPT: &str = "QUERYFILTER";
+
+/// A mandatory filter, kept with its source so it can be reported by `FT.INFO`
+/// and persisted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryFilter {
+    source: String,
+    root: QueryNode,
+}
+
+impl QueryFilter {
+    /// The filter as written on `FT.CREATE`.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The parsed filter.
+    pub const fn root(&self) -> &QueryNode {
+        &self.root
+    }
+}
+
+/// See the [module documentation](self).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryDefaults {
+    dialect: Option<u32>,
+    scorer: Option<String>,
+    max_limit: Option<u64>,
+    filter: Option<QueryFilter>,
+}
+
+impl QueryDefaults {
+    /// Try to handle the `FT.CREATE` option `name` with argument `value`.
+    ///
+    /// Returns `Ok(false)` if `name` is not a query-defaults option, leaving it
+    /// to the caller. `parse_filter` is only invoked for `QUERYFILTER`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpecError::BadValue`] if `value` is invalid for the option,
+    /// and [`SpecError::DuplicateOption`] if the option was already set.
+    pub fn try_set_option(
+        &mut self,
+        name: &str,
+        value: &str,
+        parse_filter: impl FnOnce(&str) -> Result<QueryNode, String>,
+    ) -> Result<bool, SpecError> {
+        let bad_value = |option| SpecError::BadValue {
+            option,
+            value: value.to_owned(),
+        };
+
+        if name.eq_ignore_ascii_case(DEFAULT_DIALECT_OPT) {
+            let dialect = value
+                .parse()
+                .ok()
+                .filter(|d| (MIN_DIALECT..=MAX_DIALECT).contains(d))
+                .ok_or_else(|| bad_value(DEFAULT_DIALECT_OPT))?;
+            set_once(&mut self.dialect, dialect, DEFAULT_DIALECT_OPT)?;
+        } else if name.eq_ignore_ascii_case(DEFAULT_SCORER_OPT) {
+            if value.is_empty() {
+                return Err(bad_value(DEFAULT_SCORER_OPT));
+            }
+            set_once(&mut self.scorer, value.to_owned(), DEFAULT_SCORER_OPT)?;
+        } else if name.eq_ignore_ascii_case(MAX_LIMIT_OPT) {
+            let max_limit = value
+                .parse()
+                .ok()
+                .filter(|&n: &u64| n > 0)
+                .ok_or_else(|| bad_value(MAX_LIMIT_OPT))?;
+            set_once(&mut self.max_limit, max_limit, MAX_LIMIT_OPT)?;
+        } else if name.eq_ignore_ascii_case(QUERY_FILTER_OPT) {
+            if self.filter.is_some() {
+                return Err(SpecError::DuplicateOption(QUERY_FILTER_OPT));
+            }
+            let root = parse_filter(value).map_err(|_| bad_value(QUERY_FILTER_OPT))?;
+            self.filter = Some(QueryFilter {
+                source: value.to_owned(),
+                root,
+            });
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// The dialect to parse a query with, given the one it `requested` (if any)
+    /// and the server-wide default.
+    pub fn dialect(&self, requested: Option<u32>, global_default: u32) -> u32 {
+        requested.or(self.dialect).unwrap_or(global_default)
+    }
+
+    /// The scorer to use, given the one the query `requested` (if any).
+    ///
+    /// `None` means the server-wide default scorer.
+    pub fn scorer<'a>(&'a self, requested: Option<&'a str>) -> Option<&'a str> {
+        requested.or(self.scorer.as_deref())
+    }
+
+    /// Cap the number of results `requested` by a query's `LIMIT`.
+    pub fn cap_limit(&self, requested: u64) -> u64 {
+        self.max_limit.map_or(requested, |max| requested.min(max))
+    }
+
+    /// Intersect the query rooted at `root` with the mandatory filter, if any.
+    pub fn apply_filter(&self, root: QueryNode) -> QueryNode {
+        match &self.filter {
+            Some(filter) => root.and(filter.root.clone()),
+            None => root,
+        }
+    }
+
+    /// The mandatory filter, if any.
+    pub const fn filter(&self) -> Option<&QueryFilter> {
+        self.filter.as_ref()
+    }
+
+    /// The options as they would be written on `FT.CREATE`, for `FT.INFO`
+    /// and for persisting the index definition.
+    pub fn to_args(&self) -> Vec<(&'static str, String)> {
+        let mut args = Vec::new();
+        if let Some(dialect) = self.dialect {
+            args.push((DEFAULT_DIALECT_OPT, dialect.to_string()));
+        }
+        if let Some(scorer) = &self.scorer {
+            args.push((DEFAULT_SCORER_OPT, scorer.clone()));
+        }
+        if let Some(max_limit) = self.max_limit {
+            args.push((MAX_LIMIT_OPT, max_limit.to_string()));
+        }
+        if let Some(filter) = &self.filter {
+            args.push((QUERY_FILTER_OPT, filter.source.clone()));
+        }
+        args
+    }
+}
+
+fn set_once<T>(slot: &mut Option<T>, value: T, option: &'static str) -> Result<(), SpecError> {
+    if slot.is_some() {
+        return Err(SpecError::DuplicateOption(option));
+    }
+    *slot = Some(value);
+    Ok(())
+}
@@ -0,0 +1,72 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The fields of an index, with the options that don't belong to a more
+//! specific module.
+
+use std::fmt::{self, Display};
+
+/// The type of a field, as declared on `FT.CREATE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    Text,
+    Tag,
+    Numeric,
+    Geo,
+    GeoShape,
+    Vector,
+}
+
+impl FieldType {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Text => "TEXT",
+            Self::Tag => "TAG",
+            Self::Numeric => "NUMERIC",
+            Self::Geo => "GEO",
+            Self::GeoShape => "GEOSHAPE",
+            Self::Vector => "VECTOR",
+        }
+    }
+}
+
+impl Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A field of the schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaField {
+    pub name: String,
+    pub field_type: FieldType,
+    /// `SORTABLE`: the value is kept in the sorting vector.
+    pub sortable: bool,
+    /// `NOINDEX`: the field can only be sorted on and loaded, not searched.
+    pub no_index: bool,
+    /// `INDEXMISSING`: documents without the field can be searched with
+    /// `ismissing()`.
+    pub index_missing: bool,
+    /// `INDEXEMPTY`: empty values can be searched.
+    pub index_empty: bool,
+}
+
+impl SchemaField {
+    pub fn new(name: impl Into<String>, field_type: FieldType) -> Self {
+        Self {
+            name: name.into(),
+            field_type,
+            sortable: false,
+            no_index: false,
+            index_missing: false,
+            index_empty: false,
+        }
+    }
+}
@@ -0,0 +1,159 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Declared sort orders of TAG and TEXT fields.
+//!
+//! Values such as severities or ticket states have a natural order which
+//! isn't lexicographic: `low < medium < high`. The `ORDER` field option, e.g.
+//! `severity TAG SORTABLE ORDER 3 low medium high`, declares that order, and
+//! the sorter compares the values of the field by their rank in it.
+//!
+//! Values which aren't declared sort after those which are, lexicographically
+//! among themselves, so that documents indexed before a value was added to
+//! the order still sort deterministically.
+
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use crate::SpecError;
+
+const ORDER_OPT: &str = "ORDER";
+
+/// The declared order of the values of a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumOrder {
+    values: Vec<String>,
+}
+
+impl EnumOrder {
+    /// The order of `values`, from first to last.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpecError::BadValue`] if there are no values, or if a value
+    /// is declared twice.
+    pub fn new(values: Vec<String>) -> Result<Self, SpecError> {
+        if values.is_empty() {
+            return Err(SpecError::BadValue {
+                option: ORDER_OPT,
+                value: "0".to_owned(),
+            });
+        }
+        for (i, value) in values.iter().enumerate() {
+            if values[..i].contains(value) {
+                return Err(SpecError::BadValue {
+                    option: ORDER_OPT,
+                    value: value.clone(),
+                });
+            }
+        }
+        Ok(Self { values })
+    }
+
+    /// The declared values, from first to last.
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+
+    /// The rank of `value` in the order, if it's declared.
+    pub fn rank(&self, value: &str) -> Option<usize> {
+        self.values.iter().position(|v| v == value)
+    }
+
+    /// Compare two values of the field, in ascending order. Documents
+    /// without a value are left to the caller, as for any other field.
+    pub fn cmp(&self, a: &str, b: &str) -> Ordering {
+        match (self.rank(a), self.rank(b)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => a.cmp(b),
+        }
+    }
+}
+
+/// The declared sort orders of the fields of an index.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SortOrders {
+    fields: BTreeMap<String, EnumOrder>,
+}
+
+impl SortOrders {
+    /// Try to handle the field option `name` of the TAG or TEXT field
+    /// `field`, whose arguments are consumed from `args`.
+    ///
+    /// Returns `Ok(false)` if `name` is not `ORDER`, leaving it to the caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpecError::DuplicateOption`] if the field already has an
+    /// order, and [`SpecError::BadValue`] if an argument is missing or
+    /// invalid.
+    pub fn try_set_field_option<'a>(
+        &mut self,
+        field: &str,
+        name: &str,
+        args: &mut impl Iterator<Item = &'a str>,
+    ) -> Result<bool, SpecError> {
+        if !name.eq_ignore_ascii_case(ORDER_OPT) {
+            return Ok(false);
+        }
+        if self.fields.contains_key(field) {
+            return Err(SpecError::DuplicateOption(ORDER_OPT));
+        }
+        let count = args.next().unwrap_or_default();
+        let count: usize = count.parse().map_err(|_| SpecError::BadValue {
+            option: ORDER_OPT,
+            value: count.to_owned(),
+        })?;
+        let values = args.take(count).map(str::to_owned).collect::<Vec<_>>();
+        if values.len() < count {
+            return Err(SpecError::BadValue {
+                option: ORDER_OPT,
+                value: String::new(),
+            });
+        }
+        self.fields
+            .insert(field.to_owned(), EnumOrder::new(values)?);
+        Ok(true)
+    }
+
+    /// Set the order of `field`, replacing any previous one.
+    pub fn set(&mut self, field: impl Into<String>, order: EnumOrder) {
+        self.fields.insert(field.into(), order);
+    }
+
+    /// The order of `field`, if one is declared.
+    pub fn get(&self, field: &str) -> Option<&EnumOrder> {
+        self.fields.get(field)
+    }
+
+    /// Compare two values of `field`, in ascending order: by their declared
+    /// rank if the field has an order, lexicographically otherwise.
+    pub fn cmp(&self, field: &str, a: &str, b: &str) -> Ordering {
+        match self.get(field) {
+            Some(order) => order.cmp(a, b),
+            None => a.cmp(b),
+        }
+    }
+
+    /// Forget the order of a removed field.
+    pub fn remove(&mut self, field: &str) {
+        self.fields.remove(field);
+    }
+
+    /// The options of `field` as they would be written on `FT.CREATE`.
+    pub fn to_args(&self, field: &str) -> Vec<String> {
+        let Some(order) = self.get(field) else {
+            return Vec::new();
+        };
+        let mut args = vec![ORDER_OPT.to_owned(), order.values.len().to_string()];
+        args.extend(order.values.iter().cloned());
+        args
+    }
+}
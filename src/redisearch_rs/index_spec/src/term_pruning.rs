@@ -0,0 +1,195 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Pruning of terms too frequent to be worth indexing in full.
+//!
+//! On natural-language corpora, a few terms appear in most documents and their
+//! postings take a large share of the index memory, while being useless for
+//! ranking. The following `FT.CREATE` options prune them:
+//!
+//! - `PRUNEOFFSETS {fraction}`: stop recording the offsets of terms appearing
+//!   in more than `fraction` of the documents. Such terms can't be used by
+//!   phrase or proximity queries anymore, nor highlighted;
+//! - `PRUNETERMS {fraction}`: stop indexing terms appearing in more than
+//!   `fraction` of the documents, treating them as stopwords;
+//! - `PRUNEMINDOCS {n}`: don't prune anything before the index holds `n`
+//!   documents, since frequencies are meaningless on small indexes.
+//!
+//! Pruning is sticky: once a term is pruned, GC truncates its existing
+//! postings, and it stays pruned even if its frequency decreases.
+
+use std::collections::HashMap;
+
+use crate::SpecError;
+
+const PRUNE_OFFSETS_OPT: &str = "PRUNEOFFSETS";
+const PRUNE_TERMS_OPT: &str = "PRUNETERMS";
+const PRUNE_MIN_DOCS_OPT: &str = "PRUNEMINDOCS";
+
+/// The default for `PRUNEMINDOCS`.
+pub const DEFAULT_MIN_DOCS: u64 = 1000;
+
+/// How much of a term is indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PruneLevel {
+    /// Postings with offsets.
+    Full,
+    /// Postings without offsets.
+    NoOffsets,
+    /// Nothing; the term is a stop-term.
+    Stopped,
+}
+
+/// The pruning options of an index. See the [module documentation](self).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TermPruning {
+    offsets_fraction: Option<f64>,
+    terms_fraction: Option<f64>,
+    min_docs: Option<u64>,
+}
+
+impl TermPruning {
+    /// Try to handle the `FT.CREATE` option `name` with argument `value`.
+    ///
+    /// Returns `Ok(false)` if `name` is not a pruning option, leaving it to the
+    /// caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SpecError::BadValue`] if `value` is invalid for the option,
+    /// and [`SpecError::DuplicateOption`] if the option was already set.
+    pub fn try_set_option(&mut self, name: &str, value: &str) -> Result<bool, SpecError> {
+        let bad_value = |option| SpecError::BadValue {
+            option,
+            value: value.to_owned(),
+        };
+        let fraction = |option| {
+            value
+                .parse()
+                .ok()
+                .filter(|f: &f64| *f > 0.0 && *f <= 1.0)
+                .ok_or_else(|| bad_value(option))
+        };
+
+        let (slot, option) = if name.eq_ignore_ascii_case(PRUNE_OFFSETS_OPT) {
+            (&mut self.offsets_fraction, PRUNE_OFFSETS_OPT)
+        } else if name.eq_ignore_ascii_case(PRUNE_TERMS_OPT) {
+            (&mut self.terms_fraction, PRUNE_TERMS_OPT)
+        } else if name.eq_ignore_ascii_case(PRUNE_MIN_DOCS_OPT) {
+            if self.min_docs.is_some() {
+                return Err(SpecError::DuplicateOption(PRUNE_MIN_DOCS_OPT));
+            }
+            let min_docs = value.parse().map_err(|_| bad_value(PRUNE_MIN_DOCS_OPT))?;
+            self.min_docs = Some(min_docs);
+            return Ok(true);
+        } else {
+            return Ok(false);
+        };
+        if slot.is_some() {
+            return Err(SpecError::DuplicateOption(option));
+        }
+        *slot = Some(fraction(option)?);
+        Ok(true)
+    }
+
+    /// Whether any pruning is enabled.
+    pub const fn is_enabled(&self) -> bool {
+        self.offsets_fraction.is_some() || self.terms_fraction.is_some()
+    }
+
+    /// The level a term appearing in `doc_freq` of the `num_docs` documents
+    /// should be pruned to, regardless of its current level.
+    pub fn level_for(&self, doc_freq: u64, num_docs: u64) -> PruneLevel {
+        if num_docs == 0 || num_docs < self.min_docs.unwrap_or(DEFAULT_MIN_DOCS) {
+            return PruneLevel::Full;
+        }
+        let ratio = doc_freq as f64 / num_docs as f64;
+        if self.terms_fraction.is_some_and(|f| ratio > f) {
+            PruneLevel::Stopped
+        } else if self.offsets_fraction.is_some_and(|f| ratio > f) {
+            PruneLevel::NoOffsets
+        } else {
+            PruneLevel::Full
+        }
+    }
+
+    /// The options as they would be written on `FT.CREATE`, for `FT.INFO`
+    /// and for persisting the index definition.
+    pub fn to_args(&self) -> Vec<(&'static str, String)> {
+        let mut args = Vec::new();
+        if let Some(fraction) = self.offsets_fraction {
+            args.push((PRUNE_OFFSETS_OPT, fraction.to_string()));
+        }
+        if let Some(fraction) = self.terms_fraction {
+            args.push((PRUNE_TERMS_OPT, fraction.to_string()));
+        }
+        if let Some(min_docs) = self.min_docs {
+            args.push((PRUNE_MIN_DOCS_OPT, min_docs.to_string()));
+        }
+        args
+    }
+}
+
+/// The pruned-term counts reported by `FT.INFO`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruningStats {
+    /// Terms indexed without offsets.
+    pub offsets_pruned_terms: u64,
+    /// Terms not indexed at all.
+    pub stopped_terms: u64,
+}
+
+/// The terms pruned so far in an index.
+#[derive(Debug, Clone, Default)]
+pub struct PrunedTerms {
+    levels: HashMap<Box<[u8]>, PruneLevel>,
+}
+
+impl PrunedTerms {
+    /// The current level of `term`.
+    pub fn level(&self, term: &[u8]) -> PruneLevel {
+        self.levels.get(term).copied().unwrap_or(PruneLevel::Full)
+    }
+
+    /// Update the level of `term` given its current document frequency, when
+    /// indexing a document containing it. Levels only ever increase.
+    ///
+    /// Returns the level to index the term with.
+    pub fn observe(
+        &mut self,
+        pruning: &TermPruning,
+        term: &[u8],
+        doc_freq: u64,
+        num_docs: u64,
+    ) -> PruneLevel {
+        let current = self.level(term);
+        let level = pruning.level_for(doc_freq, num_docs).max(current);
+        if level > current {
+            self.levels.insert(term.into(), level);
+        }
+        level
+    }
+
+    /// The terms GC must truncate, with the level to truncate them to.
+    pub fn pruned(&self) -> impl Iterator<Item = (&[u8], PruneLevel)> {
+        self.levels.iter().map(|(term, level)| (&**term, *level))
+    }
+
+    pub fn stats(&self) -> PruningStats {
+        let mut stats = PruningStats::default();
+        for level in self.levels.values() {
+            match level {
+                PruneLevel::Full => {}
+                PruneLevel::NoOffsets => stats.offsets_pruned_terms += 1,
+                PruneLevel::Stopped => stats.stopped_terms += 1,
+            }
+        }
+        stats
+    }
+}
@@ -0,0 +1,155 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use expr::computed::{EvalError, Value};
+use index_spec::{
+    IndexSpec, SpecError,
+    computed_fields::{ComputeError, ComputedKind},
+};
+
+fn add(
+    spec: &mut IndexSpec,
+    field: &str,
+    kind: ComputedKind,
+    args: &str,
+) -> Result<bool, SpecError> {
+    let mut args = args.split('|');
+    let name = args.next().unwrap();
+    spec.computed_fields_mut()
+        .try_set_field_option(field, kind, name, &mut args)
+}
+
+#[test]
+fn test_define_computed_fields() {
+    let mut spec = IndexSpec::new("idx");
+    assert_eq!(
+        add(
+            &mut spec,
+            "title_key",
+            ComputedKind::Tag,
+            "computed|lower(@title)"
+        ),
+        Ok(true)
+    );
+    assert_eq!(
+        add(&mut spec, "price", ComputedKind::Numeric, "SORTABLE"),
+        Ok(false)
+    );
+
+    let computed = spec.computed_fields();
+    let field = computed.get("title_key").unwrap();
+    assert_eq!(
+        (field.name(), field.kind(), field.source()),
+        ("title_key", ComputedKind::Tag, "lower(@title)")
+    );
+    assert_eq!(computed.to_args("title_key"), ["COMPUTED", "lower(@title)"]);
+    assert!(computed.to_args("title").is_empty());
+    assert!(computed.is_read("title"));
+    assert!(!computed.is_read("title_key"));
+
+    let bad = |value: &str| SpecError::BadValue {
+        option: "COMPUTED",
+        value: value.to_owned(),
+    };
+    assert_eq!(
+        add(&mut spec, "x", ComputedKind::Tag, "COMPUTED|lower(@title"),
+        Err(bad("lower(@title"))
+    );
+    assert_eq!(
+        add(&mut spec, "x", ComputedKind::Tag, "COMPUTED"),
+        Err(bad(""))
+    );
+    // Computed fields can't read computed fields, nor themselves.
+    assert_eq!(
+        add(
+            &mut spec,
+            "x",
+            ComputedKind::Tag,
+            "COMPUTED|upper(@title_key)"
+        ),
+        Err(bad("upper(@title_key)"))
+    );
+    assert_eq!(
+        add(&mut spec, "x", ComputedKind::Tag, "COMPUTED|upper(@x)"),
+        Err(bad("upper(@x)"))
+    );
+    assert_eq!(
+        add(
+            &mut spec,
+            "title_key",
+            ComputedKind::Tag,
+            "COMPUTED|upper(@title)"
+        ),
+        Err(SpecError::DuplicateOption("COMPUTED"))
+    );
+
+    spec.computed_fields_mut().remove("title_key");
+    assert!(spec.computed_fields().get("title_key").is_none());
+}
+
+#[test]
+fn test_evaluate() {
+    let mut spec = IndexSpec::new("idx");
+    add(
+        &mut spec,
+        "title_key",
+        ComputedKind::Tag,
+        "COMPUTED|lower(@title)",
+    )
+    .unwrap();
+    add(
+        &mut spec,
+        "price",
+        ComputedKind::Numeric,
+        "COMPUTED|@cents / 100",
+    )
+    .unwrap();
+    add(
+        &mut spec,
+        "label",
+        ComputedKind::Text,
+        "COMPUTED|concat(@brand, ' ', @cents)",
+    )
+    .unwrap();
+    let computed = spec.computed_fields();
+
+    let doc = |fields: &'static [(&'static str, &'static str)]| {
+        move |name: &str| fields.iter().find(|(f, _)| *f == name).map(|(_, v)| *v)
+    };
+    assert_eq!(
+        computed.evaluate(doc(&[
+            ("title", "Hello"),
+            ("cents", "1250"),
+            ("brand", "ACME")
+        ])),
+        Ok(vec![
+            ("title_key", Value::String("hello".to_owned())),
+            ("price", Value::Number(12.5)),
+            ("label", Value::String("ACME 1250".to_owned())),
+        ])
+    );
+    // Missing inputs leave the computed fields without a value.
+    assert_eq!(
+        computed.evaluate(doc(&[("title", "Hello")])),
+        Ok(vec![("title_key", Value::String("hello".to_owned()))])
+    );
+
+    let error = computed.evaluate(doc(&[("cents", "n/a")])).unwrap_err();
+    assert_eq!(
+        error,
+        ComputeError {
+            field: "price".to_owned(),
+            error: EvalError::NotANumber("n/a".to_owned())
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "Could not compute field `price`: `n/a` is not a number"
+    );
+}
@@ -0,0 +1,200 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use analysis::AnalyzerConfig;
+use index_spec::{
+    FieldType, IndexSpec, SchemaField, SpecError,
+    computed_fields::ComputedKind,
+    diff::{Impact, SpecChange, diff},
+};
+use synonyms::SynonymMode;
+
+fn spec() -> IndexSpec {
+    let mut spec = IndexSpec::new("idx");
+    spec.add_field(SchemaField::new("title", FieldType::Text))
+        .unwrap();
+    spec.add_field(SchemaField {
+        sortable: true,
+        ..SchemaField::new("price", FieldType::Numeric)
+    })
+    .unwrap();
+    spec.add_field(SchemaField::new("tags", FieldType::Tag))
+        .unwrap();
+    spec
+}
+
+#[test]
+fn test_schema() {
+    let mut spec = spec();
+    assert_eq!(spec.fields().len(), 3);
+    assert!(spec.field("price").unwrap().sortable);
+    assert_eq!(
+        spec.add_field(SchemaField::new("tags", FieldType::Text)),
+        Err(SpecError::DuplicateField("tags".to_owned()))
+    );
+    assert_eq!(
+        SpecError::DuplicateField("tags".to_owned()).to_string(),
+        "Duplicate field in schema - tags"
+    );
+}
+
+#[test]
+fn test_no_changes() {
+    let diff = diff(&spec(), &spec());
+    assert!(diff.is_empty());
+    assert_eq!(diff.impact(), Impact::Hot);
+    assert_eq!(diff.to_string(), "");
+}
+
+#[test]
+fn test_hot_changes() {
+    let mut new = spec();
+    new.add_field(SchemaField::new("location", FieldType::Geo))
+        .unwrap();
+    new.term_pruning_mut()
+        .try_set_option("PRUNETERMS", "0.5")
+        .unwrap();
+
+    let diff = diff(&spec(), &new);
+    assert_eq!(
+        diff.changes(),
+        [
+            SpecChange::FieldAdded("location".to_owned()),
+            SpecChange::TermPruningChanged
+        ]
+    );
+    assert_eq!(diff.impact(), Impact::Hot);
+    assert_eq!(
+        diff.semantic_changes().collect::<Vec<_>>(),
+        [&SpecChange::TermPruningChanged]
+    );
+    assert_eq!(
+        diff.to_string(),
+        "field `location` added (hot)\nterm pruning changed (hot, changes query results)\n"
+    );
+}
+
+#[test]
+fn test_field_changes() {
+    let old = spec();
+    let mut new = IndexSpec::new("idx");
+    new.add_field(SchemaField::new("title", FieldType::Text))
+        .unwrap();
+    new.add_field(SchemaField::new("price", FieldType::Numeric))
+        .unwrap();
+    new.add_field(SchemaField::new("tags", FieldType::Text))
+        .unwrap();
+    new.field_analyzers_mut().set(
+        "title",
+        AnalyzerConfig {
+            stem: false,
+            ..Default::default()
+        },
+    );
+    new.numeric_fields_mut()
+        .try_set_field_option("price", "INTEGER")
+        .unwrap();
+    new.set_synonym_mode(SynonymMode::Query);
+
+    let diff = diff(&old, &new);
+    assert_eq!(
+        diff.changes(),
+        [
+            SpecChange::AnalyzerChanged("title".to_owned()),
+            SpecChange::FieldOptionChanged {
+                field: "price".to_owned(),
+                option: "SORTABLE",
+                enabled: false
+            },
+            SpecChange::NumericStorageChanged("price".to_owned()),
+            SpecChange::FieldTypeChanged {
+                field: "tags".to_owned(),
+                from: FieldType::Tag,
+                to: FieldType::Text
+            },
+            SpecChange::SynonymModeChanged,
+        ]
+    );
+    assert_eq!(diff.impact(), Impact::Reindex);
+    assert_eq!(diff.semantic_changes().count(), 5);
+    assert_eq!(
+        diff.changes()[1].to_string(),
+        "SORTABLE removed from field `price`"
+    );
+    assert_eq!(
+        diff.changes()[3].to_string(),
+        "field `tags` changed from TAG to TEXT"
+    );
+}
+
+#[test]
+fn test_removed_and_computed_fields() {
+    let computed = |spec: &mut IndexSpec, expr: &str| {
+        spec.add_field(SchemaField::new("key", FieldType::Tag))
+            .unwrap();
+        let mut args = [expr].into_iter();
+        spec.computed_fields_mut()
+            .try_set_field_option("key", ComputedKind::Tag, "COMPUTED", &mut args)
+            .unwrap();
+    };
+    let mut old = spec();
+    computed(&mut old, "lower(@title)");
+    let mut new = spec();
+    computed(&mut new, "upper(@title)");
+
+    assert_eq!(
+        diff(&old, &new).changes(),
+        [SpecChange::ComputedChanged("key".to_owned())]
+    );
+    let diff = diff(&old, &spec());
+    assert_eq!(diff.changes(), [SpecChange::FieldRemoved("key".to_owned())]);
+    assert_eq!(
+        diff.to_string(),
+        "field `key` removed (reindex, changes query results)\n"
+    );
+}
+
+#[test]
+fn test_sort_order_change_is_hot() {
+    let old = spec();
+    let mut new = spec();
+    new.sort_orders_mut().set(
+        "tags",
+        index_spec::sort_orders::EnumOrder::new(vec!["a".to_owned(), "b".to_owned()]).unwrap(),
+    );
+    let diff = diff(&old, &new);
+    assert_eq!(
+        diff.changes(),
+        [SpecChange::SortOrderChanged("tags".to_owned())]
+    );
+    assert_eq!(diff.impact(), Impact::Hot);
+    assert_eq!(
+        diff.changes()[0].to_string(),
+        "sort order of field `tags` changed"
+    );
+}
+
+#[test]
+fn test_datetime_change_is_hot() {
+    let old = spec();
+    let mut new = spec();
+    new.numeric_fields_mut()
+        .try_set_field_option("price", "DATETIME")
+        .unwrap();
+    let diff = diff(&old, &new);
+    assert_eq!(
+        diff.changes(),
+        [SpecChange::BoundSyntaxChanged("price".to_owned())]
+    );
+    assert_eq!(diff.impact(), Impact::Hot);
+    assert_eq!(
+        diff.changes()[0].to_string(),
+        "DATETIME changed on field `price`"
+    );
+}
@@ -0,0 +1,236 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use analysis::{AnalyzerConfig, LongTermPolicy, TermLimit, TokenizerKind};
+use index_spec::{FieldAnalyzers, SpecError};
+use query::FieldSelector;
+
+fn parse(args: &[&str]) -> Result<AnalyzerConfig, SpecError> {
+    let mut config = AnalyzerConfig::default();
+    let mut args = args.iter().copied();
+    while let Some(name) = args.next() {
+        assert!(FieldAnalyzers::try_set_field_option(
+            &mut config,
+            name,
+            &mut args
+        )?);
+    }
+    Ok(config)
+}
+
+fn named(fields: &[&str]) -> FieldSelector {
+    FieldSelector::Named(fields.iter().map(|f| (*f).to_owned()).collect())
+}
+
+#[test]
+fn test_parse_field_options() {
+    let config = parse(&[
+        "TOKENIZER",
+        "Standard",
+        "nostem",
+        "LANGUAGE",
+        "German",
+        "STOPWORDS",
+        "2",
+        "und",
+        "oder",
+        "SYNONYMS",
+        "de",
+        "PHONETIC",
+        "dm:en",
+    ])
+    .unwrap();
+    assert_eq!(
+        config,
+        AnalyzerConfig {
+            tokenizer: TokenizerKind::Standard,
+            word_delimiter: None,
+            stem: false,
+            language: Some("german".to_owned()),
+            stopwords: Some(vec!["und".to_owned(), "oder".to_owned()]),
+            synonyms: Some("de".to_owned()),
+            phonetic: Some("dm:en".to_owned()),
+            term_limit: TermLimit::default(),
+        }
+    );
+
+    let mut analyzers = FieldAnalyzers::default();
+    analyzers.set("body_de", config);
+    assert_eq!(
+        analyzers.to_args("body_de"),
+        [
+            "NOSTEM",
+            "LANGUAGE",
+            "german",
+            "STOPWORDS",
+            "2",
+            "und",
+            "oder",
+            "SYNONYMS",
+            "de",
+            "PHONETIC",
+            "dm:en"
+        ]
+    );
+    assert!(analyzers.to_args("title").is_empty());
+}
+
+#[test]
+fn test_parse_ngram_options() {
+    let config = parse(&["TOKENIZER", "edge_ngram", "MAXGRAM", "8"]).unwrap();
+    assert_eq!(
+        config.tokenizer,
+        TokenizerKind::EdgeNGram { min: 2, max: 8 }
+    );
+    let config = parse(&["TOKENIZER", "ngram", "MINGRAM", "4"]).unwrap();
+    assert_eq!(config.tokenizer, TokenizerKind::NGram { min: 4, max: 4 });
+
+    let mut analyzers = FieldAnalyzers::default();
+    analyzers.set("sku", config);
+    assert_eq!(
+        analyzers.to_args("sku"),
+        ["TOKENIZER", "ngram", "MINGRAM", "4", "MAXGRAM", "4"]
+    );
+
+    assert_eq!(
+        parse(&["MINGRAM", "2"]),
+        Err(SpecError::BadValue {
+            option: "MINGRAM",
+            value: "2".to_owned()
+        })
+    );
+    assert!(parse(&["TOKENIZER", "ngram", "MAXGRAM", "0"]).is_err());
+}
+
+#[test]
+fn test_parse_word_delimiter() {
+    let config = parse(&[
+        "TOKENIZER",
+        "whitespace",
+        "WORDDELIMITER",
+        "2",
+        "catenateall",
+        "PRESERVEORIGINAL",
+    ])
+    .unwrap();
+    let options = config.word_delimiter.unwrap();
+    assert!(options.catenate_all && options.preserve_original);
+
+    let mut analyzers = FieldAnalyzers::default();
+    analyzers.set("sku", config);
+    assert_eq!(
+        analyzers.to_args("sku"),
+        [
+            "TOKENIZER",
+            "whitespace",
+            "WORDDELIMITER",
+            "2",
+            "PRESERVEORIGINAL",
+            "CATENATEALL"
+        ]
+    );
+    assert!(parse(&["WORDDELIMITER", "1", "SPLIT"]).is_err());
+    assert_eq!(
+        parse(&["WORDDELIMITER", "0"]).unwrap().word_delimiter,
+        Some(Default::default())
+    );
+}
+
+#[test]
+fn test_parse_notokenize() {
+    let config = parse(&["NOTOKENIZE"]).unwrap();
+    assert_eq!(config.tokenizer, TokenizerKind::Keyword { case_fold: true });
+    let config = parse(&["NOTOKENIZE", "CASESENSITIVE"]).unwrap();
+    assert_eq!(
+        config.tokenizer,
+        TokenizerKind::Keyword { case_fold: false }
+    );
+    assert!(config.tokenizer.is_keyword());
+
+    let mut analyzers = FieldAnalyzers::default();
+    analyzers.set("id", config);
+    assert_eq!(analyzers.to_args("id"), ["NOTOKENIZE", "CASESENSITIVE"]);
+    assert!(parse(&["CASESENSITIVE"]).is_err());
+}
+
+#[test]
+fn test_parse_term_limit() {
+    let config = parse(&["LONGTERMS", "hash", "MAXTERMLEN", "64"]).unwrap();
+    assert_eq!(
+        config.term_limit,
+        TermLimit {
+            max_len: 64,
+            policy: LongTermPolicy::Hash
+        }
+    );
+
+    let mut analyzers = FieldAnalyzers::default();
+    analyzers.set("message", config);
+    assert_eq!(
+        analyzers.to_args("message"),
+        ["MAXTERMLEN", "64", "LONGTERMS", "HASH"]
+    );
+    // The defaults aren't written out.
+    analyzers.set("body", parse(&["MAXTERMLEN", "128", "NOSTEM"]).unwrap());
+    assert_eq!(analyzers.to_args("body"), ["NOSTEM"]);
+
+    assert!(parse(&["MAXTERMLEN", "0"]).is_err());
+    assert_eq!(
+        parse(&["LONGTERMS", "split"]),
+        Err(SpecError::BadValue {
+            option: "LONGTERMS",
+            value: "split".to_owned()
+        })
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(
+        parse(&["TOKENIZER", "unknown"]),
+        Err(SpecError::BadValue {
+            option: "TOKENIZER",
+            value: "unknown".to_owned()
+        })
+    );
+    assert!(matches!(
+        parse(&["STOPWORDS", "3", "a", "b"]),
+        Err(SpecError::BadValue {
+            option: "STOPWORDS",
+            ..
+        })
+    ));
+    let mut config = AnalyzerConfig::default();
+    assert!(
+        !FieldAnalyzers::try_set_field_option(&mut config, "WEIGHT", &mut ["2"].into_iter())
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_query_uses_targeted_field_chain() {
+    let german = parse(&["LANGUAGE", "german"]).unwrap();
+    let mut analyzers = FieldAnalyzers::default();
+    analyzers.set("body_de", german.clone());
+    analyzers.set("title_de", german.clone());
+    analyzers.set("title", AnalyzerConfig::default());
+
+    let default = AnalyzerConfig::default();
+    assert_eq!(analyzers.for_query(&FieldSelector::All), &default);
+    assert_eq!(analyzers.for_query(&named(&["body_de"])), &german);
+    assert_eq!(
+        analyzers.for_query(&named(&["body_de", "title_de"])),
+        &german
+    );
+    // Fields with different chains fall back to the default one.
+    assert_eq!(analyzers.for_query(&named(&["body_de", "title"])), &default);
+
+    analyzers.remove("body_de");
+    assert_eq!(analyzers.for_field("body_de"), &default);
+}
@@ -0,0 +1,153 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::cmp::Ordering;
+
+use index_spec::{
+    NumericFields, SpecError,
+    numeric_storage::{NumericStorage, NumericValue},
+};
+use query::{
+    QueryNode, QueryNodeKind,
+    numeric::{BoundSyntax, IntegerRange, NumericRange},
+    params::resolve_params,
+};
+
+#[test]
+fn test_field_option() {
+    let mut fields = NumericFields::default();
+    assert_eq!(fields.try_set_field_option("id", "integer"), Ok(true));
+    assert_eq!(fields.try_set_field_option("price", "SORTABLE"), Ok(false));
+    assert_eq!(
+        fields.try_set_field_option("id", "INTEGER"),
+        Err(SpecError::DuplicateOption("INTEGER"))
+    );
+    assert_eq!(fields.storage("id"), NumericStorage::Integer);
+    assert_eq!(fields.storage("price"), NumericStorage::Double);
+    assert_eq!(fields.to_args("id"), ["INTEGER"]);
+    assert!(fields.to_args("price").is_empty());
+
+    fields.remove("id");
+    assert_eq!(fields.storage("id"), NumericStorage::Double);
+}
+
+#[test]
+fn test_datetime_option() {
+    let mut fields = NumericFields::default();
+    assert_eq!(fields.try_set_field_option("created", "datetime"), Ok(true));
+    assert_eq!(fields.try_set_field_option("created", "INTEGER"), Ok(true));
+    assert_eq!(
+        fields.try_set_field_option("created", "DATETIME"),
+        Err(SpecError::DuplicateOption("DATETIME"))
+    );
+    assert_eq!(fields.bound_syntax("created"), BoundSyntax::DateTime);
+    assert_eq!(fields.bound_syntax("price"), BoundSyntax::Number);
+    assert_eq!(fields.to_args("created"), ["INTEGER", "DATETIME"]);
+
+    fields.remove("created");
+    assert_eq!(fields.bound_syntax("created"), BoundSyntax::Number);
+    assert!(fields.to_args("created").is_empty());
+}
+
+#[test]
+fn test_resolve_datetime_params() {
+    let mut fields = NumericFields::default();
+    fields.try_set_field_option("created", "DATETIME").unwrap();
+    let range = |field: &str| {
+        QueryNode::new(QueryNodeKind::ParamNumeric {
+            field: field.to_owned(),
+            range: "$from ($to".to_owned(),
+        })
+    };
+    let values = [("from", "2024-01-01"), ("to", "2024-01-02")];
+    let lookup = |name: &str| values.iter().find(|(n, _)| *n == name).map(|(_, v)| *v);
+
+    let mut plan = range("created");
+    resolve_params(&mut plan, &lookup, &fields).unwrap();
+    assert_eq!(
+        plan,
+        NumericRange::parse("1704067200 (1704153600")
+            .unwrap()
+            .into_node("created")
+    );
+
+    let mut plan = range("price");
+    assert!(resolve_params(&mut plan, &lookup, &fields).is_err());
+}
+
+#[test]
+fn test_resolve_integer_params() {
+    let mut fields = NumericFields::default();
+    fields.try_set_field_option("id", "INTEGER").unwrap();
+    let range = |field: &str| {
+        QueryNode::new(QueryNodeKind::ParamNumeric {
+            field: field.to_owned(),
+            range: "($from +inf".to_owned(),
+        })
+    };
+    let big = 1_234_567_890_123_456_789_i64;
+    let from = big.to_string();
+    let lookup = |name: &str| (name == "from").then_some(from.as_str());
+
+    // The bound is kept exact, where an f64 would round it.
+    let mut plan = range("id");
+    resolve_params(&mut plan, &lookup, &fields).unwrap();
+    assert_eq!(
+        plan,
+        IntegerRange {
+            min: big + 1,
+            max: i64::MAX,
+        }
+        .into_node("id")
+    );
+
+    let mut plan = range("price");
+    resolve_params(&mut plan, &lookup, &fields).unwrap();
+    assert_eq!(
+        plan,
+        NumericRange::parse(&format!("({big} +inf"))
+            .unwrap()
+            .into_node("price")
+    );
+}
+
+#[test]
+fn test_parse_value() {
+    let id = "1234567890123456789";
+    let value = NumericStorage::Integer.parse_value(id).unwrap();
+    assert_eq!(value, NumericValue::Integer(1_234_567_890_123_456_789));
+    assert_eq!(value.to_string(), id);
+    // As a double, the value is rounded.
+    assert_eq!(
+        NumericStorage::Double.parse_value(id),
+        Some(NumericValue::Double(1.2345678901234568e18))
+    );
+
+    assert_eq!(
+        NumericStorage::Integer.parse_value(" 12.0 "),
+        Some(NumericValue::Integer(12))
+    );
+    assert_eq!(NumericStorage::Integer.parse_value("12.5"), None);
+    assert_eq!(NumericStorage::Integer.parse_value("1e300"), None);
+    assert_eq!(NumericStorage::Integer.parse_value("abc"), None);
+    assert_eq!(NumericStorage::Double.parse_value("nan"), None);
+}
+
+#[test]
+fn test_sort_order() {
+    // Adjacent IDs that are equal as doubles.
+    let a = NumericValue::Integer(9_007_199_254_740_992);
+    let b = NumericValue::Integer(9_007_199_254_740_993);
+    assert_eq!(a.as_f64(), b.as_f64());
+    assert_eq!(a.sort_cmp(&b), Ordering::Less);
+    assert_eq!(
+        NumericValue::Double(1.5).sort_cmp(&NumericValue::Integer(1)),
+        Ordering::Greater
+    );
+}
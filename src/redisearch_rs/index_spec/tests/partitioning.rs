@@ -0,0 +1,150 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use index_spec::{
+    IndexSpec, SpecError,
+    partitioning::{Granularity, PartitionError, PartitionKey, Partitioning, Partitions},
+};
+use query::{node::QueryNode, numeric::NumericRange};
+
+/// 2024-01-31T13:20:00Z
+const JAN_31: i64 = 1_706_707_200;
+const DAY: i64 = 86_400;
+
+fn parse(args: &str) -> Result<Option<Partitioning>, SpecError> {
+    let mut args = args.split_whitespace();
+    let name = args.next().unwrap();
+    Partitioning::try_parse_option(name, &mut args)
+}
+
+#[test]
+fn test_parse_option() {
+    let scheme = parse("partitionby ts DAY").unwrap().unwrap();
+    assert_eq!(scheme.field(), "ts");
+    assert_eq!(scheme.granularity(), Granularity::Day);
+    assert_eq!(scheme.to_args(), ["PARTITIONBY", "ts", "DAY"]);
+    assert_eq!(parse("PRUNETERMS 0.5"), Ok(None));
+    assert_eq!(
+        parse("PARTITIONBY ts WEEK"),
+        Err(SpecError::BadValue {
+            option: "PARTITIONBY",
+            value: "WEEK".to_owned()
+        })
+    );
+    assert!(parse("PARTITIONBY ts").is_err());
+
+    let mut spec = IndexSpec::new("logs");
+    assert!(spec.partitioning().is_none());
+    spec.set_partitioning(scheme.clone()).unwrap();
+    assert_eq!(
+        spec.set_partitioning(scheme),
+        Err(SpecError::DuplicateOption("PARTITIONBY"))
+    );
+}
+
+#[test]
+fn test_routing_and_names() {
+    let day = Partitioning::new("ts", Granularity::Day);
+    let key = day.route(Some("1706707200.5")).unwrap();
+    assert_eq!(day.sub_index_name("logs", key), "logs@2024-01-31");
+    assert_eq!(
+        day.bounds(key),
+        (JAN_31 - 13 * 3600 - 20 * 60, JAN_31 + DAY - 48_000)
+    );
+
+    let month = Partitioning::new("ts", Granularity::Month);
+    let january = month.key_of(JAN_31);
+    assert_eq!(month.sub_index_name("logs", january), "logs@2024-01");
+    assert_eq!(month.key_of(JAN_31 + DAY), PartitionKey(january.0 + 1));
+    // February 2024 has 29 days.
+    let (start, end) = month.bounds(PartitionKey(january.0 + 1));
+    assert_eq!((end - start) / DAY, 29);
+
+    let hour = Partitioning::new("ts", Granularity::Hour);
+    assert_eq!(
+        hour.sub_index_name("logs", hour.key_of(JAN_31)),
+        "logs@2024-01-31T13"
+    );
+    // Dates before the epoch.
+    assert_eq!(
+        month.sub_index_name("logs", month.key_of(-1)),
+        "logs@1969-12"
+    );
+
+    assert_eq!(
+        day.route(None),
+        Err(PartitionError::MissingField("ts".to_owned()))
+    );
+    assert_eq!(
+        day.route(Some("soon")).unwrap_err().to_string(),
+        "Invalid timestamp for partitioning field `ts`: soon"
+    );
+}
+
+#[test]
+fn test_pruning() {
+    let mut partitions = Partitions::new(Partitioning::new("ts", Granularity::Day));
+    for days in 0..5 {
+        let ts = (JAN_31 + days * DAY).to_string();
+        assert!(partitions.add_document(Some(&ts)).unwrap().1);
+    }
+    assert!(
+        !partitions
+            .add_document(Some(&JAN_31.to_string()))
+            .unwrap()
+            .1
+    );
+    let all: Vec<_> = partitions.keys().collect();
+    assert_eq!(partitions.num_docs(all[0]), Some(2));
+
+    let range = |min: i64, max: i64| QueryNode::numeric("ts", min as f64, max as f64);
+    // Two days, the second one starting at the end of the range.
+    let (start, _) = partitions.scheme().bounds(all[2]);
+    let query = QueryNode::intersect(vec![QueryNode::token("error"), range(start - DAY, start)]);
+    assert_eq!(partitions.prune(&query), &all[1..3]);
+
+    // The same range, with an exclusive upper bound.
+    let exclusive = NumericRange {
+        max_inclusive: false,
+        ..NumericRange::inclusive((start - DAY) as f64, start as f64)
+    };
+    assert_eq!(partitions.prune(&exclusive.into_node("ts")), &all[1..2]);
+
+    // Every branch of a union must be constrained.
+    let union = QueryNode::union(vec![
+        range(JAN_31, JAN_31),
+        range(start + 2 * DAY, i64::MAX),
+    ]);
+    assert_eq!(partitions.prune(&union), [all[0], all[4]]);
+    let unconstrained = QueryNode::union(vec![range(JAN_31, JAN_31), QueryNode::token("x")]);
+    assert_eq!(partitions.prune(&unconstrained), all);
+    assert_eq!(
+        partitions.prune(&QueryNode::numeric("other", 0.0, 1.0)),
+        all
+    );
+}
+
+#[test]
+fn test_drop_partitions() {
+    let mut partitions = Partitions::new(Partitioning::new("ts", Granularity::Day));
+    for days in 0..4 {
+        partitions
+            .add_document(Some(&(JAN_31 + days * DAY).to_string()))
+            .unwrap();
+    }
+    let all: Vec<_> = partitions.keys().collect();
+    // A partition is only dropped once it's entirely past.
+    assert_eq!(partitions.drop_before(JAN_31 + 2 * DAY), &all[..2]);
+    assert_eq!(partitions.keys().collect::<Vec<_>>(), &all[2..]);
+
+    partitions.remove_document(all[3]);
+    assert_eq!(partitions.num_docs(all[3]), Some(0));
+    assert!(partitions.drop_partition(all[3]));
+    assert!(!partitions.drop_partition(all[3]));
+}
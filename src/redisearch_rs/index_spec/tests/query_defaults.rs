@@ -0,0 +1,118 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use index_spec::{IndexSpec, QueryDefaults, SpecError};
+use query::QueryNode;
+
+fn tenant_filter(source: &str) -> Result<QueryNode, String> {
+    let tenant = source
+        .strip_prefix("@tenant:{")
+        .and_then(|s| s.strip_suffix('}'))
+        .ok_or_else(|| "syntax error".to_owned())?;
+    Ok(QueryNode::tag("tenant", [tenant]))
+}
+
+fn no_filter(_: &str) -> Result<QueryNode, String> {
+    unreachable!("not a filter option")
+}
+
+fn defaults(args: &[(&str, &str)]) -> Result<QueryDefaults, SpecError> {
+    let mut defaults = QueryDefaults::default();
+    for (name, value) in args {
+        assert!(defaults.try_set_option(name, value, tenant_filter)?);
+    }
+    Ok(defaults)
+}
+
+#[test]
+fn test_unset_defaults() {
+    let defaults = QueryDefaults::default();
+    assert_eq!(defaults.dialect(None, 1), 1);
+    assert_eq!(defaults.scorer(None), None);
+    assert_eq!(defaults.cap_limit(1_000_000), 1_000_000);
+    assert_eq!(
+        defaults.apply_filter(QueryNode::token("foo")),
+        QueryNode::token("foo")
+    );
+    assert!(defaults.to_args().is_empty());
+}
+
+#[test]
+fn test_defaults_apply() {
+    let defaults = defaults(&[
+        ("defaultdialect", "2"),
+        ("DEFAULTSCORER", "BM25STD"),
+        ("MAXLIMIT", "100"),
+    ])
+    .unwrap();
+    assert_eq!(defaults.dialect(None, 1), 2);
+    // The query's explicit choices win.
+    assert_eq!(defaults.dialect(Some(3), 1), 3);
+    assert_eq!(defaults.scorer(None), Some("BM25STD"));
+    assert_eq!(defaults.scorer(Some("TFIDF")), Some("TFIDF"));
+    // But the limit cap can't be bypassed.
+    assert_eq!(defaults.cap_limit(10), 10);
+    assert_eq!(defaults.cap_limit(10_000), 100);
+}
+
+#[test]
+fn test_query_filter() {
+    let defaults = defaults(&[("QUERYFILTER", "@tenant:{acme}")]).unwrap();
+    assert_eq!(defaults.filter().unwrap().source(), "@tenant:{acme}");
+    assert_eq!(
+        defaults.apply_filter(QueryNode::token("foo")),
+        QueryNode::intersect(vec![
+            QueryNode::token("foo"),
+            QueryNode::tag("tenant", ["acme"])
+        ])
+    );
+    assert_eq!(
+        defaults.to_args(),
+        vec![("QUERYFILTER", "@tenant:{acme}".to_owned())]
+    );
+}
+
+#[test]
+fn test_invalid_options() {
+    let bad = |option, value: &str| {
+        Err(SpecError::BadValue {
+            option,
+            value: value.to_owned(),
+        })
+    };
+    assert_eq!(
+        defaults(&[("DEFAULTDIALECT", "5")]),
+        bad("DEFAULTDIALECT", "5")
+    );
+    assert_eq!(
+        defaults(&[("DEFAULTDIALECT", "x")]),
+        bad("DEFAULTDIALECT", "x")
+    );
+    assert_eq!(defaults(&[("MAXLIMIT", "0")]), bad("MAXLIMIT", "0"));
+    assert_eq!(defaults(&[("DEFAULTSCORER", "")]), bad("DEFAULTSCORER", ""));
+    assert_eq!(
+        defaults(&[("QUERYFILTER", "@tenant:acme")]),
+        bad("QUERYFILTER", "@tenant:acme")
+    );
+    assert_eq!(
+        defaults(&[("MAXLIMIT", "10"), ("MAXLIMIT", "20")]),
+        Err(SpecError::DuplicateOption("MAXLIMIT"))
+    );
+}
+
+#[test]
+fn test_unknown_option_is_left_to_the_caller() {
+    let mut spec = IndexSpec::new("idx");
+    assert_eq!(
+        spec.query_defaults_mut()
+            .try_set_option("STOPWORDS", "0", no_filter),
+        Ok(false)
+    );
+    assert_eq!(spec.query_defaults(), &QueryDefaults::default());
+}
@@ -0,0 +1,77 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::cmp::Ordering;
+
+use index_spec::{SortOrders, SpecError, sort_orders::EnumOrder};
+
+fn severity() -> SortOrders {
+    let mut orders = SortOrders::default();
+    let mut args = "3 low medium high SORTABLE".split_whitespace();
+    assert_eq!(
+        orders.try_set_field_option("severity", "order", &mut args),
+        Ok(true)
+    );
+    assert_eq!(args.next(), Some("SORTABLE"));
+    orders
+}
+
+#[test]
+fn test_field_option() {
+    let mut orders = severity();
+    assert_eq!(
+        orders.try_set_field_option("severity", "SORTABLE", &mut std::iter::empty()),
+        Ok(false)
+    );
+    assert_eq!(
+        orders.try_set_field_option("severity", "ORDER", &mut ["1", "x"].into_iter()),
+        Err(SpecError::DuplicateOption("ORDER"))
+    );
+    let mut set =
+        |args: &[&str]| orders.try_set_field_option("state", "ORDER", &mut args.iter().copied());
+    assert!(set(&[]).is_err());
+    assert!(set(&["0"]).is_err());
+    assert!(set(&["2", "open"]).is_err());
+    assert_eq!(
+        set(&["2", "open", "open"]),
+        Err(SpecError::BadValue {
+            option: "ORDER",
+            value: "open".to_owned()
+        })
+    );
+
+    assert_eq!(
+        orders.to_args("severity"),
+        ["ORDER", "3", "low", "medium", "high"]
+    );
+    assert!(orders.to_args("state").is_empty());
+    orders.remove("severity");
+    assert_eq!(orders.get("severity"), None);
+}
+
+#[test]
+fn test_sort_by_declared_order() {
+    let orders = severity();
+    let mut values = vec!["high", "unknown", "low", "critical", "medium"];
+    values.sort_by(|a, b| orders.cmp("severity", a, b));
+    // Undeclared values come last, in lexicographic order.
+    assert_eq!(values, ["low", "medium", "high", "critical", "unknown"]);
+
+    assert_eq!(orders.cmp("title", "high", "low"), Ordering::Less);
+    assert_eq!(orders.get("severity").unwrap().rank("high"), Some(2));
+}
+
+#[test]
+fn test_enum_order() {
+    let order = EnumOrder::new(vec!["b".to_owned(), "a".to_owned()]).unwrap();
+    assert_eq!(order.values(), ["b", "a"]);
+    assert_eq!(order.cmp("b", "a"), Ordering::Less);
+    assert_eq!(order.cmp("a", "a"), Ordering::Equal);
+    assert!(EnumOrder::new(Vec::new()).is_err());
+}
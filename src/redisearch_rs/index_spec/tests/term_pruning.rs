@@ -0,0 +1,89 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use index_spec::{
+    SpecError, TermPruning,
+    term_pruning::{PruneLevel, PrunedTerms, PruningStats},
+};
+
+fn pruning(args: &[(&str, &str)]) -> Result<TermPruning, SpecError> {
+    let mut pruning = TermPruning::default();
+    for (name, value) in args {
+        assert!(pruning.try_set_option(name, value)?);
+    }
+    Ok(pruning)
+}
+
+#[test]
+fn test_options() {
+    let mut disabled = TermPruning::default();
+    assert!(!disabled.is_enabled());
+    assert!(!disabled.try_set_option("MAXLIMIT", "10").unwrap());
+
+    let p = pruning(&[
+        ("pruneoffsets", "0.3"),
+        ("PRUNETERMS", "0.8"),
+        ("PRUNEMINDOCS", "10"),
+    ])
+    .unwrap();
+    assert!(p.is_enabled());
+    assert_eq!(
+        p.to_args(),
+        [
+            ("PRUNEOFFSETS", "0.3".to_owned()),
+            ("PRUNETERMS", "0.8".to_owned()),
+            ("PRUNEMINDOCS", "10".to_owned()),
+        ]
+    );
+
+    assert_eq!(
+        pruning(&[("PRUNETERMS", "1.5")]),
+        Err(SpecError::BadValue {
+            option: "PRUNETERMS",
+            value: "1.5".to_owned()
+        })
+    );
+    assert_eq!(
+        pruning(&[("PRUNEOFFSETS", "0.5"), ("PRUNEOFFSETS", "0.6")]),
+        Err(SpecError::DuplicateOption("PRUNEOFFSETS"))
+    );
+}
+
+#[test]
+fn test_levels() {
+    let p = pruning(&[("PRUNEOFFSETS", "0.3"), ("PRUNETERMS", "0.8")]).unwrap();
+    // Below PRUNEMINDOCS, nothing is pruned.
+    assert_eq!(p.level_for(999, 999), PruneLevel::Full);
+    assert_eq!(p.level_for(300, 1000), PruneLevel::Full);
+    assert_eq!(p.level_for(301, 1000), PruneLevel::NoOffsets);
+    assert_eq!(p.level_for(801, 1000), PruneLevel::Stopped);
+}
+
+#[test]
+fn test_pruning_is_sticky() {
+    let p = pruning(&[("PRUNEOFFSETS", "0.5"), ("PRUNEMINDOCS", "1")]).unwrap();
+    let mut terms = PrunedTerms::default();
+    assert_eq!(terms.observe(&p, b"rare", 1, 10), PruneLevel::Full);
+    assert_eq!(terms.observe(&p, b"the", 9, 10), PruneLevel::NoOffsets);
+    // The frequency of "the" drops, but its postings were already truncated.
+    assert_eq!(terms.observe(&p, b"the", 9, 100), PruneLevel::NoOffsets);
+    assert_eq!(terms.level(b"the"), PruneLevel::NoOffsets);
+
+    assert_eq!(
+        terms.pruned().collect::<Vec<_>>(),
+        [(&b"the"[..], PruneLevel::NoOffsets)]
+    );
+    assert_eq!(
+        terms.stats(),
+        PruningStats {
+            offsets_pruned_terms: 1,
+            stopped_terms: 0
+        }
+    );
+}
@@ -0,0 +1,331 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Intersection of sorted doc id lists, as done by the intersection iterator
+//! for queries such as `foo bar`.
+//!
+//! No single algorithm is best for every input. Merging the lists scans every
+//! id, which is wasteful when a tiny list meets a huge one. Galloping looks up
+//! each id of the smallest list in the others, skipping over the ids in
+//! between. A bitmap AND is branch-free and wins when the lists are dense.
+//!
+//! [`Intersection`] walks the doc id space in windows, and picks the cheapest
+//! [`Strategy`] for each window from the number of ids each list has within
+//! it. It therefore adapts when the relative sizes of the lists change along
+//! the id space, e.g. for a term that is frequent in recent documents only.
+
+/// The default number of doc ids covered by a window.
+pub const DEFAULT_WINDOW: u64 = 1 << 16;
+
+/// The cost of a merge step, relative to setting a bit or combining a word of
+/// a bitmap. Merge steps branch on every id and mispredict often.
+const MERGE_STEP_COST: u64 = 4;
+/// The cost of a probe of a galloping search, on the same scale.
+const GALLOP_PROBE_COST: u64 = 4;
+
+/// How the lists are intersected within a window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Look up each id of the smallest list in the others.
+    Gallop,
+    /// Advance all the lists in lockstep.
+    Merge,
+    /// Set the ids of each list in a bitmap, and AND the bitmaps.
+    Bitmap,
+}
+
+impl Strategy {
+    /// The cheapest strategy to intersect lists with `lens` ids each, within
+    /// a window of `span` doc ids.
+    pub fn choose(lens: &[usize], span: u64) -> Self {
+        let Some((smallest, _)) = lens.iter().enumerate().min_by_key(|(_, n)| **n) else {
+            return Self::Merge;
+        };
+        let total: u64 = lens.iter().map(|&n| n as u64).sum();
+
+        let merge = total * MERGE_STEP_COST;
+        let gallop = gallop_cost(lens, smallest);
+        let bitmap = total + lens.len() as u64 * span.div_ceil(64);
+
+        if gallop < merge && gallop <= bitmap {
+            Self::Gallop
+        } else if bitmap < merge {
+            Self::Bitmap
+        } else {
+            Self::Merge
+        }
+    }
+
+    /// How a list of `len` ids should follow the ids of a `leader` list of
+    /// `leader_len` ids, when the lists can only be read in order or skipped
+    /// forward, as the children of an iterator: [`Strategy::Merge`] reads
+    /// every id, [`Strategy::Gallop`] skips to each id of the leader.
+    ///
+    /// [`Strategy::Bitmap`] needs the ids upfront, and is never chosen.
+    pub fn choose_sequential(leader_len: usize, len: usize) -> Self {
+        let lens = [leader_len, len];
+        let merge = (leader_len as u64 + len as u64) * MERGE_STEP_COST;
+        if gallop_cost(&lens, 0) < merge {
+            Self::Gallop
+        } else {
+            Self::Merge
+        }
+    }
+}
+
+/// The cost of galloping over the lists of `lens` ids with the ids of the
+/// list at `smallest`.
+fn gallop_cost(lens: &[usize], smallest: usize) -> u64 {
+    let min = lens[smallest] as u64;
+    // Each id of the smallest list gallops over about `n / min` ids of every
+    // other list, which takes twice the log of that many probes.
+    lens.iter()
+        .enumerate()
+        .filter(|&(i, _)| i != smallest)
+        .map(|(_, &n)| {
+            let gap = n as u64 / min.max(1) + 1;
+            min * GALLOP_PROBE_COST * (1 + 2 * u64::from(gap.ilog2()))
+        })
+        .sum()
+}
+
+/// The number of windows intersected with each strategy, for the profile.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IntersectionStats {
+    pub gallop: u64,
+    pub merge: u64,
+    pub bitmap: u64,
+}
+
+/// The position of the first id of `list` that is `>= target`, or the length
+/// of `list` if there is none.
+///
+/// The search probes exponentially growing distances before bisecting, so it
+/// costs `O(log d)` where `d` is the returned position, rather than
+/// `O(log n)`.
+pub fn gallop(list: &[u64], target: u64) -> usize {
+    let mut hi = 1;
+    while hi < list.len() && list[hi] < target {
+        hi *= 2;
+    }
+    let lo = hi / 2;
+    let end = (hi + 1).min(list.len());
+    lo + bsearch::bsearch_ge(&list[lo..end], &target, u64::cmp).unwrap_or(end - lo)
+}
+
+/// An iterator over the ids present in all of a set of sorted lists.
+pub struct Intersection<'a> {
+    /// The parts of the lists not intersected yet.
+    lists: Vec<&'a [u64]>,
+    window: u64,
+    /// The ids found in the current window.
+    found: Vec<u64>,
+    next: usize,
+    bitmap: Vec<u64>,
+    scratch: Vec<u64>,
+    stats: IntersectionStats,
+}
+
+impl<'a> Intersection<'a> {
+    /// Intersect `lists`, each sorted and without duplicates.
+    pub fn new(lists: Vec<&'a [u64]>) -> Self {
+        debug_assert!(
+            lists.iter().all(|l| l.is_sorted_by(|a, b| a < b)),
+            "IDs must be sorted and unique"
+        );
+        Self {
+            lists,
+            window: DEFAULT_WINDOW,
+            found: Vec::new(),
+            next: 0,
+            bitmap: Vec::new(),
+            scratch: Vec::new(),
+            stats: IntersectionStats::default(),
+        }
+    }
+
+    /// Re-evaluate the strategy every `window` doc ids, rather than every
+    /// [`DEFAULT_WINDOW`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is zero.
+    pub fn with_window(mut self, window: u64) -> Self {
+        assert!(window > 0, "the window must cover at least one doc id");
+        self.window = window;
+        self
+    }
+
+    /// The strategies used so far.
+    pub const fn stats(&self) -> IntersectionStats {
+        self.stats
+    }
+
+    /// Intersect the next window into `found`. Returns `false` once a list
+    /// is exhausted.
+    fn fill(&mut self) -> bool {
+        self.found.clear();
+        self.next = 0;
+        if self.lists.is_empty() {
+            return false;
+        }
+
+        // The window starts at the largest head: no list has a match before.
+        let Some(lo) = self
+            .lists
+            .iter()
+            .map(|l| l.first().copied())
+            .try_fold(0, |lo, head| Some(lo.max(head?)))
+        else {
+            return false;
+        };
+        let last = lo.saturating_add(self.window - 1);
+
+        let mut segments = Vec::with_capacity(self.lists.len());
+        for list in &mut self.lists {
+            *list = &list[gallop(list, lo)..];
+            let end = match last.checked_add(1) {
+                Some(hi) => gallop(list, hi),
+                None => list.len(),
+            };
+            let (segment, rest) = list.split_at(end);
+            segments.push(segment);
+            *list = rest;
+        }
+
+        let lens: Vec<usize> = segments.iter().map(|s| s.len()).collect();
+        match Strategy::choose(&lens, last - lo + 1) {
+            Strategy::Gallop => {
+                self.stats.gallop += 1;
+                intersect_gallop(&segments, &mut self.found);
+            }
+            Strategy::Merge => {
+                self.stats.merge += 1;
+                intersect_merge(&segments, &mut self.found);
+            }
+            Strategy::Bitmap => {
+                self.stats.bitmap += 1;
+                intersect_bitmap(
+                    &segments,
+                    lo,
+                    last - lo + 1,
+                    &mut self.bitmap,
+                    &mut self.scratch,
+                    &mut self.found,
+                );
+            }
+        }
+        true
+    }
+}
+
+impl Iterator for Intersection<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        while self.next == self.found.len() {
+            if !self.fill() {
+                return None;
+            }
+        }
+        self.next += 1;
+        Some(self.found[self.next - 1])
+    }
+}
+
+fn intersect_gallop(segments: &[&[u64]], found: &mut Vec<u64>) {
+    let Some(smallest) = (0..segments.len()).min_by_key(|&i| segments[i].len()) else {
+        return;
+    };
+    let mut starts = vec![0; segments.len()];
+    'ids: for &id in segments[smallest] {
+        for (i, segment) in segments.iter().enumerate() {
+            if i == smallest {
+                continue;
+            }
+            starts[i] += gallop(&segment[starts[i]..], id);
+            match segment.get(starts[i]) {
+                None => return,
+                Some(&other) if other != id => continue 'ids,
+                Some(_) => {}
+            }
+        }
+        found.push(id);
+    }
+}
+
+/// Leapfrog over the lists, advancing each in turn to the current candidate,
+/// until they all agree on it.
+fn intersect_merge(segments: &[&[u64]], found: &mut Vec<u64>) {
+    let mut cursors = vec![0; segments.len()];
+    let mut candidate = 0;
+    let mut agreeing = 0;
+    for i in (0..segments.len()).cycle() {
+        let segment = segments[i];
+        while segment.get(cursors[i]).is_some_and(|&id| id < candidate) {
+            cursors[i] += 1;
+        }
+        let Some(&id) = segment.get(cursors[i]) else {
+            return;
+        };
+        if id == candidate {
+            agreeing += 1;
+        } else {
+            candidate = id;
+            agreeing = 1;
+        }
+        if agreeing == segments.len() {
+            found.push(candidate);
+            let Some(next) = candidate.checked_add(1) else {
+                return;
+            };
+            candidate = next;
+            agreeing = 0;
+        }
+    }
+}
+
+/// Intersect the window of `span` doc ids starting at `lo` through bitmaps.
+/// `words` and `scratch` are reused across windows.
+fn intersect_bitmap(
+    segments: &[&[u64]],
+    lo: u64,
+    span: u64,
+    words: &mut Vec<u64>,
+    scratch: &mut Vec<u64>,
+    found: &mut Vec<u64>,
+) {
+    let len = span.div_ceil(64) as usize;
+    let Some((first, others)) = segments.split_first() else {
+        return;
+    };
+    set_bits(words, lo, len, first);
+    for segment in others {
+        set_bits(scratch, lo, len, segment);
+        for (word, other) in words.iter_mut().zip(scratch.iter()) {
+            *word &= other;
+        }
+    }
+    for (i, &word) in words.iter().enumerate() {
+        let mut word = word;
+        while word != 0 {
+            found.push(lo + i as u64 * 64 + u64::from(word.trailing_zeros()));
+            word &= word - 1;
+        }
+    }
+}
+
+fn set_bits(words: &mut Vec<u64>, lo: u64, len: usize, ids: &[u64]) {
+    words.clear();
+    words.resize(len, 0);
+    for &id in ids {
+        let bit = id - lo;
+        words[(bit / 64) as usize] |= 1 << (bit % 64);
+    }
+}
@@ -0,0 +1,143 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::collections::BTreeSet;
+
+use intersection::{Intersection, IntersectionStats, Strategy, gallop};
+
+fn naive(lists: &[Vec<u64>]) -> Vec<u64> {
+    let mut sets = lists
+        .iter()
+        .map(|l| l.iter().copied().collect::<BTreeSet<_>>());
+    let first = sets.next().unwrap_or_default();
+    sets.fold(first, |acc, s| &acc & &s).into_iter().collect()
+}
+
+fn intersect(lists: &[Vec<u64>], window: u64) -> (Vec<u64>, IntersectionStats) {
+    let mut it = Intersection::new(lists.iter().map(Vec::as_slice).collect()).with_window(window);
+    let ids = it.by_ref().collect();
+    (ids, it.stats())
+}
+
+/// The multiples of `step` below `end`, shifted by `offset`.
+fn multiples(step: u64, offset: u64, end: u64) -> Vec<u64> {
+    (offset..end).step_by(step as usize).collect()
+}
+
+#[test]
+fn test_gallop() {
+    let list = [2, 4, 6, 8, 10, 12, 14];
+    for target in 0..16 {
+        let expected = list.iter().position(|&x| x >= target).unwrap_or(list.len());
+        assert_eq!(gallop(&list, target), expected, "target {target}");
+    }
+    assert_eq!(gallop(&[], 3), 0);
+}
+
+#[test]
+fn test_choose() {
+    // A tiny list against a huge one.
+    assert_eq!(Strategy::choose(&[10, 10_000], 1 << 16), Strategy::Gallop);
+    // Two dense lists.
+    assert_eq!(Strategy::choose(&[1000, 1000], 1 << 16), Strategy::Bitmap);
+    // Two sparse lists of similar sizes.
+    assert_eq!(Strategy::choose(&[100, 100], 1 << 16), Strategy::Merge);
+    // An empty list makes galloping free.
+    assert_eq!(Strategy::choose(&[0, 5000], 1 << 16), Strategy::Gallop);
+}
+
+#[test]
+fn test_choose_sequential() {
+    assert_eq!(Strategy::choose_sequential(10, 10_000), Strategy::Gallop);
+    assert_eq!(Strategy::choose_sequential(100, 100), Strategy::Merge);
+    assert_eq!(Strategy::choose_sequential(1000, 1500), Strategy::Merge);
+    assert_eq!(Strategy::choose_sequential(0, 5000), Strategy::Gallop);
+}
+
+#[test]
+fn test_tiny_and_huge() {
+    let lists = [multiples(1, 0, 200_000), vec![3, 70_000, 70_001, 150_000]];
+    let (ids, stats) = intersect(&lists, 1 << 16);
+    assert_eq!(ids, naive(&lists));
+    assert_eq!(stats.merge + stats.bitmap, 0);
+    assert!(stats.gallop > 0);
+}
+
+#[test]
+fn test_dense() {
+    let lists = [multiples(2, 0, 100_000), multiples(3, 0, 100_000)];
+    let (ids, stats) = intersect(&lists, 1 << 12);
+    assert_eq!(ids, naive(&lists));
+    assert!(stats.bitmap > 0);
+    assert_eq!(stats.gallop + stats.merge, 0);
+}
+
+#[test]
+fn test_sparse() {
+    let lists = [
+        multiples(997, 0, 1_000_000),
+        multiples(991, 0, 1_000_000),
+        multiples(983, 0, 1_000_000),
+    ];
+    let (ids, stats) = intersect(&lists, 1 << 20);
+    assert_eq!(ids, naive(&lists));
+    assert!(stats.merge > 0);
+}
+
+#[test]
+fn test_strategy_changes_along_the_ids() {
+    // The second list is dense over the first half of the ids only.
+    let first = multiples(2, 0, 400_000);
+    let mut second = multiples(3, 0, 200_000);
+    second.extend([250_000, 300_000, 399_998]);
+    let lists = [first, second];
+    let (ids, stats) = intersect(&lists, 1 << 14);
+    assert_eq!(ids, naive(&lists));
+    assert!(stats.bitmap > 0);
+    assert!(stats.gallop > 0);
+}
+
+#[test]
+fn test_matches_naive() {
+    // A simple LCG, so that the lists are reproducible.
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = move || {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1);
+        state >> 33
+    };
+    for round in 0..50 {
+        let count = 1 + next() % 4;
+        let lists: Vec<Vec<u64>> = (0..count)
+            .map(|_| {
+                let density = 1 + next() % 64;
+                let end = 1 + next() % 50_000;
+                (0..end).filter(|_| next() % density == 0).collect()
+            })
+            .collect();
+        for window in [1, 63, 64, 1000, 1 << 16] {
+            let (ids, _) = intersect(&lists, window);
+            assert_eq!(ids, naive(&lists), "round {round}, window {window}");
+        }
+    }
+}
+
+#[test]
+fn test_edge_cases() {
+    assert_eq!(Intersection::new(Vec::new()).count(), 0);
+    assert_eq!(Intersection::new(vec![&[1, 2, 3], &[]]).count(), 0);
+    let single = [5, 9, u64::MAX];
+    assert_eq!(Intersection::new(vec![&single]).collect::<Vec<_>>(), single);
+    let high = [u64::MAX - 1, u64::MAX];
+    assert_eq!(
+        Intersection::new(vec![&high, &[u64::MAX]]).collect::<Vec<_>>(),
+        [u64::MAX]
+    );
+}
@@ -7,13 +7,19 @@
  * GNU Affero General Public License v3 (AGPLv3).
 */
 
-//! This module contains the debug information for an inverted index.
+//! This module contains the debug information for an inverted index, and
+//! the structured replies of the `FT.DEBUG` dump subcommands built from it.
+//!
+//! The replies mirror the ones produced by `debug_commands.c` field for field,
+//! so the test suites relying on them keep passing when the Rust index is active.
 
 use ffi::t_docId;
 
+use crate::{IndexReader, RSIndexResult};
+
 /// Summary information about an inverted index containing all key metrics
 #[repr(C)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Summary {
     pub number_of_docs: u32,
     pub number_of_entries: usize,
@@ -26,9 +32,292 @@ pub struct Summary {
 
 /// Summary information about the key metrics of a block in an inverted index
 #[repr(C)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct BlockSummary {
     pub first_doc_id: t_docId,
     pub last_doc_id: t_docId,
     pub number_of_entries: u16,
 }
+
+/// A reply of an `FT.DEBUG` subcommand, to be serialized by the caller.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DebugReply {
+    Integer(i64),
+    Double(f64),
+    String(String),
+    Array(Vec<DebugReply>),
+    Map(Vec<(String, DebugReply)>),
+}
+
+impl DebugReply {
+    fn str(s: &str) -> Self {
+        Self::String(s.to_owned())
+    }
+}
+
+/// Push a `name, value` pair on a flat array reply, as `REPLY_WITH_LONG_LONG` does.
+fn push_integer(reply: &mut Vec<DebugReply>, name: &str, value: impl TryInto<i64>) {
+    reply.push(DebugReply::str(name));
+    reply.push(DebugReply::Integer(value.try_into().unwrap_or(i64::MAX)));
+}
+
+/// Push a `name, value` pair on a flat array reply, as `REPLY_WITH_DOUBLE` does.
+fn push_double(reply: &mut Vec<DebugReply>, name: &str, value: f64) {
+    reply.push(DebugReply::str(name));
+    reply.push(DebugReply::Double(value));
+}
+
+impl Summary {
+    /// The header of `INVIDX_SUMMARY` and of `DUMP_NUMIDX ... WITH_HEADERS`, as
+    /// a flat list of name/value pairs.
+    pub fn header_reply(&self) -> Vec<DebugReply> {
+        let mut reply = Vec::new();
+        push_integer(&mut reply, "numDocs", self.number_of_docs);
+        push_integer(&mut reply, "numEntries", self.number_of_entries);
+        push_integer(&mut reply, "lastId", self.last_doc_id);
+        push_integer(&mut reply, "flags", self.flags);
+        push_integer(&mut reply, "numberOfBlocks", self.number_of_blocks);
+        if self.has_efficiency {
+            push_double(
+                &mut reply,
+                "blocks_efficiency (numEntries/numberOfBlocks)",
+                self.block_efficiency,
+            );
+        }
+        reply
+    }
+}
+
+impl BlockSummary {
+    /// The description of a single block in `INVIDX_SUMMARY`.
+    pub fn reply(&self) -> DebugReply {
+        let mut reply = Vec::new();
+        push_integer(&mut reply, "firstId", self.first_doc_id);
+        push_integer(&mut reply, "lastId", self.last_doc_id);
+        push_integer(&mut reply, "numEntries", self.number_of_entries);
+        DebugReply::Array(reply)
+    }
+}
+
+/// The reply of `FT.DEBUG INVIDX_SUMMARY`: the index header followed by the
+/// boundaries and entry count of each block.
+pub fn summary_reply(summary: &Summary, blocks: &[BlockSummary]) -> DebugReply {
+    let mut reply = summary.header_reply();
+    reply.push(DebugReply::str("blocks"));
+    reply.extend(blocks.iter().map(BlockSummary::reply));
+    DebugReply::Array(reply)
+}
+
+/// The reply of `FT.DEBUG DUMP_INVIDX`: the ID of every document in the
+/// posting list read by `reader`, in order.
+///
+/// # Errors
+///
+/// Returns the error of the reader if a record could not be decoded.
+pub fn doc_ids_reply<'index>(reader: &mut impl IndexReader<'index>) -> std::io::Result<DebugReply> {
+    let mut result = RSIndexResult::default();
+    let mut reply = Vec::new();
+    while reader.next_record(&mut result)? {
+        reply.push(DebugReply::Integer(result.doc_id as i64));
+    }
+    Ok(DebugReply::Array(reply))
+}
+
+/// The `value, docId` pairs of a numeric posting list, as returned by
+/// [`NumericRangeDump::entries`].
+///
+/// # Errors
+///
+/// Returns the error of the reader if a record could not be decoded.
+pub fn numeric_entries<'index>(
+    reader: &mut impl IndexReader<'index>,
+) -> std::io::Result<Vec<(f64, t_docId)>> {
+    let mut result = RSIndexResult::numeric(0.0);
+    let mut entries = Vec::new();
+    while reader.next_record(&mut result)? {
+        entries.push((result.as_numeric().unwrap_or_default(), result.doc_id));
+    }
+    Ok(entries)
+}
+
+/// A leaf range of a numeric tree, as dumped by `DUMP_NUMIDXTREE`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericRangeDump {
+    pub min_value: f64,
+    pub max_value: f64,
+    /// The size of the range's inverted index, in bytes.
+    pub inverted_index_size: usize,
+    /// The (estimated) number of distinct values in the range.
+    pub cardinality: usize,
+    /// The summary of the range's inverted index.
+    pub summary: Summary,
+    /// The `value, docId` pairs stored in the range, see [`numeric_entries`].
+    pub entries: Vec<(f64, t_docId)>,
+}
+
+impl NumericRangeDump {
+    /// The description of the range, which is also where the block efficiency
+    /// of its inverted index is reported.
+    pub fn reply(&self) -> DebugReply {
+        let mut reply = Vec::new();
+        push_double(&mut reply, "minVal", self.min_value);
+        push_double(&mut reply, "maxVal", self.max_value);
+        push_double(
+            &mut reply,
+            "invertedIndexSize [bytes]",
+            self.inverted_index_size as f64,
+        );
+        push_integer(&mut reply, "card", self.cardinality);
+        reply.push(DebugReply::str("entries"));
+        reply.push(self.entries_reply());
+        DebugReply::Array(reply)
+    }
+
+    fn entries_reply(&self) -> DebugReply {
+        let summary = &self.summary;
+        let mut reply = Vec::new();
+        push_integer(&mut reply, "numDocs", summary.number_of_docs);
+        push_integer(&mut reply, "numEntries", summary.number_of_entries);
+        push_integer(&mut reply, "lastId", summary.last_doc_id);
+        push_integer(&mut reply, "size", summary.number_of_blocks);
+        push_double(
+            &mut reply,
+            "blocks_efficiency (numEntries/size)",
+            summary.block_efficiency,
+        );
+        reply.push(DebugReply::str("values"));
+        let mut values = Vec::with_capacity(self.entries.len() * 4);
+        for &(value, doc_id) in &self.entries {
+            push_double(&mut values, "value", value);
+            push_integer(&mut values, "docId", doc_id);
+        }
+        reply.push(DebugReply::Array(values));
+        DebugReply::Array(reply)
+    }
+}
+
+/// A node of a numeric tree, as dumped by `DUMP_NUMIDXTREE`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumericNodeDump {
+    Leaf {
+        range: Option<NumericRangeDump>,
+    },
+    /// An inner node, which may still retain the range it had as a leaf.
+    Split {
+        value: f64,
+        max_depth: u32,
+        range: Option<NumericRangeDump>,
+        left: Box<NumericNodeDump>,
+        right: Box<NumericNodeDump>,
+    },
+}
+
+impl NumericNodeDump {
+    /// The range of this node, if it has one.
+    pub const fn range(&self) -> Option<&NumericRangeDump> {
+        match self {
+            Self::Leaf { range } | Self::Split { range, .. } => range.as_ref(),
+        }
+    }
+
+    /// The reply of this node and its children, with the sum of the block
+    /// efficiencies of the ranges found in it.
+    ///
+    /// With `minimal`, the ranges are replaced by empty arrays.
+    fn reply(&self, minimal: bool) -> (DebugReply, f64) {
+        let mut efficiency = 0.0;
+        let mut map = Vec::new();
+        if let Some(range) = self.range() {
+            let reply = if minimal {
+                DebugReply::Array(Vec::new())
+            } else {
+                efficiency += range.summary.block_efficiency;
+                range.reply()
+            };
+            map.push(("range".to_owned(), reply));
+        }
+        if let Self::Split {
+            value,
+            max_depth,
+            left,
+            right,
+            ..
+        } = self
+        {
+            map.push(("value".to_owned(), DebugReply::Double(*value)));
+            map.push((
+                "maxDepth".to_owned(),
+                DebugReply::Integer((*max_depth).into()),
+            ));
+            for (name, child) in [("left", left), ("right", right)] {
+                let (reply, child_efficiency) = child.reply(minimal);
+                efficiency += child_efficiency;
+                map.push((name.to_owned(), reply));
+            }
+        }
+        (DebugReply::Map(map), efficiency)
+    }
+}
+
+/// A numeric tree, as dumped by `DUMP_NUMIDXTREE` and summarized by
+/// `NUMIDX_SUMMARY`. A tree which was never initialized is dumped from the
+/// [`Default`] value.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NumericTreeDump {
+    pub num_ranges: usize,
+    pub num_leaves: usize,
+    pub num_entries: usize,
+    pub last_doc_id: t_docId,
+    pub revision_id: u32,
+    pub unique_id: u32,
+    pub empty_leaves: usize,
+    /// The memory used by the whole tree, in bytes.
+    pub memory_usage: usize,
+    pub root: Option<NumericNodeDump>,
+}
+
+impl NumericTreeDump {
+    /// The reply of `FT.DEBUG DUMP_NUMIDXTREE`. With `minimal`, the ranges of
+    /// the nodes are left out.
+    pub fn reply(&self, minimal: bool) -> DebugReply {
+        let (root, efficiency) = match &self.root {
+            Some(root) => root.reply(minimal),
+            None => (DebugReply::Map(Vec::new()), 0.0),
+        };
+        let integer = |name: &str, value: i64| (name.to_owned(), DebugReply::Integer(value));
+        DebugReply::Map(vec![
+            integer("numRanges", self.num_ranges as i64),
+            integer("numEntries", self.num_entries as i64),
+            integer("lastDocId", self.last_doc_id as i64),
+            integer("revisionId", self.revision_id.into()),
+            integer("uniqueId", self.unique_id.into()),
+            integer("emptyLeaves", self.empty_leaves as i64),
+            ("root".to_owned(), root),
+            (
+                "Tree stats".to_owned(),
+                DebugReply::Map(vec![(
+                    "Average memory efficiency (numEntries/size)/numRanges".to_owned(),
+                    DebugReply::Double(efficiency / self.num_ranges as f64),
+                )]),
+            ),
+        ])
+    }
+
+    /// The reply of `FT.DEBUG NUMIDX_SUMMARY`.
+    pub fn summary_reply(&self) -> DebugReply {
+        let max_depth = match &self.root {
+            Some(NumericNodeDump::Split { max_depth, .. }) => *max_depth,
+            _ => 0,
+        };
+        let mut reply = Vec::new();
+        push_integer(&mut reply, "numRanges", self.num_ranges);
+        push_integer(&mut reply, "numLeaves", self.num_leaves);
+        push_integer(&mut reply, "numEntries", self.num_entries);
+        push_integer(&mut reply, "lastDocId", self.last_doc_id);
+        push_integer(&mut reply, "revisionId", self.revision_id);
+        push_integer(&mut reply, "emptyLeaves", self.empty_leaves);
+        push_integer(&mut reply, "RootMaxDepth", max_depth);
+        push_integer(&mut reply, "MemoryUsage", self.memory_usage);
+        DebugReply::Array(reply)
+    }
+}
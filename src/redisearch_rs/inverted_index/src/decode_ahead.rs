@@ -0,0 +1,280 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! A reader that decodes whole blocks ahead of their use.
+//!
+//! [`IndexReaderCore`](crate::IndexReaderCore) decodes one record per call,
+//! interleaved with the work of its caller. For long scans, the
+//! [`DecodeAheadReader`] instead decodes a block at a time into a double
+//! buffer: the records of the current block are handed out from the front
+//! buffer, while the next block is decoded into the back buffer. This keeps
+//! the decode loop tight, and lets the caller decode the next block at a time
+//! of its choosing with [`DecodeAheadReader::prefetch`], e.g. while it waits
+//! on the other children of an intersection.
+//!
+//! The decoded records borrow the block buffers and hold raw pointers, so they
+//! can't be handed over from another thread: the decoding always runs on the
+//! thread that reads.
+
+use std::{io::Cursor, sync::atomic};
+
+use ffi::{IndexFlags, IndexFlags_Index_HasMultiValue, t_docId};
+
+use crate::{DecodedBy, Decoder, IndexReader, InvertedIndex, RSIndexResult};
+
+/// When the reader decodes the next block into the back buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefetchMode {
+    /// As soon as a block is entered.
+    Eager,
+    /// Only when [`DecodeAheadReader::prefetch`] is called.
+    Manual,
+}
+
+/// How well decoding ahead worked, for the profile.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrefetchStats {
+    /// Blocks which were already decoded when the reader entered them.
+    pub hits: u64,
+    /// Blocks which had to be decoded when the reader entered them.
+    pub misses: u64,
+    /// Blocks decoded ahead but never entered, because the reader skipped
+    /// past them or was reset.
+    pub wasted: u64,
+}
+
+impl PrefetchStats {
+    /// The percentage of the blocks entered which were already decoded.
+    pub const fn hit_rate(&self) -> f64 {
+        let entered = self.hits + self.misses;
+        if entered == 0 {
+            return 0.0;
+        }
+        self.hits as f64 * 100.0 / entered as f64
+    }
+
+    /// The fields of the reader in the profile.
+    pub const fn profile(&self) -> [(&'static str, f64); 4] {
+        [
+            ("Prefetch hits", self.hits as f64),
+            ("Prefetch misses", self.misses as f64),
+            ("Prefetch wasted", self.wasted as f64),
+            ("Prefetch hit rate", self.hit_rate()),
+        ]
+    }
+}
+
+/// The decoded records of a block.
+struct DecodedBlock<'index> {
+    /// The index of the block in the inverted index.
+    index: usize,
+    records: Vec<RSIndexResult<'index>>,
+    /// The position of the next record to hand out.
+    next: usize,
+}
+
+/// Reader of an [`InvertedIndex`] which decodes a block at a time, and the
+/// next block ahead of its use. See the [module documentation](self).
+pub struct DecodeAheadReader<'index, E, D> {
+    ii: &'index InvertedIndex<E>,
+    decoder: D,
+    mode: PrefetchMode,
+    /// The index of the block being read.
+    block: usize,
+    /// The records of `block`, once the reader entered it.
+    current: Option<DecodedBlock<'index>>,
+    /// The records of a following block, decoded ahead.
+    ahead: Option<DecodedBlock<'index>>,
+    /// The marker of the inverted index when this reader last read from it.
+    gc_marker: u32,
+    stats: PrefetchStats,
+}
+
+impl<'index, E: DecodedBy<Decoder = D>, D: Decoder> DecodeAheadReader<'index, E, D> {
+    pub(crate) fn new(ii: &'index InvertedIndex<E>, mode: PrefetchMode) -> Self {
+        Self {
+            ii,
+            decoder: E::decoder(),
+            mode,
+            block: 0,
+            current: None,
+            ahead: None,
+            gc_marker: ii.gc_marker.load(atomic::Ordering::Relaxed),
+            stats: PrefetchStats::default(),
+        }
+    }
+
+    /// How well decoding ahead worked so far.
+    pub const fn stats(&self) -> PrefetchStats {
+        self.stats
+    }
+
+    /// Decode the block following the current one into the back buffer, if
+    /// it isn't there already. Before the reader entered its first block,
+    /// this decodes that block instead.
+    pub fn prefetch(&mut self) -> std::io::Result<()> {
+        let index = if self.current.is_some() {
+            self.block + 1
+        } else {
+            self.block
+        };
+        self.prefetch_block(index)
+    }
+
+    fn prefetch_block(&mut self, index: usize) -> std::io::Result<()> {
+        if index >= self.ii.blocks.len() || self.ahead.as_ref().is_some_and(|a| a.index == index) {
+            return Ok(());
+        }
+        if self.ahead.take().is_some() {
+            self.stats.wasted += 1;
+        }
+        self.ahead = Some(self.decode_block(index)?);
+        Ok(())
+    }
+
+    fn decode_block(&self, index: usize) -> std::io::Result<DecodedBlock<'index>> {
+        let ii = self.ii;
+        let block = &ii.blocks[index];
+        let mut cursor = Cursor::new(block.buffer.as_slice());
+        let mut records = Vec::with_capacity(block.num_entries as usize);
+        let mut last_doc_id = block.first_doc_id;
+        while (cursor.position() as usize) < cursor.get_ref().len() {
+            let base = D::base_id(block, last_doc_id);
+            let record = self.decoder.decode_new(&mut cursor, base)?;
+            last_doc_id = record.doc_id;
+            records.push(record);
+        }
+        Ok(DecodedBlock {
+            index,
+            records,
+            next: 0,
+        })
+    }
+
+    /// The records of the current block, decoding them if the reader didn't
+    /// enter it yet.
+    fn entered(&mut self) -> std::io::Result<&mut DecodedBlock<'index>> {
+        if self.current.is_none() {
+            let decoded = match self.ahead.take() {
+                Some(ahead) if ahead.index == self.block => {
+                    self.stats.hits += 1;
+                    ahead
+                }
+                ahead => {
+                    match ahead {
+                        // Still ahead of the reader.
+                        Some(ahead) if ahead.index > self.block => self.ahead = Some(ahead),
+                        Some(_) => self.stats.wasted += 1,
+                        None => {}
+                    }
+                    self.stats.misses += 1;
+                    self.decode_block(self.block)?
+                }
+            };
+            if self.mode == PrefetchMode::Eager {
+                // A decoding error surfaces again when the block is entered.
+                self.prefetch_block(self.block + 1).ok();
+            }
+            self.current = Some(decoded);
+        }
+        Ok(self
+            .current
+            .as_mut()
+            .expect("the current block was decoded above"))
+    }
+}
+
+impl<'index, E: DecodedBy<Decoder = D>, D: Decoder> IndexReader<'index>
+    for DecodeAheadReader<'index, E, D>
+{
+    fn next_record(&mut self, result: &mut RSIndexResult<'index>) -> std::io::Result<bool> {
+        loop {
+            if self.block >= self.ii.blocks.len() {
+                return Ok(false);
+            }
+            let current = self.entered()?;
+            if let Some(record) = current.records.get_mut(current.next) {
+                current.next += 1;
+                std::mem::swap(result, record);
+                return Ok(true);
+            }
+            if self.block + 1 >= self.ii.blocks.len() {
+                return Ok(false);
+            }
+            self.block += 1;
+            self.current = None;
+        }
+    }
+
+    fn seek_record(
+        &mut self,
+        doc_id: t_docId,
+        result: &mut RSIndexResult<'index>,
+    ) -> std::io::Result<bool> {
+        if !self.skip_to(doc_id) {
+            return Ok(false);
+        }
+
+        let current = self.entered()?;
+        current.next += current.records[current.next..].partition_point(|r| r.doc_id < doc_id);
+        let Some(record) = current.records.get_mut(current.next) else {
+            return Ok(false);
+        };
+        current.next += 1;
+        std::mem::swap(result, record);
+        Ok(true)
+    }
+
+    fn skip_to(&mut self, doc_id: t_docId) -> bool {
+        let blocks = &self.ii.blocks;
+        let Some(last) = blocks.last() else {
+            return false;
+        };
+
+        if blocks[self.block].last_doc_id >= doc_id {
+            // We are already in the correct block
+            return true;
+        }
+        if last.last_doc_id < doc_id {
+            return false;
+        }
+
+        let search_start = self.block + 1;
+        self.block =
+            search_start + blocks[search_start..].partition_point(|b| b.last_doc_id < doc_id);
+        self.current = None;
+        true
+    }
+
+    fn reset(&mut self) {
+        self.block = 0;
+        self.current = None;
+        // The blocks might have changed since they were decoded.
+        if self.ahead.take().is_some() {
+            self.stats.wasted += 1;
+        }
+        self.gc_marker = self.ii.gc_marker.load(atomic::Ordering::Relaxed);
+    }
+
+    fn unique_docs(&self) -> u32 {
+        self.ii.unique_docs()
+    }
+
+    fn has_duplicates(&self) -> bool {
+        self.ii.flags() & IndexFlags_Index_HasMultiValue > 0
+    }
+
+    fn flags(&self) -> IndexFlags {
+        self.ii.flags()
+    }
+
+    fn needs_revalidation(&self) -> bool {
+        self.gc_marker != self.ii.gc_marker.load(atomic::Ordering::Relaxed)
+    }
+}
@@ -16,6 +16,8 @@ use std::{
 
 use controlled_cursor::ControlledCursor;
 use debug::{BlockSummary, Summary};
+use decode_ahead::{DecodeAheadReader, PrefetchMode};
+use defrag::{Defrag, DefragStatus, Defragger};
 use ffi::{
     FieldSpec, GeoFilter, IndexFlags, IndexFlags_Index_DocIdsOnly, IndexFlags_Index_HasMultiValue,
     IndexFlags_Index_StoreFieldFlags,
@@ -29,6 +31,7 @@ use smallvec::SmallVec;
 
 pub mod controlled_cursor;
 pub mod debug;
+pub mod decode_ahead;
 pub mod doc_ids_only;
 pub mod fields_offsets;
 pub mod fields_only;
@@ -38,6 +41,7 @@ pub mod freqs_only;
 pub mod full;
 mod index_result;
 pub mod numeric;
+pub mod numeric_index;
 pub mod offsets_only;
 pub mod raw_doc_ids_only;
 #[doc(hidden)]
@@ -303,6 +307,12 @@ pub struct IndexBlock {
 
 static TOTAL_BLOCKS: AtomicUsize = AtomicUsize::new(0);
 
+impl Defrag for IndexBlock {
+    fn defrag(&mut self, defragger: &mut dyn Defragger) -> bool {
+        self.buffer.defrag(defragger)
+    }
+}
+
 /// Custom deserialization for `IndexBlock` to track the total number of blocks correctly.
 impl<'de> Deserialize<'de> for IndexBlock {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -683,6 +693,19 @@ impl<E: Encoder> InvertedIndex<E> {
     pub fn gc_marker_inc(&self) {
         self.gc_marker.fetch_add(1, atomic::Ordering::Relaxed);
     }
+
+    /// Relocate the blocks of the index with `defragger`, starting at block
+    /// `*cursor`. See the [`defrag`] crate for the resumption protocol.
+    ///
+    /// Readers are invalidated through the GC marker if anything moved, so the
+    /// caller must hold the index for writing.
+    pub fn defrag(&mut self, cursor: &mut usize, defragger: &mut dyn Defragger) -> DefragStatus {
+        let (moved, status) = defrag::defrag_items(&mut self.blocks, cursor, defragger);
+        if moved > 0 {
+            self.gc_marker_inc();
+        }
+        status
+    }
 }
 
 /// Result of scanning the index for garbage collection
@@ -705,6 +728,43 @@ impl GcScanDelta {
     pub const fn last_block_idx(&self) -> usize {
         self.last_block_idx
     }
+
+    /// Returns the number of unique documents applying this delta removes from the index.
+    pub fn unique_docs_removed(&self) -> u32 {
+        self.deltas
+            .iter()
+            .map(|d| match d.repair {
+                RepairType::Delete {
+                    n_unique_docs_removed,
+                }
+                | RepairType::Replace {
+                    n_unique_docs_removed,
+                    ..
+                } => n_unique_docs_removed,
+            })
+            .sum()
+    }
+
+    /// Whether the last block of `index` changed since the scan, in which case its delta must be
+    /// ignored.
+    fn last_block_changed<E>(&self, index: &InvertedIndex<E>) -> bool {
+        index
+            .blocks
+            .get(self.last_block_idx)
+            .is_some_and(|b| b.num_entries != self.last_block_num_entries)
+    }
+
+    /// The block deltas applying this delta to `index` won't ignore. Those of the last block are
+    /// ignored if it changed since the scan.
+    fn applied<'delta, E>(
+        &'delta self,
+        index: &InvertedIndex<E>,
+    ) -> impl Iterator<Item = &'delta BlockGcScanResult> {
+        let last_block_changed = self.last_block_changed(index);
+        self.deltas
+            .iter()
+            .filter(move |d| !(last_block_changed && d.index == self.last_block_idx))
+    }
 }
 
 /// Result of scanning a block for garbage collection
@@ -740,6 +800,12 @@ impl<E: Encoder + DecodedBy> InvertedIndex<E> {
         IndexReaderCore::new(self)
     }
 
+    /// Create a [`DecodeAheadReader`] for this inverted index, which decodes a block at a time
+    /// and the next block ahead of its use. Meant for long scans.
+    pub fn decode_ahead_reader(&self, mode: PrefetchMode) -> DecodeAheadReader<'_, E, E::Decoder> {
+        DecodeAheadReader::new(self, mode)
+    }
+
     /// Scan the index for blocks that can be garbage collected. A block can be garbage collected
     /// if any of its records point to documents that no longer exist. The `doc_exist`
     /// callback is used to check if a document exists. It should return `true` if the document
@@ -779,90 +845,46 @@ impl<E: Encoder + DecodedBy> InvertedIndex<E> {
     /// Apply the deltas of a garbage collection scan to the index. This will modify the index
     /// by deleting or repairing blocks as needed.
     pub fn apply_gc(&mut self, delta: GcScanDelta) -> GcApplyInfo {
+        let info = self.gc_info(&delta);
+        if info.blocks_ignored == delta.deltas.len() {
+            // There is no point in moving everything to a new vector if there are no deltas
+            return info;
+        }
+
+        let last_block_changed = delta.last_block_changed(self);
         let GcScanDelta {
             last_block_idx,
-            last_block_num_entries,
             deltas,
+            ..
         } = delta;
-
-        let mut info = GcApplyInfo {
-            bytes_freed: 0,
-            bytes_allocated: 0,
-            entries_removed: 0,
-            blocks_ignored: 0,
-        };
-
-        // Check if the last block has changed since the scan was performed
-        let last_block_changed = self
-            .blocks
-            .get(last_block_idx)
-            .is_some_and(|b| b.num_entries != last_block_num_entries);
-
         // If the last block has changed, then we need to ignore any deltas that refer to it
-        let deltas = if last_block_changed {
-            deltas
-                .into_iter()
-                .filter(|d| {
-                    if d.index == last_block_idx {
-                        // The last block has changed since the scan, so we ignore this delta
-                        info.blocks_ignored += 1;
-                        false
-                    } else {
-                        true
-                    }
-                })
-                .collect()
-        } else {
-            deltas
-        };
-
-        // There is no point in moving everything to a new vector if there are no deltas
-        if deltas.is_empty() {
-            return info;
-        }
+        let mut deltas = deltas
+            .into_iter()
+            .filter(|d| !(last_block_changed && d.index == last_block_idx))
+            .peekable();
 
         let mut tmp_blocks = Vec::with_capacity(self.blocks.len());
         std::mem::swap(&mut self.blocks, &mut tmp_blocks);
 
-        let mut deltas = deltas.into_iter().peekable();
-
         for (block_index, block) in tmp_blocks.into_iter().enumerate() {
-            match deltas.peek() {
-                Some(delta) if delta.index == block_index => {
-                    // This block needs to be repaired
-                    let Some(delta) = deltas.next() else {
-                        unreachable!(
-                            "we are in the `Some` case and therefore know the next value exists"
-                        )
-                    };
-
-                    match delta.repair {
-                        RepairType::Delete {
-                            n_unique_docs_removed,
-                        } => {
-                            info.entries_removed += block.num_entries as usize;
-                            info.bytes_freed += IndexBlock::SIZE + block.buffer.capacity();
-                            self.n_unique_docs -= n_unique_docs_removed;
-                        }
-                        RepairType::Replace {
-                            blocks,
-                            n_unique_docs_removed,
-                        } => {
-                            info.entries_removed += block.num_entries as usize;
-                            info.bytes_freed += IndexBlock::SIZE + block.buffer.capacity();
-                            self.n_unique_docs -= n_unique_docs_removed;
-
-                            for block in blocks {
-                                info.entries_removed -= block.num_entries as usize;
-                                info.bytes_allocated += IndexBlock::SIZE + block.buffer.capacity();
-                                self.blocks.push(block);
-                            }
-                        }
-                    }
+            let Some(delta) = deltas.next_if(|d| d.index == block_index) else {
+                // This block does not need to be repaired, so just put it back
+                self.blocks.push(block);
+                continue;
+            };
+
+            match delta.repair {
+                RepairType::Delete {
+                    n_unique_docs_removed,
+                } => {
+                    self.n_unique_docs -= n_unique_docs_removed;
                 }
-                _ => {
-                    // This block does not need to be repaired, so just put it back
-                    self.blocks.push(block);
+                RepairType::Replace {
+                    blocks,
+                    n_unique_docs_removed,
+                } => {
+                    self.n_unique_docs -= n_unique_docs_removed;
+                    self.blocks.extend(blocks);
                 }
             }
         }
@@ -872,6 +894,41 @@ impl<E: Encoder + DecodedBy> InvertedIndex<E> {
 
         info
     }
+
+    /// Report what [`Self::apply_gc`] would do with `delta`, without modifying the index. This is
+    /// the dry run of the garbage collector.
+    pub fn preview_gc(&self, delta: &GcScanDelta) -> GcApplyInfo {
+        self.gc_info(delta)
+    }
+
+    /// The accounting of applying `delta` to the index, shared by [`Self::apply_gc`] and
+    /// [`Self::preview_gc`].
+    fn gc_info(&self, delta: &GcScanDelta) -> GcApplyInfo {
+        let mut info = GcApplyInfo {
+            bytes_freed: 0,
+            bytes_allocated: 0,
+            entries_removed: 0,
+            blocks_ignored: delta.deltas.len(),
+        };
+
+        for d in delta.applied(self) {
+            info.blocks_ignored -= 1;
+            let Some(block) = self.blocks.get(d.index) else {
+                continue;
+            };
+            info.entries_removed += block.num_entries as usize;
+            info.bytes_freed += IndexBlock::SIZE + block.buffer.capacity();
+
+            if let RepairType::Replace { blocks, .. } = &d.repair {
+                for block in blocks {
+                    info.entries_removed -= block.num_entries as usize;
+                    info.bytes_allocated += IndexBlock::SIZE + block.buffer.capacity();
+                }
+            }
+        }
+
+        info
+    }
 }
 
 /// A wrapper around the inverted index to track the total number of entries in the index.
@@ -1009,6 +1066,17 @@ impl<E: Encoder + DecodedBy> EntriesTrackingIndex<E> {
 
         info
     }
+
+    /// Report what [`Self::apply_gc`] would do with `delta`, without modifying the index.
+    pub fn preview_gc(&self, delta: &GcScanDelta) -> GcApplyInfo {
+        self.index.preview_gc(delta)
+    }
+
+    /// Relocate the blocks of the index with `defragger`. See
+    /// [`InvertedIndex::defrag`].
+    pub fn defrag(&mut self, cursor: &mut usize, defragger: &mut dyn Defragger) -> DefragStatus {
+        self.index.defrag(cursor, defragger)
+    }
 }
 
 /// A wrapper around the inverted index which tracks the fields for all the records in the index
@@ -1137,6 +1205,17 @@ impl<E: Encoder + DecodedBy> FieldMaskTrackingIndex<E> {
     pub fn apply_gc(&mut self, delta: GcScanDelta) -> GcApplyInfo {
         self.index.apply_gc(delta)
     }
+
+    /// Report what [`Self::apply_gc`] would do with `delta`, without modifying the index.
+    pub fn preview_gc(&self, delta: &GcScanDelta) -> GcApplyInfo {
+        self.index.preview_gc(delta)
+    }
+
+    /// Relocate the blocks of the index with `defragger`. See
+    /// [`InvertedIndex::defrag`].
+    pub fn defrag(&mut self, cursor: &mut usize, defragger: &mut dyn Defragger) -> DefragStatus {
+        self.index.defrag(cursor, defragger)
+    }
 }
 
 /// Reader that is able to read the records from an [`InvertedIndex`]
@@ -0,0 +1,515 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! A numeric inverted index which keeps the minimum and maximum value of each
+//! block, so that range queries skip the blocks they can't match without
+//! decoding them.
+//!
+//! The blocks are ordered by document ID, not by value, so their value ranges
+//! can't be searched directly. The index keeps two views of the ranges, one
+//! sorted by minimum and one by maximum. The blocks whose minimum isn't above
+//! the upper bound of a filter are a prefix of the first, found with
+//! [`partition_gt`]; those whose maximum isn't below its lower bound are a
+//! suffix of the second, found with [`partition_ge`]. The smaller of the two is
+//! then checked against the other bound.
+//!
+//! The sorted views are built on the first query after the index changed.
+//! Writes and queries are expected to come in batches, as the range tree
+//! indexes a whole document before it's queried.
+
+use std::{cmp::Ordering, io::Cursor, sync::OnceLock, sync::atomic};
+
+use bsearch::{partition_ge, partition_gt};
+use ffi::{IndexFlags, IndexFlags_Index_HasMultiValue, t_docId};
+
+use defrag::{DefragStatus, Defragger};
+
+use crate::{
+    Decoder, EntriesTrackingIndex, GcApplyInfo, GcScanDelta, IndexBlock, IndexReader,
+    IndexReaderCore, NumericFilter, NumericReader, RSIndexResult, RepairType,
+    debug::{BlockSummary, Summary},
+    numeric::Numeric,
+};
+
+/// The smallest and largest value of the records of a block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl BlockRange {
+    const fn of(value: f64) -> Self {
+        Self {
+            min: value,
+            max: value,
+        }
+    }
+
+    const fn add(&mut self, value: f64) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Whether the block may hold values matched by `filter`.
+    pub fn overlaps(&self, filter: &NumericFilter) -> bool {
+        let above_min = self.max > filter.min || (filter.min_inclusive && self.max == filter.min);
+        let below_max = self.min < filter.max || (filter.max_inclusive && self.min == filter.max);
+        above_min && below_max
+    }
+}
+
+/// How many blocks a range query skipped, for the profile.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruningStats {
+    /// Blocks excluded by their value range, never decoded.
+    pub blocks_skipped: u64,
+    /// Blocks decoded, whether or not they held matching values.
+    pub blocks_decoded: u64,
+}
+
+impl PruningStats {
+    /// The fields of the reader in the profile.
+    pub const fn profile(&self) -> [(&'static str, u64); 2] {
+        [
+            ("Blocks skipped", self.blocks_skipped),
+            ("Blocks decoded", self.blocks_decoded),
+        ]
+    }
+}
+
+/// The block ranges sorted by minimum and by maximum, with the index of their
+/// block.
+#[derive(Debug)]
+struct SortedRanges {
+    by_min: Vec<(f64, usize)>,
+    by_max: Vec<(f64, usize)>,
+}
+
+impl SortedRanges {
+    fn new(ranges: &[BlockRange]) -> Self {
+        let mut by_min: Vec<_> = ranges.iter().enumerate().map(|(i, r)| (r.min, i)).collect();
+        let mut by_max: Vec<_> = ranges.iter().enumerate().map(|(i, r)| (r.max, i)).collect();
+        by_min.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+        by_max.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+        Self { by_min, by_max }
+    }
+}
+
+/// Compare a value of a sorted view with a bound, `-0.0` being equal to
+/// `0.0` as in [`NumericFilter::value_in_range`].
+fn cmp_value(entry: &(f64, usize), bound: &f64) -> Ordering {
+    if entry.0 == *bound {
+        Ordering::Equal
+    } else {
+        entry.0.total_cmp(bound)
+    }
+}
+
+/// A numeric inverted index with the value range of each block. See the
+/// [module documentation](self).
+pub struct NumericIndex {
+    index: EntriesTrackingIndex<Numeric>,
+    /// The range of each block, in block order.
+    ranges: Vec<BlockRange>,
+    /// Built on demand, reset on every change.
+    sorted: OnceLock<SortedRanges>,
+}
+
+impl NumericIndex {
+    pub const fn new(flags: IndexFlags, encoder: Numeric) -> Self {
+        Self {
+            index: EntriesTrackingIndex::new(flags, encoder),
+            ranges: Vec::new(),
+            sorted: OnceLock::new(),
+        }
+    }
+
+    /// Add a numeric record to the index and return by how much memory grew.
+    /// See [`InvertedIndex::add_record`](crate::InvertedIndex::add_record).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `record` isn't numeric.
+    pub fn add_record(&mut self, record: &RSIndexResult) -> std::io::Result<usize> {
+        let value = record
+            .as_numeric()
+            .expect("numeric index will only be given numeric records");
+        let blocks = self.index.number_of_blocks();
+        let entries = self.index.number_of_entries();
+        let mut mem_growth = self.index.add_record(record)?;
+        if self.index.number_of_entries() == entries {
+            return Ok(mem_growth);
+        }
+
+        if self.index.number_of_blocks() > blocks {
+            if self.ranges.len() == self.ranges.capacity() {
+                mem_growth += std::mem::size_of::<BlockRange>();
+            }
+            self.ranges.push(BlockRange::of(value));
+        } else {
+            self.ranges
+                .last_mut()
+                .expect("a block was written to")
+                .add(value);
+        }
+        self.sorted.take();
+        Ok(mem_growth)
+    }
+
+    /// The underlying inverted index.
+    pub const fn inner(&self) -> &EntriesTrackingIndex<Numeric> {
+        &self.index
+    }
+
+    /// The value range of each block, in block order.
+    pub fn block_ranges(&self) -> &[BlockRange] {
+        &self.ranges
+    }
+
+    /// The memory size of the index in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.index.memory_usage() + self.ranges.capacity() * std::mem::size_of::<BlockRange>()
+    }
+
+    /// The total number of entries in the index, including duplicates.
+    pub const fn number_of_entries(&self) -> usize {
+        self.index.number_of_entries()
+    }
+
+    /// Returns the last document ID in the index, if any.
+    pub fn last_doc_id(&self) -> Option<t_docId> {
+        self.index.last_doc_id()
+    }
+
+    /// Returns the number of unique documents in the index.
+    pub const fn unique_docs(&self) -> u32 {
+        self.index.unique_docs()
+    }
+
+    /// Returns the flags of this index.
+    pub const fn flags(&self) -> IndexFlags {
+        self.index.flags()
+    }
+
+    /// Return the debug summary for this inverted index.
+    pub fn summary(&self) -> Summary {
+        self.index.summary()
+    }
+
+    /// Return basic information about the blocks in this inverted index.
+    pub fn blocks_summary(&self) -> Vec<BlockSummary> {
+        self.index.blocks_summary()
+    }
+
+    /// Returns the number of blocks in this index.
+    pub const fn number_of_blocks(&self) -> usize {
+        self.index.number_of_blocks()
+    }
+
+    /// Get a reference to the block at the given index, if it exists. This is only used by some C tests.
+    pub fn block_ref(&self, index: usize) -> Option<&IndexBlock> {
+        self.index.block_ref(index)
+    }
+
+    /// Get the current GC marker of this index. This is only used by the some C tests.
+    pub fn gc_marker(&self) -> u32 {
+        self.index.gc_marker()
+    }
+
+    /// Increment the GC marker of this index. This is only used by the some C tests.
+    pub fn gc_marker_inc(&self) {
+        self.index.gc_marker_inc();
+    }
+
+    /// Create a reader of all the records of the index, without pruning.
+    pub fn reader(&self) -> IndexReaderCore<'_, Numeric, Numeric> {
+        self.index.reader()
+    }
+
+    /// Scan the index for blocks that can be garbage collected. See
+    /// [`InvertedIndex::scan_gc`](crate::InvertedIndex::scan_gc).
+    pub fn scan_gc<'index>(
+        &'index self,
+        doc_exist: impl Fn(t_docId) -> bool,
+        repair: Option<impl FnMut(&RSIndexResult<'index>, &IndexBlock)>,
+    ) -> std::io::Result<Option<GcScanDelta>> {
+        self.index.scan_gc(doc_exist, repair)
+    }
+
+    /// Apply the deltas of a garbage collection scan, then recompute the
+    /// ranges of the blocks it repaired, which may have lost their extreme
+    /// values. The ranges of the other blocks are kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a repaired block doesn't decode, which can't happen for the
+    /// blocks written by this index.
+    pub fn apply_gc(&mut self, delta: GcScanDelta) -> GcApplyInfo {
+        // The blocks the delta applies to, with how many blocks replace them.
+        let repaired: Vec<(usize, usize)> = delta
+            .applied(self.index.inner())
+            .map(|d| match &d.repair {
+                RepairType::Delete { .. } => (d.index, 0),
+                RepairType::Replace { blocks, .. } => (d.index, blocks.len()),
+            })
+            .collect();
+
+        let info = self.index.apply_gc(delta);
+        if repaired.is_empty() {
+            return info;
+        }
+
+        let blocks = &self.index.inner().blocks;
+        let mut ranges = Vec::with_capacity(blocks.len());
+        let mut repaired = repaired.into_iter().peekable();
+        for (index, range) in self.ranges.iter().enumerate() {
+            match repaired.next_if(|&(block, _)| block == index) {
+                Some((_, replacements)) => {
+                    let start = ranges.len();
+                    for block in &blocks[start..start + replacements] {
+                        ranges.push(block_range(block).expect("the index wrote the block"));
+                    }
+                }
+                None => ranges.push(*range),
+            }
+        }
+        debug_assert_eq!(ranges.len(), blocks.len());
+
+        self.ranges = ranges;
+        self.sorted.take();
+        info
+    }
+
+    /// Report what [`Self::apply_gc`] would do with `delta`, without modifying the index.
+    pub fn preview_gc(&self, delta: &GcScanDelta) -> GcApplyInfo {
+        self.index.preview_gc(delta)
+    }
+
+    /// Relocate the blocks of the index with `defragger`. See
+    /// [`InvertedIndex::defrag`](crate::InvertedIndex::defrag).
+    pub fn defrag(&mut self, cursor: &mut usize, defragger: &mut dyn Defragger) -> DefragStatus {
+        self.index.defrag(cursor, defragger)
+    }
+
+    /// The blocks which may hold values matched by `filter`, in block order.
+    pub fn candidate_blocks(&self, filter: &NumericFilter) -> Vec<usize> {
+        let sorted = self.sorted.get_or_init(|| SortedRanges::new(&self.ranges));
+
+        // The blocks whose minimum isn't above the upper bound.
+        let below = partition_gt(&sorted.by_min, &filter.max, cmp_value);
+        // The blocks whose maximum isn't below the lower bound.
+        let above = partition_ge(&sorted.by_max, &filter.min, cmp_value);
+
+        let smaller = if below <= sorted.by_max.len() - above {
+            &sorted.by_min[..below]
+        } else {
+            &sorted.by_max[above..]
+        };
+        let mut blocks: Vec<usize> = smaller
+            .iter()
+            .map(|&(_, block)| block)
+            .filter(|&block| self.ranges[block].overlaps(filter))
+            .collect();
+        blocks.sort_unstable();
+        blocks
+    }
+
+    /// A reader of the records matched by `filter`, which only decodes the
+    /// [candidate blocks](Self::candidate_blocks).
+    ///
+    /// `filter` must be a numeric filter, not a geo filter.
+    pub fn range_reader<'index>(
+        &'index self,
+        filter: &'index NumericFilter,
+    ) -> NumericRangeReader<'index> {
+        debug_assert!(filter.is_numeric_filter(), "geo filters can't be pruned");
+        let candidates = self.candidate_blocks(filter);
+        NumericRangeReader {
+            stats: PruningStats {
+                blocks_skipped: (self.ranges.len() - candidates.len()) as u64,
+                blocks_decoded: 0,
+            },
+            ni: self,
+            filter,
+            decoder: Numeric::new(),
+            candidates,
+            next_candidate: 0,
+            cursor: None,
+            last_doc_id: 0,
+            gc_marker: self.index.inner().gc_marker.load(atomic::Ordering::Relaxed),
+        }
+    }
+}
+
+/// The value range of the records of `block`.
+fn block_range(block: &IndexBlock) -> std::io::Result<BlockRange> {
+    let decoder = Numeric::new();
+    let mut cursor = Cursor::new(block.buffer.as_slice());
+    let mut result = Numeric::base_result();
+    let mut range: Option<BlockRange> = None;
+    let mut last_doc_id = block.first_doc_id;
+    while (cursor.position() as usize) < block.buffer.len() {
+        decoder.decode(
+            &mut cursor,
+            Numeric::base_id(block, last_doc_id),
+            &mut result,
+        )?;
+        last_doc_id = result.doc_id;
+        let value = result.as_numeric().expect("numeric records");
+        match &mut range {
+            Some(range) => range.add(value),
+            None => range = Some(BlockRange::of(value)),
+        }
+    }
+    Ok(range.unwrap_or(BlockRange::of(0.0)))
+}
+
+/// Reader of the records of a [`NumericIndex`] matched by a filter, skipping
+/// the blocks which can't match.
+pub struct NumericRangeReader<'index> {
+    ni: &'index NumericIndex,
+    filter: &'index NumericFilter,
+    decoder: Numeric,
+    /// The blocks to decode, in block order.
+    candidates: Vec<usize>,
+    /// The position in `candidates` of the block after the current one.
+    next_candidate: usize,
+    /// The cursor in the current block, if any.
+    cursor: Option<(&'index IndexBlock, Cursor<&'index [u8]>)>,
+    last_doc_id: t_docId,
+    /// The marker of the inverted index when this reader last read from it.
+    gc_marker: u32,
+    stats: PruningStats,
+}
+
+impl<'index> NumericRangeReader<'index> {
+    /// The blocks skipped and decoded so far.
+    pub const fn stats(&self) -> PruningStats {
+        self.stats
+    }
+
+    /// The filter this reader matches records with.
+    pub const fn filter(&self) -> &NumericFilter {
+        self.filter
+    }
+
+    /// Check if the reader is reading from the given index.
+    pub fn is_index(&self, index: &NumericIndex) -> bool {
+        std::ptr::eq(self.ni, index)
+    }
+
+    /// Swap the index of the reader with the supplied index. This is only used by the C tests to
+    /// trigger a revalidation.
+    pub const fn swap_index(&mut self, index: &mut &'index NumericIndex) {
+        std::mem::swap(&mut self.ni, index);
+    }
+}
+
+impl<'index> IndexReader<'index> for NumericRangeReader<'index> {
+    fn next_record(&mut self, result: &mut RSIndexResult<'index>) -> std::io::Result<bool> {
+        let ni = self.ni;
+        loop {
+            let exhausted = self
+                .cursor
+                .as_ref()
+                .is_none_or(|(block, cursor)| cursor.position() as usize >= block.buffer.len());
+            if exhausted {
+                let Some(&index) = self.candidates.get(self.next_candidate) else {
+                    self.cursor = None;
+                    return Ok(false);
+                };
+                self.next_candidate += 1;
+                self.stats.blocks_decoded += 1;
+                let block = &ni.index.inner().blocks[index];
+                self.last_doc_id = block.first_doc_id;
+                self.cursor = Some((block, Cursor::new(block.buffer.as_slice())));
+            }
+
+            let (block, cursor) = self.cursor.as_mut().expect("a block was entered above");
+            let base = Numeric::base_id(block, self.last_doc_id);
+            self.decoder.decode(cursor, base, result)?;
+            self.last_doc_id = result.doc_id;
+            if result
+                .as_numeric()
+                .is_some_and(|value| self.filter.value_in_range(value))
+            {
+                return Ok(true);
+            }
+        }
+    }
+
+    fn seek_record(
+        &mut self,
+        doc_id: t_docId,
+        result: &mut RSIndexResult<'index>,
+    ) -> std::io::Result<bool> {
+        if !self.skip_to(doc_id) {
+            return Ok(false);
+        }
+        while self.next_record(result)? {
+            if result.doc_id >= doc_id {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn skip_to(&mut self, doc_id: t_docId) -> bool {
+        let blocks = &self.ni.index.inner().blocks;
+        if let Some((block, _)) = &self.cursor
+            && block.last_doc_id >= doc_id
+        {
+            // We are already in the correct block
+            return true;
+        }
+        let skipped = self.candidates[self.next_candidate..]
+            .partition_point(|&index| blocks[index].last_doc_id < doc_id);
+        self.next_candidate += skipped;
+        self.cursor = None;
+        self.next_candidate < self.candidates.len()
+    }
+
+    fn reset(&mut self) {
+        // The blocks might have changed since the candidates were found.
+        self.candidates = self.ni.candidate_blocks(self.filter);
+        self.next_candidate = 0;
+        self.cursor = None;
+        self.gc_marker = self
+            .ni
+            .index
+            .inner()
+            .gc_marker
+            .load(atomic::Ordering::Relaxed);
+    }
+
+    fn unique_docs(&self) -> u32 {
+        self.ni.index.unique_docs()
+    }
+
+    fn has_duplicates(&self) -> bool {
+        self.ni.index.flags() & IndexFlags_Index_HasMultiValue > 0
+    }
+
+    fn flags(&self) -> IndexFlags {
+        self.ni.index.flags()
+    }
+
+    fn needs_revalidation(&self) -> bool {
+        self.gc_marker
+            != self
+                .ni
+                .index
+                .inner()
+                .gc_marker
+                .load(atomic::Ordering::Relaxed)
+    }
+}
+
+impl<'index> NumericReader<'index> for NumericRangeReader<'index> {}
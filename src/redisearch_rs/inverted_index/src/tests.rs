@@ -1802,10 +1802,17 @@ fn ii_apply_gc() {
     };
 
     assert_eq!(ii.gc_marker(), 0);
+    assert_eq!(delta.unique_docs_removed(), 5);
+
+    // A dry run reports the same as the actual run, without touching the index
+    let preview = ii.preview_gc(&delta);
+    assert_eq!(ii.gc_marker(), 0);
+    assert_eq!(ii.memory_usage(), 280);
 
     let apply_info = ii.apply_gc(delta);
 
     assert_eq!(ii.gc_marker(), 1);
+    assert_eq!(apply_info, preview);
 
     // Inverted index is 40 bytes base
     // 1st index block is 40 bytes + 16 bytes for the buffer capacity
@@ -1916,9 +1923,11 @@ fn ii_apply_gc_last_block_updated() {
 
     assert_eq!(ii.gc_marker(), 0);
 
+    let preview = ii.preview_gc(&delta);
     let apply_info = ii.apply_gc(delta);
 
     assert_eq!(ii.gc_marker(), 1);
+    assert_eq!(apply_info, preview);
 
     // Inverted index is 40 bytes base
     // 1st index block is 40 bytes + 24 bytes for the buffer capacity
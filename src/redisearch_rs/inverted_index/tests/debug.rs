@@ -0,0 +1,204 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use ffi::{IndexFlags_Index_DocIdsOnly, IndexFlags_Index_StoreNumeric, t_docId};
+use inverted_index::{
+    EntriesTrackingIndex, InvertedIndex, RSIndexResult,
+    debug::{
+        DebugReply, NumericNodeDump, NumericRangeDump, NumericTreeDump, doc_ids_reply,
+        numeric_entries, summary_reply,
+    },
+    doc_ids_only::DocIdsOnly,
+    numeric::Numeric,
+};
+use pretty_assertions::assert_eq;
+
+mod c_mocks;
+
+fn s(s: &str) -> DebugReply {
+    DebugReply::String(s.to_owned())
+}
+
+const fn i(i: i64) -> DebugReply {
+    DebugReply::Integer(i)
+}
+
+#[test]
+fn test_term_dumps() {
+    let mut ii = InvertedIndex::new(IndexFlags_Index_DocIdsOnly, DocIdsOnly);
+    for id in [3, 7, 12] {
+        ii.add_record(&RSIndexResult::default().doc_id(id)).unwrap();
+    }
+
+    assert_eq!(
+        doc_ids_reply(&mut ii.reader()).unwrap(),
+        DebugReply::Array(vec![i(3), i(7), i(12)])
+    );
+    assert_eq!(
+        summary_reply(&ii.summary(), &ii.blocks_summary()),
+        DebugReply::Array(vec![
+            s("numDocs"),
+            i(3),
+            s("numEntries"),
+            i(3),
+            s("lastId"),
+            i(12),
+            s("flags"),
+            i(IndexFlags_Index_DocIdsOnly as i64),
+            s("numberOfBlocks"),
+            i(1),
+            s("blocks"),
+            DebugReply::Array(vec![
+                s("firstId"),
+                i(3),
+                s("lastId"),
+                i(12),
+                s("numEntries"),
+                i(3)
+            ]),
+        ])
+    );
+}
+
+fn numeric_range(values: &[(t_docId, f64)]) -> NumericRangeDump {
+    let mut ii = EntriesTrackingIndex::new(IndexFlags_Index_StoreNumeric, Numeric::new());
+    for &(id, value) in values {
+        ii.add_record(&RSIndexResult::numeric(value).doc_id(id))
+            .unwrap();
+    }
+    NumericRangeDump {
+        min_value: values.iter().map(|v| v.1).fold(f64::INFINITY, f64::min),
+        max_value: values.iter().map(|v| v.1).fold(f64::NEG_INFINITY, f64::max),
+        inverted_index_size: ii.memory_usage(),
+        cardinality: values.len(),
+        summary: ii.summary(),
+        entries: numeric_entries(&mut ii.reader()).unwrap(),
+    }
+}
+
+#[test]
+fn test_numeric_tree_dump() {
+    let left = numeric_range(&[(1, 1.0), (2, 2.0)]);
+    assert_eq!(left.entries, [(1.0, 1), (2.0, 2)]);
+    assert_eq!(left.summary.block_efficiency, 2.0);
+    let right = numeric_range(&[(3, 5.0)]);
+    let tree = NumericTreeDump {
+        num_ranges: 2,
+        num_leaves: 2,
+        num_entries: 3,
+        last_doc_id: 3,
+        revision_id: 1,
+        unique_id: 7,
+        empty_leaves: 0,
+        memory_usage: 100,
+        root: Some(NumericNodeDump::Split {
+            value: 3.0,
+            max_depth: 1,
+            range: None,
+            left: Box::new(NumericNodeDump::Leaf { range: Some(left) }),
+            right: Box::new(NumericNodeDump::Leaf {
+                range: Some(right.clone()),
+            }),
+        }),
+    };
+
+    let DebugReply::Map(reply) = tree.reply(false) else {
+        panic!("the tree is dumped as a map");
+    };
+    let names: Vec<_> = reply.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "numRanges",
+            "numEntries",
+            "lastDocId",
+            "revisionId",
+            "uniqueId",
+            "emptyLeaves",
+            "root",
+            "Tree stats"
+        ]
+    );
+    // (2 + 1) / 2 ranges.
+    assert_eq!(
+        reply[7].1,
+        DebugReply::Map(vec![(
+            "Average memory efficiency (numEntries/size)/numRanges".to_owned(),
+            DebugReply::Double(1.5)
+        )])
+    );
+    let DebugReply::Map(root) = &reply[6].1 else {
+        panic!("nodes are dumped as maps");
+    };
+    assert_eq!(root[0], ("value".to_owned(), DebugReply::Double(3.0)));
+    assert_eq!(root[1], ("maxDepth".to_owned(), i(1)));
+    assert_eq!(
+        root[3],
+        (
+            "right".to_owned(),
+            DebugReply::Map(vec![("range".to_owned(), right.reply())])
+        )
+    );
+    let DebugReply::Array(range) = right.reply() else {
+        panic!("ranges are dumped as arrays");
+    };
+    assert_eq!(range[..2], [s("minVal"), DebugReply::Double(5.0)]);
+    let DebugReply::Array(entries) = &range[9] else {
+        panic!("entries are dumped as an array");
+    };
+    assert_eq!(
+        entries[10..],
+        [
+            s("values"),
+            DebugReply::Array(vec![s("value"), DebugReply::Double(5.0), s("docId"), i(3)])
+        ]
+    );
+
+    // The minimal dump keeps the shape of the tree, but not the ranges.
+    let DebugReply::Map(minimal) = tree.reply(true) else {
+        panic!("the tree is dumped as a map");
+    };
+    let DebugReply::Map(root) = &minimal[6].1 else {
+        panic!("nodes are dumped as maps");
+    };
+    assert_eq!(
+        root[2].1,
+        DebugReply::Map(vec![("range".to_owned(), DebugReply::Array(Vec::new()))])
+    );
+
+    assert_eq!(
+        tree.summary_reply(),
+        DebugReply::Array(vec![
+            s("numRanges"),
+            i(2),
+            s("numLeaves"),
+            i(2),
+            s("numEntries"),
+            i(3),
+            s("lastDocId"),
+            i(3),
+            s("revisionId"),
+            i(1),
+            s("emptyLeaves"),
+            i(0),
+            s("RootMaxDepth"),
+            i(1),
+            s("MemoryUsage"),
+            i(100)
+        ])
+    );
+}
+
+#[test]
+fn test_uninitialized_numeric_tree() {
+    let DebugReply::Map(reply) = NumericTreeDump::default().reply(false) else {
+        panic!("the tree is dumped as a map");
+    };
+    assert_eq!(reply[6], ("root".to_owned(), DebugReply::Map(Vec::new())));
+}
@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use ffi::IndexFlags_Index_DocIdsOnly;
+use inverted_index::{
+    IndexReader, InvertedIndex, RSIndexResult,
+    decode_ahead::{PrefetchMode, PrefetchStats},
+    doc_ids_only::DocIdsOnly,
+};
+
+mod c_mocks;
+
+/// An index of the ids `0..10_000`, in 10 blocks.
+fn index() -> InvertedIndex<DocIdsOnly> {
+    let mut ii = InvertedIndex::new(IndexFlags_Index_DocIdsOnly, DocIdsOnly);
+    for id in 0..10_000 {
+        ii.add_record(&RSIndexResult::default().doc_id(id)).unwrap();
+    }
+    assert_eq!(ii.number_of_blocks(), 10);
+    ii
+}
+
+#[test]
+fn test_scan() {
+    let ii = index();
+    let mut reader = ii.decode_ahead_reader(PrefetchMode::Eager);
+    let mut result = RSIndexResult::default();
+
+    for expected_id in 0..10_000 {
+        assert!(reader.next_record(&mut result).unwrap());
+        assert_eq!(result.doc_id, expected_id);
+    }
+    assert!(!reader.next_record(&mut result).unwrap(), "no more records");
+
+    // Only the first block wasn't decoded ahead.
+    let stats = reader.stats();
+    assert_eq!(
+        stats,
+        PrefetchStats {
+            hits: 9,
+            misses: 1,
+            wasted: 0
+        }
+    );
+    assert_eq!(stats.hit_rate(), 90.0);
+    assert_eq!(stats.profile()[3], ("Prefetch hit rate", 90.0));
+}
+
+#[test]
+fn test_seek() {
+    let ii = index();
+    let mut reader = ii.decode_ahead_reader(PrefetchMode::Eager);
+    let mut result = RSIndexResult::default();
+
+    assert!(reader.seek_record(1_500, &mut result).unwrap());
+    assert_eq!(result.doc_id, 1_500);
+    assert!(reader.next_record(&mut result).unwrap());
+    assert_eq!(result.doc_id, 1_501);
+
+    // Skipping over the block decoded ahead wastes it.
+    assert!(reader.seek_record(5_000, &mut result).unwrap());
+    assert_eq!(result.doc_id, 5_000);
+    assert!(!reader.seek_record(10_000, &mut result).unwrap());
+
+    assert_eq!(
+        reader.stats(),
+        PrefetchStats {
+            hits: 0,
+            misses: 2,
+            wasted: 1
+        }
+    );
+
+    reader.reset();
+    assert!(reader.next_record(&mut result).unwrap());
+    assert_eq!(result.doc_id, 0);
+}
+
+#[test]
+fn test_manual_prefetch() {
+    let ii = index();
+    let mut reader = ii.decode_ahead_reader(PrefetchMode::Manual);
+    let mut result = RSIndexResult::default();
+
+    reader.prefetch().unwrap();
+    for expected_id in 0..2_000 {
+        if expected_id == 500 {
+            reader.prefetch().unwrap();
+        }
+        assert!(reader.next_record(&mut result).unwrap());
+        assert_eq!(result.doc_id, expected_id);
+    }
+    assert!(reader.next_record(&mut result).unwrap());
+    assert_eq!(result.doc_id, 2_000);
+
+    assert_eq!(
+        reader.stats(),
+        PrefetchStats {
+            hits: 2,
+            misses: 1,
+            wasted: 0
+        }
+    );
+}
+
+#[test]
+fn test_empty_index() {
+    let ii = InvertedIndex::new(IndexFlags_Index_DocIdsOnly, DocIdsOnly);
+    let mut reader = ii.decode_ahead_reader(PrefetchMode::Eager);
+    let mut result = RSIndexResult::default();
+
+    assert!(!reader.next_record(&mut result).unwrap());
+    assert!(!reader.seek_record(1, &mut result).unwrap());
+    reader.prefetch().unwrap();
+    assert_eq!(reader.stats(), PrefetchStats::default());
+    assert_eq!(reader.stats().hit_rate(), 0.0);
+}
@@ -0,0 +1,184 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use ffi::IndexFlags_Index_StoreNumeric;
+use inverted_index::{
+    IndexBlock, IndexReader, NumericFilter, RSIndexResult,
+    numeric::Numeric,
+    numeric_index::{BlockRange, NumericIndex, PruningStats},
+};
+
+mod c_mocks;
+
+/// An index of the ids `0..1_000`, in 10 blocks, whose value is `id / 10`:
+/// block `i` holds the values `10 * i..10 * i + 10`.
+fn index() -> NumericIndex {
+    let mut ni = NumericIndex::new(IndexFlags_Index_StoreNumeric, Numeric::new());
+    for id in 0..1_000 {
+        ni.add_record(&RSIndexResult::numeric((id / 10) as f64).doc_id(id))
+            .unwrap();
+    }
+    assert_eq!(ni.inner().number_of_blocks(), 10);
+    ni
+}
+
+fn filter(min: f64, max: f64) -> NumericFilter {
+    NumericFilter {
+        min,
+        max,
+        ..Default::default()
+    }
+}
+
+fn read_all(ni: &NumericIndex, filter: &NumericFilter) -> (Vec<u64>, PruningStats) {
+    let mut reader = ni.range_reader(filter);
+    let mut result = RSIndexResult::numeric(0.0);
+    let mut ids = Vec::new();
+    while reader.next_record(&mut result).unwrap() {
+        ids.push(result.doc_id);
+    }
+    (ids, reader.stats())
+}
+
+#[test]
+fn test_block_ranges() {
+    let ni = index();
+    assert_eq!(
+        ni.block_ranges()[3],
+        BlockRange {
+            min: 30.0,
+            max: 39.0
+        }
+    );
+}
+
+#[test]
+fn test_skips_blocks_outside_the_range() {
+    let ni = index();
+    assert_eq!(ni.candidate_blocks(&filter(25.0, 41.0)), [2, 3, 4]);
+
+    let (ids, stats) = read_all(&ni, &filter(25.0, 41.0));
+    assert_eq!(ids, (250..420).collect::<Vec<_>>());
+    assert_eq!(
+        stats,
+        PruningStats {
+            blocks_skipped: 7,
+            blocks_decoded: 3,
+        }
+    );
+
+    let (ids, stats) = read_all(&ni, &filter(1_000.0, 2_000.0));
+    assert!(ids.is_empty());
+    assert_eq!(stats.blocks_decoded, 0);
+}
+
+#[test]
+fn test_exclusive_bounds() {
+    let ni = index();
+    let exclusive = NumericFilter {
+        min: 39.0,
+        max: 50.0,
+        min_inclusive: false,
+        max_inclusive: false,
+        ..Default::default()
+    };
+    // Block 3 ends at 39 and block 5 starts at 50.
+    assert_eq!(ni.candidate_blocks(&exclusive), [4]);
+}
+
+#[test]
+fn test_descending_values() {
+    let mut ni = NumericIndex::new(IndexFlags_Index_StoreNumeric, Numeric::new());
+    for id in 0..1_000u64 {
+        ni.add_record(&RSIndexResult::numeric((1_000 - id) as f64).doc_id(id))
+            .unwrap();
+    }
+    let (ids, stats) = read_all(&ni, &filter(0.0, 10.0));
+    assert_eq!(ids, (990..1_000).collect::<Vec<_>>());
+    assert_eq!(stats.blocks_decoded, 1);
+}
+
+#[test]
+fn test_interleaved_values() {
+    let mut ni = NumericIndex::new(IndexFlags_Index_StoreNumeric, Numeric::new());
+    // Every block holds both negative and positive values, so none can be
+    // skipped.
+    for id in 0..1_000u64 {
+        let value = if id % 2 == 0 { id as f64 } else { -(id as f64) };
+        ni.add_record(&RSIndexResult::numeric(value).doc_id(id))
+            .unwrap();
+    }
+    let (ids, stats) = read_all(&ni, &filter(-5.0, 4.0));
+    assert_eq!(ids, [0, 1, 2, 3, 4, 5]);
+    assert_eq!(stats.blocks_skipped, 0);
+}
+
+#[test]
+fn test_seek() {
+    let ni = index();
+    let filter = filter(25.0, 61.0);
+    let mut reader = ni.range_reader(&filter);
+    let mut result = RSIndexResult::numeric(0.0);
+
+    assert!(reader.seek_record(500, &mut result).unwrap());
+    assert_eq!(result.doc_id, 500);
+    // Blocks 2 to 4 were skipped over without being decoded.
+    assert_eq!(reader.stats().blocks_decoded, 1);
+    assert!(reader.seek_record(615, &mut result).unwrap());
+    assert_eq!(result.doc_id, 615);
+    assert!(!reader.seek_record(620, &mut result).unwrap());
+
+    reader.reset();
+    assert!(reader.next_record(&mut result).unwrap());
+    assert_eq!(result.doc_id, 250);
+}
+
+#[test]
+fn test_apply_gc() {
+    let mut ni = index();
+    // Block 2 loses all its records, block 3 those of the values 30 to 35.
+    let delta = ni
+        .scan_gc(
+            |id| !(200..360).contains(&id),
+            None::<fn(&RSIndexResult, &IndexBlock)>,
+        )
+        .unwrap()
+        .unwrap();
+    let info = ni.apply_gc(delta);
+    assert_eq!(info.entries_removed, 160);
+    assert_eq!(ni.number_of_entries(), 840);
+
+    let ranges = ni.block_ranges();
+    assert_eq!(ranges.len(), 9);
+    assert_eq!(
+        ranges[1],
+        BlockRange {
+            min: 10.0,
+            max: 19.0
+        }
+    );
+    assert_eq!(
+        ranges[2],
+        BlockRange {
+            min: 36.0,
+            max: 39.0
+        }
+    );
+    assert_eq!(
+        ranges[3],
+        BlockRange {
+            min: 40.0,
+            max: 49.0
+        }
+    );
+
+    assert_eq!(ni.candidate_blocks(&filter(25.0, 41.0)), [2, 3]);
+    let (ids, _) = read_all(&ni, &filter(25.0, 41.0));
+    assert_eq!(ids, (360..420).collect::<Vec<_>>());
+}
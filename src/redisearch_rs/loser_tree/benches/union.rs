@@ -0,0 +1,148 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Compare the loser tree union with a binary heap union, for the number of
+//! children typical of prefix expansions.
+//!
+//! Besides the timings, the number of comparisons per emitted doc id of each
+//! implementation is printed before the benchmarks.
+
+use std::{cell::Cell, cmp::Ordering, collections::BinaryHeap};
+
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use loser_tree::Union;
+
+thread_local! {
+    static HEAP_COMPARISONS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// The head of a child in the heap, ordered so that the smallest id is on top.
+#[derive(PartialEq, Eq)]
+struct Head {
+    id: u64,
+    child: usize,
+}
+
+impl Ord for Head {
+    fn cmp(&self, other: &Self) -> Ordering {
+        HEAP_COMPARISONS.set(HEAP_COMPARISONS.get() + 1);
+        other.id.cmp(&self.id).then(other.child.cmp(&self.child))
+    }
+}
+
+impl PartialOrd for Head {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The union as done with a binary heap.
+struct HeapUnion<I> {
+    children: Vec<I>,
+    heap: BinaryHeap<Head>,
+}
+
+impl<I: Iterator<Item = u64>> HeapUnion<I> {
+    fn new(mut children: Vec<I>) -> Self {
+        let heap = children
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(child, it)| {
+                Some(Head {
+                    id: it.next()?,
+                    child,
+                })
+            })
+            .collect();
+        Self { children, heap }
+    }
+}
+
+impl<I: Iterator<Item = u64>> Iterator for HeapUnion<I> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let id = self.heap.peek()?.id;
+        while let Some(mut top) = self.heap.peek_mut()
+            && top.id == id
+        {
+            match self.children[top.child].next() {
+                Some(next) => top.id = next,
+                None => {
+                    std::collections::binary_heap::PeekMut::pop(top);
+                }
+            }
+        }
+        Some(id)
+    }
+}
+
+/// `k` posting lists of a prefix expansion. With `skewed`, the first terms
+/// are much more frequent than the long tail of rare ones; otherwise all the
+/// terms have about the same frequency.
+fn expansion(k: usize, skewed: bool) -> Vec<Vec<u64>> {
+    let mut state = 0x9e37_79b9_7f4a_7c15_u64;
+    (0..k)
+        .map(|i| {
+            let gap = if skewed { 2 + 50 * i as u64 } else { 200 };
+            let mut id = 0;
+            (0..100_000 / gap)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6_364_136_223_846_793_005)
+                        .wrapping_add(1);
+                    id += 1 + (state >> 33) % (2 * gap);
+                    id
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn children(lists: &[Vec<u64>]) -> Vec<std::iter::Copied<std::slice::Iter<'_, u64>>> {
+    lists.iter().map(|l| l.iter().copied()).collect()
+}
+
+fn print_comparisons(name: &str, lists: &[Vec<u64>]) {
+    let mut union = Union::new(children(lists));
+    let ids = union.by_ref().count() as f64;
+    let tree = union.comparisons() as f64 / ids;
+
+    HEAP_COMPARISONS.set(0);
+    HeapUnion::new(children(lists)).count();
+    let heap = HEAP_COMPARISONS.get() as f64 / ids;
+
+    eprintln!("{name}: {tree:.2} comparisons per id with the loser tree, {heap:.2} with the heap");
+}
+
+fn union_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("union");
+    for k in [8, 64, 256] {
+        for skewed in [false, true] {
+            let lists = expansion(k, skewed);
+            let name = format!("{k}{}", if skewed { " skewed" } else { "" });
+            print_comparisons(&name, &lists);
+
+            group.bench_with_input(BenchmarkId::new("loser_tree", &name), &lists, |b, lists| {
+                b.iter(|| Union::new(children(black_box(lists))).count());
+            });
+            group.bench_with_input(
+                BenchmarkId::new("binary_heap", &name),
+                &lists,
+                |b, lists| {
+                    b.iter(|| HeapUnion::new(children(black_box(lists))).count());
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(union, union_benchmark);
+criterion_main!(union);
@@ -0,0 +1,275 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! A loser tree (tournament tree) merging many sorted doc id streams, and the
+//! [`Union`] iterator built on it. The [`Tournament`] itself is also used by
+//! the union iterator of `rqe_iterators`, whose children aren't `Iterator`s.
+//!
+//! Unions of prefix or fuzzy expansions routinely have hundreds of children.
+//! A binary heap pays up to `2 log2(k)` comparisons to replace its top, since
+//! sifting down compares both children at each level. A loser tree stores the
+//! loser of each match in its internal nodes, so replaying the matches of the
+//! leaf that advanced takes one comparison per level: at most
+//! `ceil(log2(k))`.
+//!
+//! The heap stops sifting as soon as the new top is in place, so it remains
+//! cheaper when a single child holds most of the ids and keeps winning. The
+//! `union` benchmark measures both cases.
+
+use std::cmp::Ordering;
+
+/// A tournament over the heads of `k` sorted streams of doc ids.
+///
+/// The tournament only knows the heads: whoever owns the streams advances the
+/// winning one, and reports its new head with [`Tournament::replace_winner`].
+/// Exhausted streams have no head, and compare greater than any doc id. Ties
+/// are won by the stream with the lower index, so the order of equal heads is
+/// stable.
+pub struct Tournament {
+    /// The current head of each stream, `None` once it is exhausted.
+    heads: Vec<Option<u64>>,
+    /// `nodes[0]` is the index of the winning stream, and `nodes[1..k]` the
+    /// losers of the matches played at each internal node. The leaf of stream
+    /// `i` is the virtual node `k + i`.
+    nodes: Vec<usize>,
+    comparisons: u64,
+}
+
+impl Tournament {
+    /// Play the tournament between streams with the given `heads`.
+    pub fn new(heads: Vec<Option<u64>>) -> Self {
+        let mut tournament = Self {
+            nodes: vec![0; heads.len().max(1)],
+            heads,
+            comparisons: 0,
+        };
+        tournament.build();
+        tournament
+    }
+
+    /// Replay the whole tournament with new `heads`, e.g. once the streams
+    /// are rewound.
+    pub fn reset(&mut self, heads: impl IntoIterator<Item = Option<u64>>) {
+        self.heads.clear();
+        self.heads.extend(heads);
+        self.nodes.resize(self.heads.len().max(1), 0);
+        self.build();
+    }
+
+    /// Play all the matches, bottom-up.
+    fn build(&mut self) {
+        let k = self.heads.len();
+        if k == 0 {
+            return;
+        }
+        // The winner of the match at each node; leaves win by default.
+        let mut winners = vec![0; 2 * k];
+        for (i, winner) in winners[k..].iter_mut().enumerate() {
+            *winner = i;
+        }
+        for node in (1..k).rev() {
+            let (left, right) = (winners[2 * node], winners[2 * node + 1]);
+            let (winner, loser) = if self.beats(right, left) {
+                (right, left)
+            } else {
+                (left, right)
+            };
+            winners[node] = winner;
+            self.nodes[node] = loser;
+        }
+        self.nodes[0] = if k == 1 { 0 } else { winners[1] };
+    }
+
+    /// Whether stream `a` wins against stream `b`.
+    fn beats(&mut self, a: usize, b: usize) -> bool {
+        self.comparisons += 1;
+        let ordering = match (self.heads[a], self.heads[b]) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        ordering.then(a.cmp(&b)) == Ordering::Less
+    }
+
+    /// The smallest head, and the index of the stream it belongs to.
+    pub fn peek(&self) -> Option<(u64, usize)> {
+        let winner = *self.nodes.first()?;
+        Some((self.heads.get(winner).copied()??, winner))
+    }
+
+    /// Set the head of the winning stream, once it advanced, and replay its
+    /// matches up to the root.
+    pub fn replace_winner(&mut self, head: Option<u64>) {
+        let Some(&child) = self.nodes.first() else {
+            return;
+        };
+        let Some(previous) = self.heads.get_mut(child) else {
+            return;
+        };
+        debug_assert!(
+            head.is_none() || head > *previous,
+            "streams must yield strictly increasing ids"
+        );
+        *previous = head;
+
+        let k = self.heads.len();
+        let mut winner = child;
+        let mut node = (k + child) / 2;
+        while node > 0 {
+            if self.beats(self.nodes[node], winner) {
+                std::mem::swap(&mut self.nodes[node], &mut winner);
+            }
+            node /= 2;
+        }
+        self.nodes[0] = winner;
+    }
+
+    /// Push to `tied` the indices of all the streams whose head is the
+    /// smallest one, winner first.
+    ///
+    /// Only the matches they played are visited: a stream tied with the
+    /// winner lost its last match against another tied stream, so it's found
+    /// among the losers on the path of that stream.
+    pub fn tied(&self, tied: &mut Vec<usize>) {
+        let Some((head, winner)) = self.peek() else {
+            return;
+        };
+        tied.push(winner);
+        let k = self.heads.len();
+        // The subtrees left to visit, with their winner.
+        let mut subtrees = vec![(1, winner)];
+        while let Some((root, winner)) = subtrees.pop() {
+            let mut node = k + winner;
+            while node > root {
+                let sibling = node ^ 1;
+                node /= 2;
+                let loser = self.nodes[node];
+                if self.heads[loser] == Some(head) {
+                    tied.push(loser);
+                    if sibling < k {
+                        subtrees.push((sibling, loser));
+                    }
+                }
+            }
+        }
+    }
+
+    /// The current head of `stream`.
+    pub fn head(&self, stream: usize) -> Option<u64> {
+        self.heads.get(stream).copied().flatten()
+    }
+
+    /// The number of streams.
+    pub const fn len(&self) -> usize {
+        self.heads.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.heads.is_empty()
+    }
+
+    /// The number of comparisons made so far, including those to build the
+    /// tree.
+    pub const fn comparisons(&self) -> u64 {
+        self.comparisons
+    }
+}
+
+/// A [`Tournament`] between iterators over sorted doc ids.
+pub struct LoserTree<I> {
+    children: Vec<I>,
+    tournament: Tournament,
+}
+
+impl<I: Iterator<Item = u64>> LoserTree<I> {
+    /// Build the tree over `children`, each yielding strictly increasing ids.
+    pub fn new(children: Vec<I>) -> Self {
+        let mut children = children;
+        let heads = children.iter_mut().map(Iterator::next).collect();
+        Self {
+            children,
+            tournament: Tournament::new(heads),
+        }
+    }
+
+    /// The smallest head, and the index of the child it belongs to.
+    pub fn peek(&self) -> Option<(u64, usize)> {
+        self.tournament.peek()
+    }
+
+    /// Advance the winning child, and replay its matches up to the root.
+    /// Returns the head it had.
+    pub fn pop(&mut self) -> Option<(u64, usize)> {
+        let (id, child) = self.peek()?;
+        let next = self.children[child].next();
+        self.tournament.replace_winner(next);
+        Some((id, child))
+    }
+
+    /// The number of children.
+    pub const fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// The number of comparisons made so far, including those to build the
+    /// tree.
+    pub const fn comparisons(&self) -> u64 {
+        self.tournament.comparisons()
+    }
+}
+
+/// An iterator over the ids yielded by any of its children, in increasing
+/// order and without duplicates.
+pub struct Union<I> {
+    tree: LoserTree<I>,
+    /// The children that yielded the last id.
+    matched: Vec<usize>,
+}
+
+impl<I: Iterator<Item = u64>> Union<I> {
+    pub fn new(children: Vec<I>) -> Self {
+        Self {
+            tree: LoserTree::new(children),
+            matched: Vec::new(),
+        }
+    }
+
+    /// The indices of the children that yielded the last id, in increasing
+    /// order, so that their results can be aggregated.
+    pub fn matched(&self) -> &[usize] {
+        &self.matched
+    }
+
+    /// The comparisons made so far. See [`LoserTree::comparisons`].
+    pub const fn comparisons(&self) -> u64 {
+        self.tree.comparisons()
+    }
+}
+
+impl<I: Iterator<Item = u64>> Iterator for Union<I> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.matched.clear();
+        let (id, child) = self.tree.pop()?;
+        self.matched.push(child);
+        while let Some((next, child)) = self.tree.peek()
+            && next == id
+        {
+            self.tree.pop();
+            self.matched.push(child);
+        }
+        Some(id)
+    }
+}
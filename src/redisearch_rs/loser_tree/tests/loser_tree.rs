@@ -0,0 +1,127 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::collections::BTreeSet;
+
+use loser_tree::{LoserTree, Tournament, Union};
+
+/// `k` streams, stream `i` yielding the multiples of `i + 2` below `end`.
+fn streams(k: usize, end: u64) -> Vec<std::iter::StepBy<std::ops::Range<u64>>> {
+    (0..k).map(|i| (0..end).step_by(i + 2)).collect()
+}
+
+#[test]
+fn test_union() {
+    for k in [0, 1, 2, 3, 5, 8, 13, 200] {
+        let expected: BTreeSet<u64> = streams(k, 1000).into_iter().flatten().collect();
+        let union = Union::new(streams(k, 1000));
+        assert_eq!(
+            union.collect::<Vec<_>>(),
+            expected.into_iter().collect::<Vec<_>>(),
+            "{k} children"
+        );
+    }
+}
+
+#[test]
+fn test_matched() {
+    let mut union = Union::new(vec![
+        vec![1, 4, 6].into_iter(),
+        vec![4, 5].into_iter(),
+        vec![2, 4, 6].into_iter(),
+    ]);
+    let mut seen = Vec::new();
+    while let Some(id) = union.next() {
+        seen.push((id, union.matched().to_vec()));
+    }
+    assert_eq!(
+        seen,
+        [
+            (1, vec![0]),
+            (2, vec![2]),
+            (4, vec![0, 1, 2]),
+            (5, vec![1]),
+            (6, vec![0, 2])
+        ]
+    );
+    assert!(union.matched().is_empty());
+}
+
+#[test]
+fn test_pop_order() {
+    let mut tree = LoserTree::new(vec![
+        vec![3, 9].into_iter(),
+        vec![].into_iter(),
+        vec![3].into_iter(),
+        vec![1].into_iter(),
+    ]);
+    assert_eq!(tree.len(), 4);
+    assert_eq!(tree.peek(), Some((1, 3)));
+    let popped: Vec<_> = std::iter::from_fn(|| tree.pop()).collect();
+    // Ties go to the lower index.
+    assert_eq!(popped, [(1, 3), (3, 0), (3, 2), (9, 0)]);
+    assert_eq!(tree.peek(), None);
+}
+
+#[test]
+fn test_comparisons_per_pop() {
+    for k in [2, 7, 64, 200, 256] {
+        let mut tree = LoserTree::new(streams(k, 10_000));
+        assert_eq!(tree.comparisons(), k as u64 - 1, "building, {k} children");
+
+        let depth = u64::from(k.next_power_of_two().ilog2());
+        for _ in 0..1000 {
+            let before = tree.comparisons();
+            tree.pop().unwrap();
+            assert!(tree.comparisons() - before <= depth, "{k} children");
+        }
+    }
+}
+
+#[test]
+fn test_tied() {
+    for k in [1, 2, 3, 5, 8, 13, 64, 100] {
+        // Stream `i` yields the multiples of `i % 4 + 1`: many streams tie.
+        let mut streams: Vec<_> = (0..k).map(|i| (1..200u64).step_by(i % 4 + 1)).collect();
+        let mut tournament = Tournament::new(streams.iter_mut().map(Iterator::next).collect());
+        while let Some((head, winner)) = tournament.peek() {
+            let mut tied = Vec::new();
+            tournament.tied(&mut tied);
+            assert_eq!(tied[0], winner, "{k} streams, head {head}");
+            tied.sort_unstable();
+            let expected: Vec<_> = (0..k)
+                .filter(|&i| tournament.head(i) == Some(head))
+                .collect();
+            assert_eq!(tied, expected, "{k} streams, head {head}");
+
+            // Advance all the tied streams, as a union would.
+            while let Some((next, winner)) = tournament.peek()
+                && next == head
+            {
+                tournament.replace_winner(streams[winner].next());
+            }
+        }
+        assert!((0..k).all(|i| tournament.head(i).is_none()));
+    }
+}
+
+#[test]
+fn test_reset() {
+    let mut tournament = Tournament::new(vec![Some(5), None, Some(3)]);
+    assert_eq!(tournament.peek(), Some((3, 2)));
+    tournament.reset([Some(2), Some(2)]);
+    assert_eq!(tournament.len(), 2);
+    assert_eq!(tournament.peek(), Some((2, 0)));
+    let mut tied = Vec::new();
+    tournament.tied(&mut tied);
+    assert_eq!(tied, [0, 1]);
+    tournament.reset([]);
+    assert!(tournament.is_empty());
+    assert_eq!(tournament.peek(), None);
+}
@@ -0,0 +1,161 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The memory used by an index, structure by structure, for
+//! `FT.DEBUG MEMUSAGE <index>`.
+//!
+//! `FT.INFO` reports a few aggregated sizes, some of them estimated from
+//! counts. For capacity planning, the structures holding the index report
+//! the bytes they allocated through their `memory_usage` methods, and the
+//! index [records](MemUsage::record) them in a [`MemUsage`], broken down by
+//! field or by encoding where a structure has several instances.
+
+use std::collections::BTreeMap;
+
+/// A kind of structure of an index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Structure {
+    /// The trie of the terms of the TEXT fields.
+    TermTrie,
+    /// The posting lists of the terms, by encoding.
+    Postings,
+    /// The term offsets, for highlighting and slop.
+    Offsets,
+    /// The numeric range trees, by field.
+    NumericTree,
+    /// The tries of the TAG values, by field.
+    TagTrie,
+    /// The table of the documents' metadata.
+    DocTable,
+    /// The sorting vectors of the documents.
+    Sortables,
+    /// The graphs of the vector indexes, by field.
+    VectorGraph,
+    /// The suffix tries of the fields with `WITHSUFFIXTRIE`, by field.
+    SuffixTrie,
+}
+
+impl Structure {
+    /// Every structure, in reply order.
+    pub const ALL: [Self; 9] = [
+        Self::TermTrie,
+        Self::Postings,
+        Self::Offsets,
+        Self::NumericTree,
+        Self::TagTrie,
+        Self::DocTable,
+        Self::Sortables,
+        Self::VectorGraph,
+        Self::SuffixTrie,
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::TermTrie => "term_trie",
+            Self::Postings => "postings",
+            Self::Offsets => "offsets",
+            Self::NumericTree => "numeric_trees",
+            Self::TagTrie => "tag_tries",
+            Self::DocTable => "doc_table",
+            Self::Sortables => "sortables",
+            Self::VectorGraph => "vector_graphs",
+            Self::SuffixTrie => "suffix_tries",
+        }
+    }
+
+    /// The name of the breakdown of the structure's instances, if it has
+    /// several.
+    const fn breakdown(self) -> Option<&'static str> {
+        match self {
+            Self::Postings => Some("by_encoding"),
+            Self::NumericTree | Self::TagTrie | Self::VectorGraph | Self::SuffixTrie => {
+                Some("by_field")
+            }
+            Self::TermTrie | Self::Offsets | Self::DocTable | Self::Sortables => None,
+        }
+    }
+}
+
+/// A value of the reply.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemValue {
+    Integer(u64),
+    Map(Vec<(String, MemValue)>),
+}
+
+/// The memory used by the structures of an index, in bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemUsage {
+    /// The bytes of each instance of each structure, by field or encoding.
+    /// Structures with a single instance have an empty name.
+    bytes: BTreeMap<Structure, BTreeMap<String, u64>>,
+}
+
+impl MemUsage {
+    /// Add `bytes` used by `structure`. `instance` is the field or encoding
+    /// of structures with several instances, and is ignored for the others.
+    pub fn record(&mut self, structure: Structure, instance: &str, bytes: usize) {
+        let instance = if structure.breakdown().is_some() {
+            instance
+        } else {
+            ""
+        };
+        *self
+            .bytes
+            .entry(structure)
+            .or_default()
+            .entry(instance.to_owned())
+            .or_default() += bytes as u64;
+    }
+
+    /// The bytes used by all the instances of `structure`.
+    pub fn structure(&self, structure: Structure) -> u64 {
+        self.bytes
+            .get(&structure)
+            .map_or(0, |instances| instances.values().sum())
+    }
+
+    /// The bytes used by the instance `instance` of `structure`.
+    pub fn instance(&self, structure: Structure, instance: &str) -> u64 {
+        self.bytes
+            .get(&structure)
+            .and_then(|instances| instances.get(instance))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn total(&self) -> u64 {
+        Structure::ALL.iter().map(|s| self.structure(*s)).sum()
+    }
+
+    /// The reply of `FT.DEBUG MEMUSAGE`: the total, then every structure
+    /// with its bytes and, if it has several instances, their breakdown.
+    /// Structures the index doesn't have are reported with `0` bytes.
+    pub fn reply(&self) -> Vec<(String, MemValue)> {
+        let mut reply = vec![("total_bytes".to_owned(), MemValue::Integer(self.total()))];
+        for structure in Structure::ALL {
+            let mut entry = vec![(
+                "bytes".to_owned(),
+                MemValue::Integer(self.structure(structure)),
+            )];
+            if let Some(breakdown) = structure.breakdown() {
+                let instances = self
+                    .bytes
+                    .get(&structure)
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, bytes)| (name.clone(), MemValue::Integer(*bytes)))
+                    .collect();
+                entry.push((breakdown.to_owned(), MemValue::Map(instances)));
+            }
+            reply.push((structure.name().to_owned(), MemValue::Map(entry)));
+        }
+        reply
+    }
+}
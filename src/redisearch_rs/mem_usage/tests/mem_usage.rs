@@ -0,0 +1,93 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use mem_usage::{MemUsage, MemValue, Structure};
+use tag_index::TagIndex;
+use vector_graph::{GraphKind, VectorGraph};
+
+const fn int(n: u64) -> MemValue {
+    MemValue::Integer(n)
+}
+
+#[test]
+fn test_record_and_reply() {
+    let mut usage = MemUsage::default();
+    usage.record(Structure::TermTrie, "", 100);
+    usage.record(Structure::Postings, "raw_doc_ids", 40);
+    usage.record(Structure::Postings, "full", 60);
+    usage.record(Structure::Postings, "full", 10);
+    usage.record(Structure::NumericTree, "price", 30);
+    // Structures with a single instance ignore the instance name.
+    usage.record(Structure::DocTable, "title", 5);
+
+    assert_eq!(usage.structure(Structure::Postings), 110);
+    assert_eq!(usage.instance(Structure::Postings, "full"), 70);
+    assert_eq!(usage.instance(Structure::DocTable, ""), 5);
+    assert_eq!(usage.structure(Structure::VectorGraph), 0);
+    assert_eq!(usage.total(), 245);
+
+    let reply = usage.reply();
+    assert_eq!(reply[0], ("total_bytes".to_owned(), int(245)));
+    assert_eq!(reply.len(), 1 + Structure::ALL.len());
+    assert_eq!(
+        reply[1],
+        (
+            "term_trie".to_owned(),
+            MemValue::Map(vec![("bytes".to_owned(), int(100))])
+        )
+    );
+    assert_eq!(
+        reply[2],
+        (
+            "postings".to_owned(),
+            MemValue::Map(vec![
+                ("bytes".to_owned(), int(110)),
+                (
+                    "by_encoding".to_owned(),
+                    MemValue::Map(vec![
+                        ("full".to_owned(), int(70)),
+                        ("raw_doc_ids".to_owned(), int(40)),
+                    ])
+                ),
+            ])
+        )
+    );
+    assert_eq!(
+        reply[8],
+        (
+            "vector_graphs".to_owned(),
+            MemValue::Map(vec![
+                ("bytes".to_owned(), int(0)),
+                ("by_field".to_owned(), MemValue::Map(Vec::new())),
+            ])
+        )
+    );
+}
+
+#[test]
+fn test_structures_report_their_allocations() {
+    let mut tags = TagIndex::default();
+    assert_eq!(tags.memory_usage(), 0);
+    tags.index(&["red", "blue"], 1);
+    tags.index(&["red"], 2);
+    let tag_bytes = tags.memory_usage();
+    assert!(tag_bytes >= 2 * size_of::<u64>() + 3 * size_of::<u64>() + 7);
+
+    let mut graph = VectorGraph::new(GraphKind::Hnsw);
+    let empty = graph.memory_usage();
+    graph.add_node(vec![Vec::new()]);
+    graph.add_node(vec![vec![0; 16], vec![0; 4]]);
+    let graph_bytes = graph.memory_usage();
+    assert!(graph_bytes >= empty + 20 * size_of::<u32>());
+
+    let mut usage = MemUsage::default();
+    usage.record(Structure::TagTrie, "color", tag_bytes);
+    usage.record(Structure::VectorGraph, "embedding", graph_bytes);
+    assert_eq!(usage.total(), (tag_bytes + graph_bytes) as u64);
+}
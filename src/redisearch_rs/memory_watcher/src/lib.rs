@@ -0,0 +1,235 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Backpressure for background work when the server runs low on memory.
+//!
+//! The [`MemoryWatcher`] is fed with memory readings (from `INFO memory`, or
+//! `RedisModule_GetUsedMemoryRatio`) and pauses background indexing and the
+//! application of GC results once used memory crosses a configurable
+//! percentage of `maxmemory`. Work resumes only once usage falls below a lower
+//! percentage, so that workers don't flap around the threshold.
+//!
+//! The module polls the watcher through `memory_watcher_ffi` from the
+//! background scan loop and before the fork GC applies the child's results,
+//! with the pause percentage set by `_BG_WORK_PAUSE_MEM_PCT`. Setting it below
+//! `_BG_INDEX_MEM_PCT_THR` lets the scan wait for memory to be freed instead of
+//! being stopped, which leaves the index partially built.
+
+use std::{
+    sync::{
+        Condvar, Mutex, MutexGuard,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// Source of memory readings.
+pub trait MemoryProbe {
+    /// The memory currently used by the server, in bytes.
+    fn used_memory(&self) -> u64;
+    /// The configured `maxmemory`, in bytes. `0` means unlimited.
+    fn max_memory(&self) -> u64;
+}
+
+/// Pause and resume thresholds, as percentages of `maxmemory`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Thresholds {
+    pause_percent: u8,
+    resume_percent: u8,
+}
+
+/// `resume_percent` must be strictly lower than `pause_percent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidThresholds;
+
+impl std::fmt::Display for InvalidThresholds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(
+            "resume threshold must be lower than the pause threshold, which must be at most 100",
+        )
+    }
+}
+
+impl std::error::Error for InvalidThresholds {}
+
+impl Thresholds {
+    /// Thresholds that never pause, the equivalent of `indexingMemoryLimit` being `0`.
+    pub const DISABLED: Self = Self {
+        pause_percent: 0,
+        resume_percent: 0,
+    };
+
+    /// Pause above `pause_percent` of `maxmemory`, resume below `resume_percent`.
+    ///
+    /// A `pause_percent` of `0` disables the watcher.
+    pub const fn new(pause_percent: u8, resume_percent: u8) -> Result<Self, InvalidThresholds> {
+        if pause_percent == 0 {
+            return Ok(Self::DISABLED);
+        }
+        if pause_percent > 100 || resume_percent >= pause_percent {
+            return Err(InvalidThresholds);
+        }
+        Ok(Self {
+            pause_percent,
+            resume_percent,
+        })
+    }
+
+    pub const fn is_disabled(&self) -> bool {
+        self.pause_percent == 0
+    }
+
+    pub const fn pause_percent(&self) -> u8 {
+        self.pause_percent
+    }
+
+    pub const fn resume_percent(&self) -> u8 {
+        self.resume_percent
+    }
+}
+
+/// A change of state caused by a memory reading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    Paused,
+    Resumed,
+}
+
+/// The watcher state reported by `FT.INFO`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatcherInfo {
+    pub paused: bool,
+    /// How many times background work was paused since startup.
+    pub pause_count: u64,
+    /// Total time spent paused, including the current pause if any.
+    pub paused_time: Duration,
+}
+
+#[derive(Debug)]
+struct State {
+    thresholds: Thresholds,
+    paused_since: Option<Instant>,
+    pause_count: u64,
+    paused_time: Duration,
+}
+
+/// See the [crate documentation](crate).
+#[derive(Debug)]
+pub struct MemoryWatcher {
+    /// Mirrors `state.paused_since.is_some()`, for lock-free checks on hot paths.
+    paused: AtomicBool,
+    state: Mutex<State>,
+    resumed: Condvar,
+}
+
+impl MemoryWatcher {
+    pub const fn new(thresholds: Thresholds) -> Self {
+        Self {
+            paused: AtomicBool::new(false),
+            state: Mutex::new(State {
+                thresholds,
+                paused_since: None,
+                pause_count: 0,
+                paused_time: Duration::ZERO,
+            }),
+            resumed: Condvar::new(),
+        }
+    }
+
+    /// Change the thresholds, e.g. after `FT.CONFIG SET`.
+    ///
+    /// Disabling the watcher resumes paused work immediately; otherwise the new
+    /// thresholds apply from the next reading.
+    pub fn set_thresholds(&self, thresholds: Thresholds) {
+        let mut state = self.lock();
+        state.thresholds = thresholds;
+        if thresholds.is_disabled() {
+            self.resume(&mut state);
+        }
+    }
+
+    /// Whether background work should currently be paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    /// Take a reading from `probe`, see [`MemoryWatcher::observe`].
+    pub fn poll(&self, probe: &impl MemoryProbe) -> Option<Transition> {
+        self.observe(probe.used_memory(), probe.max_memory())
+    }
+
+    /// Update the state given the `used` memory and `max_memory`, both in bytes.
+    ///
+    /// Returns the transition the reading caused, if any.
+    pub fn observe(&self, used: u64, max_memory: u64) -> Option<Transition> {
+        let mut state = self.lock();
+        let thresholds = state.thresholds;
+        if thresholds.is_disabled() || max_memory == 0 {
+            return self.resume(&mut state);
+        }
+
+        // Compare `used / max_memory` with the percentages without losing precision.
+        let used = used as u128 * 100;
+        let percent_of_max = |percent: u8| max_memory as u128 * percent as u128;
+        if state.paused_since.is_none() && used > percent_of_max(thresholds.pause_percent) {
+            state.paused_since = Some(Instant::now());
+            state.pause_count += 1;
+            self.paused.store(true, Ordering::Release);
+            Some(Transition::Paused)
+        } else if state.paused_since.is_some() && used < percent_of_max(thresholds.resume_percent) {
+            self.resume(&mut state)
+        } else {
+            None
+        }
+    }
+
+    /// Block the calling background thread while work is paused, for at most `timeout`.
+    ///
+    /// Returns `true` if work may proceed, `false` if the timeout elapsed
+    /// while still paused (e.g. so that the caller can check for cancellation).
+    pub fn wait_while_paused(&self, timeout: Duration) -> bool {
+        if !self.is_paused() {
+            return true;
+        }
+        let state = self.lock();
+        let (state, _) = self
+            .resumed
+            .wait_timeout_while(state, timeout, |state| state.paused_since.is_some())
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.paused_since.is_none()
+    }
+
+    /// The current state, for `FT.INFO`.
+    pub fn info(&self) -> WatcherInfo {
+        let state = self.lock();
+        let current = state
+            .paused_since
+            .map_or(Duration::ZERO, |since| since.elapsed());
+        WatcherInfo {
+            paused: state.paused_since.is_some(),
+            pause_count: state.pause_count,
+            paused_time: state.paused_time + current,
+        }
+    }
+
+    fn resume(&self, state: &mut State) -> Option<Transition> {
+        let since = state.paused_since.take()?;
+        state.paused_time += since.elapsed();
+        self.paused.store(false, Ordering::Release);
+        self.resumed.notify_all();
+        Some(Transition::Resumed)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // The state is always left consistent, so a panic while holding the lock is harmless.
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,94 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{sync::Arc, thread, time::Duration};
+
+use memory_watcher::{InvalidThresholds, MemoryProbe, MemoryWatcher, Thresholds, Transition};
+
+const GB: u64 = 1 << 30;
+
+struct FixedProbe(u64, u64);
+
+impl MemoryProbe for FixedProbe {
+    fn used_memory(&self) -> u64 {
+        self.0
+    }
+
+    fn max_memory(&self) -> u64 {
+        self.1
+    }
+}
+
+fn watcher() -> MemoryWatcher {
+    MemoryWatcher::new(Thresholds::new(80, 70).unwrap())
+}
+
+#[test]
+fn test_thresholds_validation() {
+    assert_eq!(Thresholds::new(70, 70), Err(InvalidThresholds));
+    assert_eq!(Thresholds::new(101, 70), Err(InvalidThresholds));
+    assert!(Thresholds::new(0, 50).unwrap().is_disabled());
+}
+
+#[test]
+fn test_hysteresis() {
+    let watcher = watcher();
+    assert_eq!(watcher.observe(GB * 79 / 100, GB), None);
+    assert_eq!(watcher.observe(GB * 81 / 100, GB), Some(Transition::Paused));
+    assert!(watcher.is_paused());
+    // Between the thresholds: stay paused.
+    assert_eq!(watcher.observe(GB * 75 / 100, GB), None);
+    assert_eq!(watcher.observe(GB * 90 / 100, GB), None);
+    assert!(watcher.is_paused());
+    assert_eq!(
+        watcher.observe(GB * 69 / 100, GB),
+        Some(Transition::Resumed)
+    );
+    assert!(!watcher.is_paused());
+    // Between the thresholds again: stay running.
+    assert_eq!(watcher.observe(GB * 75 / 100, GB), None);
+
+    let info = watcher.info();
+    assert!(!info.paused);
+    assert_eq!(info.pause_count, 1);
+}
+
+#[test]
+fn test_unlimited_and_disabled() {
+    let watcher = watcher();
+    assert_eq!(watcher.poll(&FixedProbe(100 * GB, 0)), None);
+
+    assert_eq!(watcher.poll(&FixedProbe(GB, GB)), Some(Transition::Paused));
+    // `maxmemory` being removed resumes work.
+    assert_eq!(watcher.poll(&FixedProbe(GB, 0)), Some(Transition::Resumed));
+
+    assert_eq!(watcher.poll(&FixedProbe(GB, GB)), Some(Transition::Paused));
+    watcher.set_thresholds(Thresholds::DISABLED);
+    assert!(!watcher.is_paused());
+    assert_eq!(watcher.poll(&FixedProbe(GB, GB)), None);
+    assert_eq!(watcher.info().pause_count, 2);
+}
+
+#[test]
+fn test_wait_while_paused() {
+    let watcher = Arc::new(watcher());
+    assert!(watcher.wait_while_paused(Duration::ZERO));
+
+    watcher.observe(GB, GB);
+    assert!(!watcher.wait_while_paused(Duration::from_millis(1)));
+
+    let worker = {
+        let watcher = Arc::clone(&watcher);
+        thread::spawn(move || watcher.wait_while_paused(Duration::from_secs(60)))
+    };
+    thread::sleep(Duration::from_millis(10));
+    watcher.observe(0, GB);
+    assert!(worker.join().unwrap());
+    assert!(watcher.info().paused_time > Duration::ZERO);
+}
@@ -0,0 +1,379 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The stats registry rendered by `FT._METRICS` in the Prometheus text
+//! exposition format, so exporters can scrape RediSearch without parsing
+//! `FT.INFO`.
+//!
+//! Metric families are declared once with [`Registry::describe`], then updated
+//! by the query path, the GC, the cursors and the pools through counters,
+//! gauges and histograms identified by their labels.
+//!
+//! Every index adds its own series to the families labelled with `index`. To
+//! keep the cardinality bounded, only the first
+//! [`Registry::index_label_limit`] indexes get their own label value: the
+//! series of the other indexes are aggregated under [`OTHER_INDEX`].
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    sync::Mutex,
+};
+
+/// The name of the label identifying an index.
+pub const INDEX_LABEL: &str = "index";
+
+/// The value of the `index` label of the indexes beyond the limit.
+pub const OTHER_INDEX: &str = "__other__";
+
+/// The type of a metric family.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricKind {
+    Counter,
+    Gauge,
+    /// A histogram with the given upper bounds, in increasing order. The
+    /// `+Inf` bucket is implicit.
+    Histogram(Vec<f64>),
+}
+
+impl MetricKind {
+    const fn name(&self) -> &'static str {
+        match self {
+            Self::Counter => "counter",
+            Self::Gauge => "gauge",
+            Self::Histogram(_) => "histogram",
+        }
+    }
+}
+
+type Labels = Vec<(&'static str, String)>;
+
+#[derive(Debug, Clone)]
+enum Series {
+    Value(f64),
+    Histogram {
+        /// The observations per bucket, not cumulated. The last one is `+Inf`.
+        buckets: Vec<u64>,
+        sum: f64,
+    },
+}
+
+#[derive(Debug)]
+struct Family {
+    help: &'static str,
+    kind: MetricKind,
+    series: BTreeMap<Labels, Series>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    families: BTreeMap<&'static str, Family>,
+    /// The indexes which have their own label value.
+    indexes: BTreeSet<String>,
+    /// The indexes aggregated under [`OTHER_INDEX`].
+    overflowed: BTreeSet<String>,
+    /// The gauges of the indexes aggregated under [`OTHER_INDEX`], by family
+    /// and labels, to sum them.
+    overflowed_gauges: BTreeMap<(&'static str, Labels), BTreeMap<String, f64>>,
+}
+
+/// The metrics of the module.
+#[derive(Debug)]
+pub struct Registry {
+    index_label_limit: usize,
+    inner: Mutex<Inner>,
+}
+
+impl Registry {
+    pub fn new(index_label_limit: usize) -> Self {
+        Self {
+            index_label_limit,
+            inner: Mutex::default(),
+        }
+    }
+
+    /// The number of indexes with their own `index` label value.
+    pub const fn index_label_limit(&self) -> usize {
+        self.index_label_limit
+    }
+
+    /// Declare a metric family. Declaring it again replaces its description
+    /// and drops its series.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bounds of a histogram are not increasing.
+    pub fn describe(&self, name: &'static str, kind: MetricKind, help: &'static str) {
+        if let MetricKind::Histogram(bounds) = &kind {
+            assert!(
+                bounds.is_sorted_by(|a, b| a < b),
+                "histogram bounds of {name} must be increasing"
+            );
+        }
+        self.lock().families.insert(
+            name,
+            Family {
+                help,
+                kind,
+                series: BTreeMap::new(),
+            },
+        );
+    }
+
+    /// Add `delta` to a counter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` isn't a declared counter.
+    pub fn inc_counter(&self, name: &'static str, labels: &[(&'static str, &str)], delta: f64) {
+        self.update(name, labels, |kind, series| {
+            assert!(*kind == MetricKind::Counter, "{name} is not a counter");
+            match series {
+                Series::Value(v) => *v += delta,
+                Series::Histogram { .. } => unreachable!(),
+            }
+        });
+    }
+
+    /// Set a gauge. The gauge of [`OTHER_INDEX`] is the sum of the gauges of
+    /// the indexes beyond the limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` isn't a declared gauge.
+    pub fn set_gauge(&self, name: &'static str, labels: &[(&'static str, &str)], value: f64) {
+        let mut inner = self.lock();
+        let (labels, overflowed) = inner.labels(labels, self.index_label_limit);
+        let value = match overflowed {
+            Some(index) => {
+                let key = (name, labels.clone());
+                let gauges = inner.overflowed_gauges.entry(key).or_default();
+                gauges.insert(index, value);
+                gauges.values().sum()
+            }
+            None => value,
+        };
+        let family = inner.family(name);
+        assert!(family.kind == MetricKind::Gauge, "{name} is not a gauge");
+        family.series.insert(labels, Series::Value(value));
+    }
+
+    /// Record an observation in a histogram.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` isn't a declared histogram.
+    pub fn observe(&self, name: &'static str, labels: &[(&'static str, &str)], value: f64) {
+        self.update(name, labels, |kind, series| {
+            let MetricKind::Histogram(bounds) = kind else {
+                panic!("{name} is not a histogram");
+            };
+            if let Series::Value(_) = series {
+                *series = Series::Histogram {
+                    buckets: vec![0; bounds.len() + 1],
+                    sum: 0.0,
+                };
+            }
+            let Series::Histogram { buckets, sum } = series else {
+                unreachable!()
+            };
+            let bucket = bounds.partition_point(|bound| *bound < value);
+            buckets[bucket] += 1;
+            *sum += value;
+        });
+    }
+
+    /// Drop the series of a deleted index, freeing its label value for
+    /// another index.
+    pub fn remove_index(&self, index: &str) {
+        let mut inner = self.lock();
+        if inner.overflowed.remove(index) {
+            // Counters and histograms keep what the index contributed, as
+            // they never decrease.
+            let mut sums = Vec::new();
+            for ((name, labels), gauges) in &mut inner.overflowed_gauges {
+                if gauges.remove(index).is_some() {
+                    sums.push((*name, labels.clone(), gauges.values().sum()));
+                }
+            }
+            for (name, labels, sum) in sums {
+                inner.family(name).series.insert(labels, Series::Value(sum));
+            }
+            return;
+        }
+        if !inner.indexes.remove(index) {
+            return;
+        }
+        for family in inner.families.values_mut() {
+            family.series.retain(|labels, _| {
+                !labels
+                    .iter()
+                    .any(|(name, value)| *name == INDEX_LABEL && value == index)
+            });
+        }
+    }
+
+    /// The indexes whose series are aggregated under [`OTHER_INDEX`].
+    pub fn overflowed_indexes(&self) -> usize {
+        self.lock().overflowed.len()
+    }
+
+    /// The reply of `FT._METRICS`, in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let inner = self.lock();
+        let mut out = String::new();
+        for (name, family) in &inner.families {
+            let _ = writeln!(out, "# HELP {name} {}", escape_help(family.help));
+            let _ = writeln!(out, "# TYPE {name} {}", family.kind.name());
+            for (labels, series) in &family.series {
+                match (series, &family.kind) {
+                    (Series::Value(v), _) => {
+                        write_sample(&mut out, name, "", labels, None, *v);
+                    }
+                    (Series::Histogram { buckets, sum }, MetricKind::Histogram(bounds)) => {
+                        let mut cumulated = 0;
+                        for (i, count) in buckets.iter().enumerate() {
+                            cumulated += count;
+                            let le = bounds.get(i).copied().unwrap_or(f64::INFINITY);
+                            write_sample(
+                                &mut out,
+                                name,
+                                "_bucket",
+                                labels,
+                                Some(le),
+                                cumulated as f64,
+                            );
+                        }
+                        write_sample(&mut out, name, "_sum", labels, None, *sum);
+                        write_sample(&mut out, name, "_count", labels, None, cumulated as f64);
+                    }
+                    (Series::Histogram { .. }, _) => unreachable!(),
+                }
+            }
+        }
+        out
+    }
+
+    fn update(
+        &self,
+        name: &'static str,
+        labels: &[(&'static str, &str)],
+        f: impl FnOnce(&MetricKind, &mut Series),
+    ) {
+        let mut inner = self.lock();
+        let (labels, _) = inner.labels(labels, self.index_label_limit);
+        let family = inner.family(name);
+        let series = family.series.entry(labels).or_insert(Series::Value(0.0));
+        f(&family.kind, series);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        // The registry stays consistent even if an update panicked.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Inner {
+    fn family(&mut self, name: &'static str) -> &mut Family {
+        match self.families.get_mut(name) {
+            Some(family) => family,
+            None => panic!("metric {name} was not declared"),
+        }
+    }
+
+    /// The labels of a series, sorted by name, with an index beyond the
+    /// limit replaced by [`OTHER_INDEX`]. The replaced index is returned
+    /// along.
+    fn labels(
+        &mut self,
+        labels: &[(&'static str, &str)],
+        limit: usize,
+    ) -> (Labels, Option<String>) {
+        let mut overflowed = None;
+        let mut labels: Labels = labels
+            .iter()
+            .map(|(name, value)| {
+                let mut value = *value;
+                if *name == INDEX_LABEL && self.index_label(value, limit) == OTHER_INDEX {
+                    overflowed = Some(value.to_owned());
+                    value = OTHER_INDEX;
+                }
+                (*name, value.to_owned())
+            })
+            .collect();
+        labels.sort();
+        (labels, overflowed)
+    }
+
+    fn index_label<'a>(&mut self, index: &'a str, limit: usize) -> &'a str {
+        if self.indexes.contains(index) {
+            index
+        } else if self.indexes.len() < limit {
+            self.indexes.insert(index.to_owned());
+            index
+        } else {
+            self.overflowed.insert(index.to_owned());
+            OTHER_INDEX
+        }
+    }
+}
+
+fn write_sample(
+    out: &mut String,
+    name: &str,
+    suffix: &str,
+    labels: &Labels,
+    le: Option<f64>,
+    value: f64,
+) {
+    out.push_str(name);
+    out.push_str(suffix);
+    if !labels.is_empty() || le.is_some() {
+        out.push('{');
+        let mut first = true;
+        for (label, value) in labels {
+            if !first {
+                out.push(',');
+            }
+            first = false;
+            let _ = write!(out, "{label}=\"{}\"", escape_label(value));
+        }
+        if let Some(le) = le {
+            if !first {
+                out.push(',');
+            }
+            let _ = write!(out, "le=\"{}\"", format_value(le));
+        }
+        out.push('}');
+    }
+    let _ = writeln!(out, " {}", format_value(value));
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_owned()
+    } else if value == f64::INFINITY {
+        "+Inf".to_owned()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_owned()
+    } else {
+        value.to_string()
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
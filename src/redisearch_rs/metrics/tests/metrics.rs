@@ -0,0 +1,121 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use metrics::{MetricKind, OTHER_INDEX, Registry};
+
+fn registry(limit: usize) -> Registry {
+    let registry = Registry::new(limit);
+    registry.describe(
+        "search_queries_total",
+        MetricKind::Counter,
+        "Queries executed.",
+    );
+    registry.describe("search_cursors", MetricKind::Gauge, "Open cursors.");
+    registry.describe(
+        "search_query_seconds",
+        MetricKind::Histogram(vec![0.001, 0.01, 0.1]),
+        "Query latency.",
+    );
+    registry
+}
+
+#[test]
+fn test_render() {
+    let registry = registry(10);
+    registry.inc_counter(
+        "search_queries_total",
+        &[("index", "idx"), ("command", "FT.SEARCH")],
+        1.0,
+    );
+    registry.inc_counter(
+        "search_queries_total",
+        &[("command", "FT.SEARCH"), ("index", "idx")],
+        2.0,
+    );
+    registry.set_gauge("search_cursors", &[], 4.0);
+    for latency in [0.0005, 0.005, 0.005, 2.0] {
+        registry.observe("search_query_seconds", &[("index", "idx")], latency);
+    }
+    assert_eq!(
+        registry.render(),
+        "# HELP search_cursors Open cursors.\n\
+         # TYPE search_cursors gauge\n\
+         search_cursors 4\n\
+         # HELP search_queries_total Queries executed.\n\
+         # TYPE search_queries_total counter\n\
+         search_queries_total{command=\"FT.SEARCH\",index=\"idx\"} 3\n\
+         # HELP search_query_seconds Query latency.\n\
+         # TYPE search_query_seconds histogram\n\
+         search_query_seconds_bucket{index=\"idx\",le=\"0.001\"} 1\n\
+         search_query_seconds_bucket{index=\"idx\",le=\"0.01\"} 3\n\
+         search_query_seconds_bucket{index=\"idx\",le=\"0.1\"} 3\n\
+         search_query_seconds_bucket{index=\"idx\",le=\"+Inf\"} 4\n\
+         search_query_seconds_sum{index=\"idx\"} 2.0105\n\
+         search_query_seconds_count{index=\"idx\"} 4\n"
+    );
+}
+
+#[test]
+fn test_label_escaping() {
+    let registry = registry(10);
+    registry.inc_counter("search_queries_total", &[("index", "a\"b\\c\nd")], 1.0);
+    assert!(
+        registry
+            .render()
+            .contains("search_queries_total{index=\"a\\\"b\\\\c\\nd\"} 1\n")
+    );
+}
+
+#[test]
+fn test_index_label_limit() {
+    let registry = registry(2);
+    for index in ["a", "b", "c", "d"] {
+        registry.inc_counter("search_queries_total", &[("index", index)], 1.0);
+    }
+    registry.set_gauge("search_cursors", &[("index", "c")], 2.0);
+    registry.set_gauge("search_cursors", &[("index", "d")], 3.0);
+    registry.set_gauge("search_cursors", &[("index", "c")], 1.0);
+    assert_eq!(registry.overflowed_indexes(), 2);
+    let rendered = registry.render();
+    assert!(rendered.contains(&format!(
+        "search_queries_total{{index=\"{OTHER_INDEX}\"}} 2\n"
+    )));
+    assert!(rendered.contains(&format!("search_cursors{{index=\"{OTHER_INDEX}\"}} 4\n")));
+    assert!(!rendered.contains("index=\"c\""));
+
+    // Dropping an overflowed index removes its gauges from the aggregate.
+    registry.remove_index("d");
+    assert!(
+        registry
+            .render()
+            .contains(&format!("search_cursors{{index=\"{OTHER_INDEX}\"}} 1\n"))
+    );
+
+    // Dropping an index frees its label value.
+    registry.remove_index("a");
+    assert!(!registry.render().contains("index=\"a\""));
+    registry.inc_counter("search_queries_total", &[("index", "e")], 1.0);
+    assert!(
+        registry
+            .render()
+            .contains("search_queries_total{index=\"e\"} 1\n")
+    );
+}
+
+#[test]
+#[should_panic(expected = "search_cursors is not a counter")]
+fn test_kind_mismatch() {
+    registry(1).inc_counter("search_cursors", &[], 1.0);
+}
+
+#[test]
+#[should_panic(expected = "metric unknown was not declared")]
+fn test_undeclared() {
+    registry(1).set_gauge("unknown", &[], 1.0);
+}
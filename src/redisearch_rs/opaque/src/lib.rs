@@ -0,0 +1,135 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Opaque, sized mirrors of Rust types, passed by value to C.
+//!
+//! C code that embeds a Rust value (e.g. a `QueryError` on the stack) needs a
+//! type with the same size and alignment, but no visible fields: the
+//! `#[repr(C)]` mirror, built from [`Size`]. This crate holds the unsafe
+//! conversions between a type and its mirror in one place, so that FFI crates
+//! only declare the pair with [`IntoOpaque`] and their logic can be tested
+//! under Miri without any C code.
+//!
+//! Mismatched layouts are rejected at compile time:
+//!
+//! ```compile_fail
+//! use opaque::{IntoOpaque, Size};
+//!
+//! #[repr(C, align(8))]
+//! pub struct OpaqueFoo(Size<16>);
+//!
+//! pub struct Foo(u64);
+//!
+//! // Safety: (wrongly) claimed to be layout-compatible.
+//! unsafe impl IntoOpaque<OpaqueFoo> for Foo {}
+//!
+//! let _: OpaqueFoo = Foo(1).into_opaque();
+//! ```
+
+pub mod testing;
+
+use std::mem::{ManuallyDrop, MaybeUninit};
+
+/// A type with size `N`, and any bit pattern.
+#[repr(transparent)]
+pub struct Size<const N: usize>(MaybeUninit<[u8; N]>);
+
+/// A type that can be converted to and from its opaque mirror `Opaque`.
+///
+/// The mirror is a type parameter rather than an associated type so that FFI
+/// crates can implement the trait for types of the crate they wrap.
+///
+/// # Safety
+///
+/// Any bit pattern must be valid for `Opaque` (e.g. it's built from
+/// [`Size`]). Size and alignment equality are checked at compile time.
+pub unsafe trait IntoOpaque<Opaque: Sized>: Sized {
+    /// Referenced by every conversion, so that a layout mismatch fails the
+    /// build as soon as a conversion is used.
+    #[doc(hidden)]
+    const LAYOUT_MATCHES: () = {
+        assert!(
+            size_of::<Self>() == size_of::<Opaque>(),
+            "the type and its opaque mirror have different sizes"
+        );
+        assert!(
+            align_of::<Self>() == align_of::<Opaque>(),
+            "the type and its opaque mirror have different alignments"
+        );
+    };
+
+    /// Converts `self` into its opaque mirror.
+    fn into_opaque(self) -> Opaque {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::LAYOUT_MATCHES;
+        let this = ManuallyDrop::new(self);
+        // Safety: `Opaque` has the same size as `Self` and accepts any
+        // bit pattern. `self` is not dropped, ownership moves to the mirror.
+        unsafe { std::ptr::from_ref(&*this).cast::<Opaque>().read() }
+    }
+
+    /// Converts a reference into a pointer to the opaque mirror.
+    fn as_opaque_ptr(&self) -> *const Opaque {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::LAYOUT_MATCHES;
+        std::ptr::from_ref(self).cast()
+    }
+
+    /// Converts a mutable reference into a mutable pointer to the opaque mirror.
+    fn as_opaque_mut_ptr(&mut self) -> *mut Opaque {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::LAYOUT_MATCHES;
+        std::ptr::from_mut(self).cast()
+    }
+
+    /// Converts an opaque mirror back into the value.
+    ///
+    /// # Safety
+    ///
+    /// `opaque` must have been created via [`IntoOpaque::into_opaque`].
+    unsafe fn from_opaque(opaque: Opaque) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::LAYOUT_MATCHES;
+        let opaque = ManuallyDrop::new(opaque);
+        // Safety: the caller guarantees the bytes are those of a valid `Self`.
+        unsafe { std::ptr::from_ref(&*opaque).cast::<Self>().read() }
+    }
+
+    /// Converts a pointer to the opaque mirror into a reference, `None` if it
+    /// is NULL.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be NULL or point to a valid value, e.g. have been
+    /// created via [`IntoOpaque::as_opaque_ptr`] or point to the result of
+    /// [`IntoOpaque::into_opaque`], and respect the aliasing rules for the
+    /// lifetime `'a`.
+    unsafe fn from_opaque_ptr<'a>(opaque: *const Opaque) -> Option<&'a Self> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::LAYOUT_MATCHES;
+        debug_assert!(opaque.is_aligned(), "misaligned opaque pointer");
+        // Safety: see the function's safety requirements.
+        unsafe { opaque.cast::<Self>().as_ref() }
+    }
+
+    /// Converts a mutable pointer to the opaque mirror into a mutable
+    /// reference, `None` if it is NULL.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`IntoOpaque::from_opaque_ptr`], and the value must not be
+    /// accessed through any other pointer for the lifetime `'a`.
+    unsafe fn from_opaque_mut_ptr<'a>(opaque: *mut Opaque) -> Option<&'a mut Self> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::LAYOUT_MATCHES;
+        debug_assert!(opaque.is_aligned(), "misaligned opaque pointer");
+        // Safety: see the function's safety requirements.
+        unsafe { opaque.cast::<Self>().as_mut() }
+    }
+}
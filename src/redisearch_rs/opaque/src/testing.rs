@@ -0,0 +1,46 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Helpers for tests exercising FFI layers, with or without Miri.
+
+/// The number of iterations for soak tests: Miri is several orders of
+/// magnitude slower than native execution, so it gets far fewer.
+pub const fn soak_iterations(native: usize) -> usize {
+    if cfg!(miri) {
+        let reduced = native / 1000;
+        if reduced == 0 { 1 } else { reduced }
+    } else {
+        native
+    }
+}
+
+/// Round-trip `value` through its opaque mirror the way C code does: by
+/// value, then through a pointer to the mirror, checking `check` at every
+/// step.
+///
+/// Running this under Miri validates the conversions for the type, while
+/// ASAN builds catch use-after-free in the C callers.
+pub fn roundtrip<T: crate::IntoOpaque<O>, O>(value: T, check: impl Fn(&T)) -> T {
+    check(&value);
+    let mut opaque = value.into_opaque();
+
+    let ptr: *mut O = &mut opaque;
+    // Safety: `ptr` points to the result of `into_opaque`, and is the only
+    // way `opaque` is accessed while the reference lives.
+    let value = unsafe { T::from_opaque_mut_ptr(ptr) }.expect("pointer is not NULL");
+    check(value);
+    let value_ptr = value.as_opaque_ptr();
+    // Safety: `value_ptr` was created via `as_opaque_ptr`.
+    check(unsafe { T::from_opaque_ptr(value_ptr) }.expect("pointer is not NULL"));
+
+    // Safety: `opaque` was created via `into_opaque`.
+    let value = unsafe { T::from_opaque(opaque) };
+    check(&value);
+    value
+}
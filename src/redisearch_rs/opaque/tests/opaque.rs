@@ -0,0 +1,80 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::ffi::CString;
+
+use opaque::{
+    IntoOpaque, Size,
+    testing::{roundtrip, soak_iterations},
+};
+
+/// A heap-owning type, shaped like `QueryError`.
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Error {
+    code: u8,
+    message: Option<CString>,
+    detail: Option<CString>,
+}
+
+#[repr(C, align(8))]
+struct OpaqueError(Size<{ size_of::<Error>() }>);
+
+// Safety: `OpaqueError` is made of a `Size`.
+unsafe impl IntoOpaque<OpaqueError> for Error {}
+
+#[test]
+fn test_roundtrip_preserves_value() {
+    let error = Error {
+        code: 3,
+        message: Some(c"Syntax error".to_owned()),
+        detail: None,
+    };
+    let expected = error.clone();
+    let back = roundtrip(error, |e| assert_eq!(*e, expected));
+    assert_eq!(back, expected);
+}
+
+#[test]
+fn test_mutation_through_opaque_pointer() {
+    let mut opaque: OpaqueError = Error::default().into_opaque();
+    // Safety: `opaque` was created with `into_opaque` and isn't otherwise
+    // accessed while the reference lives.
+    let error = unsafe { Error::from_opaque_mut_ptr(&mut opaque) }.unwrap();
+    error.code = 7;
+    error.detail = Some(c"detail".to_owned());
+    // Safety: `opaque` was created with `into_opaque`.
+    let error = unsafe { Error::from_opaque(opaque) };
+    assert_eq!(error.code, 7);
+    assert_eq!(error.detail.as_deref(), Some(c"detail"));
+}
+
+#[test]
+fn test_null_pointers() {
+    // Safety: NULL is explicitly allowed.
+    assert!(unsafe { Error::from_opaque_ptr(std::ptr::null()) }.is_none());
+    // Safety: NULL is explicitly allowed.
+    assert!(unsafe { Error::from_opaque_mut_ptr(std::ptr::null_mut()) }.is_none());
+}
+
+/// Exercises the layout assertions and conversions repeatedly, with values
+/// of varying sizes, so that Miri and leak checkers see every path many times.
+#[test]
+fn test_soak() {
+    for i in 0..soak_iterations(10_000) {
+        let message = (i % 3 != 0).then(|| CString::new("x".repeat(i % 64)).unwrap());
+        let error = Error {
+            code: (i % 256) as u8,
+            message,
+            detail: (i % 5 == 0).then(|| c"detail".to_owned()),
+        };
+        let expected = error.clone();
+        let back = roundtrip(error, |e| assert_eq!(*e, expected));
+        assert_eq!(back, expected);
+    }
+}
@@ -0,0 +1,96 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! ACL key permission checks, the counterpart of `ACLUserMayAccessIndex`.
+//!
+//! A user may only query an index if they may read every key prefix of the
+//! index: otherwise the results would disclose keys they can't access. Keys
+//! loaded from the keyspace (`LOAD`) are checked as well, since they may lie
+//! outside the prefixes of the index, e.g. when loaded through `@__key`.
+//!
+//! Failures are reported with [`NOPERM_ERR`], the standard permission error.
+
+use std::fmt::{self, Display};
+
+/// The error replied when a check fails, `NOPERM_ERR` in C.
+pub const NOPERM_ERR: &str =
+    "NOPERM User does not have the required permissions to query the index";
+
+/// The key permissions of a user, as checked by
+/// `RedisModule_ACLCheckKeyPrefixPermissions` and
+/// `RedisModule_ACLCheckKeyPermissions`.
+pub trait KeyPermissions {
+    /// Whether the user may read all the keys starting with `prefix`.
+    fn may_read_prefix(&self, prefix: &[u8]) -> bool;
+
+    /// Whether the user may read `key`.
+    fn may_read_key(&self, key: &[u8]) -> bool;
+}
+
+/// Who runs a command.
+#[derive(Clone, Copy)]
+pub enum Caller<'a> {
+    /// A client without a user, such as the replication link, which has full
+    /// access.
+    Superuser,
+    /// The server doesn't support ACL checks for modules, so they're not
+    /// enforced.
+    Unchecked,
+    /// A user whose permissions are checked.
+    User(&'a dyn KeyPermissions),
+    /// The client has a user name, but no such user exists. Access is denied.
+    UnknownUser,
+}
+
+/// A permission check failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PermissionDenied;
+
+impl Display for PermissionDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(NOPERM_ERR)
+    }
+}
+
+impl std::error::Error for PermissionDenied {}
+
+impl Caller<'_> {
+    /// Check that the caller may query an index with the given key `prefixes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PermissionDenied`] if the caller may not read one of them.
+    pub fn check_index<P: AsRef<[u8]>>(
+        &self,
+        prefixes: impl IntoIterator<Item = P>,
+    ) -> Result<(), PermissionDenied> {
+        match self {
+            Self::Superuser | Self::Unchecked => Ok(()),
+            Self::UnknownUser => Err(PermissionDenied),
+            Self::User(user) => prefixes
+                .into_iter()
+                .all(|prefix| user.may_read_prefix(prefix.as_ref()))
+                .then_some(())
+                .ok_or(PermissionDenied),
+        }
+    }
+
+    /// Check that the caller may read `key`, before loading it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PermissionDenied`] if the caller may not read it.
+    pub fn check_key(&self, key: &[u8]) -> Result<(), PermissionDenied> {
+        match self {
+            Self::Superuser | Self::Unchecked => Ok(()),
+            Self::UnknownUser => Err(PermissionDenied),
+            Self::User(user) => user.may_read_key(key).then_some(()).ok_or(PermissionDenied),
+        }
+    }
+}
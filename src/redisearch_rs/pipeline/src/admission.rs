@@ -0,0 +1,359 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Admission control: caps on the number of queries running concurrently.
+//!
+//! Every query asks for a [`Ticket`] before running, and holds it until it's
+//! done. When the index (or the server) already runs its maximum number of
+//! queries, the query is queued, up to a bounded number of queued queries
+//! beyond which it fails fast: a runaway client can then no longer exhaust
+//! the worker pool for everyone else.
+//!
+//! A queued query doesn't hold a thread: it's a job, handed to the worker
+//! pool with its ticket once a slot is freed, or with [`Rejected::Timeout`]
+//! once its wait is over. Expired jobs are rejected whenever the controller
+//! is used, and by [`AdmissionController::expire`], which a timer should call
+//! so that queries don't wait past their deadline while the server is idle.
+//!
+//! The queries holding a ticket are listed by `FT._QUERIES LIST`, and can be
+//! killed with `FT._QUERIES KILL {id}`: a killed query notices it at its next
+//! check, as it does for timeouts, and stops.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt::{self, Display},
+    sync::{
+        Arc, Mutex, MutexGuard,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// The caps. A cap of `0` means unlimited, as for the other numeric
+/// configuration options.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AdmissionConfig {
+    /// Maximum number of queries running concurrently on a single index.
+    pub max_per_index: usize,
+    /// Maximum number of queries running concurrently on the server.
+    pub max_global: usize,
+    /// Maximum number of queries waiting for a slot; more queries are rejected
+    /// right away.
+    pub max_queued: usize,
+}
+
+/// A query was not admitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejected {
+    /// Too many queries are already waiting.
+    QueueFull,
+    /// No slot was freed in time.
+    Timeout,
+}
+
+impl Display for Rejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::QueueFull => f.write_str("Too many concurrent queries, try again later"),
+            Self::Timeout => f.write_str("Timeout waiting for a query slot"),
+        }
+    }
+}
+
+impl std::error::Error for Rejected {}
+
+/// A query holding a ticket, as listed by `FT._QUERIES LIST`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InFlightQuery {
+    pub id: u64,
+    pub index: String,
+    pub query: String,
+    pub started: Instant,
+}
+
+/// The rest of a query, run once it's admitted or rejected.
+pub type QueryJob = Box<dyn FnOnce(Result<Ticket, Rejected>) + Send>;
+
+/// Runs a job on the worker pool, e.g. with `workersThreadPool_AddWork`.
+pub type Spawner = Box<dyn Fn(Box<dyn FnOnce() + Send>) + Send + Sync>;
+
+struct Running {
+    query: InFlightQuery,
+    killed: Arc<AtomicBool>,
+}
+
+struct Waiting {
+    index: String,
+    query: String,
+    deadline: Instant,
+    job: QueryJob,
+}
+
+#[derive(Default)]
+struct State {
+    per_index: HashMap<String, usize>,
+    global: usize,
+    /// The queued queries, in arrival order.
+    queue: VecDeque<Waiting>,
+    running: BTreeMap<u64, Running>,
+    next_id: u64,
+}
+
+struct Shared {
+    config: AdmissionConfig,
+    state: Mutex<State>,
+    spawn: Spawner,
+}
+
+/// See the [module documentation](self).
+pub struct AdmissionController {
+    shared: Arc<Shared>,
+}
+
+impl AdmissionController {
+    /// A controller handing the queued queries to `spawn` once they're
+    /// admitted or rejected.
+    pub fn new(
+        config: AdmissionConfig,
+        spawn: impl Fn(Box<dyn FnOnce() + Send>) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                config,
+                state: Mutex::default(),
+                spawn: Box::new(spawn),
+            }),
+        }
+    }
+
+    pub fn config(&self) -> &AdmissionConfig {
+        &self.shared.config
+    }
+
+    /// Admit `query` on `index` if a slot is available, without queueing it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Rejected::Timeout`] if no slot is available.
+    pub fn try_admit(&self, index: &str, query: &str) -> Result<Ticket, Rejected> {
+        let mut state = self.shared.lock();
+        if !self.shared.has_slot(&state, index) {
+            return Err(Rejected::Timeout);
+        }
+        Ok(Shared::take_slot(&self.shared, &mut state, index, query))
+    }
+
+    /// Run `job` with a ticket for `query` on `index`.
+    ///
+    /// If a slot is available, or if the query is rejected right away because
+    /// too many queries are queued or `wait` is zero, `job` runs on the calling
+    /// thread before this returns. Otherwise the query is queued, and `job` is
+    /// handed to the spawner once a slot is freed or `wait` is over, leaving
+    /// the calling thread free.
+    pub fn admit(
+        &self,
+        index: &str,
+        query: &str,
+        wait: Duration,
+        job: impl FnOnce(Result<Ticket, Rejected>) + Send + 'static,
+    ) {
+        let mut state = self.shared.lock();
+        let expired = Shared::take_expired(&mut state, Instant::now());
+        let result = if self.shared.has_slot(&state, index) {
+            Ok(Shared::take_slot(&self.shared, &mut state, index, query))
+        } else if state.queue.len() >= self.shared.config.max_queued {
+            Err(Rejected::QueueFull)
+        } else if wait.is_zero() {
+            Err(Rejected::Timeout)
+        } else {
+            state.queue.push_back(Waiting {
+                index: index.to_owned(),
+                query: query.to_owned(),
+                deadline: Instant::now() + wait,
+                job: Box::new(job),
+            });
+            drop(state);
+            self.shared.dispatch(expired);
+            return;
+        };
+        drop(state);
+        self.shared.dispatch(expired);
+        job(result);
+    }
+
+    /// Reject the queued queries whose wait is over.
+    pub fn expire(&self) {
+        let expired = Shared::take_expired(&mut self.shared.lock(), Instant::now());
+        self.shared.dispatch(expired);
+    }
+
+    /// The queries holding a ticket, the oldest first.
+    pub fn in_flight(&self) -> Vec<InFlightQuery> {
+        let state = self.shared.lock();
+        state.running.values().map(|r| r.query.clone()).collect()
+    }
+
+    /// Ask the query `id` to stop. Returns whether it's running.
+    pub fn kill(&self, id: u64) -> bool {
+        let state = self.shared.lock();
+        match state.running.get(&id) {
+            Some(running) => {
+                running.killed.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The number of queries waiting for a slot.
+    pub fn queued(&self) -> usize {
+        self.shared.lock().queue.len()
+    }
+}
+
+type Ready = Vec<(QueryJob, Result<Ticket, Rejected>)>;
+
+impl Shared {
+    /// The state, even if a thread panicked while holding the lock: the
+    /// counters are updated atomically with respect to panics.
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn has_slot(&self, state: &State, index: &str) -> bool {
+        let under = |count: usize, max: usize| max == 0 || count < max;
+        under(state.global, self.config.max_global)
+            && under(
+                state.per_index.get(index).copied().unwrap_or(0),
+                self.config.max_per_index,
+            )
+    }
+
+    fn take_slot(this: &Arc<Self>, state: &mut State, index: &str, query: &str) -> Ticket {
+        state.global += 1;
+        *state.per_index.entry(index.to_owned()).or_default() += 1;
+        let id = state.next_id;
+        state.next_id += 1;
+        let killed = Arc::new(AtomicBool::new(false));
+        state.running.insert(
+            id,
+            Running {
+                query: InFlightQuery {
+                    id,
+                    index: index.to_owned(),
+                    query: query.to_owned(),
+                    started: Instant::now(),
+                },
+                killed: Arc::clone(&killed),
+            },
+        );
+        Ticket {
+            shared: Arc::clone(this),
+            id,
+            killed,
+        }
+    }
+
+    /// Remove the queued queries whose wait is over at `now`.
+    fn take_expired(state: &mut State, now: Instant) -> Ready {
+        let mut expired = Vec::new();
+        state.queue.retain_mut(|waiting| {
+            if waiting.deadline > now {
+                return true;
+            }
+            let job = std::mem::replace(&mut waiting.job, Box::new(|_| {}));
+            expired.push((job, Err(Rejected::Timeout)));
+            false
+        });
+        expired
+    }
+
+    /// Hand the jobs to the worker pool, outside of the lock.
+    fn dispatch(&self, ready: Ready) {
+        for (job, result) in ready {
+            (self.spawn)(Box::new(move || job(result)));
+        }
+    }
+
+    fn release(this: &Arc<Self>, id: u64) {
+        let mut state = this.lock();
+        let Some(running) = state.running.remove(&id) else {
+            return;
+        };
+        state.global -= 1;
+        let index = running.query.index;
+        if let Some(count) = state.per_index.get_mut(&index) {
+            *count -= 1;
+            if *count == 0 {
+                state.per_index.remove(&index);
+            }
+        }
+
+        let mut ready = Self::take_expired(&mut state, Instant::now());
+        // The queued queries may be on different indexes: admit the oldest
+        // ones that now fit.
+        let mut i = 0;
+        while i < state.queue.len() {
+            if this.has_slot(&state, &state.queue[i].index) {
+                let waiting = state.queue.remove(i).expect("in bounds");
+                let ticket = Self::take_slot(this, &mut state, &waiting.index, &waiting.query);
+                ready.push((waiting.job, Ok(ticket)));
+            } else {
+                i += 1;
+            }
+        }
+        drop(state);
+        this.dispatch(ready);
+    }
+}
+
+/// The right to run a query, released when dropped.
+pub struct Ticket {
+    shared: Arc<Shared>,
+    id: u64,
+    killed: Arc<AtomicBool>,
+}
+
+impl Ticket {
+    /// The id of the query, for `FT._QUERIES KILL`.
+    pub const fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Whether the query was killed and must stop.
+    pub fn is_killed(&self) -> bool {
+        self.killed.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        Shared::release(&self.shared, self.id);
+    }
+}
+
+/// A subcommand of `FT._QUERIES`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueriesCommand {
+    List,
+    Kill(u64),
+}
+
+impl QueriesCommand {
+    /// Parse the arguments of `FT._QUERIES`.
+    ///
+    /// Returns `None` if they're invalid.
+    pub fn parse(args: &[&str]) -> Option<Self> {
+        match args {
+            [list] if list.eq_ignore_ascii_case("LIST") => Some(Self::List),
+            [kill, id] if kill.eq_ignore_ascii_case("KILL") => id.parse().ok().map(Self::Kill),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,144 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Serialization of aggregate results as an Arrow IPC stream, for
+//! `FORMAT ARROW`.
+//!
+//! The rows are written as a single record batch, preceded by the schema, in
+//! the IPC streaming format which pandas and polars load without parsing.
+//! The type of each column comes from its `RLookup` key: keys flagged
+//! `Numeric` become nullable `Float64` columns, any other key a nullable
+//! `Utf8` column.
+
+use std::sync::Arc;
+
+use arrow_array::{
+    ArrayRef, RecordBatch, RecordBatchOptions,
+    builder::{Float64Builder, StringBuilder},
+};
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+
+use crate::export::ExportValue;
+
+/// The type of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Numeric,
+    String,
+}
+
+impl ColumnType {
+    /// The type of the column of an `RLookup` key, given whether the key has
+    /// the `Numeric` flag.
+    pub const fn of_key(numeric: bool) -> Self {
+        if numeric { Self::Numeric } else { Self::String }
+    }
+
+    const fn data_type(self) -> DataType {
+        match self {
+            Self::Numeric => DataType::Float64,
+            Self::String => DataType::Utf8,
+        }
+    }
+}
+
+enum ColumnBuilder {
+    Numeric(Float64Builder),
+    String(StringBuilder),
+}
+
+/// Serializes the rows of a reply into an Arrow IPC stream.
+pub struct ArrowExporter {
+    schema: Arc<Schema>,
+    columns: Vec<ColumnBuilder>,
+    rows: usize,
+}
+
+impl ArrowExporter {
+    /// Start a reply with the given columns, in the order of the row values.
+    pub fn new(columns: Vec<(String, ColumnType)>) -> Self {
+        let fields: Vec<Field> = columns
+            .iter()
+            .map(|(name, ty)| Field::new(name, ty.data_type(), true))
+            .collect();
+        let builders = columns
+            .iter()
+            .map(|(_, ty)| match ty {
+                ColumnType::Numeric => ColumnBuilder::Numeric(Float64Builder::new()),
+                ColumnType::String => ColumnBuilder::String(StringBuilder::new()),
+            })
+            .collect();
+        Self {
+            schema: Arc::new(Schema::new(fields)),
+            columns: builders,
+            rows: 0,
+        }
+    }
+
+    pub const fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Append a row. Columns beyond the end of `values` are null.
+    ///
+    /// A string in a numeric column is parsed, and is null if it isn't a
+    /// number. A number in a string column is formatted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more values than columns.
+    pub fn push_row(&mut self, values: &[ExportValue<'_>]) {
+        assert!(
+            values.len() <= self.columns.len(),
+            "row has {} values for {} columns",
+            values.len(),
+            self.columns.len()
+        );
+        for (i, column) in self.columns.iter_mut().enumerate() {
+            let value = values.get(i).copied().unwrap_or(ExportValue::Missing);
+            match (column, value) {
+                (ColumnBuilder::Numeric(b), ExportValue::Number(n)) => b.append_value(n),
+                (ColumnBuilder::Numeric(b), ExportValue::String(s)) => {
+                    b.append_option(s.parse().ok())
+                }
+                (ColumnBuilder::Numeric(b), _) => b.append_null(),
+                (ColumnBuilder::String(b), ExportValue::String(s)) => b.append_value(s),
+                (ColumnBuilder::String(b), ExportValue::Number(n)) => b.append_value(n.to_string()),
+                (ColumnBuilder::String(b), _) => b.append_null(),
+            }
+        }
+        self.rows += 1;
+    }
+
+    /// The serialized stream, to be replied as a bulk string.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the Arrow writer, which isn't expected for the
+    /// column types used here.
+    pub fn finish(self) -> Result<Vec<u8>, ArrowError> {
+        let arrays = self
+            .columns
+            .into_iter()
+            .map(|column| match column {
+                ColumnBuilder::Numeric(mut b) => Arc::new(b.finish()) as ArrayRef,
+                ColumnBuilder::String(mut b) => Arc::new(b.finish()) as ArrayRef,
+            })
+            .collect();
+        // The row count is explicit, for replies without columns.
+        let options = RecordBatchOptions::new().with_row_count(Some(self.rows));
+        let batch = RecordBatch::try_new_with_options(Arc::clone(&self.schema), arrays, &options)?;
+
+        let mut writer = StreamWriter::try_new(Vec::new(), &self.schema)?;
+        writer.write(&batch)?;
+        writer.finish()?;
+        writer.into_inner()
+    }
+}
@@ -0,0 +1,290 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Checkpointing the partial groups of a `GROUPBY` read through a cursor.
+//!
+//! A `GROUPBY` pipeline read with `WITHCURSOR` holds the partial state of
+//! every group, the accumulators of its reducers, between `FT.CURSOR READ`s.
+//! An idle cursor used to keep that state in memory until it was read again
+//! or `MAXIDLE` expired. With spilling enabled in the [`SpillConfig`], the
+//! cursor [checkpoints](CursorGroups::checkpoint) its groups to a file when
+//! it goes idle, freeing the memory, and [resumes](CursorGroups::resume) from
+//! the file on the next read, or on the first access to a
+//! [group](CursorGroups::group). Files are capped by the same quotas as the
+//! sorter's runs, and removed when the cursor is freed.
+//!
+//! The size of the state, in memory and on disk, is reported with the
+//! cursor stats, see [`CursorStateStats`].
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    fs::{self, File, OpenOptions},
+    io::{
+        self, BufReader, BufWriter,
+        ErrorKind::{InvalidData, UnexpectedEof},
+        Read, Write,
+    },
+    mem,
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::external_sort::{SpillConfig, SpillRecord};
+
+/// Errors returned when checkpointing or resuming.
+#[derive(Debug)]
+pub enum CheckpointError {
+    /// The checkpoint would exceed [`SpillConfig::max_file_bytes`] or
+    /// [`SpillConfig::max_total_bytes`].
+    QuotaExceeded,
+    Io(io::Error),
+}
+
+impl Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::QuotaExceeded => f.write_str("Cursor checkpoint exceeds its size limit"),
+            Self::Io(error) => write!(f, "Cursor checkpoint failed: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::QuotaExceeded => None,
+        }
+    }
+}
+
+impl From<io::Error> for CheckpointError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// The size of the group state of a cursor, for the cursor stats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CursorStateStats {
+    pub groups: u64,
+    /// The estimated size of the groups held in memory.
+    pub resident_bytes: u64,
+    /// The size of the checkpoint file, if the groups are spilled.
+    pub spilled_bytes: u64,
+    /// The number of times the groups were checkpointed.
+    pub checkpoints: u64,
+}
+
+impl CursorStateStats {
+    /// The fields of the cursor in `FT.INFO`'s cursor stats.
+    pub const fn info(&self) -> [(&'static str, u64); 4] {
+        [
+            ("state_groups", self.groups),
+            ("state_resident_bytes", self.resident_bytes),
+            ("state_spilled_bytes", self.spilled_bytes),
+            ("state_checkpoints", self.checkpoints),
+        ]
+    }
+}
+
+/// Distinguishes the checkpoint files of concurrent cursors.
+static NEXT_CHECKPOINT: AtomicU64 = AtomicU64::new(0);
+
+/// A checkpoint file, removed when dropped.
+#[derive(Debug)]
+struct CheckpointFile {
+    path: PathBuf,
+    bytes: u64,
+}
+
+impl Drop for CheckpointFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[derive(Debug)]
+enum State<S> {
+    Resident(BTreeMap<String, S>),
+    Spilled { file: CheckpointFile, groups: u64 },
+}
+
+/// The partial groups of a cursor, by group key.
+#[derive(Debug)]
+pub struct CursorGroups<S> {
+    state: State<S>,
+    checkpoints: u64,
+}
+
+impl<S> Default for CursorGroups<S> {
+    fn default() -> Self {
+        Self {
+            state: State::Resident(BTreeMap::new()),
+            checkpoints: 0,
+        }
+    }
+}
+
+impl<S: SpillRecord> CursorGroups<S> {
+    /// The state of the group `key`, created by `init` if it's new. The
+    /// groups are [resumed](Self::resume) first if they're checkpointed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the groups are checkpointed and can't be resumed.
+    pub fn group(
+        &mut self,
+        key: &str,
+        init: impl FnOnce() -> S,
+    ) -> Result<&mut S, CheckpointError> {
+        let groups = self.resident()?;
+        if !groups.contains_key(key) {
+            groups.insert(key.to_owned(), init());
+        }
+        Ok(groups.get_mut(key).expect("the group was just inserted"))
+    }
+
+    /// Remove and return the groups, in key order, once the source is
+    /// exhausted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the groups are checkpointed and can't be resumed.
+    pub fn take(&mut self) -> Result<BTreeMap<String, S>, CheckpointError> {
+        Ok(mem::take(self.resident()?))
+    }
+
+    /// The groups, [resumed](Self::resume) if they're checkpointed.
+    fn resident(&mut self) -> Result<&mut BTreeMap<String, S>, CheckpointError> {
+        self.resume()?;
+        let State::Resident(groups) = &mut self.state else {
+            unreachable!("the groups were just resumed");
+        };
+        Ok(groups)
+    }
+
+    pub const fn is_checkpointed(&self) -> bool {
+        matches!(self.state, State::Spilled { .. })
+    }
+
+    /// Write the groups to a checkpoint file and free their memory, when the
+    /// cursor goes idle. Does nothing if spilling is disabled, there are no
+    /// groups, or they're already checkpointed. `total_spilled` is the size
+    /// of the checkpoints of the other cursors of the query.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checkpoint can't be written, in which case the
+    /// groups stay in memory.
+    pub fn checkpoint(
+        &mut self,
+        config: &SpillConfig,
+        total_spilled: u64,
+    ) -> Result<(), CheckpointError> {
+        let State::Resident(groups) = &self.state else {
+            return Ok(());
+        };
+        if !config.enabled || groups.is_empty() {
+            return Ok(());
+        }
+        let mut encoded = Vec::new();
+        let mut state = Vec::new();
+        for (key, group) in groups {
+            state.clear();
+            group.encode(&mut state);
+            for field in [key.as_bytes(), &state] {
+                let len = u32::try_from(field.len()).map_err(|_| CheckpointError::QuotaExceeded)?;
+                encoded.extend_from_slice(&len.to_le_bytes());
+                encoded.extend_from_slice(field);
+            }
+        }
+        let bytes = encoded.len() as u64;
+        if bytes > config.max_file_bytes || total_spilled + bytes > config.max_total_bytes {
+            return Err(CheckpointError::QuotaExceeded);
+        }
+
+        let path = config.dir.join(format!(
+            "redisearch-cursor-{}-{}.groups",
+            std::process::id(),
+            NEXT_CHECKPOINT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        let checkpoint = CheckpointFile { path, bytes };
+        let mut writer = BufWriter::new(file);
+        writer.write_all(&encoded)?;
+        writer.flush()?;
+
+        self.state = State::Spilled {
+            file: checkpoint,
+            groups: groups.len() as u64,
+        };
+        self.checkpoints += 1;
+        Ok(())
+    }
+
+    /// Read the groups back from their checkpoint, if any, and remove it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checkpoint can't be read, in which case it's
+    /// kept for another attempt.
+    pub fn resume(&mut self) -> Result<(), CheckpointError> {
+        let State::Spilled { file, .. } = &self.state else {
+            return Ok(());
+        };
+        let mut reader = BufReader::new(File::open(&file.path)?);
+        let mut groups = BTreeMap::new();
+        while let Some(key) = read_field(&mut reader)? {
+            let key = String::from_utf8(key).map_err(|_| io::Error::from(InvalidData))?;
+            let state = read_field(&mut reader)?.ok_or(io::Error::from(UnexpectedEof))?;
+            groups.insert(key, S::decode(&state)?);
+        }
+        // Dropping the checkpoint removes its file.
+        self.state = State::Resident(groups);
+        Ok(())
+    }
+
+    /// The size of the state, for the cursor stats.
+    pub fn stats(&self) -> CursorStateStats {
+        let (groups, resident_bytes, spilled_bytes) = match &self.state {
+            State::Resident(groups) => {
+                let bytes = groups
+                    .keys()
+                    .map(|key| (key.len() + mem::size_of::<(String, S)>()) as u64)
+                    .sum();
+                (groups.len() as u64, bytes, 0)
+            }
+            State::Spilled { file, groups } => (*groups, 0, file.bytes),
+        };
+        CursorStateStats {
+            groups,
+            resident_bytes,
+            spilled_bytes,
+            checkpoints: self.checkpoints,
+        }
+    }
+}
+
+/// Read a length-prefixed field, or `None` at the end of the file.
+fn read_field(reader: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0; 4];
+    match reader.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(error) if error.kind() == UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error),
+    }
+    let mut bytes = vec![0; u32::from_le_bytes(len) as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(Some(bytes))
+}
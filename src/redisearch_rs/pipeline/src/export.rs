@@ -0,0 +1,217 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The `FORMAT CSV|JSON` option of `FT.AGGREGATE`.
+//!
+//! With a format, the rows are serialized into a single bulk string instead of
+//! nested RESP arrays, so they can be written to a file as is:
+//!
+//! - `CSV` follows RFC 4180: a header row with the field names, then a line per
+//!   row, with `\r\n` line endings. Values containing a comma, a quote, a line
+//!   break or surrounding spaces are quoted. Missing and null values are
+//!   empty.
+//! - `JSON` is an array with an object per row, keyed by field name. Missing
+//!   values are omitted from the object; numbers which JSON can't represent
+//!   (infinities and NaN) are written as `null`.
+
+use std::{
+    fmt::{self, Display, Write},
+    str::FromStr,
+};
+
+/// A serialization format for the reply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// The argument of `FORMAT` is not a known format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownFormat(pub String);
+
+impl Display for UnknownFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown FORMAT `{}`, expected CSV or JSON", self.0)
+    }
+}
+
+impl std::error::Error for UnknownFormat {}
+
+impl FromStr for ExportFormat {
+    type Err = UnknownFormat;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("CSV") {
+            Ok(Self::Csv)
+        } else if s.eq_ignore_ascii_case("JSON") {
+            Ok(Self::Json)
+        } else {
+            Err(UnknownFormat(s.to_owned()))
+        }
+    }
+}
+
+/// The value of a field in a row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportValue<'a> {
+    /// The row has no value for the field.
+    Missing,
+    Null,
+    Number(f64),
+    String(&'a str),
+}
+
+/// Serializes the rows of a reply into a single string.
+#[derive(Debug, Clone)]
+pub struct Exporter {
+    format: ExportFormat,
+    fields: Vec<String>,
+    out: String,
+    rows: usize,
+}
+
+impl Exporter {
+    /// Start a reply with the given fields, in the order of the row values.
+    pub fn new(format: ExportFormat, fields: Vec<String>) -> Self {
+        let mut out = String::new();
+        match format {
+            ExportFormat::Csv => {
+                for (i, field) in fields.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    write_csv_string(&mut out, field);
+                }
+                out.push_str("\r\n");
+            }
+            ExportFormat::Json => out.push('['),
+        }
+        Self {
+            format,
+            fields,
+            out,
+            rows: 0,
+        }
+    }
+
+    pub const fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Append a row. Fields beyond the end of `values` are missing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more values than fields.
+    pub fn push_row(&mut self, values: &[ExportValue<'_>]) {
+        assert!(
+            values.len() <= self.fields.len(),
+            "row has {} values for {} fields",
+            values.len(),
+            self.fields.len()
+        );
+        match self.format {
+            ExportFormat::Csv => self.push_csv_row(values),
+            ExportFormat::Json => self.push_json_row(values),
+        }
+        self.rows += 1;
+    }
+
+    fn push_csv_row(&mut self, values: &[ExportValue<'_>]) {
+        for i in 0..self.fields.len() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            match values.get(i).copied().unwrap_or(ExportValue::Missing) {
+                ExportValue::Missing | ExportValue::Null => {}
+                ExportValue::Number(n) => write_number(&mut self.out, n),
+                ExportValue::String(s) => write_csv_string(&mut self.out, s),
+            }
+        }
+        self.out.push_str("\r\n");
+    }
+
+    fn push_json_row(&mut self, values: &[ExportValue<'_>]) {
+        if self.rows > 0 {
+            self.out.push(',');
+        }
+        self.out.push('{');
+        let mut first = true;
+        for (field, value) in self.fields.iter().zip(values) {
+            if *value == ExportValue::Missing {
+                continue;
+            }
+            if !first {
+                self.out.push(',');
+            }
+            first = false;
+            write_json_string(&mut self.out, field);
+            self.out.push(':');
+            match *value {
+                ExportValue::Missing => unreachable!(),
+                ExportValue::Null => self.out.push_str("null"),
+                ExportValue::Number(n) if n.is_finite() => write_number(&mut self.out, n),
+                ExportValue::Number(_) => self.out.push_str("null"),
+                ExportValue::String(s) => write_json_string(&mut self.out, s),
+            }
+        }
+        self.out.push('}');
+    }
+
+    /// The serialized rows, to be replied as a bulk string.
+    pub fn finish(mut self) -> String {
+        if self.format == ExportFormat::Json {
+            self.out.push(']');
+        }
+        self.out
+    }
+}
+
+fn write_number(out: &mut String, n: f64) {
+    // `Display` never uses exponents, and prints integral values without a
+    // fractional part.
+    let _ = write!(out, "{n}");
+}
+
+fn write_csv_string(out: &mut String, s: &str) {
+    let needs_quotes = s.contains([',', '"', '\r', '\n'])
+        || s.starts_with(char::is_whitespace)
+        || s.ends_with(char::is_whitespace);
+    if !needs_quotes {
+        out.push_str(s);
+        return;
+    }
+    out.push('"');
+    for c in s.chars() {
+        if c == '"' {
+            out.push('"');
+        }
+        out.push(c);
+    }
+    out.push('"');
+}
+
+fn write_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,375 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Sorting more rows than fit in memory (`SORTBY` past `MAXAGGREGATERESULTS`).
+//!
+//! The sorter keeps at most `MAXAGGREGATERESULTS` rows in memory. When more
+//! rows arrive, the query fails with [`SortError::LimitExceeded`], unless
+//! spilling is enabled in the [`SpillConfig`]: the rows held in memory are then
+//! sorted and written as a run to a temporary file, and the runs are merged
+//! when the rows are read back. Files are capped in size individually and in
+//! total, and are removed when the sorter is dropped.
+//!
+//! At most [`MAX_MERGE_WIDTH`] runs are merged at once: past that, the oldest
+//! runs are first merged into bigger ones, in as many passes as needed. Rows
+//! comparing equal come out in the order they were pushed, as they do without
+//! spilling.
+//!
+//! Spilling is disabled by default. What was spilled is reported in the
+//! profile, see [`SpillStats`].
+
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering as AtomicOrdering},
+};
+
+/// A row which can be written to a run file.
+pub trait SpillRecord: Sized {
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// # Errors
+    ///
+    /// Returns [`io::ErrorKind::InvalidData`] if `bytes` isn't an encoded row.
+    fn decode(bytes: &[u8]) -> io::Result<Self>;
+}
+
+/// Where and how much the sorter may spill.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpillConfig {
+    pub enabled: bool,
+    /// The directory the run files are created in.
+    pub dir: PathBuf,
+    /// The maximum size of a single run file, in bytes.
+    pub max_file_bytes: u64,
+    /// The maximum size of all the run files of a query, in bytes.
+    pub max_total_bytes: u64,
+}
+
+impl Default for SpillConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: std::env::temp_dir(),
+            max_file_bytes: 64 << 20,
+            max_total_bytes: 1 << 30,
+        }
+    }
+}
+
+/// The spilling done by a query, for the profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SpillStats {
+    /// The number of run files written.
+    pub runs: u64,
+    pub rows_spilled: u64,
+    pub bytes_written: u64,
+}
+
+impl SpillStats {
+    /// The fields of the sorter in the profile.
+    pub const fn profile(&self) -> [(&'static str, u64); 3] {
+        [
+            ("Spilled runs", self.runs),
+            ("Spilled rows", self.rows_spilled),
+            ("Spilled bytes", self.bytes_written),
+        ]
+    }
+}
+
+/// Errors returned by the [`ExternalSorter`].
+#[derive(Debug)]
+pub enum SortError {
+    /// More than `MAXAGGREGATERESULTS` rows, and spilling is disabled.
+    LimitExceeded(usize),
+    /// Spilling would exceed [`SpillConfig::max_file_bytes`] or
+    /// [`SpillConfig::max_total_bytes`].
+    SpillQuotaExceeded,
+    Io(io::Error),
+}
+
+impl Display for SortError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LimitExceeded(limit) => {
+                write!(f, "Sorting more than {limit} results (MAXAGGREGATERESULTS)")
+            }
+            Self::SpillQuotaExceeded => f.write_str("Sorter spill files exceed their size limit"),
+            Self::Io(error) => write!(f, "Sorter spill failed: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for SortError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SortError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// The maximum number of runs merged at once, each holding an open file.
+pub const MAX_MERGE_WIDTH: usize = 16;
+
+/// Distinguishes the run files of concurrent queries.
+static NEXT_RUN: AtomicU64 = AtomicU64::new(0);
+
+/// See the [module documentation](self).
+pub struct ExternalSorter<T, F> {
+    cmp: F,
+    limit: usize,
+    config: SpillConfig,
+    rows: Vec<T>,
+    runs: RunFiles,
+    stats: SpillStats,
+}
+
+impl<T: SpillRecord, F: Fn(&T, &T) -> Ordering> ExternalSorter<T, F> {
+    /// A sorter holding at most `limit` rows in memory.
+    pub fn new(limit: usize, config: SpillConfig, cmp: F) -> Self {
+        Self {
+            cmp,
+            limit: limit.max(1),
+            config,
+            rows: Vec::new(),
+            runs: RunFiles::default(),
+            stats: SpillStats::default(),
+        }
+    }
+
+    /// Add a row.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rows don't fit in memory and can't be spilled.
+    pub fn push(&mut self, row: T) -> Result<(), SortError> {
+        if self.rows.len() == self.limit {
+            if !self.config.enabled {
+                return Err(SortError::LimitExceeded(self.limit));
+            }
+            self.spill()?;
+        }
+        self.rows.push(row);
+        Ok(())
+    }
+
+    pub const fn stats(&self) -> SpillStats {
+        self.stats
+    }
+
+    fn spill(&mut self) -> Result<(), SortError> {
+        self.rows.sort_by(&self.cmp);
+        let mut encoded = Vec::new();
+        for row in &self.rows {
+            encode_row(row, &mut encoded)?;
+        }
+        let size = encoded.len() as u64;
+        if size > self.config.max_file_bytes
+            || self.stats.bytes_written + size > self.config.max_total_bytes
+        {
+            return Err(SortError::SpillQuotaExceeded);
+        }
+
+        let mut writer = create_run(&self.config, &mut self.runs.0)?;
+        writer.write_all(&encoded)?;
+        writer.flush()?;
+
+        self.stats.runs += 1;
+        self.stats.rows_spilled += self.rows.len() as u64;
+        self.stats.bytes_written += size;
+        self.rows.clear();
+        Ok(())
+    }
+
+    /// Sort the rows, merging the spilled runs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a run file can't be read or, when there are more
+    /// than [`MAX_MERGE_WIDTH`] runs, if a merged run can't be written.
+    pub fn finish(self) -> Result<SortedRows<T, F>, SortError> {
+        let Self {
+            cmp,
+            mut rows,
+            mut runs,
+            config,
+            ..
+        } = self;
+        // Merge the oldest runs first, and put the merged run in their place,
+        // so that the runs stay in the order their rows were pushed. Merged
+        // runs hold rows already counted against the quota, so they aren't
+        // counted again.
+        while runs.0.len() > MAX_MERGE_WIDTH {
+            let group = RunFiles(runs.0.drain(..MAX_MERGE_WIDTH).collect());
+            let mut merged = Vec::new();
+            let writer = create_run(&config, &mut merged);
+            runs.0.splice(0..0, merged);
+            let mut writer = writer?;
+            let mut encoded = Vec::new();
+            for row in SortedRows::new(&cmp, Vec::new(), group)? {
+                encoded.clear();
+                encode_row(&row?, &mut encoded)?;
+                writer.write_all(&encoded)?;
+            }
+            writer.flush()?;
+        }
+        rows.sort_by(&cmp);
+        Ok(SortedRows::new(cmp, rows, runs)?)
+    }
+}
+
+/// Append `row` to `out`, after its length.
+fn encode_row<T: SpillRecord>(row: &T, out: &mut Vec<u8>) -> Result<(), SortError> {
+    let start = out.len();
+    out.extend_from_slice(&[0; 4]);
+    row.encode(out);
+    let len = u32::try_from(out.len() - start - 4).map_err(|_| SortError::SpillQuotaExceeded)?;
+    out[start..start + 4].copy_from_slice(&len.to_le_bytes());
+    Ok(())
+}
+
+/// Create a run file in the spill directory, adding its path to `paths`, so
+/// that it's removed even if writing it fails.
+fn create_run(config: &SpillConfig, paths: &mut Vec<PathBuf>) -> io::Result<BufWriter<File>> {
+    let path = config.dir.join(format!(
+        "redisearch-sort-{}-{}.run",
+        std::process::id(),
+        NEXT_RUN.fetch_add(1, AtomicOrdering::Relaxed)
+    ));
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    paths.push(path);
+    Ok(BufWriter::new(file))
+}
+
+/// The next row of a run, `None` at its end.
+///
+/// # Errors
+///
+/// Returns [`io::ErrorKind::UnexpectedEof`] if the run ends within a row,
+/// including within its length.
+fn read_row<T: SpillRecord>(reader: &mut impl Read) -> io::Result<Option<T>> {
+    let mut len = [0; 4];
+    let mut filled = 0;
+    while filled < len.len() {
+        match reader.read(&mut len[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(read) => filled += read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+    let mut bytes = vec![0; u32::from_le_bytes(len) as usize];
+    reader.read_exact(&mut bytes)?;
+    T::decode(&bytes).map(Some)
+}
+
+/// The run files of a query, removed when dropped.
+#[derive(Default)]
+struct RunFiles(Vec<PathBuf>);
+
+impl Drop for RunFiles {
+    fn drop(&mut self) {
+        for path in &self.0 {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Where the merged rows come from: a run file, or the rows held in memory.
+enum Source<T> {
+    Run(BufReader<File>),
+    Memory(std::vec::IntoIter<T>),
+}
+
+impl<T: SpillRecord> Source<T> {
+    fn next_row(&mut self) -> io::Result<Option<T>> {
+        match self {
+            Self::Run(reader) => read_row(reader),
+            Self::Memory(rows) => Ok(rows.next()),
+        }
+    }
+}
+
+/// The sorted rows, returned by [`ExternalSorter::finish`].
+pub struct SortedRows<T, F> {
+    cmp: F,
+    /// The runs in the order they were written, then the rows held in memory,
+    /// which were pushed last.
+    sources: Vec<Source<T>>,
+    /// The next row of each source.
+    heads: Vec<Option<T>>,
+    _runs: RunFiles,
+}
+
+impl<T: SpillRecord, F: Fn(&T, &T) -> Ordering> SortedRows<T, F> {
+    /// Merge `runs` with the sorted `memory` rows.
+    fn new(cmp: F, memory: Vec<T>, runs: RunFiles) -> io::Result<Self> {
+        let mut sources = Vec::with_capacity(runs.0.len() + 1);
+        for path in &runs.0 {
+            sources.push(Source::Run(BufReader::new(File::open(path)?)));
+        }
+        sources.push(Source::Memory(memory.into_iter()));
+        let heads = sources
+            .iter_mut()
+            .map(Source::next_row)
+            .collect::<io::Result<_>>()?;
+        Ok(Self {
+            cmp,
+            sources,
+            heads,
+            _runs: runs,
+        })
+    }
+}
+
+impl<T: SpillRecord, F: Fn(&T, &T) -> Ordering> Iterator for SortedRows<T, F> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // At most `MAX_MERGE_WIDTH` runs are merged, so a linear scan of their
+        // heads is cheaper than maintaining a heap. Ties go to the earliest
+        // source, which holds the rows pushed first, so the merge is stable.
+        let mut min: Option<usize> = None;
+        for (i, head) in self.heads.iter().enumerate() {
+            let Some(row) = head else {
+                continue;
+            };
+            let smaller = min.is_none_or(|m| {
+                let current = self.heads[m].as_ref().expect("the minimum has a row");
+                (self.cmp)(row, current) == Ordering::Less
+            });
+            if smaller {
+                min = Some(i);
+            }
+        }
+        let source = min?;
+        match self.sources[source].next_row() {
+            Ok(next) => std::mem::replace(&mut self.heads[source], next).map(Ok),
+            Err(error) => {
+                // Stop at the first error.
+                self.heads.iter_mut().for_each(|head| *head = None);
+                Some(Err(error))
+            }
+        }
+    }
+}
@@ -0,0 +1,272 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Running one query across several indexes.
+//!
+//! `FT.SEARCH` accepts a comma-separated list of indexes (`logs-2024-01,logs-2024-02`)
+//! or a glob pattern (`logs-*`) in place of the index name. The query runs on
+//! every selected index, as the coordinator would on every shard, and the
+//! results are merged into a single reply.
+//!
+//! Scores computed by different indexes are not comparable: they depend on
+//! each index's term statistics. They are normalized per index before merging.
+
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use wildcard::{MatchOutcome, WildcardPattern};
+
+/// Errors returned when selecting the indexes of a multi-index query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FanOutError {
+    /// An index given by name doesn't exist.
+    UnknownIndex(String),
+    /// No index matches the pattern.
+    NoMatchingIndex(String),
+    /// A field used by the query is missing from an index, or has a different
+    /// type than in the first selected index.
+    IncompatibleField { field: String, index: String },
+}
+
+impl Display for FanOutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownIndex(index) => write!(f, "{index}: no such index"),
+            Self::NoMatchingIndex(pattern) => write!(f, "No index matches `{pattern}`"),
+            Self::IncompatibleField { field, index } => {
+                write!(f, "Field `{field}` is incompatible in index `{index}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FanOutError {}
+
+/// The indexes a query runs on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexSelector {
+    /// A single index, or a comma-separated list of them.
+    Names(Vec<String>),
+    /// The indexes whose name matches a glob pattern.
+    Pattern(String),
+}
+
+impl IndexSelector {
+    /// Parse the index argument of a query command.
+    pub fn parse(arg: &str) -> Self {
+        if arg.contains(['*', '?']) {
+            Self::Pattern(arg.to_owned())
+        } else {
+            Self::Names(arg.split(',').map(str::to_owned).collect())
+        }
+    }
+
+    /// The selected indexes among the `existing` ones, without duplicates.
+    ///
+    /// The indexes matching a pattern are sorted by name, so that the results
+    /// are merged in a stable order.
+    pub fn resolve<'a>(
+        &self,
+        existing: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Vec<String>, FanOutError> {
+        let existing: Vec<&str> = existing.into_iter().collect();
+        match self {
+            Self::Names(names) => {
+                let mut selected: Vec<String> = Vec::with_capacity(names.len());
+                for name in names {
+                    if !existing.contains(&name.as_str()) {
+                        return Err(FanOutError::UnknownIndex(name.clone()));
+                    }
+                    if !selected.contains(name) {
+                        selected.push(name.clone());
+                    }
+                }
+                Ok(selected)
+            }
+            Self::Pattern(pattern) => {
+                let parsed = WildcardPattern::parse(pattern.as_bytes());
+                let mut selected: Vec<String> = existing
+                    .into_iter()
+                    .filter(|name| parsed.matches(name.as_bytes()) == MatchOutcome::Match)
+                    .map(str::to_owned)
+                    .collect();
+                if selected.is_empty() {
+                    return Err(FanOutError::NoMatchingIndex(pattern.clone()));
+                }
+                selected.sort_unstable();
+                selected.dedup();
+                Ok(selected)
+            }
+        }
+    }
+}
+
+/// Check that the `fields` used by the query have the same type in every
+/// selected index.
+///
+/// `field_type(index, field)` returns the type of `field` in `index`, or
+/// `None` if the index doesn't have it.
+pub fn check_compatible<'a>(
+    indexes: &[String],
+    fields: &[&str],
+    field_type: impl Fn(&str, &str) -> Option<&'a str>,
+) -> Result<(), FanOutError> {
+    let Some((first, rest)) = indexes.split_first() else {
+        return Ok(());
+    };
+    for &field in fields {
+        let incompatible = |index: &String| FanOutError::IncompatibleField {
+            field: field.to_owned(),
+            index: index.clone(),
+        };
+        let expected = field_type(first, field).ok_or_else(|| incompatible(first))?;
+        if let Some(index) = rest.iter().find(|i| field_type(i, field) != Some(expected)) {
+            return Err(incompatible(index));
+        }
+    }
+    Ok(())
+}
+
+/// How scores are made comparable across indexes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScoreNormalization {
+    /// Scores are merged as they are.
+    None,
+    /// Scores are divided by the best score of their index.
+    Max,
+    /// Scores are mapped linearly to `[0, 1]`, from the worst to the best
+    /// score of their index.
+    #[default]
+    MinMax,
+}
+
+impl FromStr for ScoreNormalization {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "NONE" => Ok(Self::None),
+            "MAX" => Ok(Self::Max),
+            "MINMAX" => Ok(Self::MinMax),
+            _ => Err(()),
+        }
+    }
+}
+
+impl ScoreNormalization {
+    fn normalize(self, hits: &mut [Hit]) {
+        let (min, max) = hits
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), h| {
+                (lo.min(h.score), hi.max(h.score))
+            });
+        let normalized: &dyn Fn(f64) -> f64 = match self {
+            Self::None => return,
+            Self::Max if max > 0.0 => &|s| s / max,
+            Self::MinMax if max > min => &|s| (s - min) / (max - min),
+            // All the scores are equal, or none is positive.
+            Self::Max | Self::MinMax => &|_| 1.0,
+        };
+        for hit in hits {
+            hit.score = normalized(hit.score);
+        }
+    }
+}
+
+/// A result returned by one index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub key: String,
+    pub score: f64,
+}
+
+/// A result of the merged reply.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergedHit {
+    /// The index which returned the result.
+    pub index: String,
+    pub key: String,
+    /// The normalized score.
+    pub score: f64,
+    /// The score computed by the index.
+    pub raw_score: f64,
+}
+
+/// The results returned by an index, along with its name.
+pub type IndexResults = (String, Vec<Hit>);
+
+/// The number of results to request from each index so that the merged page
+/// `[offset, offset + limit)` is exact.
+pub const fn per_index_limit(offset: usize, limit: usize) -> usize {
+    offset.saturating_add(limit)
+}
+
+/// Run a query on every index, in order, stopping at the first error.
+pub fn fan_out<E>(
+    indexes: &[String],
+    mut run: impl FnMut(&str) -> Result<Vec<Hit>, E>,
+) -> Result<Vec<IndexResults>, (String, E)> {
+    indexes
+        .iter()
+        .map(|index| match run(index) {
+            Ok(hits) => Ok((index.clone(), hits)),
+            Err(e) => Err((index.clone(), e)),
+        })
+        .collect()
+}
+
+/// Merge the results of every index into the page `[offset, offset + limit)`,
+/// by descending normalized score.
+///
+/// A document indexed by several indexes (their prefixes overlap) is returned
+/// once, with its best score. Ties are broken by key, then by index, so that
+/// the order doesn't depend on the order of `results`.
+pub fn merge(
+    results: Vec<IndexResults>,
+    normalization: ScoreNormalization,
+    offset: usize,
+    limit: usize,
+) -> Vec<MergedHit> {
+    let mut best: HashMap<String, MergedHit> = HashMap::new();
+    for (index, mut hits) in results {
+        let raw: Vec<f64> = hits.iter().map(|h| h.score).collect();
+        normalization.normalize(&mut hits);
+        for (hit, raw_score) in hits.into_iter().zip(raw) {
+            let merged = MergedHit {
+                index: index.clone(),
+                key: hit.key,
+                score: hit.score,
+                raw_score,
+            };
+            match best.entry(merged.key.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(merged);
+                }
+                Entry::Occupied(mut entry) => {
+                    if rank(&merged, entry.get()).is_lt() {
+                        entry.insert(merged);
+                    }
+                }
+            }
+        }
+    }
+    let mut merged: Vec<MergedHit> = best.into_values().collect();
+    merged.sort_by(rank);
+    merged.into_iter().skip(offset).take(limit).collect()
+}
+
+fn rank(a: &MergedHit, b: &MergedHit) -> std::cmp::Ordering {
+    b.score
+        .total_cmp(&a.score)
+        .then_with(|| a.key.cmp(&b.key))
+        .then_with(|| a.index.cmp(&b.index))
+}
@@ -0,0 +1,204 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Resource guardrails aborting queries that would otherwise exhaust the server.
+//!
+//! A [`QueryBudget`] is created for every query from the configured
+//! [`QueryLimits`]. Pipeline stages report the resources they consume
+//! (reply bytes, loaded rows, groups, temporary memory) and stop with a
+//! [`LimitExceeded`] error as soon as a limit is crossed. The error is reported
+//! to the client with the `Limit` query error code.
+
+use std::fmt::{self, Display};
+
+/// The configured limits. A limit of `0` means unlimited, as for the other
+/// numeric configuration options.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryLimits {
+    /// Maximum size of the reply, in bytes (`MAXREPLYSIZE`).
+    pub max_reply_bytes: u64,
+    /// Maximum number of rows loaded from the keyspace (`MAXLOADEDROWS`).
+    pub max_rows_loaded: u64,
+    /// Maximum number of distinct groups in a single `GROUPBY` (`MAXGROUPBYCARDINALITY`).
+    pub max_group_cardinality: u64,
+    /// Maximum temporary memory held by the query at any time, in bytes (`MAXQUERYMEMORY`).
+    pub max_temp_memory: u64,
+}
+
+/// The resource a guardrail protects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Guardrail {
+    ReplySize,
+    RowsLoaded,
+    GroupCardinality,
+    TempMemory,
+}
+
+impl Guardrail {
+    /// The name of the configuration option setting this guardrail.
+    pub const fn config_name(self) -> &'static str {
+        match self {
+            Self::ReplySize => "MAXREPLYSIZE",
+            Self::RowsLoaded => "MAXLOADEDROWS",
+            Self::GroupCardinality => "MAXGROUPBYCARDINALITY",
+            Self::TempMemory => "MAXQUERYMEMORY",
+        }
+    }
+}
+
+/// A query crossed one of its [`QueryLimits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitExceeded {
+    pub guardrail: Guardrail,
+    pub limit: u64,
+    /// The amount the query would have reached, had it not been stopped.
+    pub requested: u64,
+}
+
+impl Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Query exceeded {} (limit {}, requested {})",
+            self.guardrail.config_name(),
+            self.limit,
+            self.requested
+        )
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+/// What a query has consumed so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BudgetUsage {
+    pub reply_bytes: u64,
+    pub rows_loaded: u64,
+    /// The largest group count reported by any `GROUPBY` step.
+    pub max_group_cardinality: u64,
+    pub temp_memory: u64,
+    pub peak_temp_memory: u64,
+}
+
+/// Tracks the resources consumed by a single query against its limits.
+#[derive(Debug, Clone, Default)]
+pub struct QueryBudget {
+    limits: QueryLimits,
+    usage: BudgetUsage,
+}
+
+impl QueryBudget {
+    pub const fn new(limits: QueryLimits) -> Self {
+        Self {
+            limits,
+            usage: BudgetUsage {
+                reply_bytes: 0,
+                rows_loaded: 0,
+                max_group_cardinality: 0,
+                temp_memory: 0,
+                peak_temp_memory: 0,
+            },
+        }
+    }
+
+    pub const fn limits(&self) -> &QueryLimits {
+        &self.limits
+    }
+
+    pub const fn usage(&self) -> &BudgetUsage {
+        &self.usage
+    }
+
+    /// Account for `bytes` more bytes written to the reply.
+    pub fn add_reply_bytes(&mut self, bytes: u64) -> Result<(), LimitExceeded> {
+        self.usage.reply_bytes = add(
+            self.usage.reply_bytes,
+            bytes,
+            self.limits.max_reply_bytes,
+            Guardrail::ReplySize,
+        )?;
+        Ok(())
+    }
+
+    /// Account for `rows` more rows loaded from the keyspace.
+    pub fn add_rows_loaded(&mut self, rows: u64) -> Result<(), LimitExceeded> {
+        self.usage.rows_loaded = add(
+            self.usage.rows_loaded,
+            rows,
+            self.limits.max_rows_loaded,
+            Guardrail::RowsLoaded,
+        )?;
+        Ok(())
+    }
+
+    /// Check the number of distinct groups a `GROUPBY` step currently holds.
+    ///
+    /// Steps call this whenever they create a new group.
+    pub fn check_group_cardinality(&mut self, groups: u64) -> Result<(), LimitExceeded> {
+        check(
+            groups,
+            self.limits.max_group_cardinality,
+            Guardrail::GroupCardinality,
+        )?;
+        if groups > self.usage.max_group_cardinality {
+            self.usage.max_group_cardinality = groups;
+        }
+        Ok(())
+    }
+
+    /// Account for `bytes` of temporary memory being allocated.
+    ///
+    /// On error the allocation is not accounted for and must not be performed.
+    pub fn alloc_temp(&mut self, bytes: u64) -> Result<(), LimitExceeded> {
+        self.usage.temp_memory = add(
+            self.usage.temp_memory,
+            bytes,
+            self.limits.max_temp_memory,
+            Guardrail::TempMemory,
+        )?;
+        if self.usage.temp_memory > self.usage.peak_temp_memory {
+            self.usage.peak_temp_memory = self.usage.temp_memory;
+        }
+        Ok(())
+    }
+
+    /// Account for `bytes` of temporary memory being released.
+    pub const fn free_temp(&mut self, bytes: u64) {
+        debug_assert!(
+            bytes <= self.usage.temp_memory,
+            "freeing more than allocated"
+        );
+        self.usage.temp_memory = self.usage.temp_memory.saturating_sub(bytes);
+    }
+}
+
+const fn add(
+    current: u64,
+    delta: u64,
+    limit: u64,
+    guardrail: Guardrail,
+) -> Result<u64, LimitExceeded> {
+    let requested = current.saturating_add(delta);
+    match check(requested, limit, guardrail) {
+        Ok(()) => Ok(requested),
+        Err(e) => Err(e),
+    }
+}
+
+const fn check(requested: u64, limit: u64, guardrail: Guardrail) -> Result<(), LimitExceeded> {
+    if limit != 0 && requested > limit {
+        Err(LimitExceeded {
+            guardrail,
+            limit,
+            requested,
+        })
+    } else {
+        Ok(())
+    }
+}
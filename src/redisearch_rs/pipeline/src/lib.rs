@@ -0,0 +1,29 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Per-query execution state shared by the stages of the query pipeline,
+//! from the iterators down to the reply builder.
+
+pub mod acl;
+pub mod admission;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod cursor_groups;
+pub mod export;
+pub mod external_sort;
+pub mod fanout;
+pub mod guardrails;
+pub mod missing_docs;
+pub mod optimizer;
+pub mod projection;
+pub mod replica;
+pub mod reply_stream;
+pub mod sampling;
+pub mod slowlog;
+pub mod trace;
@@ -0,0 +1,95 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Negative cache for documents that vanished from the keyspace.
+//!
+//! A document may be deleted or evicted between the moment the index returns
+//! its id and the moment `LOAD` opens its key. A query can encounter the same
+//! document several times (e.g. once per loader, or again after a cursor
+//! read), and under heavy eviction each failed key open is expensive. The
+//! [`MissingDocs`] cache remembers the ids whose key couldn't be opened, so
+//! they're skipped for the rest of the query, and counts them for the
+//! `missing_docs` profile counter.
+
+use std::collections::HashSet;
+
+/// Per-query negative cache of document ids whose key is missing.
+#[derive(Debug, Clone)]
+pub struct MissingDocs {
+    ids: HashSet<u64>,
+    capacity: usize,
+    missing_docs: u64,
+    skipped_lookups: u64,
+}
+
+impl MissingDocs {
+    /// Default number of ids remembered by a query.
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    /// Create a cache remembering up to `capacity` missing ids. Ids found
+    /// missing once the cache is full are still counted, but not remembered.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            ids: HashSet::new(),
+            capacity,
+            missing_docs: 0,
+            skipped_lookups: 0,
+        }
+    }
+
+    /// Load the document `doc_id` with `open`, unless it's already known to be
+    /// missing.
+    ///
+    /// `open` returns `None` when the key doesn't exist, in which case the id
+    /// is remembered.
+    pub fn load<T>(&mut self, doc_id: u64, open: impl FnOnce(u64) -> Option<T>) -> Option<T> {
+        if self.is_missing(doc_id) {
+            self.skipped_lookups += 1;
+            return None;
+        }
+        let loaded = open(doc_id);
+        if loaded.is_none() {
+            self.record_missing(doc_id);
+        }
+        loaded
+    }
+
+    /// Whether `doc_id` is known to be missing.
+    pub fn is_missing(&self, doc_id: u64) -> bool {
+        self.ids.contains(&doc_id)
+    }
+
+    /// Record that the key of `doc_id` couldn't be opened.
+    pub fn record_missing(&mut self, doc_id: u64) {
+        if self.ids.contains(&doc_id) {
+            return;
+        }
+        self.missing_docs += 1;
+        if self.ids.len() < self.capacity {
+            self.ids.insert(doc_id);
+        }
+    }
+
+    /// The number of times a document was found missing, reported as
+    /// `missing_docs`. Documents remembered by the cache are only counted once.
+    pub const fn missing_docs(&self) -> u64 {
+        self.missing_docs
+    }
+
+    /// The number of key lookups avoided thanks to the cache.
+    pub const fn skipped_lookups(&self) -> u64 {
+        self.skipped_lookups
+    }
+}
+
+impl Default for MissingDocs {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
@@ -0,0 +1,226 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Rewriting the steps of an aggregation pipeline before it's built.
+//!
+//! Pipelines are executed in the order the steps are written, which is
+//! rarely the cheapest. The [optimizer](optimize) applies three rewrites,
+//! each of which leaves the reply unchanged:
+//!
+//! - Dead steps are removed: `APPLY` expressions whose alias no later step
+//!   reads and the reply doesn't return, and `LOAD`s of no such key.
+//! - A `FILTER` is moved before a `LOAD` when it only reads keys which the
+//!   `LOAD` doesn't fetch, such as those read from the sorting vector, so
+//!   that rows are dropped before their fields are loaded.
+//! - Consecutive `APPLY` steps are merged into a single step evaluating the
+//!   expressions in order, saving a result processor per row.
+//!
+//! `FT.EXPLAIN` of an aggregation shows the plan [before and
+//! after](Optimized::explain) the rewrites.
+
+use std::collections::BTreeSet;
+
+use crate::projection::KeySet;
+
+/// An `APPLY {expr} AS {alias}` expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignment {
+    pub alias: String,
+    pub expr: String,
+    /// The keys the expression reads.
+    pub reads: Vec<String>,
+}
+
+/// A step of an aggregation pipeline, as seen by the optimizer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanStep {
+    /// Read the given keys from the sorting vector.
+    SortableRead(Vec<String>),
+    /// Load the given fields from the document, or all of them for `LOAD *`.
+    Load(Option<Vec<String>>),
+    /// Evaluate the expressions, in order. Written steps have a single one.
+    Apply(Vec<Assignment>),
+    Filter {
+        expr: String,
+        reads: Vec<String>,
+    },
+    /// A step the optimizer doesn't move across, e.g. `SORTBY` or `LIMIT`.
+    Other {
+        description: String,
+        reads: Vec<String>,
+        writes: Vec<String>,
+    },
+    /// Replace the rows with new rows holding only `writes`, e.g. `GROUPBY`.
+    Group {
+        description: String,
+        reads: Vec<String>,
+        writes: Vec<String>,
+    },
+}
+
+impl PlanStep {
+    /// The line of the step in `FT.EXPLAIN`.
+    pub fn explain(&self) -> String {
+        let keys = |keys: &[String]| {
+            let mut line = keys.len().to_string();
+            for key in keys {
+                line.push_str(" @");
+                line.push_str(key);
+            }
+            line
+        };
+        match self {
+            Self::SortableRead(read) => format!("SORTABLES {}", keys(read)),
+            Self::Load(None) => "LOAD *".to_owned(),
+            Self::Load(Some(loaded)) => format!("LOAD {}", keys(loaded)),
+            Self::Apply(assignments) => {
+                let exprs: Vec<_> = assignments
+                    .iter()
+                    .map(|a| format!("{} AS {}", a.expr, a.alias))
+                    .collect();
+                format!("APPLY {}", exprs.join(", "))
+            }
+            Self::Filter { expr, .. } => format!("FILTER {expr}"),
+            Self::Other { description, .. } | Self::Group { description, .. } => {
+                description.clone()
+            }
+        }
+    }
+}
+
+/// A pipeline before and after optimization.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Optimized {
+    pub before: Vec<PlanStep>,
+    pub after: Vec<PlanStep>,
+}
+
+impl Optimized {
+    /// The lines of `FT.EXPLAIN` for the pipeline: the plan as written, then
+    /// as executed.
+    pub fn explain(&self) -> Vec<String> {
+        let mut lines = vec!["BEFORE".to_owned()];
+        lines.extend(self.before.iter().map(|s| format!("  {}", s.explain())));
+        lines.push("AFTER".to_owned());
+        lines.extend(self.after.iter().map(|s| format!("  {}", s.explain())));
+        lines
+    }
+}
+
+/// Optimize `steps`, in pipeline order, for a reply returning `returned`.
+pub fn optimize(steps: Vec<PlanStep>, returned: &KeySet) -> Optimized {
+    let before = steps.clone();
+    let mut after = remove_dead_steps(steps, returned);
+    hoist_filters(&mut after);
+    merge_applies(&mut after);
+    Optimized { before, after }
+}
+
+/// Remove the expressions and loads whose outputs are never read, walking
+/// the steps backwards while tracking the keys still needed.
+fn remove_dead_steps(steps: Vec<PlanStep>, returned: &KeySet) -> Vec<PlanStep> {
+    let mut live = returned.clone();
+    let mut kept = Vec::with_capacity(steps.len());
+    for step in steps.into_iter().rev() {
+        match step {
+            PlanStep::SortableRead(ref keys) | PlanStep::Load(Some(ref keys)) => {
+                if !keys.iter().any(|k| live.contains(k)) {
+                    continue;
+                }
+                live.remove_all(keys);
+            }
+            // The keys loaded aren't known, so everything before stays.
+            PlanStep::Load(None) => {}
+            PlanStep::Apply(assignments) => {
+                let mut needed = Vec::with_capacity(assignments.len());
+                for assignment in assignments.into_iter().rev() {
+                    if live.contains(&assignment.alias) {
+                        live.remove_all([&assignment.alias]);
+                        live.insert_all(&assignment.reads);
+                        needed.push(assignment);
+                    }
+                }
+                if !needed.is_empty() {
+                    needed.reverse();
+                    kept.push(PlanStep::Apply(needed));
+                }
+                continue;
+            }
+            PlanStep::Filter { ref reads, .. } => live.insert_all(reads),
+            PlanStep::Other {
+                ref reads,
+                ref writes,
+                ..
+            } => {
+                live.remove_all(writes);
+                live.insert_all(reads);
+            }
+            PlanStep::Group { ref reads, .. } => live = KeySet::of(reads.iter().cloned()),
+        }
+        kept.push(step);
+    }
+    kept.reverse();
+    kept
+}
+
+/// Move every `FILTER` before the `LOAD`s it doesn't depend on.
+fn hoist_filters(steps: &mut [PlanStep]) {
+    for i in 1..steps.len() {
+        let mut j = i;
+        while j > 0 && can_hoist(steps, j) {
+            steps.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+/// Whether the step at `i` is a `FILTER` which can be moved before the
+/// `LOAD` at `i - 1`: every key it reads must be provided by an earlier step
+/// and not fetched by the `LOAD`, which `LOAD *` only guarantees for keys read
+/// from the sorting vector.
+fn can_hoist(steps: &[PlanStep], i: usize) -> bool {
+    let (PlanStep::Load(loaded), PlanStep::Filter { reads, .. }) = (&steps[i - 1], &steps[i])
+    else {
+        return false;
+    };
+    let mut written = BTreeSet::new();
+    let mut sortable = BTreeSet::new();
+    for step in &steps[..i - 1] {
+        match step {
+            PlanStep::SortableRead(keys) => sortable.extend(keys),
+            PlanStep::Load(Some(keys)) => written.extend(keys),
+            PlanStep::Load(None) | PlanStep::Filter { .. } => {}
+            PlanStep::Apply(assignments) => written.extend(assignments.iter().map(|a| &a.alias)),
+            PlanStep::Other { writes, .. } => written.extend(writes),
+            PlanStep::Group { writes, .. } => {
+                sortable.clear();
+                written = writes.iter().collect();
+            }
+        }
+    }
+    reads.iter().all(|key| match loaded {
+        None => sortable.contains(key),
+        Some(keys) => (sortable.contains(key) || written.contains(key)) && !keys.contains(key),
+    })
+}
+
+/// Merge consecutive `APPLY` steps.
+fn merge_applies(steps: &mut Vec<PlanStep>) {
+    let mut merged: Vec<PlanStep> = Vec::with_capacity(steps.len());
+    for step in steps.drain(..) {
+        if let (Some(PlanStep::Apply(previous)), PlanStep::Apply(assignments)) =
+            (merged.last_mut(), &step)
+        {
+            previous.extend(assignments.iter().cloned());
+            continue;
+        }
+        merged.push(step);
+    }
+    *steps = merged;
+}
@@ -0,0 +1,146 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Projection pushdown: which fields the `LOAD` and sortable-read steps fetch.
+//!
+//! A step that fetches fields from the document or its sorting vector only
+//! needs to fetch the keys some later step reads, or the reply returns. With
+//! `LOAD *` on a wide schema, most of the fields loaded are never used. The
+//! [`Projection`] walks the steps of the pipeline backwards once, when the
+//! pipeline is built, tracking the keys still needed, and records for each
+//! fetching step the keys it must fetch.
+//!
+//! Keys read from the sorting vector are cheaper than loaded ones, so when the
+//! keys needed are known, `LOAD *` doesn't load those provided by an earlier
+//! sortable read.
+
+use std::collections::BTreeSet;
+
+/// A set of keys, which may be all of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySet {
+    /// Every key, e.g. the reply of a query without `RETURN`.
+    All,
+    Only(BTreeSet<String>),
+}
+
+impl KeySet {
+    /// A set of the given keys.
+    pub fn of<S: Into<String>>(keys: impl IntoIterator<Item = S>) -> Self {
+        Self::Only(keys.into_iter().map(Into::into).collect())
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Only(keys) => keys.contains(key),
+        }
+    }
+
+    /// The keys of `keys` in this set.
+    fn intersection(&self, keys: &[String]) -> BTreeSet<String> {
+        keys.iter().filter(|k| self.contains(k)).cloned().collect()
+    }
+
+    pub(crate) fn remove_all<'k>(&mut self, keys: impl IntoIterator<Item = &'k String>) {
+        if let Self::Only(set) = self {
+            for key in keys {
+                set.remove(key);
+            }
+        }
+    }
+
+    pub(crate) fn insert_all<'k>(&mut self, keys: impl IntoIterator<Item = &'k String>) {
+        if let Self::Only(set) = self {
+            set.extend(keys.into_iter().cloned());
+        }
+    }
+}
+
+/// A step of the pipeline, as seen by the projection analysis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// Read the given keys from the sorting vector.
+    SortableRead(Vec<String>),
+    /// Load the given fields from the document, or all of them for `LOAD *`.
+    Load(Option<Vec<String>>),
+    /// Compute `writes` from `reads`, e.g. `APPLY`, `FILTER` or `SORTBY`.
+    Compute {
+        reads: Vec<String>,
+        writes: Vec<String>,
+    },
+    /// Replace the rows with new rows holding only `writes`, computed from
+    /// `reads`, e.g. `GROUPBY`.
+    Group {
+        reads: Vec<String>,
+        writes: Vec<String>,
+    },
+}
+
+/// The keys each fetching step of a pipeline must fetch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Projection {
+    /// For each step, the keys it fetches, or `None` if it doesn't fetch.
+    fetch: Vec<Option<KeySet>>,
+}
+
+impl Projection {
+    /// Analyze `steps`, in pipeline order, for a reply returning `returned`.
+    pub fn analyze(steps: &[Step], returned: KeySet) -> Self {
+        // The keys known to be in the rows before each step.
+        let mut available = Vec::with_capacity(steps.len());
+        let mut known = BTreeSet::new();
+        for step in steps {
+            available.push(known.clone());
+            match step {
+                Step::SortableRead(keys) | Step::Load(Some(keys)) => known.extend(keys.clone()),
+                Step::Load(None) => {}
+                Step::Compute { writes, .. } => known.extend(writes.clone()),
+                Step::Group { writes, .. } => known = writes.iter().cloned().collect(),
+            }
+        }
+
+        let mut fetch = vec![None; steps.len()];
+        let mut live = returned;
+        for (i, step) in steps.iter().enumerate().rev() {
+            match step {
+                Step::SortableRead(keys) | Step::Load(Some(keys)) => {
+                    let fetched = live.intersection(keys);
+                    live.remove_all(&fetched);
+                    fetch[i] = Some(KeySet::Only(fetched));
+                }
+                Step::Load(None) => {
+                    // The keys provided by earlier steps are still needed
+                    // from them, everything else is loaded here.
+                    let (fetched, upstream) = match live {
+                        KeySet::All => (KeySet::All, available[i].clone()),
+                        KeySet::Only(keys) => {
+                            let (upstream, fetched) =
+                                keys.into_iter().partition(|k| available[i].contains(k));
+                            (KeySet::Only(fetched), upstream)
+                        }
+                    };
+                    live = KeySet::Only(upstream);
+                    fetch[i] = Some(fetched);
+                }
+                Step::Compute { reads, writes } => {
+                    live.remove_all(writes);
+                    live.insert_all(reads);
+                }
+                Step::Group { reads, .. } => live = KeySet::of(reads),
+            }
+        }
+        Self { fetch }
+    }
+
+    /// The keys step `step` must fetch, or `None` if it isn't a fetching step.
+    pub fn fetch(&self, step: usize) -> Option<&KeySet> {
+        self.fetch.get(step)?.as_ref()
+    }
+}
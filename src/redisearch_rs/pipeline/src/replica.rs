@@ -0,0 +1,204 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Read-only query execution on replicas.
+//!
+//! A replica must not modify its dataset on its own: all the changes come from
+//! the replication stream. Query execution however has a few opportunistic
+//! write paths, such as repairing posting lists of deleted documents or
+//! deleting expired keys on access. On a replica, every such path must ask the
+//! [`ExecutionMode`] first, and skip the mutation when it's denied.
+//!
+//! Replicas also don't expire keys themselves, they wait for the master's
+//! `DEL`. Until it arrives, a document may be logically expired yet still
+//! present: it must be hidden from results, but left in place.
+//!
+//! The documents returned by the index are loaded through a [`Loader`],
+//! which applies both rules: expired keys are only deleted, and the posting
+//! lists only repaired, when the mode allows it.
+
+use std::{
+    cell::Cell,
+    fmt::{self, Display},
+};
+
+use crate::missing_docs::MissingDocs;
+
+/// The replication role of the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ServerRole {
+    #[default]
+    Master,
+    Replica,
+}
+
+/// A structural change query execution may want to make opportunistically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mutation {
+    /// Removing deleted documents from a posting list while reading it.
+    GcRepair,
+    /// Populating a cache shared across queries.
+    CacheWarm,
+    /// Deleting a key found expired.
+    ExpireKey,
+    /// Updating per-document metadata, e.g. the field expiration table.
+    DocMetadata,
+}
+
+/// A mutation was attempted in read-only mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadOnlyViolation {
+    pub mutation: Mutation,
+}
+
+impl Display for ReadOnlyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} is not allowed in read-only mode", self.mutation)
+    }
+}
+
+impl std::error::Error for ReadOnlyViolation {}
+
+/// What a query should do with a document, given its expiration time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiration {
+    /// The document is live.
+    Live,
+    /// The document is expired and must not be returned. `delete` tells
+    /// whether the query may delete it.
+    Expired { delete: bool },
+}
+
+/// Whether a query may mutate shared structures.
+#[derive(Debug, Default)]
+pub struct ExecutionMode {
+    role: ServerRole,
+    read_only: bool,
+    skipped: Cell<u64>,
+}
+
+impl ExecutionMode {
+    /// The mode for a query running on a server with the given role: read
+    /// only on replicas.
+    pub const fn for_role(role: ServerRole) -> Self {
+        Self {
+            role,
+            read_only: matches!(role, ServerRole::Replica),
+            skipped: Cell::new(0),
+        }
+    }
+
+    /// A read-only mode, regardless of the role (e.g. for `FT.PROFILE` or
+    /// queries run from a read-only script).
+    pub const fn read_only() -> Self {
+        Self {
+            role: ServerRole::Master,
+            read_only: true,
+            skipped: Cell::new(0),
+        }
+    }
+
+    pub const fn role(&self) -> ServerRole {
+        self.role
+    }
+
+    pub const fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Check whether `mutation` is allowed. Denied mutations are counted.
+    pub fn check(&self, mutation: Mutation) -> Result<(), ReadOnlyViolation> {
+        if self.read_only {
+            self.skipped.set(self.skipped.get() + 1);
+            Err(ReadOnlyViolation { mutation })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Run `f` if `mutation` is allowed.
+    pub fn mutate<T>(&self, mutation: Mutation, f: impl FnOnce() -> T) -> Option<T> {
+        self.check(mutation).ok().map(|()| f())
+    }
+
+    /// Classify a document expiring at `expire_at_ms` (if ever), at `now_ms`.
+    ///
+    /// Expired documents are hidden on both masters and replicas, but only
+    /// deleted on the former.
+    pub fn expiration(&self, expire_at_ms: Option<u64>, now_ms: u64) -> Expiration {
+        match expire_at_ms {
+            Some(at) if at <= now_ms => Expiration::Expired {
+                delete: self.check(Mutation::ExpireKey).is_ok(),
+            },
+            _ => Expiration::Live,
+        }
+    }
+
+    /// The number of mutations skipped because of the read-only mode.
+    pub const fn skipped_mutations(&self) -> u64 {
+        self.skipped.get()
+    }
+}
+
+/// Loads the documents returned by the index for a query, going through the
+/// [`ExecutionMode`] before every write.
+#[derive(Debug)]
+pub struct Loader<'m> {
+    mode: &'m ExecutionMode,
+    missing: MissingDocs,
+    stale: Vec<u64>,
+}
+
+impl<'m> Loader<'m> {
+    pub const fn new(mode: &'m ExecutionMode, missing: MissingDocs) -> Self {
+        Self {
+            mode,
+            missing,
+            stale: Vec::new(),
+        }
+    }
+
+    /// Load the document `doc_id` at `now_ms`.
+    ///
+    /// `open` returns the document with its expiration time, or `None` when
+    /// its key doesn't exist. An expired document isn't returned, and is
+    /// deleted with `delete` if the mode allows it. Documents that are gone
+    /// are remembered for [`Loader::finish`].
+    pub fn load<T>(
+        &mut self,
+        doc_id: u64,
+        now_ms: u64,
+        open: impl FnOnce(u64) -> Option<(T, Option<u64>)>,
+        delete: impl FnOnce(u64),
+    ) -> Option<T> {
+        let Some((doc, expire_at)) = self.missing.load(doc_id, open) else {
+            self.stale.push(doc_id);
+            return None;
+        };
+        match self.mode.expiration(expire_at, now_ms) {
+            Expiration::Live => Some(doc),
+            Expiration::Expired { delete: true } => {
+                delete(doc_id);
+                self.stale.push(doc_id);
+                None
+            }
+            Expiration::Expired { delete: false } => None,
+        }
+    }
+
+    /// Remove the documents found gone from the posting lists with `repair`,
+    /// if the mode allows it, and return the negative cache of the query.
+    pub fn finish(self, repair: impl FnOnce(Vec<u64>)) -> MissingDocs {
+        let stale = self.stale;
+        if !stale.is_empty() {
+            self.mode.mutate(Mutation::GcRepair, || repair(stale));
+        }
+        self.missing
+    }
+}
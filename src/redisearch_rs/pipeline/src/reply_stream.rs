@@ -0,0 +1,203 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Incremental reply building for `FT.SEARCH`, `FT.AGGREGATE` and
+//! `FT.CURSOR READ`.
+//!
+//! Rather than buffering every row before replying, the [`ReplyStreamer`]
+//! writes rows to the [`ReplySink`] as the pipeline produces them, inside
+//! arrays whose length is set once the rows are known (the module API's
+//! postponed lengths). Whenever more than [`ReplyStreamer::chunk_bytes`] are
+//! buffered, the sink is flushed to the client, if its context allows it: a
+//! reply built from a background thread stays buffered until the client is
+//! unblocked. The memory held by a large export is then bounded by the chunk
+//! size instead of the size of the reply.
+//!
+//! Replies with and without `WITHCURSOR` go through [`ReplyStreamer::stream`];
+//! a cursor read is a stream stopped after `COUNT` rows.
+
+use crate::guardrails::{LimitExceeded, QueryBudget};
+
+/// The default number of bytes buffered before flushing.
+pub const DEFAULT_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Produces the rows of a reply.
+pub trait RowSource {
+    type Row;
+
+    /// The next row, or `None` once the query is exhausted.
+    fn next_row(&mut self) -> Option<Self::Row>;
+
+    /// The total number of results, as reported at the start of the reply.
+    /// Called after the first row has been read, since some pipelines only
+    /// know it once they started running.
+    fn total_results(&self) -> u64;
+}
+
+/// Where the reply is written, typically a Redis module context.
+pub trait ReplySink<R> {
+    /// Start an array whose length is set by the matching
+    /// [`ReplySink::end_array`].
+    fn begin_array(&mut self);
+
+    /// Set the length of the innermost array started by
+    /// [`ReplySink::begin_array`].
+    fn end_array(&mut self, len: usize);
+
+    fn write_int(&mut self, value: i64);
+
+    /// Write a row, returning the number of bytes it added to the reply.
+    fn write_row(&mut self, row: &R) -> usize;
+
+    /// The bytes written and not flushed to the client yet.
+    fn buffered_bytes(&self) -> usize;
+
+    /// Whether [`ReplySink::flush`] may be called at this point.
+    fn can_flush(&self) -> bool;
+
+    /// Send the buffered bytes to the client.
+    fn flush(&mut self);
+}
+
+/// How much of the results a stream replies with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyMode {
+    /// All the rows, in a single reply.
+    Full,
+    /// At most `count` rows, followed by the id of the cursor the remaining
+    /// rows can be read from, as for `WITHCURSOR` and `FT.CURSOR READ`.
+    Cursor { id: u64, count: usize },
+}
+
+/// How a stream ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamEnd {
+    /// The source is exhausted. A cursor, if any, can be freed.
+    Done,
+    /// The source has more rows for the next `FT.CURSOR READ`.
+    Paused,
+}
+
+/// Counters of the streams written by a [`ReplyStreamer`], for `FT.PROFILE`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamStats {
+    pub rows: u64,
+    pub flushes: u64,
+    /// The most bytes buffered at once.
+    pub peak_buffered_bytes: usize,
+}
+
+/// Writes rows to a reply incrementally, see the [module](self) docs.
+#[derive(Debug, Clone)]
+pub struct ReplyStreamer {
+    chunk_bytes: usize,
+    stats: StreamStats,
+}
+
+impl Default for ReplyStreamer {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHUNK_BYTES)
+    }
+}
+
+impl ReplyStreamer {
+    /// A streamer flushing whenever `chunk_bytes` or more are buffered.
+    pub const fn new(chunk_bytes: usize) -> Self {
+        Self {
+            chunk_bytes,
+            stats: StreamStats {
+                rows: 0,
+                flushes: 0,
+                peak_buffered_bytes: 0,
+            },
+        }
+    }
+
+    pub const fn chunk_bytes(&self) -> usize {
+        self.chunk_bytes
+    }
+
+    pub const fn stats(&self) -> &StreamStats {
+        &self.stats
+    }
+
+    /// Write the reply for `source` to `sink`.
+    ///
+    /// The reply is `[total, rows...]`, wrapped as `[[total, rows...],
+    /// cursor_id]` in [`ReplyMode::Cursor`]. The cursor id is `0` once the
+    /// source is exhausted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LimitExceeded`] once the rows written exceed
+    /// `MAXREPLYSIZE`. The arrays are closed with the rows written so far,
+    /// so the reply stays well-formed, and the caller reports the error.
+    pub fn stream<S, R>(
+        &mut self,
+        source: &mut S,
+        sink: &mut R,
+        mode: ReplyMode,
+        budget: &mut QueryBudget,
+    ) -> Result<StreamEnd, LimitExceeded>
+    where
+        S: RowSource,
+        R: ReplySink<S::Row>,
+    {
+        let (cursor, limit) = match mode {
+            ReplyMode::Full => (None, usize::MAX),
+            ReplyMode::Cursor { id, count } => {
+                sink.begin_array();
+                (Some(id), count)
+            }
+        };
+
+        sink.begin_array();
+        let mut row = if limit > 0 { source.next_row() } else { None };
+        sink.write_int(source.total_results() as i64);
+        let mut written = 0;
+        let mut result = Ok(());
+        while let Some(current) = row {
+            let bytes = sink.write_row(&current);
+            written += 1;
+            self.stats.rows += 1;
+            self.stats.peak_buffered_bytes =
+                self.stats.peak_buffered_bytes.max(sink.buffered_bytes());
+            if let Err(e) = budget.add_reply_bytes(bytes as u64) {
+                result = Err(e);
+                break;
+            }
+            if sink.buffered_bytes() >= self.chunk_bytes && sink.can_flush() {
+                sink.flush();
+                self.stats.flushes += 1;
+            }
+            row = if written < limit {
+                source.next_row()
+            } else {
+                None
+            };
+        }
+        // The total is followed by the rows.
+        sink.end_array(written + 1);
+
+        // Without a cursor, a stream only ends once the source is exhausted.
+        let end = match cursor {
+            Some(_) if result.is_ok() && written == limit => StreamEnd::Paused,
+            _ => StreamEnd::Done,
+        };
+        if let Some(id) = cursor {
+            sink.write_int(if end == StreamEnd::Paused {
+                id as i64
+            } else {
+                0
+            });
+            sink.end_array(2);
+        }
+        result.map(|()| end)
+    }
+}
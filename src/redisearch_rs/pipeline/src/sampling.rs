@@ -0,0 +1,240 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Reproducible randomness for sampling and tie-breaking.
+//!
+//! The `RANDOM_SAMPLE` reducer, the `SAMPLE` step and the tie-breaking of
+//! equal sort keys draw from a [`QuerySeed`]. Without `SEED`, every query gets
+//! a fresh random seed as before. With `SEED {n}`, repeated runs of a query
+//! return the same samples and the same order, which QA and pagination
+//! snapshots rely on.
+//!
+//! Each consumer derives its own [`SeededRng`] from the seed and a stable
+//! name, e.g. the reducer's alias and the group's key, so that the results
+//! don't depend on the order in which groups or steps are processed.
+
+use std::{
+    collections::hash_map::RandomState,
+    fmt::{self, Display},
+    hash::{BuildHasher, Hasher},
+};
+
+const SEED_OPT: &str = "SEED";
+const SAMPLE_OPT: &str = "SAMPLE";
+
+/// An invalid `SEED` or `SAMPLE` argument.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SamplingError {
+    MissingArgument(&'static str),
+    BadValue { option: &'static str, value: String },
+    DuplicateOption(&'static str),
+}
+
+impl Display for SamplingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingArgument(option) => write!(f, "Missing argument for {option}"),
+            Self::BadValue { option, value } => write!(f, "Bad value for {option}: {value}"),
+            Self::DuplicateOption(option) => write!(f, "Option {option} given more than once"),
+        }
+    }
+}
+
+impl std::error::Error for SamplingError {}
+
+/// The seed of the randomness of a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuerySeed {
+    seed: u64,
+    explicit: bool,
+}
+
+impl Default for QuerySeed {
+    /// A random seed.
+    fn default() -> Self {
+        Self {
+            seed: RandomState::new().build_hasher().finish(),
+            explicit: false,
+        }
+    }
+}
+
+impl QuerySeed {
+    pub const fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            explicit: true,
+        }
+    }
+
+    /// Try to handle the query option `name`, consuming its argument from
+    /// `args`: `SEED {n}`, with `n` an unsigned 64-bit integer.
+    ///
+    /// Returns `Ok(false)` if `name` is not `SEED`, leaving it to the caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SamplingError`] if the seed is missing or invalid, or if
+    /// it was already given.
+    pub fn try_parse_option<'a>(
+        &mut self,
+        name: &str,
+        args: &mut impl Iterator<Item = &'a str>,
+    ) -> Result<bool, SamplingError> {
+        if !name.eq_ignore_ascii_case(SEED_OPT) {
+            return Ok(false);
+        }
+        if self.explicit {
+            return Err(SamplingError::DuplicateOption(SEED_OPT));
+        }
+        let value = args
+            .next()
+            .ok_or(SamplingError::MissingArgument(SEED_OPT))?;
+        let seed = value.parse().map_err(|_| SamplingError::BadValue {
+            option: SEED_OPT,
+            value: value.to_owned(),
+        })?;
+        *self = Self::new(seed);
+        Ok(true)
+    }
+
+    pub const fn value(&self) -> u64 {
+        self.seed
+    }
+
+    /// Whether the seed was given with `SEED`, and should be reported in the
+    /// profile.
+    pub const fn is_explicit(&self) -> bool {
+        self.explicit
+    }
+
+    /// The generator of the consumer identified by `names`.
+    pub fn rng<'a>(&self, names: impl IntoIterator<Item = &'a [u8]>) -> SeededRng {
+        let mut state = self.seed;
+        for name in names {
+            state = mix(state ^ name.len() as u64);
+            for chunk in name.chunks(8) {
+                let mut word = [0; 8];
+                word[..chunk.len()].copy_from_slice(chunk);
+                state = mix(state ^ u64::from_le_bytes(word));
+            }
+        }
+        SeededRng { state }
+    }
+
+    /// A key ordering documents with equal sort keys. The order is random,
+    /// but the same for every run with the same seed.
+    pub const fn tie_breaker(&self, doc_id: u64) -> u64 {
+        mix(self.seed ^ mix(doc_id))
+    }
+}
+
+/// The SplitMix64 finalizer.
+const fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// A SplitMix64 generator, derived from a [`QuerySeed`].
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub const fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        mix(self.state)
+    }
+
+    /// A number in `0..bound`, `bound` being non-zero.
+    pub const fn below(&mut self, bound: u64) -> u64 {
+        // Multiply-shift, unbiased enough for sampling.
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+
+    /// A number in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// The state of a `RANDOM_SAMPLE` reducer: a uniform sample of at most `size`
+/// of the values added to it (reservoir sampling).
+#[derive(Debug, Clone)]
+pub struct Reservoir<T> {
+    size: usize,
+    seen: u64,
+    sample: Vec<T>,
+    rng: SeededRng,
+}
+
+impl<T> Reservoir<T> {
+    pub fn new(size: usize, rng: SeededRng) -> Self {
+        Self {
+            size,
+            seen: 0,
+            sample: Vec::with_capacity(size),
+            rng,
+        }
+    }
+
+    pub fn add(&mut self, value: T) {
+        if self.sample.len() < self.size {
+            self.sample.push(value);
+        } else {
+            let i = self.rng.below(self.seen + 1) as usize;
+            if i < self.size {
+                self.sample[i] = value;
+            }
+        }
+        self.seen += 1;
+    }
+
+    /// The number of values added.
+    pub const fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    pub fn into_sample(self) -> Vec<T> {
+        self.sample
+    }
+}
+
+/// The `SAMPLE {rate}` step, keeping each row with probability `rate`.
+#[derive(Debug, Clone)]
+pub struct Sample {
+    rate: f64,
+    rng: SeededRng,
+}
+
+impl Sample {
+    /// Parse the argument of `SAMPLE`, a rate in `(0, 1]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SamplingError::BadValue`] if the rate is invalid.
+    pub fn parse(rate: &str, rng: SeededRng) -> Result<Self, SamplingError> {
+        let bad_value = || SamplingError::BadValue {
+            option: SAMPLE_OPT,
+            value: rate.to_owned(),
+        };
+        let rate: f64 = rate.parse().map_err(|_| bad_value())?;
+        if !(rate > 0.0 && rate <= 1.0) {
+            return Err(bad_value());
+        }
+        Ok(Self { rate, rng })
+    }
+
+    /// Whether to keep the next row.
+    pub fn keep(&mut self) -> bool {
+        self.rng.next_f64() < self.rate
+    }
+}
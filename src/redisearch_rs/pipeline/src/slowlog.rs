@@ -0,0 +1,240 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The per-index slow log, read and reset with `FT._SLOWLOG`.
+//!
+//! Queries slower than the configured threshold are recorded with the time
+//! spent in each phase, which the Redis slow log can't tell apart: parsing,
+//! executing the iterators, loading documents and serializing the reply. The
+//! log keeps the most recent entries only.
+//!
+//! `FT._SLOWLOG {index} GET [count]` returns the entries, the most recent
+//! first, `FT._SLOWLOG {index} LEN` their number and `FT._SLOWLOG {index} RESET`
+//! clears the log.
+
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display},
+    sync::Mutex,
+    time::Duration,
+};
+
+/// The default number of entries kept.
+pub const DEFAULT_MAX_LEN: usize = 128;
+
+/// What replaces the values of parameters when they're redacted.
+pub const REDACTED: &str = "?";
+
+/// How queries are recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlowLogConfig {
+    /// Queries taking at least this long are recorded.
+    pub threshold: Duration,
+    /// The number of entries kept.
+    pub max_len: usize,
+    /// Whether the values of `PARAMS` are replaced by [`REDACTED`], as they
+    /// may hold user data.
+    pub redact_params: bool,
+}
+
+impl Default for SlowLogConfig {
+    fn default() -> Self {
+        Self {
+            threshold: Duration::from_millis(10),
+            max_len: DEFAULT_MAX_LEN,
+            redact_params: false,
+        }
+    }
+}
+
+/// The time spent in each phase of a query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryTimings {
+    pub parse: Duration,
+    pub execute: Duration,
+    pub load: Duration,
+    pub serialize: Duration,
+}
+
+impl QueryTimings {
+    pub fn total(&self) -> Duration {
+        self.parse + self.execute + self.load + self.serialize
+    }
+}
+
+/// A query, as reported to the slow log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowQuery<'a> {
+    pub query: &'a str,
+    pub params: &'a [(&'a str, &'a str)],
+    pub timings: QueryTimings,
+    /// The number of matching documents.
+    pub total_results: u64,
+    /// The number of results in the reply.
+    pub returned_results: u64,
+}
+
+/// A recorded query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowLogEntry {
+    /// Increases with every recorded query, and survives `RESET`.
+    pub id: u64,
+    /// When the query was recorded, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub query: String,
+    pub params: Vec<(String, String)>,
+    pub timings: QueryTimings,
+    pub total_results: u64,
+    pub returned_results: u64,
+}
+
+struct Entries {
+    entries: VecDeque<SlowLogEntry>,
+    next_id: u64,
+}
+
+/// See the [module documentation](self).
+pub struct SlowLog {
+    config: SlowLogConfig,
+    entries: Mutex<Entries>,
+}
+
+impl SlowLog {
+    pub const fn new(config: SlowLogConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(Entries {
+                entries: VecDeque::new(),
+                next_id: 0,
+            }),
+        }
+    }
+
+    pub const fn config(&self) -> &SlowLogConfig {
+        &self.config
+    }
+
+    /// Record `query`, finished at `timestamp`, if it's slower than the
+    /// threshold. Returns whether it was recorded.
+    pub fn record(&self, query: &SlowQuery<'_>, timestamp: u64) -> bool {
+        if self.config.max_len == 0 || query.timings.total() < self.config.threshold {
+            return false;
+        }
+        let params = query
+            .params
+            .iter()
+            .map(|(name, value)| {
+                let value = if self.config.redact_params {
+                    REDACTED
+                } else {
+                    value
+                };
+                ((*name).to_owned(), value.to_owned())
+            })
+            .collect();
+        let mut log = self.entries.lock().unwrap();
+        let entry = SlowLogEntry {
+            id: log.next_id,
+            timestamp,
+            query: query.query.to_owned(),
+            params,
+            timings: query.timings,
+            total_results: query.total_results,
+            returned_results: query.returned_results,
+        };
+        log.next_id += 1;
+        if log.entries.len() == self.config.max_len {
+            log.entries.pop_back();
+        }
+        log.entries.push_front(entry);
+        true
+    }
+
+    /// The `count` most recent entries, all of them if `None`, the most
+    /// recent first.
+    pub fn get(&self, count: Option<usize>) -> Vec<SlowLogEntry> {
+        let log = self.entries.lock().unwrap();
+        let count = count.unwrap_or(log.entries.len());
+        log.entries.iter().take(count).cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn reset(&self) {
+        self.entries.lock().unwrap().entries.clear();
+    }
+}
+
+/// A subcommand of `FT._SLOWLOG`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlowLogCommand {
+    Get(Option<usize>),
+    Len,
+    Reset,
+}
+
+/// Invalid arguments to `FT._SLOWLOG`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlowLogCommandError {
+    UnknownSubcommand(String),
+    BadCount(String),
+    WrongArity,
+}
+
+impl Display for SlowLogCommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownSubcommand(s) => write!(f, "Unknown subcommand `{s}`"),
+            Self::BadCount(count) => write!(f, "Invalid count `{count}`"),
+            Self::WrongArity => f.write_str("Wrong number of arguments"),
+        }
+    }
+}
+
+impl std::error::Error for SlowLogCommandError {}
+
+impl SlowLogCommand {
+    /// Parse the arguments following the index name.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SlowLogCommandError`] if the arguments are invalid.
+    pub fn parse(args: &[&str]) -> Result<Self, SlowLogCommandError> {
+        let (subcommand, rest) = args.split_first().ok_or(SlowLogCommandError::WrongArity)?;
+        let command = if subcommand.eq_ignore_ascii_case("GET") {
+            match rest {
+                [] => Self::Get(None),
+                [count] => {
+                    Self::Get(Some(count.parse().map_err(|_| {
+                        SlowLogCommandError::BadCount((*count).to_owned())
+                    })?))
+                }
+                _ => return Err(SlowLogCommandError::WrongArity),
+            }
+        } else if subcommand.eq_ignore_ascii_case("LEN") {
+            Self::Len
+        } else if subcommand.eq_ignore_ascii_case("RESET") {
+            Self::Reset
+        } else {
+            return Err(SlowLogCommandError::UnknownSubcommand(
+                (*subcommand).to_owned(),
+            ));
+        };
+        if !matches!(command, Self::Get(_)) && !rest.is_empty() {
+            return Err(SlowLogCommandError::WrongArity);
+        }
+        Ok(command)
+    }
+}
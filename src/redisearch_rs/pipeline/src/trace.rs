@@ -0,0 +1,281 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Tracing of the stages of a query, linked to the trace of the client.
+//!
+//! A client passes its W3C `traceparent` with the `TRACEPARENT` query option
+//! or in its client info. The query then runs in a [`QueryTrace`], and each of
+//! its stages (parse, plan, execute, load, serialize) in a [`StageGuard`].
+//!
+//! With the `tracing` feature, the query and its stages are `tracing` spans,
+//! named `search.query` and `search.<stage>`, carrying the trace and parent
+//! ids of the client. With the `otlp` feature, the query span is also made a
+//! child of the client's span in OpenTelemetry, so that an OTLP exporter
+//! installed by the module reports the time spent in Redis as part of the
+//! client's trace. Without either feature, only the durations of the stages
+//! are recorded, for the slow log.
+
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::slowlog::QueryTimings;
+
+/// A W3C trace context `traceparent` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceParent {
+    pub version: u8,
+    pub trace_id: [u8; 16],
+    /// The id of the client's span.
+    pub parent_id: [u8; 8],
+    pub flags: u8,
+}
+
+impl TraceParent {
+    /// Whether the client sampled the trace.
+    pub const fn sampled(&self) -> bool {
+        self.flags & 1 != 0
+    }
+}
+
+/// A `traceparent` couldn't be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceParentError {
+    /// The header isn't made of the expected dash-separated hex fields.
+    Malformed,
+    /// Version `ff` is forbidden.
+    InvalidVersion,
+    /// The trace id or the parent id is all zeros.
+    ZeroId,
+}
+
+impl Display for TraceParentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Malformed => "Malformed traceparent",
+            Self::InvalidVersion => "Invalid traceparent version",
+            Self::ZeroId => "Invalid all-zero id in traceparent",
+        })
+    }
+}
+
+impl std::error::Error for TraceParentError {}
+
+impl FromStr for TraceParent {
+    type Err = TraceParentError;
+
+    /// Parse `version-trace_id-parent_id-flags`. As the specification
+    /// requires, headers of versions after `00` may have more fields, which
+    /// are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.splitn(5, '-');
+        let mut next = || fields.next().ok_or(TraceParentError::Malformed);
+        let [version] = parse_hex(next()?)?;
+        let trace_id = parse_hex(next()?)?;
+        let parent_id = parse_hex(next()?)?;
+        let [flags] = parse_hex(next()?)?;
+        let rest = fields.next();
+        if version == 0xff {
+            return Err(TraceParentError::InvalidVersion);
+        }
+        if version == 0 && rest.is_some() {
+            return Err(TraceParentError::Malformed);
+        }
+        if trace_id == [0; 16] || parent_id == [0; 8] {
+            return Err(TraceParentError::ZeroId);
+        }
+        Ok(Self {
+            version,
+            trace_id,
+            parent_id,
+            flags,
+        })
+    }
+}
+
+/// Parse exactly `N` bytes of lowercase hex.
+fn parse_hex<const N: usize>(s: &str) -> Result<[u8; N], TraceParentError> {
+    if s.len() != 2 * N {
+        return Err(TraceParentError::Malformed);
+    }
+    let digit = |c: u8| match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        _ => Err(TraceParentError::Malformed),
+    };
+    let mut bytes = [0; N];
+    for (byte, pair) in bytes.iter_mut().zip(s.as_bytes().chunks_exact(2)) {
+        *byte = digit(pair[0])? << 4 | digit(pair[1])?;
+    }
+    Ok(bytes)
+}
+
+impl Display for TraceParent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02x}-", self.version)?;
+        for b in self.trace_id {
+            write!(f, "{b:02x}")?;
+        }
+        f.write_str("-")?;
+        for b in self.parent_id {
+            write!(f, "{b:02x}")?;
+        }
+        write!(f, "-{:02x}", self.flags)
+    }
+}
+
+/// A stage of a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Parse,
+    Plan,
+    Execute,
+    Load,
+    Serialize,
+}
+
+impl Stage {
+    /// The name of the stage's span.
+    pub const fn span_name(self) -> &'static str {
+        match self {
+            Self::Parse => "search.parse",
+            Self::Plan => "search.plan",
+            Self::Execute => "search.execute",
+            Self::Load => "search.load",
+            Self::Serialize => "search.serialize",
+        }
+    }
+}
+
+/// The trace of a query.
+#[derive(Debug)]
+pub struct QueryTrace {
+    parent: Option<TraceParent>,
+    timings: QueryTimings,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl QueryTrace {
+    /// Start tracing a query of `command`, as a child of the client's span if
+    /// it sent a `traceparent`.
+    pub fn new(command: &str, parent: Option<TraceParent>) -> Self {
+        #[cfg(not(feature = "tracing"))]
+        let _ = command;
+        Self {
+            parent,
+            timings: QueryTimings::default(),
+            #[cfg(feature = "tracing")]
+            span: spans::query_span(command, parent.as_ref()),
+        }
+    }
+
+    pub const fn parent(&self) -> Option<&TraceParent> {
+        self.parent.as_ref()
+    }
+
+    /// Run a stage until the returned guard is dropped.
+    pub fn stage(&mut self, stage: Stage) -> StageGuard<'_> {
+        StageGuard {
+            #[cfg(feature = "tracing")]
+            _entered: spans::stage_span(&self.span, stage).entered(),
+            trace: self,
+            stage,
+            start: Instant::now(),
+        }
+    }
+
+    /// The time spent in each stage so far. Planning is accounted as parsing.
+    pub const fn timings(&self) -> &QueryTimings {
+        &self.timings
+    }
+
+    const fn record(&mut self, stage: Stage, elapsed: Duration) {
+        let timing = match stage {
+            Stage::Parse | Stage::Plan => &mut self.timings.parse,
+            Stage::Execute => &mut self.timings.execute,
+            Stage::Load => &mut self.timings.load,
+            Stage::Serialize => &mut self.timings.serialize,
+        };
+        *timing = timing.saturating_add(elapsed);
+    }
+}
+
+/// A stage of a query in progress, see [`QueryTrace::stage`].
+#[must_use = "the stage ends when the guard is dropped"]
+pub struct StageGuard<'a> {
+    #[cfg(feature = "tracing")]
+    _entered: tracing::span::EnteredSpan,
+    trace: &'a mut QueryTrace,
+    stage: Stage,
+    start: Instant,
+}
+
+impl Drop for StageGuard<'_> {
+    fn drop(&mut self) {
+        self.trace.record(self.stage, self.start.elapsed());
+    }
+}
+
+#[cfg(feature = "tracing")]
+mod spans {
+    use super::{Stage, TraceParent};
+
+    pub(super) fn query_span(command: &str, parent: Option<&TraceParent>) -> tracing::Span {
+        let span = tracing::info_span!(
+            "search.query",
+            command,
+            trace_id = tracing::field::Empty,
+            parent_id = tracing::field::Empty,
+        );
+        if let Some(parent) = parent {
+            let id = parent.to_string();
+            // `version-trace_id-parent_id-flags`
+            span.record("trace_id", &id[3..35]);
+            span.record("parent_id", &id[36..52]);
+            #[cfg(feature = "otlp")]
+            otlp::set_parent(&span, parent);
+        }
+        span
+    }
+
+    pub(super) fn stage_span(query: &tracing::Span, stage: Stage) -> tracing::Span {
+        // The span names must be literals.
+        match stage {
+            Stage::Parse => tracing::info_span!(parent: query, "search.parse"),
+            Stage::Plan => tracing::info_span!(parent: query, "search.plan"),
+            Stage::Execute => tracing::info_span!(parent: query, "search.execute"),
+            Stage::Load => tracing::info_span!(parent: query, "search.load"),
+            Stage::Serialize => tracing::info_span!(parent: query, "search.serialize"),
+        }
+    }
+
+    #[cfg(feature = "otlp")]
+    mod otlp {
+        use opentelemetry::trace::{
+            SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+        };
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        use super::TraceParent;
+
+        pub(super) fn set_parent(span: &tracing::Span, parent: &TraceParent) {
+            let context = SpanContext::new(
+                TraceId::from_bytes(parent.trace_id),
+                SpanId::from_bytes(parent.parent_id),
+                TraceFlags::new(parent.flags),
+                true,
+                TraceState::default(),
+            );
+            span.set_parent(opentelemetry::Context::new().with_remote_span_context(context));
+        }
+    }
+}
@@ -0,0 +1,59 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use pipeline::acl::{Caller, KeyPermissions, NOPERM_ERR, PermissionDenied};
+
+/// A user allowed to read the keys matching `allowed*`.
+struct PrefixUser(&'static [u8]);
+
+impl KeyPermissions for PrefixUser {
+    fn may_read_prefix(&self, prefix: &[u8]) -> bool {
+        prefix.starts_with(self.0)
+    }
+
+    fn may_read_key(&self, key: &[u8]) -> bool {
+        key.starts_with(self.0)
+    }
+}
+
+#[test]
+fn test_index_prefixes() {
+    let user = PrefixUser(b"app:");
+    let caller = Caller::User(&user);
+    assert_eq!(caller.check_index(["app:users:", "app:orders:"]), Ok(()));
+    // Every prefix must be readable.
+    assert_eq!(
+        caller.check_index(["app:users:", "admin:"]),
+        Err(PermissionDenied)
+    );
+    // An index on the whole keyspace.
+    assert_eq!(caller.check_index([""]), Err(PermissionDenied));
+    assert_eq!(PermissionDenied.to_string(), NOPERM_ERR);
+}
+
+#[test]
+fn test_loaded_keys() {
+    let user = PrefixUser(b"app:");
+    let caller = Caller::User(&user);
+    assert_eq!(caller.check_key(b"app:users:1"), Ok(()));
+    assert_eq!(caller.check_key(b"secret"), Err(PermissionDenied));
+}
+
+#[test]
+fn test_special_callers() {
+    for caller in [Caller::Superuser, Caller::Unchecked] {
+        assert_eq!(caller.check_index(["admin:"]), Ok(()));
+        assert_eq!(caller.check_key(b"admin:1"), Ok(()));
+    }
+    assert_eq!(
+        Caller::UnknownUser.check_index(Vec::<&str>::new()),
+        Err(PermissionDenied)
+    );
+    assert_eq!(Caller::UnknownUser.check_key(b"k"), Err(PermissionDenied));
+}
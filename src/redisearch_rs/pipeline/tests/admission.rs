@@ -0,0 +1,204 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{
+    sync::{Arc, Mutex, mpsc},
+    thread,
+    time::Duration,
+};
+
+use pipeline::admission::{AdmissionConfig, AdmissionController, QueriesCommand, Rejected, Ticket};
+
+const NO_WAIT: Duration = Duration::ZERO;
+
+/// Runs the admitted jobs on the thread freeing the slot.
+fn inline(job: Box<dyn FnOnce() + Send>) {
+    job();
+}
+
+/// A job recording its outcome, keeping the ticket in `tickets`.
+fn record(
+    outcomes: &Arc<Mutex<Vec<Result<u64, Rejected>>>>,
+    tickets: &Arc<Mutex<Vec<Ticket>>>,
+) -> impl FnOnce(Result<Ticket, Rejected>) + Send + 'static {
+    let outcomes = Arc::clone(outcomes);
+    let tickets = Arc::clone(tickets);
+    move |result| {
+        outcomes
+            .lock()
+            .unwrap()
+            .push(result.as_ref().map(Ticket::id).map_err(|e| *e));
+        tickets.lock().unwrap().extend(result);
+    }
+}
+
+#[test]
+fn test_unlimited_by_default() {
+    let controller = AdmissionController::new(AdmissionConfig::default(), inline);
+    let tickets: Vec<_> = (0..100)
+        .map(|_| controller.try_admit("idx", "*").unwrap())
+        .collect();
+    assert_eq!(controller.in_flight().len(), 100);
+    drop(tickets);
+    assert!(controller.in_flight().is_empty());
+}
+
+#[test]
+fn test_caps() {
+    let controller = AdmissionController::new(
+        AdmissionConfig {
+            max_per_index: 1,
+            max_global: 2,
+            max_queued: 1,
+        },
+        inline,
+    );
+    let a = controller.try_admit("a", "q1").unwrap();
+    // The index is full, the server isn't.
+    assert_eq!(
+        controller.try_admit("a", "q2").err(),
+        Some(Rejected::Timeout)
+    );
+    let _b = controller.try_admit("b", "q3").unwrap();
+    assert_eq!(
+        controller.try_admit("c", "q4").err(),
+        Some(Rejected::Timeout)
+    );
+    drop(a);
+    let _c = controller.try_admit("c", "q5").unwrap();
+
+    // Without waiting, the job is rejected on the calling thread.
+    let (sender, receiver) = mpsc::channel();
+    controller.admit("c", "q6", NO_WAIT, move |result| {
+        sender.send(result.err()).unwrap();
+    });
+    assert_eq!(receiver.try_recv(), Ok(Some(Rejected::Timeout)));
+    assert_eq!(controller.queued(), 0);
+}
+
+#[test]
+fn test_queued_queries_dont_hold_threads() {
+    let controller = AdmissionController::new(
+        AdmissionConfig {
+            max_per_index: 1,
+            max_global: 0,
+            max_queued: 1,
+        },
+        inline,
+    );
+    let outcomes = Arc::default();
+    let tickets = Arc::default();
+    let first = controller.try_admit("idx", "first").unwrap();
+
+    // Queued: `admit` returns right away, without running the job.
+    controller.admit(
+        "idx",
+        "second",
+        Duration::from_secs(10),
+        record(&outcomes, &tickets),
+    );
+    assert_eq!(controller.queued(), 1);
+    assert!(outcomes.lock().unwrap().is_empty());
+
+    // The queue is full: fail fast.
+    controller.admit(
+        "idx",
+        "third",
+        Duration::from_secs(10),
+        record(&outcomes, &tickets),
+    );
+    assert_eq!(*outcomes.lock().unwrap(), [Err(Rejected::QueueFull)]);
+
+    // Freeing the slot hands it to the queued job.
+    drop(first);
+    assert_eq!(*outcomes.lock().unwrap(), [Err(Rejected::QueueFull), Ok(1)]);
+    assert_eq!(controller.queued(), 0);
+    assert_eq!(controller.in_flight()[0].query, "second");
+}
+
+#[test]
+fn test_queued_queries_time_out() {
+    let controller = AdmissionController::new(
+        AdmissionConfig {
+            max_per_index: 1,
+            max_global: 0,
+            max_queued: 2,
+        },
+        inline,
+    );
+    let outcomes = Arc::default();
+    let tickets = Arc::default();
+    let _first = controller.try_admit("idx", "first").unwrap();
+    controller.admit(
+        "idx",
+        "second",
+        Duration::from_millis(1),
+        record(&outcomes, &tickets),
+    );
+    thread::sleep(Duration::from_millis(5));
+    controller.expire();
+    assert_eq!(*outcomes.lock().unwrap(), [Err(Rejected::Timeout)]);
+    assert_eq!(controller.queued(), 0);
+}
+
+#[test]
+fn test_jobs_run_on_the_spawner() {
+    let (sender, receiver) = mpsc::channel::<thread::JoinHandle<()>>();
+    let sender = Mutex::new(sender);
+    let controller = AdmissionController::new(
+        AdmissionConfig {
+            max_per_index: 1,
+            max_global: 0,
+            max_queued: 1,
+        },
+        move |job| {
+            sender.lock().unwrap().send(thread::spawn(job)).unwrap();
+        },
+    );
+    let first = controller.try_admit("idx", "first").unwrap();
+    let (done, admitted) = mpsc::channel();
+    controller.admit("idx", "second", Duration::from_secs(10), move |result| {
+        done.send((thread::current().id(), result.map(|t| t.id())))
+            .unwrap();
+    });
+    drop(first);
+    receiver.recv().unwrap().join().unwrap();
+    let (thread_id, result) = admitted.recv().unwrap();
+    assert_ne!(thread_id, thread::current().id());
+    assert_eq!(result, Ok(1));
+}
+
+#[test]
+fn test_list_and_kill() {
+    let controller = AdmissionController::new(AdmissionConfig::default(), inline);
+    let ticket = controller.try_admit("idx", "@title:hello").unwrap();
+    let listed = controller.in_flight();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(
+        (
+            listed[0].id,
+            listed[0].index.as_str(),
+            listed[0].query.as_str()
+        ),
+        (ticket.id(), "idx", "@title:hello")
+    );
+
+    assert!(!ticket.is_killed());
+    assert!(controller.kill(ticket.id()));
+    assert!(ticket.is_killed());
+    assert!(!controller.kill(42));
+
+    assert_eq!(QueriesCommand::parse(&["list"]), Some(QueriesCommand::List));
+    assert_eq!(
+        QueriesCommand::parse(&["KILL", "3"]),
+        Some(QueriesCommand::Kill(3))
+    );
+    assert_eq!(QueriesCommand::parse(&["KILL", "x"]), None);
+    assert_eq!(QueriesCommand::parse(&[]), None);
+}
@@ -0,0 +1,71 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+#![cfg(feature = "arrow")]
+
+use std::io::Cursor;
+
+use arrow_array::{Array, Float64Array, StringArray};
+use arrow_ipc::reader::StreamReader;
+use arrow_schema::DataType;
+use pipeline::{
+    arrow::{ArrowExporter, ColumnType},
+    export::ExportValue,
+};
+
+#[test]
+fn test_round_trip() {
+    let mut exporter = ArrowExporter::new(vec![
+        ("name".to_owned(), ColumnType::of_key(false)),
+        ("price".to_owned(), ColumnType::of_key(true)),
+    ]);
+    exporter.push_row(&[ExportValue::String("a"), ExportValue::Number(1.5)]);
+    exporter.push_row(&[ExportValue::Number(2.0), ExportValue::String("3")]);
+    exporter.push_row(&[ExportValue::Null, ExportValue::String("n/a")]);
+    exporter.push_row(&[]);
+    assert_eq!(exporter.rows(), 4);
+    let bytes = exporter.finish().unwrap();
+
+    let mut reader = StreamReader::try_new(Cursor::new(bytes), None).unwrap();
+    let schema = reader.schema();
+    assert_eq!(schema.field(0).name(), "name");
+    assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+    assert_eq!(schema.field(1).data_type(), &DataType::Float64);
+
+    let batch = reader.next().unwrap().unwrap();
+    assert!(reader.next().is_none());
+    let names = batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(
+        names.iter().collect::<Vec<_>>(),
+        [Some("a"), Some("2"), None, None]
+    );
+    let prices = batch
+        .column(1)
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .unwrap();
+    assert_eq!(
+        prices.iter().collect::<Vec<_>>(),
+        [Some(1.5), Some(3.0), None, None]
+    );
+    assert_eq!(prices.null_count(), 2);
+}
+
+#[test]
+fn test_no_columns() {
+    let mut exporter = ArrowExporter::new(Vec::new());
+    exporter.push_row(&[]);
+    let bytes = exporter.finish().unwrap();
+    let mut reader = StreamReader::try_new(Cursor::new(bytes), None).unwrap();
+    assert_eq!(reader.next().unwrap().unwrap().num_rows(), 1);
+}
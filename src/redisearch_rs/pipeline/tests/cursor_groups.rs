@@ -0,0 +1,169 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{fs, io, path::PathBuf};
+
+use pipeline::{
+    cursor_groups::{CheckpointError, CursorGroups, CursorStateStats},
+    external_sort::{SpillConfig, SpillRecord},
+};
+
+/// The accumulators of `COUNT` and `SUM`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct CountSum {
+    count: u64,
+    sum: f64,
+}
+
+impl SpillRecord for CountSum {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.count.to_le_bytes());
+        out.extend_from_slice(&self.sum.to_le_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let (count, sum) = bytes
+            .split_first_chunk()
+            .ok_or(io::ErrorKind::InvalidData)?;
+        let sum = sum.try_into().map_err(|_| io::ErrorKind::InvalidData)?;
+        Ok(Self {
+            count: u64::from_le_bytes(*count),
+            sum: f64::from_le_bytes(sum),
+        })
+    }
+}
+
+fn spill_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cursor_groups_{test}_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn files(dir: &PathBuf) -> usize {
+    fs::read_dir(dir).unwrap().count()
+}
+
+fn add(groups: &mut CursorGroups<CountSum>, brand: &str, price: f64) {
+    let group = groups.group(brand, CountSum::default).unwrap();
+    group.count += 1;
+    group.sum += price;
+}
+
+#[test]
+fn test_checkpoint_and_resume() {
+    let dir = spill_dir("resume");
+    let config = SpillConfig {
+        enabled: true,
+        dir: dir.clone(),
+        ..Default::default()
+    };
+    let mut groups = CursorGroups::default();
+    add(&mut groups, "acme", 10.0);
+    add(&mut groups, "globex", 5.0);
+    let resident = groups.stats();
+    assert_eq!(resident.groups, 2);
+    assert!(resident.resident_bytes > 0);
+    assert_eq!(resident.spilled_bytes, 0);
+
+    // The cursor goes idle.
+    groups.checkpoint(&config, 0).unwrap();
+    assert!(groups.is_checkpointed());
+    assert_eq!(files(&dir), 1);
+    assert_eq!(
+        groups.stats(),
+        CursorStateStats {
+            groups: 2,
+            resident_bytes: 0,
+            // Two keys and states, each prefixed by its length.
+            spilled_bytes: 4 * 4 + 4 + 6 + 2 * 16,
+            checkpoints: 1,
+        }
+    );
+    // Checkpointing again does nothing.
+    groups.checkpoint(&config, 0).unwrap();
+    assert_eq!(groups.stats().checkpoints, 1);
+
+    // The next read resumes the groups and removes the file.
+    groups.resume().unwrap();
+    assert_eq!(files(&dir), 0);
+    add(&mut groups, "acme", 20.0);
+    let result = groups.take().unwrap();
+    assert_eq!(
+        result["acme"],
+        CountSum {
+            count: 2,
+            sum: 30.0
+        }
+    );
+    assert_eq!(result["globex"].count, 1);
+    assert_eq!(groups.stats().info()[0], ("state_groups", 0));
+    fs::remove_dir(dir).unwrap();
+}
+
+#[test]
+fn test_group_resumes_checkpoint() {
+    let dir = spill_dir("group");
+    let config = SpillConfig {
+        enabled: true,
+        dir: dir.clone(),
+        ..Default::default()
+    };
+    let mut groups = CursorGroups::default();
+    add(&mut groups, "acme", 10.0);
+    groups.checkpoint(&config, 0).unwrap();
+
+    // Reading a group resumes the checkpoint.
+    add(&mut groups, "acme", 20.0);
+    assert!(!groups.is_checkpointed());
+    assert_eq!(files(&dir), 0);
+    assert_eq!(groups.take().unwrap()["acme"].count, 2);
+
+    // A checkpoint that can't be read is reported, and kept.
+    add(&mut groups, "acme", 10.0);
+    groups.checkpoint(&config, 0).unwrap();
+    let file = fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+    fs::write(&file, [1, 0, 0, 0]).unwrap();
+    let error = groups.group("acme", CountSum::default).unwrap_err();
+    assert!(matches!(error, CheckpointError::Io(_)));
+    assert!(groups.is_checkpointed());
+    drop(groups);
+    fs::remove_dir(dir).unwrap();
+}
+
+#[test]
+fn test_checkpoint_disabled_or_over_quota() {
+    let mut groups = CursorGroups::default();
+    add(&mut groups, "acme", 10.0);
+    groups.checkpoint(&SpillConfig::default(), 0).unwrap();
+    assert!(!groups.is_checkpointed());
+
+    let dir = spill_dir("quota");
+    let config = SpillConfig {
+        enabled: true,
+        dir: dir.clone(),
+        max_file_bytes: 1 << 20,
+        max_total_bytes: 100,
+    };
+    let error = groups.checkpoint(&config, 90).unwrap_err();
+    assert!(matches!(error, CheckpointError::QuotaExceeded));
+    assert_eq!(
+        error.to_string(),
+        "Cursor checkpoint exceeds its size limit"
+    );
+    assert!(!groups.is_checkpointed());
+    assert_eq!(files(&dir), 0);
+
+    // Freeing the cursor removes its checkpoint.
+    groups.checkpoint(&config, 0).unwrap();
+    assert_eq!(files(&dir), 1);
+    drop(groups);
+    assert_eq!(files(&dir), 0);
+    fs::remove_dir(dir).unwrap();
+}
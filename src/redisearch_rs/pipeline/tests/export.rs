@@ -0,0 +1,70 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use pipeline::export::{ExportFormat, ExportValue, Exporter};
+
+fn fields() -> Vec<String> {
+    vec!["name".to_owned(), "price".to_owned(), "note".to_owned()]
+}
+
+#[test]
+fn test_parse_format() {
+    assert_eq!("csv".parse(), Ok(ExportFormat::Csv));
+    assert_eq!("JSON".parse(), Ok(ExportFormat::Json));
+    assert_eq!(
+        "xml".parse::<ExportFormat>().unwrap_err().to_string(),
+        "Unknown FORMAT `xml`, expected CSV or JSON"
+    );
+}
+
+#[test]
+fn test_csv() {
+    let mut exporter = Exporter::new(ExportFormat::Csv, fields());
+    exporter.push_row(&[
+        ExportValue::String("plain"),
+        ExportValue::Number(3.0),
+        ExportValue::String("a, \"quoted\"\nline"),
+    ]);
+    exporter.push_row(&[
+        ExportValue::String(" padded"),
+        ExportValue::Number(0.25),
+        ExportValue::Null,
+    ]);
+    exporter.push_row(&[ExportValue::String("short")]);
+    assert_eq!(exporter.rows(), 3);
+    assert_eq!(
+        exporter.finish(),
+        "name,price,note\r\n\
+         plain,3,\"a, \"\"quoted\"\"\nline\"\r\n\
+         \" padded\",0.25,\r\n\
+         short,,\r\n"
+    );
+}
+
+#[test]
+fn test_json() {
+    let mut exporter = Exporter::new(ExportFormat::Json, fields());
+    assert_eq!(exporter.clone().finish(), "[]");
+    exporter.push_row(&[
+        ExportValue::String("say \"hi\"\\\u{1}"),
+        ExportValue::Number(f64::INFINITY),
+        ExportValue::Null,
+    ]);
+    exporter.push_row(&[ExportValue::Missing, ExportValue::Number(-1.5)]);
+    assert_eq!(
+        exporter.finish(),
+        r#"[{"name":"say \"hi\"\\\u0001","price":null,"note":null},{"price":-1.5}]"#
+    );
+}
+
+#[test]
+#[should_panic(expected = "row has 4 values for 3 fields")]
+fn test_too_many_values() {
+    Exporter::new(ExportFormat::Csv, fields()).push_row(&[ExportValue::Null; 4]);
+}
@@ -0,0 +1,209 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use pipeline::external_sort::{
+    ExternalSorter, MAX_MERGE_WIDTH, SortError, SpillConfig, SpillRecord, SpillStats,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    score: f64,
+    key: String,
+}
+
+impl SpillRecord for Row {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.score.to_le_bytes());
+        out.extend_from_slice(self.key.as_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let (score, key) = bytes
+            .split_first_chunk()
+            .ok_or(io::ErrorKind::InvalidData)?;
+        Ok(Self {
+            score: f64::from_le_bytes(*score),
+            key: String::from_utf8(key.to_vec()).map_err(|_| io::ErrorKind::InvalidData)?,
+        })
+    }
+}
+
+fn rows(n: u32) -> impl Iterator<Item = Row> {
+    // A permutation of 0..n.
+    (0..n).map(move |i| Row {
+        score: f64::from(i * 7919 % n),
+        key: format!("doc{i}"),
+    })
+}
+
+/// A directory of its own for each test, so that leftover files are noticed.
+fn spill_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("external_sort_{test}_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn files(dir: &PathBuf) -> usize {
+    fs::read_dir(dir).unwrap().count()
+}
+
+fn by_score(a: &Row, b: &Row) -> std::cmp::Ordering {
+    a.score.total_cmp(&b.score)
+}
+
+#[test]
+fn test_limit_without_spilling() {
+    let mut sorter = ExternalSorter::new(10, SpillConfig::default(), by_score);
+    let mut rows = rows(11);
+    for row in rows.by_ref().take(10) {
+        sorter.push(row).unwrap();
+    }
+    let error = sorter.push(rows.next().unwrap()).unwrap_err();
+    assert!(matches!(error, SortError::LimitExceeded(10)));
+    assert_eq!(
+        error.to_string(),
+        "Sorting more than 10 results (MAXAGGREGATERESULTS)"
+    );
+
+    // Below the limit, nothing is spilled.
+    let mut sorter = ExternalSorter::new(10, SpillConfig::default(), by_score);
+    for row in self::rows(10) {
+        sorter.push(row).unwrap();
+    }
+    assert_eq!(sorter.stats(), SpillStats::default());
+    let scores: Vec<f64> = sorter.finish().unwrap().map(|r| r.unwrap().score).collect();
+    assert_eq!(scores, (0..10).map(f64::from).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_spill_and_merge() {
+    let dir = spill_dir("merge");
+    let config = SpillConfig {
+        enabled: true,
+        dir: dir.clone(),
+        ..Default::default()
+    };
+    let mut sorter = ExternalSorter::new(100, config, by_score);
+    for row in rows(1000) {
+        sorter.push(row).unwrap();
+    }
+    let stats = sorter.stats();
+    assert_eq!(stats.runs, 9);
+    assert_eq!(stats.rows_spilled, 900);
+    assert_eq!(files(&dir), 9);
+    assert_eq!(stats.profile()[0], ("Spilled runs", 9));
+
+    let sorted = sorter.finish().unwrap();
+    let rows: Vec<Row> = sorted.map(Result::unwrap).collect();
+    assert_eq!(rows.len(), 1000);
+    assert!(rows.windows(2).all(|w| w[0].score < w[1].score));
+    assert_eq!(rows[0].key, "doc0");
+    assert_eq!(files(&dir), 0);
+    fs::remove_dir(dir).unwrap();
+}
+
+#[test]
+fn test_spill_quota() {
+    let dir = spill_dir("quota");
+    let config = SpillConfig {
+        enabled: true,
+        dir: dir.clone(),
+        max_file_bytes: 1 << 20,
+        // Room for two runs of 10 rows of about 20 bytes.
+        max_total_bytes: 450,
+    };
+    let mut sorter = ExternalSorter::new(10, config, by_score);
+    let result: Result<(), SortError> = rows(100).try_for_each(|row| sorter.push(row));
+    assert!(matches!(result, Err(SortError::SpillQuotaExceeded)));
+    assert_eq!(sorter.stats().runs, 2);
+
+    // Dropping the sorter removes its files.
+    assert_eq!(files(&dir), 2);
+    drop(sorter);
+    assert_eq!(files(&dir), 0);
+    fs::remove_dir(dir).unwrap();
+}
+
+fn spill_config(dir: &Path) -> SpillConfig {
+    SpillConfig {
+        enabled: true,
+        dir: dir.to_owned(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_spilling_keeps_ties_in_push_order() {
+    let dir = spill_dir("stable");
+    // Ten rows per score, interleaved, so that each score spans every run and
+    // the rows still held in memory.
+    let rows: Vec<Row> = (0..100)
+        .map(|i| Row {
+            score: f64::from(i % 10),
+            key: format!("doc{i}"),
+        })
+        .collect();
+    let mut in_memory = rows.clone();
+    in_memory.sort_by(by_score);
+
+    let mut sorter = ExternalSorter::new(30, spill_config(&dir), by_score);
+    for row in rows {
+        sorter.push(row).unwrap();
+    }
+    assert_eq!(sorter.stats().runs, 3);
+    let spilled: Vec<Row> = sorter.finish().unwrap().map(Result::unwrap).collect();
+    assert_eq!(spilled, in_memory);
+    fs::remove_dir(dir).unwrap();
+}
+
+#[test]
+fn test_many_runs_are_merged_in_passes() {
+    let dir = spill_dir("passes");
+    let mut sorter = ExternalSorter::new(10, spill_config(&dir), by_score);
+    let n = (MAX_MERGE_WIDTH as u32 * 3 + 5) * 10;
+    for row in rows(n) {
+        sorter.push(row).unwrap();
+    }
+    let spilled_runs = files(&dir);
+    assert!(spilled_runs > MAX_MERGE_WIDTH);
+
+    let sorted = sorter.finish().unwrap();
+    // The merged runs replaced the ones they were merged from.
+    assert!(files(&dir) <= MAX_MERGE_WIDTH);
+    let scores: Vec<f64> = sorted.map(|r| r.unwrap().score).collect();
+    assert_eq!(scores, (0..n).map(f64::from).collect::<Vec<_>>());
+    assert_eq!(files(&dir), 0);
+    fs::remove_dir(dir).unwrap();
+}
+
+#[test]
+fn test_truncated_run_is_an_error() {
+    let dir = spill_dir("truncated");
+    let mut sorter = ExternalSorter::new(10, spill_config(&dir), by_score);
+    for row in rows(20) {
+        sorter.push(row).unwrap();
+    }
+    // Cut the run within the length of a row.
+    let run = fs::read_dir(&dir).unwrap().next().unwrap().unwrap().path();
+    let mut bytes = fs::read(&run).unwrap();
+    bytes.extend_from_slice(&[1, 0]);
+    fs::write(&run, bytes).unwrap();
+
+    let results: Vec<io::Result<Row>> = sorter.finish().unwrap().collect();
+    let error = results.last().unwrap().as_ref().unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(files(&dir), 0);
+    fs::remove_dir(dir).unwrap();
+}
@@ -0,0 +1,143 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use pipeline::fanout::{
+    FanOutError, Hit, IndexSelector, ScoreNormalization, check_compatible, fan_out, merge,
+    per_index_limit,
+};
+
+const INDEXES: [&str; 4] = ["logs-2024-01", "logs-2024-02", "users", "logs-2023-12"];
+
+fn hits(scored: &[(&str, f64)]) -> Vec<Hit> {
+    scored
+        .iter()
+        .map(|&(key, score)| Hit {
+            key: key.to_owned(),
+            score,
+        })
+        .collect()
+}
+
+#[test]
+fn test_resolve_names_and_patterns() {
+    let names = IndexSelector::parse("users,logs-2024-01,users");
+    assert_eq!(names.resolve(INDEXES).unwrap(), ["users", "logs-2024-01"]);
+    assert_eq!(
+        IndexSelector::parse("users,nope").resolve(INDEXES),
+        Err(FanOutError::UnknownIndex("nope".to_owned()))
+    );
+
+    let pattern = IndexSelector::parse("logs-*");
+    assert_eq!(pattern, IndexSelector::Pattern("logs-*".to_owned()));
+    assert_eq!(
+        pattern.resolve(INDEXES).unwrap(),
+        ["logs-2023-12", "logs-2024-01", "logs-2024-02"]
+    );
+    assert_eq!(
+        IndexSelector::parse("metrics-*")
+            .resolve(INDEXES)
+            .unwrap_err()
+            .to_string(),
+        "No index matches `metrics-*`"
+    );
+}
+
+#[test]
+fn test_check_compatible() {
+    let field_type = |index: &str, field: &str| match (index, field) {
+        (_, "title") => Some("TEXT"),
+        ("a" | "b", "price") => Some("NUMERIC"),
+        ("c", "price") => Some("TAG"),
+        _ => None,
+    };
+    let indexes = ["a".to_owned(), "b".to_owned(), "c".to_owned()];
+    assert_eq!(
+        check_compatible(&indexes[..2], &["title", "price"], field_type),
+        Ok(())
+    );
+    assert_eq!(
+        check_compatible(&indexes, &["title", "price"], field_type),
+        Err(FanOutError::IncompatibleField {
+            field: "price".to_owned(),
+            index: "c".to_owned()
+        })
+    );
+    assert!(check_compatible(&indexes, &["body"], field_type).is_err());
+}
+
+#[test]
+fn test_merge_normalizes_scores() {
+    let results = vec![
+        (
+            "a".to_owned(),
+            hits(&[("a:1", 10.0), ("a:2", 5.0), ("a:3", 0.0)]),
+        ),
+        ("b".to_owned(), hits(&[("b:1", 0.4), ("b:2", 0.2)])),
+    ];
+    let merged = merge(results.clone(), ScoreNormalization::MinMax, 0, 10);
+    let keys: Vec<_> = merged.iter().map(|h| (h.key.as_str(), h.score)).collect();
+    assert_eq!(
+        keys,
+        [
+            ("a:1", 1.0),
+            ("b:1", 1.0),
+            ("a:2", 0.5),
+            ("a:3", 0.0),
+            ("b:2", 0.0)
+        ]
+    );
+    assert_eq!(merged[0].raw_score, 10.0);
+    assert_eq!(merged[1].index, "b");
+
+    // Without normalization, the index with the larger scores dominates.
+    let raw = merge(results.clone(), ScoreNormalization::None, 2, 2);
+    let keys: Vec<_> = raw.iter().map(|h| h.key.as_str()).collect();
+    assert_eq!(keys, ["b:1", "b:2"]);
+
+    let max = merge(results, ScoreNormalization::Max, 0, 2);
+    assert_eq!(max[1].score, 1.0);
+    assert_eq!("minmax".parse(), Ok(ScoreNormalization::MinMax));
+}
+
+#[test]
+fn test_merge_deduplicates_documents() {
+    let results = vec![
+        ("a".to_owned(), hits(&[("doc", 1.0), ("other", 3.0)])),
+        ("b".to_owned(), hits(&[("doc", 2.0)])),
+    ];
+    let merged = merge(results, ScoreNormalization::Max, 0, 10);
+    assert_eq!(merged.len(), 2);
+    assert_eq!(
+        (merged[0].key.as_str(), merged[0].index.as_str()),
+        ("doc", "b")
+    );
+}
+
+#[test]
+fn test_fan_out() {
+    let indexes = ["a".to_owned(), "b".to_owned(), "c".to_owned()];
+    let mut queried = Vec::new();
+    let results = fan_out(&indexes, |index| {
+        queried.push(index.to_owned());
+        Ok::<_, ()>(hits(&[(index, 1.0)]))
+    })
+    .unwrap();
+    assert_eq!(queried, indexes);
+    assert_eq!(results[2].0, "c");
+
+    let failed = fan_out(&indexes, |index| {
+        if index == "b" {
+            Err("timeout")
+        } else {
+            Ok(Vec::new())
+        }
+    });
+    assert_eq!(failed, Err(("b".to_owned(), "timeout")));
+    assert_eq!(per_index_limit(20, 10), 30);
+}
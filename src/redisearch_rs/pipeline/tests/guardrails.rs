@@ -0,0 +1,90 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use pipeline::guardrails::{Guardrail, LimitExceeded, QueryBudget, QueryLimits};
+
+#[test]
+fn test_unlimited_by_default() {
+    let mut budget = QueryBudget::default();
+    budget.add_reply_bytes(u64::MAX).unwrap();
+    budget.add_reply_bytes(1).unwrap();
+    budget.add_rows_loaded(1_000_000).unwrap();
+    budget.check_group_cardinality(1_000_000).unwrap();
+    budget.alloc_temp(1 << 40).unwrap();
+    assert_eq!(budget.usage().reply_bytes, u64::MAX);
+}
+
+#[test]
+fn test_reply_size() {
+    let mut budget = QueryBudget::new(QueryLimits {
+        max_reply_bytes: 100,
+        ..Default::default()
+    });
+    budget.add_reply_bytes(60).unwrap();
+    budget.add_reply_bytes(40).unwrap();
+    let err = budget.add_reply_bytes(1).unwrap_err();
+    assert_eq!(
+        err,
+        LimitExceeded {
+            guardrail: Guardrail::ReplySize,
+            limit: 100,
+            requested: 101
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Query exceeded MAXREPLYSIZE (limit 100, requested 101)"
+    );
+    // The failed addition isn't accounted for.
+    assert_eq!(budget.usage().reply_bytes, 100);
+}
+
+#[test]
+fn test_rows_and_groups() {
+    let mut budget = QueryBudget::new(QueryLimits {
+        max_rows_loaded: 10,
+        max_group_cardinality: 3,
+        ..Default::default()
+    });
+    budget.add_rows_loaded(10).unwrap();
+    assert_eq!(
+        budget.add_rows_loaded(1).unwrap_err().guardrail,
+        Guardrail::RowsLoaded
+    );
+    for groups in 1..=3 {
+        budget.check_group_cardinality(groups).unwrap();
+    }
+    assert_eq!(
+        budget.check_group_cardinality(4).unwrap_err().guardrail,
+        Guardrail::GroupCardinality
+    );
+    assert_eq!(budget.usage().max_group_cardinality, 3);
+}
+
+#[test]
+fn test_temp_memory_peak() {
+    let mut budget = QueryBudget::new(QueryLimits {
+        max_temp_memory: 1024,
+        ..Default::default()
+    });
+    budget.alloc_temp(800).unwrap();
+    budget.free_temp(500);
+    budget.alloc_temp(700).unwrap();
+    assert_eq!(
+        budget.alloc_temp(100).unwrap_err(),
+        LimitExceeded {
+            guardrail: Guardrail::TempMemory,
+            limit: 1024,
+            requested: 1100
+        }
+    );
+    let usage = budget.usage();
+    assert_eq!(usage.temp_memory, 1000);
+    assert_eq!(usage.peak_temp_memory, 1000);
+}
@@ -0,0 +1,48 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::cell::Cell;
+
+use pipeline::missing_docs::MissingDocs;
+
+#[test]
+fn test_missing_doc_is_opened_once() {
+    let mut cache = MissingDocs::default();
+    let opens = Cell::new(0);
+    let open = |id: u64| {
+        opens.set(opens.get() + 1);
+        id.is_multiple_of(2).then_some(id)
+    };
+
+    assert_eq!(cache.load(2, open), Some(2));
+    assert_eq!(cache.load(3, open), None);
+    assert_eq!(cache.load(3, open), None);
+    assert_eq!(cache.load(2, open), Some(2));
+
+    assert_eq!(opens.get(), 3);
+    assert!(cache.is_missing(3));
+    assert!(!cache.is_missing(2));
+    assert_eq!(cache.missing_docs(), 1);
+    assert_eq!(cache.skipped_lookups(), 1);
+}
+
+#[test]
+fn test_full_cache_still_counts() {
+    let mut cache = MissingDocs::new(1);
+    cache.record_missing(1);
+    cache.record_missing(2);
+    cache.record_missing(1);
+    assert_eq!(cache.missing_docs(), 2);
+    assert!(cache.is_missing(1));
+    assert!(!cache.is_missing(2));
+    // Not remembered, so it's looked up again.
+    assert_eq!(cache.load(2, |_| None::<()>), None);
+    assert_eq!(cache.skipped_lookups(), 0);
+    assert_eq!(cache.missing_docs(), 3);
+}
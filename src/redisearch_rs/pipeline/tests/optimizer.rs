@@ -0,0 +1,169 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use pipeline::{
+    optimizer::{Assignment, PlanStep, optimize},
+    projection::KeySet,
+};
+
+fn keys(keys: &[&str]) -> Vec<String> {
+    keys.iter().map(|k| (*k).to_owned()).collect()
+}
+
+fn apply(alias: &str, expr: &str, reads: &[&str]) -> Assignment {
+    Assignment {
+        alias: alias.to_owned(),
+        expr: expr.to_owned(),
+        reads: keys(reads),
+    }
+}
+
+fn filter(expr: &str, reads: &[&str]) -> PlanStep {
+    PlanStep::Filter {
+        expr: expr.to_owned(),
+        reads: keys(reads),
+    }
+}
+
+fn sort_by(key: &str) -> PlanStep {
+    PlanStep::Other {
+        description: format!("SORTBY 1 @{key}"),
+        reads: keys(&[key]),
+        writes: Vec::new(),
+    }
+}
+
+#[test]
+fn test_filter_on_sortables_moves_before_load() {
+    let steps = vec![
+        PlanStep::SortableRead(keys(&["price"])),
+        PlanStep::Load(None),
+        filter("@price>10", &["price"]),
+        filter("@title=='x'", &["title"]),
+    ];
+    let optimized = optimize(steps, &KeySet::All);
+    assert_eq!(
+        optimized.after,
+        [
+            PlanStep::SortableRead(keys(&["price"])),
+            filter("@price>10", &["price"]),
+            PlanStep::Load(None),
+            filter("@title=='x'", &["title"]),
+        ]
+    );
+}
+
+#[test]
+fn test_filter_stays_after_the_load_it_reads() {
+    // `price` is loaded again, so the filter must see the loaded value.
+    let steps = vec![
+        PlanStep::SortableRead(keys(&["price"])),
+        PlanStep::Load(Some(keys(&["price", "title"]))),
+        filter("@price>10", &["price"]),
+    ];
+    let optimized = optimize(steps.clone(), &KeySet::All);
+    assert_eq!(optimized.after, steps);
+
+    // A key computed earlier isn't fetched by an explicit LOAD.
+    let steps = vec![
+        PlanStep::Apply(vec![apply("total", "@a*2", &["a"])]),
+        PlanStep::Load(Some(keys(&["title"]))),
+        filter("@total>10", &["total"]),
+    ];
+    let optimized = optimize(steps, &KeySet::All);
+    assert!(matches!(optimized.after[1], PlanStep::Filter { .. }));
+
+    // Sortables don't survive a GROUPBY.
+    let steps = vec![
+        PlanStep::SortableRead(keys(&["price"])),
+        PlanStep::Group {
+            description: "GROUPBY 1 @brand".to_owned(),
+            reads: keys(&["brand"]),
+            writes: keys(&["brand"]),
+        },
+        PlanStep::Load(None),
+        filter("@price>10", &["price"]),
+    ];
+    let optimized = optimize(steps.clone(), &KeySet::All);
+    // The sortable read itself is dead, as the GROUPBY doesn't read it.
+    assert_eq!(optimized.after, steps[1..]);
+}
+
+#[test]
+fn test_consecutive_applies_are_merged() {
+    let steps = vec![
+        PlanStep::Apply(vec![apply("total", "@price*@qty", &["price", "qty"])]),
+        PlanStep::Apply(vec![apply("taxed", "@total*1.2", &["total"])]),
+        sort_by("taxed"),
+        PlanStep::Apply(vec![apply("label", "upper(@name)", &["name"])]),
+    ];
+    let optimized = optimize(steps, &KeySet::All);
+    assert_eq!(
+        optimized.after,
+        [
+            PlanStep::Apply(vec![
+                apply("total", "@price*@qty", &["price", "qty"]),
+                apply("taxed", "@total*1.2", &["total"]),
+            ]),
+            sort_by("taxed"),
+            PlanStep::Apply(vec![apply("label", "upper(@name)", &["name"])]),
+        ]
+    );
+}
+
+#[test]
+fn test_dead_steps_are_removed() {
+    let steps = vec![
+        PlanStep::Load(Some(keys(&["unused"]))),
+        PlanStep::Load(Some(keys(&["price", "qty"]))),
+        PlanStep::Apply(vec![apply("total", "@price*@qty", &["price", "qty"])]),
+        PlanStep::Apply(vec![apply("debug", "@total", &["total"])]),
+        sort_by("total"),
+    ];
+    let optimized = optimize(steps, &KeySet::of(["total"]));
+    assert_eq!(
+        optimized.after,
+        [
+            PlanStep::Load(Some(keys(&["price", "qty"]))),
+            PlanStep::Apply(vec![apply("total", "@price*@qty", &["price", "qty"])]),
+            sort_by("total"),
+        ]
+    );
+
+    // Without a projection of the reply, every output is returned.
+    let optimized = optimize(optimized.before, &KeySet::All);
+    assert_eq!(optimized.after.len(), 4);
+}
+
+#[test]
+fn test_explain() {
+    let steps = vec![
+        PlanStep::SortableRead(keys(&["price"])),
+        PlanStep::Load(None),
+        filter("@price>10", &["price"]),
+        PlanStep::Apply(vec![apply("a", "1", &[])]),
+        PlanStep::Apply(vec![apply("b", "2", &[])]),
+    ];
+    assert_eq!(
+        optimize(steps, &KeySet::All).explain(),
+        [
+            "BEFORE",
+            "  SORTABLES 1 @price",
+            "  LOAD *",
+            "  FILTER @price>10",
+            "  APPLY 1 AS a",
+            "  APPLY 2 AS b",
+            "AFTER",
+            "  SORTABLES 1 @price",
+            "  FILTER @price>10",
+            "  LOAD *",
+            "  APPLY 1 AS a, 2 AS b",
+        ]
+    );
+}
@@ -0,0 +1,101 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use pipeline::projection::{KeySet, Projection, Step};
+
+fn keys(keys: &[&str]) -> Vec<String> {
+    keys.iter().map(|k| (*k).to_owned()).collect()
+}
+
+#[test]
+fn test_load_all_with_return() {
+    // FT.AGGREGATE idx * LOAD * APPLY @price*@qty AS total FILTER @total>10
+    // with RETURN-like projection of `title` and `total`.
+    let steps = [
+        Step::Load(None),
+        Step::Compute {
+            reads: keys(&["price", "qty"]),
+            writes: keys(&["total"]),
+        },
+        Step::Compute {
+            reads: keys(&["total"]),
+            writes: Vec::new(),
+        },
+    ];
+    let projection = Projection::analyze(&steps, KeySet::of(["title", "total"]));
+    assert_eq!(
+        projection.fetch(0),
+        Some(&KeySet::of(["price", "qty", "title"]))
+    );
+    assert_eq!(projection.fetch(1), None);
+    assert_eq!(projection.fetch(3), None);
+
+    // Without a projection of the reply, everything is loaded.
+    let projection = Projection::analyze(&steps, KeySet::All);
+    assert_eq!(projection.fetch(0), Some(&KeySet::All));
+}
+
+#[test]
+fn test_explicit_load() {
+    let steps = [
+        Step::Load(Some(keys(&["a", "b", "c", "d"]))),
+        Step::Compute {
+            reads: keys(&["a"]),
+            writes: keys(&["x"]),
+        },
+    ];
+    let projection = Projection::analyze(&steps, KeySet::of(["x", "c"]));
+    assert_eq!(projection.fetch(0), Some(&KeySet::of(["a", "c"])));
+
+    // A key overwritten before it's read doesn't need loading.
+    let projection = Projection::analyze(&steps, KeySet::of(["x"]));
+    assert_eq!(projection.fetch(0), Some(&KeySet::of(["a"])));
+    assert!(!projection.fetch(0).unwrap().contains("x"));
+}
+
+#[test]
+fn test_group_by_is_a_barrier() {
+    // LOAD * GROUPBY 1 @brand REDUCE AVG 1 @price AS avg SORTBY 2 @avg DESC
+    let steps = [
+        Step::Load(None),
+        Step::Group {
+            reads: keys(&["brand", "price"]),
+            writes: keys(&["brand", "avg"]),
+        },
+        Step::Compute {
+            reads: keys(&["avg"]),
+            writes: Vec::new(),
+        },
+    ];
+    let projection = Projection::analyze(&steps, KeySet::All);
+    assert_eq!(projection.fetch(0), Some(&KeySet::of(["brand", "price"])));
+}
+
+#[test]
+fn test_sortables_are_not_loaded() {
+    let steps = [
+        Step::SortableRead(keys(&["price", "rating", "date"])),
+        Step::Load(None),
+        Step::Compute {
+            reads: keys(&["price", "title"]),
+            writes: keys(&["label"]),
+        },
+    ];
+    let projection = Projection::analyze(&steps, KeySet::of(["label", "rating"]));
+    assert_eq!(projection.fetch(0), Some(&KeySet::of(["price", "rating"])));
+    assert_eq!(projection.fetch(1), Some(&KeySet::of(["title"])));
+
+    // All the fields are loaded, except the sortable ones.
+    let projection = Projection::analyze(&steps, KeySet::All);
+    assert_eq!(
+        projection.fetch(0),
+        Some(&KeySet::of(["date", "price", "rating"]))
+    );
+    assert_eq!(projection.fetch(1), Some(&KeySet::All));
+}
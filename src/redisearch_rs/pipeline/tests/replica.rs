@@ -0,0 +1,197 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use pipeline::{
+    missing_docs::MissingDocs,
+    replica::{ExecutionMode, Expiration, Loader, Mutation, ReadOnlyViolation, ServerRole},
+};
+
+/// A minimal node: a keyspace with expiration times, and an index whose
+/// posting list may reference deleted documents until it's repaired.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Node {
+    keys: BTreeMap<u64, Option<u64>>,
+    posting: BTreeSet<u64>,
+}
+
+impl Node {
+    fn add(&mut self, id: u64, expire_at: Option<u64>) {
+        self.keys.insert(id, expire_at);
+        self.posting.insert(id);
+    }
+
+    /// The `DEL` of a document replicated from the master: the keyspace
+    /// notification removes it from the index as well.
+    fn apply_del(&mut self, id: u64) {
+        self.keys.remove(&id);
+        self.posting.remove(&id);
+    }
+
+    /// Run a query matching every indexed document, the way the pipeline
+    /// does: iterate the posting list, then load each document. The keys
+    /// deleted by the query are appended to `replicated`, the stream of
+    /// commands the master sends to its replicas.
+    fn query(
+        &mut self,
+        mode: &ExecutionMode,
+        now: u64,
+        missing: MissingDocs,
+        replicated: &mut Vec<u64>,
+    ) -> (Vec<u64>, MissingDocs) {
+        let mut loader = Loader::new(mode, missing);
+        let mut results = Vec::new();
+        let mut deleted = Vec::new();
+        for &id in &self.posting {
+            let keys = &self.keys;
+            let open = |id| keys.get(&id).map(|&expire_at| (id, expire_at));
+            if let Some(id) = loader.load(id, now, open, |id| deleted.push(id)) {
+                results.push(id);
+            }
+        }
+        for id in deleted {
+            self.keys.remove(&id);
+            replicated.push(id);
+        }
+        let posting = &mut self.posting;
+        let missing = loader.finish(|stale| {
+            for id in stale {
+                posting.remove(&id);
+            }
+        });
+        (results, missing)
+    }
+}
+
+#[test]
+fn test_modes() {
+    let master = ExecutionMode::for_role(ServerRole::Master);
+    assert!(!master.is_read_only());
+    assert_eq!(master.mutate(Mutation::CacheWarm, || 1), Some(1));
+
+    let replica = ExecutionMode::for_role(ServerRole::Replica);
+    assert_eq!(replica.role(), ServerRole::Replica);
+    assert_eq!(
+        replica.check(Mutation::GcRepair),
+        Err(ReadOnlyViolation {
+            mutation: Mutation::GcRepair
+        })
+    );
+    assert_eq!(replica.mutate(Mutation::CacheWarm, || 1), None);
+    assert_eq!(replica.skipped_mutations(), 2);
+
+    assert!(ExecutionMode::read_only().is_read_only());
+    assert_eq!(master.expiration(None, 10), Expiration::Live);
+    assert_eq!(master.expiration(Some(11), 10), Expiration::Live);
+    assert_eq!(
+        master.expiration(Some(10), 10),
+        Expiration::Expired { delete: true }
+    );
+}
+
+#[test]
+fn test_master_replica_divergence() {
+    let mut master = Node::default();
+    master.add(1, None);
+    master.add(2, Some(100));
+    master.add(3, None);
+    let mut replica = master.clone();
+    let mut replicated = Vec::new();
+
+    // Document 2 expires. The master deletes it on access and repairs its
+    // index; the `DEL` hasn't reached the replica yet.
+    let master_mode = ExecutionMode::for_role(ServerRole::Master);
+    let (results, _) = master.query(&master_mode, 150, MissingDocs::default(), &mut replicated);
+    assert_eq!(results, [1, 3]);
+    assert_eq!(replicated, [2]);
+    assert!(!master.keys.contains_key(&2));
+    assert!(!master.posting.contains(&2));
+
+    // The replica hides the expired document without touching anything.
+    let replica_mode = ExecutionMode::for_role(ServerRole::Replica);
+    let before = replica.clone();
+    let mut not_replicated = Vec::new();
+    let (results, _) = replica.query(
+        &replica_mode,
+        150,
+        MissingDocs::default(),
+        &mut not_replicated,
+    );
+    assert_eq!(results, [1, 3]);
+    assert_eq!(replica, before);
+    assert!(not_replicated.is_empty());
+    // Deleting the expired key.
+    assert_eq!(replica_mode.skipped_mutations(), 1);
+
+    // The master deletes document 3; the replica applies the `DEL` to the
+    // keyspace, but its index still references the documents until the
+    // notifications are processed. The query tolerates it, still without
+    // repairing.
+    master.apply_del(3);
+    replica.keys.remove(&3);
+    replica.keys.remove(&2);
+    let before = replica.clone();
+    let (results, missing) = replica.query(
+        &replica_mode,
+        150,
+        MissingDocs::default(),
+        &mut not_replicated,
+    );
+    assert_eq!(results, [1]);
+    assert_eq!(missing.missing_docs(), 2);
+    assert_eq!(replica, before);
+    // Repairing the posting list.
+    assert_eq!(replica_mode.skipped_mutations(), 2);
+}
+
+#[test]
+fn test_replay_on_replica() {
+    let mut master = Node::default();
+    for id in 1..=6 {
+        master.add(id, (id % 2 == 0).then_some(id * 100));
+    }
+    let mut replica = master.clone();
+    let master_mode = ExecutionMode::for_role(ServerRole::Master);
+    let replica_mode = ExecutionMode::for_role(ServerRole::Replica);
+
+    // Both nodes serve queries as time passes, and the replica lags behind:
+    // the stream is replayed on it only at the end.
+    let mut replicated = Vec::new();
+    for now in [150, 250, 450, 650] {
+        let (on_master, _) =
+            master.query(&master_mode, now, MissingDocs::default(), &mut replicated);
+        let mut not_replicated = Vec::new();
+        let (on_replica, _) = replica.query(
+            &replica_mode,
+            now,
+            MissingDocs::default(),
+            &mut not_replicated,
+        );
+        // The replica returns the same results without mutating anything.
+        assert_eq!(on_replica, on_master);
+        assert!(not_replicated.is_empty());
+    }
+    assert_eq!(replicated, [2, 4, 6]);
+    assert_ne!(replica, master);
+
+    // Replaying the stream makes the replica converge.
+    for id in replicated {
+        replica.apply_del(id);
+    }
+    assert_eq!(replica, master);
+    let mut not_replicated = Vec::new();
+    let (results, _) = replica.query(
+        &replica_mode,
+        650,
+        MissingDocs::default(),
+        &mut not_replicated,
+    );
+    assert_eq!(results, [1, 3, 5]);
+}
@@ -0,0 +1,196 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use pipeline::{
+    guardrails::{Guardrail, QueryBudget, QueryLimits},
+    reply_stream::{ReplyMode, ReplySink, ReplyStreamer, RowSource, StreamEnd},
+};
+
+struct Rows {
+    next: u32,
+    end: u32,
+}
+
+impl RowSource for Rows {
+    type Row = u32;
+
+    fn next_row(&mut self) -> Option<u32> {
+        (self.next < self.end).then(|| {
+            self.next += 1;
+            self.next - 1
+        })
+    }
+
+    fn total_results(&self) -> u64 {
+        self.end as u64
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Item {
+    Int(i64),
+    Row(u32),
+    Array(usize),
+}
+
+/// Records the reply, each row taking 10 bytes.
+#[derive(Default)]
+struct Sink {
+    flushed: Vec<Item>,
+    buffer: Vec<Item>,
+    open: Vec<usize>,
+    can_flush: bool,
+    flushes: usize,
+}
+
+impl Sink {
+    fn reply(mut self) -> Vec<Item> {
+        assert!(self.open.is_empty());
+        self.flushed.append(&mut self.buffer);
+        self.flushed
+    }
+}
+
+impl ReplySink<u32> for Sink {
+    fn begin_array(&mut self) {
+        self.open.push(self.flushed.len() + self.buffer.len());
+        self.buffer.push(Item::Array(usize::MAX));
+    }
+
+    fn end_array(&mut self, len: usize) {
+        let at = self.open.pop().unwrap();
+        let item = if at < self.flushed.len() {
+            &mut self.flushed[at]
+        } else {
+            &mut self.buffer[at - self.flushed.len()]
+        };
+        *item = Item::Array(len);
+    }
+
+    fn write_int(&mut self, value: i64) {
+        self.buffer.push(Item::Int(value));
+    }
+
+    fn write_row(&mut self, row: &u32) -> usize {
+        self.buffer.push(Item::Row(*row));
+        10
+    }
+
+    fn buffered_bytes(&self) -> usize {
+        self.buffer
+            .iter()
+            .filter(|item| matches!(item, Item::Row(_)))
+            .count()
+            * 10
+    }
+
+    fn can_flush(&self) -> bool {
+        self.can_flush
+    }
+
+    fn flush(&mut self) {
+        self.flushes += 1;
+        self.flushed.append(&mut self.buffer);
+    }
+}
+
+fn rows(range: std::ops::Range<u32>) -> Vec<Item> {
+    range.map(Item::Row).collect()
+}
+
+#[test]
+fn test_full_reply_is_flushed_in_chunks() {
+    let mut streamer = ReplyStreamer::new(100);
+    let mut sink = Sink {
+        can_flush: true,
+        ..Default::default()
+    };
+    let end = streamer
+        .stream(
+            &mut Rows { next: 0, end: 95 },
+            &mut sink,
+            ReplyMode::Full,
+            &mut QueryBudget::default(),
+        )
+        .unwrap();
+    assert_eq!(end, StreamEnd::Done);
+    assert_eq!(sink.flushes, 9);
+    assert_eq!(streamer.stats().flushes, 9);
+    assert_eq!(streamer.stats().rows, 95);
+    assert_eq!(streamer.stats().peak_buffered_bytes, 100);
+
+    let mut expected = vec![Item::Array(96), Item::Int(95)];
+    expected.extend(rows(0..95));
+    assert_eq!(sink.reply(), expected);
+}
+
+#[test]
+fn test_no_flush_when_the_context_does_not_allow_it() {
+    let mut streamer = ReplyStreamer::new(100);
+    let mut sink = Sink::default();
+    streamer
+        .stream(
+            &mut Rows { next: 0, end: 50 },
+            &mut sink,
+            ReplyMode::Full,
+            &mut QueryBudget::default(),
+        )
+        .unwrap();
+    assert_eq!(sink.flushes, 0);
+    assert_eq!(streamer.stats().peak_buffered_bytes, 500);
+}
+
+#[test]
+fn test_cursor_reads_share_the_stream() {
+    let mut streamer = ReplyStreamer::default();
+    let mut source = Rows { next: 0, end: 5 };
+    let mode = ReplyMode::Cursor { id: 7, count: 3 };
+
+    let mut sink = Sink::default();
+    let end = streamer
+        .stream(&mut source, &mut sink, mode, &mut QueryBudget::default())
+        .unwrap();
+    assert_eq!(end, StreamEnd::Paused);
+    let mut expected = vec![Item::Array(2), Item::Array(4), Item::Int(5)];
+    expected.extend(rows(0..3));
+    expected.push(Item::Int(7));
+    assert_eq!(sink.reply(), expected);
+
+    let mut sink = Sink::default();
+    let end = streamer
+        .stream(&mut source, &mut sink, mode, &mut QueryBudget::default())
+        .unwrap();
+    assert_eq!(end, StreamEnd::Done);
+    let mut expected = vec![Item::Array(2), Item::Array(3), Item::Int(5)];
+    expected.extend(rows(3..5));
+    expected.push(Item::Int(0));
+    assert_eq!(sink.reply(), expected);
+}
+
+#[test]
+fn test_reply_size_limit_closes_the_reply() {
+    let mut budget = QueryBudget::new(QueryLimits {
+        max_reply_bytes: 25,
+        ..Default::default()
+    });
+    let mut sink = Sink::default();
+    let err = ReplyStreamer::default()
+        .stream(
+            &mut Rows { next: 0, end: 10 },
+            &mut sink,
+            ReplyMode::Cursor { id: 1, count: 100 },
+            &mut budget,
+        )
+        .unwrap_err();
+    assert_eq!(err.guardrail, Guardrail::ReplySize);
+    let mut expected = vec![Item::Array(2), Item::Array(4), Item::Int(10)];
+    expected.extend(rows(0..3));
+    expected.push(Item::Int(0));
+    assert_eq!(sink.reply(), expected);
+}
@@ -0,0 +1,110 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use pipeline::sampling::{QuerySeed, Reservoir, Sample, SamplingError};
+
+fn parse(args: &[&str]) -> Result<QuerySeed, SamplingError> {
+    let mut seed = QuerySeed::default();
+    let mut args = args.iter().copied();
+    while let Some(name) = args.next() {
+        assert!(seed.try_parse_option(name, &mut args)?, "unknown {name}");
+    }
+    Ok(seed)
+}
+
+fn sample(seed: &QuerySeed, group: &str) -> Vec<u32> {
+    let mut reservoir = Reservoir::new(5, seed.rng([b"sample".as_slice(), group.as_bytes()]));
+    for value in 0..1000 {
+        reservoir.add(value);
+    }
+    assert_eq!(reservoir.seen(), 1000);
+    reservoir.into_sample()
+}
+
+#[test]
+fn test_seed_option() {
+    let seed = parse(&["seed", "42"]).unwrap();
+    assert_eq!(seed.value(), 42);
+    assert!(seed.is_explicit());
+    assert!(!parse(&[]).unwrap().is_explicit());
+
+    assert_eq!(
+        parse(&["SEED"]),
+        Err(SamplingError::MissingArgument("SEED"))
+    );
+    assert_eq!(
+        parse(&["SEED", "-1"]),
+        Err(SamplingError::BadValue {
+            option: "SEED",
+            value: "-1".to_owned()
+        })
+    );
+    assert_eq!(
+        parse(&["SEED", "1", "SEED", "1"]),
+        Err(SamplingError::DuplicateOption("SEED"))
+    );
+    let mut seed = QuerySeed::default();
+    assert_eq!(
+        seed.try_parse_option("LIMIT", &mut ["0", "10"].into_iter()),
+        Ok(false)
+    );
+}
+
+#[test]
+fn test_reproducible_samples() {
+    let seed = QuerySeed::new(7);
+    let first = sample(&seed, "red");
+    assert_eq!(first.len(), 5);
+    assert_eq!(first, sample(&QuerySeed::new(7), "red"));
+    // Each group draws from its own stream.
+    assert_ne!(first, sample(&seed, "blue"));
+    assert_ne!(first, sample(&QuerySeed::new(8), "red"));
+
+    // Fewer values than the sample size are all kept, in order.
+    let mut reservoir = Reservoir::new(5, seed.rng([]));
+    reservoir.add("a");
+    reservoir.add("b");
+    assert_eq!(reservoir.into_sample(), ["a", "b"]);
+}
+
+#[test]
+fn test_sample_step() {
+    let seed = QuerySeed::new(1);
+    let kept = |rate: &str| {
+        let mut sample = Sample::parse(rate, seed.rng([b"SAMPLE".as_slice()])).unwrap();
+        (0..10_000).filter(|_| sample.keep()).count()
+    };
+    assert_eq!(kept("1"), 10_000);
+    let tenth = kept("0.1");
+    assert!((900..1100).contains(&tenth), "{tenth}");
+    assert_eq!(kept("0.1"), tenth);
+
+    for rate in ["0", "1.5", "nan", "x"] {
+        assert_eq!(
+            Sample::parse(rate, seed.rng([])).err(),
+            Some(SamplingError::BadValue {
+                option: "SAMPLE",
+                value: rate.to_owned()
+            })
+        );
+    }
+}
+
+#[test]
+fn test_tie_breaker() {
+    let order = |seed: u64| {
+        let seed = QuerySeed::new(seed);
+        let mut ids: Vec<u64> = (1..=20).collect();
+        ids.sort_by_key(|&id| seed.tie_breaker(id));
+        ids
+    };
+    assert_eq!(order(3), order(3));
+    assert_ne!(order(3), order(4));
+    assert_ne!(order(3), (1..=20).collect::<Vec<_>>());
+}
@@ -0,0 +1,106 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::time::Duration;
+
+use pipeline::slowlog::{
+    QueryTimings, REDACTED, SlowLog, SlowLogCommand, SlowLogCommandError, SlowLogConfig, SlowQuery,
+};
+
+const fn timings(execute_ms: u64) -> QueryTimings {
+    QueryTimings {
+        parse: Duration::from_millis(1),
+        execute: Duration::from_millis(execute_ms),
+        load: Duration::from_millis(2),
+        serialize: Duration::from_millis(1),
+    }
+}
+
+const fn query<'a>(
+    query: &'a str,
+    params: &'a [(&'a str, &'a str)],
+    execute_ms: u64,
+) -> SlowQuery<'a> {
+    SlowQuery {
+        query,
+        params,
+        timings: timings(execute_ms),
+        total_results: 100,
+        returned_results: 10,
+    }
+}
+
+#[test]
+fn test_threshold_and_order() {
+    let log = SlowLog::new(SlowLogConfig {
+        threshold: Duration::from_millis(10),
+        max_len: 2,
+        redact_params: false,
+    });
+    assert!(!log.record(&query("fast", &[], 1), 0));
+    assert!(log.record(&query("a", &[], 6), 1));
+    assert!(log.record(&query("b", &[], 20), 2));
+    assert!(log.record(&query("c", &[], 20), 3));
+
+    let entries = log.get(None);
+    let queries: Vec<_> = entries.iter().map(|e| (e.id, e.query.as_str())).collect();
+    assert_eq!(queries, [(2, "c"), (1, "b")]);
+    assert_eq!(entries[0].timings.total(), Duration::from_millis(24));
+    assert_eq!(log.get(Some(1)).len(), 1);
+
+    log.reset();
+    assert!(log.is_empty());
+    log.record(&query("d", &[], 20), 4);
+    // Ids keep increasing across resets.
+    assert_eq!(log.get(None)[0].id, 3);
+}
+
+#[test]
+fn test_redacted_params() {
+    let log = SlowLog::new(SlowLogConfig {
+        threshold: Duration::ZERO,
+        redact_params: true,
+        ..Default::default()
+    });
+    log.record(&query("@name:$name", &[("name", "alice")], 0), 0);
+    assert_eq!(
+        log.get(None)[0].params,
+        [("name".to_owned(), REDACTED.to_owned())]
+    );
+}
+
+#[test]
+fn test_command() {
+    assert_eq!(
+        SlowLogCommand::parse(&["get"]),
+        Ok(SlowLogCommand::Get(None))
+    );
+    assert_eq!(
+        SlowLogCommand::parse(&["GET", "5"]),
+        Ok(SlowLogCommand::Get(Some(5)))
+    );
+    assert_eq!(SlowLogCommand::parse(&["LEN"]), Ok(SlowLogCommand::Len));
+    assert_eq!(SlowLogCommand::parse(&["reset"]), Ok(SlowLogCommand::Reset));
+    assert_eq!(
+        SlowLogCommand::parse(&["GET", "x"]),
+        Err(SlowLogCommandError::BadCount("x".to_owned()))
+    );
+    assert_eq!(
+        SlowLogCommand::parse(&["LEN", "1"]),
+        Err(SlowLogCommandError::WrongArity)
+    );
+    assert_eq!(
+        SlowLogCommand::parse(&[]),
+        Err(SlowLogCommandError::WrongArity)
+    );
+    assert_eq!(
+        SlowLogCommand::parse(&["DUMP"]),
+        Err(SlowLogCommandError::UnknownSubcommand("DUMP".to_owned()))
+    );
+}
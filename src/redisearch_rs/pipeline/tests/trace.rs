@@ -0,0 +1,71 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{thread, time::Duration};
+
+use pipeline::trace::{QueryTrace, Stage, TraceParent, TraceParentError};
+
+const HEADER: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+#[test]
+fn test_parse_traceparent() {
+    let parent: TraceParent = HEADER.parse().unwrap();
+    assert_eq!(parent.version, 0);
+    assert_eq!(parent.trace_id[0], 0x4b);
+    assert_eq!(
+        parent.parent_id,
+        [0x00, 0xf0, 0x67, 0xaa, 0x0b, 0xa9, 0x02, 0xb7]
+    );
+    assert!(parent.sampled());
+    assert_eq!(parent.to_string(), HEADER);
+
+    // Later versions may add fields.
+    let future: TraceParent = format!("01{}-extra", &HEADER[2..]).parse().unwrap();
+    assert_eq!(future.version, 1);
+}
+
+#[test]
+fn test_invalid_traceparent() {
+    for (header, err) in [
+        ("", TraceParentError::Malformed),
+        (&HEADER[..54], TraceParentError::Malformed),
+        (&HEADER.to_uppercase(), TraceParentError::Malformed),
+        (&format!("{HEADER}-extra"), TraceParentError::Malformed),
+        (
+            &format!("ff{}", &HEADER[2..]),
+            TraceParentError::InvalidVersion,
+        ),
+        (
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            TraceParentError::ZeroId,
+        ),
+        (
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            TraceParentError::ZeroId,
+        ),
+    ] {
+        assert_eq!(header.parse::<TraceParent>(), Err(err), "{header}");
+    }
+}
+
+#[test]
+fn test_stage_timings() {
+    let mut trace = QueryTrace::new("FT.SEARCH", Some(HEADER.parse().unwrap()));
+    assert!(trace.parent().is_some());
+    for stage in [Stage::Parse, Stage::Plan, Stage::Execute] {
+        let _stage = trace.stage(stage);
+        thread::sleep(Duration::from_millis(2));
+    }
+    let timings = trace.timings();
+    // Planning is accounted as parsing.
+    assert!(timings.parse >= Duration::from_millis(4));
+    assert!(timings.execute >= Duration::from_millis(2));
+    assert_eq!(timings.load, Duration::ZERO);
+    assert_eq!(Stage::Serialize.span_name(), "search.serialize");
+}
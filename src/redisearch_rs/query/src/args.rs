@@ -0,0 +1,153 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The query-time arguments of `FT.SEARCH` and `FT.AGGREGATE` that affect how
+//! the query string is parsed and analyzed.
+//!
+//! Options are handled one at a time by [`SearchArgs::try_parse_option`],
+//! leaving the ones it doesn't know about to the caller, so that the
+//! arguments can be migrated from `aggregate_request.c` incrementally.
+
+use std::fmt::{self, Display};
+
+const STOPWORDS_OPT: &str = "STOPWORDS";
+const SCOREFIELDS_OPT: &str = "SCOREFIELDS";
+const VALIDATE_OPT: &str = "VALIDATE";
+const WITHCOST_OPT: &str = "WITHCOST";
+
+/// An invalid query-time argument.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgError {
+    /// The option is missing some of its arguments.
+    MissingArgument(&'static str),
+    BadValue {
+        option: &'static str,
+        value: String,
+    },
+    DuplicateOption(&'static str),
+}
+
+impl Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingArgument(option) => write!(f, "Missing argument for {option}"),
+            Self::BadValue { option, value } => write!(f, "Bad value for {option}: {value}"),
+            Self::DuplicateOption(option) => write!(f, "Option {option} given more than once"),
+        }
+    }
+}
+
+impl std::error::Error for ArgError {}
+
+/// The parsed query-time arguments.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchArgs {
+    stopwords: Option<Vec<String>>,
+    score_fields: Option<Vec<String>>,
+    validate: bool,
+    with_cost: bool,
+}
+
+impl SearchArgs {
+    /// Try to handle the option `name`, consuming its arguments from `args`.
+    ///
+    /// Returns `Ok(false)` if `name` is not handled here, leaving it to the
+    /// caller. Supported options:
+    ///
+    /// - `STOPWORDS {n} {word}...`: the stopwords removed from the query
+    ///   string, instead of those of the index and its fields. `STOPWORDS 0`
+    ///   keeps every word.
+    /// - `SCOREFIELDS {n} {field}...`: the TEXT fields whose matches count
+    ///   towards the score. Unlike `INFIELDS`, documents are still matched in
+    ///   every field.
+    /// - `VALIDATE`: validate the query and return its plan, without executing
+    ///   it. See [`validate`](crate::validate).
+    /// - `WITHCOST`: with `FT.EXPLAIN`, add the estimated cost of the query to
+    ///   its plan.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`ArgError`] if the option's arguments are missing or invalid,
+    /// or if it was already given.
+    pub fn try_parse_option<'a>(
+        &mut self,
+        name: &str,
+        args: &mut impl Iterator<Item = &'a str>,
+    ) -> Result<bool, ArgError> {
+        if name.eq_ignore_ascii_case(STOPWORDS_OPT) {
+            if self.stopwords.is_some() {
+                return Err(ArgError::DuplicateOption(STOPWORDS_OPT));
+            }
+            let words = counted_list(STOPWORDS_OPT, args)?;
+            self.stopwords = Some(words.into_iter().map(|w| w.to_lowercase()).collect());
+        } else if name.eq_ignore_ascii_case(SCOREFIELDS_OPT) {
+            if self.score_fields.is_some() {
+                return Err(ArgError::DuplicateOption(SCOREFIELDS_OPT));
+            }
+            let fields = counted_list(SCOREFIELDS_OPT, args)?;
+            if fields.is_empty() {
+                return Err(ArgError::BadValue {
+                    option: SCOREFIELDS_OPT,
+                    value: "0".to_owned(),
+                });
+            }
+            self.score_fields = Some(fields.into_iter().map(str::to_owned).collect());
+        } else if name.eq_ignore_ascii_case(VALIDATE_OPT) {
+            if self.validate {
+                return Err(ArgError::DuplicateOption(VALIDATE_OPT));
+            }
+            self.validate = true;
+        } else if name.eq_ignore_ascii_case(WITHCOST_OPT) {
+            if self.with_cost {
+                return Err(ArgError::DuplicateOption(WITHCOST_OPT));
+            }
+            self.with_cost = true;
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// The stopwords overriding those of the index, if any.
+    pub fn stopwords(&self) -> Option<&[String]> {
+        self.stopwords.as_deref()
+    }
+
+    /// The TEXT fields restricting the score (`SCOREFIELDS`), if any.
+    pub fn score_fields(&self) -> Option<&[String]> {
+        self.score_fields.as_deref()
+    }
+
+    /// Whether the query is only validated (`VALIDATE`).
+    pub const fn validate(&self) -> bool {
+        self.validate
+    }
+
+    /// Whether `FT.EXPLAIN` reports the estimated cost (`WITHCOST`).
+    pub const fn with_cost(&self) -> bool {
+        self.with_cost
+    }
+}
+
+/// Consume a count followed by that many arguments.
+fn counted_list<'a>(
+    option: &'static str,
+    args: &mut impl Iterator<Item = &'a str>,
+) -> Result<Vec<&'a str>, ArgError> {
+    let count = args.next().ok_or(ArgError::MissingArgument(option))?;
+    let count: usize = count.parse().map_err(|_| ArgError::BadValue {
+        option,
+        value: count.to_owned(),
+    })?;
+    let list = args.take(count).collect::<Vec<_>>();
+    if list.len() < count {
+        return Err(ArgError::MissingArgument(option));
+    }
+    Ok(list)
+}
@@ -0,0 +1,133 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Query attributes, `=>{$name: value; ...}`, and the other per-node modifiers
+//! of the query syntax.
+//!
+//! Starting with [`VERBATIM_SYNTAX_DIALECT`], the verbatim flag can be set per
+//! node rather than for the whole query with `VERBATIM`, so that stemmed and
+//! exact terms can be mixed in a single query:
+//!
+//! - a double-quoted single term, e.g. `"running"`, is kept as written;
+//! - the `$verbatim: true` attribute applies to a node and all its descendants,
+//!   e.g. `(running shoes)=>{$verbatim: true}`.
+//!
+//! Verbatim terms are neither stemmed nor removed as stopwords, and are not
+//! expanded with synonyms or spelling corrections. With older dialects, a
+//! quoted single term is a regular term and `$verbatim` is rejected.
+
+use std::fmt::{self, Display};
+
+use crate::{QueryNode, QueryNodeKind, node::VectorAggregation};
+
+/// The first dialect supporting per-node verbatim syntax.
+pub const VERBATIM_SYNTAX_DIALECT: u32 = 4;
+
+/// An attribute couldn't be applied to its node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttributeError {
+    Unknown(String),
+    BadValue {
+        name: String,
+        value: String,
+    },
+    /// The attribute isn't supported by the dialect of the query.
+    Unsupported {
+        name: String,
+        dialect: u32,
+    },
+}
+
+impl Display for AttributeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(name) => write!(f, "Invalid attribute {name}"),
+            Self::BadValue { name, value } => {
+                write!(f, "Invalid value ({value}) for attribute {name}")
+            }
+            Self::Unsupported { name, dialect } => {
+                write!(f, "Attribute {name} is not supported in dialect {dialect}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AttributeError {}
+
+/// Apply the attribute `$name: value` to `node`, for a query parsed with
+/// `dialect`. `name` doesn't include the `$`.
+///
+/// # Errors
+///
+/// Returns an [`AttributeError`] if the attribute is unknown, unsupported by
+/// `dialect`, or if `value` is invalid.
+pub fn apply_attribute(
+    node: &mut QueryNode,
+    name: &str,
+    value: &str,
+    dialect: u32,
+) -> Result<(), AttributeError> {
+    let bad_value = || AttributeError::BadValue {
+        name: name.to_owned(),
+        value: value.to_owned(),
+    };
+    if name.eq_ignore_ascii_case("weight") {
+        let weight: f64 = value.parse().map_err(|_| bad_value())?;
+        if !(weight.is_finite() && weight >= 0.0) {
+            return Err(bad_value());
+        }
+        node.opts.weight = weight;
+        node.opts.explicit_weight = true;
+    } else if name.eq_ignore_ascii_case("slop") {
+        let slop: i64 = value.parse().map_err(|_| bad_value())?;
+        // A negative slop means no restriction, as in C.
+        node.opts.max_slop = u32::try_from(slop).ok();
+    } else if name.eq_ignore_ascii_case("inorder") {
+        node.opts.in_order = parse_bool(value).ok_or_else(bad_value)?;
+    } else if name.eq_ignore_ascii_case("verbatim") {
+        if dialect < VERBATIM_SYNTAX_DIALECT {
+            return Err(AttributeError::Unsupported {
+                name: name.to_owned(),
+                dialect,
+            });
+        }
+        let verbatim = parse_bool(value).ok_or_else(bad_value)?;
+        node.for_each_mut(&mut |n| n.opts.verbatim = verbatim);
+    } else if name.eq_ignore_ascii_case("aggregation") {
+        let aggregation = match value.to_ascii_lowercase().as_str() {
+            "min" => VectorAggregation::Min,
+            "max" => VectorAggregation::Max,
+            "avg" => VectorAggregation::Avg,
+            _ => return Err(bad_value()),
+        };
+        node.opts.aggregation = Some(aggregation);
+    } else {
+        return Err(AttributeError::Unknown(name.to_owned()));
+    }
+    Ok(())
+}
+
+/// The node for the double-quoted single term `"term"`.
+///
+/// It's verbatim from [`VERBATIM_SYNTAX_DIALECT`], a regular term before.
+pub fn quoted_term(term: impl Into<String>, dialect: u32) -> QueryNode {
+    let mut node = QueryNode::new(QueryNodeKind::Token { term: term.into() });
+    node.opts.verbatim = dialect >= VERBATIM_SYNTAX_DIALECT;
+    node
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    if value.eq_ignore_ascii_case("true") || value == "1" {
+        Some(true)
+    } else if value.eq_ignore_ascii_case("false") || value == "0" {
+        Some(false)
+    } else {
+        None
+    }
+}
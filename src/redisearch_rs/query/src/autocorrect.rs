@@ -0,0 +1,141 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Automatic spelling correction of rare query terms.
+//!
+//! With the `AUTOCORRECT` query flag, every term whose document frequency is
+//! below a threshold is expanded with the best spelling suggestions, as
+//! `FT.SPELLCHECK` would return them: the term is replaced with the union of
+//! itself and the suggestions, the suggestions weighing less than the term.
+//! The [`Substitution`]s are reported to the client as warnings, so it can
+//! show a "did you mean" hint.
+//!
+//! Verbatim terms, and terms under a negation (which would then exclude more
+//! documents than asked for), are never expanded.
+
+use std::fmt::{self, Display};
+
+use crate::{QueryNode, QueryNodeKind};
+
+/// A spelling suggestion for a term.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub term: String,
+    /// The higher the better, as the scores of `FT.SPELLCHECK`.
+    pub score: f64,
+}
+
+/// Where terms and suggestions come from: the terms dictionary of the index.
+pub trait Speller {
+    /// The number of documents containing `term`.
+    fn doc_freq(&self, term: &str) -> u64;
+    /// The terms within `max_distance` edits of `term`.
+    fn suggest(&self, term: &str, max_distance: u8) -> Vec<Suggestion>;
+}
+
+/// A term expanded with suggestions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Substitution {
+    pub term: String,
+    pub suggestions: Vec<String>,
+}
+
+impl Display for Substitution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Term '{}' expanded with ", self.term)?;
+        for (i, suggestion) in self.suggestions.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "'{suggestion}'")?;
+        }
+        Ok(())
+    }
+}
+
+/// The parameters of the `AUTOCORRECT` flag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoCorrect {
+    /// Terms found in fewer documents are expanded.
+    pub min_doc_freq: u64,
+    /// The maximal number of suggestions per term.
+    pub max_suggestions: usize,
+    /// The maximal Levenshtein distance of suggestions, as `DISTANCE` for
+    /// `FT.SPELLCHECK`.
+    pub max_distance: u8,
+    /// The weight of suggestions, relative to the term they replace.
+    pub weight: f64,
+}
+
+impl Default for AutoCorrect {
+    fn default() -> Self {
+        Self {
+            min_doc_freq: 1,
+            max_suggestions: 3,
+            max_distance: 1,
+            weight: 0.5,
+        }
+    }
+}
+
+impl AutoCorrect {
+    /// Expand the rare terms of the query rooted at `root`. Returns the
+    /// substitutions made, in query order.
+    pub fn apply(&self, root: &mut QueryNode, speller: &dyn Speller) -> Vec<Substitution> {
+        let mut substitutions = Vec::new();
+        self.visit(root, speller, &mut substitutions);
+        substitutions
+    }
+
+    fn visit(&self, node: &mut QueryNode, speller: &dyn Speller, out: &mut Vec<Substitution>) {
+        match &node.kind {
+            QueryNodeKind::Not => {}
+            QueryNodeKind::Token { term } if !node.opts.verbatim => {
+                if let Some(substitution) = self.correct(term, speller) {
+                    expand(node, &substitution.suggestions, self.weight);
+                    out.push(substitution);
+                }
+            }
+            _ => {
+                for child in &mut node.children {
+                    self.visit(child, speller, out);
+                }
+            }
+        }
+    }
+
+    fn correct(&self, term: &str, speller: &dyn Speller) -> Option<Substitution> {
+        if speller.doc_freq(term) >= self.min_doc_freq {
+            return None;
+        }
+        let mut suggestions = speller.suggest(term, self.max_distance);
+        suggestions.retain(|s| s.term != term);
+        suggestions.sort_by(|a, b| b.score.total_cmp(&a.score));
+        suggestions.truncate(self.max_suggestions);
+        (!suggestions.is_empty()).then(|| Substitution {
+            term: term.to_owned(),
+            suggestions: suggestions.into_iter().map(|s| s.term).collect(),
+        })
+    }
+}
+
+/// Replace the token `node` with the union of itself and `suggestions`. The
+/// union takes over the options of the token, such as its fields.
+fn expand(node: &mut QueryNode, suggestions: &[String], weight: f64) {
+    let opts = std::mem::take(&mut node.opts);
+    let original = std::mem::replace(node, QueryNode::new(QueryNodeKind::Union));
+    node.opts = opts;
+    node.children.push(original);
+    node.children.extend(suggestions.iter().map(|term| {
+        let mut suggestion = QueryNode::token(term.as_str());
+        suggestion.opts.weight = weight;
+        suggestion.opts.explicit_weight = true;
+        suggestion
+    }));
+}
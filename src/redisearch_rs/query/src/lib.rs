@@ -0,0 +1,25 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The query language: the tree produced by parsing a query string, and the
+//! machinery operating on it before it's turned into an iterator tree.
+
+pub mod args;
+pub mod attributes;
+pub mod autocorrect;
+pub mod node;
+pub mod numeric;
+pub mod params;
+pub mod plan_cache;
+pub mod prepared;
+pub mod rewrite;
+pub mod tag_range;
+pub mod validate;
+
+pub use node::{FieldSelector, QueryNode, QueryNodeKind, QueryNodeOptions};
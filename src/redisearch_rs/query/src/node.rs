@@ -0,0 +1,253 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The query tree, mirroring `QueryNode` from `query_node.h`.
+//!
+//! Unlike the C version, nodes reference fields by name rather than through
+//! `FieldSpec` pointers or field masks: the tree is resolved against the
+//! index schema only once it's been fully rewritten, which keeps it
+//! self-contained and easy to build from outside the parser.
+
+use crate::numeric::NumericRange;
+
+/// Which fields a node applies to, the counterpart of `QueryNodeOptions::fieldMask`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FieldSelector {
+    /// All the fields of the index (`RS_FIELDMASK_ALL`).
+    #[default]
+    All,
+    /// Only the named fields, e.g. `@title|body:(...)`.
+    Named(Vec<String>),
+}
+
+/// Modifiers that can apply to any node, mirroring `QueryNodeOptions`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryNodeOptions {
+    pub fields: FieldSelector,
+    pub weight: f64,
+    /// Whether `weight` was explicitly set by the user in the query.
+    pub explicit_weight: bool,
+    /// `None` means no slop restriction (`-1` in C).
+    pub max_slop: Option<u32>,
+    pub in_order: bool,
+    /// The node must not be expanded (`QueryNode_Verbatim`).
+    pub verbatim: bool,
+    /// How the distances of the vectors of a multi-vector document combine
+    /// into its distance, for KNN nodes (`$AGGREGATION`). `None` keeps the
+    /// closest vector, as [`VectorAggregation::Min`].
+    pub aggregation: Option<VectorAggregation>,
+}
+
+/// How the distances of the vectors of a document combine into the distance
+/// of the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorAggregation {
+    /// The distance of the closest vector.
+    Min,
+    /// The distance of the farthest vector.
+    Max,
+    /// The mean distance of the vectors.
+    Avg,
+}
+
+impl Default for QueryNodeOptions {
+    fn default() -> Self {
+        Self {
+            fields: FieldSelector::All,
+            weight: 1.0,
+            explicit_weight: false,
+            max_slop: None,
+            in_order: false,
+            verbatim: false,
+            aggregation: None,
+        }
+    }
+}
+
+/// The type-specific part of a [`QueryNode`], mirroring `QueryNodeType` and the
+/// union of node structs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryNodeKind {
+    /// Intersection of the children, or an exact phrase if `exact` is set (`QN_PHRASE`).
+    Phrase { exact: bool },
+    /// Union of the children (`QN_UNION`).
+    Union,
+    /// A single term (`QN_TOKEN`).
+    Token { term: String },
+    /// A numeric range on a field (`QN_NUMERIC`).
+    Numeric {
+        field: String,
+        min: f64,
+        max: f64,
+        inclusive_min: bool,
+        inclusive_max: bool,
+    },
+    /// Disjoint numeric ranges on a field, sorted by their lower bound, matched
+    /// with a single descent of the numeric range tree. Produced by
+    /// [`merge_numeric_unions`](crate::numeric::merge_numeric_unions).
+    NumericUnion {
+        field: String,
+        ranges: Vec<NumericRange>,
+    },
+    /// An inclusive range on a field with integer storage, with exact bounds
+    /// rather than `f64`s. Produced by
+    /// [`IntegerRange::into_node`](crate::numeric::IntegerRange::into_node).
+    IntegerNumeric { field: String, min: i64, max: i64 },
+    /// A numeric range whose bounds are parameters, e.g. `[$min ($max]`, kept
+    /// as written until the parameters are resolved into a
+    /// [`Numeric`](Self::Numeric) or [`IntegerNumeric`](Self::IntegerNumeric)
+    /// node by [`resolve_params`](crate::params::resolve_params).
+    ParamNumeric { field: String, range: String },
+    /// Negation of the single child (`QN_NOT`).
+    Not,
+    /// The single child should, but doesn't have to, match (`QN_OPTIONAL`).
+    Optional,
+    /// Prefix, suffix or infix expansion of a term (`QN_PREFIX`).
+    Prefix {
+        term: String,
+        prefix: bool,
+        suffix: bool,
+    },
+    /// A list of document keys (`QN_IDS`).
+    Ids(Vec<String>),
+    /// Matches all the documents (`QN_WILDCARD`).
+    Wildcard,
+    /// A tag field, the children being the tag values (`QN_TAG`).
+    Tag { field: String },
+    /// Levenshtein expansion of a term (`QN_FUZZY`).
+    Fuzzy { term: String, max_dist: u8 },
+    /// Lexical range over the terms of a field (`QN_LEXRANGE`).
+    LexRange {
+        begin: Option<String>,
+        include_begin: bool,
+        end: Option<String>,
+        include_end: bool,
+    },
+    /// A wildcard pattern such as `w'foo*bar'` (`QN_WILDCARD_QUERY`).
+    WildcardQuery { pattern: String },
+    /// A query that's empty but valid, e.g. only stopwords (`QN_NULL`).
+    Null,
+    /// Documents missing a value for the field (`QN_MISSING`).
+    Missing { field: String },
+}
+
+/// A node of the query tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryNode {
+    pub kind: QueryNodeKind,
+    pub opts: QueryNodeOptions,
+    pub children: Vec<QueryNode>,
+}
+
+impl QueryNode {
+    /// Create a childless node of the given kind with default options.
+    pub fn new(kind: QueryNodeKind) -> Self {
+        Self {
+            kind,
+            opts: QueryNodeOptions::default(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Create a node of the given kind with the given children.
+    pub fn with_children(kind: QueryNodeKind, children: Vec<QueryNode>) -> Self {
+        Self {
+            children,
+            ..Self::new(kind)
+        }
+    }
+
+    /// A single term.
+    pub fn token(term: impl Into<String>) -> Self {
+        Self::new(QueryNodeKind::Token { term: term.into() })
+    }
+
+    /// Intersection of `children`.
+    pub fn intersect(children: Vec<QueryNode>) -> Self {
+        Self::with_children(QueryNodeKind::Phrase { exact: false }, children)
+    }
+
+    /// Union of `children`.
+    pub fn union(children: Vec<QueryNode>) -> Self {
+        Self::with_children(QueryNodeKind::Union, children)
+    }
+
+    /// Negation of `child`.
+    pub fn negate(child: QueryNode) -> Self {
+        Self::with_children(QueryNodeKind::Not, vec![child])
+    }
+
+    /// An inclusive numeric range on `field`.
+    pub fn numeric(field: impl Into<String>, min: f64, max: f64) -> Self {
+        Self::new(QueryNodeKind::Numeric {
+            field: field.into(),
+            min,
+            max,
+            inclusive_min: true,
+            inclusive_max: true,
+        })
+    }
+
+    /// A tag node on `field` matching any of `values`.
+    pub fn tag<S: Into<String>>(
+        field: impl Into<String>,
+        values: impl IntoIterator<Item = S>,
+    ) -> Self {
+        let children = values.into_iter().map(Self::token).collect();
+        Self::with_children(
+            QueryNodeKind::Tag {
+                field: field.into(),
+            },
+            children,
+        )
+    }
+
+    /// Restrict `self` with `filter`: the result only matches documents
+    /// matched by both.
+    ///
+    /// If `self` is already a (non-exact) intersection, `filter` is appended to
+    /// its children instead of nesting a new intersection.
+    pub fn and(mut self, filter: QueryNode) -> Self {
+        if self.kind == (QueryNodeKind::Phrase { exact: false })
+            && self.opts == QueryNodeOptions::default()
+        {
+            self.children.push(filter);
+            self
+        } else {
+            Self::intersect(vec![self, filter])
+        }
+    }
+
+    /// Visit this node and all its descendants in pre-order.
+    pub fn for_each(&self, f: &mut impl FnMut(&QueryNode)) {
+        f(self);
+        for child in &self.children {
+            child.for_each(f);
+        }
+    }
+
+    /// Visit this node and all its descendants in pre-order, allowing
+    /// modifications.
+    ///
+    /// The children of a node are visited after `f` returns for that node,
+    /// so `f` may replace them.
+    pub fn for_each_mut(&mut self, f: &mut impl FnMut(&mut QueryNode)) {
+        f(self);
+        for child in &mut self.children {
+            child.for_each_mut(f);
+        }
+    }
+
+    /// The number of nodes in this tree, including `self`.
+    pub fn num_nodes(&self) -> usize {
+        let mut n = 0;
+        self.for_each(&mut |_| n += 1);
+        n
+    }
+}
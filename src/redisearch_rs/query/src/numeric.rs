@@ -0,0 +1,541 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Numeric ranges: their syntax, and the scan of the sorted values of a
+//! numeric range tree leaf.
+//!
+//! A range is written `@field:[min max]`, where each bound is exclusive when
+//! prefixed by `(` and may be `-inf`, `inf` or `+inf`. From dialect 2, the
+//! comparison operators `==`, `!=`, `>`, `>=`, `<` and `<=` are accepted too,
+//! e.g. `@price>=10`: they're turned into the equivalent range, negated for
+//! `!=`, so that later stages only deal with canonical [`NumericRange`]s.
+//! On fields with the `DATETIME` attribute, bounds may also be ISO-8601
+//! literals, e.g. `@created:[2024-01-01 (2025-01-01]`: see [`BoundSyntax`].
+//!
+//! Unions of ranges on the same field, e.g. `@price:[1 5] | @price:[10 20]`
+//! as generated by BI tools, are merged by [`merge_numeric_unions`] into a
+//! single node holding the sorted, disjoint ranges. The tree is then descended
+//! once for all the ranges, and the leaves scanned with [`matching_ranges`],
+//! instead of walking it once per range and merging the results with a heap.
+
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    ops::Range,
+};
+
+use bsearch::{bsearch_range, partition_ge, partition_gt};
+use expr::datetime::parse_datetime_bound;
+
+use crate::{QueryNode, QueryNodeKind, QueryNodeOptions};
+
+/// The first dialect accepting comparison operators.
+pub const OPERATORS_DIALECT: u32 = 2;
+
+/// An invalid numeric range or comparison.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NumericSyntaxError {
+    /// A bound isn't a number.
+    BadValue(String),
+    /// A range doesn't have exactly two bounds.
+    BadRange(String),
+    UnknownOperator(String),
+    /// Comparison operators aren't supported by the dialect of the query.
+    Unsupported {
+        dialect: u32,
+    },
+}
+
+impl Display for NumericSyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadValue(value) => write!(f, "Bad value for numeric range: {value}"),
+            Self::BadRange(range) => write!(f, "Bad numeric range: [{range}]"),
+            Self::UnknownOperator(op) => write!(f, "Unknown numeric operator {op}"),
+            Self::Unsupported { dialect } => {
+                write!(
+                    f,
+                    "Numeric operators are not supported in dialect {dialect}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for NumericSyntaxError {}
+
+/// A range of numeric values, the Rust counterpart of `NumericFilter`.
+///
+/// Infinite bounds are always inclusive, so that equal ranges compare equal
+/// whatever their syntax.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumericRange {
+    pub min: f64,
+    pub max: f64,
+    pub min_inclusive: bool,
+    pub max_inclusive: bool,
+}
+
+impl NumericRange {
+    /// The range `[min max]`.
+    pub const fn inclusive(min: f64, max: f64) -> Self {
+        Self {
+            min,
+            max,
+            min_inclusive: true,
+            max_inclusive: true,
+        }
+    }
+
+    /// Parse the body of `[...]`, e.g. `(10 +inf`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`NumericSyntaxError`] if there aren't exactly two bounds, or
+    /// if a bound isn't a number.
+    pub fn parse(body: &str) -> Result<Self, NumericSyntaxError> {
+        Self::parse_with(body, BoundSyntax::Number)
+    }
+
+    /// Parse the body of `[...]`, with bounds written in `syntax`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`NumericSyntaxError`] if there aren't exactly two bounds, or
+    /// if a bound isn't valid in `syntax`.
+    pub fn parse_with(body: &str, syntax: BoundSyntax) -> Result<Self, NumericSyntaxError> {
+        let mut bounds = body.split_whitespace();
+        let (Some(min), Some(max), None) = (bounds.next(), bounds.next(), bounds.next()) else {
+            return Err(NumericSyntaxError::BadRange(body.to_owned()));
+        };
+        let (min, min_exclusive) = parse_bound(min, syntax)?;
+        let (max, max_exclusive) = parse_bound(max, syntax)?;
+        Ok(Self {
+            min,
+            max,
+            min_inclusive: !min_exclusive,
+            max_inclusive: !max_exclusive,
+        }
+        .canonical())
+    }
+
+    /// The range of values `x` for which `x {op} value` is true. `!=` isn't a
+    /// range: see [`comparison`].
+    fn from_operator(op: &str, value: f64) -> Option<Self> {
+        let (min, min_inclusive, max, max_inclusive) = match op {
+            "==" => (value, true, value, true),
+            ">" => (value, false, f64::INFINITY, true),
+            ">=" => (value, true, f64::INFINITY, true),
+            "<" => (f64::NEG_INFINITY, true, value, false),
+            "<=" => (f64::NEG_INFINITY, true, value, true),
+            _ => return None,
+        };
+        Some(
+            Self {
+                min,
+                max,
+                min_inclusive,
+                max_inclusive,
+            }
+            .canonical(),
+        )
+    }
+
+    const fn canonical(mut self) -> Self {
+        if self.min == f64::NEG_INFINITY {
+            self.min_inclusive = true;
+        }
+        if self.max == f64::INFINITY {
+            self.max_inclusive = true;
+        }
+        self
+    }
+
+    /// Whether no value is in the range, e.g. `[(5 5]`.
+    pub fn is_empty(&self) -> bool {
+        self.min > self.max || (self.min == self.max && !(self.min_inclusive && self.max_inclusive))
+    }
+
+    /// Whether `value` is in the range.
+    pub fn contains(&self, value: f64) -> bool {
+        let min_ok = value > self.min || (self.min_inclusive && value == self.min);
+        let max_ok = value < self.max || (self.max_inclusive && value == self.max);
+        min_ok && max_ok
+    }
+
+    /// The positions of the values in the range, in the `sorted` values of a
+    /// numeric range tree leaf.
+    pub fn matching(&self, sorted: &[f64]) -> Range<usize> {
+        let start = if self.min_inclusive {
+            partition_ge(sorted, &self.min, cmp_value)
+        } else {
+            partition_gt(sorted, &self.min, cmp_value)
+        };
+        let end = if self.max_inclusive {
+            partition_gt(sorted, &self.max, cmp_value)
+        } else {
+            partition_ge(sorted, &self.max, cmp_value)
+        };
+        start..end.max(start)
+    }
+
+    /// Whether `self` and `next`, which doesn't start before `self`, have no gap
+    /// between them, in which case they can be merged.
+    fn touches(&self, next: &Self) -> bool {
+        next.min < self.max || (next.min == self.max && (next.min_inclusive || self.max_inclusive))
+    }
+
+    /// The query node matching the values of `field` in the range.
+    pub fn into_node(self, field: impl Into<String>) -> QueryNode {
+        QueryNode::new(QueryNodeKind::Numeric {
+            field: field.into(),
+            min: self.min,
+            max: self.max,
+            inclusive_min: self.min_inclusive,
+            inclusive_max: self.max_inclusive,
+        })
+    }
+}
+
+/// How the bounds of a range, or the value of a comparison, are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BoundSyntax {
+    /// Plain numbers, including `inf`, `+inf` and `-inf`.
+    #[default]
+    Number,
+    /// Numbers, or ISO-8601 literals converted to their timestamp, on fields
+    /// with the `DATETIME` attribute.
+    DateTime,
+}
+
+/// The options of the NUMERIC fields of a schema which change how their
+/// ranges are parsed.
+pub trait NumericSchema {
+    /// How the bounds of range queries on `field` are written.
+    fn bound_syntax(&self, field: &str) -> BoundSyntax;
+
+    /// Whether the values of `field` are stored as exact integers, in which
+    /// case its ranges are parsed as [`IntegerRange`]s.
+    fn integer_storage(&self, _field: &str) -> bool {
+        false
+    }
+}
+
+/// A schema whose NUMERIC fields all take plain numbers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlainNumbers;
+
+impl NumericSchema for PlainNumbers {
+    fn bound_syntax(&self, _field: &str) -> BoundSyntax {
+        BoundSyntax::Number
+    }
+}
+
+/// A range of values of a field with integer storage.
+///
+/// Bounds are parsed exactly, rather than through an `f64` which can't hold
+/// integers above 2^53 such as snowflake IDs. Exclusive and fractional bounds
+/// are turned into the equivalent inclusive integer bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntegerRange {
+    pub min: i64,
+    pub max: i64,
+}
+
+impl IntegerRange {
+    /// A range containing no value.
+    pub const EMPTY: Self = Self {
+        min: i64::MAX,
+        max: i64::MIN,
+    };
+
+    /// Parse the body of `[...]`, e.g. `(1234567890123456789 +inf`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`NumericSyntaxError`] if there aren't exactly two bounds, or
+    /// if a bound isn't a number.
+    pub fn parse(body: &str) -> Result<Self, NumericSyntaxError> {
+        Self::parse_with(body, BoundSyntax::Number)
+    }
+
+    /// Parse the body of `[...]`, with bounds written in `syntax`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`NumericSyntaxError`] if there aren't exactly two bounds, or
+    /// if a bound isn't valid in `syntax`.
+    pub fn parse_with(body: &str, syntax: BoundSyntax) -> Result<Self, NumericSyntaxError> {
+        let mut bounds = body.split_whitespace();
+        let (Some(min), Some(max), None) = (bounds.next(), bounds.next(), bounds.next()) else {
+            return Err(NumericSyntaxError::BadRange(body.to_owned()));
+        };
+        let min = parse_integer_bound(min, true, syntax)?;
+        let max = parse_integer_bound(max, false, syntax)?;
+        if min > max || min > i128::from(i64::MAX) || max < i128::from(i64::MIN) {
+            return Ok(Self::EMPTY);
+        }
+        let clamp = |v: i128| v.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64;
+        Ok(Self {
+            min: clamp(min),
+            max: clamp(max),
+        })
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.min > self.max
+    }
+
+    pub const fn contains(&self, value: i64) -> bool {
+        self.min <= value && value <= self.max
+    }
+
+    /// The positions of the values in the range, in the `sorted` values of a
+    /// numeric range tree leaf.
+    pub fn matching(&self, sorted: &[i64]) -> Range<usize> {
+        if self.is_empty() {
+            return 0..0;
+        }
+        bsearch_range(sorted, &self.min, &self.max, i64::cmp).unwrap_or(0..0)
+    }
+
+    /// The range with `f64` bounds, which may round bounds above 2^53.
+    pub const fn to_numeric(self) -> NumericRange {
+        if self.is_empty() {
+            return NumericRange {
+                min: 0.0,
+                max: 0.0,
+                min_inclusive: false,
+                max_inclusive: false,
+            };
+        }
+        NumericRange::inclusive(self.min as f64, self.max as f64)
+    }
+
+    /// The query node matching the values of `field` in the range.
+    pub fn into_node(self, field: impl Into<String>) -> QueryNode {
+        QueryNode::new(QueryNodeKind::IntegerNumeric {
+            field: field.into(),
+            min: self.min,
+            max: self.max,
+        })
+    }
+}
+
+/// The node for the comparison `@field {op} value`, e.g. `@price>=10`.
+///
+/// # Errors
+///
+/// Returns a [`NumericSyntaxError`] if operators aren't supported by `dialect`,
+/// if `op` is unknown or if `value` isn't a number.
+pub fn comparison(
+    field: &str,
+    op: &str,
+    value: &str,
+    dialect: u32,
+) -> Result<QueryNode, NumericSyntaxError> {
+    comparison_with(field, op, value, dialect, BoundSyntax::Number)
+}
+
+/// The node for the comparison `@field {op} value`, with `value` written in
+/// `syntax`, e.g. `@created>=2024-01-01`.
+///
+/// # Errors
+///
+/// Returns a [`NumericSyntaxError`] if operators aren't supported by `dialect`,
+/// if `op` is unknown or if `value` isn't valid in `syntax`.
+pub fn comparison_with(
+    field: &str,
+    op: &str,
+    value: &str,
+    dialect: u32,
+    syntax: BoundSyntax,
+) -> Result<QueryNode, NumericSyntaxError> {
+    if dialect < OPERATORS_DIALECT {
+        return Err(NumericSyntaxError::Unsupported { dialect });
+    }
+    let value = parse_value(value, syntax)?;
+    if op == "!=" {
+        return Ok(QueryNode::negate(
+            NumericRange::inclusive(value, value).into_node(field),
+        ));
+    }
+    NumericRange::from_operator(op, value)
+        .map(|range| range.into_node(field))
+        .ok_or_else(|| NumericSyntaxError::UnknownOperator(op.to_owned()))
+}
+
+/// The positions of the values in each of `ranges`, sorted and disjoint, in
+/// the `sorted` values of a numeric range tree leaf.
+///
+/// The leaf is scanned once: the search for each range starts where the
+/// previous one ended.
+pub fn matching_ranges(ranges: &[NumericRange], sorted: &[f64]) -> Vec<Range<usize>> {
+    let mut offset = 0;
+    ranges
+        .iter()
+        .map(|range| {
+            let found = range.matching(&sorted[offset..]);
+            let found = offset + found.start..offset + found.end;
+            offset = found.end;
+            found
+        })
+        .collect()
+}
+
+/// Merge the numeric ranges on the same field under every union of the tree
+/// rooted at `root`.
+///
+/// Overlapping and adjacent ranges are coalesced, and empty ones dropped.
+/// Only ranges without modifiers, such as a weight, are merged. A union left
+/// with a single child is replaced by it.
+pub fn merge_numeric_unions(root: &mut QueryNode) {
+    for child in &mut root.children {
+        merge_numeric_unions(child);
+    }
+    if root.kind != QueryNodeKind::Union {
+        return;
+    }
+
+    let mut fields: Vec<(String, Vec<NumericRange>, usize)> = Vec::new();
+    let mut others = Vec::new();
+    for (i, child) in std::mem::take(&mut root.children).into_iter().enumerate() {
+        match range_of(&child) {
+            Some((field, range)) => match fields.iter_mut().find(|(f, ..)| f == field) {
+                Some((_, ranges, _)) => ranges.push(range),
+                None => fields.push((field.to_owned(), vec![range], i)),
+            },
+            None => others.push((i, child)),
+        }
+    }
+    for (field, ranges, i) in fields {
+        others.push((i, merged_node(field, ranges)));
+    }
+    others.sort_by_key(|(i, _)| *i);
+    root.children = others.into_iter().map(|(_, child)| child).collect();
+
+    if root.children.len() == 1 && root.opts == QueryNodeOptions::default() {
+        *root = root.children.pop().expect("one child");
+    }
+}
+
+fn range_of(node: &QueryNode) -> Option<(&str, NumericRange)> {
+    match &node.kind {
+        QueryNodeKind::Numeric {
+            field,
+            min,
+            max,
+            inclusive_min,
+            inclusive_max,
+        } if node.opts == QueryNodeOptions::default() => Some((
+            field,
+            NumericRange {
+                min: *min,
+                max: *max,
+                min_inclusive: *inclusive_min,
+                max_inclusive: *inclusive_max,
+            },
+        )),
+        _ => None,
+    }
+}
+
+fn merged_node(field: String, mut ranges: Vec<NumericRange>) -> QueryNode {
+    ranges.retain(|r| !r.is_empty());
+    // Inclusive lower bounds first, so that they absorb the exclusive ones.
+    ranges.sort_by(|a, b| cmp_value(&a.min, &b.min).then(b.min_inclusive.cmp(&a.min_inclusive)));
+    let mut merged: Vec<NumericRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if last.touches(&range) => match cmp_value(&range.max, &last.max) {
+                Ordering::Greater => {
+                    last.max = range.max;
+                    last.max_inclusive = range.max_inclusive;
+                }
+                Ordering::Equal => last.max_inclusive |= range.max_inclusive,
+                Ordering::Less => {}
+            },
+            _ => merged.push(range),
+        }
+    }
+    match merged.as_slice() {
+        [] => QueryNode::new(QueryNodeKind::Null),
+        [range] => range.into_node(field),
+        _ => QueryNode::new(QueryNodeKind::NumericUnion {
+            field,
+            ranges: merged,
+        }),
+    }
+}
+
+/// A bound, and whether it's exclusive.
+fn parse_bound(bound: &str, syntax: BoundSyntax) -> Result<(f64, bool), NumericSyntaxError> {
+    match bound.strip_prefix('(') {
+        Some(value) => Ok((parse_value(value, syntax)?, true)),
+        None => Ok((parse_value(bound, syntax)?, false)),
+    }
+}
+
+/// The inclusive integer equivalent of a bound, which may lie outside of the
+/// `i64` range, e.g. for infinite bounds.
+fn parse_integer_bound(
+    bound: &str,
+    lower: bool,
+    syntax: BoundSyntax,
+) -> Result<i128, NumericSyntaxError> {
+    let (value, exclusive) = match bound.strip_prefix('(') {
+        Some(value) => (value, true),
+        None => (bound, false),
+    };
+    let exclusive = i128::from(exclusive);
+    if let Ok(exact) = value.parse::<i64>() {
+        let exact = i128::from(exact);
+        return Ok(if lower {
+            exact + exclusive
+        } else {
+            exact - exclusive
+        });
+    }
+    let value = parse_value(value, syntax)?;
+    if value.is_infinite() {
+        let beyond = i128::from(i64::MAX) + 1;
+        return Ok(if value > 0.0 { beyond } else { -beyond - 1 });
+    }
+    // Out of range values saturate, which is enough to compare them with the
+    // bounds of the `i64` range.
+    Ok(if lower {
+        if exclusive == 1 {
+            value.floor() as i128 + 1
+        } else {
+            value.ceil() as i128
+        }
+    } else if exclusive == 1 {
+        value.ceil() as i128 - 1
+    } else {
+        value.floor() as i128
+    })
+}
+
+fn parse_value(value: &str, syntax: BoundSyntax) -> Result<f64, NumericSyntaxError> {
+    let bad_value = || NumericSyntaxError::BadValue(value.to_owned());
+    let number: f64 = match syntax {
+        BoundSyntax::Number => value.parse().map_err(|_| bad_value())?,
+        BoundSyntax::DateTime => parse_datetime_bound(value).map_err(|_| bad_value())?,
+    };
+    if number.is_nan() {
+        return Err(bad_value());
+    }
+    Ok(number)
+}
+
+/// Numeric indexes don't hold NaNs, for which the total order is a fallback
+/// keeping the search well-defined.
+fn cmp_value(a: &f64, b: &f64) -> Ordering {
+    a.partial_cmp(b).unwrap_or_else(|| a.total_cmp(b))
+}
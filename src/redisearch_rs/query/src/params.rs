@@ -0,0 +1,151 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Query parameters, `$name`, given values with `PARAMS`.
+//!
+//! Cached and prepared plans keep their parameters unresolved: terms, tag
+//! values, lexical range bounds, document IDs and wildcard patterns hold the
+//! `$name` placeholder, and numeric ranges with parameter bounds are
+//! [`QueryNodeKind::ParamNumeric`] nodes. [`resolve_params`] substitutes the
+//! values in a copy of the plan.
+
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+};
+
+use crate::{
+    QueryNode, QueryNodeKind,
+    numeric::{IntegerRange, NumericRange, NumericSchema, NumericSyntaxError},
+};
+
+/// The prefix of parameter names in a query.
+pub const PARAM_PREFIX: char = '$';
+
+/// A parameter couldn't be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamError {
+    /// No value was given for the parameter.
+    Missing(String),
+    /// The parameters of a numeric range don't make a valid range.
+    BadNumericRange(NumericSyntaxError),
+}
+
+impl Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(name) => write!(f, "No such parameter `{name}`"),
+            Self::BadNumericRange(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+/// The name of the parameter `s` refers to, if it's a placeholder.
+pub fn param_name(s: &str) -> Option<&str> {
+    s.strip_prefix(PARAM_PREFIX).filter(|name| !name.is_empty())
+}
+
+/// The names of the parameters referred to by the tree rooted at `root`.
+pub fn referenced_params(root: &QueryNode) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    root.for_each(&mut |node| {
+        for value in placeholders(&node.kind) {
+            if let Some(name) = param_name(value) {
+                names.insert(name.to_owned());
+            }
+        }
+        if let QueryNodeKind::ParamNumeric { range, .. } = &node.kind {
+            names.extend(range_params(range).map(str::to_owned));
+        }
+    });
+    names
+}
+
+/// Substitute the parameters of the tree rooted at `root` with their values,
+/// as returned by `lookup`. The bounds of numeric ranges are parsed with the
+/// syntax of their field in `numeric`, as an [`IntegerRange`] if the field has
+/// integer storage.
+///
+/// # Errors
+///
+/// Returns a [`ParamError`] if a parameter has no value, or if a numeric range
+/// isn't valid once its parameters are resolved.
+pub fn resolve_params<'a>(
+    root: &mut QueryNode,
+    lookup: &impl Fn(&str) -> Option<&'a str>,
+    numeric: &(impl NumericSchema + ?Sized),
+) -> Result<(), ParamError> {
+    let resolve = |value: &mut String| -> Result<(), ParamError> {
+        if let Some(name) = param_name(value) {
+            let resolved = lookup(name).ok_or_else(|| ParamError::Missing(name.to_owned()))?;
+            *value = resolved.to_owned();
+        }
+        Ok(())
+    };
+    match &mut root.kind {
+        QueryNodeKind::Token { term }
+        | QueryNodeKind::Prefix { term, .. }
+        | QueryNodeKind::Fuzzy { term, .. } => resolve(term)?,
+        QueryNodeKind::WildcardQuery { pattern } => resolve(pattern)?,
+        QueryNodeKind::Ids(ids) => ids.iter_mut().try_for_each(resolve)?,
+        QueryNodeKind::LexRange { begin, end, .. } => begin
+            .iter_mut()
+            .chain(end.iter_mut())
+            .try_for_each(resolve)?,
+        QueryNodeKind::ParamNumeric { field, range } => {
+            let mut resolved = Vec::new();
+            for bound in range.split_whitespace() {
+                let (exclusive, value) = match bound.strip_prefix('(') {
+                    Some(value) => ("(", value),
+                    None => ("", bound),
+                };
+                let mut value = value.to_owned();
+                resolve(&mut value)?;
+                resolved.push(format!("{exclusive}{value}"));
+            }
+            let body = resolved.join(" ");
+            let syntax = numeric.bound_syntax(field);
+            let field = std::mem::take(field);
+            let node = if numeric.integer_storage(&field) {
+                IntegerRange::parse_with(&body, syntax).map(|range| range.into_node(field))
+            } else {
+                NumericRange::parse_with(&body, syntax).map(|range| range.into_node(field))
+            };
+            root.kind = node.map_err(ParamError::BadNumericRange)?.kind;
+        }
+        _ => {}
+    }
+    for child in &mut root.children {
+        resolve_params(child, lookup, numeric)?;
+    }
+    Ok(())
+}
+
+/// The string attributes of a node that may hold a placeholder.
+fn placeholders(kind: &QueryNodeKind) -> Vec<&str> {
+    match kind {
+        QueryNodeKind::Token { term }
+        | QueryNodeKind::Prefix { term, .. }
+        | QueryNodeKind::Fuzzy { term, .. } => vec![term],
+        QueryNodeKind::WildcardQuery { pattern } => vec![pattern],
+        QueryNodeKind::Ids(ids) => ids.iter().map(String::as_str).collect(),
+        QueryNodeKind::LexRange { begin, end, .. } => {
+            begin.iter().chain(end).map(String::as_str).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn range_params(range: &str) -> impl Iterator<Item = &str> {
+    range
+        .split_whitespace()
+        .filter_map(|bound| param_name(bound.strip_prefix('(').unwrap_or(bound)))
+}
@@ -0,0 +1,220 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! A cache of parsed and optimized query plans.
+//!
+//! Applications typically send the same few query templates over and over,
+//! only changing the values of their `PARAMS`. The plans are cached with their
+//! parameters left unresolved, keyed by the query string and the dialect, so
+//! that a repeated template skips parsing and optimization: parameters are
+//! substituted in a copy of the cached plan.
+//!
+//! Plans refer to the schema, so the whole cache is invalidated whenever the
+//! index is altered. The least recently used plan is evicted when the cache is
+//! full.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+/// The default number of plans cached per index.
+pub const DEFAULT_CAPACITY: usize = 128;
+
+/// What identifies a cached plan.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlanKey {
+    /// The query string, with its parameters unresolved.
+    pub query: String,
+    pub dialect: u32,
+}
+
+/// Counters reported by `FT.INFO`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlanCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub invalidations: u64,
+}
+
+impl PlanCacheStats {
+    /// The fraction of lookups served from the cache, `0` before any lookup.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+struct Entry<P> {
+    plan: Arc<P>,
+    last_used: u64,
+}
+
+struct Inner<P> {
+    entries: HashMap<PlanKey, Entry<P>>,
+    /// The keys by last use, the least recently used first.
+    by_use: BTreeMap<u64, PlanKey>,
+    tick: u64,
+    /// Incremented by every invalidation, so that a plan built against the
+    /// schema before it isn't cached after it.
+    epoch: u64,
+    stats: PlanCacheStats,
+}
+
+/// See the [module documentation](self).
+pub struct PlanCache<P> {
+    capacity: usize,
+    inner: Mutex<Inner<P>>,
+}
+
+impl<P> PlanCache<P> {
+    /// A cache holding up to `capacity` plans. A capacity of `0` disables it.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(Inner {
+                entries: HashMap::new(),
+                by_use: BTreeMap::new(),
+                tick: 0,
+                epoch: 0,
+                stats: PlanCacheStats::default(),
+            }),
+        }
+    }
+
+    /// The plan for `key`, built with `build` if it isn't cached.
+    ///
+    /// `build` is called without holding the cache lock, so concurrent misses
+    /// on the same key may both build the plan. Errors aren't cached, and
+    /// neither are plans whose build overlapped an [invalidation](Self::invalidate),
+    /// as they may refer to the old schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `build`.
+    pub fn get_or_build<E>(
+        &self,
+        key: &PlanKey,
+        build: impl FnOnce() -> Result<P, E>,
+    ) -> Result<Arc<P>, E> {
+        let epoch = match self.lookup(key) {
+            Ok(plan) => return Ok(plan),
+            Err(epoch) => epoch,
+        };
+        let plan = Arc::new(build()?);
+        if self.capacity > 0 {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.epoch == epoch {
+                self.insert_locked(&mut inner, key.clone(), Arc::clone(&plan));
+            }
+        }
+        Ok(plan)
+    }
+
+    /// The cached plan for `key`, if any. Counts as a hit or a miss.
+    pub fn get(&self, key: &PlanKey) -> Option<Arc<P>> {
+        self.lookup(key).ok()
+    }
+
+    /// The cached plan for `key`, or the current invalidation epoch on a miss.
+    fn lookup(&self, key: &PlanKey) -> Result<Arc<P>, u64> {
+        let mut inner = self.inner.lock().unwrap();
+        let inner = &mut *inner;
+        inner.tick += 1;
+        let Some(entry) = inner.entries.get_mut(key) else {
+            inner.stats.misses += 1;
+            return Err(inner.epoch);
+        };
+        inner.stats.hits += 1;
+        let key = inner
+            .by_use
+            .remove(&entry.last_used)
+            .expect("cached plans are tracked by use");
+        entry.last_used = inner.tick;
+        inner.by_use.insert(inner.tick, key);
+        Ok(Arc::clone(&entry.plan))
+    }
+
+    /// Cache `plan` for `key`, evicting the least recently used plan if the
+    /// cache is full.
+    pub fn insert(&self, key: PlanKey, plan: Arc<P>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        self.insert_locked(&mut inner, key, plan);
+    }
+
+    fn insert_locked(&self, inner: &mut Inner<P>, key: PlanKey, plan: Arc<P>) {
+        inner.tick += 1;
+        let entry = Entry {
+            plan,
+            last_used: inner.tick,
+        };
+        if let Some(old) = inner.entries.insert(key.clone(), entry) {
+            inner.by_use.remove(&old.last_used);
+        } else if inner.entries.len() > self.capacity {
+            let (_, lru) = inner.by_use.pop_first().expect("the cache isn't empty");
+            inner.entries.remove(&lru);
+            inner.stats.evictions += 1;
+        }
+        inner.by_use.insert(inner.tick, key);
+    }
+
+    /// Drop all the plans, after a change to the schema.
+    pub fn invalidate(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.entries.clear();
+        inner.by_use.clear();
+        inner.epoch += 1;
+        inner.stats.invalidations += 1;
+    }
+
+    /// The number of cached plans.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn stats(&self) -> PlanCacheStats {
+        self.inner.lock().unwrap().stats
+    }
+
+    /// The fields reported in the `plan_cache` section of `FT.INFO`.
+    pub fn info(&self) -> Vec<(&'static str, f64)> {
+        let stats = self.stats();
+        vec![
+            ("size", self.len() as f64),
+            ("capacity", self.capacity as f64),
+            ("hits", stats.hits as f64),
+            ("misses", stats.misses as f64),
+            ("hit_rate", stats.hit_rate()),
+            ("evictions", stats.evictions as f64),
+            ("invalidations", stats.invalidations as f64),
+        ]
+    }
+}
+
+impl<P> Default for PlanCache<P> {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
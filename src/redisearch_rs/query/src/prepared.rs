@@ -0,0 +1,276 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Prepared queries: named query templates with typed parameters.
+//!
+//! High-QPS applications register their templates once, and then only send
+//! the name of the template and the values of its parameters, rather than the
+//! whole query string on every request.
+//!
+//! Templates are validated when they're prepared: the query must parse, and
+//! its parameters must be exactly the declared ones. Values are checked
+//! against the declared types when the query is executed. The parsed plans
+//! are kept in a [`PlanCache`], so that they're parsed again, and validated
+//! against the new schema, after the index is altered.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::{self, Display},
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    QueryNode,
+    numeric::NumericSchema,
+    params::{ParamError, referenced_params, resolve_params},
+    plan_cache::{PlanCache, PlanKey},
+};
+
+/// The type of a parameter of a prepared query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamType {
+    /// Any string: a term, a tag, a pattern...
+    Text,
+    /// A number, or `-inf`/`+inf`.
+    Numeric,
+}
+
+impl ParamType {
+    fn accepts(self, value: &str) -> bool {
+        match self {
+            Self::Text => true,
+            Self::Numeric => value.parse::<f64>().is_ok_and(|v| !v.is_nan()),
+        }
+    }
+}
+
+impl FromStr for ParamType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("TEXT") {
+            Ok(Self::Text)
+        } else if s.eq_ignore_ascii_case("NUMERIC") {
+            Ok(Self::Numeric)
+        } else {
+            Err(())
+        }
+    }
+}
+
+/// A query template couldn't be prepared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrepareError {
+    /// A parameter was declared more than once.
+    DuplicateParam(String),
+    /// The query refers to a parameter that wasn't declared.
+    UndeclaredParam(String),
+    /// A declared parameter isn't used by the query.
+    UnusedParam(String),
+    /// The query doesn't parse.
+    Parse(String),
+}
+
+impl Display for PrepareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateParam(name) => write!(f, "Parameter `{name}` declared twice"),
+            Self::UndeclaredParam(name) => write!(f, "Undeclared parameter `{name}`"),
+            Self::UnusedParam(name) => write!(f, "Unused parameter `{name}`"),
+            Self::Parse(e) => write!(f, "Syntax error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PrepareError {}
+
+/// A prepared query couldn't be executed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecuteError {
+    UnknownQuery(String),
+    /// A value was given for a parameter the query doesn't declare.
+    UnknownParam(String),
+    /// The value of a parameter doesn't match its declared type.
+    BadValue {
+        param: String,
+        value: String,
+    },
+    /// The template is no longer valid, after a change to the schema.
+    Prepare(PrepareError),
+    Param(ParamError),
+}
+
+impl Display for ExecuteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownQuery(name) => write!(f, "Unknown prepared query `{name}`"),
+            Self::UnknownParam(name) => write!(f, "Unknown parameter `{name}`"),
+            Self::BadValue { param, value } => {
+                write!(f, "Invalid value `{value}` for parameter `{param}`")
+            }
+            Self::Prepare(e) => e.fmt(f),
+            Self::Param(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ExecuteError {}
+
+/// Parses a query string with the given dialect.
+pub type QueryParser = Box<dyn Fn(&str, u32) -> Result<QueryNode, String> + Send + Sync>;
+
+/// A registered template.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparedQuery {
+    pub query: String,
+    pub dialect: u32,
+    pub params: Vec<(String, ParamType)>,
+}
+
+impl PreparedQuery {
+    fn key(&self) -> PlanKey {
+        PlanKey {
+            query: self.query.clone(),
+            dialect: self.dialect,
+        }
+    }
+}
+
+/// The prepared queries of an index.
+pub struct PreparedQueries {
+    parse: QueryParser,
+    queries: RwLock<HashMap<String, Arc<PreparedQuery>>>,
+    plans: PlanCache<QueryNode>,
+}
+
+impl PreparedQueries {
+    /// `parse` turns a template into a plan, leaving its parameters unresolved.
+    pub fn new(parse: QueryParser, plans: PlanCache<QueryNode>) -> Self {
+        Self {
+            parse,
+            queries: RwLock::default(),
+            plans,
+        }
+    }
+
+    /// Register `query` as `name`, replacing any query with the same name.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PrepareError`] if the query doesn't parse, or if its
+    /// parameters aren't exactly `params`.
+    pub fn prepare(
+        &self,
+        name: &str,
+        query: &str,
+        dialect: u32,
+        params: Vec<(String, ParamType)>,
+    ) -> Result<(), PrepareError> {
+        let mut declared = BTreeSet::new();
+        for (param, _) in &params {
+            if !declared.insert(param.as_str()) {
+                return Err(PrepareError::DuplicateParam(param.clone()));
+            }
+        }
+        let prepared = PreparedQuery {
+            query: query.to_owned(),
+            dialect,
+            params,
+        };
+        self.plan(&prepared)?;
+        self.queries
+            .write()
+            .unwrap()
+            .insert(name.to_owned(), Arc::new(prepared));
+        Ok(())
+    }
+
+    /// Unregister the query `name`. Returns whether it existed.
+    pub fn remove(&self, name: &str) -> bool {
+        self.queries.write().unwrap().remove(name).is_some()
+    }
+
+    /// The query registered as `name`.
+    pub fn get(&self, name: &str) -> Option<Arc<PreparedQuery>> {
+        self.queries.read().unwrap().get(name).cloned()
+    }
+
+    /// The plan of the query `name` with its parameters set to `values`, and
+    /// its numeric ranges parsed as the fields of `numeric` require.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`ExecuteError`] if there's no such query, if `values` don't
+    /// match its parameters, or if it's no longer valid.
+    pub fn execute(
+        &self,
+        name: &str,
+        values: &[(&str, &str)],
+        numeric: &impl NumericSchema,
+    ) -> Result<QueryNode, ExecuteError> {
+        let prepared = self
+            .get(name)
+            .ok_or_else(|| ExecuteError::UnknownQuery(name.to_owned()))?;
+        for (param, value) in values {
+            let (_, ty) = prepared
+                .params
+                .iter()
+                .find(|(declared, _)| declared == param)
+                .ok_or_else(|| ExecuteError::UnknownParam((*param).to_owned()))?;
+            if !ty.accepts(value) {
+                return Err(ExecuteError::BadValue {
+                    param: (*param).to_owned(),
+                    value: (*value).to_owned(),
+                });
+            }
+        }
+        let plan = self.plan(&prepared).map_err(ExecuteError::Prepare)?;
+        let mut plan = QueryNode::clone(&plan);
+        let lookup = |param: &str| {
+            values
+                .iter()
+                .find(|(name, _)| *name == param)
+                .map(|(_, value)| *value)
+        };
+        resolve_params(&mut plan, &lookup, numeric).map_err(ExecuteError::Param)?;
+        Ok(plan)
+    }
+
+    /// Drop the cached plans, after a change to the schema.
+    pub fn invalidate(&self) {
+        self.plans.invalidate();
+    }
+
+    pub const fn plans(&self) -> &PlanCache<QueryNode> {
+        &self.plans
+    }
+
+    fn plan(&self, prepared: &PreparedQuery) -> Result<Arc<QueryNode>, PrepareError> {
+        self.plans.get_or_build(&prepared.key(), || {
+            let plan =
+                (self.parse)(&prepared.query, prepared.dialect).map_err(PrepareError::Parse)?;
+            let used = referenced_params(&plan);
+            for (param, _) in &prepared.params {
+                if !used.contains(param) {
+                    return Err(PrepareError::UnusedParam(param.clone()));
+                }
+            }
+            if let Some(undeclared) = used.into_iter().find(|param| {
+                prepared
+                    .params
+                    .iter()
+                    .all(|(declared, _)| declared != param)
+            }) {
+                return Err(PrepareError::UndeclaredParam(undeclared));
+            }
+            Ok(plan)
+        })
+    }
+}
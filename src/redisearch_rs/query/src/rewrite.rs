@@ -0,0 +1,238 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Query rewriting hooks.
+//!
+//! Embedders can install callbacks that run on every query:
+//! - *pre-parse* hooks receive the raw query string and may return a new one;
+//! - *post-parse* hooks receive the parsed [`QueryNode`] tree and may modify it
+//!   in place, e.g. to inject a mandatory tenant filter.
+//!
+//! Hooks of a stage run by ascending priority, hooks with the same priority
+//! running in registration order. Each hook sees the output of the previous
+//! one. The first hook returning an error stops the stage, and the error is
+//! reported to the client with the name of the failing hook.
+
+use std::{
+    borrow::Cow,
+    fmt::{self, Display},
+    sync::RwLock,
+};
+
+use crate::QueryNode;
+
+/// Information about the query being rewritten.
+#[derive(Debug, Clone, Copy)]
+pub struct RewriteContext<'a> {
+    /// The name of the index being queried.
+    pub index_name: &'a str,
+    /// The dialect the query is parsed with.
+    pub dialect: u32,
+}
+
+/// A hook receiving the raw query string.
+///
+/// Returns `Ok(None)` to leave the query untouched, `Ok(Some(_))` to replace it.
+pub type PreParseHook =
+    Box<dyn Fn(&RewriteContext<'_>, &str) -> Result<Option<String>, String> + Send + Sync>;
+
+/// A hook receiving the root of the parsed query tree.
+pub type PostParseHook =
+    Box<dyn Fn(&RewriteContext<'_>, &mut QueryNode) -> Result<(), String> + Send + Sync>;
+
+/// Identifies a registered hook, to unregister it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HookId(u64);
+
+/// Error returned when registering a hook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegisterError {
+    /// A hook with the same name is already registered for this stage.
+    DuplicateName(String),
+}
+
+impl Display for RegisterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateName(name) => write!(f, "query rewrite hook `{name}` already exists"),
+        }
+    }
+}
+
+impl std::error::Error for RegisterError {}
+
+/// A hook failed to rewrite the query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewriteError {
+    /// The name of the hook that failed.
+    pub hook: String,
+    /// The message returned by the hook.
+    pub message: String,
+}
+
+impl Display for RewriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Query rewrite `{}` failed: {}", self.hook, self.message)
+    }
+}
+
+impl std::error::Error for RewriteError {}
+
+struct Registered<H> {
+    id: HookId,
+    name: String,
+    priority: i32,
+    hook: H,
+}
+
+/// The registered hooks of both stages.
+#[derive(Default)]
+pub struct RewriteHooks {
+    pre_parse: Vec<Registered<PreParseHook>>,
+    post_parse: Vec<Registered<PostParseHook>>,
+    next_id: u64,
+}
+
+impl RewriteHooks {
+    /// Create an empty set of hooks.
+    pub const fn new() -> Self {
+        Self {
+            pre_parse: Vec::new(),
+            post_parse: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Register a hook rewriting the query string before it's parsed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegisterError::DuplicateName`] if a pre-parse hook named `name`
+    /// is already registered.
+    pub fn register_pre_parse(
+        &mut self,
+        name: impl Into<String>,
+        priority: i32,
+        hook: PreParseHook,
+    ) -> Result<HookId, RegisterError> {
+        let id = self.allocate_id();
+        insert(&mut self.pre_parse, id, name.into(), priority, hook)
+    }
+
+    /// Register a hook transforming the query tree after it's parsed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RegisterError::DuplicateName`] if a post-parse hook named `name`
+    /// is already registered.
+    pub fn register_post_parse(
+        &mut self,
+        name: impl Into<String>,
+        priority: i32,
+        hook: PostParseHook,
+    ) -> Result<HookId, RegisterError> {
+        let id = self.allocate_id();
+        insert(&mut self.post_parse, id, name.into(), priority, hook)
+    }
+
+    /// Unregister a hook of either stage.
+    ///
+    /// Returns `false` if no hook with that id is registered.
+    pub fn unregister(&mut self, id: HookId) -> bool {
+        let before = self.pre_parse.len() + self.post_parse.len();
+        self.pre_parse.retain(|h| h.id != id);
+        self.post_parse.retain(|h| h.id != id);
+        before != self.pre_parse.len() + self.post_parse.len()
+    }
+
+    /// `true` if no hook is registered, in which case queries can skip the
+    /// rewriting stages entirely.
+    pub const fn is_empty(&self) -> bool {
+        self.pre_parse.is_empty() && self.post_parse.is_empty()
+    }
+
+    /// Run the pre-parse hooks over `query`.
+    ///
+    /// Borrows `query` if no hook modified it.
+    pub fn pre_parse<'q>(
+        &self,
+        cx: &RewriteContext<'_>,
+        query: &'q str,
+    ) -> Result<Cow<'q, str>, RewriteError> {
+        let mut query = Cow::Borrowed(query);
+        for registered in &self.pre_parse {
+            match (registered.hook)(cx, &query) {
+                Ok(Some(rewritten)) => query = Cow::Owned(rewritten),
+                Ok(None) => {}
+                Err(message) => return Err(failed(registered, message)),
+            }
+        }
+        Ok(query)
+    }
+
+    /// Run the post-parse hooks over the tree rooted at `root`.
+    ///
+    /// On error the tree may have been partially rewritten and must be discarded.
+    pub fn post_parse(
+        &self,
+        cx: &RewriteContext<'_>,
+        root: &mut QueryNode,
+    ) -> Result<(), RewriteError> {
+        for registered in &self.post_parse {
+            (registered.hook)(cx, root).map_err(|message| failed(registered, message))?;
+        }
+        Ok(())
+    }
+
+    const fn allocate_id(&mut self) -> HookId {
+        self.next_id += 1;
+        HookId(self.next_id)
+    }
+}
+
+fn insert<H>(
+    hooks: &mut Vec<Registered<H>>,
+    id: HookId,
+    name: String,
+    priority: i32,
+    hook: H,
+) -> Result<HookId, RegisterError> {
+    if hooks.iter().any(|h| h.name == name) {
+        return Err(RegisterError::DuplicateName(name));
+    }
+    // Insert after every hook with the same priority to preserve registration order.
+    let pos = hooks.partition_point(|h| h.priority <= priority);
+    hooks.insert(
+        pos,
+        Registered {
+            id,
+            name,
+            priority,
+            hook,
+        },
+    );
+    Ok(id)
+}
+
+fn failed<H>(registered: &Registered<H>, message: String) -> RewriteError {
+    RewriteError {
+        hook: registered.name.clone(),
+        message,
+    }
+}
+
+static GLOBAL_HOOKS: RwLock<RewriteHooks> = RwLock::new(RewriteHooks::new());
+
+/// The process-wide hooks applied to every query.
+///
+/// Registration takes the write lock, so it should happen at module load time
+/// rather than while queries are running.
+pub fn global() -> &'static RwLock<RewriteHooks> {
+    &GLOBAL_HOOKS
+}
@@ -0,0 +1,104 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Lexicographic ranges over the values of a TAG field, for tags encoding
+//! sortable identifiers such as SKUs or dates.
+//!
+//! From [`TAG_RANGE_DIALECT`], a tag list may hold a range instead of values,
+//! e.g. `@sku:{[A100 TO B200]}`. Each bound is exclusive when prefixed by `(`,
+//! and `*` leaves the range open on that side, e.g. `@sku:{[(A100 TO *]}`.
+//!
+//! The range is a [`QueryNodeKind::LexRange`] child of the tag node, and is
+//! evaluated by iterating the tag values trie between the bounds, in reverse
+//! order when the results are sorted by the field in descending order.
+
+use std::fmt::{self, Display};
+
+use crate::{QueryNode, QueryNodeKind};
+
+/// The first dialect supporting tag ranges.
+pub const TAG_RANGE_DIALECT: u32 = 4;
+
+const RANGE_SEPARATOR: &str = "TO";
+const UNBOUNDED: &str = "*";
+
+/// An invalid tag range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagRangeError {
+    /// The range isn't of the form `min TO max`.
+    BadRange(String),
+    /// Tag ranges aren't supported by the dialect of the query.
+    Unsupported { dialect: u32 },
+}
+
+impl Display for TagRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadRange(range) => write!(f, "Bad tag range: [{range}]"),
+            Self::Unsupported { dialect } => {
+                write!(f, "Tag ranges are not supported in dialect {dialect}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TagRangeError {}
+
+/// The node for the tag range `@field:{[body]}`, `body` being e.g. `A TO (B`.
+///
+/// The bounds are taken as written: case folding of the tag values, if any,
+/// is up to the caller.
+///
+/// # Errors
+///
+/// Returns a [`TagRangeError`] if `body` isn't a valid range, or if tag ranges
+/// aren't supported by `dialect`.
+pub fn tag_range(field: &str, body: &str, dialect: u32) -> Result<QueryNode, TagRangeError> {
+    if dialect < TAG_RANGE_DIALECT {
+        return Err(TagRangeError::Unsupported { dialect });
+    }
+    let bad_range = || TagRangeError::BadRange(body.to_owned());
+    let mut parts = body.split_whitespace();
+    let (Some(min), Some(separator), Some(max), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(bad_range());
+    };
+    if !separator.eq_ignore_ascii_case(RANGE_SEPARATOR) {
+        return Err(bad_range());
+    }
+    let (begin, include_begin) = parse_bound(min).ok_or_else(bad_range)?;
+    let (end, include_end) = parse_bound(max).ok_or_else(bad_range)?;
+
+    let range = QueryNode::new(QueryNodeKind::LexRange {
+        begin,
+        include_begin,
+        end,
+        include_end,
+    });
+    Ok(QueryNode::with_children(
+        QueryNodeKind::Tag {
+            field: field.to_owned(),
+        },
+        vec![range],
+    ))
+}
+
+/// The value of a bound, `None` if unbounded, and whether it's inclusive.
+fn parse_bound(bound: &str) -> Option<(Option<String>, bool)> {
+    let (value, exclusive) = match bound.strip_prefix('(') {
+        Some(value) => (value, true),
+        None => (bound, false),
+    };
+    match value {
+        "" => None,
+        UNBOUNDED => Some((None, true)),
+        value => Some((Some(value.to_owned()), !exclusive)),
+    }
+}
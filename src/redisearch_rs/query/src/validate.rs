@@ -0,0 +1,333 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Validating a query without executing it (`VALIDATE`).
+//!
+//! Client frameworks check user-supplied queries before sending them. A
+//! validated query goes through the same stages as an executed one: the
+//! rewrite hooks, the parser, the parameters and the optimizations. Instead of
+//! being executed, the resulting plan is returned along with the fields it
+//! references and an estimate of its cost, computed from the statistics of
+//! the index through a [`CostModel`].
+//!
+//! `FT.EXPLAIN ... WITHCOST` reports the same [`estimate`] alongside the plan,
+//! as [lines](Estimate::explain) ending with a [`TimeClass`], so that users can
+//! compare formulations of a query without running them.
+
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+};
+
+use crate::{
+    FieldSelector, QueryNode, QueryNodeKind,
+    numeric::{IntegerRange, NumericRange, NumericSchema, merge_numeric_unions},
+    params::{ParamError, resolve_params},
+    rewrite::{RewriteContext, RewriteError, RewriteHooks},
+};
+
+/// The statistics of an index a cost estimate is computed from.
+///
+/// The counts may be estimated by sampling, e.g. from the headers of a few
+/// blocks of a posting list. The provided methods assume the worst, that
+/// every document matches.
+pub trait CostModel {
+    /// The number of documents in the index.
+    fn num_docs(&self) -> u64;
+
+    /// The number of documents containing `term`.
+    fn term_docs(&self, term: &str) -> u64;
+
+    /// The number of documents with the tag `value` in `field`.
+    fn tag_docs(&self, _field: &str, _value: &str) -> u64 {
+        self.num_docs()
+    }
+
+    /// The number of documents with a value of `field` in `range`.
+    fn numeric_docs(&self, _field: &str, _range: &NumericRange) -> u64 {
+        self.num_docs()
+    }
+
+    /// The number of documents matched by an expansion (prefix, fuzzy,
+    /// wildcard pattern, lexical range) or by `ismissing`.
+    fn expansion_docs(&self, _node: &QueryNode) -> u64 {
+        self.num_docs()
+    }
+
+    /// The number of postings in a block, `INDEX_BLOCK_SIZE`.
+    fn block_size(&self) -> u64 {
+        100
+    }
+}
+
+/// The estimated cost of a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Estimate {
+    /// An upper bound of the number of matching documents.
+    pub results: u64,
+    /// The number of postings read to find them.
+    pub postings: u64,
+    /// The number of blocks decoded to read the postings.
+    pub blocks: u64,
+}
+
+impl Estimate {
+    /// The time class of the query.
+    pub const fn time_class(&self) -> TimeClass {
+        TimeClass::of(self.postings)
+    }
+
+    /// The lines added to the reply of `FT.EXPLAIN ... WITHCOST`.
+    pub fn explain(&self) -> Vec<String> {
+        vec![
+            format!("Estimated results: {}", self.results),
+            format!("Estimated postings: {}", self.postings),
+            format!("Estimated blocks: {}", self.blocks),
+            format!("Estimated time: {}", self.time_class()),
+        ]
+    }
+}
+
+/// A rough class of the execution time of a query, from the number of
+/// postings it reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TimeClass {
+    /// Up to [`TimeClass::FAST_POSTINGS`] postings.
+    Fast,
+    /// Up to [`TimeClass::MODERATE_POSTINGS`] postings.
+    Moderate,
+    Slow,
+}
+
+impl TimeClass {
+    pub const FAST_POSTINGS: u64 = 10_000;
+    pub const MODERATE_POSTINGS: u64 = 1_000_000;
+
+    pub const fn of(postings: u64) -> Self {
+        if postings <= Self::FAST_POSTINGS {
+            Self::Fast
+        } else if postings <= Self::MODERATE_POSTINGS {
+            Self::Moderate
+        } else {
+            Self::Slow
+        }
+    }
+}
+
+impl Display for TimeClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Fast => "fast",
+            Self::Moderate => "moderate",
+            Self::Slow => "slow",
+        })
+    }
+}
+
+/// A validated query, see [`validate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Validation {
+    /// The tree the query would be executed with.
+    pub plan: QueryNode,
+    /// The fields referenced by the query.
+    pub fields: BTreeSet<String>,
+    pub estimate: Estimate,
+}
+
+/// Why a query is invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidateError {
+    Rewrite(RewriteError),
+    Parse(String),
+    Param(ParamError),
+    /// The query references a field which isn't in the schema.
+    UnknownField(String),
+}
+
+impl Display for ValidateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rewrite(error) => error.fmt(f),
+            Self::Parse(message) => write!(f, "Syntax error: {message}"),
+            Self::Param(error) => error.fmt(f),
+            Self::UnknownField(field) => write!(f, "Unknown field `{field}`"),
+        }
+    }
+}
+
+impl std::error::Error for ValidateError {}
+
+/// The stages a query goes through before being executed.
+pub struct Validator<'a> {
+    pub hooks: &'a RewriteHooks,
+    pub parse: &'a dyn Fn(&str, u32) -> Result<QueryNode, String>,
+    /// Whether a field exists in the schema.
+    pub has_field: &'a dyn Fn(&str) -> bool,
+    /// How the numeric ranges of the fields of the schema are parsed.
+    pub numeric: &'a dyn NumericSchema,
+}
+
+impl Validator<'_> {
+    /// Validate `query`, with the parameters `params`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error the query would fail with if it was executed.
+    pub fn validate<'p>(
+        &self,
+        cx: &RewriteContext<'_>,
+        query: &str,
+        params: &impl Fn(&str) -> Option<&'p str>,
+        model: &impl CostModel,
+    ) -> Result<Validation, ValidateError> {
+        let query = self
+            .hooks
+            .pre_parse(cx, query)
+            .map_err(ValidateError::Rewrite)?;
+        let mut plan = (self.parse)(&query, cx.dialect).map_err(ValidateError::Parse)?;
+        self.hooks
+            .post_parse(cx, &mut plan)
+            .map_err(ValidateError::Rewrite)?;
+        resolve_params(&mut plan, params, self.numeric).map_err(ValidateError::Param)?;
+        merge_numeric_unions(&mut plan);
+
+        let fields = referenced_fields(&plan);
+        if let Some(unknown) = fields.iter().find(|f| !(self.has_field)(f)) {
+            return Err(ValidateError::UnknownField(unknown.clone()));
+        }
+        let estimate = estimate(&plan, model);
+        Ok(Validation {
+            plan,
+            fields,
+            estimate,
+        })
+    }
+}
+
+/// The fields referenced by the tree rooted at `root`.
+pub fn referenced_fields(root: &QueryNode) -> BTreeSet<String> {
+    let mut fields = BTreeSet::new();
+    root.for_each(&mut |node| {
+        if let FieldSelector::Named(names) = &node.opts.fields {
+            fields.extend(names.iter().cloned());
+        }
+        match &node.kind {
+            QueryNodeKind::Numeric { field, .. }
+            | QueryNodeKind::NumericUnion { field, .. }
+            | QueryNodeKind::IntegerNumeric { field, .. }
+            | QueryNodeKind::ParamNumeric { field, .. }
+            | QueryNodeKind::Tag { field }
+            | QueryNodeKind::Missing { field } => {
+                fields.insert(field.clone());
+            }
+            _ => {}
+        }
+    });
+    fields
+}
+
+/// Estimate the cost of the tree rooted at `node`.
+pub fn estimate(node: &QueryNode, model: &impl CostModel) -> Estimate {
+    let all = model.num_docs();
+    let block_size = model.block_size().max(1);
+    let leaf = |results: u64| Estimate {
+        results: results.min(all),
+        postings: results,
+        blocks: results.div_ceil(block_size),
+    };
+    let children = || node.children.iter().map(|child| estimate(child, model));
+    match &node.kind {
+        QueryNodeKind::Token { term } => leaf(model.term_docs(term)),
+        QueryNodeKind::Numeric {
+            field,
+            min,
+            max,
+            inclusive_min,
+            inclusive_max,
+        } => leaf(model.numeric_docs(
+            field,
+            &NumericRange {
+                min: *min,
+                max: *max,
+                min_inclusive: *inclusive_min,
+                max_inclusive: *inclusive_max,
+            },
+        )),
+        QueryNodeKind::IntegerNumeric { field, min, max } => leaf(
+            model.numeric_docs(
+                field,
+                &IntegerRange {
+                    min: *min,
+                    max: *max,
+                }
+                .to_numeric(),
+            ),
+        ),
+        QueryNodeKind::NumericUnion { field, ranges } => union(
+            ranges
+                .iter()
+                .map(|range| leaf(model.numeric_docs(field, range))),
+            all,
+        ),
+        QueryNodeKind::Tag { field } => union(
+            node.children.iter().map(|child| match &child.kind {
+                QueryNodeKind::Token { term } => leaf(model.tag_docs(field, term)),
+                _ => leaf(model.expansion_docs(child)),
+            }),
+            all,
+        ),
+        QueryNodeKind::Prefix { .. }
+        | QueryNodeKind::Fuzzy { .. }
+        | QueryNodeKind::LexRange { .. }
+        | QueryNodeKind::WildcardQuery { .. }
+        | QueryNodeKind::Missing { .. } => leaf(model.expansion_docs(node)),
+        // Unresolved parameters only remain in prepared templates.
+        QueryNodeKind::ParamNumeric { .. } | QueryNodeKind::Wildcard => leaf(all),
+        QueryNodeKind::Ids(keys) => leaf(keys.len() as u64),
+        QueryNodeKind::Null => Estimate::default(),
+        QueryNodeKind::Union => union(children(), all),
+        QueryNodeKind::Phrase { .. } => children().fold(
+            Estimate {
+                results: all,
+                postings: 0,
+                blocks: 0,
+            },
+            |acc, child| Estimate {
+                results: acc.results.min(child.results),
+                postings: acc.postings + child.postings,
+                blocks: acc.blocks + child.blocks,
+            },
+        ),
+        // Negations are driven by a wildcard iterator.
+        QueryNodeKind::Not => {
+            let child = union(children(), all);
+            Estimate {
+                results: all - child.results,
+                postings: child.postings + all,
+                blocks: child.blocks + all.div_ceil(block_size),
+            }
+        }
+        QueryNodeKind::Optional => Estimate {
+            results: all,
+            ..union(children(), all)
+        },
+    }
+}
+
+fn union(children: impl Iterator<Item = Estimate>, all: u64) -> Estimate {
+    let sum = children.fold(Estimate::default(), |acc, child| Estimate {
+        results: acc.results + child.results,
+        postings: acc.postings + child.postings,
+        blocks: acc.blocks + child.blocks,
+    });
+    Estimate {
+        results: sum.results.min(all),
+        ..sum
+    }
+}
@@ -0,0 +1,107 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use query::args::{ArgError, SearchArgs};
+
+fn parse(args: &[&str]) -> Result<SearchArgs, ArgError> {
+    let mut parsed = SearchArgs::default();
+    let mut args = args.iter().copied();
+    while let Some(name) = args.next() {
+        assert!(parsed.try_parse_option(name, &mut args)?, "unknown {name}");
+    }
+    Ok(parsed)
+}
+
+#[test]
+fn test_stopwords() {
+    assert_eq!(parse(&[]).unwrap().stopwords(), None);
+    assert_eq!(
+        parse(&["STOPWORDS", "0"]).unwrap().stopwords(),
+        Some(&[][..])
+    );
+    assert_eq!(
+        parse(&["stopwords", "2", "The", "a"]).unwrap().stopwords(),
+        Some(&["the".to_owned(), "a".to_owned()][..])
+    );
+}
+
+#[test]
+fn test_validate() {
+    assert!(!parse(&[]).unwrap().validate());
+    assert!(parse(&["validate"]).unwrap().validate());
+    assert_eq!(
+        parse(&["VALIDATE", "VALIDATE"]),
+        Err(ArgError::DuplicateOption("VALIDATE"))
+    );
+}
+
+#[test]
+fn test_stopwords_errors() {
+    assert_eq!(
+        parse(&["STOPWORDS"]),
+        Err(ArgError::MissingArgument("STOPWORDS"))
+    );
+    assert_eq!(
+        parse(&["STOPWORDS", "2", "a"]),
+        Err(ArgError::MissingArgument("STOPWORDS"))
+    );
+    assert_eq!(
+        parse(&["STOPWORDS", "-1"]),
+        Err(ArgError::BadValue {
+            option: "STOPWORDS",
+            value: "-1".to_owned()
+        })
+    );
+    assert_eq!(
+        parse(&["STOPWORDS", "0", "STOPWORDS", "0"]),
+        Err(ArgError::DuplicateOption("STOPWORDS"))
+    );
+
+    let mut args = SearchArgs::default();
+    assert_eq!(
+        args.try_parse_option("LIMIT", &mut ["0", "10"].into_iter()),
+        Ok(false)
+    );
+}
+
+#[test]
+fn test_score_fields() {
+    assert_eq!(parse(&[]).unwrap().score_fields(), None);
+    assert_eq!(
+        parse(&["SCOREFIELDS", "1", "Title"])
+            .unwrap()
+            .score_fields(),
+        Some(&["Title".to_owned()][..])
+    );
+    assert_eq!(
+        parse(&["SCOREFIELDS", "0"]),
+        Err(ArgError::BadValue {
+            option: "SCOREFIELDS",
+            value: "0".to_owned()
+        })
+    );
+    assert_eq!(
+        parse(&["SCOREFIELDS", "2", "title"]),
+        Err(ArgError::MissingArgument("SCOREFIELDS"))
+    );
+    assert_eq!(
+        parse(&["SCOREFIELDS", "1", "a", "scorefields", "1", "b"]),
+        Err(ArgError::DuplicateOption("SCOREFIELDS"))
+    );
+}
+
+#[test]
+fn test_with_cost() {
+    assert!(!parse(&[]).unwrap().with_cost());
+    assert!(parse(&["withcost"]).unwrap().with_cost());
+    assert_eq!(
+        parse(&["WITHCOST", "WITHCOST"]),
+        Err(ArgError::DuplicateOption("WITHCOST"))
+    );
+}
@@ -0,0 +1,76 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use query::{
+    QueryNode,
+    attributes::{AttributeError, apply_attribute, quoted_term},
+    node::VectorAggregation,
+};
+
+#[test]
+fn test_scoring_attributes() {
+    let mut node = QueryNode::intersect(vec![QueryNode::token("a"), QueryNode::token("b")]);
+    apply_attribute(&mut node, "weight", "2.5", 2).unwrap();
+    apply_attribute(&mut node, "slop", "1", 2).unwrap();
+    apply_attribute(&mut node, "inorder", "true", 2).unwrap();
+    assert_eq!(node.opts.weight, 2.5);
+    assert!(node.opts.explicit_weight);
+    assert_eq!(node.opts.max_slop, Some(1));
+    assert!(node.opts.in_order);
+
+    apply_attribute(&mut node, "slop", "-1", 2).unwrap();
+    assert_eq!(node.opts.max_slop, None);
+    assert_eq!(
+        apply_attribute(&mut node, "weight", "-1", 2),
+        Err(AttributeError::BadValue {
+            name: "weight".to_owned(),
+            value: "-1".to_owned()
+        })
+    );
+    assert_eq!(
+        apply_attribute(&mut node, "color", "red", 2),
+        Err(AttributeError::Unknown("color".to_owned()))
+    );
+}
+
+#[test]
+fn test_verbatim_attribute() {
+    let mut node =
+        QueryNode::intersect(vec![QueryNode::token("running"), QueryNode::token("shoes")]);
+    assert_eq!(
+        apply_attribute(&mut node, "verbatim", "true", 2),
+        Err(AttributeError::Unsupported {
+            name: "verbatim".to_owned(),
+            dialect: 2
+        })
+    );
+    assert!(!node.opts.verbatim);
+
+    apply_attribute(&mut node, "verbatim", "true", 4).unwrap();
+    let mut all = true;
+    node.for_each(&mut |n| all &= n.opts.verbatim);
+    assert!(all);
+}
+
+#[test]
+fn test_quoted_term() {
+    assert!(!quoted_term("running", 3).opts.verbatim);
+    assert!(quoted_term("running", 4).opts.verbatim);
+}
+
+#[test]
+fn test_aggregation_attribute() {
+    let mut node = QueryNode::token("a");
+    assert_eq!(node.opts.aggregation, None);
+    apply_attribute(&mut node, "AGGREGATION", "avg", 2).unwrap();
+    assert_eq!(node.opts.aggregation, Some(VectorAggregation::Avg));
+    apply_attribute(&mut node, "aggregation", "MAX", 2).unwrap();
+    assert_eq!(node.opts.aggregation, Some(VectorAggregation::Max));
+    assert!(apply_attribute(&mut node, "aggregation", "sum", 2).is_err());
+}
@@ -0,0 +1,130 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::collections::HashMap;
+
+use query::{
+    FieldSelector, QueryNode, QueryNodeKind,
+    autocorrect::{AutoCorrect, Speller, Substitution, Suggestion},
+};
+
+struct Dictionary(HashMap<&'static str, u64>);
+
+impl Dictionary {
+    fn new() -> Self {
+        Self(HashMap::from([
+            ("hello", 100),
+            ("help", 40),
+            ("held", 5),
+            ("world", 80),
+        ]))
+    }
+}
+
+impl Speller for Dictionary {
+    fn doc_freq(&self, term: &str) -> u64 {
+        self.0.get(term).copied().unwrap_or(0)
+    }
+
+    /// Suggest the terms of the same length differing by one character, scored
+    /// by their frequency.
+    fn suggest(&self, term: &str, _max_distance: u8) -> Vec<Suggestion> {
+        self.0
+            .iter()
+            .filter(|(t, _)| {
+                t.len() == term.len()
+                    && t.chars().zip(term.chars()).filter(|(a, b)| a != b).count() <= 1
+            })
+            .map(|(t, &freq)| Suggestion {
+                term: (*t).to_owned(),
+                score: freq as f64,
+            })
+            .collect()
+    }
+}
+
+fn suggestion(term: &str, weight: f64) -> QueryNode {
+    let mut node = QueryNode::token(term);
+    node.opts.weight = weight;
+    node.opts.explicit_weight = true;
+    node
+}
+
+#[test]
+fn test_expands_rare_terms() {
+    let mut root = QueryNode::intersect(vec![QueryNode::token("helx"), QueryNode::token("world")]);
+    root.children[0].opts.fields = FieldSelector::Named(vec!["title".to_owned()]);
+
+    let autocorrect = AutoCorrect {
+        max_suggestions: 2,
+        ..Default::default()
+    };
+    let substitutions = autocorrect.apply(&mut root, &Dictionary::new());
+    assert_eq!(
+        substitutions,
+        [Substitution {
+            term: "helx".to_owned(),
+            suggestions: vec!["help".to_owned(), "held".to_owned()],
+        }]
+    );
+    assert_eq!(
+        substitutions[0].to_string(),
+        "Term 'helx' expanded with 'help', 'held'"
+    );
+
+    let mut expected = QueryNode::union(vec![
+        QueryNode::token("helx"),
+        suggestion("help", 0.5),
+        suggestion("held", 0.5),
+    ]);
+    expected.opts.fields = FieldSelector::Named(vec!["title".to_owned()]);
+    assert_eq!(
+        root,
+        QueryNode::intersect(vec![expected, QueryNode::token("world")])
+    );
+}
+
+#[test]
+fn test_threshold() {
+    let mut root = QueryNode::token("held");
+    let autocorrect = AutoCorrect {
+        min_doc_freq: 10,
+        ..Default::default()
+    };
+    autocorrect.apply(&mut root, &Dictionary::new());
+    // "held" is rare enough, but isn't suggested for itself.
+    assert_eq!(root.kind, QueryNodeKind::Union);
+    assert_eq!(
+        root.children,
+        [QueryNode::token("held"), suggestion("help", 0.5)]
+    );
+
+    // Frequent terms are left alone.
+    let mut root = QueryNode::token("help");
+    assert!(autocorrect.apply(&mut root, &Dictionary::new()).is_empty());
+}
+
+#[test]
+fn test_skips_verbatim_and_negated_terms() {
+    let mut verbatim = QueryNode::token("helx");
+    verbatim.opts.verbatim = true;
+    let mut root = QueryNode::intersect(vec![
+        verbatim,
+        QueryNode::negate(QueryNode::token("wrld")),
+        QueryNode::token("xyzzy"),
+    ]);
+    let before = root.clone();
+    // "xyzzy" has no suggestions.
+    assert!(
+        AutoCorrect::default()
+            .apply(&mut root, &Dictionary::new())
+            .is_empty()
+    );
+    assert_eq!(root, before);
+}
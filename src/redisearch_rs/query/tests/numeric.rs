@@ -0,0 +1,263 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use query::{
+    QueryNode, QueryNodeKind,
+    numeric::{
+        BoundSyntax, IntegerRange, NumericRange, NumericSyntaxError, comparison, comparison_with,
+        matching_ranges, merge_numeric_unions,
+    },
+};
+
+#[test]
+fn test_parse_range() {
+    assert_eq!(
+        NumericRange::parse("(10 (20").unwrap(),
+        NumericRange {
+            min: 10.0,
+            max: 20.0,
+            min_inclusive: false,
+            max_inclusive: false,
+        }
+    );
+    assert_eq!(
+        NumericRange::parse("(-inf +inf").unwrap(),
+        NumericRange::inclusive(f64::NEG_INFINITY, f64::INFINITY)
+    );
+    assert_eq!(
+        NumericRange::parse("1.5 (INF").unwrap(),
+        NumericRange::inclusive(1.5, f64::INFINITY)
+    );
+    assert_eq!(
+        NumericRange::parse("1 2 3"),
+        Err(NumericSyntaxError::BadRange("1 2 3".to_owned()))
+    );
+    assert_eq!(
+        NumericRange::parse("1 nan"),
+        Err(NumericSyntaxError::BadValue("nan".to_owned()))
+    );
+    assert_eq!(
+        NumericRange::parse("(a 2"),
+        Err(NumericSyntaxError::BadValue("a".to_owned()))
+    );
+}
+
+#[test]
+fn test_empty_and_contains() {
+    assert!(NumericRange::parse("(5 5").unwrap().is_empty());
+    assert!(NumericRange::parse("6 5").unwrap().is_empty());
+    assert!(!NumericRange::parse("5 5").unwrap().is_empty());
+
+    let range = NumericRange::parse("(10 20").unwrap();
+    assert!(!range.contains(10.0));
+    assert!(range.contains(20.0));
+}
+
+#[test]
+fn test_operators() {
+    let node = comparison("price", ">=", "10", 2).unwrap();
+    assert_eq!(
+        node.kind,
+        QueryNodeKind::Numeric {
+            field: "price".to_owned(),
+            min: 10.0,
+            max: f64::INFINITY,
+            inclusive_min: true,
+            inclusive_max: true,
+        }
+    );
+    assert_eq!(
+        comparison("price", "<", "10", 2).unwrap(),
+        NumericRange {
+            min: f64::NEG_INFINITY,
+            max: 10.0,
+            min_inclusive: true,
+            max_inclusive: false,
+        }
+        .into_node("price")
+    );
+    assert_eq!(
+        comparison("price", "!=", "5", 3).unwrap(),
+        QueryNode::negate(QueryNode::numeric("price", 5.0, 5.0))
+    );
+    assert_eq!(
+        comparison("price", ">=", "10", 1),
+        Err(NumericSyntaxError::Unsupported { dialect: 1 })
+    );
+    assert_eq!(
+        comparison("price", "=>", "10", 2),
+        Err(NumericSyntaxError::UnknownOperator("=>".to_owned()))
+    );
+}
+
+#[test]
+fn test_datetime_bounds() {
+    // 2024-01-01T00:00:00Z and 2025-01-01T00:00:00Z.
+    let (start, end) = (1_704_067_200.0, 1_735_689_600.0);
+    assert_eq!(
+        NumericRange::parse_with("2024-01-01 (2025-01-01T00:00:00Z", BoundSyntax::DateTime),
+        Ok(NumericRange {
+            min: start,
+            max: end,
+            min_inclusive: true,
+            max_inclusive: false,
+        })
+    );
+    // Plain numbers are still accepted.
+    assert_eq!(
+        NumericRange::parse_with("1704067200 +inf", BoundSyntax::DateTime),
+        Ok(NumericRange::inclusive(start, f64::INFINITY))
+    );
+    assert_eq!(
+        NumericRange::parse("2024-01-01 +inf"),
+        Err(NumericSyntaxError::BadValue("2024-01-01".to_owned()))
+    );
+    assert_eq!(
+        NumericRange::parse_with("2024-13-01 +inf", BoundSyntax::DateTime),
+        Err(NumericSyntaxError::BadValue("2024-13-01".to_owned()))
+    );
+    assert_eq!(
+        IntegerRange::parse_with("(2024-01-01 2025-01-01", BoundSyntax::DateTime),
+        Ok(IntegerRange {
+            min: 1_704_067_201,
+            max: 1_735_689_600,
+        })
+    );
+    assert_eq!(
+        comparison_with("created", ">=", "2024-01-01", 2, BoundSyntax::DateTime),
+        Ok(NumericRange::inclusive(start, f64::INFINITY).into_node("created"))
+    );
+}
+
+#[test]
+fn test_leaf_scan() {
+    let values = [1.0, 5.0, 5.0, 10.0, 10.0, 20.0, 30.0];
+    let scan = |range: &str| NumericRange::parse(range).unwrap().matching(&values);
+    assert_eq!(scan("5 20"), 1..6);
+    assert_eq!(scan("(5 (20"), 3..5);
+    assert_eq!(scan("(5 20"), 3..6);
+    assert_eq!(scan("-inf (5"), 0..1);
+    assert_eq!(scan("(30 +inf"), 7..7);
+    assert_eq!(scan("(10 (10"), 5..5);
+    assert_eq!(scan("11 19"), 5..5);
+    assert_eq!(scan("-inf +inf"), 0..7);
+}
+
+fn range(field: &str, body: &str) -> QueryNode {
+    NumericRange::parse(body).unwrap().into_node(field)
+}
+
+#[test]
+fn test_merge_unions() {
+    let mut root = QueryNode::union(vec![
+        range("price", "10 20"),
+        QueryNode::token("cheap"),
+        range("price", "1 5"),
+        range("price", "(20 30"),
+        range("year", "2000 2010"),
+        range("price", "(40 (40"),
+    ]);
+    merge_numeric_unions(&mut root);
+    assert_eq!(
+        root.children,
+        [
+            QueryNode::new(QueryNodeKind::NumericUnion {
+                field: "price".to_owned(),
+                ranges: vec![
+                    NumericRange::inclusive(1.0, 5.0),
+                    NumericRange::inclusive(10.0, 30.0),
+                ],
+            }),
+            QueryNode::token("cheap"),
+            range("year", "2000 2010"),
+        ]
+    );
+}
+
+#[test]
+fn test_merge_gaps_and_collapse() {
+    // `(5` leaves out 5, which neither range contains.
+    let mut root = QueryNode::intersect(vec![
+        QueryNode::union(vec![range("price", "1 (5"), range("price", "(5 10")]),
+        QueryNode::union(vec![range("price", "1 (5"), range("price", "5 10")]),
+    ]);
+    merge_numeric_unions(&mut root);
+    assert_eq!(
+        root.children[0].kind,
+        QueryNodeKind::NumericUnion {
+            field: "price".to_owned(),
+            ranges: vec![
+                NumericRange::parse("1 (5").unwrap(),
+                NumericRange::parse("(5 10").unwrap(),
+            ],
+        }
+    );
+    assert_eq!(root.children[1], range("price", "1 10"));
+
+    // Weighted ranges are left alone.
+    let mut weighted = range("price", "1 2");
+    weighted.opts.weight = 2.0;
+    let mut root = QueryNode::union(vec![weighted.clone(), range("price", "3 4")]);
+    let before = root.clone();
+    merge_numeric_unions(&mut root);
+    assert_eq!(root, before);
+}
+
+#[test]
+fn test_matching_ranges() {
+    let values = [1.0, 3.0, 5.0, 10.0, 12.0, 20.0];
+    let ranges = [
+        NumericRange::inclusive(0.0, 4.0),
+        NumericRange::inclusive(10.0, 10.0),
+        NumericRange::parse("(12 +inf").unwrap(),
+    ];
+    assert_eq!(matching_ranges(&ranges, &values), [0..2, 3..4, 5..6]);
+}
+
+#[test]
+fn test_integer_range() {
+    let big = 9_007_199_254_740_993_i64; // 2^53 + 1, not representable as f64.
+    assert_eq!(
+        IntegerRange::parse(&format!("({big} +inf")).unwrap(),
+        IntegerRange {
+            min: big + 1,
+            max: i64::MAX
+        }
+    );
+    assert_eq!(
+        IntegerRange::parse("-inf (10").unwrap(),
+        IntegerRange {
+            min: i64::MIN,
+            max: 9
+        }
+    );
+    assert_eq!(
+        IntegerRange::parse("1.5 (4.0").unwrap(),
+        IntegerRange { min: 2, max: 3 }
+    );
+    assert!(IntegerRange::parse("(5 (6").unwrap().is_empty());
+    assert!(IntegerRange::parse("+inf +inf").unwrap().is_empty());
+    assert!(
+        IntegerRange::parse(&format!("({} +inf", i64::MAX))
+            .unwrap()
+            .is_empty()
+    );
+
+    let values = [big - 1, big, big + 1, big + 2];
+    let range = IntegerRange::parse(&format!("{big} ({}", big + 2)).unwrap();
+    assert_eq!(range.matching(&values), 1..3);
+    assert!(range.contains(big + 1));
+    assert!(!range.contains(big + 2));
+    assert_eq!(IntegerRange::EMPTY.matching(&values), 0..0);
+    assert!(IntegerRange::EMPTY.to_numeric().is_empty());
+    assert_eq!(
+        IntegerRange { min: 2, max: 3 }.to_numeric(),
+        NumericRange::inclusive(2.0, 3.0)
+    );
+}
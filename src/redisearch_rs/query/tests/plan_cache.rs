@@ -0,0 +1,118 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::sync::Arc;
+
+use query::{
+    QueryNode,
+    plan_cache::{PlanCache, PlanCacheStats, PlanKey},
+};
+
+fn key(query: &str) -> PlanKey {
+    PlanKey {
+        query: query.to_owned(),
+        dialect: 2,
+    }
+}
+
+fn parse(query: &str) -> Result<QueryNode, String> {
+    Ok(QueryNode::token(query))
+}
+
+#[test]
+fn test_hits_and_misses() {
+    let cache = PlanCache::new(4);
+    let mut parses = 0;
+    for _ in 0..3 {
+        let plan = cache
+            .get_or_build(&key("@name:$name"), || {
+                parses += 1;
+                parse("$name")
+            })
+            .unwrap();
+        assert_eq!(*plan, QueryNode::token("$name"));
+    }
+    assert_eq!(parses, 1);
+
+    // The dialect is part of the key.
+    let other_dialect = PlanKey {
+        dialect: 3,
+        ..key("@name:$name")
+    };
+    assert!(cache.get(&other_dialect).is_none());
+
+    let stats = cache.stats();
+    assert_eq!((stats.hits, stats.misses), (2, 2));
+    assert_eq!(stats.hit_rate(), 0.5);
+
+    // Errors aren't cached.
+    assert!(
+        cache
+            .get_or_build(&key("bad"), || Err::<QueryNode, _>("syntax error"))
+            .is_err()
+    );
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn test_lru_eviction() {
+    let cache = PlanCache::new(2);
+    cache.insert(key("a"), Arc::new(1));
+    cache.insert(key("b"), Arc::new(2));
+    assert!(cache.get(&key("a")).is_some());
+    cache.insert(key("c"), Arc::new(3));
+    // `b` was the least recently used.
+    assert!(cache.get(&key("b")).is_none());
+    assert_eq!(cache.get(&key("a")).as_deref(), Some(&1));
+    assert_eq!(cache.get(&key("c")).as_deref(), Some(&3));
+    assert_eq!(cache.stats().evictions, 1);
+
+    // Replacing a plan doesn't evict anything.
+    cache.insert(key("a"), Arc::new(10));
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&key("a")).as_deref(), Some(&10));
+}
+
+#[test]
+fn test_invalidation_and_disabled() {
+    let cache = PlanCache::new(2);
+    cache.insert(key("a"), Arc::new(1));
+    cache.invalidate();
+    assert!(cache.is_empty());
+    assert_eq!(
+        cache.stats(),
+        PlanCacheStats {
+            invalidations: 1,
+            ..Default::default()
+        }
+    );
+
+    let disabled = PlanCache::new(0);
+    disabled.insert(key("a"), Arc::new(1));
+    assert!(disabled.get(&key("a")).is_none());
+    assert_eq!(disabled.info()[0], ("size", 0.0));
+}
+
+#[test]
+fn test_plan_built_across_invalidation_isnt_cached() {
+    let cache = PlanCache::new(2);
+    // The index is altered while the plan is being built.
+    let plan = cache
+        .get_or_build(&key("a"), || {
+            cache.invalidate();
+            Ok::<_, ()>(1)
+        })
+        .unwrap();
+    assert_eq!(*plan, 1);
+    assert!(cache.is_empty());
+
+    // The next build is cached again.
+    cache.get_or_build(&key("a"), || Ok::<_, ()>(2)).unwrap();
+    assert_eq!(cache.get(&key("a")).as_deref(), Some(&2));
+}
@@ -0,0 +1,216 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use query::{
+    QueryNode, QueryNodeKind,
+    numeric::{BoundSyntax, NumericRange, NumericSchema, PlainNumbers},
+    params::{ParamError, referenced_params, resolve_params},
+    plan_cache::PlanCache,
+    prepared::{ExecuteError, ParamType, PrepareError, PreparedQueries},
+};
+
+/// A toy parser: `@price:[...]` is a numeric range, anything else a term.
+fn parse(query: &str) -> Result<QueryNode, String> {
+    let children = query
+        .split(" AND ")
+        .map(|part| match part.strip_prefix("@price:[") {
+            Some(range) => Ok(QueryNode::new(QueryNodeKind::ParamNumeric {
+                field: "price".to_owned(),
+                range: range
+                    .strip_suffix(']')
+                    .ok_or("unterminated range")?
+                    .to_owned(),
+            })),
+            None => Ok(QueryNode::token(part)),
+        })
+        .collect::<Result<_, &str>>()?;
+    Ok(QueryNode::intersect(children))
+}
+
+fn prepared() -> (PreparedQueries, Arc<AtomicUsize>) {
+    let parses = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&parses);
+    let queries = PreparedQueries::new(
+        Box::new(move |query, _dialect| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            parse(query)
+        }),
+        PlanCache::new(8),
+    );
+    (queries, parses)
+}
+
+fn params(names: &[(&str, ParamType)]) -> Vec<(String, ParamType)> {
+    names.iter().map(|(n, t)| ((*n).to_owned(), *t)).collect()
+}
+
+#[test]
+fn test_resolve_params() {
+    let mut plan = parse("$term AND @price:[$min ($max]").unwrap();
+    assert_eq!(
+        referenced_params(&plan).into_iter().collect::<Vec<_>>(),
+        ["max", "min", "term"]
+    );
+    let values = [("term", "shoes"), ("min", "10"), ("max", "20")];
+    let lookup = |name: &str| values.iter().find(|(n, _)| *n == name).map(|(_, v)| *v);
+    resolve_params(&mut plan, &lookup, &PlainNumbers).unwrap();
+    assert_eq!(
+        plan.children,
+        [
+            QueryNode::token("shoes"),
+            NumericRange::parse("10 (20").unwrap().into_node("price")
+        ]
+    );
+
+    let mut plan = parse("$other").unwrap();
+    assert_eq!(
+        resolve_params(&mut plan, &lookup, &PlainNumbers),
+        Err(ParamError::Missing("other".to_owned()))
+    );
+}
+
+/// `created` takes ISO-8601 bounds.
+struct DateTimeCreated;
+
+impl NumericSchema for DateTimeCreated {
+    fn bound_syntax(&self, field: &str) -> BoundSyntax {
+        if field == "created" {
+            BoundSyntax::DateTime
+        } else {
+            BoundSyntax::Number
+        }
+    }
+}
+
+#[test]
+fn test_resolve_datetime_params() {
+    let range = |field: &str| {
+        QueryNode::new(QueryNodeKind::ParamNumeric {
+            field: field.to_owned(),
+            range: "$from +inf".to_owned(),
+        })
+    };
+    let lookup = |name: &str| (name == "from").then_some("2024-01-01T00:00:00Z");
+
+    let mut plan = range("created");
+    resolve_params(&mut plan, &lookup, &DateTimeCreated).unwrap();
+    assert_eq!(
+        plan,
+        NumericRange::parse("1704067200 +inf")
+            .unwrap()
+            .into_node("created")
+    );
+
+    let mut plan = range("price");
+    assert!(matches!(
+        resolve_params(&mut plan, &lookup, &DateTimeCreated),
+        Err(ParamError::BadNumericRange(_))
+    ));
+}
+
+#[test]
+fn test_prepare_and_execute() {
+    let (queries, parses) = prepared();
+    let declared = params(&[("term", ParamType::Text), ("min", ParamType::Numeric)]);
+    queries
+        .prepare("by_price", "$term AND @price:[$min +inf]", 2, declared)
+        .unwrap();
+
+    for min in ["1", "2", "-inf"] {
+        let plan = queries
+            .execute(
+                "by_price",
+                &[("term", "shoes"), ("min", min)],
+                &PlainNumbers,
+            )
+            .unwrap();
+        assert_eq!(plan.children[0], QueryNode::token("shoes"));
+    }
+    // Parsed once, when prepared.
+    assert_eq!(parses.load(Ordering::Relaxed), 1);
+
+    // After a schema change, the template is parsed again.
+    queries.invalidate();
+    queries
+        .execute("by_price", &[("term", "a"), ("min", "1")], &PlainNumbers)
+        .unwrap();
+    assert_eq!(parses.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn test_prepare_validation() {
+    let (queries, _) = prepared();
+    assert_eq!(
+        queries.prepare("q", "$a AND $b", 2, params(&[("a", ParamType::Text)])),
+        Err(PrepareError::UndeclaredParam("b".to_owned()))
+    );
+    assert_eq!(
+        queries.prepare(
+            "q",
+            "$a",
+            2,
+            params(&[("a", ParamType::Text), ("b", ParamType::Text)])
+        ),
+        Err(PrepareError::UnusedParam("b".to_owned()))
+    );
+    assert_eq!(
+        queries.prepare(
+            "q",
+            "$a",
+            2,
+            params(&[("a", ParamType::Text), ("a", ParamType::Numeric)])
+        ),
+        Err(PrepareError::DuplicateParam("a".to_owned()))
+    );
+    assert_eq!(
+        queries.prepare("q", "@price:[1 2", 2, Vec::new()),
+        Err(PrepareError::Parse("unterminated range".to_owned()))
+    );
+    assert!(queries.get("q").is_none());
+}
+
+#[test]
+fn test_execute_errors() {
+    let (queries, _) = prepared();
+    queries
+        .prepare(
+            "q",
+            "@price:[$min $max]",
+            2,
+            params(&[("min", ParamType::Numeric), ("max", ParamType::Numeric)]),
+        )
+        .unwrap();
+    assert_eq!(
+        queries.execute("other", &[], &PlainNumbers),
+        Err(ExecuteError::UnknownQuery("other".to_owned()))
+    );
+    assert_eq!(
+        queries.execute("q", &[("min", "1"), ("max", "x")], &PlainNumbers),
+        Err(ExecuteError::BadValue {
+            param: "max".to_owned(),
+            value: "x".to_owned()
+        })
+    );
+    assert_eq!(
+        queries.execute("q", &[("size", "1")], &PlainNumbers),
+        Err(ExecuteError::UnknownParam("size".to_owned()))
+    );
+    assert_eq!(
+        queries.execute("q", &[("min", "1")], &PlainNumbers),
+        Err(ExecuteError::Param(ParamError::Missing("max".to_owned())))
+    );
+    assert_eq!("numeric".parse(), Ok(ParamType::Numeric));
+    assert!(queries.remove("q"));
+    assert!(!queries.remove("q"));
+}
@@ -0,0 +1,149 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::borrow::Cow;
+
+use query::QueryNode;
+use query::rewrite::{RegisterError, RewriteContext, RewriteError, RewriteHooks};
+
+const CX: RewriteContext<'static> = RewriteContext {
+    index_name: "idx:acme",
+    dialect: 2,
+};
+
+#[test]
+fn test_no_hooks_borrows() {
+    let hooks = RewriteHooks::new();
+    assert!(hooks.is_empty());
+    assert!(matches!(
+        hooks.pre_parse(&CX, "hello world"),
+        Ok(Cow::Borrowed("hello world"))
+    ));
+}
+
+#[test]
+fn test_pre_parse_ordering() {
+    let mut hooks = RewriteHooks::new();
+    hooks
+        .register_pre_parse(
+            "suffix",
+            10,
+            Box::new(|_, q| Ok(Some(format!("{q} suffix")))),
+        )
+        .unwrap();
+    hooks
+        .register_pre_parse("noop", 0, Box::new(|_, _| Ok(None)))
+        .unwrap();
+    hooks
+        .register_pre_parse(
+            "prefix",
+            0,
+            Box::new(|_, q| Ok(Some(format!("prefix {q}")))),
+        )
+        .unwrap();
+    // Same priority: registration order. Lower priority first.
+    assert_eq!(
+        hooks.pre_parse(&CX, "query").unwrap(),
+        "prefix query suffix"
+    );
+}
+
+#[test]
+fn test_post_parse_injects_tenant_filter() {
+    let mut hooks = RewriteHooks::new();
+    hooks
+        .register_post_parse(
+            "tenant",
+            0,
+            Box::new(|cx, root| {
+                let tenant = cx
+                    .index_name
+                    .strip_prefix("idx:")
+                    .ok_or_else(|| "unexpected index name".to_owned())?;
+                let filter = QueryNode::tag("tenant", [tenant]);
+                *root = std::mem::replace(root, QueryNode::token("")).and(filter);
+                Ok(())
+            }),
+        )
+        .unwrap();
+
+    let mut root = QueryNode::token("hello");
+    hooks.post_parse(&CX, &mut root).unwrap();
+    assert_eq!(
+        root,
+        QueryNode::intersect(vec![
+            QueryNode::token("hello"),
+            QueryNode::tag("tenant", ["acme"]),
+        ])
+    );
+
+    let other = RewriteContext {
+        index_name: "other",
+        dialect: 2,
+    };
+    assert_eq!(
+        hooks.post_parse(&other, &mut root),
+        Err(RewriteError {
+            hook: "tenant".to_owned(),
+            message: "unexpected index name".to_owned(),
+        })
+    );
+}
+
+#[test]
+fn test_error_stops_the_stage() {
+    let mut hooks = RewriteHooks::new();
+    hooks
+        .register_pre_parse("deny", 0, Box::new(|_, _| Err("denied".to_owned())))
+        .unwrap();
+    hooks
+        .register_pre_parse("unreachable", 1, Box::new(|_, _| panic!("must not run")))
+        .unwrap();
+    let err = hooks.pre_parse(&CX, "query").unwrap_err();
+    assert_eq!(err.to_string(), "Query rewrite `deny` failed: denied");
+}
+
+#[test]
+fn test_register_and_unregister() {
+    let mut hooks = RewriteHooks::new();
+    let id = hooks
+        .register_pre_parse("a", 0, Box::new(|_, _| Ok(None)))
+        .unwrap();
+    assert_eq!(
+        hooks
+            .register_pre_parse("a", 1, Box::new(|_, _| Ok(None)))
+            .unwrap_err(),
+        RegisterError::DuplicateName("a".to_owned())
+    );
+    // Names are scoped to a stage.
+    let post = hooks
+        .register_post_parse("a", 0, Box::new(|_, _| Ok(())))
+        .unwrap();
+    assert!(hooks.unregister(id));
+    assert!(!hooks.unregister(id));
+    assert!(hooks.unregister(post));
+    assert!(hooks.is_empty());
+}
+
+#[test]
+fn test_global_registry() {
+    let id = query::rewrite::global()
+        .write()
+        .unwrap()
+        .register_pre_parse("global", 0, Box::new(|_, q| Ok(Some(q.to_uppercase()))))
+        .unwrap();
+    let rewritten = query::rewrite::global()
+        .read()
+        .unwrap()
+        .pre_parse(&CX, "abc")
+        .unwrap()
+        .into_owned();
+    assert_eq!(rewritten, "ABC");
+    assert!(query::rewrite::global().write().unwrap().unregister(id));
+}
@@ -0,0 +1,62 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use query::{
+    QueryNode, QueryNodeKind,
+    tag_range::{TagRangeError, tag_range},
+};
+
+fn lex_range(node: &QueryNode) -> &QueryNodeKind {
+    assert_eq!(
+        node.kind,
+        QueryNodeKind::Tag {
+            field: "sku".to_owned()
+        }
+    );
+    &node.children[0].kind
+}
+
+#[test]
+fn test_tag_range() {
+    let node = tag_range("sku", "A100 to (B200", 4).unwrap();
+    assert_eq!(
+        *lex_range(&node),
+        QueryNodeKind::LexRange {
+            begin: Some("A100".to_owned()),
+            include_begin: true,
+            end: Some("B200".to_owned()),
+            include_end: false,
+        }
+    );
+
+    let node = tag_range("sku", "(A100 TO *", 4).unwrap();
+    assert_eq!(
+        *lex_range(&node),
+        QueryNodeKind::LexRange {
+            begin: Some("A100".to_owned()),
+            include_begin: false,
+            end: None,
+            include_end: true,
+        }
+    );
+}
+
+#[test]
+fn test_tag_range_errors() {
+    for body in ["A100 B200", "A100 TO", "A TO B TO C", "( TO B"] {
+        assert_eq!(
+            tag_range("sku", body, 4),
+            Err(TagRangeError::BadRange(body.to_owned()))
+        );
+    }
+    assert_eq!(
+        tag_range("sku", "A TO B", 2),
+        Err(TagRangeError::Unsupported { dialect: 2 })
+    );
+}
@@ -0,0 +1,230 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::collections::HashMap;
+
+use query::{
+    QueryNode, QueryNodeKind,
+    numeric::{NumericRange, PlainNumbers},
+    params::ParamError,
+    rewrite::{RewriteContext, RewriteHooks},
+    validate::{
+        CostModel, Estimate, TimeClass, ValidateError, Validator, estimate, referenced_fields,
+    },
+};
+
+/// A toy parser: `@price:[...]` is a numeric range, `@tags:{...}` a tag,
+/// `-x` a negation, anything else a term.
+fn parse(query: &str, _dialect: u32) -> Result<QueryNode, String> {
+    let node = |part: &str| -> Result<QueryNode, String> {
+        if let Some(range) = part.strip_prefix("@price:[") {
+            let range = range.strip_suffix(']').ok_or("unterminated range")?;
+            Ok(QueryNode::new(QueryNodeKind::ParamNumeric {
+                field: "price".to_owned(),
+                range: range.to_owned(),
+            }))
+        } else if let Some(tags) = part.strip_prefix("@tags:{") {
+            let tags = tags.strip_suffix('}').ok_or("unterminated tag")?;
+            Ok(QueryNode::tag("tags", tags.split('|')))
+        } else if let Some(term) = part.strip_prefix('-') {
+            Ok(QueryNode::negate(QueryNode::token(term)))
+        } else {
+            Ok(QueryNode::token(part))
+        }
+    };
+    let children = query.split(" AND ").map(node).collect::<Result<_, _>>()?;
+    Ok(QueryNode::intersect(children))
+}
+
+struct Stats(HashMap<&'static str, u64>);
+
+impl CostModel for Stats {
+    fn num_docs(&self) -> u64 {
+        100
+    }
+
+    fn term_docs(&self, term: &str) -> u64 {
+        self.0.get(term).copied().unwrap_or(0)
+    }
+
+    fn tag_docs(&self, _field: &str, value: &str) -> u64 {
+        self.term_docs(value)
+    }
+
+    fn numeric_docs(&self, _field: &str, range: &NumericRange) -> u64 {
+        (range.max - range.min) as u64
+    }
+}
+
+fn stats() -> Stats {
+    Stats(HashMap::from([
+        ("hello", 40),
+        ("world", 10),
+        ("red", 70),
+        ("blue", 50),
+    ]))
+}
+
+const CX: RewriteContext<'static> = RewriteContext {
+    index_name: "idx",
+    dialect: 2,
+};
+
+fn validator(hooks: &RewriteHooks) -> Validator<'_> {
+    Validator {
+        hooks,
+        parse: &parse,
+        has_field: &|field| field == "price" || field == "tags",
+        numeric: &PlainNumbers,
+    }
+}
+
+#[test]
+fn test_validate() {
+    let hooks = RewriteHooks::default();
+    let params = HashMap::from([("min", "10"), ("max", "30")]);
+    let validation = validator(&hooks)
+        .validate(
+            &CX,
+            "hello AND @price:[$min $max] AND @tags:{red|blue}",
+            &|name| params.get(name).copied(),
+            &stats(),
+        )
+        .unwrap();
+
+    assert_eq!(
+        validation.plan.children[1],
+        QueryNode::numeric("price", 10.0, 30.0)
+    );
+    assert_eq!(
+        validation.fields.into_iter().collect::<Vec<_>>(),
+        ["price", "tags"]
+    );
+    assert_eq!(
+        validation.estimate,
+        Estimate {
+            // The smallest of the intersected children.
+            results: 20,
+            postings: 40 + 20 + 70 + 50,
+            // A block for each leaf.
+            blocks: 4,
+        }
+    );
+}
+
+#[test]
+fn test_validate_errors() {
+    let mut hooks = RewriteHooks::default();
+    let no_params = |_: &str| None;
+    assert_eq!(
+        validator(&hooks).validate(&CX, "@price:[1 2", &no_params, &stats()),
+        Err(ValidateError::Parse("unterminated range".to_owned()))
+    );
+    assert_eq!(
+        validator(&hooks).validate(&CX, "@price:[$min 2]", &no_params, &stats()),
+        Err(ValidateError::Param(ParamError::Missing("min".to_owned())))
+    );
+
+    hooks
+        .register_post_parse(
+            "tenant",
+            0,
+            Box::new(|_, root| {
+                *root = root.clone().and(QueryNode::tag("tenant", ["acme"]));
+                Ok(())
+            }),
+        )
+        .unwrap();
+    let error = validator(&hooks)
+        .validate(&CX, "hello", &no_params, &stats())
+        .unwrap_err();
+    assert_eq!(error, ValidateError::UnknownField("tenant".to_owned()));
+    assert_eq!(error.to_string(), "Unknown field `tenant`");
+
+    hooks
+        .register_pre_parse("reject", 0, Box::new(|_, _| Err("nope".to_owned())))
+        .unwrap();
+    assert!(matches!(
+        validator(&hooks).validate(&CX, "hello", &no_params, &stats()),
+        Err(ValidateError::Rewrite(_))
+    ));
+}
+
+#[test]
+fn test_estimate() {
+    let stats = stats();
+    let union = QueryNode::union(vec![QueryNode::token("hello"), QueryNode::token("red")]);
+    // Capped by the number of documents.
+    assert_eq!(
+        estimate(&union, &stats),
+        Estimate {
+            results: 100,
+            postings: 110,
+            blocks: 2
+        }
+    );
+    // Negations are driven by a wildcard iterator.
+    assert_eq!(
+        estimate(&QueryNode::negate(QueryNode::token("world")), &stats),
+        Estimate {
+            results: 90,
+            postings: 110,
+            blocks: 2
+        }
+    );
+    assert_eq!(
+        estimate(&QueryNode::token("missing"), &stats),
+        Estimate::default()
+    );
+    assert_eq!(
+        estimate(&QueryNode::new(QueryNodeKind::Wildcard), &stats).results,
+        100
+    );
+}
+
+#[test]
+fn test_referenced_fields() {
+    let mut title = QueryNode::token("hello");
+    title.opts.fields = query::FieldSelector::Named(vec!["title".to_owned()]);
+    let root = QueryNode::intersect(vec![
+        title,
+        QueryNode::numeric("price", 0.0, 1.0),
+        QueryNode::new(QueryNodeKind::Missing {
+            field: "color".to_owned(),
+        }),
+    ]);
+    assert_eq!(
+        referenced_fields(&root).into_iter().collect::<Vec<_>>(),
+        ["color", "price", "title"]
+    );
+}
+
+#[test]
+fn test_explain_with_cost() {
+    let stats = stats();
+    let query = |q: &str| parse(q, 2).unwrap();
+    let narrow = estimate(&query("world AND hello"), &stats);
+    assert_eq!(
+        narrow.explain(),
+        [
+            "Estimated results: 10",
+            "Estimated postings: 50",
+            "Estimated blocks: 2",
+            "Estimated time: fast",
+        ]
+    );
+    // The same documents, found by reading more postings.
+    let broad = estimate(&query("world AND red AND blue"), &stats);
+    assert_eq!(broad.results, narrow.results);
+    assert!(broad.postings > narrow.postings);
+
+    assert_eq!(TimeClass::of(10_000), TimeClass::Fast);
+    assert_eq!(TimeClass::of(10_001), TimeClass::Moderate);
+    assert_eq!(TimeClass::of(2_000_000).to_string(), "slow");
+}
@@ -0,0 +1,274 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Accounting of the memory allocated by a query, for the `memory` section of
+//! `FT.PROFILE`.
+//!
+//! The [`AccountingAllocator`] wraps the global allocator. While a query runs
+//! a stage inside a [`QueryMemory::enter`] scope, the allocations made on that
+//! thread are attributed to the query and to the [`Category`] of the stage:
+//! their number, the bytes currently held and the peak. Memory taken from the
+//! query's arena doesn't go through the allocator, and is reported separately
+//! with [`QueryMemory::record_arena`].
+//!
+//! Memory is attributed to the scope active when it is allocated, and released
+//! from the scope active when it is freed. Memory outliving the query, such as
+//! cached values, is therefore still counted as held by it.
+
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    cell::Cell,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+/// The stage of the query an allocation is attributed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    /// The sorter, including the heap of the top results.
+    Sorter,
+    /// The grouper and its reducers.
+    Grouper,
+    /// The reply buffer.
+    Reply,
+    /// Everything else.
+    Other,
+}
+
+impl Category {
+    const ALL: [Self; 4] = [Self::Sorter, Self::Grouper, Self::Reply, Self::Other];
+
+    /// The name of the line item in the profile.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Sorter => "Sorter",
+            Self::Grouper => "Grouper",
+            Self::Reply => "Reply",
+            Self::Other => "Other",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    allocations: AtomicU64,
+    current: AtomicU64,
+    peak: AtomicU64,
+}
+
+impl Counters {
+    fn alloc(&self, bytes: u64) {
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        let current = self.current.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.peak.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn free(&self, bytes: u64) {
+        // Memory allocated outside of the scope may be freed within it.
+        let _ = self
+            .current
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+                Some(current.saturating_sub(bytes))
+            });
+    }
+
+    fn stats(&self) -> MemoryStats {
+        MemoryStats {
+            allocations: self.allocations.load(Ordering::Relaxed),
+            current_bytes: self.current.load(Ordering::Relaxed),
+            peak_bytes: self.peak.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The memory accounted to a query or to one of its categories.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub allocations: u64,
+    /// The bytes allocated and not freed yet.
+    pub current_bytes: u64,
+    /// The highest value reached by `current_bytes`.
+    pub peak_bytes: u64,
+}
+
+/// The memory accounting of a query, shared by the threads running it.
+#[derive(Debug, Default)]
+pub struct QueryMemory {
+    total: Counters,
+    categories: [Counters; 4],
+    arena: AtomicU64,
+}
+
+impl QueryMemory {
+    pub fn new() -> Arc<Self> {
+        Arc::default()
+    }
+
+    /// Attribute the allocations made by this thread to the query and
+    /// `category`, until the returned guard is dropped. Scopes can be nested.
+    pub fn enter(self: &Arc<Self>, category: Category) -> Scope {
+        let previous = CURRENT.replace(Some((Arc::clone(self), category)));
+        Scope { previous }
+    }
+
+    /// Record `bytes` taken from the query's arena.
+    pub fn record_arena(&self, bytes: u64) {
+        self.arena.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn alloc(&self, category: Category, bytes: u64) {
+        self.total.alloc(bytes);
+        self.categories[category as usize].alloc(bytes);
+    }
+
+    fn free(&self, category: Category, bytes: u64) {
+        self.total.free(bytes);
+        self.categories[category as usize].free(bytes);
+    }
+
+    /// The memory accounted to the query, all categories together.
+    pub fn total(&self) -> MemoryStats {
+        self.total.stats()
+    }
+
+    /// The memory accounted to `category`.
+    pub fn category(&self, category: Category) -> MemoryStats {
+        self.categories[category as usize].stats()
+    }
+
+    /// The bytes taken from the arena.
+    pub fn arena_bytes(&self) -> u64 {
+        self.arena.load(Ordering::Relaxed)
+    }
+
+    /// The `memory` section of `FT.PROFILE`.
+    pub fn profile(&self) -> MemoryProfile {
+        let total = self.total();
+        MemoryProfile {
+            allocations: total.allocations,
+            peak_bytes: total.peak_bytes,
+            arena_bytes: self.arena_bytes(),
+            items: Category::ALL
+                .into_iter()
+                .map(|category| (category.name(), self.category(category)))
+                .collect(),
+        }
+    }
+}
+
+/// The `memory` section of `FT.PROFILE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryProfile {
+    pub allocations: u64,
+    pub peak_bytes: u64,
+    pub arena_bytes: u64,
+    /// The line item of each category.
+    pub items: Vec<(&'static str, MemoryStats)>,
+}
+
+impl MemoryProfile {
+    /// The totals of the section. The line items follow them in the reply.
+    pub const fn fields(&self) -> [(&'static str, u64); 3] {
+        [
+            ("Allocations", self.allocations),
+            ("Peak bytes", self.peak_bytes),
+            ("Arena bytes", self.arena_bytes),
+        ]
+    }
+}
+
+thread_local! {
+    /// The scope allocations of this thread are attributed to.
+    static CURRENT: Cell<Option<(Arc<QueryMemory>, Category)>> = const { Cell::new(None) };
+}
+
+/// Attribute the allocations of this thread to a query until dropped, see
+/// [`QueryMemory::enter`].
+#[must_use = "allocations are only attributed while the scope is alive"]
+pub struct Scope {
+    previous: Option<(Arc<QueryMemory>, Category)>,
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        // The scope may hold the last reference to the query's accounting,
+        // which must not be dropped while it is current.
+        let current = CURRENT.replace(self.previous.take());
+        drop(current);
+    }
+}
+
+/// Run `f` with the scope of this thread, if any.
+fn with_current(f: impl FnOnce(&QueryMemory, Category)) {
+    // The thread local may already be destroyed on thread exit.
+    let _ = CURRENT.try_with(|current| {
+        let scope = current.take();
+        if let Some((memory, category)) = &scope {
+            f(memory, *category);
+        }
+        current.set(scope);
+    });
+}
+
+/// A global allocator which accounts allocations to the current query scope,
+/// and delegates them to `A`.
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: AccountingAllocator<RedisAlloc> = AccountingAllocator::new(RedisAlloc);
+/// ```
+pub struct AccountingAllocator<A>(A);
+
+impl<A> AccountingAllocator<A> {
+    pub const fn new(inner: A) -> Self {
+        Self(inner)
+    }
+}
+
+// SAFETY: the allocations are delegated to `A`, which upholds the contract of
+// `GlobalAlloc`. The accounting doesn't allocate.
+unsafe impl<A: GlobalAlloc> GlobalAlloc for AccountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // SAFETY: forwarded from the caller.
+        let ptr = unsafe { self.0.alloc(layout) };
+        if !ptr.is_null() {
+            with_current(|memory, category| memory.alloc(category, layout.size() as u64));
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        // SAFETY: forwarded from the caller.
+        let ptr = unsafe { self.0.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            with_current(|memory, category| memory.alloc(category, layout.size() as u64));
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: forwarded from the caller.
+        unsafe { self.0.dealloc(ptr, layout) };
+        with_current(|memory, category| memory.free(category, layout.size() as u64));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // SAFETY: forwarded from the caller.
+        let new = unsafe { self.0.realloc(ptr, layout, new_size) };
+        if !new.is_null() {
+            with_current(|memory, category| {
+                memory.free(category, layout.size() as u64);
+                memory.alloc(category, new_size as u64);
+            });
+        }
+        new
+    }
+}
@@ -0,0 +1,103 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{alloc::System, hint::black_box, thread};
+
+use query_memory::{AccountingAllocator, Category, QueryMemory};
+
+#[global_allocator]
+static ALLOCATOR: AccountingAllocator<System> = AccountingAllocator::new(System);
+
+#[test]
+fn test_allocations_are_attributed_to_the_scope() {
+    let memory = QueryMemory::new();
+    {
+        let _scope = memory.enter(Category::Sorter);
+        let heap = black_box(vec![0u8; 1000]);
+        drop(heap);
+        let _kept = black_box(vec![0u8; 300]);
+    }
+    let sorter = memory.category(Category::Sorter);
+    assert_eq!(sorter.allocations, 2);
+    assert_eq!(sorter.peak_bytes, 1000);
+    assert_eq!(sorter.current_bytes, 0);
+    assert_eq!(memory.category(Category::Grouper).allocations, 0);
+
+    // Nothing is accounted outside of a scope.
+    let _outside = black_box(vec![0u8; 5000]);
+    assert_eq!(memory.total().peak_bytes, 1000);
+}
+
+#[test]
+fn test_nested_scopes() {
+    let memory = QueryMemory::new();
+    let _scope = memory.enter(Category::Other);
+    let a = black_box(vec![0u8; 100]);
+    {
+        let _reply = memory.enter(Category::Reply);
+        let b = black_box(vec![0u8; 400]);
+        assert_eq!(memory.category(Category::Reply).current_bytes, 400);
+        drop(b);
+    }
+    let c = black_box(vec![0u8; 50]);
+    assert_eq!(memory.category(Category::Other).current_bytes, 150);
+    assert_eq!(memory.category(Category::Other).peak_bytes, 150);
+    assert_eq!(memory.total().peak_bytes, 500);
+    drop((a, c));
+}
+
+#[test]
+fn test_realloc_is_accounted() {
+    let memory = QueryMemory::new();
+    let _scope = memory.enter(Category::Grouper);
+    let mut v: Vec<u8> = Vec::with_capacity(16);
+    v.reserve_exact(64);
+    black_box(&v);
+    let grouper = memory.category(Category::Grouper);
+    assert_eq!(grouper.current_bytes, v.capacity() as u64);
+    assert!(grouper.peak_bytes >= 64);
+    assert_eq!(grouper.allocations, 2);
+}
+
+#[test]
+fn test_threads_share_the_accounting() {
+    let memory = QueryMemory::new();
+    thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                let _scope = memory.enter(Category::Sorter);
+                black_box(vec![0u8; 256]);
+            });
+        }
+    });
+    assert_eq!(memory.category(Category::Sorter).allocations, 4);
+    assert_eq!(memory.category(Category::Sorter).current_bytes, 0);
+}
+
+#[test]
+fn test_profile() {
+    let memory = QueryMemory::new();
+    {
+        let _scope = memory.enter(Category::Reply);
+        black_box(vec![0u8; 128]);
+    }
+    memory.record_arena(4096);
+    let profile = memory.profile();
+    assert_eq!(
+        profile.fields(),
+        [
+            ("Allocations", 1),
+            ("Peak bytes", 128),
+            ("Arena bytes", 4096)
+        ]
+    );
+    let names: Vec<_> = profile.items.iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, ["Sorter", "Grouper", "Reply", "Other"]);
+    assert_eq!(profile.items[2].1.peak_bytes, 128);
+}
@@ -0,0 +1,220 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Versioned RDB encoding, with a downgrade path.
+//!
+//! Every type persisted to RDB implements [`Persist`], saving itself for a
+//! given [`FormatVersion`] (the module `encver`). By default, the current
+//! version is written. When `FT.CONFIG SET _FORMAT_COMPAT <version>` is set, the
+//! [negotiated](negotiate) version is the requested older one instead, so that
+//! the RDB can be loaded by the previous release during a rolling downgrade.
+//! Data that can't be expressed in the older format makes the negotiation
+//! fail with an explicit error, rather than silently losing information.
+//!
+//! Loading an RDB written by a newer release fails with
+//! [`FormatError::TooNew`] instead of aborting.
+
+mod memory;
+
+pub use memory::MemoryRdb;
+
+use std::fmt::{self, Display};
+
+/// An RDB encoding version, `INDEX_*_VERSION` in `spec.h`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FormatVersion(pub u32);
+
+impl FormatVersion {
+    /// `INDEX_CURRENT_VERSION`.
+    pub const CURRENT: Self = Self(25);
+    /// The oldest version that can still be loaded.
+    pub const MIN_LOADABLE: Self = Self(2);
+    /// The version that will add persisted vector graphs. It's above
+    /// [`CURRENT`](Self::CURRENT) until the C save path writes the graph
+    /// section, so [`negotiate`] refuses it and no RDB is tagged with it.
+    pub const VECSIM_GRAPH: Self = Self(26);
+    /// `INDEX_VECSIM_SVS_VAMANA_VERSION`.
+    pub const VECSIM_SVS_VAMANA: Self = Self(25);
+    /// `INDEX_INDEXALL_VERSION`.
+    pub const INDEXALL: Self = Self(24);
+    /// `INDEX_GEOMETRY_VERSION`.
+    pub const GEOMETRY: Self = Self(23);
+    /// `INDEX_VECSIM_TIERED_VERSION`.
+    pub const VECSIM_TIERED: Self = Self(22);
+
+    /// Whether data written with this version can be loaded.
+    pub fn check_loadable(self) -> Result<(), FormatError> {
+        if self > Self::CURRENT {
+            Err(FormatError::TooNew {
+                found: self,
+                max: Self::CURRENT,
+            })
+        } else if self < Self::MIN_LOADABLE {
+            Err(FormatError::TooOld {
+                found: self,
+                min: Self::MIN_LOADABLE,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Display for FormatVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The reasons encoding or decoding can fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    /// The data was written by a newer release.
+    TooNew {
+        found: FormatVersion,
+        max: FormatVersion,
+    },
+    /// The data was written by a release too old to be supported.
+    TooOld {
+        found: FormatVersion,
+        min: FormatVersion,
+    },
+    /// A feature in use can't be encoded in the target version.
+    Unsupported {
+        feature: &'static str,
+        since: FormatVersion,
+        target: FormatVersion,
+    },
+    /// The RDB ended early or holds an unexpected value.
+    Corrupt(&'static str),
+    /// The `_FORMAT_COMPAT` value is neither `off` nor a version number.
+    InvalidCompat(String),
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooNew { found, max } => write!(
+                f,
+                "RDB format version {found} is newer than the supported version {max}"
+            ),
+            Self::TooOld { found, min } => write!(
+                f,
+                "RDB format version {found} is older than the oldest supported version {min}"
+            ),
+            Self::Unsupported {
+                feature,
+                since,
+                target,
+            } => write!(
+                f,
+                "{feature} requires RDB format version {since}, cannot write version {target}"
+            ),
+            Self::Corrupt(what) => write!(f, "Corrupt RDB: {what}"),
+            Self::InvalidCompat(value) => write!(
+                f,
+                "Invalid _FORMAT_COMPAT value `{value}`, expected `off` or a version number"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// Parse the value of the `_FORMAT_COMPAT` configuration: `off` (or `0`) to
+/// write the current version, or the version number to write.
+pub fn parse_format_compat(value: &str) -> Result<Option<FormatVersion>, FormatError> {
+    if value.eq_ignore_ascii_case("off") || value == "0" {
+        return Ok(None);
+    }
+    let version = value
+        .parse()
+        .map(FormatVersion)
+        .map_err(|_| FormatError::InvalidCompat(value.to_owned()))?;
+    version.check_loadable()?;
+    Ok(Some(version))
+}
+
+/// A feature a value uses, and the version that introduced its encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Requirement {
+    pub feature: &'static str,
+    pub since: FormatVersion,
+}
+
+/// Choose the version to write data with `requirements`.
+///
+/// Without a compatibility target, that's [`FormatVersion::CURRENT`].
+/// Otherwise it's the target. Either way, every requirement must be met.
+pub fn negotiate(
+    compat: Option<FormatVersion>,
+    requirements: impl IntoIterator<Item = Requirement>,
+) -> Result<FormatVersion, FormatError> {
+    let target = compat.unwrap_or(FormatVersion::CURRENT);
+    target.check_loadable()?;
+    match requirements.into_iter().find(|r| r.since > target) {
+        Some(Requirement { feature, since }) => Err(FormatError::Unsupported {
+            feature,
+            since,
+            target,
+        }),
+        None => Ok(target),
+    }
+}
+
+/// The save half of the RDB API (`RedisModule_Save*`).
+pub trait RdbWrite {
+    fn save_unsigned(&mut self, value: u64);
+    fn save_signed(&mut self, value: i64);
+    fn save_double(&mut self, value: f64);
+    fn save_string_buffer(&mut self, value: &[u8]);
+}
+
+/// The load half of the RDB API (`RedisModule_Load*`). Every method fails
+/// with [`FormatError::Corrupt`] on a truncated or mistyped input.
+pub trait RdbRead {
+    fn load_unsigned(&mut self) -> Result<u64, FormatError>;
+    fn load_signed(&mut self) -> Result<i64, FormatError>;
+    fn load_double(&mut self) -> Result<f64, FormatError>;
+    fn load_string_buffer(&mut self) -> Result<Vec<u8>, FormatError>;
+}
+
+/// A type persisted to RDB.
+pub trait Persist: Sized {
+    /// The features used by this value, restricting the versions it can be
+    /// written with.
+    fn requirements(&self) -> Vec<Requirement> {
+        Vec::new()
+    }
+
+    /// Save the value with the encoding of `version`, which
+    /// [`negotiate`] accepted for [`Persist::requirements`].
+    fn save(&self, rdb: &mut dyn RdbWrite, version: FormatVersion);
+
+    /// Load a value saved with `version`.
+    fn load(rdb: &mut dyn RdbRead, version: FormatVersion) -> Result<Self, FormatError>;
+}
+
+/// Negotiate the version for `value` and save it. Returns the version written,
+/// which must be stored as the `encver` of the data.
+pub fn save<T: Persist>(
+    value: &T,
+    rdb: &mut dyn RdbWrite,
+    compat: Option<FormatVersion>,
+) -> Result<FormatVersion, FormatError> {
+    let version = negotiate(compat, value.requirements())?;
+    value.save(rdb, version);
+    Ok(version)
+}
+
+/// Check `version` and load a value.
+pub fn load<T: Persist>(rdb: &mut dyn RdbRead, version: FormatVersion) -> Result<T, FormatError> {
+    version.check_loadable()?;
+    T::load(rdb, version)
+}
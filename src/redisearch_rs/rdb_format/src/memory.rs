@@ -0,0 +1,92 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::collections::VecDeque;
+
+use crate::{FormatError, RdbRead, RdbWrite};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Item {
+    Unsigned(u64),
+    Signed(i64),
+    Double(f64),
+    Buffer(Vec<u8>),
+}
+
+/// An in-memory RDB, which checks that values are loaded with the type they
+/// were saved with, as the real RDB does.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryRdb {
+    items: VecDeque<Item>,
+}
+
+impl MemoryRdb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of values saved and not loaded yet.
+    pub fn remaining(&self) -> usize {
+        self.items.len()
+    }
+
+    fn next(&mut self) -> Result<Item, FormatError> {
+        self.items
+            .pop_front()
+            .ok_or(FormatError::Corrupt("unexpected end of data"))
+    }
+}
+
+impl RdbWrite for MemoryRdb {
+    fn save_unsigned(&mut self, value: u64) {
+        self.items.push_back(Item::Unsigned(value));
+    }
+
+    fn save_signed(&mut self, value: i64) {
+        self.items.push_back(Item::Signed(value));
+    }
+
+    fn save_double(&mut self, value: f64) {
+        self.items.push_back(Item::Double(value));
+    }
+
+    fn save_string_buffer(&mut self, value: &[u8]) {
+        self.items.push_back(Item::Buffer(value.to_vec()));
+    }
+}
+
+impl RdbRead for MemoryRdb {
+    fn load_unsigned(&mut self) -> Result<u64, FormatError> {
+        match self.next()? {
+            Item::Unsigned(v) => Ok(v),
+            _ => Err(FormatError::Corrupt("expected an unsigned integer")),
+        }
+    }
+
+    fn load_signed(&mut self) -> Result<i64, FormatError> {
+        match self.next()? {
+            Item::Signed(v) => Ok(v),
+            _ => Err(FormatError::Corrupt("expected a signed integer")),
+        }
+    }
+
+    fn load_double(&mut self) -> Result<f64, FormatError> {
+        match self.next()? {
+            Item::Double(v) => Ok(v),
+            _ => Err(FormatError::Corrupt("expected a double")),
+        }
+    }
+
+    fn load_string_buffer(&mut self) -> Result<Vec<u8>, FormatError> {
+        match self.next()? {
+            Item::Buffer(v) => Ok(v),
+            _ => Err(FormatError::Corrupt("expected a string buffer")),
+        }
+    }
+}
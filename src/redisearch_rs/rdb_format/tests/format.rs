@@ -0,0 +1,150 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use rdb_format::{
+    FormatError, FormatVersion, MemoryRdb, Persist, RdbRead, RdbWrite, Requirement, load,
+    negotiate, parse_format_compat, save,
+};
+
+/// A field spec, whose `index_missing` flag was added in version 24: older
+/// versions don't have it, and neither do geometry fields before 23.
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    name: String,
+    geometry: bool,
+    index_missing: bool,
+}
+
+impl Persist for Field {
+    fn requirements(&self) -> Vec<Requirement> {
+        let mut requirements = Vec::new();
+        if self.geometry {
+            requirements.push(Requirement {
+                feature: "GEOSHAPE fields",
+                since: FormatVersion::GEOMETRY,
+            });
+        }
+        // Only non-default values need the newer encoding.
+        if self.index_missing {
+            requirements.push(Requirement {
+                feature: "INDEXMISSING",
+                since: FormatVersion::INDEXALL,
+            });
+        }
+        requirements
+    }
+
+    fn save(&self, rdb: &mut dyn RdbWrite, version: FormatVersion) {
+        rdb.save_string_buffer(self.name.as_bytes());
+        rdb.save_unsigned(u64::from(self.geometry));
+        if version >= FormatVersion::INDEXALL {
+            rdb.save_unsigned(u64::from(self.index_missing));
+        }
+    }
+
+    fn load(rdb: &mut dyn RdbRead, version: FormatVersion) -> Result<Self, FormatError> {
+        let name = String::from_utf8(rdb.load_string_buffer()?)
+            .map_err(|_| FormatError::Corrupt("field name is not UTF-8"))?;
+        let geometry = rdb.load_unsigned()? != 0;
+        let index_missing = version >= FormatVersion::INDEXALL && rdb.load_unsigned()? != 0;
+        Ok(Self {
+            name,
+            geometry,
+            index_missing,
+        })
+    }
+}
+
+fn field(geometry: bool, index_missing: bool) -> Field {
+    Field {
+        name: "title".to_owned(),
+        geometry,
+        index_missing,
+    }
+}
+
+#[test]
+fn test_current_roundtrip() {
+    let value = field(true, true);
+    let mut rdb = MemoryRdb::new();
+    let version = save(&value, &mut rdb, None).unwrap();
+    assert_eq!(version, FormatVersion::CURRENT);
+    assert_eq!(load::<Field>(&mut rdb, version), Ok(value));
+    assert_eq!(rdb.remaining(), 0);
+}
+
+#[test]
+fn test_downgrade() {
+    let value = field(true, false);
+    let mut rdb = MemoryRdb::new();
+    let version = save(&value, &mut rdb, Some(FormatVersion::GEOMETRY)).unwrap();
+    assert_eq!(version, FormatVersion::GEOMETRY);
+    // The older encoding has one value fewer.
+    assert_eq!(rdb.remaining(), 2);
+    assert_eq!(load::<Field>(&mut rdb, version), Ok(value));
+}
+
+#[test]
+fn test_downgrade_rejects_newer_features() {
+    let mut rdb = MemoryRdb::new();
+    let err = save(&field(false, true), &mut rdb, Some(FormatVersion(23))).unwrap_err();
+    assert_eq!(
+        err,
+        FormatError::Unsupported {
+            feature: "INDEXMISSING",
+            since: FormatVersion(24),
+            target: FormatVersion(23),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "INDEXMISSING requires RDB format version 24, cannot write version 23"
+    );
+    assert_eq!(rdb.remaining(), 0);
+
+    assert!(negotiate(Some(FormatVersion(22)), field(true, false).requirements()).is_err());
+    assert!(negotiate(Some(FormatVersion(99)), []).is_err());
+}
+
+#[test]
+fn test_load_newer_fails_gracefully() {
+    let mut rdb = MemoryRdb::new();
+    field(false, false).save(&mut rdb, FormatVersion::CURRENT);
+    assert_eq!(
+        load::<Field>(&mut rdb, FormatVersion(26)),
+        Err(FormatError::TooNew {
+            found: FormatVersion(26),
+            max: FormatVersion::CURRENT,
+        })
+    );
+    assert!(load::<Field>(&mut rdb, FormatVersion(1)).is_err());
+}
+
+#[test]
+fn test_corrupt() {
+    let mut rdb = MemoryRdb::new();
+    rdb.save_unsigned(1);
+    assert_eq!(
+        load::<Field>(&mut rdb, FormatVersion::CURRENT),
+        Err(FormatError::Corrupt("expected a string buffer"))
+    );
+    assert_eq!(
+        load::<Field>(&mut rdb, FormatVersion::CURRENT),
+        Err(FormatError::Corrupt("unexpected end of data"))
+    );
+}
+
+#[test]
+fn test_parse_format_compat() {
+    assert_eq!(parse_format_compat("OFF"), Ok(None));
+    assert_eq!(parse_format_compat("0"), Ok(None));
+    assert_eq!(parse_format_compat("24"), Ok(Some(FormatVersion(24))));
+    assert!(parse_format_compat("26").is_err());
+    assert!(parse_format_compat("latest").is_err());
+}
@@ -0,0 +1,272 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The search engine as a library, without Redis.
+//!
+//! A [`SearchEngine`] wires an [`IndexSpec`], the analysis chains of its TEXT
+//! fields, the Rust index structures and query evaluation together. Documents
+//! are field-value maps, as Redis hashes are, kept in an internal map keyed by
+//! document key. Queries are [`QueryNode`] trees, as produced by the parser.
+//!
+//! This lets applications embed the engine, and makes end-to-end fuzzing and
+//! benchmarking possible without a Redis server. Results are returned in
+//! insertion order: scoring and sorting belong to the pipeline, which isn't
+//! wired in yet. Tag fields use the default [`TagTokenizer`].
+//!
+//! [`TagTokenizer`]: tag_index::tokenizer::TagTokenizer
+
+mod postings;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display},
+    sync::Arc,
+};
+
+use analysis::{
+    AnalysisResources, Analyzer, StopWords, TokenFilter,
+    config::ConfigError,
+    filter::{PhoneticEncoder, Stemmer},
+};
+use index_spec::{FieldType, IndexSpec};
+use query::QueryNode;
+
+use postings::Postings;
+
+/// A document ID, assigned in insertion order.
+pub type DocId = u64;
+
+/// A document: the value of each of its fields.
+pub type Document = BTreeMap<String, String>;
+
+/// Errors returned by a [`SearchEngine`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmbeddedError {
+    /// The analysis chain of a field refers to a missing resource.
+    Analysis(ConfigError),
+    /// The value of a NUMERIC field is not a number.
+    BadNumeric { field: String, value: String },
+    /// A query refers to a field which isn't in the schema, or doesn't have
+    /// the expected type.
+    BadField(String),
+    /// The query uses a feature the embedded engine doesn't support yet.
+    Unsupported(&'static str),
+}
+
+impl Display for EmbeddedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Analysis(e) => e.fmt(f),
+            Self::BadNumeric { field, value } => {
+                write!(f, "Invalid numeric value `{value}` for field {field}")
+            }
+            Self::BadField(field) => write!(f, "Unknown field {field}"),
+            Self::Unsupported(feature) => write!(f, "{feature} are not supported"),
+        }
+    }
+}
+
+impl std::error::Error for EmbeddedError {}
+
+impl From<ConfigError> for EmbeddedError {
+    fn from(e: ConfigError) -> Self {
+        Self::Analysis(e)
+    }
+}
+
+/// Resources without stemmers, synonyms nor phonetic matchers, and with the
+/// default stopwords.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BasicResources;
+
+impl AnalysisResources for BasicResources {
+    fn stemmer(&self, _language: Option<&str>) -> Option<Arc<dyn Stemmer>> {
+        None
+    }
+
+    fn index_stopwords(&self) -> Arc<StopWords> {
+        Arc::new(StopWords::default_list())
+    }
+
+    fn synonyms(&self, _set: &str) -> Option<Box<dyn TokenFilter>> {
+        None
+    }
+
+    fn phonetic(&self, _matcher: &str) -> Option<Arc<dyn PhoneticEncoder>> {
+        None
+    }
+}
+
+/// The page of results to return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchOptions {
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Default for SearchOptions {
+    /// `LIMIT 0 10`, as for `FT.SEARCH`.
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            limit: 10,
+        }
+    }
+}
+
+/// The results of a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResults {
+    /// The number of matching documents.
+    pub total: usize,
+    /// The keys of the requested page of results.
+    pub keys: Vec<String>,
+}
+
+/// An index and its documents, see the [crate](crate) docs.
+pub struct SearchEngine {
+    spec: IndexSpec,
+    resources: Box<dyn AnalysisResources>,
+    /// The analyzer of each field of the schema, for TEXT fields.
+    analyzers: Vec<Option<Analyzer>>,
+    docs: HashMap<String, (DocId, Document)>,
+    keys: BTreeMap<DocId, String>,
+    next_id: DocId,
+    postings: Postings,
+}
+
+impl SearchEngine {
+    /// An empty index for `spec`, whose analysis chains take their resources
+    /// from `resources`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EmbeddedError::Analysis`] if a chain refers to a missing
+    /// resource.
+    pub fn new(
+        spec: IndexSpec,
+        resources: Box<dyn AnalysisResources>,
+    ) -> Result<Self, EmbeddedError> {
+        let analyzers = spec
+            .fields()
+            .iter()
+            .map(|field| match field.field_type {
+                FieldType::Text => spec
+                    .field_analyzers()
+                    .for_field(&field.name)
+                    .build(resources.as_ref())
+                    .map(Some),
+                _ => Ok(None),
+            })
+            .collect::<Result<_, _>>()?;
+        let postings = Postings::new(&spec);
+        Ok(Self {
+            spec,
+            resources,
+            analyzers,
+            docs: HashMap::new(),
+            keys: BTreeMap::new(),
+            next_id: 1,
+            postings,
+        })
+    }
+
+    pub const fn spec(&self) -> &IndexSpec {
+        &self.spec
+    }
+
+    /// The number of documents.
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Document> {
+        self.docs.get(key).map(|(_, doc)| doc)
+    }
+
+    /// Add the document `key`, replacing any previous version, as `HSET`
+    /// does. Fields which aren't in the schema are stored but not indexed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EmbeddedError::BadNumeric`] if the value of a NUMERIC field
+    /// is not a number. The document is then not added, and any previous
+    /// version is removed, as the module does on indexing failures.
+    pub fn add(&mut self, key: impl Into<String>, doc: Document) -> Result<(), EmbeddedError> {
+        let key = key.into();
+        self.delete(&key);
+        for field in self.spec.fields() {
+            if field.field_type == FieldType::Numeric
+                && let Some(value) = doc.get(&field.name)
+                && value.trim().parse::<f64>().is_err()
+            {
+                return Err(EmbeddedError::BadNumeric {
+                    field: field.name.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.postings
+            .index(&self.spec, &self.analyzers, id, &doc, true);
+        self.keys.insert(id, key.clone());
+        self.docs.insert(key, (id, doc));
+        Ok(())
+    }
+
+    /// Remove the document `key`. Returns whether it existed.
+    pub fn delete(&mut self, key: &str) -> bool {
+        let Some((id, doc)) = self.docs.remove(key) else {
+            return false;
+        };
+        self.postings
+            .index(&self.spec, &self.analyzers, id, &doc, false);
+        self.keys.remove(&id);
+        true
+    }
+
+    /// The documents matching `query`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EmbeddedError::BadField`] if the query refers to an unknown
+    /// field, and [`EmbeddedError::Unsupported`] for exact phrases, suffix,
+    /// fuzzy, lexical range and wildcard pattern queries, and unresolved
+    /// parameters.
+    pub fn search(
+        &self,
+        query: &QueryNode,
+        options: SearchOptions,
+    ) -> Result<SearchResults, EmbeddedError> {
+        let all: Vec<DocId> = self.keys.keys().copied().collect();
+        let ids = postings::Evaluator {
+            spec: &self.spec,
+            resources: self.resources.as_ref(),
+            postings: &self.postings,
+            all: &all,
+            keys: &self.docs,
+        }
+        .eval(query)?
+        .unwrap_or_default();
+        Ok(SearchResults {
+            total: ids.len(),
+            keys: ids
+                .iter()
+                .skip(options.offset)
+                .take(options.limit)
+                .map(|id| self.keys[id].clone())
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,421 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The index structures of the embedded engine, and the evaluation of queries
+//! over them.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use analysis::{AnalysisResources, Analyzer};
+use bsearch::{
+    bsearch_ge, bsearch_ge_batch, bsearch_gt, bsearch_le, bsearch_lt, insertion_point_for,
+    partition_ge,
+};
+use field_mask::{FieldMask, Mask};
+use index_spec::{FieldType, IndexSpec};
+use intersection::Intersection;
+use loser_tree::Union;
+use query::{
+    FieldSelector, QueryNode, QueryNodeKind,
+    numeric::{IntegerRange, NumericRange},
+};
+use tag_index::{TagIndex, tokenizer::TagTokenizer};
+
+use crate::{DocId, Document, EmbeddedError};
+
+/// The index of a field, by position in the schema.
+enum FieldIndex {
+    /// TEXT fields share the term index.
+    Text,
+    /// The entries of a NUMERIC field, sorted by value then id.
+    Numeric(Vec<(f64, DocId)>),
+    Tag(TagIndex),
+    /// GEO, GEOSHAPE and VECTOR fields are stored but not indexed yet.
+    Unindexed,
+}
+
+pub(crate) struct Postings {
+    /// The documents of each term, with the TEXT fields it appears in.
+    terms: BTreeMap<String, BTreeMap<DocId, Mask>>,
+    fields: Vec<FieldIndex>,
+    /// The documents having a value for each field, for `ismissing()`.
+    present: Vec<BTreeSet<DocId>>,
+    /// Masks are indexed by the position of fields in the schema.
+    mask_width: usize,
+    tags: TagTokenizer,
+}
+
+impl Postings {
+    pub(crate) fn new(spec: &IndexSpec) -> Self {
+        let fields = spec
+            .fields()
+            .iter()
+            .map(|field| match field.field_type {
+                FieldType::Text => FieldIndex::Text,
+                FieldType::Numeric => FieldIndex::Numeric(Vec::new()),
+                FieldType::Tag => FieldIndex::Tag(TagIndex::default()),
+                FieldType::Geo | FieldType::GeoShape | FieldType::Vector => FieldIndex::Unindexed,
+            })
+            .collect();
+        Self {
+            terms: BTreeMap::new(),
+            fields,
+            present: vec![BTreeSet::new(); spec.fields().len()],
+            mask_width: spec.fields().len(),
+            tags: TagTokenizer::default(),
+        }
+    }
+
+    /// Index `doc` as `id` if `add`, otherwise remove it from the index. The
+    /// numeric values must have been validated.
+    pub(crate) fn index(
+        &mut self,
+        spec: &IndexSpec,
+        analyzers: &[Option<Analyzer>],
+        id: DocId,
+        doc: &Document,
+        add: bool,
+    ) {
+        for (position, field) in spec.fields().iter().enumerate() {
+            let Some(value) = doc.get(&field.name) else {
+                continue;
+            };
+            if field.no_index {
+                continue;
+            }
+            if add {
+                self.present[position].insert(id);
+            } else {
+                self.present[position].remove(&id);
+            }
+            match &mut self.fields[position] {
+                FieldIndex::Text => {
+                    let Some(analyzer) = &analyzers[position] else {
+                        continue;
+                    };
+                    for token in analyzer.analyze(value) {
+                        let docs = self.terms.entry(token.term).or_default();
+                        if add {
+                            docs.entry(id)
+                                .or_insert_with(|| Mask::empty_for(self.mask_width))
+                                .insert(position);
+                        } else {
+                            docs.remove(&id);
+                        }
+                    }
+                }
+                FieldIndex::Numeric(entries) => {
+                    let n: f64 = value.trim().parse().expect("validated on add");
+                    if add {
+                        let at = insertion_point_for(entries, &(n, id), cmp_entry);
+                        entries.insert(at, (n, id));
+                    } else {
+                        let at = partition_ge(entries, &(n, id), cmp_entry);
+                        if entries.get(at) == Some(&(n, id)) {
+                            entries.remove(at);
+                        }
+                    }
+                }
+                FieldIndex::Tag(index) => {
+                    let tags = self.tags.tokenize(value.as_bytes());
+                    if add {
+                        index.index(&tags, id);
+                    } else {
+                        for tag in &tags {
+                            index.remove(tag, id);
+                        }
+                    }
+                }
+                FieldIndex::Unindexed => {}
+            }
+        }
+        if !add {
+            self.terms.retain(|_, docs| !docs.is_empty());
+        }
+    }
+}
+
+/// Evaluates a query. `None` stands for a node which doesn't restrict the
+/// results, such as a term made only of stopwords.
+pub(crate) struct Evaluator<'a> {
+    pub(crate) spec: &'a IndexSpec,
+    pub(crate) resources: &'a dyn AnalysisResources,
+    pub(crate) postings: &'a Postings,
+    /// Every document, by increasing id.
+    pub(crate) all: &'a [DocId],
+    pub(crate) keys: &'a HashMap<String, (DocId, Document)>,
+}
+
+type Hits = Option<Vec<DocId>>;
+
+impl Evaluator<'_> {
+    pub(crate) fn eval(&self, node: &QueryNode) -> Result<Hits, EmbeddedError> {
+        let hits = match &node.kind {
+            QueryNodeKind::Phrase { exact: false } => {
+                let children = self.eval_children(node)?;
+                if children.is_empty() {
+                    return Ok(None);
+                }
+                Intersection::new(children.iter().map(Vec::as_slice).collect()).collect()
+            }
+            QueryNodeKind::Phrase { exact: true } => {
+                return Err(EmbeddedError::Unsupported("Exact phrases"));
+            }
+            QueryNodeKind::Union => {
+                let children = self.eval_children(node)?;
+                if children.is_empty() {
+                    return Ok(None);
+                }
+                union(children)
+            }
+            QueryNodeKind::Token { term } => return self.term(term, &node.opts.fields),
+            QueryNodeKind::Numeric {
+                field,
+                min,
+                max,
+                inclusive_min,
+                inclusive_max,
+            } => self.numeric(
+                field,
+                &NumericRange {
+                    min: *min,
+                    max: *max,
+                    min_inclusive: *inclusive_min,
+                    max_inclusive: *inclusive_max,
+                },
+            )?,
+            QueryNodeKind::IntegerNumeric { field, min, max } => self.numeric(
+                field,
+                &IntegerRange {
+                    min: *min,
+                    max: *max,
+                }
+                .to_numeric(),
+            )?,
+            QueryNodeKind::NumericUnion { field, ranges } => self.numeric_union(field, ranges)?,
+            QueryNodeKind::Not => {
+                let excluded = match node.children.first() {
+                    Some(child) => self.eval(child)?.unwrap_or_default(),
+                    None => Vec::new(),
+                };
+                self.complement(&excluded)
+            }
+            // An optional node only affects the scores.
+            QueryNodeKind::Optional => return Ok(None),
+            QueryNodeKind::Prefix {
+                term,
+                prefix: true,
+                suffix: false,
+            } => {
+                let term = term.to_lowercase();
+                let lists = self
+                    .postings
+                    .terms
+                    .range(term.clone()..)
+                    .take_while(|(t, _)| t.starts_with(&term))
+                    .map(|(_, docs)| self.in_fields(docs, &node.opts.fields))
+                    .collect::<Result<_, _>>()?;
+                union(lists)
+            }
+            QueryNodeKind::Prefix { .. } => {
+                return Err(EmbeddedError::Unsupported("Suffix and infix queries"));
+            }
+            QueryNodeKind::Ids(keys) => {
+                let mut ids: Vec<DocId> = keys
+                    .iter()
+                    .filter_map(|key| self.keys.get(key).map(|(id, _)| *id))
+                    .collect();
+                ids.sort_unstable();
+                ids.dedup();
+                ids
+            }
+            QueryNodeKind::Wildcard => self.all.to_vec(),
+            QueryNodeKind::Tag { field } => {
+                let (_, FieldIndex::Tag(index)) = self.field(field)? else {
+                    return Err(EmbeddedError::BadField(field.clone()));
+                };
+                let mut lists = Vec::new();
+                for child in &node.children {
+                    let QueryNodeKind::Token { term } = &child.kind else {
+                        return Err(EmbeddedError::Unsupported(
+                            "Tag expressions other than values",
+                        ));
+                    };
+                    for tag in self.postings.tags.tokenize(term.as_bytes()) {
+                        lists.push(index.docs(&tag).to_vec());
+                    }
+                }
+                union(lists)
+            }
+            QueryNodeKind::Null => Vec::new(),
+            QueryNodeKind::Missing { field } => {
+                let (position, _) = self.field(field)?;
+                let present = &self.postings.present[position];
+                self.all
+                    .iter()
+                    .copied()
+                    .filter(|id| !present.contains(id))
+                    .collect()
+            }
+            QueryNodeKind::ParamNumeric { .. } => {
+                return Err(EmbeddedError::Unsupported("Unresolved parameters"));
+            }
+            QueryNodeKind::Fuzzy { .. } => {
+                return Err(EmbeddedError::Unsupported("Fuzzy queries"));
+            }
+            QueryNodeKind::LexRange { .. } => {
+                return Err(EmbeddedError::Unsupported("Lexical ranges"));
+            }
+            QueryNodeKind::WildcardQuery { .. } => {
+                return Err(EmbeddedError::Unsupported("Wildcard patterns"));
+            }
+        };
+        Ok(Some(hits))
+    }
+
+    /// The hits of the children which restrict the results.
+    fn eval_children(&self, node: &QueryNode) -> Result<Vec<Vec<DocId>>, EmbeddedError> {
+        let mut children = Vec::with_capacity(node.children.len());
+        for child in &node.children {
+            if let Some(hits) = self.eval(child)? {
+                children.push(hits);
+            }
+        }
+        Ok(children)
+    }
+
+    fn field(&self, name: &str) -> Result<(usize, &FieldIndex), EmbeddedError> {
+        self.spec
+            .fields()
+            .iter()
+            .position(|f| f.name == name)
+            .map(|position| (position, &self.postings.fields[position]))
+            .ok_or_else(|| EmbeddedError::BadField(name.to_owned()))
+    }
+
+    /// The documents of a term, analyzed as the query analyzer of `fields`
+    /// does: the term matches if any of its tokens, such as its stem, does.
+    fn term(&self, term: &str, fields: &FieldSelector) -> Result<Hits, EmbeddedError> {
+        let analyzer = self
+            .spec
+            .field_analyzers()
+            .for_query(fields)
+            .build(self.resources)?;
+        let tokens = analyzer.analyze(term);
+        if tokens.is_empty() {
+            return Ok(None);
+        }
+        let lists = tokens
+            .iter()
+            .filter_map(|token| self.postings.terms.get(&token.term))
+            .map(|docs| self.in_fields(docs, fields))
+            .collect::<Result<_, _>>()?;
+        Ok(Some(union(lists)))
+    }
+
+    /// The documents of a term, restricted to `fields`.
+    fn in_fields(
+        &self,
+        docs: &BTreeMap<DocId, Mask>,
+        fields: &FieldSelector,
+    ) -> Result<Vec<DocId>, EmbeddedError> {
+        let FieldSelector::Named(names) = fields else {
+            return Ok(docs.keys().copied().collect());
+        };
+        let mut selected = Mask::empty_for(self.postings.mask_width);
+        for name in names {
+            selected.insert(self.field(name)?.0);
+        }
+        Ok(docs
+            .iter()
+            .filter(|(_, mask)| mask.intersects(&selected))
+            .map(|(id, _)| *id)
+            .collect())
+    }
+
+    fn numeric(&self, field: &str, range: &NumericRange) -> Result<Vec<DocId>, EmbeddedError> {
+        let (_, FieldIndex::Numeric(entries)) = self.field(field)? else {
+            return Err(EmbeddedError::BadField(field.to_owned()));
+        };
+        let by_value = |entry: &(f64, DocId), target: &f64| entry.0.total_cmp(target);
+        let first = if range.min_inclusive {
+            bsearch_ge(entries, &range.min, by_value)
+        } else {
+            bsearch_gt(entries, &range.min, by_value)
+        };
+        let last = if range.max_inclusive {
+            bsearch_le(entries, &range.max, by_value)
+        } else {
+            bsearch_lt(entries, &range.max, by_value)
+        };
+        let (Some(first), Some(last)) = (first, last) else {
+            return Ok(Vec::new());
+        };
+        let matching = entries.get(first..=last).unwrap_or_default();
+        let mut ids: Vec<DocId> = matching.iter().map(|e| e.1).collect();
+        ids.sort_unstable();
+        Ok(ids)
+    }
+
+    /// The documents in any of the disjoint `ranges`, whose bounds are all
+    /// searched in a single batch.
+    fn numeric_union(
+        &self,
+        field: &str,
+        ranges: &[NumericRange],
+    ) -> Result<Vec<DocId>, EmbeddedError> {
+        let (_, FieldIndex::Numeric(entries)) = self.field(field)? else {
+            return Err(EmbeddedError::BadField(field.to_owned()));
+        };
+        // Each bound is a value, and whether the entries equal to it are
+        // before it.
+        let bounds: Vec<(f64, bool)> = ranges
+            .iter()
+            .flat_map(|r| [(r.min, !r.min_inclusive), (r.max, r.max_inclusive)])
+            .collect();
+        let by_bound = |entry: &(f64, DocId), &(value, after_equal): &(f64, bool)| match entry
+            .0
+            .total_cmp(&value)
+        {
+            Ordering::Equal if after_equal => Ordering::Less,
+            ordering => ordering,
+        };
+        let positions = bsearch_ge_batch(entries, &bounds, by_bound);
+        let mut ids: Vec<DocId> = positions
+            .chunks_exact(2)
+            .flat_map(|bounds| {
+                let start = bounds[0].unwrap_or(entries.len());
+                let end = bounds[1].unwrap_or(entries.len());
+                &entries[start..end.max(start)]
+            })
+            .map(|e| e.1)
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        Ok(ids)
+    }
+
+    fn complement(&self, excluded: &[DocId]) -> Vec<DocId> {
+        self.all
+            .iter()
+            .copied()
+            .filter(|id| excluded.binary_search(id).is_err())
+            .collect()
+    }
+}
+
+/// Orders numeric entries by value, then by document.
+fn cmp_entry(a: &(f64, DocId), b: &(f64, DocId)) -> Ordering {
+    a.0.total_cmp(&b.0).then(a.1.cmp(&b.1))
+}
+
+fn union(lists: Vec<Vec<DocId>>) -> Vec<DocId> {
+    Union::new(lists.into_iter().map(Vec::into_iter).collect()).collect()
+}
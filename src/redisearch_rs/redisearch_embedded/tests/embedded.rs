@@ -0,0 +1,217 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use index_spec::{FieldType, IndexSpec, SchemaField};
+use query::{FieldSelector, QueryNode, QueryNodeKind, numeric::NumericRange};
+use redisearch_embedded::{BasicResources, Document, EmbeddedError, SearchEngine, SearchOptions};
+
+fn engine() -> SearchEngine {
+    let mut spec = IndexSpec::new("idx");
+    for (name, field_type) in [
+        ("title", FieldType::Text),
+        ("body", FieldType::Text),
+        ("price", FieldType::Numeric),
+        ("tags", FieldType::Tag),
+    ] {
+        spec.add_field(SchemaField::new(name, field_type)).unwrap();
+    }
+    let mut engine = SearchEngine::new(spec, Box::new(BasicResources)).unwrap();
+    for (key, title, body, price, tags) in [
+        ("doc:1", "Red shoes", "for running", "10", "sport,Red"),
+        ("doc:2", "Blue shoes", "the classic", "25.5", "classic"),
+        ("doc:3", "Red hat", "running in the rain", "40", "sport"),
+    ] {
+        engine
+            .add(
+                key,
+                doc(&[
+                    ("title", title),
+                    ("body", body),
+                    ("price", price),
+                    ("tags", tags),
+                ]),
+            )
+            .unwrap();
+    }
+    engine
+}
+
+fn doc(fields: &[(&str, &str)]) -> Document {
+    fields
+        .iter()
+        .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+        .collect()
+}
+
+fn keys(engine: &SearchEngine, query: &QueryNode) -> Vec<String> {
+    engine.search(query, SearchOptions::default()).unwrap().keys
+}
+
+#[test]
+fn test_terms() {
+    let engine = engine();
+    assert_eq!(keys(&engine, &QueryNode::token("Red")), ["doc:1", "doc:3"]);
+    assert_eq!(
+        keys(
+            &engine,
+            &QueryNode::intersect(vec![QueryNode::token("red"), QueryNode::token("running")])
+        ),
+        ["doc:1", "doc:3"]
+    );
+    assert_eq!(
+        keys(
+            &engine,
+            &QueryNode::union(vec![QueryNode::token("blue"), QueryNode::token("hat")])
+        ),
+        ["doc:2", "doc:3"]
+    );
+    assert_eq!(
+        keys(&engine, &QueryNode::negate(QueryNode::token("red"))),
+        ["doc:2"]
+    );
+
+    // Stopwords don't restrict the results.
+    assert_eq!(
+        keys(
+            &engine,
+            &QueryNode::intersect(vec![QueryNode::token("the"), QueryNode::token("shoes")])
+        ),
+        ["doc:1", "doc:2"]
+    );
+
+    let mut in_body = QueryNode::token("red");
+    in_body.opts.fields = FieldSelector::Named(vec!["body".to_owned()]);
+    assert!(keys(&engine, &in_body).is_empty());
+
+    let prefix = QueryNode::new(QueryNodeKind::Prefix {
+        term: "sho".to_owned(),
+        prefix: true,
+        suffix: false,
+    });
+    assert_eq!(keys(&engine, &prefix), ["doc:1", "doc:2"]);
+}
+
+#[test]
+fn test_numeric_and_tags() {
+    let engine = engine();
+    assert_eq!(
+        keys(&engine, &QueryNode::numeric("price", 10.0, 25.5)),
+        ["doc:1", "doc:2"]
+    );
+    let exclusive = QueryNode::new(QueryNodeKind::Numeric {
+        field: "price".to_owned(),
+        min: 10.0,
+        max: 40.0,
+        inclusive_min: false,
+        inclusive_max: false,
+    });
+    assert_eq!(keys(&engine, &exclusive), ["doc:2"]);
+
+    let union = QueryNode::new(QueryNodeKind::NumericUnion {
+        field: "price".to_owned(),
+        ranges: vec![
+            NumericRange::parse("(0 10").unwrap(),
+            NumericRange::parse("(25.5 +inf").unwrap(),
+        ],
+    });
+    assert_eq!(keys(&engine, &union), ["doc:1", "doc:3"]);
+
+    assert_eq!(
+        keys(&engine, &QueryNode::tag("tags", ["SPORT"])),
+        ["doc:1", "doc:3"]
+    );
+    assert_eq!(
+        keys(
+            &engine,
+            &QueryNode::tag("tags", ["red", "classic"]).and(QueryNode::numeric("price", 0.0, 30.0))
+        ),
+        ["doc:1", "doc:2"]
+    );
+    assert_eq!(
+        engine.search(
+            &QueryNode::numeric("title", 0.0, 1.0),
+            SearchOptions::default()
+        ),
+        Err(EmbeddedError::BadField("title".to_owned()))
+    );
+}
+
+#[test]
+fn test_update_and_delete() {
+    let mut engine = engine();
+    engine
+        .add("doc:1", doc(&[("title", "green boots"), ("price", "99")]))
+        .unwrap();
+    assert_eq!(engine.len(), 3);
+    assert_eq!(keys(&engine, &QueryNode::token("red")), ["doc:3"]);
+    // The new version is the most recent document.
+    assert_eq!(
+        keys(&engine, &QueryNode::new(QueryNodeKind::Wildcard)),
+        ["doc:2", "doc:3", "doc:1"]
+    );
+    assert_eq!(
+        keys(&engine, &QueryNode::numeric("price", 50.0, 100.0)),
+        ["doc:1"]
+    );
+    assert_eq!(
+        keys(
+            &engine,
+            &QueryNode::new(QueryNodeKind::Missing {
+                field: "tags".to_owned()
+            })
+        ),
+        ["doc:1"]
+    );
+
+    assert!(engine.delete("doc:3"));
+    assert!(!engine.delete("doc:3"));
+    assert!(keys(&engine, &QueryNode::token("red")).is_empty());
+    assert_eq!(engine.get("doc:1").unwrap()["title"], "green boots");
+
+    let err = engine.add("doc:2", doc(&[("price", "cheap")])).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid numeric value `cheap` for field price"
+    );
+    assert!(engine.get("doc:2").is_none());
+}
+
+#[test]
+fn test_paging_and_unsupported() {
+    let engine = engine();
+    let results = engine
+        .search(
+            &QueryNode::new(QueryNodeKind::Wildcard),
+            SearchOptions {
+                offset: 1,
+                limit: 1,
+            },
+        )
+        .unwrap();
+    assert_eq!(results.total, 3);
+    assert_eq!(results.keys, ["doc:2"]);
+
+    let ids = QueryNode::new(QueryNodeKind::Ids(vec![
+        "doc:3".to_owned(),
+        "nope".to_owned(),
+    ]));
+    assert_eq!(keys(&engine, &ids), ["doc:3"]);
+
+    let exact = QueryNode::with_children(
+        QueryNodeKind::Phrase { exact: true },
+        vec![QueryNode::token("red"), QueryNode::token("shoes")],
+    );
+    assert_eq!(
+        engine
+            .search(&exact, SearchOptions::default())
+            .unwrap_err()
+            .to_string(),
+        "Exact phrases are not supported"
+    );
+}
@@ -0,0 +1,345 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Strong and weak references to shared objects such as index specs, the Rust
+//! port of `util/references.h`.
+//!
+//! A [`StrongRef`] keeps the object alive, a [`WeakRef`] only allows
+//! [promoting](WeakRef::promote) to a strong reference while the object is
+//! alive. On top of what [`Arc`] offers, an object can be
+//! [invalidated](StrongRef::invalidate), e.g. when its index is dropped: no new
+//! strong reference can be obtained from then on, while the existing ones
+//! keep it alive until they're released.
+//!
+//! In debug builds, every strong reference records where it was created, so
+//! that leaked references can be listed with [`StrongRef::info`]
+//! (`FT.DEBUG SPECREFS`). Release builds skip the bookkeeping, which takes a
+//! lock per reference.
+//!
+//! References can be turned into raw pointers and back, for C code to hold
+//! them. A weak reference is the pointer to the shared allocation. So is a
+//! strong reference in release builds, so that cloning or releasing one from C
+//! is a reference count operation. Debug builds box strong references along
+//! with their id.
+
+#[cfg(debug_assertions)]
+use std::{collections::BTreeMap, sync::Mutex, sync::atomic::AtomicU64};
+use std::{
+    fmt,
+    mem::ManuallyDrop,
+    panic::Location,
+    sync::{
+        Arc, Weak,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+struct Inner<T> {
+    value: T,
+    invalid: AtomicBool,
+    #[cfg(debug_assertions)]
+    next_ref_id: AtomicU64,
+    /// The live strong references, by id.
+    #[cfg(debug_assertions)]
+    live: Mutex<BTreeMap<u64, Site>>,
+}
+
+impl<T> Inner<T> {
+    fn track(self: &Arc<Self>, site: Site) -> StrongRef<T> {
+        #[cfg(debug_assertions)]
+        let id = {
+            let id = self.next_ref_id.fetch_add(1, Ordering::Relaxed);
+            self.live
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(id, site);
+            id
+        };
+        #[cfg(not(debug_assertions))]
+        let _ = site;
+        StrongRef {
+            inner: Arc::clone(self),
+            #[cfg(debug_assertions)]
+            id,
+        }
+    }
+}
+
+/// A strong reference, keeping the object alive.
+pub struct StrongRef<T> {
+    inner: Arc<Inner<T>>,
+    /// Identifies this reference in the live references of the object.
+    #[cfg(debug_assertions)]
+    id: u64,
+}
+
+/// A weak reference, which doesn't keep the object alive.
+pub struct WeakRef<T> {
+    inner: Weak<Inner<T>>,
+}
+
+/// Where a strong reference was created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Site {
+    pub file: &'static str,
+    pub line: u32,
+}
+
+impl Site {
+    /// The location of the caller.
+    #[track_caller]
+    pub fn caller() -> Self {
+        Location::caller().into()
+    }
+}
+
+impl From<&'static Location<'static>> for Site {
+    fn from(location: &'static Location<'static>) -> Self {
+        Self {
+            file: location.file(),
+            line: location.line(),
+        }
+    }
+}
+
+/// A live strong reference, as reported by [`StrongRef::info`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefSite {
+    pub id: u64,
+    pub site: Site,
+}
+
+/// The state of the references to an object, for `FT.DEBUG SPECREFS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefInfo {
+    pub strong: usize,
+    pub weak: usize,
+    pub invalid: bool,
+    /// The live strong references, oldest first. Always empty in release
+    /// builds.
+    pub live: Vec<RefSite>,
+}
+
+impl<T> StrongRef<T> {
+    /// Create the first strong reference to `value`.
+    #[track_caller]
+    pub fn new(value: T) -> Self {
+        Self::new_at(value, Site::caller())
+    }
+
+    /// Like [`Self::new`], for a reference created at `site`, e.g. by C code.
+    pub fn new_at(value: T, site: Site) -> Self {
+        Arc::new(Inner {
+            value,
+            invalid: AtomicBool::new(false),
+            #[cfg(debug_assertions)]
+            next_ref_id: AtomicU64::new(0),
+            #[cfg(debug_assertions)]
+            live: Mutex::default(),
+        })
+        .track(site)
+    }
+
+    /// The referenced object.
+    pub fn get(&self) -> &T {
+        &self.inner.value
+    }
+
+    /// Another strong reference to the object, or `None` if it's been
+    /// invalidated.
+    #[track_caller]
+    pub fn try_clone(&self) -> Option<Self> {
+        self.try_clone_at(Site::caller())
+    }
+
+    /// Like [`Self::try_clone`], for a reference created at `site`.
+    pub fn try_clone_at(&self, site: Site) -> Option<Self> {
+        (!self.is_invalid()).then(|| self.inner.track(site))
+    }
+
+    /// A weak reference to the object.
+    pub fn demote(&self) -> WeakRef<T> {
+        WeakRef {
+            inner: Arc::downgrade(&self.inner),
+        }
+    }
+
+    /// Prevent new strong references from being created. The existing ones
+    /// remain usable.
+    pub fn invalidate(&self) {
+        self.inner.invalid.store(true, Ordering::Release);
+    }
+
+    /// Whether the object has been invalidated.
+    pub fn is_invalid(&self) -> bool {
+        self.inner.invalid.load(Ordering::Acquire)
+    }
+
+    /// Whether both references point to the same object (`StrongRef_Equals`).
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Turn the reference into a raw pointer, to be turned back with
+    /// [`Self::from_raw`].
+    pub fn into_raw(self) -> *const () {
+        #[cfg(debug_assertions)]
+        return Box::into_raw(Box::new(self)).cast_const().cast();
+        #[cfg(not(debug_assertions))]
+        return Arc::into_raw(self.inner).cast();
+    }
+
+    /// Take back the reference turned into `ptr` by [`Self::into_raw`].
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by [`Self::into_raw`], with the same `T`,
+    /// and not have been taken back already.
+    pub unsafe fn from_raw(ptr: *const ()) -> Self {
+        #[cfg(debug_assertions)]
+        // SAFETY: Ensured by the caller.
+        return *unsafe { Box::from_raw(ptr.cast::<Self>().cast_mut()) };
+        #[cfg(not(debug_assertions))]
+        return Self {
+            // SAFETY: Ensured by the caller.
+            inner: unsafe { Arc::from_raw(ptr.cast()) },
+        };
+    }
+
+    /// Use the reference turned into `ptr` by [`Self::into_raw`], without
+    /// taking it back.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by [`Self::into_raw`], with the same `T`,
+    /// and not be taken back while the returned reference is in use.
+    // Only const in debug builds, where the reference is read from its box.
+    #[allow(clippy::missing_const_for_fn)]
+    pub unsafe fn borrow_raw(ptr: *const ()) -> ManuallyDrop<Self> {
+        #[cfg(debug_assertions)]
+        // SAFETY: Ensured by the caller. The copy isn't dropped.
+        return ManuallyDrop::new(unsafe { ptr.cast::<Self>().read() });
+        #[cfg(not(debug_assertions))]
+        // SAFETY: Ensured by the caller. The reference isn't dropped.
+        return ManuallyDrop::new(unsafe { Self::from_raw(ptr) });
+    }
+
+    /// The state of the references to the object.
+    pub fn info(&self) -> RefInfo {
+        #[cfg(debug_assertions)]
+        let live = self
+            .inner
+            .live
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(&id, &site)| RefSite { id, site })
+            .collect();
+        #[cfg(not(debug_assertions))]
+        let live = Vec::new();
+        RefInfo {
+            strong: Arc::strong_count(&self.inner),
+            weak: Arc::weak_count(&self.inner),
+            invalid: self.is_invalid(),
+            live,
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+impl<T> Drop for StrongRef<T> {
+    fn drop(&mut self) {
+        self.inner
+            .live
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.id);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for StrongRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("StrongRef");
+        #[cfg(debug_assertions)]
+        debug.field("id", &self.id);
+        debug.field("value", &self.inner.value).finish()
+    }
+}
+
+impl<T> WeakRef<T> {
+    /// A strong reference to the object, or `None` if it's been freed or
+    /// invalidated.
+    #[track_caller]
+    pub fn promote(&self) -> Option<StrongRef<T>> {
+        self.promote_at(Site::caller())
+    }
+
+    /// Like [`Self::promote`], for a reference created at `site`.
+    pub fn promote_at(&self, site: Site) -> Option<StrongRef<T>> {
+        let inner = self.inner.upgrade()?;
+        if inner.invalid.load(Ordering::Acquire) {
+            return None;
+        }
+        Some(inner.track(site))
+    }
+
+    /// Whether the object is still alive and valid.
+    pub fn is_valid(&self) -> bool {
+        self.inner
+            .upgrade()
+            .is_some_and(|inner| !inner.invalid.load(Ordering::Acquire))
+    }
+
+    /// Turn the reference into a raw pointer, to be turned back with
+    /// [`Self::from_raw`].
+    pub fn into_raw(self) -> *const () {
+        Weak::into_raw(self.inner).cast()
+    }
+
+    /// Take back the reference turned into `ptr` by [`Self::into_raw`].
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by [`Self::into_raw`], with the same `T`,
+    /// and not have been taken back already.
+    pub unsafe fn from_raw(ptr: *const ()) -> Self {
+        Self {
+            // SAFETY: Ensured by the caller.
+            inner: unsafe { Weak::from_raw(ptr.cast()) },
+        }
+    }
+
+    /// Use the reference turned into `ptr` by [`Self::into_raw`], without
+    /// taking it back.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by [`Self::into_raw`], with the same `T`,
+    /// and not be taken back while the returned reference is in use.
+    pub unsafe fn borrow_raw(ptr: *const ()) -> ManuallyDrop<Self> {
+        // SAFETY: Ensured by the caller. The reference isn't dropped.
+        ManuallyDrop::new(unsafe { Self::from_raw(ptr) })
+    }
+}
+
+impl<T> Clone for WeakRef<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Weak::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> fmt::Debug for WeakRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakRef")
+            .field("valid", &self.is_valid())
+            .finish()
+    }
+}
@@ -0,0 +1,174 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{
+    sync::{
+        Arc, Barrier,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use references::{Site, StrongRef, WeakRef};
+
+/// Counts how many times it's dropped.
+struct Spec(Arc<AtomicUsize>);
+
+impl Drop for Spec {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_freed_with_last_strong_ref() {
+    let drops = Arc::new(AtomicUsize::new(0));
+    let strong = StrongRef::new(Spec(Arc::clone(&drops)));
+    let weak = strong.demote();
+    let other = weak.promote().unwrap();
+    assert!(strong.ptr_eq(&other));
+
+    drop(strong);
+    assert_eq!(drops.load(Ordering::SeqCst), 0);
+    drop(other);
+    assert_eq!(drops.load(Ordering::SeqCst), 1);
+    assert!(weak.promote().is_none());
+    assert!(!weak.is_valid());
+}
+
+#[test]
+fn test_invalidate() {
+    let strong = StrongRef::new(42);
+    let weak = strong.demote();
+    let other = strong.try_clone().unwrap();
+    strong.invalidate();
+
+    assert!(weak.promote().is_none());
+    assert!(strong.try_clone().is_none());
+    // Existing references stay usable.
+    assert_eq!(*other.get(), 42);
+    assert!(other.is_invalid());
+}
+
+#[test]
+fn test_info_counts_refs() {
+    let strong = StrongRef::new("idx");
+    let weak = strong.demote();
+    let other = weak.promote().unwrap();
+
+    let info = strong.info();
+    assert_eq!(info.strong, 2);
+    assert_eq!(info.weak, 1);
+    assert!(!info.invalid);
+
+    drop(other);
+    strong.invalidate();
+    let info = strong.info();
+    assert_eq!(info.strong, 1);
+    assert!(info.invalid);
+}
+
+#[test]
+fn test_raw_round_trip() {
+    let strong = StrongRef::new("idx");
+    let raw_strong = strong.try_clone().unwrap().into_raw();
+    let raw_weak = strong.demote().into_raw();
+
+    // SAFETY: `raw_strong` is a live `StrongRef<&str>`.
+    let borrowed = unsafe { StrongRef::<&str>::borrow_raw(raw_strong) };
+    assert!(borrowed.ptr_eq(&strong));
+    // SAFETY: `raw_weak` is a live `WeakRef<&str>`.
+    let promoted = unsafe { WeakRef::<&str>::borrow_raw(raw_weak) }
+        .promote()
+        .unwrap();
+    assert_eq!(*promoted.get(), "idx");
+    assert_eq!(strong.info().strong, 3);
+    drop(promoted);
+
+    // SAFETY: `raw_strong` is taken back once.
+    drop(unsafe { StrongRef::<&str>::from_raw(raw_strong) });
+    // SAFETY: `raw_weak` is taken back once.
+    drop(unsafe { WeakRef::<&str>::from_raw(raw_weak) });
+    let info = strong.info();
+    assert_eq!((info.strong, info.weak), (1, 0));
+}
+
+#[cfg(debug_assertions)]
+#[test]
+fn test_info_lists_live_refs() {
+    let strong = StrongRef::new("idx");
+    let weak = strong.demote();
+    let leaked = weak.promote().unwrap();
+    let line = line!() - 1;
+    let from_c = weak
+        .promote_at(Site {
+            file: "spec.c",
+            line: 42,
+        })
+        .unwrap();
+
+    let info = strong.info();
+    assert_eq!(info.live.len(), 3);
+    assert_eq!(info.live[1].site.line, line);
+    assert!(info.live[1].site.file.ends_with("references.rs"));
+    assert_eq!(
+        info.live[2].site,
+        Site {
+            file: "spec.c",
+            line: 42
+        }
+    );
+
+    drop(leaked);
+    drop(from_c);
+    let info = strong.info();
+    assert_eq!(info.live.len(), 1);
+    assert_eq!(info.live[0].id, 0);
+}
+
+#[cfg(not(debug_assertions))]
+#[test]
+fn test_info_skips_live_refs() {
+    let strong = StrongRef::new("idx");
+    let _other = strong.try_clone_at(Site::caller()).unwrap();
+    assert!(strong.info().live.is_empty());
+}
+
+#[test]
+fn test_concurrent_promote_and_drop() {
+    const THREADS: usize = 8;
+    let drops = Arc::new(AtomicUsize::new(0));
+    for _ in 0..50 {
+        let strong = StrongRef::new(Spec(Arc::clone(&drops)));
+        let weak = strong.demote();
+        let barrier = Arc::new(Barrier::new(THREADS + 1));
+        let handles: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let weak = weak.clone();
+                let barrier = Arc::clone(&barrier);
+                thread::spawn(move || {
+                    barrier.wait();
+                    for _ in 0..100 {
+                        if let Some(strong) = weak.promote() {
+                            let _ = strong.get();
+                        }
+                    }
+                })
+            })
+            .collect();
+        barrier.wait();
+        strong.invalidate();
+        drop(strong);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert!(weak.promote().is_none());
+    }
+    assert_eq!(drops.load(Ordering::SeqCst), 50);
+}
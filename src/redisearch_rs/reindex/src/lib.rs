@@ -0,0 +1,228 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Rebuilding an index in the background, without a query outage.
+//!
+//! Changing the stopwords or the stemming of an index changes how every
+//! document is analyzed. Instead of dropping and recreating the index, a
+//! [`ReindexJob`] builds shadow structures with the new schema, by scanning
+//! the keyspace on a background thread while queries keep reading the live
+//! index. Documents written during the scan are recorded, and re-indexed
+//! into the shadow structures right before they are swapped in, under the
+//! write lock, so no update is lost.
+//!
+//! Dropping a job without [finishing](ReindexJob::finish) it aborts the
+//! rebuild, and leaves the live index untouched.
+//!
+//! This crate only provides the rebuild and the swap of a Rust index. The C
+//! `IndexSpec` can't be rebuilt this way yet: that needs a copy of the spec's
+//! schema, rules, stopwords and aliases to scan into, and a swap of the spec
+//! in the global dictionaries. The `FT.REINDEX` command and its `FT.INFO`
+//! fields will come with that port.
+
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+    sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    time::{Duration, Instant},
+};
+
+/// Errors returned when starting a rebuild.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReindexError {
+    /// A rebuild of the index is already in progress.
+    AlreadyRunning,
+}
+
+impl Display for ReindexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadyRunning => f.write_str("Index is already being reindexed"),
+        }
+    }
+}
+
+impl std::error::Error for ReindexError {}
+
+struct Rebuild {
+    scanned: u64,
+    total: u64,
+    started: Instant,
+    /// The keys written since the rebuild started.
+    dirty: BTreeSet<String>,
+}
+
+/// The progress of a rebuild.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReindexProgress {
+    /// The documents indexed into the shadow structures so far.
+    pub scanned: u64,
+    /// The documents in the keyspace when the rebuild started.
+    pub total: u64,
+    /// The documents written since the rebuild started, which will be
+    /// re-indexed before the swap.
+    pub pending_replays: usize,
+    pub elapsed: Duration,
+}
+
+impl ReindexProgress {
+    /// The ratio of the documents scanned, between 0 and 1.
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            (self.scanned as f64 / self.total as f64).min(1.0)
+        }
+    }
+}
+
+/// An index which can be rebuilt in the background. See the
+/// [crate documentation](crate).
+pub struct Reindexable<T> {
+    live: RwLock<T>,
+    rebuild: Mutex<Option<Rebuild>>,
+}
+
+impl<T> Reindexable<T> {
+    pub const fn new(index: T) -> Self {
+        Self {
+            live: RwLock::new(index),
+            rebuild: Mutex::new(None),
+        }
+    }
+
+    /// Lock the live index for reading.
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.live.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Lock the live index to write the document `key`. If a rebuild is in
+    /// progress, the document is re-indexed into the shadow structures before
+    /// they are swapped in.
+    pub fn write(&self, key: &str) -> RwLockWriteGuard<'_, T> {
+        let live = self.write_live();
+        if let Some(rebuild) = self.lock_rebuild().as_mut() {
+            rebuild.dirty.insert(key.to_owned());
+        }
+        live
+    }
+
+    /// Start rebuilding the index into `shadow`, with `total` documents to
+    /// scan.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReindexError::AlreadyRunning`] if the index is already being
+    /// rebuilt.
+    pub fn begin(self: &Arc<Self>, total: u64, shadow: T) -> Result<ReindexJob<T>, ReindexError> {
+        let mut rebuild = self.lock_rebuild();
+        if rebuild.is_some() {
+            return Err(ReindexError::AlreadyRunning);
+        }
+        *rebuild = Some(Rebuild {
+            scanned: 0,
+            total,
+            started: Instant::now(),
+            dirty: BTreeSet::new(),
+        });
+        Ok(ReindexJob {
+            owner: Arc::clone(self),
+            shadow: Some(shadow),
+        })
+    }
+
+    /// The progress of the rebuild, if one is in progress.
+    pub fn progress(&self) -> Option<ReindexProgress> {
+        self.lock_rebuild().as_ref().map(|rebuild| ReindexProgress {
+            scanned: rebuild.scanned,
+            total: rebuild.total,
+            pending_replays: rebuild.dirty.len(),
+            elapsed: rebuild.started.elapsed(),
+        })
+    }
+
+    /// The progress as the fields `FT.INFO` is to report, once an index can
+    /// be rebuilt from the C code.
+    pub fn info(&self) -> [(&'static str, f64); 3] {
+        let progress = self.progress();
+        [
+            ("reindexing", f64::from(u8::from(progress.is_some()))),
+            (
+                "reindex_percent",
+                progress.as_ref().map_or(1.0, ReindexProgress::percent),
+            ),
+            (
+                "reindex_pending_replays",
+                progress.map_or(0, |p| p.pending_replays) as f64,
+            ),
+        ]
+    }
+
+    fn write_live(&self) -> RwLockWriteGuard<'_, T> {
+        self.live.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_rebuild(&self) -> MutexGuard<'_, Option<Rebuild>> {
+        self.rebuild.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A rebuild in progress, returned by [`Reindexable::begin`].
+pub struct ReindexJob<T> {
+    owner: Arc<Reindexable<T>>,
+    /// Only `None` once the job is finished.
+    shadow: Option<T>,
+}
+
+impl<T> ReindexJob<T> {
+    /// The shadow structures, to index the scanned documents into.
+    pub const fn shadow_mut(&mut self) -> &mut T {
+        self.shadow.as_mut().expect("the job is not finished")
+    }
+
+    /// Report that `count` more documents were indexed into the shadow
+    /// structures.
+    pub fn advance(&self, count: u64) {
+        if let Some(rebuild) = self.owner.lock_rebuild().as_mut() {
+            rebuild.scanned += count;
+        }
+    }
+
+    /// Finish the rebuild: re-index the documents written during the scan
+    /// with `replay`, then swap the shadow structures in. Both happen under
+    /// the write lock of the index, so queries see either the old or the new
+    /// index, never a mix.
+    ///
+    /// Returns the replaced index, to be freed by the caller, possibly on
+    /// another thread.
+    pub fn finish(mut self, mut replay: impl FnMut(&mut T, &str)) -> T {
+        let mut shadow = self.shadow.take().expect("the job is not finished");
+        let mut live = self.owner.write_live();
+        let dirty = self
+            .owner
+            .lock_rebuild()
+            .take()
+            .map(|rebuild| rebuild.dirty)
+            .unwrap_or_default();
+        for key in &dirty {
+            replay(&mut shadow, key);
+        }
+        std::mem::swap(&mut *live, &mut shadow);
+        shadow
+    }
+}
+
+impl<T> Drop for ReindexJob<T> {
+    fn drop(&mut self) {
+        if self.shadow.is_some() {
+            // Aborted.
+            self.owner.lock_rebuild().take();
+        }
+    }
+}
@@ -0,0 +1,150 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{collections::BTreeMap, sync::Arc, thread};
+
+use reindex::{ReindexError, Reindexable};
+
+/// A toy index from terms to keys, with an optional stopword.
+#[derive(Debug, Default, PartialEq)]
+struct Index {
+    stopword: Option<&'static str>,
+    terms: BTreeMap<String, Vec<String>>,
+}
+
+impl Index {
+    fn with_stopword(stopword: &'static str) -> Self {
+        Self {
+            stopword: Some(stopword),
+            ..Default::default()
+        }
+    }
+
+    fn add(&mut self, key: &str, text: &str) {
+        for term in text.split(' ') {
+            if Some(term) != self.stopword {
+                self.terms
+                    .entry(term.to_owned())
+                    .or_default()
+                    .push(key.to_owned());
+            }
+        }
+    }
+
+    fn keys(&self, term: &str) -> &[String] {
+        self.terms.get(term).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[test]
+fn test_rebuild_and_swap() {
+    let mut keyspace = BTreeMap::from([("a", "the cat"), ("b", "the dog")]);
+    let mut live = Index::default();
+    for (key, text) in &keyspace {
+        live.add(key, text);
+    }
+    let index = Arc::new(Reindexable::new(live));
+    assert_eq!(index.read().keys("the"), ["a", "b"]);
+
+    let mut job = index
+        .begin(keyspace.len() as u64, Index::with_stopword("the"))
+        .unwrap();
+    assert_eq!(
+        index.begin(0, Index::default()).err(),
+        Some(ReindexError::AlreadyRunning)
+    );
+
+    // Scan the first document.
+    job.shadow_mut().add("a", keyspace["a"]);
+    job.advance(1);
+    assert_eq!(index.progress().unwrap().percent(), 0.5);
+
+    // A write during the scan goes to the live index, and is recorded.
+    keyspace.insert("a", "the bird");
+    index.write("a").add("a", "the bird");
+    assert_eq!(index.read().keys("bird"), ["a"]);
+    assert_eq!(
+        index.info(),
+        [
+            ("reindexing", 1.0),
+            ("reindex_percent", 0.5),
+            ("reindex_pending_replays", 1.0)
+        ]
+    );
+
+    job.shadow_mut().add("b", keyspace["b"]);
+    job.advance(1);
+    let old = job.finish(|shadow, key| {
+        shadow
+            .terms
+            .values_mut()
+            .for_each(|keys| keys.retain(|k| k != key));
+        shadow.add(key, keyspace[key]);
+    });
+
+    assert_eq!(old.keys("the"), ["a", "b", "a"]);
+    let live = index.read();
+    assert!(live.keys("the").is_empty());
+    assert_eq!(live.keys("bird"), ["a"]);
+    assert!(live.keys("cat").is_empty());
+    assert_eq!(live.keys("dog"), ["b"]);
+    drop(live);
+
+    assert!(index.progress().is_none());
+    assert_eq!(index.info()[0], ("reindexing", 0.0));
+}
+
+#[test]
+fn test_abort() {
+    let index = Arc::new(Reindexable::new(Index::default()));
+    index.write("a").add("a", "the cat");
+    let mut job = index.begin(1, Index::with_stopword("the")).unwrap();
+    job.shadow_mut().add("a", "the cat");
+    drop(job);
+
+    assert!(index.progress().is_none());
+    assert_eq!(index.read().keys("the"), ["a"]);
+    // A new rebuild can start.
+    assert!(index.begin(0, Index::default()).is_ok());
+}
+
+#[test]
+fn test_background_rebuild() {
+    let index = Arc::new(Reindexable::new(Index::default()));
+    let keys: Vec<String> = (0..100).map(|i| format!("doc{i}")).collect();
+    for key in &keys {
+        index.write(key).add(key, "the text");
+    }
+
+    let job = index
+        .begin(keys.len() as u64, Index::with_stopword("the"))
+        .unwrap();
+    let worker = {
+        let keys = keys.clone();
+        thread::spawn(move || {
+            let mut job = job;
+            for key in &keys {
+                job.shadow_mut().add(key, "the text");
+                job.advance(1);
+            }
+            job
+        })
+    };
+    // Queries keep being served from the live index during the rebuild.
+    while !worker.is_finished() {
+        assert_eq!(index.read().keys("the").len(), 100);
+    }
+    let job = worker.join().unwrap();
+    assert_eq!(index.progress().unwrap().percent(), 1.0);
+    job.finish(|_, _| unreachable!("no document was written"));
+
+    let live = index.read();
+    assert!(live.keys("the").is_empty());
+    assert_eq!(live.keys("text"), keys);
+}
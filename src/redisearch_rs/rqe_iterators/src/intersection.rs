@@ -0,0 +1,236 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Intersection iterator implementation
+
+use std::ptr;
+
+use ::intersection::{IntersectionStats, Strategy};
+use ffi::t_docId;
+use inverted_index::{RSIndexResult, ResultMetrics_Reset_func};
+
+use crate::{RQEIterator, RQEIteratorError, RQEValidateStatus, SkipToOutcome};
+
+/// An iterator yielding the documents found in all of its children, as for
+/// the query `foo bar`.
+///
+/// The children are led by the one with the fewest estimated results. Every
+/// other child follows the leader with the [`Strategy`] that is the cheapest
+/// for their relative sizes: by reading its results one by one when they're
+/// about as many, or by skipping to the ids of the leader when it has many
+/// more.
+pub struct Intersection<'index, I> {
+    /// The children, sorted by their estimated number of results.
+    children: Vec<I>,
+    /// How each child is advanced to a target id.
+    strategies: Vec<Strategy>,
+    /// The result each child is at, borrowed by `result`.
+    current: Vec<*const RSIndexResult<'index>>,
+    result: RSIndexResult<'index>,
+    last_doc_id: t_docId,
+    at_eof: bool,
+    stats: IntersectionStats,
+}
+
+impl<'index, I: RQEIterator<'index>> Intersection<'index, I> {
+    /// Creates a new intersection iterator over `children`.
+    pub fn new(mut children: Vec<I>) -> Self {
+        children.sort_by_key(|child| child.num_estimated());
+        let leader_len = children.first().map_or(0, |child| child.num_estimated());
+        let strategies: Vec<_> = children
+            .iter()
+            .enumerate()
+            .map(|(i, child)| match i {
+                // The leader is the one being followed.
+                0 => Strategy::Merge,
+                _ => Strategy::choose_sequential(leader_len, child.num_estimated()),
+            })
+            .collect();
+        let mut stats = IntersectionStats::default();
+        for strategy in strategies.iter().skip(1) {
+            match strategy {
+                Strategy::Gallop => stats.gallop += 1,
+                Strategy::Merge => stats.merge += 1,
+                Strategy::Bitmap => stats.bitmap += 1,
+            }
+        }
+
+        Self {
+            current: vec![ptr::null(); children.len()],
+            result: RSIndexResult::intersect(children.len()),
+            at_eof: children.is_empty(),
+            children,
+            strategies,
+            last_doc_id: 0,
+            stats,
+        }
+    }
+
+    /// The number of children following the leader with each strategy.
+    pub const fn stats(&self) -> IntersectionStats {
+        self.stats
+    }
+
+    /// Advance the child `i` to its first result with an id `>= target`,
+    /// returning that id, or `None` if the child is depleted.
+    fn advance(&mut self, i: usize, target: t_docId) -> Result<Option<t_docId>, RQEIteratorError> {
+        let child = &mut self.children[i];
+        if child.last_doc_id() >= target {
+            return Ok(Some(child.last_doc_id()));
+        }
+        let result: Option<*const RSIndexResult<'index>> = match self.strategies[i] {
+            Strategy::Gallop => match child.skip_to(target)? {
+                Some(SkipToOutcome::Found(result) | SkipToOutcome::NotFound(result)) => {
+                    Some(ptr::from_ref(result))
+                }
+                None => None,
+            },
+            // Bitmaps aren't used to follow the leader: read ids one by one.
+            Strategy::Merge | Strategy::Bitmap => loop {
+                match child.read()? {
+                    Some(result) if result.doc_id < target => {}
+                    Some(result) => break Some(ptr::from_ref(result)),
+                    None => break None,
+                }
+            },
+        };
+        let Some(result) = result else {
+            self.at_eof = true;
+            return Ok(None);
+        };
+        self.current[i] = result;
+        Ok(Some(self.children[i].last_doc_id()))
+    }
+
+    /// Advance every child to the first id `>= target` they all have, leapfrogging
+    /// from one child to the next until they agree, and aggregate their results.
+    fn agree(&mut self, target: t_docId) -> Result<Option<t_docId>, RQEIteratorError> {
+        if self.at_eof {
+            return Ok(None);
+        }
+        let mut candidate = target;
+        let mut agreeing = 0;
+        for i in (0..self.children.len()).cycle() {
+            let Some(id) = self.advance(i, candidate)? else {
+                return Ok(None);
+            };
+            if id == candidate {
+                agreeing += 1;
+            } else {
+                candidate = id;
+                agreeing = 1;
+            }
+            if agreeing == self.children.len() {
+                break;
+            }
+        }
+
+        self.reset_result();
+        for &child in &self.current {
+            // SAFETY: `child` points to the result the child is at. The child
+            // keeps it in place until it's advanced again, which only happens
+            // after `self.result` is reset.
+            self.result.push_borrowed(unsafe { &*child });
+        }
+        self.last_doc_id = candidate;
+        // A depleted child can't agree on any further id.
+        self.at_eof = self.children.iter().any(|child| child.at_eof());
+        Ok(Some(candidate))
+    }
+
+    fn reset_result(&mut self) {
+        self.result.doc_id = 0;
+        self.result.freq = 0;
+        self.result.field_mask = 0;
+        if let Some(aggregate) = self.result.as_aggregate_mut() {
+            aggregate.reset();
+        }
+        // SAFETY: the metrics of `self.result` are either NULL or were
+        // concatenated from the ones of the children.
+        unsafe { ResultMetrics_Reset_func(&mut self.result) };
+    }
+}
+
+impl<'index, I: RQEIterator<'index>> RQEIterator<'index> for Intersection<'index, I> {
+    fn read(&mut self) -> Result<Option<&mut RSIndexResult<'index>>, RQEIteratorError> {
+        Ok(self.agree(self.last_doc_id + 1)?.map(|_| &mut self.result))
+    }
+
+    fn skip_to(
+        &mut self,
+        doc_id: t_docId,
+    ) -> Result<Option<SkipToOutcome<'_, 'index>>, RQEIteratorError> {
+        debug_assert!(self.last_doc_id < doc_id);
+        Ok(self.agree(doc_id)?.map(|id| {
+            if id == doc_id {
+                SkipToOutcome::Found(&mut self.result)
+            } else {
+                SkipToOutcome::NotFound(&mut self.result)
+            }
+        }))
+    }
+
+    fn revalidate(&mut self) -> Result<RQEValidateStatus<'_, 'index>, RQEIteratorError> {
+        let mut moved = false;
+        for i in 0..self.children.len() {
+            match self.children[i].revalidate()? {
+                RQEValidateStatus::Ok => {}
+                RQEValidateStatus::Aborted => return Ok(RQEValidateStatus::Aborted),
+                RQEValidateStatus::Moved { current } => {
+                    moved = true;
+                    match current {
+                        Some(result) => self.current[i] = ptr::from_ref(result),
+                        None => self.at_eof = true,
+                    }
+                }
+            }
+        }
+        if !moved {
+            return Ok(RQEValidateStatus::Ok);
+        }
+
+        // The moved children are past the current result: agree on the
+        // furthest of them.
+        let target = self
+            .children
+            .iter()
+            .map(|child| child.last_doc_id())
+            .max()
+            .unwrap_or(0);
+        let current = match self.agree(target)? {
+            Some(_) => Some(&mut self.result),
+            None => None,
+        };
+        Ok(RQEValidateStatus::Moved { current })
+    }
+
+    fn rewind(&mut self) {
+        for child in &mut self.children {
+            child.rewind();
+        }
+        self.current.fill(ptr::null());
+        self.reset_result();
+        self.last_doc_id = 0;
+        self.at_eof = self.children.is_empty();
+    }
+
+    fn num_estimated(&self) -> usize {
+        self.children
+            .first()
+            .map_or(0, |child| child.num_estimated())
+    }
+
+    fn last_doc_id(&self) -> t_docId {
+        self.last_doc_id
+    }
+
+    fn at_eof(&self) -> bool {
+        self.at_eof
+    }
+}
@@ -14,8 +14,10 @@ use ::inverted_index::RSIndexResult;
 
 pub mod empty;
 pub mod id_list;
+pub mod intersection;
 pub mod inverted_index;
 pub mod metric;
+pub mod union;
 pub mod wildcard;
 
 #[derive(Debug, PartialEq)]
@@ -0,0 +1,221 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Union iterator implementation
+
+use std::ptr;
+
+use ffi::t_docId;
+use inverted_index::{RSIndexResult, ResultMetrics_Reset_func};
+use loser_tree::Tournament;
+
+use crate::{RQEIterator, RQEIteratorError, RQEValidateStatus, SkipToOutcome};
+
+/// An iterator yielding the documents found in any of its children, as for
+/// the query `foo|bar` or the expansions of a prefix.
+///
+/// The children are merged with a loser tree rather than a heap: replacing
+/// the smallest id takes one comparison per level of the tree, which matters
+/// for unions with hundreds of children.
+pub struct Union<'index, I> {
+    children: Vec<I>,
+    /// The tournament between the last ids of the children, `None` for the
+    /// depleted ones.
+    tournament: Tournament,
+    /// Whether the children were advanced to their first result.
+    started: bool,
+    /// The result each child is at, borrowed by `result`.
+    current: Vec<*const RSIndexResult<'index>>,
+    /// The children at the current result.
+    matched: Vec<usize>,
+    result: RSIndexResult<'index>,
+    last_doc_id: t_docId,
+    at_eof: bool,
+}
+
+impl<'index, I: RQEIterator<'index>> Union<'index, I> {
+    /// Creates a new union iterator over `children`.
+    pub fn new(children: Vec<I>) -> Self {
+        Self {
+            tournament: Tournament::new(Vec::new()),
+            started: false,
+            current: vec![ptr::null(); children.len()],
+            matched: Vec::new(),
+            result: RSIndexResult::union(children.len()),
+            last_doc_id: 0,
+            at_eof: children.is_empty(),
+            children,
+        }
+    }
+
+    /// Advance the child `i` to its first result with an id `>= target`,
+    /// or to its next result if `target` is `None`. Returns its id, or `None`
+    /// if the child is depleted.
+    fn advance(
+        &mut self,
+        i: usize,
+        target: Option<t_docId>,
+    ) -> Result<Option<t_docId>, RQEIteratorError> {
+        let child = &mut self.children[i];
+        let result = match target {
+            Some(target) => match child.skip_to(target)? {
+                Some(SkipToOutcome::Found(result) | SkipToOutcome::NotFound(result)) => {
+                    Some(ptr::from_ref(result))
+                }
+                None => None,
+            },
+            None => child.read()?.map(|result| ptr::from_ref(result)),
+        };
+        self.current[i] = result.unwrap_or(ptr::null());
+        Ok(result.map(|_| self.children[i].last_doc_id()))
+    }
+
+    /// Advance the children behind `target`, or the ones at the current
+    /// result if `target` is `None`, then aggregate the results of the
+    /// children at the smallest id.
+    fn next(&mut self, target: Option<t_docId>) -> Result<Option<t_docId>, RQEIteratorError> {
+        if self.at_eof {
+            return Ok(None);
+        }
+        if !self.started {
+            let mut heads = Vec::with_capacity(self.children.len());
+            for i in 0..self.children.len() {
+                heads.push(self.advance(i, target)?);
+            }
+            self.tournament.reset(heads);
+            self.started = true;
+        } else {
+            // Only the children at the current result, or behind the target,
+            // are advanced: each of them wins the tournament in turn.
+            let behind = target.unwrap_or(self.last_doc_id + 1);
+            while let Some((id, winner)) = self.tournament.peek()
+                && id < behind
+            {
+                let head = self.advance(winner, target)?;
+                self.tournament.replace_winner(head);
+            }
+        }
+        self.set_result()
+    }
+
+    /// Aggregate the results of the children at the smallest id.
+    fn set_result(&mut self) -> Result<Option<t_docId>, RQEIteratorError> {
+        self.reset_result();
+        let Some((id, _)) = self.tournament.peek() else {
+            self.at_eof = true;
+            return Ok(None);
+        };
+        self.matched.clear();
+        self.tournament.tied(&mut self.matched);
+        for &child in &self.matched {
+            // SAFETY: `current[child]` points to the result the child is at.
+            // The child keeps it in place until it's advanced again, which
+            // only happens after `self.result` is reset.
+            self.result.push_borrowed(unsafe { &*self.current[child] });
+        }
+        self.last_doc_id = id;
+        Ok(Some(id))
+    }
+
+    fn reset_result(&mut self) {
+        self.result.doc_id = 0;
+        self.result.freq = 0;
+        self.result.field_mask = 0;
+        if let Some(aggregate) = self.result.as_aggregate_mut() {
+            aggregate.reset();
+        }
+        // SAFETY: the metrics of `self.result` are either NULL or were
+        // concatenated from the ones of the children.
+        unsafe { ResultMetrics_Reset_func(&mut self.result) };
+    }
+}
+
+impl<'index, I: RQEIterator<'index>> RQEIterator<'index> for Union<'index, I> {
+    fn read(&mut self) -> Result<Option<&mut RSIndexResult<'index>>, RQEIteratorError> {
+        Ok(self.next(None)?.map(|_| &mut self.result))
+    }
+
+    fn skip_to(
+        &mut self,
+        doc_id: t_docId,
+    ) -> Result<Option<SkipToOutcome<'_, 'index>>, RQEIteratorError> {
+        debug_assert!(self.last_doc_id < doc_id);
+        Ok(self.next(Some(doc_id))?.map(|id| {
+            if id == doc_id {
+                SkipToOutcome::Found(&mut self.result)
+            } else {
+                SkipToOutcome::NotFound(&mut self.result)
+            }
+        }))
+    }
+
+    fn revalidate(&mut self) -> Result<RQEValidateStatus<'_, 'index>, RQEIteratorError> {
+        if !self.started {
+            return Ok(RQEValidateStatus::Ok);
+        }
+        let mut changed = false;
+        for i in 0..self.children.len() {
+            match self.children[i].revalidate()? {
+                RQEValidateStatus::Ok => {}
+                // The union goes on without the children that are gone.
+                RQEValidateStatus::Aborted => {
+                    changed = true;
+                    self.current[i] = ptr::null();
+                }
+                RQEValidateStatus::Moved { current } => {
+                    changed = true;
+                    self.current[i] = current.map_or(ptr::null(), |result| ptr::from_ref(result));
+                }
+            }
+        }
+        if !changed {
+            return Ok(RQEValidateStatus::Ok);
+        }
+
+        let heads = (0..self.children.len())
+            .map(|i| (!self.current[i].is_null()).then(|| self.children[i].last_doc_id()));
+        self.tournament.reset(heads.collect::<Vec<_>>());
+        let previous = self.last_doc_id;
+        let current = match self.set_result()? {
+            Some(_) => Some(&mut self.result),
+            None => None,
+        };
+        if self.last_doc_id == previous && current.is_some() {
+            return Ok(RQEValidateStatus::Ok);
+        }
+        Ok(RQEValidateStatus::Moved { current })
+    }
+
+    fn rewind(&mut self) {
+        for child in &mut self.children {
+            child.rewind();
+        }
+        self.current.fill(ptr::null());
+        self.matched.clear();
+        self.reset_result();
+        self.started = false;
+        self.last_doc_id = 0;
+        self.at_eof = self.children.is_empty();
+    }
+
+    fn num_estimated(&self) -> usize {
+        self.children
+            .iter()
+            .map(|child| child.num_estimated())
+            .sum()
+    }
+
+    fn last_doc_id(&self) -> t_docId {
+        self.last_doc_id
+    }
+
+    fn at_eof(&self) -> bool {
+        self.at_eof
+    }
+}
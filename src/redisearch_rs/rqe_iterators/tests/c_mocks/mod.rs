@@ -61,6 +61,14 @@ pub extern "C" fn ResultMetrics_Reset_func(result: *mut ffi::RSIndexResult) {
     }
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn RSYieldableMetric_Concat(
+    _parent: *mut *mut ffi::RSYieldableMetric,
+    _child: *const ffi::RSYieldableMetric,
+) {
+    // Do nothing since the code will call this
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn IndexResult_ConcatMetrics(
     _parent: *mut RSIndexResult,
@@ -0,0 +1,121 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::collections::BTreeSet;
+
+use intersection::Strategy;
+use rqe_iterators::{RQEIterator, SkipToOutcome, id_list::IdList, intersection::Intersection};
+
+mod c_mocks;
+
+fn intersection(lists: &[&[u64]]) -> Intersection<'static, IdList<'static>> {
+    Intersection::new(lists.iter().map(|ids| IdList::new(ids.to_vec())).collect())
+}
+
+fn naive(lists: &[&[u64]]) -> Vec<u64> {
+    let mut sets = lists
+        .iter()
+        .map(|l| l.iter().copied().collect::<BTreeSet<_>>());
+    let first = sets.next().unwrap_or_default();
+    sets.fold(first, |acc, s| &acc & &s).into_iter().collect()
+}
+
+fn read_all(it: &mut Intersection<'static, IdList<'static>>) -> Vec<u64> {
+    let mut ids = Vec::new();
+    while let Some(result) = it.read().unwrap() {
+        ids.push(result.doc_id);
+    }
+    ids
+}
+
+#[test]
+fn read() {
+    let lists: &[&[u64]] = &[&[1, 2, 3, 5, 8, 13, 21], &[2, 3, 5, 7, 11, 13, 17, 19, 21]];
+    let mut it = intersection(lists);
+    assert_eq!(it.num_estimated(), 7);
+
+    let result = it.read().unwrap().unwrap();
+    assert_eq!(result.doc_id, 2);
+    let children = result.as_aggregate().unwrap();
+    assert_eq!(children.len(), 2);
+    assert!((0..2).all(|i| children.get(i).unwrap().doc_id == 2));
+    assert_eq!(it.last_doc_id(), 2);
+
+    assert_eq!(read_all(&mut it), [3, 5, 13, 21]);
+    assert!(it.at_eof());
+    assert!(matches!(it.read(), Ok(None)));
+
+    it.rewind();
+    assert!(!it.at_eof());
+    assert_eq!(read_all(&mut it), naive(lists));
+}
+
+#[test]
+fn skip_to() {
+    let lists: &[&[u64]] = &[&[2, 4, 6, 8, 10, 12], &[3, 6, 9, 12, 15]];
+    let mut it = intersection(lists);
+
+    let Ok(Some(SkipToOutcome::Found(result))) = it.skip_to(6) else {
+        panic!("expected to find 6");
+    };
+    assert_eq!(result.doc_id, 6);
+    let Ok(Some(SkipToOutcome::NotFound(result))) = it.skip_to(7) else {
+        panic!("expected to land past 7");
+    };
+    assert_eq!(result.doc_id, 12);
+    assert_eq!(it.last_doc_id(), 12);
+    assert!(it.at_eof());
+    assert!(matches!(it.skip_to(13), Ok(None)));
+}
+
+#[test]
+fn followers_pick_their_strategy() {
+    let tiny: Vec<u64> = (1..=10).map(|i| i * 1000).collect();
+    let huge: Vec<u64> = (1..=10_000).collect();
+    let similar: Vec<u64> = (1..=15).map(|i| i * 500).collect();
+    let lists: &[&[u64]] = &[&huge, &similar, &tiny];
+    let mut it = intersection(lists);
+
+    // The tiny list leads, gallops over the huge one and merges with the
+    // similar one.
+    assert_eq!(it.num_estimated(), tiny.len());
+    let stats = it.stats();
+    assert_eq!((stats.gallop, stats.merge, stats.bitmap), (1, 1, 0));
+    assert_eq!(
+        Strategy::choose_sequential(tiny.len(), huge.len()),
+        Strategy::Gallop
+    );
+    assert_eq!(read_all(&mut it), naive(lists));
+}
+
+#[test]
+fn matches_naive() {
+    let lists: Vec<Vec<u64>> = vec![
+        (1..2000).step_by(2).collect(),
+        (1..2000).step_by(3).collect(),
+        (1..2000).step_by(7).collect(),
+        vec![21, 42, 63, 105, 210, 1000, 1050, 1995],
+    ];
+    for n in 1..=lists.len() {
+        let lists: Vec<&[u64]> = lists[..n].iter().map(Vec::as_slice).collect();
+        let mut it = intersection(&lists);
+        assert_eq!(read_all(&mut it), naive(&lists), "{n} lists");
+    }
+}
+
+#[test]
+fn no_common_ids() {
+    let mut it = intersection(&[&[1, 3, 5], &[2, 4, 6]]);
+    assert!(matches!(it.read(), Ok(None)));
+    assert!(it.at_eof());
+
+    let mut it = Intersection::<IdList>::new(Vec::new());
+    assert!(it.at_eof());
+    assert!(matches!(it.read(), Ok(None)));
+}
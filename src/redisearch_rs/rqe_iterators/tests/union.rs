@@ -0,0 +1,104 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::collections::BTreeSet;
+
+use rqe_iterators::{RQEIterator, SkipToOutcome, id_list::IdList, union::Union};
+
+mod c_mocks;
+
+fn union(lists: &[&[u64]]) -> Union<'static, IdList<'static>> {
+    Union::new(lists.iter().map(|ids| IdList::new(ids.to_vec())).collect())
+}
+
+fn naive(lists: &[&[u64]]) -> Vec<u64> {
+    let ids: BTreeSet<u64> = lists.iter().flat_map(|l| l.iter().copied()).collect();
+    ids.into_iter().collect()
+}
+
+/// The ids read, with the number of children matching each.
+fn read_all(it: &mut Union<'static, IdList<'static>>) -> Vec<(u64, usize)> {
+    let mut ids = Vec::new();
+    while let Some(result) = it.read().unwrap() {
+        ids.push((result.doc_id, result.as_aggregate().unwrap().len()));
+    }
+    ids
+}
+
+#[test]
+fn read() {
+    let lists: &[&[u64]] = &[&[1, 4, 6], &[4, 5], &[2, 4, 6]];
+    let mut it = union(lists);
+    assert_eq!(it.num_estimated(), 8);
+
+    assert_eq!(read_all(&mut it), [(1, 1), (2, 1), (4, 3), (5, 1), (6, 2)]);
+    assert!(it.at_eof());
+    assert!(matches!(it.read(), Ok(None)));
+
+    it.rewind();
+    assert!(!it.at_eof());
+    let result = it.read().unwrap().unwrap();
+    assert_eq!(result.doc_id, 1);
+    assert_eq!(it.last_doc_id(), 1);
+}
+
+#[test]
+fn skip_to() {
+    let lists: &[&[u64]] = &[&[2, 4, 6, 8, 10, 12], &[3, 6, 9, 12, 15]];
+    let mut it = union(lists);
+
+    let Ok(Some(SkipToOutcome::Found(result))) = it.skip_to(6) else {
+        panic!("expected to find 6");
+    };
+    assert_eq!(result.doc_id, 6);
+    assert_eq!(result.as_aggregate().unwrap().len(), 2);
+    let Ok(Some(SkipToOutcome::NotFound(result))) = it.skip_to(7) else {
+        panic!("expected to land past 7");
+    };
+    assert_eq!(result.doc_id, 8);
+    assert_eq!(it.read().unwrap().unwrap().doc_id, 9);
+    let Ok(Some(SkipToOutcome::Found(result))) = it.skip_to(15) else {
+        panic!("expected to find 15");
+    };
+    assert_eq!(result.doc_id, 15);
+    assert!(matches!(it.skip_to(16), Ok(None)));
+    assert!(it.at_eof());
+
+    // Skipping first, before anything was read.
+    it.rewind();
+    let Ok(Some(SkipToOutcome::NotFound(result))) = it.skip_to(11) else {
+        panic!("expected to land past 11");
+    };
+    assert_eq!(result.doc_id, 12);
+}
+
+#[test]
+fn many_children() {
+    // Children `i` has the multiples of `i + 2`, many of them sharing ids.
+    let lists: Vec<Vec<u64>> = (0..200u64)
+        .map(|i| (1..3000).filter(|id| id % (i + 2) == 0).collect())
+        .collect();
+    let lists: Vec<&[u64]> = lists.iter().map(Vec::as_slice).collect();
+    let mut it = union(&lists);
+    let read = read_all(&mut it);
+    let ids: Vec<u64> = read.iter().map(|&(id, _)| id).collect();
+    assert_eq!(ids, naive(&lists));
+    for (id, matched) in read {
+        let expected = lists.iter().filter(|l| l.contains(&id)).count();
+        assert_eq!(matched, expected, "doc {id}");
+    }
+}
+
+#[test]
+fn no_children() {
+    let mut it = Union::<IdList>::new(Vec::new());
+    assert!(it.at_eof());
+    assert!(matches!(it.read(), Ok(None)));
+    assert_eq!(it.num_estimated(), 0);
+}
@@ -0,0 +1,196 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The standard BM25 scorer, `BM25STD`, ported from `ext/default.c` along with
+//! its explanation, and an optional proximity bonus.
+//!
+//! BM25 only looks at the frequency of each term, so a document where the
+//! query terms are scattered ranks as high as one where they form the exact
+//! phrase. With a [proximity weight](Bm25Params::proximity_weight), the score
+//! of a multi-term match is multiplied by `1 + weight * (n - 1) / span`, where
+//! `span` is the [minimal span](min_span) of the `n` terms in the document:
+//! adjacent terms get the full bonus, which decreases as they spread apart.
+
+use crate::{
+    Explanation,
+    tfidf::{DocStats, Match},
+};
+
+/// The parameters of the scorer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bm25Params {
+    /// The saturation of the term frequency. As in C, the parameters are
+    /// single-precision.
+    pub k1: f32,
+    /// How much the score is normalized by the document length.
+    pub b: f32,
+    /// The weight of the proximity bonus, `0` to disable it.
+    pub proximity_weight: f64,
+}
+
+impl Default for Bm25Params {
+    fn default() -> Self {
+        Self {
+            k1: 1.2,
+            b: 0.75,
+            proximity_weight: 0.0,
+        }
+    }
+}
+
+/// The score of a document, explained if `explain` is set.
+///
+/// `avg_doc_len` is the average document length of the index. `offsets`
+/// holds the positions of each query term in the document, for the
+/// proximity bonus; it's ignored if there are fewer than two terms or the
+/// bonus is disabled.
+pub fn score(
+    matched: &Match,
+    doc: &DocStats,
+    avg_doc_len: f64,
+    params: &Bm25Params,
+    offsets: &[&[u32]],
+    explain: bool,
+) -> (f64, Option<Explanation>) {
+    let (bm25, terms) = bm25(matched, doc, avg_doc_len, params, explain);
+    let proximity = if params.proximity_weight > 0.0 && offsets.len() > 1 {
+        min_span(offsets).map(|span| {
+            let gaps = (offsets.len() - 1) as f64;
+            let bonus = params.proximity_weight * gaps / f64::from(span.max(1));
+            (span, bonus)
+        })
+    } else {
+        None
+    };
+    let bonus = proximity.map_or(0.0, |(_, bonus)| bonus);
+    let final_score = doc.score * bm25 * (1.0 + bonus);
+
+    let e = terms.map(|t| {
+        let mut description = format!(
+            "Final BM25 : words BM25 {bm25:.2} * document score {:.2}",
+            doc.score
+        );
+        let mut factors = vec![("WordsBM25", bm25), ("DocumentScore", doc.score)];
+        let mut children = vec![t];
+        if let Some((span, bonus)) = proximity {
+            let gaps = offsets.len() - 1;
+            description.push_str(&format!(" * (1 + proximity {bonus:.2})"));
+            factors.push(("Proximity", bonus));
+            children.push(
+                Explanation::new(
+                    "Proximity",
+                    bonus,
+                    format!(
+                        "(Proximity {bonus:.2} = Weight {:.2} * Gaps {gaps} / Span {span})",
+                        params.proximity_weight
+                    ),
+                )
+                .with_factor("Weight", params.proximity_weight)
+                .with_factor("Gaps", gaps as f64)
+                .with_factor("Span", f64::from(span)),
+            );
+        }
+        factors
+            .into_iter()
+            .fold(
+                Explanation::new("Normalization", final_score, description),
+                |e, (name, value)| e.with_factor(name, value),
+            )
+            .with_children(children)
+    });
+    (final_score, e)
+}
+
+/// The raw BM25 of the matches, `bm25StdRecursive` in C. The `idf` of terms
+/// is expected to be their BM25 IDF.
+fn bm25(
+    matched: &Match,
+    doc: &DocStats,
+    avg_doc_len: f64,
+    params: &Bm25Params,
+    explain: bool,
+) -> (f64, Option<Explanation>) {
+    match matched {
+        Match::Term {
+            term,
+            field,
+            weight,
+            freq,
+            idf,
+        } => {
+            let f = f64::from(*freq);
+            let (k1, b) = (f64::from(params.k1), f64::from(params.b));
+            let len_norm = 1.0 - b + b * f64::from(doc.len) / avg_doc_len;
+            let score = weight * idf * f * (k1 + 1.0) / (f + k1 * len_norm);
+            let e = explain.then(|| {
+                let description = format!(
+                    "{term}: ({score:.2} = Weight {weight:.2} * IDF {idf:.2} * (F {f:.2} * (k1 {k1:.1} + 1)) / (F {f:.2} + k1 {k1:.1} * (1 - b {b:.2} + b {b:.2} * Doc Len {} / Average Doc Len {avg_doc_len:.2})))",
+                    doc.len
+                );
+                Explanation::new("BM25", score, description)
+                    .with_term(term.clone(), field.clone())
+                    .with_factor("Weight", *weight)
+                    .with_factor("IDF", *idf)
+                    .with_factor("TF", f)
+                    .with_factor("DocLen", f64::from(doc.len))
+                    .with_factor("AvgDocLen", avg_doc_len)
+            });
+            (score, e)
+        }
+        Match::Aggregate { weight, children } => {
+            let mut total = 0.0;
+            let mut explanations = Vec::new();
+            for child in children {
+                let (score, e) = bm25(child, doc, avg_doc_len, params, explain);
+                total += score;
+                explanations.extend(e);
+            }
+            let e = explain.then(|| {
+                let description = format!("(Weight {weight:.2} * children BM25 {total:.2})");
+                Explanation::new("Aggregate", weight * total, description)
+                    .with_factor("Weight", *weight)
+                    .with_factor("ChildrenBM25", total)
+                    .with_children(explanations)
+            });
+            (weight * total, e)
+        }
+        Match::Other { .. } => {
+            let e = explain
+                .then(|| Explanation::new("Irrelevant", 0.0, "Irrelevant token -> score is 0"));
+            (0.0, e)
+        }
+    }
+}
+
+/// The length of the smallest window of positions holding a position of
+/// every list, as the distance between its first and last positions. Each
+/// list must be sorted. `None` if any list is empty.
+pub fn min_span(offsets: &[&[u32]]) -> Option<u32> {
+    if offsets.iter().any(|o| o.is_empty()) {
+        return None;
+    }
+    let mut heads = vec![0; offsets.len()];
+    let mut best = u32::MAX;
+    loop {
+        let (mut min, mut max, mut min_list) = (u32::MAX, 0, 0);
+        for (list, (&head, positions)) in heads.iter().zip(offsets).enumerate() {
+            let position = positions[head];
+            if position < min {
+                min = position;
+                min_list = list;
+            }
+            max = max.max(position);
+        }
+        best = best.min(max - min);
+        heads[min_list] += 1;
+        if heads[min_list] == offsets[min_list].len() {
+            return Some(best);
+        }
+    }
+}
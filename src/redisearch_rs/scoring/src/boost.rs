@@ -0,0 +1,128 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Boosting by a numeric field, applied at query time on top of the scorer.
+//!
+//! `BOOSTBY {field} [MISSING {value}]` multiplies the score of every document
+//! by the value of the sortable NUMERIC `field`, such as a popularity count,
+//! so that simple searches don't have to become aggregations with an `APPLY`
+//! just for boosting. The value is read from the sorting vector by the scorer
+//! stage, without loading the document.
+//!
+//! Documents without a value, or with a value which isn't a finite number,
+//! are boosted by the `MISSING` value, `1` by default so that they keep
+//! their score. Negative values boost by `0`.
+
+use std::fmt::{self, Display};
+
+use crate::Explanation;
+
+const BOOSTBY_OPT: &str = "BOOSTBY";
+const MISSING_OPT: &str = "MISSING";
+
+/// Errors returned when parsing `BOOSTBY`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoostError {
+    MissingArgument,
+    BadValue { option: &'static str, value: String },
+}
+
+impl Display for BoostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingArgument => write!(f, "Missing argument for {BOOSTBY_OPT}"),
+            Self::BadValue { option, value } => write!(f, "Invalid value for {option}: {value}"),
+        }
+    }
+}
+
+impl std::error::Error for BoostError {}
+
+/// A boost of the scores by a numeric field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldBoost {
+    pub field: String,
+    /// The boost of documents without a value.
+    pub missing: f64,
+}
+
+impl FieldBoost {
+    /// Try to handle the query option `name`, taking its arguments from
+    /// `args`.
+    ///
+    /// Returns `Ok(None)` if `name` is not `BOOSTBY`, leaving it to the caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`BoostError`] if an argument is missing or invalid.
+    pub fn try_parse_option<'a, I>(
+        name: &str,
+        args: &mut std::iter::Peekable<I>,
+    ) -> Result<Option<Self>, BoostError>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        if !name.eq_ignore_ascii_case(BOOSTBY_OPT) {
+            return Ok(None);
+        }
+        let field = args.next().ok_or(BoostError::MissingArgument)?;
+        let field = field.strip_prefix('@').unwrap_or(field).to_owned();
+        let mut missing = 1.0;
+        if args
+            .next_if(|arg| arg.eq_ignore_ascii_case(MISSING_OPT))
+            .is_some()
+        {
+            let value = args.next().ok_or(BoostError::MissingArgument)?;
+            missing = value
+                .parse()
+                .ok()
+                .filter(|m: &f64| m.is_finite() && *m >= 0.0)
+                .ok_or_else(|| BoostError::BadValue {
+                    option: MISSING_OPT,
+                    value: value.to_owned(),
+                })?;
+        }
+        Ok(Some(Self { field, missing }))
+    }
+
+    /// The factor applied to the score of a document whose sortable value
+    /// is `value`.
+    pub fn factor(&self, value: Option<f64>) -> f64 {
+        value
+            .filter(|v| v.is_finite())
+            .map_or(self.missing, |v| v.max(0.0))
+    }
+
+    /// Apply the boost to `score`, wrapping its explanation if there's one.
+    pub fn apply(
+        &self,
+        score: f64,
+        explanation: Option<Explanation>,
+        value: Option<f64>,
+    ) -> (f64, Option<Explanation>) {
+        let factor = self.factor(value);
+        let boosted = score * factor;
+        let explanation = explanation.map(|e| {
+            let source = if value.is_some_and(f64::is_finite) {
+                "value"
+            } else {
+                "missing value"
+            };
+            let description = format!(
+                "Boost {boosted:.2} = score {score:.2} * {source} {factor:.2} of @{}",
+                self.field
+            );
+            Explanation::new("Boost", boosted, description)
+                .with_factor("Score", score)
+                .with_factor("Factor", factor)
+                .with_children(vec![e])
+        });
+        (boosted, explanation)
+    }
+}
@@ -0,0 +1,199 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Recency boosting, applied at query time on top of the scorer.
+//!
+//! `DECAY {field} {EXP|GAUSS|LINEAR} {half-life} [OFFSET {duration}]`
+//! multiplies the score of every document by a factor decreasing with its
+//! age, computed from the timestamp held by `field` (in seconds since the
+//! Unix epoch, as in a `SCORE_FIELD`). Unlike writing decayed scores to the
+//! documents nightly, the boost is always up to date.
+//!
+//! The factor is `1` for documents younger than the offset, and `0.5` once
+//! they're a half-life older than that. Durations are given in seconds, or
+//! with a unit suffix: `90s`, `30m`, `12h`, `7d`.
+
+use std::fmt::{self, Display};
+
+use crate::Explanation;
+
+const DECAY_OPT: &str = "DECAY";
+const OFFSET_OPT: &str = "OFFSET";
+
+/// How the factor decreases with the age of the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecayFunction {
+    /// Halves with every half-life.
+    Exponential,
+    /// Bell-shaped: slow at first, then faster than exponential.
+    Gaussian,
+    /// Linear, reaching `0` at twice the half-life.
+    Linear,
+}
+
+impl DecayFunction {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "EXP" => Some(Self::Exponential),
+            "GAUSS" => Some(Self::Gaussian),
+            "LINEAR" => Some(Self::Linear),
+            _ => None,
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Exponential => "EXP",
+            Self::Gaussian => "GAUSS",
+            Self::Linear => "LINEAR",
+        }
+    }
+}
+
+/// Errors returned when parsing `DECAY`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecayError {
+    MissingArgument,
+    BadValue { option: &'static str, value: String },
+}
+
+impl Display for DecayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingArgument => write!(f, "Missing argument for {DECAY_OPT}"),
+            Self::BadValue { option, value } => write!(f, "Invalid value for {option}: {value}"),
+        }
+    }
+}
+
+impl std::error::Error for DecayError {}
+
+/// A decay of the scores by document age.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decay {
+    pub field: String,
+    pub function: DecayFunction,
+    /// In seconds.
+    pub half_life: f64,
+    /// In seconds.
+    pub offset: f64,
+}
+
+impl Decay {
+    /// Try to handle the query option `name`, taking its arguments from
+    /// `args`.
+    ///
+    /// Returns `Ok(None)` if `name` is not `DECAY`, leaving it to the caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`DecayError`] if an argument is missing or invalid.
+    pub fn try_parse_option<'a, I>(
+        name: &str,
+        args: &mut std::iter::Peekable<I>,
+    ) -> Result<Option<Self>, DecayError>
+    where
+        I: Iterator<Item = &'a str>,
+    {
+        if !name.eq_ignore_ascii_case(DECAY_OPT) {
+            return Ok(None);
+        }
+        let mut next = || args.next().ok_or(DecayError::MissingArgument);
+        let field = next()?;
+        let field = field.strip_prefix('@').unwrap_or(field).to_owned();
+        let function = next()?;
+        let function =
+            DecayFunction::parse(function).ok_or_else(|| bad_value(DECAY_OPT, function))?;
+        let half_life = next()?;
+        let half_life = parse_duration(half_life)
+            .filter(|&h| h > 0.0)
+            .ok_or_else(|| bad_value(DECAY_OPT, half_life))?;
+        let mut offset = 0.0;
+        if args
+            .next_if(|arg| arg.eq_ignore_ascii_case(OFFSET_OPT))
+            .is_some()
+        {
+            let value = args.next().ok_or(DecayError::MissingArgument)?;
+            offset = parse_duration(value).ok_or_else(|| bad_value(OFFSET_OPT, value))?;
+        }
+        Ok(Some(Self {
+            field,
+            function,
+            half_life,
+            offset,
+        }))
+    }
+
+    /// The factor applied to the score of a document whose timestamp is
+    /// `timestamp`, at the time `now`.
+    ///
+    /// Documents without a timestamp are treated as infinitely old, so they
+    /// don't outrank recent ones; documents from the future aren't boosted
+    /// beyond `1`.
+    pub fn factor(&self, timestamp: Option<f64>, now: f64) -> f64 {
+        let Some(timestamp) = timestamp.filter(|t| t.is_finite()) else {
+            return 0.0;
+        };
+        let age = ((now - timestamp) - self.offset).max(0.0) / self.half_life;
+        match self.function {
+            DecayFunction::Exponential => 0.5f64.powf(age),
+            DecayFunction::Gaussian => (-std::f64::consts::LN_2 * age * age).exp(),
+            DecayFunction::Linear => (1.0 - 0.5 * age).max(0.0),
+        }
+    }
+
+    /// Apply the decay to `score`, wrapping its explanation if there's one.
+    pub fn apply(
+        &self,
+        score: f64,
+        explanation: Option<Explanation>,
+        timestamp: Option<f64>,
+        now: f64,
+    ) -> (f64, Option<Explanation>) {
+        let factor = self.factor(timestamp, now);
+        let decayed = score * factor;
+        let explanation = explanation.map(|e| {
+            let description = format!(
+                "Decay {decayed:.2} = score {score:.2} * {} decay {factor:.2} of @{}",
+                self.function.name(),
+                self.field
+            );
+            let mut decay = Explanation::new("Decay", decayed, description)
+                .with_factor("Score", score)
+                .with_factor("Factor", factor);
+            if let Some(timestamp) = timestamp {
+                decay = decay.with_factor("Age", now - timestamp);
+            }
+            decay
+                .with_factor("HalfLife", self.half_life)
+                .with_children(vec![e])
+        });
+        (decayed, explanation)
+    }
+}
+
+fn bad_value(option: &'static str, value: &str) -> DecayError {
+    DecayError::BadValue {
+        option,
+        value: value.to_owned(),
+    }
+}
+
+/// Parse a duration in seconds, with an optional `s`, `m`, `h` or `d` unit.
+fn parse_duration(value: &str) -> Option<f64> {
+    let (number, unit) = match value.char_indices().last()? {
+        (i, 's') => (&value[..i], 1.0),
+        (i, 'm') => (&value[..i], 60.0),
+        (i, 'h') => (&value[..i], 3600.0),
+        (i, 'd') => (&value[..i], 86_400.0),
+        _ => (value, 1.0),
+    };
+    let number: f64 = number.parse().ok()?;
+    (number.is_finite() && number >= 0.0).then_some(number * unit)
+}
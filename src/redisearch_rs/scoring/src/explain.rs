@@ -0,0 +1,132 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Score explanations, as returned with `EXPLAINSCORE`.
+//!
+//! Scorers build a tree of [`Explanation`]s mirroring the computation of the
+//! score: one node per term contribution, aggregated by the nodes of the
+//! query operators, up to the normalization of the final score.
+//!
+//! From dialect 4, the tree is replied as nested maps, with the score, the
+//! named factors, and the term and field of every node, so that relevance
+//! tuning tools don't have to parse text. Older dialects get the legacy
+//! format of `score_explain.c`: a string per node, and nodes with children
+//! replied as `[string, [children...]]`.
+
+/// The first dialect replying explanations as maps.
+pub const STRUCTURED_EXPLAIN_DIALECT: u32 = 4;
+
+/// A node of the explanation of a score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    /// The kind of computation, e.g. `TFIDF` or `Normalization`.
+    pub kind: &'static str,
+    /// The value computed by this node.
+    pub score: f64,
+    /// The term matched, for term contributions.
+    pub term: Option<String>,
+    /// The field the term was matched in.
+    pub field: Option<String>,
+    /// The inputs of the computation, e.g. `("IDF", 1.2)`.
+    pub factors: Vec<(&'static str, f64)>,
+    /// The legacy, human-readable description.
+    pub description: String,
+    pub children: Vec<Explanation>,
+}
+
+impl Explanation {
+    pub fn new(kind: &'static str, score: f64, description: impl Into<String>) -> Self {
+        Self {
+            kind,
+            score,
+            term: None,
+            field: None,
+            factors: Vec::new(),
+            description: description.into(),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn with_factor(mut self, name: &'static str, value: f64) -> Self {
+        self.factors.push((name, value));
+        self
+    }
+
+    pub fn with_term(mut self, term: impl Into<String>, field: Option<String>) -> Self {
+        self.term = Some(term.into());
+        self.field = field;
+        self
+    }
+
+    pub fn with_children(mut self, children: Vec<Explanation>) -> Self {
+        self.children = children;
+        self
+    }
+
+    /// The reply for a query parsed with `dialect`.
+    pub fn reply(&self, dialect: u32) -> ExplainReply {
+        if dialect >= STRUCTURED_EXPLAIN_DIALECT {
+            self.structured()
+        } else {
+            self.legacy()
+        }
+    }
+
+    fn structured(&self) -> ExplainReply {
+        let mut map = vec![
+            (
+                "kind".to_owned(),
+                ExplainReply::SimpleString(self.kind.to_owned()),
+            ),
+            ("score".to_owned(), ExplainReply::Double(self.score)),
+        ];
+        if let Some(term) = &self.term {
+            map.push(("term".to_owned(), ExplainReply::SimpleString(term.clone())));
+        }
+        if let Some(field) = &self.field {
+            map.push((
+                "field".to_owned(),
+                ExplainReply::SimpleString(field.clone()),
+            ));
+        }
+        if !self.factors.is_empty() {
+            let factors = self
+                .factors
+                .iter()
+                .map(|(name, value)| ((*name).to_owned(), ExplainReply::Double(*value)))
+                .collect();
+            map.push(("factors".to_owned(), ExplainReply::Map(factors)));
+        }
+        if !self.children.is_empty() {
+            let children = self.children.iter().map(Self::structured).collect();
+            map.push(("children".to_owned(), ExplainReply::Array(children)));
+        }
+        ExplainReply::Map(map)
+    }
+
+    fn legacy(&self) -> ExplainReply {
+        let description = ExplainReply::SimpleString(self.description.clone());
+        if self.children.is_empty() {
+            description
+        } else {
+            let children = self.children.iter().map(Self::legacy).collect();
+            ExplainReply::Array(vec![description, ExplainReply::Array(children)])
+        }
+    }
+}
+
+/// An explanation, as replied to the client.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExplainReply {
+    SimpleString(String),
+    Double(f64),
+    Array(Vec<ExplainReply>),
+    /// A RESP3 map, replied as a flat array of keys and values in RESP2.
+    Map(Vec<(String, ExplainReply)>),
+}
@@ -0,0 +1,20 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Scoring functions, and the explanation of the scores they compute.
+
+pub mod bm25;
+pub mod boost;
+pub mod decay;
+pub mod explain;
+pub mod score_fields;
+pub mod tfidf;
+
+pub use explain::{ExplainReply, Explanation};
+pub use score_fields::ScoreFields;
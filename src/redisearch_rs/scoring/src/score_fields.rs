@@ -0,0 +1,88 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! `SCOREFIELDS`: the TEXT fields whose matches count towards the score.
+//!
+//! Matching is restricted by `INFIELDS` and field modifiers, which filter the
+//! records of the iterators by their field mask. `SCOREFIELDS` doesn't filter
+//! anything: a term matched only in fields outside of its mask still matches
+//! the document, but is weighted `0` by the scorers. Users can thus match on
+//! `body` while ranking on `title`, without changing the query.
+
+use std::fmt::{self, Display};
+
+use field_mask::FieldMask;
+
+use crate::tfidf::Match;
+
+/// A `SCOREFIELDS` field which isn't a TEXT field of the index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownField(pub String);
+
+impl Display for UnknownField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown TEXT field in SCOREFIELDS: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownField {}
+
+/// The mask of the fields which count towards the score.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreFields<M: FieldMask = u128> {
+    mask: M,
+}
+
+impl<M: FieldMask> ScoreFields<M> {
+    /// The mask of `fields`, `index_of` giving the position of a TEXT field
+    /// in the field masks of the index.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UnknownField`] for the first field which isn't a TEXT field.
+    pub fn new<'a>(
+        fields: impl IntoIterator<Item = &'a str>,
+        index_of: impl Fn(&str) -> Option<usize>,
+    ) -> Result<Self, UnknownField> {
+        let mut mask = M::empty();
+        for field in fields {
+            let index = index_of(field).ok_or_else(|| UnknownField(field.to_owned()))?;
+            mask.insert(index);
+        }
+        Ok(Self { mask })
+    }
+
+    /// The weight of a record matched in the fields of `record_mask`: `1` if
+    /// any of them counts towards the score, `0` otherwise.
+    pub fn weight(&self, record_mask: &M) -> f64 {
+        if self.mask.intersects(record_mask) {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Weight the term matches of `matched` by their fields. Terms matched in
+    /// an unknown field, and matches of other kinds, are kept as they are.
+    pub fn apply(&self, matched: &mut Match, index_of: &impl Fn(&str) -> Option<usize>) {
+        match matched {
+            Match::Term { field, weight, .. } => {
+                if let Some(index) = field.as_deref().and_then(index_of) {
+                    *weight *= self.weight(&M::single(index));
+                }
+            }
+            Match::Aggregate { children, .. } => {
+                for child in children {
+                    self.apply(child, index_of);
+                }
+            }
+            Match::Other { .. } => {}
+        }
+    }
+}
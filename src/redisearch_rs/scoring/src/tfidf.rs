@@ -0,0 +1,166 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The TF-IDF scorers, `TFIDF` and `TFIDF.DOCNORM`, ported from `ext/default.c`
+//! along with their explanations.
+
+use crate::Explanation;
+
+/// The matches of a document, mirroring the tree of `RSIndexResult`s.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Match {
+    /// A term matched `freq` times.
+    Term {
+        term: String,
+        field: Option<String>,
+        weight: f64,
+        freq: u32,
+        idf: f64,
+    },
+    /// An intersection or union of matches.
+    Aggregate { weight: f64, children: Vec<Match> },
+    /// Any other match, such as a numeric range or a tag.
+    Other { weight: f64, freq: u32 },
+}
+
+/// What the scorer needs to know about the document, from its metadata.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DocStats {
+    /// The a priori score of the document.
+    pub score: f64,
+    /// The frequency of its most frequent term.
+    pub max_freq: u32,
+    /// Its number of terms.
+    pub len: u32,
+}
+
+/// How the TF-IDF of a document is normalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    /// By the frequency of its most frequent term (`TFIDF`).
+    MaxFreq,
+    /// By its number of terms (`TFIDF.DOCNORM`).
+    DocLen,
+}
+
+/// The score of a document, explained if `explain` is set.
+///
+/// `slop` is the minimal distance between the matched terms, `1` for adjacent
+/// terms. Scores below `min_score` are reported as `0`.
+pub fn score(
+    matched: &Match,
+    doc: &DocStats,
+    normalization: Normalization,
+    min_score: f64,
+    slop: u32,
+    explain: bool,
+) -> (f64, Option<Explanation>) {
+    let explanation = |e: Explanation| explain.then_some(e);
+    if doc.score == 0.0 {
+        return (
+            0.0,
+            explanation(Explanation::new("Zero", 0.0, "Document score is 0")),
+        );
+    }
+    let (norm, norm_name) = match normalization {
+        Normalization::MaxFreq => (doc.max_freq, "max frequency"),
+        Normalization::DocLen => (doc.len, "length"),
+    };
+    if norm == 0 {
+        return (
+            0.0,
+            explanation(Explanation::new(
+                "Zero",
+                0.0,
+                format!("Document {norm_name} is 0"),
+            )),
+        );
+    }
+
+    let (raw, terms) = tfidf(matched, explain);
+    let tfidf = doc.score * raw / f64::from(norm);
+    if tfidf < min_score {
+        let description =
+            format!("TFIDF score of {tfidf:.2} is smaller than minimum score {min_score:.2}");
+        let e = Explanation::new("MinScore", 0.0, description)
+            .with_factor("TFIDF", tfidf)
+            .with_factor("MinScore", min_score);
+        return (0.0, terms.map(|t| e.with_children(vec![t])));
+    }
+
+    let slop = slop.max(1);
+    let final_score = tfidf / f64::from(slop);
+    let e = terms.map(|t| {
+        let description = format!(
+            "Final TFIDF : words TFIDF {raw:.2} * document score {:.2} / norm {norm} / slop {slop}",
+            doc.score
+        );
+        Explanation::new("Normalization", final_score, description)
+            .with_factor("WordsTFIDF", raw)
+            .with_factor("DocumentScore", doc.score)
+            .with_factor("Norm", f64::from(norm))
+            .with_factor("Slop", f64::from(slop))
+            .with_children(vec![t])
+    });
+    (final_score, e)
+}
+
+/// The raw TF-IDF of the matches, `tfidfRecursive` in C.
+fn tfidf(matched: &Match, explain: bool) -> (f64, Option<Explanation>) {
+    match matched {
+        Match::Term {
+            term,
+            field,
+            weight,
+            freq,
+            idf,
+        } => {
+            let score = weight * f64::from(*freq) * idf;
+            let e = explain.then(|| {
+                let description =
+                    format!("(TFIDF {score:.2} = Weight {weight:.2} * TF {freq} * IDF {idf:.2})");
+                Explanation::new("TFIDF", score, description)
+                    .with_term(term.clone(), field.clone())
+                    .with_factor("Weight", *weight)
+                    .with_factor("TF", f64::from(*freq))
+                    .with_factor("IDF", *idf)
+            });
+            (score, e)
+        }
+        Match::Aggregate { weight, children } => {
+            let mut total = 0.0;
+            let mut explanations = Vec::new();
+            for child in children {
+                let (score, e) = tfidf(child, explain);
+                total += score;
+                explanations.extend(e);
+            }
+            let score = weight * total;
+            let e = explain.then(|| {
+                let description = format!("(Weight {weight:.2} * total children TFIDF {total:.2})");
+                Explanation::new("Aggregate", score, description)
+                    .with_factor("Weight", *weight)
+                    .with_factor("ChildrenTFIDF", total)
+                    .with_children(explanations)
+            });
+            (score, e)
+        }
+        Match::Other { weight, freq } => {
+            let score = weight * f64::from(*freq);
+            let e = explain.then(|| {
+                let description =
+                    format!("(TFIDF {score:.2} = Weight {weight:.2} * Frequency {freq})");
+                Explanation::new("Frequency", score, description)
+                    .with_factor("Weight", *weight)
+                    .with_factor("Frequency", f64::from(*freq))
+            });
+            (score, e)
+        }
+    }
+}
@@ -0,0 +1,127 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use scoring::{
+    ExplainReply,
+    bm25::{Bm25Params, min_span, score},
+    tfidf::{DocStats, Match},
+};
+
+fn s(s: &str) -> ExplainReply {
+    ExplainReply::SimpleString(s.to_owned())
+}
+
+fn term(term: &str) -> Match {
+    Match::Term {
+        term: term.to_owned(),
+        field: None,
+        weight: 1.0,
+        freq: 1,
+        idf: 1.0,
+    }
+}
+
+fn matches() -> Match {
+    Match::Aggregate {
+        weight: 1.0,
+        children: vec![term("hello"), term("world")],
+    }
+}
+
+const DOC: DocStats = DocStats {
+    score: 1.0,
+    max_freq: 1,
+    len: 10,
+};
+
+#[test]
+fn test_min_span() {
+    assert_eq!(min_span(&[&[1, 20], &[2]]), Some(1));
+    assert_eq!(min_span(&[&[1, 9], &[5, 30], &[12]]), Some(7));
+    assert_eq!(min_span(&[&[4], &[4]]), Some(0));
+    assert_eq!(min_span(&[&[1], &[]]), None);
+}
+
+#[test]
+fn test_bm25() {
+    let (single, _) = score(
+        &term("hello"),
+        &DOC,
+        10.0,
+        &Bm25Params::default(),
+        &[],
+        false,
+    );
+    // With an average length document, F * (k1 + 1) / (F + k1) is 1.
+    assert!((single - 1.0).abs() < 1e-12);
+    let (both, _) = score(&matches(), &DOC, 10.0, &Bm25Params::default(), &[], false);
+    assert!((both - 2.0).abs() < 1e-12);
+    let (shorter, _) = score(&matches(), &DOC, 20.0, &Bm25Params::default(), &[], false);
+    assert!(shorter > both);
+}
+
+#[test]
+fn test_proximity_bonus() {
+    let params = Bm25Params {
+        proximity_weight: 0.5,
+        ..Default::default()
+    };
+    let phrase: [&[u32]; 2] = [&[3], &[4]];
+    let scattered: [&[u32]; 2] = [&[1], &[9]];
+    let (pure, _) = score(
+        &matches(),
+        &DOC,
+        10.0,
+        &Bm25Params::default(),
+        &phrase,
+        false,
+    );
+    let (tight, _) = score(&matches(), &DOC, 10.0, &params, &phrase, false);
+    let (loose, _) = score(&matches(), &DOC, 10.0, &params, &scattered, false);
+    assert!((tight - pure * 1.5).abs() < 1e-12);
+    assert!((loose - pure * (1.0 + 0.5 / 8.0)).abs() < 1e-12);
+
+    // A single term gets no bonus.
+    let (single, _) = score(&term("hello"), &DOC, 10.0, &params, &[&[3]], false);
+    assert!((single - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn test_explain_proximity() {
+    let params = Bm25Params {
+        proximity_weight: 0.5,
+        ..Default::default()
+    };
+    let (_, e) = score(&matches(), &DOC, 10.0, &params, &[&[3], &[4]], true);
+    let e = e.unwrap();
+    assert_eq!(e.factors[2], ("Proximity", 0.5));
+    assert_eq!(
+        e.reply(2),
+        ExplainReply::Array(vec![
+            s("Final BM25 : words BM25 2.00 * document score 1.00 * (1 + proximity 0.50)"),
+            ExplainReply::Array(vec![
+                ExplainReply::Array(vec![
+                    s("(Weight 1.00 * children BM25 2.00)"),
+                    ExplainReply::Array(vec![
+                        s(
+                            "hello: (1.00 = Weight 1.00 * IDF 1.00 * (F 1.00 * (k1 1.2 + 1)) / (F 1.00 + k1 1.2 * (1 - b 0.75 + b 0.75 * Doc Len 10 / Average Doc Len 10.00)))"
+                        ),
+                        s(
+                            "world: (1.00 = Weight 1.00 * IDF 1.00 * (F 1.00 * (k1 1.2 + 1)) / (F 1.00 + k1 1.2 * (1 - b 0.75 + b 0.75 * Doc Len 10 / Average Doc Len 10.00)))"
+                        ),
+                    ]),
+                ]),
+                s("(Proximity 0.50 = Weight 0.50 * Gaps 1 / Span 1)"),
+            ]),
+        ])
+    );
+
+    let (_, e) = score(&matches(), &DOC, 10.0, &Bm25Params::default(), &[], true);
+    assert_eq!(e.unwrap().factors.len(), 2);
+}
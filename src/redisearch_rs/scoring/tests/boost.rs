@@ -0,0 +1,83 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use scoring::{
+    Explanation,
+    boost::{BoostError, FieldBoost},
+};
+
+fn parse(args: &str) -> Result<Option<FieldBoost>, BoostError> {
+    let mut args = args.split_whitespace().peekable();
+    let name = args.next().unwrap();
+    FieldBoost::try_parse_option(name, &mut args)
+}
+
+#[test]
+fn test_parse() {
+    assert_eq!(
+        parse("boostby @popularity MISSING 0.5"),
+        Ok(Some(FieldBoost {
+            field: "popularity".to_owned(),
+            missing: 0.5,
+        }))
+    );
+    let mut args = "BOOSTBY views LIMIT 0 10".split_whitespace().peekable();
+    args.next();
+    let plain = FieldBoost::try_parse_option("BOOSTBY", &mut args)
+        .unwrap()
+        .unwrap();
+    assert_eq!(plain.missing, 1.0);
+    assert_eq!(args.next(), Some("LIMIT"));
+
+    assert_eq!(parse("DECAY ts EXP 1d"), Ok(None));
+    assert_eq!(parse("BOOSTBY"), Err(BoostError::MissingArgument));
+    assert_eq!(
+        parse("BOOSTBY views MISSING"),
+        Err(BoostError::MissingArgument)
+    );
+    assert_eq!(
+        parse("BOOSTBY views MISSING -1").unwrap_err().to_string(),
+        "Invalid value for MISSING: -1"
+    );
+    assert!(parse("BOOSTBY views MISSING nan").is_err());
+}
+
+#[test]
+fn test_factors() {
+    let boost = parse("BOOSTBY views").unwrap().unwrap();
+    assert_eq!(boost.factor(Some(3.0)), 3.0);
+    assert_eq!(boost.factor(Some(-3.0)), 0.0);
+    assert_eq!(boost.factor(None), 1.0);
+    assert_eq!(boost.factor(Some(f64::NAN)), 1.0);
+    let boost = parse("BOOSTBY views MISSING 0").unwrap().unwrap();
+    assert_eq!(boost.factor(None), 0.0);
+}
+
+#[test]
+fn test_apply_with_explanation() {
+    let boost = parse("BOOSTBY views").unwrap().unwrap();
+    assert_eq!(boost.apply(1.5, None, Some(2.0)), (3.0, None));
+
+    let base = Explanation::new("BM25", 1.5, "base");
+    let (score, explanation) = boost.apply(1.5, Some(base.clone()), Some(2.0));
+    let explanation = explanation.unwrap();
+    assert_eq!(score, 3.0);
+    assert_eq!(
+        explanation.description,
+        "Boost 3.00 = score 1.50 * value 2.00 of @views"
+    );
+    assert_eq!(explanation.factors, [("Score", 1.5), ("Factor", 2.0)]);
+    assert_eq!(explanation.children, std::slice::from_ref(&base));
+
+    let (_, explanation) = boost.apply(1.5, Some(base), None);
+    assert_eq!(
+        explanation.unwrap().description,
+        "Boost 1.50 = score 1.50 * missing value 1.00 of @views"
+    );
+}
@@ -0,0 +1,107 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use scoring::{
+    Explanation,
+    decay::{Decay, DecayError, DecayFunction},
+};
+
+const DAY: f64 = 86_400.0;
+const NOW: f64 = 1_700_000_000.0;
+
+fn parse(args: &str) -> Result<Option<Decay>, DecayError> {
+    let mut args = args.split_whitespace().peekable();
+    let name = args.next().unwrap();
+    Decay::try_parse_option(name, &mut args)
+}
+
+fn decay(function: DecayFunction) -> Decay {
+    Decay {
+        field: "published".to_owned(),
+        function,
+        half_life: 7.0 * DAY,
+        offset: DAY,
+    }
+}
+
+#[test]
+fn test_parse() {
+    assert_eq!(
+        parse("decay @published GAUSS 7d OFFSET 24h"),
+        Ok(Some(decay(DecayFunction::Gaussian)))
+    );
+    let plain = parse("DECAY ts exp 3600").unwrap().unwrap();
+    assert_eq!(plain.field, "ts");
+    assert_eq!(plain.half_life, 3600.0);
+    assert_eq!(plain.offset, 0.0);
+
+    assert_eq!(parse("LIMIT 0 10"), Ok(None));
+    assert_eq!(parse("DECAY ts EXP"), Err(DecayError::MissingArgument));
+    assert_eq!(
+        parse("DECAY ts EXP 1d OFFSET"),
+        Err(DecayError::MissingArgument)
+    );
+    assert_eq!(
+        parse("DECAY ts STEP 1d").unwrap_err().to_string(),
+        "Invalid value for DECAY: STEP"
+    );
+    assert!(parse("DECAY ts EXP 0").is_err());
+    assert!(parse("DECAY ts EXP 1w").is_err());
+    assert!(parse("DECAY ts EXP 1d OFFSET -1d").is_err());
+}
+
+#[test]
+fn test_factors() {
+    let at = |age_days: f64| Some(NOW - age_days * DAY);
+    for function in [
+        DecayFunction::Exponential,
+        DecayFunction::Gaussian,
+        DecayFunction::Linear,
+    ] {
+        let decay = decay(function);
+        // No decay within the offset, nor for documents from the future.
+        assert_eq!(decay.factor(at(0.5), NOW), 1.0);
+        assert_eq!(decay.factor(at(-3.0), NOW), 1.0);
+        // Half a point one half-life past the offset.
+        assert!((decay.factor(at(8.0), NOW) - 0.5).abs() < 1e-12);
+        assert_eq!(decay.factor(None, NOW), 0.0);
+    }
+
+    let at_two_half_lives = |function| decay(function).factor(at(15.0), NOW);
+    assert!((at_two_half_lives(DecayFunction::Exponential) - 0.25).abs() < 1e-12);
+    assert!((at_two_half_lives(DecayFunction::Gaussian) - 0.0625).abs() < 1e-12);
+    assert_eq!(at_two_half_lives(DecayFunction::Linear), 0.0);
+}
+
+#[test]
+fn test_apply_with_explanation() {
+    let decay = decay(DecayFunction::Exponential);
+    let timestamp = Some(NOW - 8.0 * DAY);
+    assert_eq!(decay.apply(3.0, None, timestamp, NOW), (1.5, None));
+
+    let base = Explanation::new("TFIDF", 3.0, "base");
+    let (score, explanation) = decay.apply(3.0, Some(base.clone()), timestamp, NOW);
+    let explanation = explanation.unwrap();
+    assert_eq!(score, 1.5);
+    assert_eq!(explanation.kind, "Decay");
+    assert_eq!(
+        explanation.description,
+        "Decay 1.50 = score 3.00 * EXP decay 0.50 of @published"
+    );
+    assert_eq!(
+        explanation.factors,
+        [
+            ("Score", 3.0),
+            ("Factor", 0.5),
+            ("Age", 8.0 * DAY),
+            ("HalfLife", 7.0 * DAY)
+        ]
+    );
+    assert_eq!(explanation.children, [base]);
+}
@@ -0,0 +1,71 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use field_mask::WideMask;
+use scoring::{
+    ScoreFields,
+    score_fields::UnknownField,
+    tfidf::{DocStats, Match, Normalization, score},
+};
+
+fn index_of(field: &str) -> Option<usize> {
+    ["title", "body"].iter().position(|f| *f == field)
+}
+
+fn term(field: &str) -> Match {
+    Match::Term {
+        term: "hello".to_owned(),
+        field: Some(field.to_owned()),
+        weight: 1.0,
+        freq: 1,
+        idf: 1.0,
+    }
+}
+
+#[test]
+fn test_weight() {
+    let fields = ScoreFields::<u128>::new(["title"], index_of).unwrap();
+    assert_eq!(fields.weight(&0b01), 1.0);
+    assert_eq!(fields.weight(&0b11), 1.0);
+    assert_eq!(fields.weight(&0b10), 0.0);
+
+    let wide = ScoreFields::<WideMask>::new(["body"], index_of).unwrap();
+    assert_eq!(wide, ScoreFields::new(["body"], index_of).unwrap());
+    assert_eq!(
+        ScoreFields::<u128>::new(["title", "price"], index_of),
+        Err(UnknownField("price".to_owned()))
+    );
+}
+
+#[test]
+fn test_matches_outside_score_fields_dont_score() {
+    let doc = DocStats {
+        score: 1.0,
+        max_freq: 1,
+        len: 2,
+    };
+    let mut matched = Match::Aggregate {
+        weight: 1.0,
+        children: vec![
+            term("title"),
+            term("body"),
+            Match::Other {
+                weight: 1.0,
+                freq: 1,
+            },
+        ],
+    };
+    let (all, _) = score(&matched, &doc, Normalization::MaxFreq, 0.0, 1, false);
+    assert_eq!(all, 3.0);
+
+    let fields = ScoreFields::<u128>::new(["title"], index_of).unwrap();
+    fields.apply(&mut matched, &index_of);
+    let (title, _) = score(&matched, &doc, Normalization::MaxFreq, 0.0, 1, false);
+    assert_eq!(title, 2.0);
+}
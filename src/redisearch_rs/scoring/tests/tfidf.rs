@@ -0,0 +1,121 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use scoring::{
+    ExplainReply, Explanation,
+    tfidf::{DocStats, Match, Normalization, score},
+};
+
+fn s(s: &str) -> ExplainReply {
+    ExplainReply::SimpleString(s.to_owned())
+}
+
+fn matches() -> Match {
+    Match::Aggregate {
+        weight: 1.0,
+        children: vec![
+            Match::Term {
+                term: "hello".to_owned(),
+                field: Some("title".to_owned()),
+                weight: 2.0,
+                freq: 3,
+                idf: 0.5,
+            },
+            Match::Other {
+                weight: 1.0,
+                freq: 1,
+            },
+        ],
+    }
+}
+
+const DOC: DocStats = DocStats {
+    score: 1.0,
+    max_freq: 2,
+    len: 10,
+};
+
+#[test]
+fn test_tfidf() {
+    let (max_freq, e) = score(&matches(), &DOC, Normalization::MaxFreq, 0.0, 1, false);
+    assert_eq!(max_freq, 2.0);
+    assert!(e.is_none());
+    let (doc_len, _) = score(&matches(), &DOC, Normalization::DocLen, 0.0, 2, false);
+    assert_eq!(doc_len, 0.2);
+    let (below_min, _) = score(&matches(), &DOC, Normalization::MaxFreq, 3.0, 1, false);
+    assert_eq!(below_min, 0.0);
+}
+
+#[test]
+fn test_legacy_reply() {
+    let (_, e) = score(&matches(), &DOC, Normalization::MaxFreq, 0.0, 1, true);
+    assert_eq!(
+        e.unwrap().reply(2),
+        ExplainReply::Array(vec![
+            s("Final TFIDF : words TFIDF 4.00 * document score 1.00 / norm 2 / slop 1"),
+            ExplainReply::Array(vec![ExplainReply::Array(vec![
+                s("(Weight 1.00 * total children TFIDF 4.00)"),
+                ExplainReply::Array(vec![
+                    s("(TFIDF 3.00 = Weight 2.00 * TF 3 * IDF 0.50)"),
+                    s("(TFIDF 1.00 = Weight 1.00 * Frequency 1)"),
+                ]),
+            ])]),
+        ])
+    );
+}
+
+#[test]
+fn test_structured_reply() {
+    let (_, e) = score(&matches(), &DOC, Normalization::MaxFreq, 0.0, 1, true);
+    let ExplainReply::Map(root) = e.unwrap().reply(4) else {
+        panic!("expected a map");
+    };
+    let keys: Vec<_> = root.iter().map(|(k, _)| k.as_str()).collect();
+    assert_eq!(keys, ["kind", "score", "factors", "children"]);
+    assert_eq!(root[1].1, ExplainReply::Double(2.0));
+    assert_eq!(
+        root[2].1,
+        ExplainReply::Map(vec![
+            ("WordsTFIDF".to_owned(), ExplainReply::Double(4.0)),
+            ("DocumentScore".to_owned(), ExplainReply::Double(1.0)),
+            ("Norm".to_owned(), ExplainReply::Double(2.0)),
+            ("Slop".to_owned(), ExplainReply::Double(1.0)),
+        ])
+    );
+
+    // The term contribution carries its term and field.
+    let term = Explanation::new("TFIDF", 3.0, "")
+        .with_term("hello", Some("title".to_owned()))
+        .with_factor("IDF", 0.5);
+    assert_eq!(
+        term.reply(4),
+        ExplainReply::Map(vec![
+            ("kind".to_owned(), s("TFIDF")),
+            ("score".to_owned(), ExplainReply::Double(3.0)),
+            ("term".to_owned(), s("hello")),
+            ("field".to_owned(), s("title")),
+            (
+                "factors".to_owned(),
+                ExplainReply::Map(vec![("IDF".to_owned(), ExplainReply::Double(0.5))])
+            ),
+        ])
+    );
+}
+
+#[test]
+fn test_zero_explanations() {
+    let zero = DocStats { score: 0.0, ..DOC };
+    let (score_, e) = score(&matches(), &zero, Normalization::MaxFreq, 0.0, 1, true);
+    assert_eq!(score_, 0.0);
+    assert_eq!(e.unwrap().reply(2), s("Document score is 0"));
+
+    let empty = DocStats { len: 0, ..DOC };
+    let (_, e) = score(&matches(), &empty, Normalization::DocLen, 0.0, 1, true);
+    assert_eq!(e.unwrap().reply(1), s("Document length is 0"));
+}
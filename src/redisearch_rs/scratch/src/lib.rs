@@ -0,0 +1,174 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Reusable thread-local buffers for the hottest loops: tokenizing, decoding
+//! varint vectors and highlighting.
+//!
+//! These run once per term, per record or per result, and used to allocate a
+//! fresh `Vec` every time. [`with_bytes`] and [`with_offsets`] lend instead
+//! a buffer owned by the current thread, empty but with the capacity left by
+//! previous uses.
+//!
+//! A single huge document would otherwise pin its buffer size for the
+//! lifetime of the thread. Every [`SHRINK_PERIOD`] uses, a buffer whose
+//! capacity is more than twice the longest length it reached during the
+//! period shrinks to that length.
+//!
+//! The buffers are lent once at a time: a nested call gets a temporary
+//! buffer instead. [`threads`] reports the memory held by the buffers of each
+//! thread, for `FT.DEBUG SCRATCH_MEMORY`.
+
+use std::{
+    cell::RefCell,
+    mem,
+    sync::{
+        Arc, Mutex, Weak,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+/// The number of uses of a buffer after which it may shrink.
+pub const SHRINK_PERIOD: u32 = 256;
+
+/// The capacity, in bytes, a buffer may keep whatever its use.
+pub const RETAINED_BYTES: usize = 4096;
+
+/// Run `f` with an empty byte buffer of the current thread.
+pub fn with_bytes<R>(f: impl FnOnce(&mut Vec<u8>) -> R) -> R {
+    SCRATCH.with(|scratch| lend(&scratch.bytes, &scratch.memory, f))
+}
+
+/// Run `f` with an empty offset buffer of the current thread.
+pub fn with_offsets<R>(f: impl FnOnce(&mut Vec<u32>) -> R) -> R {
+    SCRATCH.with(|scratch| lend(&scratch.offsets, &scratch.memory, f))
+}
+
+/// The memory held by the buffers of the current thread, in bytes.
+pub fn memory() -> usize {
+    SCRATCH.with(|scratch| scratch.memory.load(Ordering::Relaxed))
+}
+
+/// The memory held by the scratch buffers of a thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadScratch {
+    /// The name of the thread, or its ID if unnamed.
+    pub thread: String,
+    pub bytes: usize,
+}
+
+/// The memory held by the scratch buffers of every thread which used them,
+/// in the order the threads first did.
+pub fn threads() -> Vec<ThreadScratch> {
+    let mut registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    registry.retain(|(_, memory)| memory.strong_count() > 0);
+    registry
+        .iter()
+        .filter_map(|(thread, memory)| {
+            Some(ThreadScratch {
+                thread: thread.clone(),
+                bytes: memory.upgrade()?.load(Ordering::Relaxed),
+            })
+        })
+        .collect()
+}
+
+/// A buffer and its use over the current period.
+struct Buffer<T> {
+    buf: Vec<T>,
+    uses: u32,
+    /// The longest length reached during the period.
+    high_water: usize,
+}
+
+impl<T> Buffer<T> {
+    const fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            uses: 0,
+            high_water: 0,
+        }
+    }
+
+    const fn bytes(&self) -> usize {
+        self.buf.capacity() * mem::size_of::<T>()
+    }
+
+    /// Record a use which left the buffer at `len`, and shrink it at the end
+    /// of the period if it's much larger than needed.
+    fn record(&mut self, len: usize) {
+        self.buf.clear();
+        self.high_water = self.high_water.max(len);
+        self.uses += 1;
+        if self.uses < SHRINK_PERIOD {
+            return;
+        }
+        let retained = (RETAINED_BYTES / mem::size_of::<T>().max(1)).max(self.high_water);
+        if self.buf.capacity() > 2 * retained {
+            self.buf.shrink_to(retained);
+        }
+        self.uses = 0;
+        self.high_water = 0;
+    }
+}
+
+struct Scratch {
+    bytes: RefCell<Buffer<u8>>,
+    offsets: RefCell<Buffer<u32>>,
+    /// The capacity of both buffers, in bytes, shared with the registry.
+    memory: Arc<AtomicUsize>,
+}
+
+impl Scratch {
+    fn new() -> Self {
+        let memory = Arc::new(AtomicUsize::new(0));
+        let current = std::thread::current();
+        let thread = current
+            .name()
+            .map_or_else(|| format!("{:?}", current.id()), str::to_owned);
+        REGISTRY
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((thread, Arc::downgrade(&memory)));
+        Self {
+            bytes: RefCell::new(Buffer::new()),
+            offsets: RefCell::new(Buffer::new()),
+            memory,
+        }
+    }
+}
+
+fn lend<T, R>(
+    buffer: &RefCell<Buffer<T>>,
+    memory: &AtomicUsize,
+    f: impl FnOnce(&mut Vec<T>) -> R,
+) -> R {
+    let Ok(mut buffer) = buffer.try_borrow_mut() else {
+        // Nested use: the thread's buffer is already lent.
+        return f(&mut Vec::new());
+    };
+    let before = buffer.bytes();
+    buffer.buf.clear();
+    let result = f(&mut buffer.buf);
+    let len = buffer.buf.len();
+    buffer.record(len);
+    let after = buffer.bytes();
+    if after >= before {
+        memory.fetch_add(after - before, Ordering::Relaxed);
+    } else {
+        memory.fetch_sub(before - after, Ordering::Relaxed);
+    }
+    result
+}
+
+thread_local! {
+    static SCRATCH: Scratch = Scratch::new();
+}
+
+/// The memory counters of the threads, with their names.
+static REGISTRY: Mutex<Vec<(String, Weak<AtomicUsize>)>> = Mutex::new(Vec::new());
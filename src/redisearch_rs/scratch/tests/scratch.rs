@@ -0,0 +1,95 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use scratch::{RETAINED_BYTES, SHRINK_PERIOD, ThreadScratch};
+
+#[test]
+fn test_buffers_are_reused() {
+    std::thread::spawn(|| {
+        scratch::with_bytes(|buf| buf.extend_from_slice(b"hello"));
+        let capacity = scratch::with_bytes(|buf| {
+            assert!(buf.is_empty());
+            buf.capacity()
+        });
+        assert!(capacity >= 5);
+        scratch::with_offsets(|offsets| offsets.extend([1, 2, 3]));
+        assert_eq!(
+            scratch::memory(),
+            capacity + scratch::with_offsets(|offsets| offsets.capacity() * 4)
+        );
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn test_nested_use() {
+    std::thread::spawn(|| {
+        scratch::with_bytes(|outer| {
+            outer.push(1);
+            scratch::with_bytes(|inner| {
+                assert!(inner.is_empty());
+                inner.push(2);
+            });
+            assert_eq!(outer, &[1]);
+        });
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn test_shrinks_after_a_large_use() {
+    std::thread::spawn(|| {
+        scratch::with_bytes(|buf| buf.resize(1 << 20, 0));
+        assert!(scratch::memory() >= 1 << 20);
+        for _ in 1..SHRINK_PERIOD {
+            scratch::with_bytes(|buf| buf.push(0));
+        }
+        assert!(
+            scratch::memory() >= 1 << 20,
+            "the large use is in this period"
+        );
+        for _ in 0..SHRINK_PERIOD {
+            scratch::with_bytes(|buf| buf.push(0));
+        }
+        assert_eq!(scratch::memory(), RETAINED_BYTES);
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn test_threads() {
+    std::thread::Builder::new()
+        .name("scratch-test".to_owned())
+        .spawn(|| {
+            scratch::with_offsets(|offsets| offsets.reserve_exact(10));
+            let this = scratch::threads()
+                .into_iter()
+                .find(|t| t.thread == "scratch-test")
+                .unwrap();
+            assert_eq!(
+                this,
+                ThreadScratch {
+                    thread: "scratch-test".to_owned(),
+                    bytes: scratch::memory(),
+                }
+            );
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    // Exited threads are forgotten.
+    assert!(
+        scratch::threads()
+            .iter()
+            .all(|t| t.thread != "scratch-test")
+    );
+}
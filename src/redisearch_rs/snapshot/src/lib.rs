@@ -0,0 +1,329 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Snapshot-consistent reads under concurrent writes.
+//!
+//! Every write to an index advances its [`GenerationClock`]. A query takes a
+//! [`Snapshot`] of the last committed generation when it starts, and only
+//! sees the documents indexed before it, and all the documents deleted after
+//! it:
+//!
+//! - The [`VisibilityTable`] records the generations at which documents were
+//!   added to and deleted from the doc table. An updated document is deleted
+//!   and re-added under a new id, so a query sees exactly one of its versions.
+//! - [`BlockGenerations`] records the generation at which each posting block
+//!   was created, so that readers stop at the first block created after their
+//!   snapshot.
+//!
+//! Tracking deleted documents costs memory until no snapshot can see them
+//! anymore, so the guarantee is opt-in with [`SnapshotIsolation`].
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    str::FromStr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+/// A point in the history of an index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Generation(pub u64);
+
+/// Whether queries read a consistent snapshot (`SNAPSHOT_ISOLATION`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SnapshotIsolation {
+    /// Queries see concurrent writes, as before.
+    #[default]
+    Disabled,
+    Enabled,
+}
+
+impl FromStr for SnapshotIsolation {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s.to_ascii_lowercase().as_str() {
+            "disabled" | "off" | "false" => Ok(Self::Disabled),
+            "enabled" | "on" | "true" => Ok(Self::Enabled),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The generation counter of an index, and the registry of the snapshots
+/// currently held by queries.
+///
+/// A write gets its generation when it starts, but is only visible to the
+/// snapshots taken after it completes: the committed generation is the last
+/// one before the oldest write still in progress.
+#[derive(Debug, Default)]
+pub struct GenerationClock {
+    current: AtomicU64,
+    committed: AtomicU64,
+    in_flight: Mutex<BTreeSet<Generation>>,
+    active: Arc<Mutex<BTreeMap<Generation, usize>>>,
+}
+
+impl GenerationClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The generation of the last write started.
+    pub fn current(&self) -> Generation {
+        Generation(self.current.load(Ordering::Acquire))
+    }
+
+    /// The generation up to which every write has completed.
+    pub fn committed(&self) -> Generation {
+        Generation(self.committed.load(Ordering::Acquire))
+    }
+
+    /// Start a write. It's committed when the returned [`Write`] is dropped.
+    pub fn write(&self) -> Write<'_> {
+        let mut in_flight = self.in_flight.lock().expect("write registry poisoned");
+        let generation = Generation(self.current.fetch_add(1, Ordering::AcqRel) + 1);
+        in_flight.insert(generation);
+        Write {
+            clock: self,
+            generation,
+        }
+    }
+
+    fn commit(&self, generation: Generation) {
+        let mut in_flight = self.in_flight.lock().expect("write registry poisoned");
+        in_flight.remove(&generation);
+        let committed = match in_flight.first() {
+            Some(oldest) => oldest.0 - 1,
+            None => self.current.load(Ordering::Acquire),
+        };
+        self.committed.store(committed, Ordering::Release);
+    }
+
+    /// Take a snapshot of the committed generation. It's released on drop.
+    pub fn snapshot(&self) -> Snapshot {
+        let mut active = self.active.lock().expect("snapshot registry poisoned");
+        // Read under the lock, so that `oldest_active` never misses a
+        // snapshot older than the generation it returns.
+        let generation = self.committed();
+        *active.entry(generation).or_default() += 1;
+        Snapshot {
+            generation,
+            active: Arc::clone(&self.active),
+        }
+    }
+
+    /// The generation of the oldest snapshot still held, or the committed one
+    /// if there's none. History older than this is invisible to every query.
+    pub fn oldest_active(&self) -> Generation {
+        let active = self.active.lock().expect("snapshot registry poisoned");
+        active
+            .keys()
+            .next()
+            .copied()
+            .unwrap_or_else(|| self.committed())
+    }
+
+    /// The number of snapshots held.
+    pub fn active_snapshots(&self) -> usize {
+        let active = self.active.lock().expect("snapshot registry poisoned");
+        active.values().sum()
+    }
+}
+
+/// A write in progress. See [`GenerationClock::write`].
+#[derive(Debug)]
+pub struct Write<'a> {
+    clock: &'a GenerationClock,
+    generation: Generation,
+}
+
+impl Write<'_> {
+    /// The generation to record the changes of the write at.
+    pub const fn generation(&self) -> Generation {
+        self.generation
+    }
+}
+
+impl Drop for Write<'_> {
+    fn drop(&mut self) {
+        self.clock.commit(self.generation);
+    }
+}
+
+/// The view of a query on an index. See the [crate documentation](crate).
+#[derive(Debug)]
+pub struct Snapshot {
+    generation: Generation,
+    active: Arc<Mutex<BTreeMap<Generation, usize>>>,
+}
+
+impl Snapshot {
+    pub const fn generation(&self) -> Generation {
+        self.generation
+    }
+
+    /// Whether something written at `generation` is visible.
+    pub fn sees(&self, generation: Generation) -> bool {
+        generation <= self.generation
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        let mut active = self.active.lock().expect("snapshot registry poisoned");
+        if let Some(count) = active.get_mut(&self.generation) {
+            *count -= 1;
+            if *count == 0 {
+                active.remove(&self.generation);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Lifetime {
+    added: Generation,
+    deleted: Option<Generation>,
+}
+
+/// The generations at which documents were added and deleted.
+///
+/// Only documents added or deleted since the oldest active snapshot need to be
+/// tracked: [`VisibilityTable::prune`] forgets the others. Untracked documents
+/// are visible, since a query doesn't find deleted documents in the doc table.
+#[derive(Debug, Default)]
+pub struct VisibilityTable {
+    isolation: SnapshotIsolation,
+    docs: HashMap<u64, Lifetime>,
+}
+
+impl VisibilityTable {
+    pub fn new(isolation: SnapshotIsolation) -> Self {
+        Self {
+            isolation,
+            docs: HashMap::new(),
+        }
+    }
+
+    pub const fn isolation(&self) -> SnapshotIsolation {
+        self.isolation
+    }
+
+    /// Record that `doc_id` was added at `generation`.
+    pub fn added(&mut self, doc_id: u64, generation: Generation) {
+        if self.isolation == SnapshotIsolation::Enabled {
+            self.docs.insert(
+                doc_id,
+                Lifetime {
+                    added: generation,
+                    deleted: None,
+                },
+            );
+        }
+    }
+
+    /// Record that `doc_id` was deleted at `generation`.
+    pub fn deleted(&mut self, doc_id: u64, generation: Generation) {
+        if self.isolation == SnapshotIsolation::Enabled {
+            self.docs
+                .entry(doc_id)
+                .or_insert(Lifetime {
+                    added: Generation::default(),
+                    deleted: None,
+                })
+                .deleted = Some(generation);
+        }
+    }
+
+    /// Whether `doc_id` is part of `snapshot`.
+    pub fn is_visible(&self, doc_id: u64, snapshot: &Snapshot) -> bool {
+        match self.docs.get(&doc_id) {
+            None => true,
+            Some(lifetime) => {
+                snapshot.sees(lifetime.added)
+                    && lifetime
+                        .deleted
+                        .is_none_or(|deleted| !snapshot.sees(deleted))
+            }
+        }
+    }
+
+    /// Whether the deletion of `doc_id` is still visible to some snapshot,
+    /// in which case its entries must not be collected yet.
+    pub fn is_deletion_pending(&self, doc_id: u64) -> bool {
+        self.docs
+            .get(&doc_id)
+            .is_some_and(|lifetime| lifetime.deleted.is_some())
+    }
+
+    /// Forget what every snapshot from `oldest` on agrees on. Returns the
+    /// number of documents forgotten.
+    pub fn prune(&mut self, oldest: Generation) -> usize {
+        let before = self.docs.len();
+        self.docs.retain(|_, lifetime| match lifetime.deleted {
+            Some(deleted) => deleted > oldest,
+            None => lifetime.added > oldest,
+        });
+        before - self.docs.len()
+    }
+
+    /// The number of documents tracked.
+    pub fn tracked(&self) -> usize {
+        self.docs.len()
+    }
+
+    /// The memory used for tracking, in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.docs.capacity() * (size_of::<u64>() + size_of::<Lifetime>())
+    }
+}
+
+/// The generations at which the blocks of a posting list were created.
+///
+/// Blocks are appended in generation order, and GC keeps that order when it
+/// removes blocks.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockGenerations {
+    created: Vec<Generation>,
+}
+
+impl BlockGenerations {
+    pub const fn new() -> Self {
+        Self {
+            created: Vec::new(),
+        }
+    }
+
+    /// Record a new block, appended at `generation`.
+    pub fn push(&mut self, generation: Generation) {
+        debug_assert!(
+            self.created.last().is_none_or(|last| *last <= generation),
+            "blocks must be appended in generation order"
+        );
+        self.created.push(generation);
+    }
+
+    /// Record the removal of the block at `index`.
+    pub fn remove(&mut self, index: usize) {
+        self.created.remove(index);
+    }
+
+    /// The number of leading blocks created before `snapshot`. Blocks past
+    /// that contain only documents the snapshot doesn't see.
+    pub fn visible_blocks(&self, snapshot: &Snapshot) -> usize {
+        self.created.partition_point(|g| snapshot.sees(*g))
+    }
+
+    pub const fn memory_usage(&self) -> usize {
+        self.created.capacity() * size_of::<Generation>()
+    }
+}
@@ -0,0 +1,135 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use snapshot::{BlockGenerations, Generation, GenerationClock, SnapshotIsolation, VisibilityTable};
+
+#[test]
+fn test_snapshot_registry() {
+    let clock = GenerationClock::new();
+    drop(clock.write());
+    let first = clock.snapshot();
+    drop(clock.write());
+    let second = clock.snapshot();
+    let also_second = clock.snapshot();
+    assert_eq!(first.generation(), Generation(1));
+    assert_eq!(second.generation(), Generation(2));
+    assert_eq!(clock.active_snapshots(), 3);
+    assert_eq!(clock.oldest_active(), Generation(1));
+
+    drop(first);
+    assert_eq!(clock.oldest_active(), Generation(2));
+    drop(second);
+    assert_eq!(clock.oldest_active(), Generation(2));
+    drop(also_second);
+    drop(clock.write());
+    assert_eq!(clock.active_snapshots(), 0);
+    assert_eq!(clock.oldest_active(), Generation(3));
+}
+
+#[test]
+fn test_update_during_query_is_seen_once() {
+    let clock = GenerationClock::new();
+    let mut table = VisibilityTable::new(SnapshotIsolation::Enabled);
+    table.added(1, clock.write().generation());
+
+    let query = clock.snapshot();
+    // The document is updated under a new id while the query runs.
+    let write = clock.write();
+    table.deleted(1, write.generation());
+    table.added(2, write.generation());
+    drop(write);
+    // A new document is added.
+    table.added(3, clock.write().generation());
+
+    assert!(table.is_visible(1, &query));
+    assert!(!table.is_visible(2, &query));
+    assert!(!table.is_visible(3, &query));
+
+    let later = clock.snapshot();
+    assert!(!table.is_visible(1, &later));
+    assert!(table.is_visible(2, &later));
+    assert!(table.is_visible(3, &later));
+}
+
+#[test]
+fn test_prune() {
+    let clock = GenerationClock::new();
+    let mut table = VisibilityTable::new(SnapshotIsolation::Enabled);
+    table.added(1, clock.write().generation());
+    let query = clock.snapshot();
+    table.deleted(1, clock.write().generation());
+    table.added(2, clock.write().generation());
+
+    // The query still sees document 1.
+    assert_eq!(table.prune(clock.oldest_active()), 0);
+    assert!(table.is_deletion_pending(1));
+
+    drop(query);
+    assert_eq!(table.prune(clock.oldest_active()), 2);
+    assert_eq!(table.tracked(), 0);
+    assert!(!table.is_deletion_pending(1));
+    // Untracked documents are visible.
+    assert!(table.is_visible(2, &clock.snapshot()));
+}
+
+#[test]
+fn test_disabled_tracks_nothing() {
+    let clock = GenerationClock::new();
+    let mut table = VisibilityTable::new(SnapshotIsolation::Disabled);
+    let query = clock.snapshot();
+    table.added(1, clock.write().generation());
+    table.deleted(1, clock.write().generation());
+    assert_eq!(table.tracked(), 0);
+    assert_eq!(table.memory_usage(), 0);
+    assert!(table.is_visible(1, &query));
+    assert_eq!("ON".parse(), Ok(SnapshotIsolation::Enabled));
+}
+
+#[test]
+fn test_visible_blocks() {
+    let clock = GenerationClock::new();
+    let mut blocks = BlockGenerations::new();
+    blocks.push(clock.write().generation());
+    blocks.push(clock.write().generation());
+    let query = clock.snapshot();
+    blocks.push(clock.write().generation());
+    blocks.push(clock.write().generation());
+    assert_eq!(blocks.visible_blocks(&query), 2);
+
+    blocks.remove(0);
+    assert_eq!(blocks.visible_blocks(&query), 1);
+    assert_eq!(blocks.visible_blocks(&clock.snapshot()), 3);
+}
+
+#[test]
+fn test_snapshot_skips_writes_in_progress() {
+    let clock = GenerationClock::new();
+    let mut table = VisibilityTable::new(SnapshotIsolation::Enabled);
+    let first = clock.write();
+    table.added(1, first.generation());
+    let second = clock.write();
+    table.added(2, second.generation());
+    assert_eq!(clock.current(), Generation(2));
+
+    // Neither write is visible until it completes.
+    let during = clock.snapshot();
+    assert_eq!(during.generation(), Generation(0));
+    assert!(!table.is_visible(1, &during));
+    // Completing the second write doesn't expose it before the first.
+    drop(second);
+    assert_eq!(clock.committed(), Generation(0));
+    assert_eq!(clock.oldest_active(), Generation(0));
+    drop(first);
+    assert_eq!(clock.committed(), Generation(2));
+
+    let after = clock.snapshot();
+    assert!(table.is_visible(1, &after));
+    assert!(table.is_visible(2, &after));
+    assert!(!table.is_visible(2, &during));
+}
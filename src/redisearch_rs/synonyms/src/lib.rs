@@ -0,0 +1,178 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Synonym groups, as managed by `FT.SYNUPDATE` and `FT.SYNDUMP`.
+//!
+//! A group is a set of terms identified by a group id. Terms are either:
+//!
+//! - *equivalent*: each of them matches the others, as in the original
+//!   symmetric groups;
+//! - *one-way*: they match the equivalent terms of the group, but aren't
+//!   matched by them. This expresses hypernyms: with `laptop => computer`, a
+//!   query for `laptop` matches documents about computers, but a query for
+//!   `computer` doesn't match every document about laptops.
+//!
+//! Every term has a weight, 1 by default, applied by the scorer to the
+//! matches it produces when other terms are expanded to it.
+
+pub mod mode;
+pub mod update;
+
+use std::collections::{BTreeMap, HashMap};
+
+pub use mode::{SynonymMode, expand_query};
+pub use update::{SynUpdate, SynUpdateError};
+
+/// The prefix of group ids in the index, `SYNONYM_PREFIX_CHAR` in C.
+pub const SYNONYM_PREFIX_CHAR: char = '~';
+
+/// How a term takes part in a group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Matches, and is matched by, the other equivalent terms.
+    Equivalent,
+    /// Matches the equivalent terms, but isn't matched by them.
+    OneWay,
+}
+
+/// A term of a group.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Member {
+    pub direction: Direction,
+    pub weight: f64,
+}
+
+impl Default for Member {
+    fn default() -> Self {
+        Self {
+            direction: Direction::Equivalent,
+            weight: 1.0,
+        }
+    }
+}
+
+/// A term a query term expands to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expansion {
+    pub term: String,
+    pub group_id: String,
+    pub weight: f64,
+}
+
+/// See the [crate documentation](crate).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SynonymMap {
+    groups: BTreeMap<String, BTreeMap<String, Member>>,
+    /// The groups of every term.
+    terms: HashMap<String, Vec<String>>,
+}
+
+impl SynonymMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add terms to a group, creating it if needed. Terms already in the group
+    /// are updated. Terms are lowercased.
+    pub fn update<S: AsRef<str>>(
+        &mut self,
+        group_id: &str,
+        terms: impl IntoIterator<Item = (S, Member)>,
+    ) {
+        let group = self.groups.entry(group_id.to_owned()).or_default();
+        for (term, member) in terms {
+            let term = term.as_ref().to_lowercase();
+            let groups = self.terms.entry(term.clone()).or_default();
+            if !groups.iter().any(|g| g == group_id) {
+                groups.push(group_id.to_owned());
+            }
+            group.insert(term, member);
+        }
+    }
+
+    /// Apply a parsed `FT.SYNUPDATE`.
+    pub fn apply(&mut self, update: &SynUpdate) {
+        self.update(
+            &update.group_id,
+            update.terms.iter().map(|(term, member)| (term, *member)),
+        );
+    }
+
+    /// The number of groups.
+    pub fn num_groups(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// The terms of a group.
+    pub fn group(&self, group_id: &str) -> Option<&BTreeMap<String, Member>> {
+        self.groups.get(group_id)
+    }
+
+    fn memberships<'a>(&'a self, term: &str) -> impl Iterator<Item = (&'a str, &'a Member)> {
+        self.terms
+            .get(term)
+            .into_iter()
+            .flatten()
+            .filter_map(move |group_id| {
+                let member = self.groups.get(group_id)?.get(term)?;
+                Some((group_id.as_str(), member))
+            })
+    }
+
+    /// The terms a query for `term` also matches: the other equivalent terms
+    /// of its groups, weighted by their weight.
+    pub fn expansions(&self, term: &str) -> Vec<Expansion> {
+        let mut out = Vec::new();
+        for (group_id, _) in self.memberships(term) {
+            for (other, member) in &self.groups[group_id] {
+                if other != term && member.direction == Direction::Equivalent {
+                    out.push(Expansion {
+                        term: other.clone(),
+                        group_id: group_id.to_owned(),
+                        weight: member.weight,
+                    });
+                }
+            }
+        }
+        out
+    }
+
+    /// The groups whose id is indexed along with `term`: those it's an
+    /// equivalent term of.
+    pub fn index_group_ids(&self, term: &str) -> Vec<&str> {
+        self.memberships(term)
+            .filter(|(_, member)| member.direction == Direction::Equivalent)
+            .map(|(group_id, _)| group_id)
+            .collect()
+    }
+
+    /// The groups whose id a query for `term` is expanded to, with the weight
+    /// of `term` in the group.
+    pub fn query_group_ids(&self, term: &str) -> Vec<(&str, f64)> {
+        self.memberships(term)
+            .map(|(group_id, member)| (group_id, member.weight))
+            .collect()
+    }
+
+    /// The terms with their groups, sorted, for `FT.SYNDUMP`.
+    pub fn dump(&self) -> Vec<(&str, Vec<&str>)> {
+        let mut dump: Vec<_> = self
+            .terms
+            .iter()
+            .map(|(term, groups)| (term.as_str(), groups.iter().map(String::as_str).collect()))
+            .collect();
+        dump.sort_unstable();
+        dump
+    }
+}
+
+/// The term indexing the group `group_id`, e.g. `~g1`.
+pub fn group_term(group_id: &str) -> String {
+    format!("{SYNONYM_PREFIX_CHAR}{group_id}")
+}
@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Applying synonyms, at index time or at query time.
+//!
+//! With [`SynonymMode::Index`], the default, the id of every group a term
+//! belongs to is indexed along with it, and queries look up the group ids.
+//! Queries are cheap, but the index grows with every group, and updating a
+//! group requires rescanning the documents.
+//!
+//! With [`SynonymMode::Query`], nothing is indexed: queries are expanded to
+//! the union of the terms of the groups, each weighted by its weight in the
+//! group. Groups can then be updated without reindexing.
+
+use std::{
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use analysis::{Token, TokenFilter};
+use query::{QueryNode, QueryNodeKind};
+
+use crate::{SynUpdate, SynonymMap, group_term};
+
+/// When synonyms are applied (`SYNONYMMODE`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SynonymMode {
+    #[default]
+    Index,
+    Query,
+}
+
+impl FromStr for SynonymMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s.to_ascii_lowercase().as_str() {
+            "index" => Ok(Self::Index),
+            "query" => Ok(Self::Query),
+            _ => Err(()),
+        }
+    }
+}
+
+impl SynonymMode {
+    /// Whether the existing documents must be rescanned after `update`.
+    pub const fn needs_rescan(self, update: &SynUpdate) -> bool {
+        matches!(self, Self::Index) && !update.skip_initial_scan
+    }
+}
+
+/// The index-time filter adding the group ids of every term.
+pub struct IndexSynonymsFilter(pub Arc<RwLock<SynonymMap>>);
+
+impl TokenFilter for IndexSynonymsFilter {
+    fn apply(&self, tokens: Vec<Token>) -> Vec<Token> {
+        let map = self.0.read().expect("synonym map poisoned");
+        let mut out = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let ids: Vec<_> = if token.variant {
+                Vec::new()
+            } else {
+                map.index_group_ids(&token.term)
+                    .into_iter()
+                    .map(|id| token.variant(group_term(id)))
+                    .collect()
+            };
+            out.push(token);
+            out.extend(ids);
+        }
+        out
+    }
+}
+
+/// Expand the terms of the query rooted at `root` with their synonyms.
+///
+/// Every non-verbatim term belonging to a group is replaced by the union of
+/// itself and either the group ids or the terms of the groups, depending on
+/// `mode`. The union takes over the options of the term.
+pub fn expand_query(root: &mut QueryNode, map: &SynonymMap, mode: SynonymMode) {
+    let QueryNodeKind::Token { term } = &root.kind else {
+        for child in &mut root.children {
+            expand_query(child, map, mode);
+        }
+        return;
+    };
+    if root.opts.verbatim {
+        return;
+    }
+    let alternatives: Vec<_> = match mode {
+        SynonymMode::Index => map
+            .query_group_ids(term)
+            .into_iter()
+            .map(|(id, weight)| (group_term(id), weight))
+            .collect(),
+        SynonymMode::Query => map
+            .expansions(term)
+            .into_iter()
+            .map(|e| (e.term, e.weight))
+            .collect(),
+    };
+    if alternatives.is_empty() {
+        return;
+    }
+    let opts = std::mem::take(&mut root.opts);
+    let original = std::mem::replace(root, QueryNode::new(QueryNodeKind::Union));
+    root.opts = opts;
+    root.children.push(original);
+    for (term, weight) in alternatives {
+        let mut alternative = QueryNode::token(term);
+        alternative.opts.weight = weight;
+        alternative.opts.explicit_weight = true;
+        // Synonyms are matched exactly, not stemmed.
+        alternative.opts.verbatim = true;
+        root.children.push(alternative);
+    }
+}
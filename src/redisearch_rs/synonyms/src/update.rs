@@ -0,0 +1,116 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Parsing of `FT.SYNUPDATE`.
+//!
+//! ```text
+//! FT.SYNUPDATE {index} {group_id} [SKIPINITIALSCAN] {term}... [=> {term}...]
+//! ```
+//!
+//! Terms may be followed by `^{weight}`, e.g. `notebook^0.8`. When `=>` is
+//! present, the terms before it are one-way, and the terms after it are
+//! equivalent.
+
+use std::fmt::{self, Display};
+
+use crate::{Direction, Member};
+
+const SKIP_INITIAL_SCAN: &str = "SKIPINITIALSCAN";
+const ONE_WAY_SEPARATOR: &str = "=>";
+
+/// A parsed `FT.SYNUPDATE`, without the index name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SynUpdate {
+    pub group_id: String,
+    pub skip_initial_scan: bool,
+    pub terms: Vec<(String, Member)>,
+}
+
+/// An invalid `FT.SYNUPDATE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SynUpdateError {
+    /// The group id or the terms are missing.
+    MissingArguments,
+    /// A weight isn't a positive number.
+    BadWeight(String),
+    /// `=>` appears more than once, or without terms on one of its sides.
+    BadSeparator,
+}
+
+impl Display for SynUpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingArguments => f.write_str("wrong number of arguments for FT.SYNUPDATE"),
+            Self::BadWeight(term) => write!(f, "Invalid synonym weight in `{term}`"),
+            Self::BadSeparator => write!(
+                f,
+                "`{ONE_WAY_SEPARATOR}` must appear once, between one-way and equivalent terms"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SynUpdateError {}
+
+impl SynUpdate {
+    /// Parse the arguments following the index name.
+    ///
+    /// # Errors
+    ///
+    /// See [`SynUpdateError`].
+    pub fn parse(args: &[&str]) -> Result<Self, SynUpdateError> {
+        let (group_id, mut args) = args.split_first().ok_or(SynUpdateError::MissingArguments)?;
+        let skip_initial_scan = args
+            .first()
+            .is_some_and(|a| a.eq_ignore_ascii_case(SKIP_INITIAL_SCAN));
+        if skip_initial_scan {
+            args = &args[1..];
+        }
+        if args.is_empty() {
+            return Err(SynUpdateError::MissingArguments);
+        }
+
+        let separators = args.iter().filter(|a| **a == ONE_WAY_SEPARATOR).count();
+        let (one_way, equivalent) = match args.iter().position(|a| *a == ONE_WAY_SEPARATOR) {
+            None => (&args[..0], args),
+            Some(at) if separators == 1 && at > 0 && at + 1 < args.len() => {
+                (&args[..at], &args[at + 1..])
+            }
+            Some(_) => return Err(SynUpdateError::BadSeparator),
+        };
+
+        let mut terms = Vec::with_capacity(one_way.len() + equivalent.len());
+        for (args, direction) in [
+            (one_way, Direction::OneWay),
+            (equivalent, Direction::Equivalent),
+        ] {
+            for arg in args {
+                let (term, weight) = parse_term(arg)?;
+                terms.push((term.to_owned(), Member { direction, weight }));
+            }
+        }
+        Ok(Self {
+            group_id: (*group_id).to_owned(),
+            skip_initial_scan,
+            terms,
+        })
+    }
+}
+
+fn parse_term(arg: &str) -> Result<(&str, f64), SynUpdateError> {
+    let Some((term, weight)) = arg.rsplit_once('^') else {
+        return Ok((arg, 1.0));
+    };
+    weight
+        .parse()
+        .ok()
+        .filter(|w: &f64| w.is_finite() && *w > 0.0 && !term.is_empty())
+        .map(|w| (term, w))
+        .ok_or_else(|| SynUpdateError::BadWeight(arg.to_owned()))
+}
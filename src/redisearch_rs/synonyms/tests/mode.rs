@@ -0,0 +1,85 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::sync::{Arc, RwLock};
+
+use analysis::{Token, TokenFilter};
+use query::{QueryNode, QueryNodeKind};
+use synonyms::{SynUpdate, SynonymMap, SynonymMode, expand_query, mode::IndexSynonymsFilter};
+
+fn map() -> SynonymMap {
+    let mut map = SynonymMap::new();
+    map.apply(&SynUpdate::parse(&["g1", "car", "auto^0.5"]).unwrap());
+    map
+}
+
+fn alternative(term: &str, weight: f64) -> QueryNode {
+    let mut node = QueryNode::token(term);
+    node.opts.weight = weight;
+    node.opts.explicit_weight = true;
+    node.opts.verbatim = true;
+    node
+}
+
+#[test]
+fn test_mode() {
+    assert_eq!("QUERY".parse(), Ok(SynonymMode::Query));
+    assert_eq!(SynonymMode::default(), SynonymMode::Index);
+
+    let update = SynUpdate::parse(&["g1", "a", "b"]).unwrap();
+    assert!(SynonymMode::Index.needs_rescan(&update));
+    assert!(!SynonymMode::Query.needs_rescan(&update));
+    let skip = SynUpdate::parse(&["g1", "SKIPINITIALSCAN", "a", "b"]).unwrap();
+    assert!(!SynonymMode::Index.needs_rescan(&skip));
+}
+
+#[test]
+fn test_index_time_filter() {
+    let filter = IndexSynonymsFilter(Arc::new(RwLock::new(map())));
+    let tokens = filter.apply(vec![Token::new("car", 1, 0..3), Token::new("red", 2, 4..7)]);
+    let terms: Vec<_> = tokens
+        .iter()
+        .map(|t| (t.term.as_str(), t.position))
+        .collect();
+    assert_eq!(terms, [("car", 1), ("~g1", 1), ("red", 2)]);
+    assert!(tokens[1].variant);
+}
+
+#[test]
+fn test_expand_index_mode() {
+    let mut root = QueryNode::intersect(vec![QueryNode::token("auto"), QueryNode::token("red")]);
+    expand_query(&mut root, &map(), SynonymMode::Index);
+    assert_eq!(
+        root,
+        QueryNode::intersect(vec![
+            QueryNode::union(vec![QueryNode::token("auto"), alternative("~g1", 0.5)]),
+            QueryNode::token("red"),
+        ])
+    );
+}
+
+#[test]
+fn test_expand_query_mode() {
+    let mut root = QueryNode::token("car");
+    root.opts.weight = 2.0;
+    expand_query(&mut root, &map(), SynonymMode::Query);
+    assert_eq!(root.kind, QueryNodeKind::Union);
+    assert_eq!(root.opts.weight, 2.0);
+    assert_eq!(
+        root.children,
+        [QueryNode::token("car"), alternative("auto", 0.5)]
+    );
+
+    // Verbatim terms aren't expanded.
+    let mut verbatim = QueryNode::token("car");
+    verbatim.opts.verbatim = true;
+    let before = verbatim.clone();
+    expand_query(&mut verbatim, &map(), SynonymMode::Query);
+    assert_eq!(verbatim, before);
+}
@@ -0,0 +1,143 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use synonyms::{Direction, Expansion, Member, SynUpdate, SynUpdateError, SynonymMap, group_term};
+
+fn map(updates: &[&[&str]]) -> SynonymMap {
+    let mut map = SynonymMap::new();
+    for args in updates {
+        map.apply(&SynUpdate::parse(args).unwrap());
+    }
+    map
+}
+
+fn expansions(map: &SynonymMap, term: &str) -> Vec<(String, f64)> {
+    map.expansions(term)
+        .into_iter()
+        .map(|e| (e.term, e.weight))
+        .collect()
+}
+
+#[test]
+fn test_parse() {
+    let update = SynUpdate::parse(&[
+        "g1",
+        "SKIPINITIALSCAN",
+        "Laptop",
+        "notebook^0.8",
+        "=>",
+        "computer",
+    ])
+    .unwrap();
+    assert_eq!(update.group_id, "g1");
+    assert!(update.skip_initial_scan);
+    assert_eq!(
+        update.terms,
+        [
+            (
+                "Laptop".to_owned(),
+                Member {
+                    direction: Direction::OneWay,
+                    weight: 1.0
+                }
+            ),
+            (
+                "notebook".to_owned(),
+                Member {
+                    direction: Direction::OneWay,
+                    weight: 0.8
+                }
+            ),
+            ("computer".to_owned(), Member::default()),
+        ]
+    );
+
+    assert_eq!(
+        SynUpdate::parse(&["g1"]),
+        Err(SynUpdateError::MissingArguments)
+    );
+    assert_eq!(
+        SynUpdate::parse(&["g1", "SKIPINITIALSCAN"]),
+        Err(SynUpdateError::MissingArguments)
+    );
+    assert_eq!(
+        SynUpdate::parse(&["g1", "a^x"]),
+        Err(SynUpdateError::BadWeight("a^x".to_owned()))
+    );
+    assert_eq!(
+        SynUpdate::parse(&["g1", "a^0"]).unwrap_err().to_string(),
+        "Invalid synonym weight in `a^0`"
+    );
+    for args in [
+        &["g1", "=>", "a"][..],
+        &["g1", "a", "=>"],
+        &["g1", "a", "=>", "b", "=>", "c"],
+    ] {
+        assert_eq!(SynUpdate::parse(args), Err(SynUpdateError::BadSeparator));
+    }
+}
+
+#[test]
+fn test_symmetric_groups() {
+    let map = map(&[&["g1", "car", "auto", "automobile^0.5"]]);
+    assert_eq!(
+        expansions(&map, "car"),
+        [("auto".to_owned(), 1.0), ("automobile".to_owned(), 0.5)]
+    );
+    assert_eq!(
+        map.expansions("auto")[0],
+        Expansion {
+            term: "automobile".to_owned(),
+            group_id: "g1".to_owned(),
+            weight: 0.5,
+        }
+    );
+    assert_eq!(map.index_group_ids("car"), ["g1"]);
+    assert_eq!(group_term("g1"), "~g1");
+}
+
+#[test]
+fn test_one_way_mappings() {
+    let map = map(&[&["hw", "laptop", "notebook^0.9", "=>", "computer", "pc^0.7"]]);
+    assert_eq!(
+        expansions(&map, "laptop"),
+        [("computer".to_owned(), 1.0), ("pc".to_owned(), 0.7)]
+    );
+    // Hypernyms don't expand to their hyponyms.
+    assert_eq!(expansions(&map, "computer"), [("pc".to_owned(), 0.7)]);
+
+    // Only equivalent terms index the group id, but all query it.
+    assert!(map.index_group_ids("laptop").is_empty());
+    assert_eq!(map.index_group_ids("pc"), ["hw"]);
+    assert_eq!(map.query_group_ids("notebook"), [("hw", 0.9)]);
+}
+
+#[test]
+fn test_updates_and_dump() {
+    let mut map = map(&[&["g1", "Car", "auto"], &["g2", "car", "vehicle"]]);
+    assert_eq!(map.num_groups(), 2);
+    assert_eq!(
+        map.dump(),
+        [
+            ("auto", vec!["g1"]),
+            ("car", vec!["g1", "g2"]),
+            ("vehicle", vec!["g2"])
+        ]
+    );
+    assert_eq!(
+        expansions(&map, "car"),
+        [("auto".to_owned(), 1.0), ("vehicle".to_owned(), 1.0)]
+    );
+
+    // Updating a term changes its membership.
+    map.apply(&SynUpdate::parse(&["g1", "auto", "=>", "car^2"]).unwrap());
+    assert_eq!(map.group("g1").unwrap()["car"].weight, 2.0);
+    assert_eq!(expansions(&map, "car"), [("vehicle".to_owned(), 1.0)]);
+    assert_eq!(expansions(&map, "auto"), [("car".to_owned(), 2.0)]);
+}
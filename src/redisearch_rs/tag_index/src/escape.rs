@@ -0,0 +1,102 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Escaping of tag values in the query syntax.
+//!
+//! In `@field:{value}`, punctuation and spaces are escaped with a backslash,
+//! as in `{user\@example\.com}`. From dialect 4, `\xHH` stands for the byte
+//! of hexadecimal value `HH`, so that any value can be written in a query,
+//! including NULs and bytes which aren't valid UTF-8.
+
+use std::fmt::{self, Display};
+
+/// The first dialect supporting `\xHH` escapes.
+pub const BYTE_ESCAPE_DIALECT: u32 = 4;
+
+/// Errors returned when unescaping a tag value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EscapeError {
+    /// The value ends with a lone backslash.
+    TrailingBackslash,
+    /// `\x` isn't followed by two hexadecimal digits.
+    BadByteEscape,
+}
+
+impl Display for EscapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TrailingBackslash => f.write_str("Tag value ends with an escape character"),
+            Self::BadByteEscape => f.write_str("Invalid byte escape in tag value"),
+        }
+    }
+}
+
+impl std::error::Error for EscapeError {}
+
+/// The bytes of the tag value written as `value` in a query.
+///
+/// Before [`BYTE_ESCAPE_DIALECT`], `\x` is a plain `x`.
+///
+/// # Errors
+///
+/// Returns an [`EscapeError`] if an escape sequence is invalid.
+pub fn unescape(value: &str, dialect: u32) -> Result<Vec<u8>, EscapeError> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'\\' {
+            out.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+        let escaped = *bytes.get(i + 1).ok_or(EscapeError::TrailingBackslash)?;
+        if escaped == b'x' && dialect >= BYTE_ESCAPE_DIALECT {
+            let byte = bytes
+                .get(i + 2..i + 4)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or(EscapeError::BadByteEscape)?;
+            out.push(byte);
+            i += 4;
+        } else {
+            // The escaped character may span several bytes, which are copied
+            // as they are by the next iterations.
+            out.push(escaped);
+            i += 2;
+        }
+    }
+    Ok(out)
+}
+
+/// `value` written so that [`unescape`] returns it, in dialect
+/// [`BYTE_ESCAPE_DIALECT`] or later.
+pub fn escape(value: &[u8]) -> String {
+    let mut out = String::with_capacity(value.len());
+    for chunk in value.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            if c.is_ascii_control() {
+                push_byte(&mut out, c as u8);
+            } else if c.is_ascii_punctuation() || c.is_whitespace() {
+                out.push('\\');
+                out.push(c);
+            } else {
+                out.push(c);
+            }
+        }
+        for &byte in chunk.invalid() {
+            push_byte(&mut out, byte);
+        }
+    }
+    out
+}
+
+fn push_byte(out: &mut String, byte: u8) {
+    out.push_str(&format!("\\x{byte:02x}"));
+}
@@ -0,0 +1,118 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The TAG index, the Rust port of `tag_index.c`.
+//!
+//! Tag values are arbitrary bytes, not C strings: UUIDs and hashes stored in
+//! binary form may hold NULs, which used to truncate them. Every step handles
+//! them as byte slices, from splitting the field value ([`tokenizer`]) to the
+//! query syntax ([`escape`]) and the reply, where values are sent as bulk
+//! strings ([`encode_bulk`]).
+
+pub mod escape;
+pub mod tokenizer;
+
+use std::{collections::BTreeMap, ops::Bound};
+
+/// A document ID.
+pub type DocId = u64;
+
+/// The documents of every value of a TAG field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagIndex {
+    values: BTreeMap<Box<[u8]>, Vec<DocId>>,
+}
+
+impl TagIndex {
+    /// Index the `values` of the document `doc`, whose ID is greater than
+    /// those of the documents indexed so far. Returns the number of new
+    /// values.
+    pub fn index<V: AsRef<[u8]>>(&mut self, values: &[V], doc: DocId) -> usize {
+        let mut created = 0;
+        for value in values {
+            let value = value.as_ref();
+            let docs = match self.values.get_mut(value) {
+                Some(docs) => docs,
+                None => {
+                    created += 1;
+                    self.values.entry(value.into()).or_default()
+                }
+            };
+            // A value repeated in a document is indexed once.
+            if docs.last() != Some(&doc) {
+                docs.push(doc);
+            }
+        }
+        created
+    }
+
+    /// Remove `doc` from the documents of `value`, dropping the value if it
+    /// has no documents left, as GC does. Returns whether `doc` was found.
+    pub fn remove(&mut self, value: &[u8], doc: DocId) -> bool {
+        let Some(docs) = self.values.get_mut(value) else {
+            return false;
+        };
+        let Ok(i) = docs.binary_search(&doc) else {
+            return false;
+        };
+        docs.remove(i);
+        if docs.is_empty() {
+            self.values.remove(value);
+        }
+        true
+    }
+
+    /// The documents holding `value`, in increasing order.
+    pub fn docs(&self, value: &[u8]) -> &[DocId] {
+        self.values.get(value).map_or(&[], |docs| docs)
+    }
+
+    /// The values starting with `prefix`, in lexicographic byte order.
+    pub fn prefixed<'a>(&'a self, prefix: &'a [u8]) -> impl Iterator<Item = &'a [u8]> + 'a {
+        self.values
+            .range::<[u8], _>((Bound::Included(prefix), Bound::Unbounded))
+            .map(|(value, _)| &**value)
+            .take_while(move |value| value.starts_with(prefix))
+    }
+
+    /// All the values, as listed by `FT.TAGVALS`.
+    pub fn values(&self) -> impl Iterator<Item = &[u8]> {
+        self.values.keys().map(|value| &**value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The memory size of the index in bytes: the values, their document
+    /// lists, and the entries of the map.
+    pub fn memory_usage(&self) -> usize {
+        self.values
+            .iter()
+            .map(|(value, docs)| {
+                size_of::<(Box<[u8]>, Vec<DocId>)>()
+                    + value.len()
+                    + docs.capacity() * size_of::<DocId>()
+            })
+            .sum()
+    }
+}
+
+/// `value` encoded as a RESP bulk string, which is length-prefixed and so
+/// holds any byte.
+pub fn encode_bulk(value: &[u8]) -> Vec<u8> {
+    let mut out = format!("${}\r\n", value.len()).into_bytes();
+    out.extend_from_slice(value);
+    out.extend_from_slice(b"\r\n");
+    out
+}
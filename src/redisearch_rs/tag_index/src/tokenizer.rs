@@ -0,0 +1,199 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Splitting the value of a TAG field into tags, `tokenizeTagString` in C.
+//!
+//! The separator may be several characters long, e.g. `SEPARATOR ", "` or
+//! `SEPARATOR "||"`, as found in data exported from CSV pipelines. It's
+//! given with the escapes `\t`, `\n`, `\\` and `\xHH`, for separators which
+//! are hard to pass on a command line. `TRIM {BOTH|LEFT|RIGHT|NONE}` controls
+//! which whitespace around tags is removed.
+
+use std::fmt::{self, Display};
+
+/// The default separator of TAG fields in hashes.
+pub const DEFAULT_SEPARATOR: u8 = b',';
+
+const SEPARATOR_OPT: &str = "SEPARATOR";
+const TRIM_OPT: &str = "TRIM";
+
+/// Errors returned when parsing the options of a TAG field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagOptionError {
+    MissingArgument(&'static str),
+    BadValue { option: &'static str, value: String },
+}
+
+impl Display for TagOptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingArgument(option) => write!(f, "Missing argument for {option}"),
+            Self::BadValue { option, value } => write!(f, "Invalid value for {option}: {value}"),
+        }
+    }
+}
+
+impl std::error::Error for TagOptionError {}
+
+/// The whitespace removed around tags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Trim {
+    #[default]
+    Both,
+    Left,
+    Right,
+    None,
+}
+
+impl Trim {
+    const fn apply(self, tag: &[u8]) -> &[u8] {
+        match self {
+            Self::Both => tag.trim_ascii(),
+            Self::Left => tag.trim_ascii_start(),
+            Self::Right => tag.trim_ascii_end(),
+            Self::None => tag,
+        }
+    }
+}
+
+/// How the value of a TAG field is split into tags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagTokenizer {
+    /// `None` for JSON fields, whose values are tags as a whole.
+    pub separator: Option<Vec<u8>>,
+    /// `CASESENSITIVE`.
+    pub case_sensitive: bool,
+    /// `INDEXEMPTY`: empty tags are indexed rather than skipped.
+    pub index_empty: bool,
+    pub trim: Trim,
+}
+
+impl Default for TagTokenizer {
+    fn default() -> Self {
+        Self {
+            separator: Some(vec![DEFAULT_SEPARATOR]),
+            case_sensitive: false,
+            index_empty: false,
+            trim: Trim::default(),
+        }
+    }
+}
+
+impl TagTokenizer {
+    /// Try to handle the field option `name`, taking its argument from
+    /// `args`.
+    ///
+    /// Returns `Ok(false)` if `name` is neither `SEPARATOR` nor `TRIM`,
+    /// leaving it to the caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`TagOptionError`] if the argument is missing or invalid.
+    pub fn try_set_field_option<'a>(
+        &mut self,
+        name: &str,
+        args: &mut impl Iterator<Item = &'a str>,
+    ) -> Result<bool, TagOptionError> {
+        let option = if name.eq_ignore_ascii_case(SEPARATOR_OPT) {
+            SEPARATOR_OPT
+        } else if name.eq_ignore_ascii_case(TRIM_OPT) {
+            TRIM_OPT
+        } else {
+            return Ok(false);
+        };
+        let value = args.next().ok_or(TagOptionError::MissingArgument(option))?;
+        let bad_value = || TagOptionError::BadValue {
+            option,
+            value: value.to_owned(),
+        };
+        if option == SEPARATOR_OPT {
+            self.separator = Some(parse_separator(value).ok_or_else(bad_value)?);
+        } else {
+            self.trim = match value.to_ascii_uppercase().as_str() {
+                "BOTH" => Trim::Both,
+                "LEFT" => Trim::Left,
+                "RIGHT" => Trim::Right,
+                "NONE" => Trim::None,
+                _ => return Err(bad_value()),
+            };
+        }
+        Ok(true)
+    }
+
+    /// The tags of `value`, trimmed and, unless case sensitive, lowercased.
+    ///
+    /// `value` is split on the separator only: NULs and invalid UTF-8 are
+    /// kept as they are.
+    pub fn tokenize(&self, value: &[u8]) -> Vec<Vec<u8>> {
+        let Some(separator) = &self.separator else {
+            return vec![self.normalize(value)];
+        };
+        split(value, separator)
+            .map(|tag| self.trim.apply(tag))
+            .filter(|tag| self.index_empty || !tag.is_empty())
+            .map(|tag| self.normalize(tag))
+            .collect()
+    }
+
+    fn normalize(&self, tag: &[u8]) -> Vec<u8> {
+        if self.case_sensitive {
+            return tag.to_vec();
+        }
+        match std::str::from_utf8(tag) {
+            Ok(text) => text.to_lowercase().into_bytes(),
+            // Binary values only have their ASCII letters lowercased.
+            Err(_) => tag.to_ascii_lowercase(),
+        }
+    }
+}
+
+/// Split `value` on every occurrence of the non-empty `separator`.
+fn split<'a>(value: &'a [u8], separator: &'a [u8]) -> impl Iterator<Item = &'a [u8]> + 'a {
+    let mut rest = Some(value);
+    std::iter::from_fn(move || {
+        let current = rest?;
+        match current
+            .windows(separator.len())
+            .position(|window| window == separator)
+        {
+            Some(at) => {
+                rest = Some(&current[at + separator.len()..]);
+                Some(&current[..at])
+            }
+            None => {
+                rest = None;
+                Some(current)
+            }
+        }
+    })
+}
+
+/// Parse the argument of `SEPARATOR`. Returns `None` if it's empty or holds
+/// an invalid escape.
+fn parse_separator(value: &str) -> Option<Vec<u8>> {
+    let mut separator = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+    while let Some(b) = bytes.next() {
+        if b != b'\\' {
+            separator.push(b);
+            continue;
+        }
+        separator.push(match bytes.next()? {
+            b't' => b'\t',
+            b'n' => b'\n',
+            b'\\' => b'\\',
+            b'x' => {
+                let hex = [bytes.next()?, bytes.next()?];
+                u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?
+            }
+            _ => return None,
+        });
+    }
+    (!separator.is_empty()).then_some(separator)
+}
@@ -0,0 +1,148 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use tag_index::{
+    TagIndex, encode_bulk,
+    escape::{EscapeError, escape, unescape},
+    tokenizer::{TagOptionError, TagTokenizer, Trim},
+};
+
+const UUID: [u8; 16] = [
+    0x12, 0x00, 0x34, 0x00, 0xff, 0xfe, 0x00, 0x00, 0x41, 0x2c, 0x42, 0x00, 0x00, 0x00, 0x00, 0x01,
+];
+
+#[test]
+fn test_tokenize_binary_values() {
+    let tokenizer = TagTokenizer::default();
+    assert_eq!(
+        tokenizer.tokenize(b" Foo\0Bar , ,\xffX"),
+        [b"foo\0bar".to_vec(), b"\xffx".to_vec()]
+    );
+
+    let json = TagTokenizer {
+        separator: None,
+        case_sensitive: true,
+        ..Default::default()
+    };
+    assert_eq!(json.tokenize(&UUID), [UUID.to_vec()]);
+
+    let empty = TagTokenizer {
+        index_empty: true,
+        ..Default::default()
+    };
+    assert_eq!(empty.tokenize(b"a, "), [b"a".to_vec(), Vec::new()]);
+    assert_eq!(empty.tokenize(b""), [Vec::<u8>::new()]);
+}
+
+fn set_options(args: &str) -> Result<TagTokenizer, TagOptionError> {
+    let mut tokenizer = TagTokenizer::default();
+    let mut args = args.split(' ');
+    while let Some(name) = args.next() {
+        assert!(tokenizer.try_set_field_option(name, &mut args)?);
+    }
+    Ok(tokenizer)
+}
+
+#[test]
+fn test_multi_char_separators() {
+    let tokenizer = set_options("SEPARATOR ||").unwrap();
+    assert_eq!(
+        tokenizer.tokenize(b"a|b || c||||d||"),
+        [b"a|b".to_vec(), b"c".to_vec(), b"d".to_vec()]
+    );
+
+    // Escaped separators.
+    let tab = set_options(r"separator \t|\x00").unwrap();
+    assert_eq!(tab.separator.as_deref(), Some(b"\t|\0".as_slice()));
+    assert_eq!(tab.tokenize(b"x\t|\0y\t|\0"), [b"x".to_vec(), b"y".to_vec()]);
+
+    let bad = |value: &str| TagOptionError::BadValue {
+        option: "SEPARATOR",
+        value: value.to_owned(),
+    };
+    assert_eq!(set_options(r"SEPARATOR \q"), Err(bad(r"\q")));
+    assert_eq!(set_options(r"SEPARATOR \x4"), Err(bad(r"\x4")));
+    assert_eq!(set_options("SEPARATOR "), Err(bad("")));
+    assert_eq!(
+        TagTokenizer::default().try_set_field_option("SEPARATOR", &mut std::iter::empty()),
+        Err(TagOptionError::MissingArgument("SEPARATOR"))
+    );
+    assert_eq!(
+        TagTokenizer::default().try_set_field_option("SORTABLE", &mut std::iter::empty()),
+        Ok(false)
+    );
+}
+
+#[test]
+fn test_trim() {
+    let value = b" a ;  b;";
+    let tags = |trim: &str| {
+        let tokenizer = set_options(&format!("SEPARATOR ; TRIM {trim}")).unwrap();
+        tokenizer.tokenize(value)
+    };
+    assert_eq!(tags("both"), [b"a".to_vec(), b"b".to_vec()]);
+    assert_eq!(tags("LEFT"), [b"a ".to_vec(), b"b".to_vec()]);
+    assert_eq!(tags("RIGHT"), [b" a".to_vec(), b"  b".to_vec()]);
+    assert_eq!(tags("NONE"), [b" a ".to_vec(), b"  b".to_vec()]);
+    assert_eq!(
+        set_options("TRIM").unwrap_err().to_string(),
+        "Missing argument for TRIM"
+    );
+    assert_eq!(
+        set_options("TRIM all").unwrap_err().to_string(),
+        "Invalid value for TRIM: all"
+    );
+    assert_eq!(TagTokenizer::default().trim, Trim::Both);
+}
+
+#[test]
+fn test_index_binary_values() {
+    let mut index = TagIndex::default();
+    let with_nul = b"ab\0c".as_slice();
+    assert_eq!(index.index(&[with_nul, b"ab", &UUID], 1), 3);
+    assert_eq!(index.index(&[with_nul, with_nul], 2), 0);
+    // Values sharing a prefix up to the NUL are distinct.
+    assert_eq!(index.docs(with_nul), [1, 2]);
+    assert_eq!(index.docs(b"ab"), [1]);
+    assert_eq!(index.docs(b"ab\0"), [] as [u64; 0]);
+    assert_eq!(
+        index.prefixed(b"ab").collect::<Vec<_>>(),
+        [b"ab".as_slice(), with_nul]
+    );
+
+    assert!(index.remove(b"ab", 1));
+    assert!(!index.remove(b"ab", 1));
+    assert_eq!(index.len(), 2);
+    assert_eq!(index.values().collect::<Vec<_>>(), [&UUID[..], with_nul]);
+}
+
+#[test]
+fn test_escaping() {
+    assert_eq!(
+        unescape(r"user\@example\.com", 2).unwrap(),
+        b"user@example.com"
+    );
+    assert_eq!(unescape(r"a\x00b\xFF", 4).unwrap(), b"a\0b\xff");
+    // Byte escapes are new in dialect 4.
+    assert_eq!(unescape(r"\x00", 3).unwrap(), b"x00");
+    assert_eq!(unescape(r"\x0", 4), Err(EscapeError::BadByteEscape));
+    assert_eq!(unescape(r"ab\", 2), Err(EscapeError::TrailingBackslash));
+    assert_eq!(unescape(r"caf\é", 2).unwrap(), "café".as_bytes());
+
+    assert_eq!(escape(b"a b\0\xff.\xc3\xa9"), r"a\ b\x00\xff\.é");
+    for value in [&UUID[..], b"a|b{c}", "naïve\t".as_bytes()] {
+        assert_eq!(unescape(&escape(value), 4).unwrap(), value);
+    }
+}
+
+#[test]
+fn test_encode_bulk() {
+    assert_eq!(encode_bulk(b"a\0b"), b"$3\r\na\0b\r\n");
+    assert_eq!(encode_bulk(b""), b"$0\r\n\r\n");
+}
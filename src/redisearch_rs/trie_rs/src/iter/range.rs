@@ -12,9 +12,10 @@ use std::{cmp::Ordering, fmt::Write};
 use crate::{node::Node, utils::longest_common_prefix};
 
 /// Iterates over the entries of a [`TrieMap`](crate::TrieMap) between the specified `min` and `max`,
-/// in lexicographical order.
+/// in lexicographical order, or in reverse lexicographical order.
 ///
-/// Invoke [`TrieMap::range_iter`](crate::TrieMap::range_iter) to create an instance of this iterator.
+/// Invoke [`TrieMap::range_iter`](crate::TrieMap::range_iter) or
+/// [`TrieMap::rev_range_iter`](crate::TrieMap::rev_range_iter) to create an instance of this iterator.
 pub struct RangeIter<'tm, Data> {
     /// Stack of nodes and whether they have been visited.
     stack: Vec<StackEntry<'tm, Data>>,
@@ -29,6 +30,11 @@ pub struct RangeIter<'tm, Data> {
     ///
     /// It is only taken into account if the range specifies a maximum boundary.
     is_max_included: bool,
+    /// Whether entries are yielded in reverse lexicographical order.
+    reverse: bool,
+    /// The length of the label to remove from `key` on the next call to
+    /// [`Self::advance`], in reverse order.
+    pending_truncate: usize,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -92,6 +98,8 @@ impl std::fmt::Display for RangeFilter<'_> {
 struct StackEntry<'a, Data> {
     node: &'a Node<Data>,
     was_visited: bool,
+    /// Whether to yield the node's entry once its descendants have been visited.
+    yield_on_exit: bool,
     min: Option<&'a [u8]>,
     max: Option<&'a [u8]>,
 }
@@ -166,6 +174,7 @@ impl<'tm, Data> RangeIter<'tm, Data> {
                 .map(|node| StackEntry {
                     node,
                     was_visited: false,
+                    yield_on_exit: false,
                     min: range.min.map(|m| m.value),
                     max: range.max.map(|m| m.value),
                 })
@@ -173,9 +182,17 @@ impl<'tm, Data> RangeIter<'tm, Data> {
             key: prefix,
             is_min_included: range.min.map(|m| m.is_included).unwrap_or(false),
             is_max_included: range.max.map(|m| m.is_included).unwrap_or(false),
+            reverse: false,
+            pending_truncate: 0,
         }
     }
 
+    /// Yield the entries in reverse lexicographical order instead.
+    pub(crate) const fn reversed(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
     /// The current key, obtained by concatenating the labels of the nodes
     /// between the root and the current node.
     pub(crate) fn key(&self) -> &[u8] {
@@ -185,33 +202,34 @@ impl<'tm, Data> RangeIter<'tm, Data> {
     /// Advance this iterator to the next node, and set the
     /// key to the one matching that node's entry
     pub(crate) fn advance(&mut self) -> Option<&'tm Data> {
+        if self.pending_truncate > 0 {
+            // The label of the entry yielded last, in reverse order.
+            self.key.truncate(self.key.len() - self.pending_truncate);
+            self.pending_truncate = 0;
+        }
         loop {
             let StackEntry {
                 node,
                 was_visited,
+                yield_on_exit,
                 min,
                 max,
             } = self.stack.pop()?;
 
             if was_visited {
                 // We have now visited this node and all its descendants.
+                if yield_on_exit && let Some(data) = node.data() {
+                    // In reverse order, a node comes after its descendants.
+                    // Its label is removed from the key on the next call.
+                    self.pending_truncate = node.label_len() as usize;
+                    return Some(data);
+                }
                 // We restore the key to the value matching its parent.
                 self.key
                     .truncate(self.key.len() - node.label_len() as usize);
                 continue;
             }
 
-            self.key.extend(node.label());
-            // Push the current node into the stack to remember, once all
-            // its descendants have been visited, to remove its label
-            // from the key buffer.
-            self.stack.push(StackEntry {
-                node,
-                was_visited: true,
-                min,
-                max,
-            });
-
             let mut child_min = None;
             let mut child_max = None;
             let mut yield_current = true;
@@ -289,71 +307,93 @@ impl<'tm, Data> RangeIter<'tm, Data> {
                 }
             }
 
-            if visit_descendants {
-                self.stack.reserve(node.children().len());
+            self.key.extend(node.label());
+            // Push the current node into the stack to remember, once all
+            // its descendants have been visited, to remove its label
+            // from the key buffer.
+            self.stack.push(StackEntry {
+                node,
+                was_visited: true,
+                yield_on_exit: self.reverse && yield_current,
+                min,
+                max,
+            });
 
-                let mut max_index = node.children().len();
-                if let Some(max) = child_max
-                    && let Some(first) = max.first()
-                {
-                    max_index = match node.children_first_bytes().binary_search(first) {
-                        Ok(i) => {
-                            self.stack.push(StackEntry {
-                                node: &node.children()[i],
-                                was_visited: false,
-                                min: child_min,
-                                max: child_max,
-                            });
-                            i
-                        }
-                        Err(i) => i,
-                    };
-                }
+            if visit_descendants {
+                self.push_children(node, child_min, child_max);
+            }
 
-                let mut min_index = 0;
+            if !self.reverse
+                && yield_current
+                && let Some(data) = node.data()
+            {
+                return Some(data);
+            }
+        }
+    }
 
-                let mut min_entry = None;
-                if let Some(min) = child_min
-                    && let Some(first) = min.first()
-                {
-                    min_index = match node.children_first_bytes()[..max_index].binary_search(first)
-                    {
-                        Ok(i) => {
-                            min_entry = Some(StackEntry {
-                                node: &node.children()[i],
-                                was_visited: false,
-                                min: child_min,
-                                max: child_max,
-                            });
-                            i + 1
-                        }
-                        Err(i) => i,
-                    };
-                }
+    /// Push the children of `node` within the bounds onto the stack, so that
+    /// they're popped in iteration order.
+    fn push_children(
+        &mut self,
+        node: &'tm Node<Data>,
+        child_min: Option<&'tm [u8]>,
+        child_max: Option<&'tm [u8]>,
+    ) {
+        let bounded = |node| StackEntry {
+            node,
+            was_visited: false,
+            yield_on_exit: false,
+            min: child_min,
+            max: child_max,
+        };
+        self.stack.reserve(node.children().len());
 
-                for child in node
-                    .children()
-                    .iter()
-                    .skip(min_index)
-                    .rev()
-                    .skip(node.children().len() - max_index)
-                {
-                    self.stack.push(StackEntry {
-                        node: child,
-                        was_visited: false,
-                        min: None,
-                        max: None,
-                    });
+        let mut max_index = node.children().len();
+        let mut max_entry = None;
+        if let Some(max) = child_max
+            && let Some(first) = max.first()
+        {
+            max_index = match node.children_first_bytes().binary_search(first) {
+                Ok(i) => {
+                    max_entry = Some(bounded(&node.children()[i]));
+                    i
                 }
+                Err(i) => i,
+            };
+        }
 
-                if let Some(min_child) = min_entry {
-                    self.stack.push(min_child);
+        let mut min_index = 0;
+        let mut min_entry = None;
+        if let Some(min) = child_min
+            && let Some(first) = min.first()
+        {
+            min_index = match node.children_first_bytes()[..max_index].binary_search(first) {
+                Ok(i) => {
+                    min_entry = Some(bounded(&node.children()[i]));
+                    i + 1
                 }
-            }
+                Err(i) => i,
+            };
+        }
 
-            if yield_current && let Some(data) = node.data() {
-                return Some(data);
-            }
+        let unbounded = node.children()[min_index..max_index]
+            .iter()
+            .map(|node| StackEntry {
+                node,
+                was_visited: false,
+                yield_on_exit: false,
+                min: None,
+                max: None,
+            });
+        if self.reverse {
+            self.stack.extend(min_entry);
+            self.stack.extend(unbounded);
+            self.stack.extend(max_entry);
+        } else {
+            self.stack.extend(max_entry);
+            self.stack.extend(unbounded.rev());
+            self.stack.extend(min_entry);
         }
     }
 }
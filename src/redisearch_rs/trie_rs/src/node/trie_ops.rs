@@ -10,6 +10,7 @@
 //! Trie operations.
 use super::{Node, metadata::DeallocOptions};
 use crate::utils::{longest_common_prefix, strip_prefix};
+use defrag::Defragger;
 use std::cmp::Ordering;
 
 impl<Data> Node<Data> {
@@ -394,4 +395,37 @@ impl<Data> Node<Data> {
         }
         n_descendants
     }
+
+    /// Relocate the buffers of this node and of its descendants with
+    /// `defragger`, in pre-order, leaving alone the nodes before the
+    /// `cursor`-th one.
+    ///
+    /// `position` is the pre-order index of this node, and is advanced past
+    /// the nodes visited. Returns `false` if `defragger` stopped the pass, in
+    /// which case `position` is the index of the first node left.
+    pub fn defrag(
+        &mut self,
+        position: &mut usize,
+        cursor: usize,
+        defragger: &mut dyn Defragger,
+    ) -> bool {
+        if *position >= cursor {
+            if defragger.should_stop() {
+                return false;
+            }
+            let layout = self.metadata().layout();
+            // SAFETY:
+            // - The buffer was allocated with `layout` by the global allocator,
+            //   thanks to invariants 1. and 3. in [`Self::ptr`]'s documentation.
+            // - If it moved, the old pointer is overwritten just below.
+            let relocated = unsafe { defragger.relocate(self.ptr.cast(), layout) };
+            if let Some(new_ptr) = relocated {
+                self.ptr = new_ptr.cast();
+            }
+        }
+        *position += 1;
+        self.children_mut()
+            .iter_mut()
+            .all(|child| child.defrag(position, cursor, defragger))
+    }
 }
@@ -7,6 +7,7 @@
  * GNU Affero General Public License v3 (AGPLv3).
 */
 
+use defrag::{DefragStatus, Defragger};
 use wildcard::WildcardPattern;
 
 use crate::{
@@ -191,6 +192,27 @@ impl<Data> TrieMap<Data> {
         }
     }
 
+    /// Relocate the nodes of the trie with `defragger`, starting at the
+    /// `*cursor`-th node in pre-order. See [`DefragStatus`] for the
+    /// resumption protocol.
+    ///
+    /// The cursor is an index: if the trie is modified between two calls,
+    /// the pass may skip or revisit some nodes, which the next pass covers.
+    pub fn defrag(&mut self, cursor: &mut usize, defragger: &mut dyn Defragger) -> DefragStatus {
+        let mut position = 0;
+        let done = self
+            .root
+            .as_mut()
+            .is_none_or(|root| root.defrag(&mut position, *cursor, defragger));
+        if done {
+            *cursor = 0;
+            DefragStatus::Done
+        } else {
+            *cursor = position;
+            DefragStatus::Paused
+        }
+    }
+
     /// Iterate over the entries, in lexicographical key order.
     pub fn iter(&self) -> Iter<'_, Data, VisitAll> {
         Iter::new(self.root.as_ref(), vec![])
@@ -224,6 +246,12 @@ impl<Data> TrieMap<Data> {
         RangeIter::new(self.root.as_ref(), filter)
     }
 
+    /// Iterates over the entries between the specified `min` and `max`, in reverse
+    /// lexicographical order.
+    pub fn rev_range_iter<'a>(&'a self, filter: RangeFilter<'a>) -> RangeIter<'a, Data> {
+        RangeIter::new(self.root.as_ref(), filter).reversed()
+    }
+
     /// Iterate over the entries that contain the target fragment, in lexicographical key order.
     pub fn contains_iter<'a>(&'a self, target: &'a [u8]) -> ContainsIter<'a, Data> {
         ContainsIter::new(self.root.as_ref(), target)
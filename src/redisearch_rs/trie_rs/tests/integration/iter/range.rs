@@ -22,11 +22,26 @@ fn in_range<Data>(t: &TrieMap<Data>, filter: RangeFilter) -> Vec<Vec<u8>> {
         }
         keys
     };
-    let iter_keys = t.range_iter(filter).map(|(k, _)| k).collect();
+    let iter_keys: Vec<_> = t.range_iter(filter).map(|(k, _)| k).collect();
     assert_eq!(
         iter_keys, lending_keys,
         "Lending and non-lending iterator don't agree on the result set"
     );
+    let mut rev_keys: Vec<_> = t.rev_range_iter(filter).map(|(k, _)| k).collect();
+    let rev_lending_keys = {
+        let mut keys = Vec::new();
+        let mut iter: RangeLendingIter<_> = t.rev_range_iter(filter).into();
+        while let Some((key, _)) = LendingIterator::next(&mut iter) {
+            keys.push(key.to_owned());
+        }
+        keys
+    };
+    assert_eq!(rev_keys, rev_lending_keys);
+    rev_keys.reverse();
+    assert_eq!(
+        iter_keys, rev_keys,
+        "Forward and reverse iterators don't agree on the result set"
+    );
     iter_keys
 }
 
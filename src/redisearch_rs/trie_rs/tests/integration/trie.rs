@@ -7,6 +7,7 @@
  * GNU Affero General Public License v3 (AGPLv3).
 */
 
+use defrag::{DefragStatus, testing::MovingDefragger};
 use std::{ffi::c_void, ptr::NonNull};
 use trie_rs::TrieMap;
 
@@ -216,6 +217,40 @@ fn test_trie_clone() {
     assert_eq!(trie, cloned);
 }
 
+#[test]
+/// Every node is relocated once, across several incremental passes, and the
+/// entries are preserved.
+fn test_trie_defrag() {
+    let mut trie = TrieMap::new();
+    let keys: Vec<String> = (0..200).map(|i| format!("key{i}:{}", i * 7)).collect();
+    for (i, key) in keys.iter().enumerate() {
+        trie.insert(key.as_bytes(), i);
+    }
+    let expected = trie.clone();
+
+    let mut defragger = MovingDefragger::new(7);
+    let mut cursor = 0;
+    let mut passes = 0;
+    loop {
+        passes += 1;
+        if trie.defrag(&mut cursor, &mut defragger) == DefragStatus::Done {
+            break;
+        }
+        assert_ne!(cursor, 0);
+    }
+    assert!(passes > 1);
+    assert_eq!(cursor, 0);
+    assert_eq!(defragger.moves, trie.n_nodes());
+    assert_eq!(trie, expected);
+    assert_eq!(trie.mem_usage(), trie.recursive_mem_usage());
+
+    let mut empty = TrieMap::<u64>::new();
+    assert_eq!(
+        empty.defrag(&mut cursor, &mut defragger),
+        DefragStatus::Done
+    );
+}
+
 #[test]
 /// Tests whether the trie merges nodes
 /// correctly upon removal of entries.
@@ -94,6 +94,30 @@ where
     T::read_as_varint(reader)
 }
 
+/// Decode the values of a delta-encoded vector, as written by a
+/// [`VectorWriter`], and run `f` with them.
+///
+/// The values are decoded into a [scratch] buffer of the thread, so that
+/// decoding the offset vector of every result doesn't allocate.
+///
+/// # Errors
+///
+/// Returns an error if `bytes` ends in the middle of a value.
+pub fn with_decoded_vector<R>(
+    mut bytes: &[u8],
+    f: impl FnOnce(&[u32]) -> R,
+) -> Result<R, std::io::Error> {
+    scratch::with_offsets(|values| {
+        let mut last = 0u32;
+        while !bytes.is_empty() {
+            let delta: u32 = read(&mut bytes)?;
+            last = last.wrapping_add(delta);
+            values.push(last);
+        }
+        Ok(f(values))
+    })
+}
+
 /// Utilities to varint encode/decode an integer.
 pub trait VarintEncode {
     /// Encode an integer in varint format, then write it to the given writer.
@@ -141,6 +141,24 @@ fn roundtrip_values(values: &[u32]) {
     }
 }
 
+#[test]
+fn test_with_decoded_vector() {
+    let mut writer = VectorWriter::new(16);
+    for value in [3, 10, 10, 200, 70_000] {
+        writer.write(value).unwrap();
+    }
+    let sum = varint::with_decoded_vector(writer.bytes(), |values| {
+        assert_eq!(values, [3, 10, 10, 200, 70_000]);
+        values.iter().sum::<u32>()
+    })
+    .unwrap();
+    assert_eq!(sum, 70_223);
+
+    // A truncated value.
+    let bytes = &writer.bytes()[..writer.bytes_len() - 1];
+    assert!(varint::with_decoded_vector(bytes, |_| ()).is_err());
+}
+
 mod property_based {
     //! Property-based tests using random values
     #![cfg(not(miri))]
@@ -0,0 +1,249 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Validation of vector blobs, when documents are indexed and when they're
+//! given as query parameters.
+//!
+//! A blob whose length doesn't match `DIM` times the size of the vector type,
+//! or holding NaN or infinite components, would be indexed as garbage and
+//! silently degrade the results. Such documents are rejected instead, and the
+//! rejection is recorded in the [`IndexError`] of the field with the key of
+//! the document, as `FT.INFO` reports it. Zero vectors are rejected for the
+//! `COSINE` metric, for which they have no direction.
+
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// The type of the components of a vector (`TYPE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorType {
+    Float32,
+    Float64,
+    Float16,
+    BFloat16,
+    UInt8,
+    Int8,
+}
+
+impl VectorType {
+    /// The size of a component, in bytes.
+    pub const fn size(self) -> usize {
+        match self {
+            Self::Float64 => 8,
+            Self::Float32 => 4,
+            Self::Float16 | Self::BFloat16 => 2,
+            Self::UInt8 | Self::Int8 => 1,
+        }
+    }
+
+    /// Whether the component at `bytes` is NaN or infinite. Integers never are.
+    fn is_non_finite(self, bytes: &[u8]) -> bool {
+        match self {
+            Self::Float64 => !f64::from_le_bytes(bytes.try_into().expect("8 bytes")).is_finite(),
+            Self::Float32 => !f32::from_le_bytes(bytes.try_into().expect("4 bytes")).is_finite(),
+            // The exponent bits are all set.
+            Self::Float16 => u16::from_le_bytes([bytes[0], bytes[1]]) & 0x7C00 == 0x7C00,
+            Self::BFloat16 => u16::from_le_bytes([bytes[0], bytes[1]]) & 0x7F80 == 0x7F80,
+            Self::UInt8 | Self::Int8 => false,
+        }
+    }
+}
+
+impl FromStr for VectorType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s.to_ascii_uppercase().as_str() {
+            "FLOAT32" => Ok(Self::Float32),
+            "FLOAT64" => Ok(Self::Float64),
+            "FLOAT16" => Ok(Self::Float16),
+            "BFLOAT16" => Ok(Self::BFloat16),
+            "UINT8" => Ok(Self::UInt8),
+            "INT8" => Ok(Self::Int8),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The distance metric of a vector field (`DISTANCE_METRIC`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    L2,
+    Ip,
+    Cosine,
+}
+
+/// The attributes of a vector field that blobs are checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectorSpec {
+    pub vector_type: VectorType,
+    pub dim: usize,
+    pub metric: Metric,
+    /// Whether NaN and infinite components are accepted
+    /// (`VECSIM_ALLOW_NON_FINITE`), as they were before validation.
+    pub allow_non_finite: bool,
+}
+
+/// Why a blob was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlobError {
+    /// The blob isn't `DIM` components long.
+    BadSize { found: usize, expected: usize },
+    /// The component at `index` is NaN or infinite.
+    NonFinite { index: usize },
+    /// All the components are zero, and the metric is `COSINE`.
+    ZeroVector,
+}
+
+impl BlobError {
+    /// The message, without the values of the document, for the obfuscated
+    /// error of `FT.INFO`.
+    pub const fn message(&self) -> &'static str {
+        match self {
+            Self::BadSize { .. } => "Could not add vector with blob size",
+            Self::NonFinite { .. } => "Could not add vector with a NaN or infinite component",
+            Self::ZeroVector => "Could not add zero vector with the COSINE metric",
+        }
+    }
+}
+
+impl Display for BlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadSize { found, expected } => {
+                write!(f, "{} {found} (expected size {expected})", self.message())
+            }
+            Self::NonFinite { index } => write!(f, "{} at index {index}", self.message()),
+            Self::ZeroVector => f.write_str(self.message()),
+        }
+    }
+}
+
+impl std::error::Error for BlobError {}
+
+/// A query parameter holding an invalid vector blob.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryBlobError {
+    pub param: String,
+    pub error: BlobError,
+}
+
+impl Display for QueryBlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let param = &self.param;
+        match self.error {
+            BlobError::BadSize { found, expected } => write!(
+                f,
+                "Error parsing vector similarity query: query vector blob size ({found}) does not match index's expected size ({expected})."
+            ),
+            BlobError::NonFinite { index } => write!(
+                f,
+                "Error parsing vector similarity query: parameter `{param}` has a NaN or infinite component at index {index}"
+            ),
+            BlobError::ZeroVector => write!(
+                f,
+                "Error parsing vector similarity query: parameter `{param}` is a zero vector, which has no COSINE distance"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for QueryBlobError {}
+
+impl VectorSpec {
+    /// The size of a valid blob.
+    pub const fn blob_size(&self) -> usize {
+        self.dim * self.vector_type.size()
+    }
+
+    /// Check a blob of one vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`BlobError`] if the blob has the wrong size, has non-finite
+    /// components that aren't allowed, or is a zero vector for `COSINE`.
+    pub fn validate(&self, blob: &[u8]) -> Result<(), BlobError> {
+        if blob.len() != self.blob_size() {
+            return Err(BlobError::BadSize {
+                found: blob.len(),
+                expected: self.blob_size(),
+            });
+        }
+        let mut components = blob.chunks_exact(self.vector_type.size());
+        if !self.allow_non_finite
+            && let Some(index) = components
+                .clone()
+                .position(|c| self.vector_type.is_non_finite(c))
+        {
+            return Err(BlobError::NonFinite { index });
+        }
+        // Only the sign bit, in the last byte, may be set in a zero float.
+        let sign_mask = match self.vector_type {
+            VectorType::UInt8 | VectorType::Int8 => 0xFF,
+            _ => 0x7F,
+        };
+        if self.metric == Metric::Cosine
+            && components.all(|c| {
+                let (last, rest) = c.split_last().expect("components aren't empty");
+                last & sign_mask == 0 && rest.iter().all(|&b| b == 0)
+            })
+        {
+            return Err(BlobError::ZeroVector);
+        }
+        Ok(())
+    }
+
+    /// Check the blob given as the query parameter `param`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`QueryBlobError`] if [`validate`](Self::validate) fails.
+    pub fn validate_query_param(&self, param: &str, blob: &[u8]) -> Result<(), QueryBlobError> {
+        self.validate(blob).map_err(|error| QueryBlobError {
+            param: param.to_owned(),
+            error,
+        })
+    }
+}
+
+/// The indexing errors of a field, the Rust counterpart of `IndexError` in
+/// `info/index_error.h`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexError {
+    pub error_count: usize,
+    /// The last error, with the values of the document.
+    pub last_error_with_user_data: Option<String>,
+    /// The last error, without the values of the document.
+    pub last_error_without_user_data: Option<&'static str>,
+    /// The key of the document of the last error.
+    pub key: Option<String>,
+}
+
+impl IndexError {
+    pub fn add_blob_error(&mut self, error: &BlobError, key: &str) {
+        self.error_count += 1;
+        self.last_error_with_user_data = Some(error.to_string());
+        self.last_error_without_user_data = Some(error.message());
+        self.key = Some(key.to_owned());
+    }
+
+    /// Check the blob of the document `key`, recording the error if it's
+    /// invalid. Returns whether the vector can be indexed.
+    pub fn check(&mut self, spec: &VectorSpec, key: &str, blob: &[u8]) -> bool {
+        match spec.validate(blob) {
+            Ok(()) => true,
+            Err(error) => {
+                self.add_blob_error(&error, key);
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Top-k search over documents holding several vectors, as chunked text
+//! embedded chunk by chunk.
+//!
+//! Every vector of a document is a candidate of the graph search; the
+//! distances of a document's vectors are then combined into the distance of
+//! the document, as chosen by the `$AGGREGATION` query attribute. The index of
+//! the closest vector of each result is loadable as the pseudo-field
+//! [`BEST_ELEMENT_FIELD`].
+
+use std::collections::HashMap;
+
+use query::node::VectorAggregation;
+
+/// The pseudo-field holding the index, in the document's array of vectors,
+/// of the vector closest to the query.
+pub const BEST_ELEMENT_FIELD: &str = "__vector_element";
+
+/// A document returned by [`MultiVectorKnn`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KnnResult {
+    pub doc: u64,
+    /// The aggregated distance of the document's vectors.
+    pub distance: f64,
+    /// The index of the closest vector of the document.
+    pub best_element: usize,
+}
+
+impl KnnResult {
+    /// The value of the pseudo-field `name`, if it's one of this module's.
+    pub fn pseudo_field(&self, name: &str) -> Option<usize> {
+        (name == BEST_ELEMENT_FIELD).then_some(self.best_element)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Distances {
+    min: f64,
+    max: f64,
+    sum: f64,
+    count: usize,
+    best_element: usize,
+}
+
+/// Collects the distances of the vectors found by the graph search, and
+/// returns the `k` documents with the smallest aggregated distances.
+#[derive(Debug, Clone)]
+pub struct MultiVectorKnn {
+    k: usize,
+    aggregation: VectorAggregation,
+    docs: HashMap<u64, Distances>,
+}
+
+impl MultiVectorKnn {
+    /// `aggregation` is the `$AGGREGATION` attribute of the KNN node, if any;
+    /// by default a document is as close as its closest vector.
+    pub fn new(k: usize, aggregation: Option<VectorAggregation>) -> Self {
+        Self {
+            k,
+            aggregation: aggregation.unwrap_or(VectorAggregation::Min),
+            docs: HashMap::new(),
+        }
+    }
+
+    /// Record that the vector at `element` in `doc`'s array is at `distance`
+    /// from the query.
+    pub fn push(&mut self, doc: u64, element: usize, distance: f64) {
+        let entry = self.docs.entry(doc).or_insert(Distances {
+            min: distance,
+            max: distance,
+            sum: 0.0,
+            count: 0,
+            best_element: element,
+        });
+        if distance < entry.min || (distance == entry.min && element < entry.best_element) {
+            entry.min = distance;
+            entry.best_element = element;
+        }
+        entry.max = entry.max.max(distance);
+        entry.sum += distance;
+        entry.count += 1;
+    }
+
+    /// The `k` closest documents, closest first. Documents at the same
+    /// distance are ordered by ID.
+    pub fn finish(self) -> Vec<KnnResult> {
+        let aggregation = self.aggregation;
+        let mut results: Vec<_> = self
+            .docs
+            .into_iter()
+            .map(|(doc, d)| KnnResult {
+                doc,
+                distance: match aggregation {
+                    VectorAggregation::Min => d.min,
+                    VectorAggregation::Max => d.max,
+                    VectorAggregation::Avg => d.sum / d.count as f64,
+                },
+                best_element: d.best_element,
+            })
+            .collect();
+        results.sort_by(|a, b| a.distance.total_cmp(&b.distance).then(a.doc.cmp(&b.doc)));
+        results.truncate(self.k);
+        results
+    }
+}
@@ -0,0 +1,199 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The graph of an HNSW or SVS-Vamana vector index, persisted to RDB so that
+//! it doesn't have to be rebuilt from the raw vectors after a restart.
+//!
+//! The graph is saved in [chunks](persist) of at most [`CHUNK_NODES`] nodes,
+//! each followed by its CRC32, so that no RDB string gets too large and
+//! corruption is detected before the graph is used. Whether a corrupt graph
+//! fails the load or is rebuilt from the vectors is set by
+//! [`CorruptGraphPolicy`].
+//!
+//! Vectors are [validated](blob) before being added.
+//!
+//! Deleted nodes stay in the graph as tombstones, which searches traverse
+//! but don't return, until they're [repaired](tombstones) away.
+//!
+//! Documents holding several vectors are ranked by [`knn::MultiVectorKnn`].
+
+pub mod blob;
+pub mod knn;
+pub mod persist;
+pub mod tombstones;
+
+use std::str::FromStr;
+
+use defrag::{Defrag, DefragStatus, Defragger};
+
+pub use blob::{BlobError, IndexError, Metric, VectorSpec, VectorType};
+pub use knn::{BEST_ELEMENT_FIELD, KnnResult, MultiVectorKnn};
+pub use persist::{CHUNK_NODES, GRAPH_FORMAT_VERSION, LoadedGraph, load_graph};
+pub use tombstones::{RepairProgress, TombstoneConfig};
+
+/// The ID of a node, which is the ID of its vector in the index.
+pub type NodeId = u32;
+
+/// The algorithm which built the graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphKind {
+    /// A hierarchy of levels, each node being in the levels up to its own.
+    Hnsw,
+    /// A single level.
+    Vamana,
+}
+
+/// The neighbors of every node of a vector index, level by level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorGraph {
+    kind: GraphKind,
+    entry_point: Option<NodeId>,
+    nodes: Vec<Node>,
+    repair: tombstones::RepairCursor,
+}
+
+/// Whether a node is live or deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeState {
+    Live,
+    /// Deleted, but still linked from other nodes.
+    Tombstone,
+    /// Deleted and unlinked, its neighbors having been dropped.
+    Compacted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Node {
+    state: NodeState,
+    /// The neighbors on each of the node's levels, from level 0.
+    levels: Vec<Vec<NodeId>>,
+}
+
+impl Defrag for Node {
+    fn defrag(&mut self, defragger: &mut dyn Defragger) -> bool {
+        let mut moved = self.levels.defrag(defragger);
+        for level in &mut self.levels {
+            moved |= level.defrag(defragger);
+        }
+        moved
+    }
+}
+
+impl VectorGraph {
+    pub const fn new(kind: GraphKind) -> Self {
+        Self {
+            kind,
+            entry_point: None,
+            nodes: Vec::new(),
+            repair: tombstones::RepairCursor::new(),
+        }
+    }
+
+    pub const fn kind(&self) -> GraphKind {
+        self.kind
+    }
+
+    /// The node searches start from.
+    pub const fn entry_point(&self) -> Option<NodeId> {
+        self.entry_point
+    }
+
+    pub const fn set_entry_point(&mut self, node: Option<NodeId>) {
+        self.entry_point = node;
+    }
+
+    /// Add a node with its neighbors on each of its levels, and return its ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a Vamana node has more than one level.
+    pub fn add_node(&mut self, levels: Vec<Vec<NodeId>>) -> NodeId {
+        assert!(
+            self.kind == GraphKind::Hnsw || levels.len() <= 1,
+            "Vamana graphs have a single level"
+        );
+        self.nodes.push(Node {
+            state: NodeState::Live,
+            levels,
+        });
+        (self.nodes.len() - 1) as NodeId
+    }
+
+    pub const fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The number of levels of `node`.
+    pub fn levels(&self, node: NodeId) -> usize {
+        self.nodes[node as usize].levels.len()
+    }
+
+    pub fn state(&self, node: NodeId) -> NodeState {
+        self.nodes[node as usize].state
+    }
+
+    /// The neighbors of `node` on `level`, empty if it isn't on that level.
+    pub fn neighbors(&self, node: NodeId, level: usize) -> &[NodeId] {
+        self.nodes[node as usize]
+            .levels
+            .get(level)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// The memory size of the graph in bytes, excluding the vectors.
+    pub fn memory_usage(&self) -> usize {
+        let nodes: usize = self
+            .nodes
+            .iter()
+            .map(|node| {
+                node.levels.capacity() * size_of::<Vec<NodeId>>()
+                    + node
+                        .levels
+                        .iter()
+                        .map(|level| level.capacity() * size_of::<NodeId>())
+                        .sum::<usize>()
+            })
+            .sum();
+        self.nodes.capacity() * size_of::<Node>() + nodes + self.repair.memory_usage()
+    }
+
+    /// Relocate the neighbor lists with `defragger`, starting at node
+    /// `*cursor`. See [`DefragStatus`] for the resumption protocol.
+    pub fn defrag(&mut self, cursor: &mut usize, defragger: &mut dyn Defragger) -> DefragStatus {
+        defrag::defrag_items(&mut self.nodes, cursor, defragger).1
+    }
+}
+
+/// What to do when the persisted graph of an index is corrupt
+/// (`VECSIM_GRAPH_ON_CORRUPT`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CorruptGraphPolicy {
+    /// Fail the load, as for any other corrupt data.
+    Fail,
+    /// Drop the graph and rebuild it from the vectors, as if it hadn't been
+    /// persisted.
+    #[default]
+    Rebuild,
+}
+
+impl FromStr for CorruptGraphPolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        match s.to_ascii_lowercase().as_str() {
+            "fail" => Ok(Self::Fail),
+            "rebuild" => Ok(Self::Rebuild),
+            _ => Err(()),
+        }
+    }
+}
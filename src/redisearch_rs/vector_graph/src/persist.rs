@@ -0,0 +1,226 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! The RDB encoding of a [`VectorGraph`].
+//!
+//! The header holds the [`GRAPH_FORMAT_VERSION`], the kind of graph, the
+//! number of nodes and the entry point. Then come the nodes, in chunks of
+//! [`CHUNK_NODES`], each saved as a string buffer followed by its CRC32. Each
+//! node is encoded as its [state](NodeState) and its number of levels, then
+//! for each level the number of neighbors and their IDs, all as little-endian
+//! `u32`s.
+//!
+//! The framing doesn't depend on the contents of the chunks, so a corrupt or
+//! unknown graph can be skipped entirely, leaving the rest of the RDB
+//! readable.
+
+use deferred::Deferred;
+use rdb_format::{FormatError, FormatVersion, Persist, RdbRead, RdbWrite, Requirement};
+
+use crate::{CorruptGraphPolicy, GraphKind, Node, NodeId, NodeState, VectorGraph, tombstones};
+
+/// The version of the encoding of the chunks, checked on load.
+pub const GRAPH_FORMAT_VERSION: u64 = 1;
+
+/// The maximum number of nodes saved in a chunk.
+pub const CHUNK_NODES: usize = 4096;
+
+const NO_ENTRY_POINT: u64 = u64::MAX;
+
+impl Persist for VectorGraph {
+    fn requirements(&self) -> Vec<Requirement> {
+        vec![Requirement {
+            feature: "Persisted vector graphs",
+            since: FormatVersion::VECSIM_GRAPH,
+        }]
+    }
+
+    fn save(&self, rdb: &mut dyn RdbWrite, _version: FormatVersion) {
+        rdb.save_unsigned(GRAPH_FORMAT_VERSION);
+        rdb.save_unsigned(match self.kind {
+            GraphKind::Hnsw => 0,
+            GraphKind::Vamana => 1,
+        });
+        rdb.save_unsigned(self.nodes.len() as u64);
+        rdb.save_unsigned(self.entry_point.map_or(NO_ENTRY_POINT, u64::from));
+        for chunk in self.nodes.chunks(CHUNK_NODES) {
+            let mut buffer = Vec::new();
+            for node in chunk {
+                push_u32(
+                    &mut buffer,
+                    match node.state {
+                        NodeState::Live => 0,
+                        NodeState::Tombstone => 1,
+                        NodeState::Compacted => 2,
+                    },
+                );
+                push_u32(&mut buffer, node.levels.len());
+                for neighbors in &node.levels {
+                    push_u32(&mut buffer, neighbors.len());
+                    for &neighbor in neighbors {
+                        buffer.extend_from_slice(&neighbor.to_le_bytes());
+                    }
+                }
+            }
+            rdb.save_string_buffer(&buffer);
+            rdb.save_unsigned(u64::from(crc32fast::hash(&buffer)));
+        }
+    }
+
+    fn load(rdb: &mut dyn RdbRead, _version: FormatVersion) -> Result<Self, FormatError> {
+        SavedGraph::read(rdb)?.decode()
+    }
+}
+
+fn push_u32(buffer: &mut Vec<u8>, value: usize) {
+    let value = u32::try_from(value).expect("graph sizes fit in u32");
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+/// A graph as read from the RDB, before its chunks are checked and decoded.
+struct SavedGraph {
+    format: u64,
+    kind: u64,
+    len: u64,
+    entry_point: u64,
+    chunks: Vec<(Vec<u8>, u64)>,
+}
+
+impl SavedGraph {
+    /// Read the whole graph, failing only if the RDB itself is truncated or
+    /// mistyped.
+    fn read(rdb: &mut dyn RdbRead) -> Result<Self, FormatError> {
+        let format = rdb.load_unsigned()?;
+        let kind = rdb.load_unsigned()?;
+        let len = rdb.load_unsigned()?;
+        let entry_point = rdb.load_unsigned()?;
+        let chunks = (0..len.div_ceil(CHUNK_NODES as u64))
+            .map(|_| Ok((rdb.load_string_buffer()?, rdb.load_unsigned()?)))
+            .collect::<Result<_, FormatError>>()?;
+        Ok(Self {
+            format,
+            kind,
+            len,
+            entry_point,
+            chunks,
+        })
+    }
+
+    fn decode(self) -> Result<VectorGraph, FormatError> {
+        if self.format != GRAPH_FORMAT_VERSION {
+            return Err(FormatError::Corrupt("unknown vector graph format"));
+        }
+        let kind = match self.kind {
+            0 => GraphKind::Hnsw,
+            1 => GraphKind::Vamana,
+            _ => return Err(FormatError::Corrupt("unknown vector graph kind")),
+        };
+        let len = NodeId::try_from(self.len)
+            .map_err(|_| FormatError::Corrupt("too many vector graph nodes"))?;
+        let entry_point = match self.entry_point {
+            NO_ENTRY_POINT => None,
+            node if node < u64::from(len) => Some(node as NodeId),
+            _ => {
+                return Err(FormatError::Corrupt(
+                    "vector graph entry point out of range",
+                ));
+            }
+        };
+
+        let mut nodes = Vec::with_capacity(len as usize);
+        for (buffer, checksum) in &self.chunks {
+            if u64::from(crc32fast::hash(buffer)) != *checksum {
+                return Err(FormatError::Corrupt("vector graph chunk checksum mismatch"));
+            }
+            let expected = (len as usize - nodes.len()).min(CHUNK_NODES);
+            let mut chunk = buffer.as_slice();
+            for _ in 0..expected {
+                let state = match take_u32(&mut chunk)? {
+                    0 => NodeState::Live,
+                    1 => NodeState::Tombstone,
+                    2 => NodeState::Compacted,
+                    _ => return Err(FormatError::Corrupt("unknown vector graph node state")),
+                };
+                let levels = (0..take_u32(&mut chunk)?)
+                    .map(|_| {
+                        (0..take_u32(&mut chunk)?)
+                            .map(|_| match take_u32(&mut chunk)? {
+                                neighbor if neighbor < len => Ok(neighbor),
+                                _ => {
+                                    Err(FormatError::Corrupt("vector graph neighbor out of range"))
+                                }
+                            })
+                            .collect()
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if kind == GraphKind::Vamana && levels.len() > 1 {
+                    return Err(FormatError::Corrupt(
+                        "Vamana graph node with several levels",
+                    ));
+                }
+                nodes.push(Node { state, levels });
+            }
+            if !chunk.is_empty() {
+                return Err(FormatError::Corrupt("trailing data in vector graph chunk"));
+            }
+        }
+        Ok(VectorGraph {
+            kind,
+            entry_point,
+            nodes,
+            repair: tombstones::RepairCursor::new(),
+        })
+    }
+}
+
+const fn take_u32(chunk: &mut &[u8]) -> Result<u32, FormatError> {
+    let Some((bytes, rest)) = chunk.split_first_chunk() else {
+        return Err(FormatError::Corrupt("truncated vector graph chunk"));
+    };
+    *chunk = rest;
+    Ok(u32::from_le_bytes(*bytes))
+}
+
+/// A graph loaded by [`load_graph`].
+pub struct LoadedGraph {
+    pub graph: Deferred<VectorGraph>,
+    /// Why the persisted graph was dropped, if it was corrupt and is rebuilt
+    /// instead, to be logged.
+    pub corruption: Option<FormatError>,
+}
+
+/// Load a graph saved with `version`. If it's corrupt and `policy` is
+/// [`CorruptGraphPolicy::Rebuild`], the graph is skipped and `rebuild` builds
+/// it from the vectors when it's first needed, or when the
+/// [`RebuildQueue`](deferred::RebuildQueue) gets to it.
+///
+/// # Errors
+///
+/// Returns a [`FormatError`] if `version` can't be loaded, if the RDB is
+/// truncated, or if the graph is corrupt and `policy` is
+/// [`CorruptGraphPolicy::Fail`].
+pub fn load_graph(
+    rdb: &mut dyn RdbRead,
+    version: FormatVersion,
+    policy: CorruptGraphPolicy,
+    rebuild: impl FnOnce() -> VectorGraph + Send + 'static,
+) -> Result<LoadedGraph, FormatError> {
+    version.check_loadable()?;
+    match (SavedGraph::read(rdb)?.decode(), policy) {
+        (Ok(graph), _) => Ok(LoadedGraph {
+            graph: Deferred::ready(graph),
+            corruption: None,
+        }),
+        (Err(err), CorruptGraphPolicy::Rebuild) => Ok(LoadedGraph {
+            graph: Deferred::new(rebuild),
+            corruption: Some(err),
+        }),
+        (Err(err), CorruptGraphPolicy::Fail) => Err(err),
+    }
+}
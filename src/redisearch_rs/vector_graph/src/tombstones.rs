@@ -0,0 +1,237 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! Repair of the links to deleted nodes.
+//!
+//! Deleting a vector leaves a tombstone in the graph, which searches still
+//! traverse. After heavy churn, the tombstones take a large part of every
+//! search, and recall degrades as live nodes lose their live neighbors.
+//!
+//! A repair pass replaces, in the neighbors of each live node, the tombstones
+//! with their own live neighbors, then compacts the tombstones: they're
+//! unlinked and their neighbors dropped. Passes are incremental, so that the
+//! worker pool can run them in short steps: once the ratio of tombstones
+//! reaches [`TombstoneConfig::deleted_ratio`], the graph reports a
+//! [`Candidate`] to the compaction [`Scheduler`](compaction::Scheduler),
+//! whose task runs [`VectorGraph::repair_step`]. [`VectorGraph::repair`]
+//! runs a whole pass at once, regardless of the ratio, as
+//! `FT.DEBUG VECSIM_REPAIR` does for the tiered VecSim indexes of an index,
+//! on the worker pool.
+
+use compaction::{Candidate, TaskKind};
+
+use crate::{NodeId, NodeState, VectorGraph};
+
+/// When and how fast to repair the graphs (`VECSIM_TOMBSTONE_RATIO` and
+/// `VECSIM_REPAIR_BATCH`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TombstoneConfig {
+    /// The ratio of tombstones above which the graph is repaired.
+    pub deleted_ratio: f64,
+    /// The number of nodes repaired per step.
+    pub batch: usize,
+}
+
+impl Default for TombstoneConfig {
+    fn default() -> Self {
+        Self {
+            deleted_ratio: 0.2,
+            batch: 1024,
+        }
+    }
+}
+
+/// The progress of a repair pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RepairProgress {
+    /// The live nodes whose neighbors were checked.
+    pub scanned: usize,
+    /// The links to tombstones replaced.
+    pub relinked: usize,
+    /// The tombstones compacted, at the end of the pass.
+    pub compacted: usize,
+    /// Whether the pass is over.
+    pub done: bool,
+}
+
+/// Where the current pass is.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct RepairCursor {
+    next: usize,
+    /// The tombstones when the pass started, which it compacts. Nodes deleted
+    /// since may still be linked from nodes already scanned.
+    tombstones: Vec<NodeId>,
+}
+
+impl RepairCursor {
+    pub(crate) const fn new() -> Self {
+        Self {
+            next: 0,
+            tombstones: Vec::new(),
+        }
+    }
+
+    pub(crate) const fn memory_usage(&self) -> usize {
+        self.tombstones.capacity() * size_of::<NodeId>()
+    }
+}
+
+/// The estimated size of a link, in bytes.
+const LINK_SIZE: u64 = size_of::<NodeId>() as u64;
+
+impl VectorGraph {
+    /// Delete `node`, leaving a tombstone. Returns `false` if it was already
+    /// deleted.
+    pub fn delete(&mut self, node: NodeId) -> bool {
+        let state = &mut self.nodes[node as usize].state;
+        if *state != NodeState::Live {
+            return false;
+        }
+        *state = NodeState::Tombstone;
+        if self.entry_point == Some(node) {
+            // The live node on the most levels, as HNSW would have picked.
+            self.entry_point = (0..self.nodes.len() as NodeId)
+                .filter(|&n| self.state(n) == NodeState::Live)
+                .max_by_key(|&n| (self.levels(n), std::cmp::Reverse(n)));
+        }
+        true
+    }
+
+    /// The number of tombstones, not compacted yet.
+    pub fn tombstones(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|n| n.state == NodeState::Tombstone)
+            .count()
+    }
+
+    /// The ratio of tombstones among the nodes.
+    pub fn deleted_ratio(&self) -> f64 {
+        if self.nodes.is_empty() {
+            return 0.0;
+        }
+        self.tombstones() as f64 / self.nodes.len() as f64
+    }
+
+    /// The compaction task of the field `field` of `index`, if its ratio of
+    /// tombstones reached the threshold of `config`.
+    pub fn compaction_candidate(
+        &self,
+        index: &str,
+        field: &str,
+        config: &TombstoneConfig,
+    ) -> Option<Candidate> {
+        if self.deleted_ratio() < config.deleted_ratio {
+            return None;
+        }
+        let links = |state: Option<NodeState>| {
+            self.nodes
+                .iter()
+                .filter(|n| state.is_none_or(|s| n.state == s))
+                .flat_map(|n| &n.levels)
+                .map(|l| l.len() as u64)
+                .sum::<u64>()
+        };
+        Some(Candidate {
+            index: index.to_owned(),
+            kind: TaskKind::RewriteVectorRegion,
+            target: field.to_owned(),
+            cost: links(None) * LINK_SIZE,
+            benefit: links(Some(NodeState::Tombstone)) * LINK_SIZE,
+        })
+    }
+
+    /// Repair up to `max_nodes` nodes, continuing the current pass or
+    /// starting a new one. The returned progress is that of this step.
+    pub fn repair_step(&mut self, max_nodes: usize) -> RepairProgress {
+        if self.repair.next == 0 {
+            self.repair.tombstones = (0..self.nodes.len() as NodeId)
+                .filter(|&n| self.state(n) == NodeState::Tombstone)
+                .collect();
+        }
+        let mut progress = RepairProgress::default();
+        while progress.scanned < max_nodes && self.repair.next < self.nodes.len() {
+            let node = self.repair.next;
+            self.repair.next += 1;
+            if self.nodes[node].state != NodeState::Live {
+                continue;
+            }
+            progress.scanned += 1;
+            for level in 0..self.nodes[node].levels.len() {
+                progress.relinked += self.relink(node as NodeId, level);
+            }
+        }
+        if self.repair.next < self.nodes.len() {
+            return progress;
+        }
+
+        // Nothing links to the tombstones of the pass anymore.
+        for &tombstone in &std::mem::take(&mut self.repair.tombstones) {
+            let node = &mut self.nodes[tombstone as usize];
+            if node.state == NodeState::Tombstone {
+                node.state = NodeState::Compacted;
+                node.levels = Vec::new();
+                progress.compacted += 1;
+            }
+        }
+        self.repair.next = 0;
+        progress.done = true;
+        progress
+    }
+
+    /// Run a whole repair pass, or the rest of the current one.
+    pub fn repair(&mut self) -> RepairProgress {
+        let mut total = RepairProgress::default();
+        loop {
+            let step = self.repair_step(usize::MAX);
+            total.scanned += step.scanned;
+            total.relinked += step.relinked;
+            total.compacted += step.compacted;
+            if step.done {
+                total.done = true;
+                return total;
+            }
+        }
+    }
+
+    /// Replace the deleted neighbors of `node` on `level` by their live
+    /// neighbors, keeping the number of neighbors. Returns the number of
+    /// deleted neighbors replaced.
+    fn relink(&mut self, node: NodeId, level: usize) -> usize {
+        let neighbors = &self.nodes[node as usize].levels[level];
+        if neighbors.iter().all(|&n| self.state(n) == NodeState::Live) {
+            return 0;
+        }
+        let degree = neighbors.len();
+        let mut live: Vec<NodeId> = Vec::with_capacity(degree);
+        let mut replacements = Vec::new();
+        let mut relinked = 0;
+        for &neighbor in neighbors {
+            if self.state(neighbor) == NodeState::Live {
+                live.push(neighbor);
+            } else {
+                relinked += 1;
+                replacements.extend_from_slice(self.neighbors(neighbor, level));
+            }
+        }
+        for candidate in replacements {
+            if live.len() == degree {
+                break;
+            }
+            if candidate != node
+                && self.state(candidate) == NodeState::Live
+                && !live.contains(&candidate)
+            {
+                live.push(candidate);
+            }
+        }
+        self.nodes[node as usize].levels[level] = live;
+        relinked
+    }
+}
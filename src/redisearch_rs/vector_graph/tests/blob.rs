@@ -0,0 +1,132 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use vector_graph::{BlobError, IndexError, Metric, VectorSpec, VectorType, blob::QueryBlobError};
+
+const fn spec(vector_type: VectorType, metric: Metric) -> VectorSpec {
+    VectorSpec {
+        vector_type,
+        dim: 3,
+        metric,
+        allow_non_finite: false,
+    }
+}
+
+fn f32_blob(values: [f32; 3]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+#[test]
+fn test_size() {
+    let spec = spec(VectorType::Float32, Metric::L2);
+    assert_eq!(spec.validate(&f32_blob([1.0, 2.0, 3.0])), Ok(()));
+    let err = spec.validate(&[0; 8]).unwrap_err();
+    assert_eq!(
+        err,
+        BlobError::BadSize {
+            found: 8,
+            expected: 12
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Could not add vector with blob size 8 (expected size 12)"
+    );
+    assert_eq!("bfloat16".parse::<VectorType>().unwrap().size(), 2);
+}
+
+#[test]
+fn test_non_finite() {
+    let mut floats = spec(VectorType::Float32, Metric::L2);
+    assert_eq!(
+        floats.validate(&f32_blob([1.0, f32::NAN, 3.0])),
+        Err(BlobError::NonFinite { index: 1 })
+    );
+    assert_eq!(
+        floats.validate(&f32_blob([1.0, 2.0, f32::NEG_INFINITY])),
+        Err(BlobError::NonFinite { index: 2 })
+    );
+    floats.allow_non_finite = true;
+    assert_eq!(floats.validate(&f32_blob([1.0, f32::NAN, 3.0])), Ok(()));
+
+    // Half-precision infinity and NaN.
+    let half = spec(VectorType::Float16, Metric::L2);
+    assert_eq!(
+        half.validate(&[0, 0x3C, 0, 0x7C, 0, 0]),
+        Err(BlobError::NonFinite { index: 1 })
+    );
+    let brain = spec(VectorType::BFloat16, Metric::L2);
+    assert_eq!(
+        brain.validate(&[0xC0, 0x7F, 0x80, 0x3F, 0, 0]),
+        Err(BlobError::NonFinite { index: 0 })
+    );
+    // Integers are always finite.
+    assert_eq!(
+        spec(VectorType::Int8, Metric::L2).validate(&[0xFF, 0x7F, 0x80]),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_zero_vector() {
+    let cosine = spec(VectorType::Float32, Metric::Cosine);
+    assert_eq!(
+        cosine.validate(&f32_blob([0.0, -0.0, 0.0])),
+        Err(BlobError::ZeroVector)
+    );
+    assert_eq!(cosine.validate(&f32_blob([0.0, 1e-30, 0.0])), Ok(()));
+    assert_eq!(
+        spec(VectorType::Float32, Metric::Ip).validate(&f32_blob([0.0; 3])),
+        Ok(())
+    );
+    assert_eq!(
+        spec(VectorType::UInt8, Metric::Cosine).validate(&[0, 0, 0]),
+        Err(BlobError::ZeroVector)
+    );
+}
+
+#[test]
+fn test_query_param() {
+    let spec = spec(VectorType::Float32, Metric::L2);
+    let err = spec.validate_query_param("vec", &[0; 4]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Error parsing vector similarity query: query vector blob size (4) does not match index's expected size (12)."
+    );
+    let err = spec
+        .validate_query_param("vec", &f32_blob([f32::NAN, 0.0, 0.0]))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        QueryBlobError {
+            param: "vec".to_owned(),
+            error: BlobError::NonFinite { index: 0 },
+        }
+    );
+    assert!(err.to_string().contains("parameter `vec`"));
+}
+
+#[test]
+fn test_index_error() {
+    let spec = spec(VectorType::Float32, Metric::L2);
+    let mut errors = IndexError::default();
+    assert!(errors.check(&spec, "doc:1", &f32_blob([1.0, 2.0, 3.0])));
+    assert!(!errors.check(&spec, "doc:2", &[1, 2]));
+    assert!(!errors.check(&spec, "doc:3", &f32_blob([f32::INFINITY, 2.0, 3.0])));
+    assert_eq!(errors.error_count, 2);
+    assert_eq!(errors.key.as_deref(), Some("doc:3"));
+    assert_eq!(
+        errors.last_error_with_user_data.as_deref(),
+        Some("Could not add vector with a NaN or infinite component at index 0")
+    );
+    assert_eq!(
+        errors.last_error_without_user_data,
+        Some("Could not add vector with a NaN or infinite component")
+    );
+}
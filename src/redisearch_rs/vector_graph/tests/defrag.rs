@@ -0,0 +1,36 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+
+use defrag::{DefragStatus, testing::MovingDefragger};
+use vector_graph::{GraphKind, VectorGraph};
+
+#[test]
+fn test_defrag() {
+    let mut graph = VectorGraph::new(GraphKind::Hnsw);
+    for node in 0..10 {
+        graph.add_node(vec![vec![(node + 1) % 10, (node + 2) % 10], vec![0]]);
+    }
+    let expected = graph.clone();
+
+    let mut defragger = MovingDefragger::new(4);
+    let mut cursor = 0;
+    let mut passes = 0;
+    loop {
+        passes += 1;
+        if graph.defrag(&mut cursor, &mut defragger) == DefragStatus::Done {
+            break;
+        }
+    }
+    assert!(passes > 1);
+    assert_eq!(cursor, 0);
+    // The node array, and the level array and both levels of each node.
+    assert_eq!(defragger.moves, 1 + 10 * 3);
+    assert_eq!(graph, expected);
+}
@@ -0,0 +1,55 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use query::node::VectorAggregation;
+use vector_graph::knn::{BEST_ELEMENT_FIELD, MultiVectorKnn};
+
+fn search(aggregation: Option<VectorAggregation>) -> Vec<(u64, f64, usize)> {
+    let mut knn = MultiVectorKnn::new(2, aggregation);
+    for (doc, element, distance) in [
+        (1, 0, 0.5),
+        (1, 1, 0.1),
+        (1, 2, 0.9),
+        (2, 0, 0.3),
+        (2, 1, 0.4),
+        (3, 0, 0.2),
+    ] {
+        knn.push(doc, element, distance);
+    }
+    knn.finish()
+        .into_iter()
+        .map(|r| (r.doc, r.distance, r.best_element))
+        .collect()
+}
+
+#[test]
+fn test_aggregations() {
+    assert_eq!(search(None), [(1, 0.1, 1), (3, 0.2, 0)]);
+    assert_eq!(search(Some(VectorAggregation::Min)), search(None));
+    assert_eq!(
+        search(Some(VectorAggregation::Max)),
+        [(3, 0.2, 0), (2, 0.4, 0)]
+    );
+    assert_eq!(
+        search(Some(VectorAggregation::Avg)),
+        [(3, 0.2, 0), (2, 0.35, 0)]
+    );
+}
+
+#[test]
+fn test_ties_and_pseudo_field() {
+    let mut knn = MultiVectorKnn::new(10, None);
+    knn.push(7, 3, 1.0);
+    knn.push(7, 1, 1.0);
+    knn.push(4, 0, 1.0);
+    let results = knn.finish();
+    assert_eq!(results.iter().map(|r| r.doc).collect::<Vec<_>>(), [4, 7]);
+    assert_eq!(results[1].pseudo_field(BEST_ELEMENT_FIELD), Some(1));
+    assert_eq!(results[1].pseudo_field("__geo_distance"), None);
+}
@@ -0,0 +1,183 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use rdb_format::{FormatError, FormatVersion, MemoryRdb, Persist, RdbRead, RdbWrite, load, save};
+use vector_graph::{
+    CHUNK_NODES, CorruptGraphPolicy, GRAPH_FORMAT_VERSION, GraphKind, VectorGraph, load_graph,
+};
+
+/// A ring of `len` nodes, every tenth node also being on level 1.
+fn hnsw(len: u32) -> VectorGraph {
+    let mut graph = VectorGraph::new(GraphKind::Hnsw);
+    for node in 0..len {
+        let mut levels = vec![vec![(node + 1) % len, (node + len - 1) % len]];
+        if node % 10 == 0 {
+            levels.push(vec![(node + 10) % len]);
+        }
+        graph.add_node(levels);
+    }
+    graph.set_entry_point(Some(0));
+    graph
+}
+
+/// Save `graph` with the graph encoding, which [`save`] doesn't negotiate yet.
+fn save_graph(graph: &VectorGraph, rdb: &mut MemoryRdb) {
+    graph.save(rdb, FormatVersion::VECSIM_GRAPH);
+}
+
+/// A saved one-node graph whose chunk is `chunk`, with the given checksum.
+fn saved_chunk(chunk: &[u8], checksum: u64) -> MemoryRdb {
+    let mut rdb = MemoryRdb::new();
+    for value in [GRAPH_FORMAT_VERSION, 0, 1, 0] {
+        rdb.save_unsigned(value);
+    }
+    rdb.save_string_buffer(chunk);
+    rdb.save_unsigned(checksum);
+    // The data saved after the graph.
+    rdb.save_unsigned(42);
+    rdb
+}
+
+#[test]
+fn test_roundtrip() {
+    for graph in [hnsw(CHUNK_NODES as u32 * 2 + 7), hnsw(1)] {
+        let mut rdb = MemoryRdb::new();
+        save_graph(&graph, &mut rdb);
+        // The header, then a buffer and a checksum per chunk.
+        assert_eq!(rdb.remaining(), 4 + 2 * graph.len().div_ceil(CHUNK_NODES));
+        assert_eq!(
+            VectorGraph::load(&mut rdb, FormatVersion::VECSIM_GRAPH),
+            Ok(graph)
+        );
+    }
+
+    // Tombstones and compacted nodes stay deleted.
+    let mut graph = hnsw(20);
+    graph.delete(3);
+    graph.repair();
+    graph.delete(4);
+    let mut rdb = MemoryRdb::new();
+    save_graph(&graph, &mut rdb);
+    assert_eq!(
+        VectorGraph::load(&mut rdb, FormatVersion::VECSIM_GRAPH),
+        Ok(graph)
+    );
+
+    let mut vamana = VectorGraph::new(GraphKind::Vamana);
+    vamana.add_node(vec![vec![1]]);
+    vamana.add_node(vec![vec![0]]);
+    let mut rdb = MemoryRdb::new();
+    save_graph(&vamana, &mut rdb);
+    let loaded = VectorGraph::load(&mut rdb, FormatVersion::VECSIM_GRAPH).unwrap();
+    assert_eq!(loaded.entry_point(), None);
+    assert_eq!(loaded.neighbors(1, 0), [0]);
+    assert_eq!(loaded.neighbors(1, 1), [] as [u32; 0]);
+}
+
+#[test]
+fn test_older_formats_dont_persist_graphs() {
+    let mut rdb = MemoryRdb::new();
+    // The current version predates the graph section, which isn't written
+    // until the C save path writes it.
+    assert_eq!(
+        save(&hnsw(3), &mut rdb, None),
+        Err(FormatError::Unsupported {
+            feature: "Persisted vector graphs",
+            since: FormatVersion::VECSIM_GRAPH,
+            target: FormatVersion::CURRENT,
+        })
+    );
+    assert_eq!(rdb.remaining(), 0);
+    assert!(matches!(
+        save(&hnsw(3), &mut rdb, Some(FormatVersion::VECSIM_SVS_VAMANA)),
+        Err(FormatError::Unsupported { since, .. }) if since == FormatVersion::VECSIM_GRAPH
+    ));
+}
+
+#[test]
+fn test_corrupt_graph_is_rebuilt() {
+    let chunk = [0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0];
+    let mut rdb = saved_chunk(&chunk, 0xBAD);
+    let loaded = load_graph(
+        &mut rdb,
+        FormatVersion::CURRENT,
+        CorruptGraphPolicy::Rebuild,
+        || hnsw(5),
+    )
+    .unwrap();
+    assert_eq!(
+        loaded.corruption,
+        Some(FormatError::Corrupt("vector graph chunk checksum mismatch"))
+    );
+    assert!(!loaded.graph.is_built());
+    assert_eq!(loaded.graph.get().len(), 5);
+    // The whole graph was skipped.
+    assert_eq!(rdb.load_unsigned(), Ok(42));
+
+    let mut rdb = saved_chunk(&chunk, 0xBAD);
+    assert!(
+        load_graph(
+            &mut rdb,
+            FormatVersion::CURRENT,
+            CorruptGraphPolicy::Fail,
+            || hnsw(5),
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn test_checked_chunks() {
+    let load_chunk = |chunk: &[u8]| {
+        let checksum = u64::from(crc32fast::hash(chunk));
+        load::<VectorGraph>(&mut saved_chunk(chunk, checksum), FormatVersion::CURRENT)
+    };
+    // A live node with one level and a self-loop.
+    assert!(load_chunk(&[0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]).is_ok());
+    assert_eq!(
+        load_chunk(&[0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 9, 0, 0, 0]),
+        Err(FormatError::Corrupt("vector graph neighbor out of range"))
+    );
+    assert_eq!(
+        load_chunk(&[0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0]),
+        Err(FormatError::Corrupt("truncated vector graph chunk"))
+    );
+    assert_eq!(
+        load_chunk(&[0, 0, 0, 0, 0, 0, 0, 0, 0]),
+        Err(FormatError::Corrupt("trailing data in vector graph chunk"))
+    );
+    assert_eq!(
+        load_chunk(&[7, 0, 0, 0, 0, 0, 0, 0]),
+        Err(FormatError::Corrupt("unknown vector graph node state"))
+    );
+}
+
+#[test]
+fn test_truncated_rdb_fails() {
+    let mut rdb = MemoryRdb::new();
+    rdb.save_unsigned(GRAPH_FORMAT_VERSION);
+    let loaded = load_graph(
+        &mut rdb,
+        FormatVersion::CURRENT,
+        CorruptGraphPolicy::Rebuild,
+        || hnsw(5),
+    );
+    assert!(matches!(
+        loaded,
+        Err(FormatError::Corrupt("unexpected end of data"))
+    ));
+}
+
+#[test]
+fn test_policy_config() {
+    assert_eq!("REBUILD".parse(), Ok(CorruptGraphPolicy::Rebuild));
+    assert_eq!("fail".parse(), Ok(CorruptGraphPolicy::Fail));
+    assert_eq!("skip".parse::<CorruptGraphPolicy>(), Err(()));
+    assert_eq!(CorruptGraphPolicy::default(), CorruptGraphPolicy::Rebuild);
+}
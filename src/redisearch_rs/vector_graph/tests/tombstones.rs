@@ -0,0 +1,126 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use compaction::{Scheduler, SchedulerConfig, TaskKind, TaskOutcome};
+use vector_graph::{GraphKind, NodeState, TombstoneConfig, VectorGraph};
+
+/// A ring of `len` nodes, each linked to the two nodes before and after it.
+fn ring(len: u32) -> VectorGraph {
+    let mut graph = VectorGraph::new(GraphKind::Vamana);
+    for node in 0..len {
+        graph.add_node(vec![
+            [1, 2, len - 1, len - 2].map(|d| (node + d) % len).to_vec(),
+        ]);
+    }
+    graph.set_entry_point(Some(0));
+    graph
+}
+
+/// Whether a live node links to a deleted one.
+fn links_to_deleted(graph: &VectorGraph) -> bool {
+    (0..graph.len() as u32)
+        .filter(|&n| graph.state(n) == NodeState::Live)
+        .any(|n| {
+            graph
+                .neighbors(n, 0)
+                .iter()
+                .any(|&m| graph.state(m) != NodeState::Live)
+        })
+}
+
+#[test]
+fn test_delete() {
+    let mut graph = ring(10);
+    assert!(graph.delete(0));
+    assert!(!graph.delete(0));
+    assert_eq!(graph.state(0), NodeState::Tombstone);
+    assert_eq!(graph.tombstones(), 1);
+    assert_eq!(graph.deleted_ratio(), 0.1);
+    // A new entry point is picked.
+    assert_eq!(graph.entry_point(), Some(1));
+}
+
+#[test]
+fn test_repair() {
+    let mut graph = ring(10);
+    for node in [3, 4, 7] {
+        graph.delete(node);
+    }
+    let progress = graph.repair();
+    assert_eq!(progress.scanned, 7);
+    assert_eq!(progress.compacted, 3);
+    assert!(progress.done);
+    assert!(!links_to_deleted(&graph));
+    assert_eq!(graph.tombstones(), 0);
+    assert_eq!(graph.state(4), NodeState::Compacted);
+    assert!(graph.neighbors(4, 0).is_empty());
+    // Node 5 lost 7, 4 and 3, and is linked to their live neighbors instead,
+    // keeping 4 neighbors.
+    assert_eq!(graph.neighbors(5, 0), [6, 8, 9, 2]);
+}
+
+#[test]
+fn test_incremental_repair() {
+    let mut graph = ring(100);
+    for node in (0..100).step_by(3) {
+        graph.delete(node);
+    }
+    let mut steps = 0;
+    loop {
+        let progress = graph.repair_step(10);
+        steps += 1;
+        if progress.done {
+            assert_eq!(progress.compacted, 34);
+            break;
+        }
+        assert!(progress.scanned <= 10);
+        // Deleting during the pass defers the tombstone to the next pass.
+        if steps == 2 {
+            graph.delete(1);
+        }
+    }
+    assert_eq!(steps, 7);
+    assert_eq!(graph.tombstones(), 1);
+    graph.repair();
+    assert_eq!(graph.tombstones(), 0);
+    assert!(!links_to_deleted(&graph));
+}
+
+#[test]
+fn test_compaction_trigger() {
+    let config = TombstoneConfig {
+        deleted_ratio: 0.25,
+        batch: 8,
+    };
+    let mut graph = ring(8);
+    graph.delete(0);
+    assert_eq!(graph.compaction_candidate("idx", "vec", &config), None);
+    graph.delete(5);
+
+    let candidate = graph.compaction_candidate("idx", "vec", &config).unwrap();
+    assert_eq!(candidate.kind, TaskKind::RewriteVectorRegion);
+    assert_eq!(candidate.target, "vec");
+    assert_eq!((candidate.cost, candidate.benefit), (8 * 4 * 4, 2 * 4 * 4));
+
+    let mut scheduler = Scheduler::new(SchedulerConfig {
+        cycle_budget: 1 << 20,
+        min_benefit: 0,
+    });
+    assert!(scheduler.submit(candidate));
+    let mut cycles = 0;
+    while graph.tombstones() > 0 {
+        scheduler.run_cycle(|_| {
+            graph.repair_step(config.batch);
+            TaskOutcome::default()
+        });
+        cycles += 1;
+    }
+    assert_eq!(cycles, 1);
+    assert_eq!(graph.compaction_candidate("idx", "vec", &config), None);
+}
@@ -0,0 +1,341 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+//! A string which borrows or owns its bytes, wherever they were allocated.
+//!
+//! Field values on the query and reply path come from `RedisModuleString`s,
+//! `sds` strings and Rust buffers. Converting all of them to a single owned
+//! Rust type copies every value at least once per result field, and often
+//! again when the reply hands it back to C. A [`ZStr`] wraps any of them
+//! without copying:
+//!
+//! - [`ZStr::borrowed`] and [`ZStr::from_raw_parts`] borrow bytes owned by
+//!   someone else, e.g. the contents of a `RedisModuleString` kept alive by
+//!   the key being loaded;
+//! - [`ZStr::owned`] owns a Rust buffer;
+//! - [`ZStr::from_foreign`] takes ownership of a C allocation, e.g. an `sds`,
+//!   and frees it with the destructor it was given.
+//!
+//! Ownership crosses the FFI boundary explicitly, through [`RawZStr`]:
+//! [`ZStr::into_raw`] hands an owned string over to C together with the
+//! function that frees it, and [`ZStr::from_raw`] takes it back.
+
+use std::{
+    borrow::Cow,
+    ffi::c_void,
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    ops::Deref,
+    str::Utf8Error,
+};
+
+/// Frees a string handed over with [`RawZStr`].
+///
+/// It receives the `handle` and the bytes of the string: the destructor of an
+/// `sds` only needs the handle, the one of a Rust buffer only the bytes.
+pub type ReleaseFn = unsafe extern "C" fn(handle: *mut c_void, ptr: *const u8, len: usize);
+
+enum Repr<'a> {
+    Borrowed(&'a [u8]),
+    Owned(Box<[u8]>),
+    Foreign {
+        ptr: *const u8,
+        len: usize,
+        handle: *mut c_void,
+        release: ReleaseFn,
+    },
+}
+
+/// A borrowed or owned string of bytes. See the [crate documentation](crate).
+pub struct ZStr<'a>(Repr<'a>);
+
+/// A [`ZStr`] handed over to C.
+///
+/// Whoever holds it must eventually call `release(handle, ptr, len)` if
+/// `release` isn't NULL, or turn it back into a [`ZStr`] with
+/// [`ZStr::from_raw`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct RawZStr {
+    pub ptr: *const u8,
+    pub len: usize,
+    pub handle: *mut c_void,
+    /// NULL if the bytes don't need freeing, e.g. for a static string.
+    pub release: Option<ReleaseFn>,
+}
+
+impl<'a> ZStr<'a> {
+    /// Borrow `bytes`.
+    pub const fn borrowed(bytes: &'a [u8]) -> Self {
+        Self(Repr::Borrowed(bytes))
+    }
+
+    /// Borrow the `len` bytes at `ptr`, e.g. the contents of a
+    /// `RedisModuleString`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be non-null and valid for reads of `len` bytes, which must
+    /// not be modified or freed for the lifetime `'a`.
+    pub const unsafe fn from_raw_parts(ptr: *const u8, len: usize) -> Self {
+        // SAFETY: upheld by the caller.
+        Self::borrowed(unsafe { std::slice::from_raw_parts(ptr, len) })
+    }
+
+    /// The bytes of the string.
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.0 {
+            Repr::Borrowed(bytes) => bytes,
+            Repr::Owned(bytes) => bytes,
+            Repr::Foreign { ptr, len, .. } => {
+                // SAFETY: `from_foreign` requires `ptr` to be valid for reads
+                // of `len` bytes until the string is released.
+                unsafe { std::slice::from_raw_parts(*ptr, *len) }
+            }
+        }
+    }
+
+    /// The string, if it is valid UTF-8.
+    pub fn to_str(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self.as_bytes())
+    }
+
+    /// The string, with invalid UTF-8 sequences replaced.
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.as_bytes())
+    }
+
+    /// Whether the string borrows its bytes.
+    pub const fn is_borrowed(&self) -> bool {
+        matches!(self.0, Repr::Borrowed(_))
+    }
+
+    /// A string owning its bytes. Only a borrowed string is copied.
+    pub fn into_owned(self) -> ZStr<'static> {
+        match self.into_static() {
+            Ok(owned) => owned,
+            Err(bytes) => ZStr::owned(bytes),
+        }
+    }
+
+    /// Move the representation out, leaving an empty borrowed string which
+    /// has nothing to release.
+    const fn take(&mut self) -> Repr<'a> {
+        std::mem::replace(&mut self.0, Repr::Borrowed(&[]))
+    }
+
+    /// The string, if it owns its bytes, or the bytes it borrows.
+    fn into_static(mut self) -> Result<ZStr<'static>, &'a [u8]> {
+        match self.take() {
+            Repr::Borrowed(bytes) => Err(bytes),
+            Repr::Owned(bytes) => Ok(ZStr(Repr::Owned(bytes))),
+            Repr::Foreign {
+                ptr,
+                len,
+                handle,
+                release,
+            } => Ok(ZStr(Repr::Foreign {
+                ptr,
+                len,
+                handle,
+                release,
+            })),
+        }
+    }
+}
+
+impl ZStr<'static> {
+    /// Own the Rust buffer `bytes`.
+    pub fn owned(bytes: impl Into<Box<[u8]>>) -> Self {
+        Self(Repr::Owned(bytes.into()))
+    }
+
+    /// Take ownership of the `len` bytes at `ptr`, which are freed by calling
+    /// `release(handle, ptr, len)` when the string is dropped.
+    ///
+    /// For an `sds`, `handle` and `ptr` are both the `sds`, and `release`
+    /// calls `sdsfree`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be non-null and valid for reads of `len` bytes, which must
+    /// not be modified until `release` is called. The caller gives up its
+    /// ownership: nothing else may free the string.
+    pub const unsafe fn from_foreign(
+        ptr: *const u8,
+        len: usize,
+        handle: *mut c_void,
+        release: ReleaseFn,
+    ) -> Self {
+        Self(Repr::Foreign {
+            ptr,
+            len,
+            handle,
+            release,
+        })
+    }
+
+    /// Hand the string over to C, without copying it.
+    pub fn into_raw(mut self) -> RawZStr {
+        match self.take() {
+            Repr::Borrowed(bytes) => RawZStr {
+                ptr: bytes.as_ptr(),
+                len: bytes.len(),
+                handle: std::ptr::null_mut(),
+                release: None,
+            },
+            Repr::Owned(bytes) => {
+                let len = bytes.len();
+                RawZStr {
+                    ptr: Box::into_raw(bytes).cast::<u8>(),
+                    len,
+                    handle: std::ptr::null_mut(),
+                    release: Some(release_owned),
+                }
+            }
+            Repr::Foreign {
+                ptr,
+                len,
+                handle,
+                release,
+            } => RawZStr {
+                ptr,
+                len,
+                handle,
+                release: Some(release),
+            },
+        }
+    }
+
+    /// Take back a string handed over with [`ZStr::into_raw`], or received
+    /// from C.
+    ///
+    /// # Safety
+    ///
+    /// `raw` must be valid as described by [`RawZStr`], and the caller gives
+    /// up its ownership. If `release` is NULL, the bytes must live forever.
+    pub unsafe fn from_raw(raw: RawZStr) -> Self {
+        match raw.release {
+            // SAFETY: upheld by the caller.
+            Some(release) => unsafe { Self::from_foreign(raw.ptr, raw.len, raw.handle, release) },
+            // SAFETY: upheld by the caller.
+            None => unsafe { Self::from_raw_parts(raw.ptr, raw.len) },
+        }
+    }
+}
+
+/// The [`ReleaseFn`] of the Rust buffers handed over by [`ZStr::into_raw`].
+unsafe extern "C" fn release_owned(_handle: *mut c_void, ptr: *const u8, len: usize) {
+    let bytes = std::ptr::slice_from_raw_parts_mut(ptr.cast_mut(), len);
+    // SAFETY: `into_raw` leaked a `Box<[u8]>` of `len` bytes at `ptr`, which
+    // is released only once.
+    drop(unsafe { Box::from_raw(bytes) });
+}
+
+impl Drop for ZStr<'_> {
+    fn drop(&mut self) {
+        if let Repr::Foreign {
+            ptr,
+            len,
+            handle,
+            release,
+        } = self.0
+        {
+            // SAFETY: the string owns the bytes, as required by
+            // `from_foreign`, and is dropped only once.
+            unsafe { release(handle, ptr, len) };
+        }
+    }
+}
+
+impl Clone for ZStr<'_> {
+    /// Borrowed strings are cloned without copying their bytes.
+    fn clone(&self) -> Self {
+        match &self.0 {
+            Repr::Borrowed(bytes) => Self::borrowed(bytes),
+            _ => ZStr::owned(self.as_bytes()),
+        }
+    }
+}
+
+impl Deref for ZStr<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AsRef<[u8]> for ZStr<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl PartialEq for ZStr<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for ZStr<'_> {}
+
+impl PartialOrd for ZStr<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ZStr<'_> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+impl Hash for ZStr<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
+impl Debug for ZStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.to_string_lossy(), f)
+    }
+}
+
+impl Display for ZStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.to_string_lossy(), f)
+    }
+}
+
+impl<'a> From<&'a str> for ZStr<'a> {
+    fn from(s: &'a str) -> Self {
+        Self::borrowed(s.as_bytes())
+    }
+}
+
+impl<'a> From<&'a [u8]> for ZStr<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        Self::borrowed(bytes)
+    }
+}
+
+impl From<String> for ZStr<'static> {
+    fn from(s: String) -> Self {
+        Self::owned(s.into_bytes())
+    }
+}
+
+impl From<Vec<u8>> for ZStr<'static> {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::owned(bytes)
+    }
+}
@@ -0,0 +1,108 @@
+/*
+ * Copyright (c) 2006-Present, Redis Ltd.
+ * All rights reserved.
+ *
+ * Licensed under your choice of the Redis Source Available License 2.0
+ * (RSALv2); or (b) the Server Side Public License v1 (SSPLv1); or (c) the
+ * GNU Affero General Public License v3 (AGPLv3).
+*/
+
+use std::{
+    collections::HashSet,
+    ffi::c_void,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use zstr::{RawZStr, ZStr};
+
+/// A stand-in for a C allocator: the handle is a leaked `Vec<u8>`, released
+/// by `free_foreign`, which counts the calls.
+static RELEASED: AtomicUsize = AtomicUsize::new(0);
+
+fn foreign(bytes: &[u8]) -> ZStr<'static> {
+    let handle = Box::into_raw(Box::new(bytes.to_vec()));
+    // SAFETY: the vector stays alive until `free_foreign` is called with it.
+    let ptr = unsafe { (*handle).as_ptr() };
+    // SAFETY: `ptr` points to the `bytes.len()` bytes of the vector, owned by
+    // the string from now on.
+    unsafe { ZStr::from_foreign(ptr, bytes.len(), handle.cast(), free_foreign) }
+}
+
+unsafe extern "C" fn free_foreign(handle: *mut c_void, _ptr: *const u8, _len: usize) {
+    RELEASED.fetch_add(1, Ordering::SeqCst);
+    // SAFETY: `handle` was leaked by `foreign`, and is released only once.
+    drop(unsafe { Box::from_raw(handle.cast::<Vec<u8>>()) });
+}
+
+#[test]
+fn test_kinds() {
+    let text = String::from("hello");
+    let borrowed = ZStr::from(text.as_str());
+    assert!(borrowed.is_borrowed());
+    assert_eq!(borrowed.as_bytes().as_ptr(), text.as_ptr(), "not copied");
+
+    let owned = ZStr::from(String::from("hello"));
+    assert!(!owned.is_borrowed());
+    assert_eq!(owned, borrowed);
+    assert_eq!(owned.to_str(), Ok("hello"));
+
+    // SAFETY: `text` outlives the string.
+    let raw_parts = unsafe { ZStr::from_raw_parts(text.as_ptr(), 4) };
+    assert_eq!(&*raw_parts, b"hell");
+    assert!(raw_parts < borrowed);
+
+    let invalid = ZStr::borrowed(b"a\xffb");
+    assert!(invalid.to_str().is_err());
+    assert_eq!(invalid.to_string(), "a\u{fffd}b");
+    assert_eq!(format!("{invalid:?}"), "\"a\u{fffd}b\"");
+
+    let set: HashSet<ZStr> = [borrowed, owned].into_iter().collect();
+    assert_eq!(set.len(), 1);
+}
+
+#[test]
+fn test_ownership() {
+    let before = RELEASED.load(Ordering::SeqCst);
+
+    let s = foreign(b"sds");
+    assert_eq!(&*s, b"sds");
+    // Owning strings are copied by `clone`, borrowed ones aren't.
+    let copy = s.clone();
+    drop(s);
+    assert_eq!(RELEASED.load(Ordering::SeqCst), before + 1);
+    assert_eq!(&*copy, b"sds");
+
+    // A foreign string goes back to C with its own destructor.
+    let raw = foreign(b"reply").into_raw();
+    assert_eq!(raw.len, 5);
+    // SAFETY: `raw` was just handed over, and is taken back once.
+    let back = unsafe { ZStr::from_raw(raw) };
+    assert_eq!(&*back, b"reply");
+    drop(back);
+    assert_eq!(RELEASED.load(Ordering::SeqCst), before + 2);
+
+    // `into_owned` only copies borrowed strings.
+    let text = String::from("field");
+    let owned = ZStr::from(text.as_str()).into_owned();
+    drop(text);
+    assert_eq!(&*owned, b"field");
+    let moved = foreign(b"value").into_owned();
+    assert_eq!(RELEASED.load(Ordering::SeqCst), before + 2);
+    drop(moved);
+    assert_eq!(RELEASED.load(Ordering::SeqCst), before + 3);
+}
+
+#[test]
+fn test_rust_buffer_to_c() {
+    let raw: RawZStr = ZStr::from(vec![1, 2, 3]).into_raw();
+    let release = raw.release.expect("Rust buffers must be freed");
+    // SAFETY: this is what C does with the string it was handed.
+    unsafe { release(raw.handle, raw.ptr, raw.len) };
+
+    let raw = ZStr::borrowed(b"static").into_raw();
+    assert!(raw.release.is_none());
+    // SAFETY: the bytes are static.
+    let back = unsafe { ZStr::from_raw(raw) };
+    assert!(back.is_borrowed());
+    assert_eq!(&*back, b"static");
+}
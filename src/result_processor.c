@@ -45,6 +45,7 @@ typedef struct {
   ResultProcessor base;
   QueryIterator *iterator;
   size_t timeoutLimiter;    // counter to limit number of calls to TimedOut_WithCounter()
+  size_t killLimiter;       // counter to limit number of calls to AdmissionTicket_IsKilled()
   RedisSearchCtx *sctx;
   const SharedSlotRangeArray *slotRanges; // Owned slot ranges info, may be used for filtering
 } RPQueryIterator;
@@ -116,6 +117,12 @@ static int rpQueryItNext(ResultProcessor *base, SearchResult *res) {
     if (TimedOut_WithCounter(&sctx->time.timeout, &self->timeoutLimiter) == TIMED_OUT) {
       return UnlockSpec_and_ReturnRPResult(sctx, RS_RESULT_TIMEDOUT);
     }
+    // A killed query stops at the pace timeouts are checked
+    const AdmissionTicket *ticket = base->parent->ticket;
+    if (ticket && self->killLimiter++ % TIMEOUT_COUNTER_LIMIT == 0 && AdmissionTicket_IsKilled(ticket)) {
+      QueryError_SetError(base->parent->err, QUERY_ERROR_CODE_GENERIC, "The query was killed");
+      return UnlockSpec_and_ReturnRPResult(sctx, RS_RESULT_ERROR);
+    }
     IteratorStatus rc = it->Read(it);
     switch (rc) {
     case ITERATOR_EOF:
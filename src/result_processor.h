@@ -19,6 +19,7 @@
 #include "rlookup.h"
 #include "extension.h"
 #include "score_explain.h"
+#include "admission_rs.h"
 #include "rs_wall_clock.h"
 #include "util/references.h"
 #include "hybrid/hybrid_scoring.h"
@@ -82,7 +83,8 @@ struct ResultProcessor;
 struct RLookup;
 
 // Define our own structures to avoid conflicts with the iterator_api.h QueryIterator
-/// <div rustbindgen hide></div>
+// Mirrored by hand in the `ffi` Rust crate: update `QueryProcessingCtx` there
+// when changing the fields.
 typedef struct QueryProcessingCtx {
   // First processor
   struct ResultProcessor *rootProc;
@@ -116,6 +118,9 @@ typedef struct QueryProcessingCtx {
 
   bool isProfile;
   RSTimeoutPolicy timeoutPolicy;
+
+  // The admission ticket of the query, not owned. The query stops once it's killed with FT._QUERIES KILL
+  const AdmissionTicket *ticket;
 } QueryProcessingCtx;
 
 QueryIterator *QITR_GetRootFilter(QueryProcessingCtx *it);
@@ -46,6 +46,7 @@
 #include "rs_wall_clock.h"
 #include "util/redis_mem_info.h"
 #include "search_disk.h"
+#include "index_events_rs.h"
 
 #define INITIAL_DOC_TABLE_SIZE 1000
 
@@ -420,6 +421,31 @@ void Indexes_SetTempSpecsTimers(TimerOp op) {
   dictReleaseIterator(iter);
 }
 
+// How often the main thread delivers the published index events.
+#define INDEX_EVENTS_DISPATCH_PERIOD_MS 100
+
+static RedisModuleTimerID indexEventsTimer;
+static bool indexEventsTimerSet = false;
+
+static void Indexes_DispatchEvents(RedisModuleCtx *ctx, void *data) {
+  IndexEvents_DispatchPending();
+  indexEventsTimer = RedisModule_CreateTimer(ctx, INDEX_EVENTS_DISPATCH_PERIOD_MS, Indexes_DispatchEvents, NULL);
+}
+
+void Indexes_StartEventsDispatch() {
+  indexEventsTimer = RedisModule_CreateTimer(RSDummyContext, INDEX_EVENTS_DISPATCH_PERIOD_MS, Indexes_DispatchEvents, NULL);
+  indexEventsTimerSet = true;
+}
+
+void Indexes_StopEventsDispatch() {
+  if (indexEventsTimerSet) {
+    RedisModule_StopTimer(RSDummyContext, indexEventsTimer, NULL);
+    indexEventsTimerSet = false;
+  }
+  // Deliver what was published until now
+  IndexEvents_DispatchPending();
+}
+
 //---------------------------------------------------------------------------------------------
 
 double IndexesScanner_IndexedPercent(RedisModuleCtx *ctx, IndexesScanner *scanner, const IndexSpec *sp) {
@@ -551,6 +577,8 @@ IndexSpec *IndexSpec_CreateNew(RedisModuleCtx *ctx, RedisModuleString **argv, in
 
   Cursors_initSpec(sp);
 
+  IndexEvents_IndexCreated(rawName, nameLen);
+
   // set timeout for temporary index on master
   if ((sp->flags & Index_Temporary) && IsMaster()) {
     IndexSpec_SetTimeoutTimer(sp, StrongRef_Demote(spec_ref));
@@ -1821,7 +1849,8 @@ static void IndexSpec_FreeUnlinkedData(IndexSpec *spec) {
   }
 
   // Destroy the spec's lock
-  pthread_rwlock_destroy(&spec->rwlock);
+  IndexLock_Free(spec->rwlock);
+  if (spec->slowlog) SlowLog_Free(spec->slowlog);
 
   if (spec->diskSpec) SearchDisk_CloseIndex(spec->diskSpec);
 
@@ -1888,6 +1917,11 @@ void IndexSpec_RemoveFromGlobals(StrongRef spec_ref, bool removeActive) {
   // Remove spec from global index list
   dictDelete(specDict_g, (void*)spec->specName);
 
+  size_t nameLen;
+  const char *name = HiddenString_GetUnsafe(spec->specName, &nameLen);
+  IndexEvents_IndexDropped(name, nameLen);
+  Metrics_RemoveIndex(name, nameLen);
+
   if (!spec->isDuplicate) {
     // Remove spec from global aliases list
     IndexSpec_ClearAliases(spec_ref);
@@ -2066,17 +2100,14 @@ void IndexSpec_InitializeSynonym(IndexSpec *sp) {
 ///////////////////////////////////////////////////////////////////////////////////////////////
 
 static void IndexSpec_InitLock(IndexSpec *sp) {
-  int res = 0;
-  pthread_rwlockattr_t attr;
-  res = pthread_rwlockattr_init(&attr);
-  RS_ASSERT(res == 0);
-#if !defined(__APPLE__) && !defined(__FreeBSD__) && defined(__GLIBC__)
-  int pref = PTHREAD_RWLOCK_PREFER_WRITER_NONRECURSIVE_NP;
-  res = pthread_rwlockattr_setkind_np(&attr, pref);
-  RS_ASSERT(res == 0);
-#endif
+  // Phase-fair: a waiting writer holds off new readers, without starving them
+  sp->rwlock = IndexLock_New();
+}
 
-  pthread_rwlock_init(&sp->rwlock, &attr);
+static void IndexSpec_InitSlowLog(IndexSpec *sp) {
+  // The values of PARAMS are user data
+  sp->slowlog = SlowLog_New(RSGlobalConfig.slowlogThresholdMicros, SLOWLOG_MAX_LEN,
+                            RSGlobalConfig.hideUserDataFromLog);
 }
 
 // Helper function for initializing a field spec
@@ -2117,6 +2148,7 @@ static void initializeIndexSpec(IndexSpec *sp, const HiddenString *name, IndexFl
   sp->terms = NewTrie(NULL, Trie_Sort_Lex);
 
   IndexSpec_InitLock(sp);
+  IndexSpec_InitSlowLog(sp);
   // First, initialise fields IndexError for every field
   // In the RDB flow if some fields are not loaded correctly, we will free the spec and attempt to cleanup all the fields.
   for (t_fieldIndex i = 0; i < sp->numFields; i++) {
@@ -2499,6 +2531,7 @@ void IndexesScanner_Cancel(IndexesScanner *scanner) {
 void IndexesScanner_ResetProgression(IndexesScanner *scanner) {
   scanner-> scanFailedOnOOM = false;
   scanner-> scannedKeys = 0;
+  scanner-> indexedKeys = 0;
 }
 
 //---------------------------------------------------------------------------------------------
@@ -2553,8 +2586,9 @@ static void Indexes_ScanProc(RedisModuleCtx *ctx, RedisModuleString *keyname, Re
     if (sp) {
       // This check is performed without locking the spec, but it's ok since we locked the GIL
       // So the main thread is not running and the GC is not touching the relevant data
-      if (SchemaRule_ShouldIndex(sp, keyname, type)) {
-        IndexSpec_UpdateDoc(sp, ctx, keyname, type);
+      if (SchemaRule_ShouldIndex(sp, keyname, type) &&
+          IndexSpec_UpdateDoc(sp, ctx, keyname, type) == REDISMODULE_OK) {
+        ++scanner->indexedKeys;
       }
       IndexSpecRef_Release(curr_run_ref);
     } else {
@@ -2575,6 +2609,24 @@ if (scanner->isDebug) { \
 #define IF_DEBUG_PAUSE_CHECK_BEFORE_OOM_RETRY(scanner, ctx) IF_DEBUG_PAUSE_CHECK(scanner, ctx, pauseBeforeOOMRetry, DEBUG_INDEX_SCANNER_CODE_PAUSED_BEFORE_OOM_RETRY)
 #define IF_DEBUG_PAUSE_CHECK_ON_OOM(scanner, ctx) IF_DEBUG_PAUSE_CHECK(scanner, ctx, pauseOnOOM, DEBUG_INDEX_SCANNER_CODE_PAUSED_ON_OOM)
 
+// Hold the scan while the memory watcher reports that background work is paused.
+// Called with the GIL held; releases it while waiting so the server can free memory.
+static void scanWaitWhileLowOnMemory(RedisModuleCtx *ctx, IndexesScanner *scanner) {
+  if (!RedisMemory_PollWatcher(ctx)) {
+    return;
+  }
+  const char *name = scanner->global ? "indexes" : scanner->spec_name_for_logs;
+  RedisModule_Log(ctx, "notice", "Scanning %s in background: paused on memory usage (scanned=%ld)",
+                  name, scanner->scannedKeys);
+  do {
+    RedisModule_ThreadSafeContextUnlock(ctx);
+    MemoryWatcher_WaitWhilePaused(100);
+    RedisModule_ThreadSafeContextLock(ctx);
+  } while (!scanner->cancelled && RedisMemory_PollWatcher(ctx));
+  RedisModule_Log(ctx, "notice", "Scanning %s in background: resumed (scanned=%ld)",
+                  name, scanner->scannedKeys);
+}
+
 static void Indexes_ScanAndReindexTask(IndexesScanner *scanner) {
   RS_LOG_ASSERT(scanner, "invalid IndexesScanner");
 
@@ -2621,6 +2673,8 @@ static void Indexes_ScanAndReindexTask(IndexesScanner *scanner) {
     }
     RedisModule_ThreadSafeContextLock(ctx);
 
+    scanWaitWhileLowOnMemory(ctx, scanner);
+
     // Check if we need to handle OOM but must check if the scanner was cancelled for other reasons (i.e. FT. ALTER)
     if (scanner->scanFailedOnOOM && !scanner->cancelled) {
 
@@ -2668,6 +2722,16 @@ static void Indexes_ScanAndReindexTask(IndexesScanner *scanner) {
   } else {
     RedisModule_Log(ctx, "notice", "Scanning index %s in background: done (scanned=%ld)",
                     scanner->spec_name_for_logs, scanner->scannedKeys);
+    StrongRef spec_ref = IndexSpecRef_Promote(scanner->spec_ref);
+    IndexSpec *spec = StrongRef_Get(spec_ref);
+    if (spec) {
+      size_t nameLen;
+      const char *name = HiddenString_GetUnsafe(spec->specName, &nameLen);
+      IndexEventScanStats stats = {.docs_scanned = scanner->scannedKeys,
+                                   .docs_indexed = scanner->indexedKeys};
+      IndexEvents_ScanFinished(name, nameLen, stats);
+      IndexSpecRef_Release(spec_ref);
+    }
   }
 
 end:
@@ -3120,6 +3184,7 @@ void *IndexSpec_LegacyRdbLoad(RedisModuleIO *rdb, int encver) {
   RedisModuleCtx *ctx = RedisModule_GetContextFromIO(rdb);
   IndexSpec *sp = rm_calloc(1, sizeof(IndexSpec));
   IndexSpec_InitLock(sp);
+  IndexSpec_InitSlowLog(sp);
   StrongRef spec_ref = StrongRef_New(sp, (RefManager_Free)IndexSpec_Free);
   sp->own_ref = spec_ref;
 
@@ -3453,15 +3518,30 @@ int IndexSpec_UpdateDoc(IndexSpec *spec, RedisModuleCtx *ctx, RedisModuleString
   return REDISMODULE_OK;
 }
 
+void IndexSpec_OnDocIndexed(IndexSpec *spec, const char *key, size_t len) {
+  size_t nameLen;
+  const char *name = HiddenString_GetUnsafe(spec->specName, &nameLen);
+  IndexEvents_DocIndexed(name, nameLen, key, len);
+}
+
+void IndexSpec_OnDocDeleted(IndexSpec *spec, const char *key, size_t len) {
+  // Increment the index's garbage collector's scanning frequency after document deletions
+  if (spec->gc) {
+    GCContext_OnDelete(spec->gc);
+  }
+  size_t nameLen;
+  const char *name = HiddenString_GetUnsafe(spec->specName, &nameLen);
+  IndexEvents_DocDeleted(name, nameLen, key, len);
+}
+
 void IndexSpec_DeleteDoc_Unsafe(IndexSpec *spec, RedisModuleCtx *ctx, RedisModuleString *key, t_docId id) {
 
   if (DocTable_DeleteR(&spec->docs, key)) {
     spec->stats.numDocuments--;
 
-    // Increment the index's garbage collector's scanning frequency after document deletions
-    if (spec->gc) {
-      GCContext_OnDelete(spec->gc);
-    }
+    size_t len;
+    const char *rawKey = RedisModule_StringPtrLen(key, &len);
+    IndexSpec_OnDocDeleted(spec, rawKey, len);
   }
 
   // VecSim fields clear deleted data on the fly
@@ -23,6 +23,9 @@
 #include "field_spec.h"
 #include "util/dict.h"
 #include "util/references.h"
+#include "index_lock_rs.h"
+#include "slowlog_rs.h"
+#include "metrics_rs.h"
 #include "redisearch_api.h"
 #include "rules.h"
 #include <pthread.h>
@@ -341,7 +344,10 @@ typedef struct IndexSpec {
   long long counter;
 
   // read write lock
-  pthread_rwlock_t rwlock;
+  IndexLock *rwlock;
+
+  // Queries slower than the slowlog threshold, for FT._SLOWLOG
+  SlowLog *slowlog;
 
   // Cursors counters
   size_t activeCursors;
@@ -544,6 +550,11 @@ int IndexSpec_DeleteDoc(IndexSpec *spec, RedisModuleCtx *ctx, RedisModuleString
 // This function does not lock the spec. use it if you know the spec is locked for writing
 void IndexSpec_DeleteDoc_Unsafe(IndexSpec *spec, RedisModuleCtx *ctx, RedisModuleString *key, t_docId id);
 
+// Notify the index events subscribers (and, on deletion, the GC) that the document `key` was
+// indexed or deleted.
+void IndexSpec_OnDocIndexed(IndexSpec *spec, const char *key, size_t len);
+void IndexSpec_OnDocDeleted(IndexSpec *spec, const char *key, size_t len);
+
 /**
  * Indicate that the index spec should use an internal dictionary,rather than
  * the Redis keyspace
@@ -653,6 +664,10 @@ void IndexSpec_ClearAliases(StrongRef ref);
 void IndexSpec_InitializeSynonym(IndexSpec *sp);
 void Indexes_SetTempSpecsTimers(TimerOp op);
 
+// Start (stop) delivering the index lifecycle events to their subscribers from the main thread.
+void Indexes_StartEventsDispatch();
+void Indexes_StopEventsDispatch();
+
 //---------------------------------------------------------------------------------------------
 
 typedef struct IndexesScanner {
@@ -663,6 +678,7 @@ typedef struct IndexesScanner {
   WeakRef spec_ref;
   char *spec_name_for_logs;
   size_t scannedKeys;
+  size_t indexedKeys;
   RedisModuleString *OOMkey; // The key that caused the OOM
 } IndexesScanner;
 
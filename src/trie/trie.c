@@ -8,7 +8,7 @@
 */
 #include <sys/param.h>
 #include "trie.h"
-#include "util/bsearch.h"
+#include "bsearch_rs.h"
 #include "sparse_vector.h"
 #include "redisearch.h"
 #include "rmutil/rm_assert.h"
@@ -9,23 +9,36 @@
 
 #include "redis_mem_info.h"
 #include "minmax.h"
+#include "config.h"
 
 #define MIN_NOT_0(a,b) (((a)&&(b))?MIN((a),(b)):MAX((a),(b)))
 
-// Get the used memory ratio from Redis server info.
-// Same function as before
+// Get the used memory and the memory limit from Redis server info.
 // GIL must be held before calling this function
-// Returns 0 if maxmemory is 0
-float RedisMemory_GetUsedMemoryRatioUnified(RedisModuleCtx *ctx) {
-
+static void getUsedAndMaxMemory(RedisModuleCtx *ctx, size_t *used, size_t *maxmemory) {
   RedisModuleServerInfoData *info = RedisModule_GetServerInfo(ctx, "memory");
 
-  size_t maxmemory = RedisModule_ServerInfoGetFieldUnsigned(info, "maxmemory", NULL);
+  *maxmemory = RedisModule_ServerInfoGetFieldUnsigned(info, "maxmemory", NULL);
   size_t max_process_mem = RedisModule_ServerInfoGetFieldUnsigned(info, "max_process_mem", NULL); // Enterprise limit
-  maxmemory = MIN_NOT_0(maxmemory, max_process_mem);
+  *maxmemory = MIN_NOT_0(*maxmemory, max_process_mem);
 
-  float used_memory = (float)RedisModule_ServerInfoGetFieldUnsigned(info, "used_memory", NULL);
+  *used = RedisModule_ServerInfoGetFieldUnsigned(info, "used_memory", NULL);
 
   RedisModule_FreeServerInfo(ctx, info);
-  return maxmemory ? used_memory / (float)maxmemory : 0;
+}
+
+// Get the used memory ratio from Redis server info.
+// Same function as before
+// GIL must be held before calling this function
+// Returns 0 if maxmemory is 0
+float RedisMemory_GetUsedMemoryRatioUnified(RedisModuleCtx *ctx) {
+  size_t used_memory, maxmemory;
+  getUsedAndMaxMemory(ctx, &used_memory, &maxmemory);
+  return maxmemory ? (float)used_memory / (float)maxmemory : 0;
+}
+
+bool RedisMemory_PollWatcher(RedisModuleCtx *ctx) {
+  size_t used_memory, maxmemory;
+  getUsedAndMaxMemory(ctx, &used_memory, &maxmemory);
+  return MemoryWatcher_Poll(RSGlobalConfig.bgWorkPauseMemoryLimit, used_memory, maxmemory);
 }
@@ -11,6 +11,7 @@
 
 #include "redismodule.h"
 #include <stdbool.h>
+#include "memory_watcher_rs.h"
 
 /** Unified Memory Consumption Checker
  *
@@ -46,3 +47,10 @@ static inline float RedisMemory_GetUsedMemoryRatio(void) {
 // Returns 0 if maxmemory is 0
 // TODO: remove this function and use RedisMemory_GetUsedMemoryRatio instead after benchmarking
 float RedisMemory_GetUsedMemoryRatioUnified(RedisModuleCtx *ctx);
+
+// Feed the memory watcher with a reading from Redis server info, and return whether background
+// work (the scan of the indexes and the application of the GC results) is paused.
+// Work pauses above _BG_WORK_PAUSE_MEM_PCT percent of the memory limit, and resumes
+// MEMORY_WATCHER_HYSTERESIS_PERCENT below it.
+// GIL must be held before calling this function
+bool RedisMemory_PollWatcher(RedisModuleCtx *ctx);
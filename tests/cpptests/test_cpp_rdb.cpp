@@ -104,13 +104,13 @@ TEST_F(RdbMockTest, testCreateIndexSpec) {
     // Verify the rwlock is properly initialized
     // We can't directly test the lock state, but we can verify it's initialized
     // by trying to acquire and release it
-    int lock_result = pthread_rwlock_tryrdlock(&spec->rwlock);
-    if (lock_result == 0) {
-        pthread_rwlock_unlock(&spec->rwlock);
+    bool locked = IndexLock_TryReadLock(spec->rwlock);
+    if (locked) {
+        IndexLock_Unlock(spec->rwlock);
     }
-    // If tryrdlock failed, it means the lock is either already locked or there's an error
-    // For a newly created spec, it should be unlocked, so we expect success (0)
-    EXPECT_EQ(0, lock_result);
+    // If the read lock couldn't be taken, the lock is held by a writer
+    // For a newly created spec, it should be unlocked, so we expect success
+    EXPECT_TRUE(locked);
 
     // Clean up
     IndexSpec_RemoveFromGlobals(spec_ref, false);
@@ -118,12 +118,11 @@ TEST_F(RdbMockTest, testCreateIndexSpec) {
 
 // Helper function to test lock state
 bool testLockState(IndexSpec *spec) {
-    int lock_result = pthread_rwlock_tryrdlock(&spec->rwlock);
-    if (lock_result == 0) {
-        pthread_rwlock_unlock(&spec->rwlock);
+    if (IndexLock_TryReadLock(spec->rwlock)) {
+        IndexLock_Unlock(spec->rwlock);
         return true;  // Lock is properly initialized and unlocked
     }
-    return false;  // Lock failed - either not initialized or locked
+    return false;  // Lock failed - held by a writer
 }
 
 // Second function - IndexSpec RDB serialization test
@@ -183,27 +182,27 @@ TEST_F(RdbMockTest, testIndexSpecRdbSerialization) {
     EXPECT_EQ(spec->counter, loadedSpec->counter);
     EXPECT_EQ(spec->activeCursors, loadedSpec->activeCursors);
     // verify read locks can be taken
-    int lockResult = pthread_rwlock_tryrdlock(&spec->rwlock);
-    EXPECT_EQ(0, lockResult);
-    if (lockResult == 0) {
-        pthread_rwlock_unlock(&spec->rwlock);
+    bool lockResult = IndexLock_TryReadLock(spec->rwlock);
+    EXPECT_TRUE(lockResult);
+    if (lockResult) {
+        IndexLock_Unlock(spec->rwlock);
     }
-    lockResult = pthread_rwlock_tryrdlock(&loadedSpec->rwlock);
-    EXPECT_EQ(0, lockResult);
-    if (lockResult == 0) {
-        pthread_rwlock_unlock(&loadedSpec->rwlock);
+    lockResult = IndexLock_TryReadLock(loadedSpec->rwlock);
+    EXPECT_TRUE(lockResult);
+    if (lockResult) {
+        IndexLock_Unlock(loadedSpec->rwlock);
     }
 
     // verify write locks can be taken
-    lockResult = pthread_rwlock_trywrlock(&spec->rwlock);
-    EXPECT_EQ(0, lockResult);
-    if (lockResult == 0) {
-        pthread_rwlock_unlock(&spec->rwlock);
+    lockResult = IndexLock_TryWriteLock(spec->rwlock);
+    EXPECT_TRUE(lockResult);
+    if (lockResult) {
+        IndexLock_Unlock(spec->rwlock);
     }
-    lockResult = pthread_rwlock_trywrlock(&loadedSpec->rwlock);
-    EXPECT_EQ(0, lockResult);
-    if (lockResult == 0) {
-        pthread_rwlock_unlock(&loadedSpec->rwlock);
+    lockResult = IndexLock_TryWriteLock(loadedSpec->rwlock);
+    EXPECT_TRUE(lockResult);
+    if (lockResult) {
+        IndexLock_Unlock(loadedSpec->rwlock);
     }
 
     // Verify field specifications are preserved
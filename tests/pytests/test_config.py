@@ -91,9 +91,11 @@ def testGetConfigOptions(env):
     check_config('INDEX_CURSOR_LIMIT')
     check_config('ENABLE_UNSTABLE_FEATURES')
     check_config('_BG_INDEX_MEM_PCT_THR')
+    check_config('_BG_WORK_PAUSE_MEM_PCT')
     check_config('BM25STD_TANH_FACTOR')
     check_config('_BG_INDEX_OOM_PAUSE_TIME')
     check_config('INDEXER_YIELD_EVERY_OPS')
+    check_config('_SLOWLOG_THRESHOLD_MICROS')
     check_config('ON_OOM')
 
 @skip(cluster=True)
@@ -122,6 +124,7 @@ def testSetConfigOptions(env):
     env.expect(config_cmd(), 'set', 'INDEX_CURSOR_LIMIT', 1).equal('OK')
     env.expect(config_cmd(), 'set', 'ENABLE_UNSTABLE_FEATURES', 'true').equal('OK')
     env.expect(config_cmd(), 'set', '_BG_INDEX_MEM_PCT_THR', 1).equal('OK')
+    env.expect(config_cmd(), 'set', '_BG_WORK_PAUSE_MEM_PCT', 1).equal('OK')
     env.expect(config_cmd(), 'set', 'BM25STD_TANH_FACTOR', 1).equal('OK')
     env.expect(config_cmd(), 'set', '_BG_INDEX_OOM_PAUSE_TIME', 1).equal('OK')
     env.expect(config_cmd(), 'set', 'INDEXER_YIELD_EVERY_OPS', 1).equal('OK')
@@ -139,6 +142,7 @@ def testSetConfigOptionsErrors(env):
     env.expect(config_cmd(), 'set', 'INDEX_CURSOR_LIMIT', -1).contains('Value is outside acceptable bounds')
     env.expect(config_cmd(), 'set', '_BG_INDEX_MEM_PCT_THR', -1).contains('Value is outside acceptable bounds')
     env.expect(config_cmd(), 'set', '_BG_INDEX_MEM_PCT_THR', 101).contains('Memory limit for indexing cannot be greater then 100%')
+    env.expect(config_cmd(), 'set', '_BG_WORK_PAUSE_MEM_PCT', 101).contains('Memory limit for background work cannot be greater then 100%')
     env.expect(config_cmd(), 'set', 'BM25STD_TANH_FACTOR', -1).contains('Value is outside acceptable bounds')
     env.expect(config_cmd(), 'set', 'BM25STD_TANH_FACTOR', 10001).contains('BM25STD_TANH_FACTOR must be between 1 and 10000')
     env.expect(config_cmd(), 'set', '_BG_INDEX_OOM_PAUSE_TIME', -1).contains('Value is outside acceptable bounds')
@@ -191,9 +195,11 @@ def testAllConfig(env):
     env.assertEqual(res_dict['INDEX_CURSOR_LIMIT'][0], '128')
     env.assertEqual(res_dict['ENABLE_UNSTABLE_FEATURES'][0], 'false')
     env.assertEqual(res_dict['_BG_INDEX_MEM_PCT_THR'][0], '100')
+    env.assertEqual(res_dict['_BG_WORK_PAUSE_MEM_PCT'][0], '0')
     env.assertEqual(res_dict['BM25STD_TANH_FACTOR'][0], '4')
     env.assertEqual(res_dict['_BG_INDEX_OOM_PAUSE_TIME'][0], '0')
     env.assertEqual(res_dict['INDEXER_YIELD_EVERY_OPS'][0], '1000')
+    env.assertEqual(res_dict['_SLOWLOG_THRESHOLD_MICROS'][0], '10000')
     env.assertEqual(res_dict['ON_OOM'][0], 'ignore')
 
 @skip(cluster=True)
@@ -221,6 +227,7 @@ def testInitConfig():
     _test_config_num('MINSTEMLEN', 3)
     _test_config_num('INDEX_CURSOR_LIMIT', 128)
     _test_config_num('_BG_INDEX_MEM_PCT_THR', 100)
+    _test_config_num('_BG_WORK_PAUSE_MEM_PCT', 0)
     _test_config_num('BM25STD_TANH_FACTOR', 4)
     _test_config_num('_BG_INDEX_OOM_PAUSE_TIME', 0)
 
@@ -500,9 +507,11 @@ numericConfigs = [
     ('search-workers', 'WORKERS', 0, 0, 16, False, False),
     ('search-workers-priority-bias-threshold', 'WORKERS_PRIORITY_BIAS_THRESHOLD', 1, 0, LLONG_MAX, True, False),
     ('search-_bg-index-mem-pct-thr', '_BG_INDEX_MEM_PCT_THR', 100, 0, 100, False, False),
+    ('search-_bg-work-pause-mem-pct', '_BG_WORK_PAUSE_MEM_PCT', 0, 0, 100, False, False),
     ('search-bm25std-tanh-factor', 'BM25STD_TANH_FACTOR', 4, 1, 10000, False, False),
     ('search-_bg-index-oom-pause-time','_BG_INDEX_OOM_PAUSE_TIME', 0, 0, UINT32_MAX, False, False),
     ('search-indexer-yield-every-ops', 'INDEXER_YIELD_EVERY_OPS', 1000, 1, UINT32_MAX, False, False),
+    ('search-_slowlog-threshold-micros', '_SLOWLOG_THRESHOLD_MICROS', 10000, 0, UINT32_MAX, False, False),
     # Cluster parameters
     ('search-threads', 'SEARCH_THREADS', 20, 1, LLONG_MAX, True, True),
     ('search-topology-validation-timeout', 'TOPOLOGY_VALIDATION_TIMEOUT', 30_000, 0, LLONG_MAX, False, True),
@@ -40,8 +40,13 @@ class TestDebugCommands(object):
             "INVIDX_SUMMARY",
             "NUMIDX_SUMMARY",
             "SPEC_INVIDXES_INFO",
+            "SPECREFS",
+            "LOCKSTATS",
+            "MEMUSAGE",
+            "SCRATCH_MEMORY",
             "GC_FORCEINVOKE",
             "GC_FORCEBGINVOKE",
+            "GC_STATS",
             "GC_CLEAN_NUMERIC",
             "GC_STOP_SCHEDULE",
             "GC_CONTINUE_SCHEDULE",
@@ -51,6 +56,7 @@ class TestDebugCommands(object):
             "TTL_PAUSE",
             "TTL_EXPIRE",
             "VECSIM_INFO",
+            "VECSIM_REPAIR",
             "DELETE_LOCAL_CURSORS",
             "DUMP_HNSW",
             "SET_MONITOR_EXPIRATION",
@@ -90,6 +96,57 @@ class TestDebugCommands(object):
         self.env.expect(debug_cmd(), 'docinfo', 'idx').error()
         self.env.expect(debug_cmd(), 'docinfo', 'idx', 'doc2').error()
 
+    def testSpecRefs(self):
+        res = to_dict(self.env.cmd(debug_cmd(), 'SPECREFS', 'idx'))
+        self.env.assertGreaterEqual(res['strong'], 1)
+        self.env.assertEqual(res['invalid'], 0)
+        # Only debug builds track where the live references were created
+        self.env.assertLessEqual(len(res['live']), res['strong'])
+        for site in res['live']:
+            self.env.assertContains('.c:', site)
+        self.env.expect(debug_cmd(), 'SPECREFS', 'idx1').error().contains('Unknown index name')
+
+    def testLockStats(self):
+        self.env.cmd('FT.SEARCH', 'idx', '*')
+        res = to_dict(self.env.cmd(debug_cmd(), 'LOCKSTATS', 'idx', 'RESET'))
+        self.env.assertGreaterEqual(res['read_acquisitions'], 1)
+        self.env.assertGreaterEqual(res['write_acquisitions'], 1)
+        self.env.assertLessEqual(res['read_contended'], res['read_acquisitions'])
+        self.env.assertEqual(len(res['read_waits']), 24)
+        self.env.assertEqual(sum(res['write_waits']), res['write_contended'])
+        res = to_dict(self.env.cmd(debug_cmd(), 'LOCKSTATS', 'idx'))
+        self.env.assertEqual(res['write_acquisitions'], 0)
+        self.env.expect(debug_cmd(), 'LOCKSTATS', 'idx', 'CLEAR').error().contains('expected RESET')
+        self.env.expect(debug_cmd(), 'LOCKSTATS', 'idx1').error().contains('Unknown index name')
+
+    def testMemUsage(self):
+        res = to_dict(self.env.cmd(debug_cmd(), 'MEMUSAGE', 'idx'))
+        structures = {name: to_dict(res[name]) for name in res if name != 'total_bytes'}
+        self.env.assertEqual(list(structures), ['term_trie', 'postings', 'offsets', 'numeric_trees',
+                                                'tag_tries', 'doc_table', 'sortables', 'vector_graphs',
+                                                'suffix_tries'])
+        self.env.assertEqual(res['total_bytes'], sum(s['bytes'] for s in structures.values()))
+        for name in ['term_trie', 'doc_table', 'sortables']:
+            self.env.assertGreater(structures[name]['bytes'], 0)
+        # The TEXT field is indexed with the default, full, encoding
+        postings = to_dict(structures['postings']['by_encoding'])
+        self.env.assertEqual(list(postings), ['full'])
+        self.env.assertEqual(postings['full'], structures['postings']['bytes'])
+        self.env.assertGreater(to_dict(structures['numeric_trees']['by_field'])['age'], 0)
+        self.env.assertGreater(to_dict(structures['tag_tries']['by_field'])['t'], 0)
+        self.env.assertContains('v', to_dict(structures['vector_graphs']['by_field']))
+        self.env.expect(debug_cmd(), 'MEMUSAGE', 'idx1').error().contains('Can not create a search ctx')
+        self.env.expect(debug_cmd(), 'MEMUSAGE', 'idx', 'extra').error().contains('wrong number of arguments')
+
+    def testScratchMemory(self):
+        self.env.cmd('FT.SEARCH', 'idx', 'meir', 'HIGHLIGHT')
+        # Only the threads which used their scratch buffers are listed
+        threads = [to_dict(thread) for thread in self.env.cmd(debug_cmd(), 'SCRATCH_MEMORY')]
+        for thread in threads:
+            self.env.assertEqual(sorted(thread), ['bytes', 'thread'])
+            self.env.assertGreaterEqual(thread['bytes'], 0)
+        self.env.expect(debug_cmd(), 'SCRATCH_MEMORY', 'idx').error().contains('wrong number of arguments')
+
     def testDumpInvertedIndex(self):
         self.env.expect(debug_cmd(), 'dump_invidx', 'idx', 'meir').equal([1])
         self.env.expect(debug_cmd(), 'DUMP_INVIDX', 'idx', 'meir').equal([1])
@@ -394,6 +451,28 @@ def testVecsimInfo_badParams(env: Env):
     env.expect(debug_cmd(), 'VECSIM_INFO', 'idx','v').error() \
         .contains("Can't open vector index")
 
+@skip(cluster=True)
+def testVecsimRepair():
+    env = Env(moduleArgs='WORKERS 1 FORK_GC_RUN_INTERVAL 50000')
+    env.expect('FT.CREATE', 'idx', 'ON', 'HASH', 'SCHEMA', 'vector', 'VECTOR', 'HNSW', 6, 'DIM', 6,
+               'TYPE', 'float32', 'DISTANCE_METRIC', 'L2').ok()
+    load_vectors_to_redis(env, 1000, 0, 6)
+    env.expect(debug_cmd(), 'WORKERS', 'DRAIN').ok() # wait for HNSW graph construction to finish
+    env.expect(debug_cmd(), 'VECSIM_REPAIR', 'idx').equal(0)
+
+    for i in range(500):
+        env.cmd('DEL', f'{i}')
+    env.expect(debug_cmd(), 'WORKERS', 'DRAIN').ok() # wait for the repair jobs of the deletions
+
+    # The repair runs on the workers, then the tombstones are gone
+    env.expect(debug_cmd(), 'VECSIM_REPAIR', 'idx').equal(500)
+    env.expect(debug_cmd(), 'WORKERS', 'DRAIN').ok()
+    info = index_info(env, 'idx')
+    env.assertEqual(to_dict(info['field statistics'][0])['marked_deleted'], 0)
+
+    env.expect(debug_cmd(), 'VECSIM_REPAIR', 'idx1').error().contains('Unknown index name')
+    env.expect(debug_cmd(), 'VECSIM_REPAIR').error().contains('wrong number of arguments')
+
 def testHNSWdump_badParams(env: Env):
     # Scenerio1: Vecsim Index scheme with vector type with invalid parameter
 
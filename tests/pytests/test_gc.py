@@ -585,3 +585,47 @@ def test_gc_oom(env:Env):
     bytes_collected = int(gc_dict['bytes_collected'])
     env.assertGreater(bytes_collected, 0)
 
+
+@skip(cluster=True)
+def test_gc_dry_run(env:Env):
+    env.expect(config_cmd(), 'SET', 'FORK_GC_CLEAN_THRESHOLD', '0').ok()
+    env.expect('FT.CREATE', 'idx', 'SCHEMA', 't', 'TEXT', 'n', 'NUMERIC').ok()
+    for i in range(10):
+        env.expect('HSET', f'doc{i}', 't', 'hello', 'n', i).equal(2)
+    for i in range(0, 10, 2):
+        env.expect('DEL', f'doc{i}').equal(1)
+
+    # The dry run reports what it would collect, and collects nothing
+    res = to_dict(env.cmd(debug_cmd(), 'GC_FORCEINVOKE', 'idx', 'DRYRUN'))
+    env.assertEqual(res['dry_run'], 1)
+    env.assertEqual(res['docs_collected'], 10) # 5 docs, in the text and numeric indexes
+    env.assertGreater(res['bytes_reclaimed'], 0)
+    env.assertEqual(env.cmd(debug_cmd(), 'DUMP_INVIDX', 'idx', 'hello'), list(range(1, 11)))
+    stats = to_dict(env.cmd(debug_cmd(), 'GC_STATS', 'idx'))
+    env.assertEqual(stats['total_cycles'], 0)
+
+    # The next run collects them
+    forceInvokeGC(env, 'idx')
+    env.assertEqual(env.cmd(debug_cmd(), 'DUMP_INVIDX', 'idx', 'hello'), list(range(2, 11, 2)))
+    stats = to_dict(env.cmd(debug_cmd(), 'GC_STATS', 'idx'))
+    env.assertEqual(stats['total_cycles'], 1)
+    env.assertEqual(stats['bytes_collected'], res['bytes_reclaimed'])
+
+    env.expect(debug_cmd(), 'GC_FORCEINVOKE', 'idx', 'NOW').error().contains('Invalid arguments')
+
+
+@skip(cluster=True)
+def test_gc_dry_run_below_threshold(env:Env):
+    # Fewer deleted documents than the threshold: a regular run skips them, a dry run doesn't
+    env.expect(config_cmd(), 'SET', 'FORK_GC_CLEAN_THRESHOLD', '100').ok()
+    env.expect('FT.CREATE', 'idx', 'SCHEMA', 't', 'TEXT').ok()
+    for i in range(4):
+        env.expect('HSET', f'doc{i}', 't', 'hello').equal(1)
+    env.expect('DEL', 'doc0').equal(1)
+
+    res = to_dict(env.cmd(debug_cmd(), 'GC_FORCEINVOKE', 'idx', 'DRYRUN'))
+    env.assertEqual(res['dry_run'], 1)
+    env.assertEqual(res['docs_collected'], 1)
+    env.assertEqual(env.cmd(debug_cmd(), 'DUMP_INVIDX', 'idx', 'hello'), [1, 2, 3, 4])
+    forceInvokeGC(env, 'idx')
+    env.assertEqual(env.cmd(debug_cmd(), 'DUMP_INVIDX', 'idx', 'hello'), [1, 2, 3, 4])
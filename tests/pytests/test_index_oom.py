@@ -963,3 +963,29 @@ def test_unlimited_memory_thrs(env):
   # Verify that all docs were indexed
   docs_in_index = get_index_num_docs(env)
   env.assertEqual(docs_in_index, 100)
+
+@skip(cluster=True)
+def test_pause_background_scan_on_memory_watcher(env):
+  num_docs = 10000
+  for i in range(num_docs):
+      env.expect('HSET', f'doc{i}', 'name', f'name{i}').equal(1)
+  # Used memory is about half of maxmemory, well above the 10% pause threshold
+  set_tight_maxmemory_for_oom(env, 0.5)
+  env.expect('FT.CONFIG', 'SET', '_BG_WORK_PAUSE_MEM_PCT', '10').ok()
+
+  env.expect('FT.CREATE', 'idx', 'SCHEMA', 'name', 'TEXT').ok()
+  with TimeLimit(60, 'Timeout while waiting for the scan to pause'):
+      while index_info(env)['bg_work_paused_on_memory'] != 1:
+          time.sleep(0.1)
+  info = index_info(env)
+  env.assertEqual(info['indexing'], 1)
+  env.assertLess(info['num_docs'], num_docs)
+
+  # Disabling the watcher resumes the scan
+  env.expect('FT.CONFIG', 'SET', '_BG_WORK_PAUSE_MEM_PCT', '0').ok()
+  waitForIndex(env, 'idx')
+  info = index_info(env)
+  env.assertEqual(info['bg_work_paused_on_memory'], 0)
+  env.assertGreaterEqual(info['bg_work_memory_pauses'], 1)
+  env.assertEqual(info['num_docs'], num_docs)
+  set_unlimited_maxmemory_for_oom(env)
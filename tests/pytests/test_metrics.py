@@ -0,0 +1,54 @@
+from common import *
+
+
+def _metrics(env):
+    text = env.cmd('FT._METRICS')
+    if isinstance(text, bytes):
+        text = text.decode()
+    return text.splitlines()
+
+
+def _samples(env, index):
+    label = f'index="{index}"'
+    return {line.rsplit(' ', 1)[0]: float(line.rsplit(' ', 1)[1])
+            for line in _metrics(env) if not line.startswith('#') and label in line}
+
+
+@skip(cluster=True)
+def test_metrics_exposition(env):
+    env.expect('FT.CREATE', 'idx', 'SCHEMA', 't', 'TEXT').ok()
+    for i in range(10):
+        env.cmd('HSET', f'doc{i}', 't', 'hello world')
+
+    env.cmd('FT.SEARCH', 'idx', 'hello')
+    env.cmd('FT.AGGREGATE', 'idx', 'world')
+
+    lines = _metrics(env)
+    for family in ['# TYPE redisearch_queries_total counter',
+                   '# TYPE redisearch_query_duration_seconds histogram',
+                   '# TYPE redisearch_index_docs gauge',
+                   '# TYPE redisearch_index_records gauge']:
+        env.assertContains(family, lines)
+
+    samples = _samples(env, 'idx')
+    env.assertEqual(samples['redisearch_queries_total{index="idx"}'], 2)
+    env.assertEqual(samples['redisearch_query_duration_seconds_count{index="idx"}'], 2)
+    env.assertEqual(samples['redisearch_query_duration_seconds_bucket{index="idx",le="+Inf"}'], 2)
+    env.assertEqual(samples['redisearch_index_docs{index="idx"}'], 10)
+    env.assertEqual(samples['redisearch_index_records{index="idx"}'], 20)
+
+    # A cursor query is counted once it's exhausted
+    _, cursor = env.cmd('FT.AGGREGATE', 'idx', 'hello', 'WITHCURSOR', 'COUNT', 1)
+    env.assertEqual(_samples(env, 'idx')['redisearch_queries_total{index="idx"}'], 2)
+    _, cursor = env.cmd('FT.CURSOR', 'READ', 'idx', cursor, 'COUNT', 100)
+    env.assertEqual(cursor, 0)
+    env.assertEqual(_samples(env, 'idx')['redisearch_queries_total{index="idx"}'], 3)
+
+    # The series of a dropped index are removed
+    env.expect('FT.DROPINDEX', 'idx').ok()
+    env.assertEqual(_samples(env, 'idx'), {})
+
+
+@skip(cluster=True)
+def test_metrics_arity(env):
+    env.expect('FT._METRICS', 'idx').error().contains('wrong number of arguments')
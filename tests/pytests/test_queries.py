@@ -0,0 +1,38 @@
+from common import *
+
+
+def _in_flight(env, index):
+    return [to_dict(query) for query in env.cmd('FT._QUERIES', 'LIST') if to_dict(query)['index'] == index]
+
+
+@skip(cluster=True)
+def test_queries_list_and_kill(env):
+    env.expect('FT.CREATE', 'idx', 'SCHEMA', 't', 'TEXT').ok()
+    for i in range(1000):
+        env.cmd('HSET', f'doc{i}', 't', 'hello')
+
+    # The queries are done once replied
+    env.cmd('FT.SEARCH', 'idx', 'hello')
+    env.assertEqual(_in_flight(env, 'idx'), [])
+
+    # A cursor holds its ticket until it's exhausted
+    _, cursor = env.cmd('FT.AGGREGATE', 'idx', 'hello', 'WITHCURSOR', 'COUNT', 10)
+    queries = _in_flight(env, 'idx')
+    env.assertEqual(len(queries), 1)
+    env.assertEqual(queries[0]['query'], 'hello')
+    env.assertGreaterEqual(queries[0]['elapsed_ms'], 0)
+
+    env.expect('FT._QUERIES', 'KILL', queries[0]['id']).ok()
+    # The query stops at its next check
+    env.expect('FT.CURSOR', 'READ', 'idx', cursor, 'COUNT', 500).error().contains('The query was killed')
+    env.assertEqual(_in_flight(env, 'idx'), [])
+    env.expect('FT._QUERIES', 'KILL', queries[0]['id']).error().contains('No such query')
+
+
+@skip(cluster=True)
+def test_queries_errors(env):
+    env.expect('FT._QUERIES').error().contains('expected LIST or KILL {id}')
+    env.expect('FT._QUERIES', 'LIST', 'idx').error().contains('expected LIST or KILL {id}')
+    env.expect('FT._QUERIES', 'KILL').error().contains('expected LIST or KILL {id}')
+    env.expect('FT._QUERIES', 'KILL', 'all').error().contains('expected LIST or KILL {id}')
+    env.expect('FT._QUERIES', 'KILL', 1_000_000).error().contains('No such query')
@@ -448,6 +448,9 @@ def test_info():
       'offset_vectors_sz_mb': ANY,
       'offsets_per_term_avg': ANY,
       'percent_indexed': 1.0,
+      'bg_work_paused_on_memory': 0,
+      'bg_work_memory_pauses': ANY,
+      'bg_work_memory_paused_ms': ANY,
       'records_per_doc_avg': ANY,
       'sortable_values_size_mb': 0.0,
       'geoshapes_sz_mb': 0.0,
@@ -1325,6 +1328,9 @@ def test_ft_info():
         'offset_vectors_sz_mb': 0.0,
         'offsets_per_term_avg': nan,
         'percent_indexed': 1.0,
+        'bg_work_paused_on_memory': 0,
+        'bg_work_memory_pauses': ANY,
+        'bg_work_memory_paused_ms': ANY,
         'records_per_doc_avg': nan,
         'sortable_values_size_mb': 0.0,
         'geoshapes_sz_mb': 0.0,
@@ -1405,6 +1411,9 @@ def test_ft_info():
         'offset_vectors_sz_mb': 0.0,
         'offsets_per_term_avg': nan,
         'percent_indexed': 1.0,
+        'bg_work_paused_on_memory': 0,
+        'bg_work_memory_pauses': ANY,
+        'bg_work_memory_paused_ms': ANY,
         'records_per_doc_avg': nan,
         'sortable_values_size_mb': 0.0,
         'geoshapes_sz_mb': 0.0,
@@ -0,0 +1,65 @@
+from common import *
+
+
+def _create_index(env, threshold_micros):
+    # The threshold applies to the indexes created afterwards
+    env.expect(config_cmd(), 'SET', '_SLOWLOG_THRESHOLD_MICROS', threshold_micros).ok()
+    env.expect('FT.CREATE', 'idx', 'SCHEMA', 't', 'TEXT', 'n', 'NUMERIC').ok()
+    for i in range(5):
+        env.expect('HSET', f'doc{i}', 't', 'hello', 'n', i).equal(2)
+
+
+@skip(cluster=True)
+def test_slowlog_get_len_reset(env):
+    _create_index(env, 0)
+    env.expect('FT._SLOWLOG', 'idx', 'LEN').equal(0)
+    env.expect('FT._SLOWLOG', 'idx', 'GET').equal([])
+
+    env.cmd('FT.SEARCH', 'idx', 'hello', 'LIMIT', 0, 2)
+    env.cmd('FT.SEARCH', 'idx', '@n:[$lo $hi]', 'PARAMS', 4, 'lo', 1, 'hi', 3, 'DIALECT', 2)
+    env.expect('FT._SLOWLOG', 'idx', 'LEN').equal(2)
+
+    entries = [to_dict(entry) for entry in env.cmd('FT._SLOWLOG', 'idx', 'GET')]
+    env.assertEqual(len(entries), 2)
+    # The most recent first
+    latest, first = entries
+    env.assertEqual(latest['id'], 1)
+    env.assertEqual(latest['query'], '@n:[$lo $hi]')
+    env.assertEqual(to_dict(latest['params']), {'lo': '1', 'hi': '3'})
+    env.assertEqual(latest['total_results'], 3)
+    env.assertEqual(first['id'], 0)
+    env.assertEqual(first['query'], 'hello')
+    env.assertEqual(to_dict(first['params']), {})
+    env.assertEqual(first['total_results'], 5)
+    env.assertEqual(first['returned_results'], 2)
+    for timing in ('parse_ms', 'execute_ms', 'load_ms', 'serialize_ms'):
+        env.assertGreaterEqual(float(first[timing]), 0)
+
+    res = env.cmd('FT._SLOWLOG', 'idx', 'GET', 1)
+    env.assertEqual(len(res), 1)
+    env.assertEqual(to_dict(res[0])['id'], 1)
+
+    env.expect('FT._SLOWLOG', 'idx', 'RESET').ok()
+    env.expect('FT._SLOWLOG', 'idx', 'LEN').equal(0)
+
+    # The ids keep increasing after a reset
+    env.cmd('FT.AGGREGATE', 'idx', 'hello')
+    env.assertEqual(to_dict(env.cmd('FT._SLOWLOG', 'idx', 'GET')[0])['id'], 2)
+
+
+@skip(cluster=True)
+def test_slowlog_threshold(env):
+    # No query takes an hour
+    _create_index(env, 3_600_000_000)
+    env.cmd('FT.SEARCH', 'idx', 'hello')
+    env.expect('FT._SLOWLOG', 'idx', 'LEN').equal(0)
+
+
+@skip(cluster=True)
+def test_slowlog_errors(env):
+    _create_index(env, 0)
+    env.expect('FT._SLOWLOG', 'idx').error().contains('wrong number of arguments')
+    env.expect('FT._SLOWLOG', 'missing', 'LEN').error().contains('no such index')
+    env.expect('FT._SLOWLOG', 'idx', 'FLUSH').error().contains('expected GET [count], LEN or RESET')
+    env.expect('FT._SLOWLOG', 'idx', 'GET', 'many').error().contains('expected GET [count], LEN or RESET')
+    env.expect('FT._SLOWLOG', 'idx', 'LEN', 1).error().contains('expected GET [count], LEN or RESET')